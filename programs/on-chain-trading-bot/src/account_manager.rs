@@ -1,54 +1,48 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
-use crate::dex::{serum::*, raydium::*, jupiter::*};
+use crate::events::{LotRealized, PositionReduced, PositionRejected};
+use crate::state::Strategy;
+use crate::{errors::TradingBotError, math};
+use crate::types::DexType;
 
 pub struct DexAccountManager;
 
 #[derive(Accounts)]
-pub struct InitializeDexAccounts<'info> {
-    // Serum accounts
-    #[account(mut)]
-    pub serum_market: AccountInfo<'info>,
-    #[account(mut)]
-    pub serum_open_orders: AccountInfo<'info>,
-    
-    // Raydium accounts
-    #[account(mut)]
-    pub raydium_pool: AccountInfo<'info>,
-    #[account(mut)]
-    pub raydium_position: AccountInfo<'info>,
-    
-    // Jupiter accounts
-    #[account(mut)]
-    pub jupiter_route: AccountInfo<'info>,
-    
-    // Common accounts
-    #[account(mut)]
-    pub user_token_a: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub user_token_b: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+pub struct InitUserPositions<'info> {
+    // allocated by the client at UserPositions::LEN bytes and owned by the program
+    #[account(zero)]
+    pub user_positions: AccountLoader<'info, UserPositions>,
     pub owner: Signer<'info>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
 pub struct ManagePositions<'info> {
-    #[account(mut)]
+    #[account(mut, has_one = owner)]
     pub strategy: Account<'info, Strategy>,
-    #[account(mut)]
-    pub user_positions: Account<'info, UserPositions>,
+    #[account(mut, has_one = owner)]
+    pub user_positions: AccountLoader<'info, UserPositions>,
     pub owner: Signer<'info>,
 }
 
-#[account]
+pub const MAX_DEX_POSITIONS: usize = 32;
+
+// Zero-copy: instructions read and write entries in place instead of deserializing every position
+// and lot. Each list is a fixed array filled up to its count. Too large to create through a CPI,
+// so the client allocates it and `init_user_positions` claims it.
+#[account(zero_copy)]
 pub struct UserPositions {
     pub owner: Pubkey,
-    pub serum_positions: Vec<SerumPosition>,
-    pub raydium_positions: Vec<RaydiumPosition>,
-    pub jupiter_positions: Vec<JupiterPosition>,
     pub last_update: i64,
+    pub serum_positions: [SerumPosition; MAX_DEX_POSITIONS],
+    pub raydium_positions: [RaydiumPosition; MAX_DEX_POSITIONS],
+    pub jupiter_positions: [JupiterPosition; MAX_DEX_POSITIONS],
+    pub lots: [TaxLot; MAX_TAX_LOTS],
+    pub serum_count: u16,
+    pub raydium_count: u16,
+    pub jupiter_count: u16,
+    pub lot_count: u16,
+    // LotMethod plus one, lot tracking is off while zero
+    pub lot_method: u8,
+    pub padding: [u8; 7],
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -57,13 +51,7 @@ pub struct PositionUpdate {
     pub action: PositionAction,
     pub amount: u64,
     pub market_id: Pubkey,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub enum DexType {
-    Serum,
-    Raydium,
-    Jupiter,
+    pub lot_trade: Option<LotTrade>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -74,30 +62,10 @@ pub enum PositionAction {
 }
 
 impl DexAccountManager {
-    // Initialize accounts for all DEXs
-    pub fn initialize_dex_accounts(
-        ctx: Context<InitializeDexAccounts>,
-    ) -> Result<()> {
-        // Initialize Serum accounts
-        Self::init_serum_accounts(
-            ctx.accounts.serum_market.clone(),
-            ctx.accounts.serum_open_orders.clone(),
-            ctx.accounts.owner.key(),
-        )?;
-
-        // Initialize Raydium accounts
-        Self::init_raydium_accounts(
-            ctx.accounts.raydium_pool.clone(),
-            ctx.accounts.raydium_position.clone(),
-            ctx.accounts.owner.key(),
-        )?;
-
-        // Initialize Jupiter accounts
-        Self::init_jupiter_accounts(
-            ctx.accounts.jupiter_route.clone(),
-            ctx.accounts.owner.key(),
-        )?;
-
+    pub fn init_user_positions(ctx: Context<InitUserPositions>) -> Result<()> {
+        let mut positions = ctx.accounts.user_positions.load_init()?;
+        positions.owner = ctx.accounts.owner.key();
+        positions.last_update = Clock::get()?.unix_timestamp;
         Ok(())
     }
 
@@ -106,65 +74,97 @@ impl DexAccountManager {
         ctx: Context<ManagePositions>,
         position_updates: Vec<PositionUpdate>,
     ) -> Result<()> {
-        let positions = &mut ctx.accounts.user_positions;
+        let max_open_positions = ctx.accounts.strategy.risk_parameters.max_open_positions;
+        let mut positions = ctx.accounts.user_positions.load_mut()?;
         
         for update in position_updates {
+            if matches!(update.action, PositionAction::Open) {
+                Self::check_open_position_cap(
+                    &positions,
+                    ctx.accounts.strategy.key(),
+                    max_open_positions,
+                    Some(update.dex),
+                    update.market_id,
+                )?;
+            }
             match update.dex {
                 DexType::Serum => {
-                    Self::update_serum_position(positions, update)?;
+                    Self::update_serum_position(&mut positions, update.clone())?;
                 },
                 DexType::Raydium => {
-                    Self::update_raydium_position(positions, update)?;
+                    Self::update_raydium_position(&mut positions, update.clone())?;
                 },
                 DexType::Jupiter => {
-                    Self::update_jupiter_position(positions, update)?;
+                    Self::update_jupiter_position(&mut positions, update.clone())?;
                 },
             }
+
+            if let Some(trade) = update.lot_trade {
+                positions.apply_lot_trade(trade, Clock::get()?.unix_timestamp)?;
+            }
         }
 
         positions.last_update = Clock::get()?.unix_timestamp;
         Ok(())
     }
 
-    // Helper functions for account initialization
-    fn init_serum_accounts(
-        market: AccountInfo,
-        open_orders: AccountInfo,
-        owner: Pubkey,
+    // Partial close: takes `pct_bps` of what is still open, 10000 closes the position. Unwinding
+    // the order, LP or route itself is left to the venue's own instructions.
+    pub fn reduce_position(
+        ctx: Context<ManagePositions>,
+        dex: DexType,
+        position_id: Pubkey,
+        pct_bps: u16,
     ) -> Result<()> {
-        // Initialize Serum market and open orders accounts
-        serum_dex::instruction::init_open_orders(
-            market,
-            open_orders,
-            owner,
-        )?;
+        let mut positions = ctx.accounts.user_positions.load_mut()?;
+        let reduced = positions.position_share(dex, position_id, pct_bps, false)?;
+        let remaining = positions.reduce_position(dex, position_id, reduced)?;
+        let now = Clock::get()?.unix_timestamp;
+        positions.last_update = now;
+
+        emit!(PositionReduced {
+            owner: positions.owner,
+            dex,
+            position_id,
+            reduced,
+            remaining,
+            timestamp: now,
+        });
         Ok(())
     }
 
-    fn init_raydium_accounts(
-        pool: AccountInfo,
-        position: AccountInfo,
-        owner: Pubkey,
+    // Turning tracking off keeps existing lots so it can be resumed later
+    pub fn configure_lot_tracking(
+        ctx: Context<ManagePositions>,
+        lot_method: Option<LotMethod>,
     ) -> Result<()> {
-        // Initialize Raydium pool and position accounts
-        raydium_amm::instruction::init_position(
-            pool,
-            position,
-            owner,
-        )?;
+        ctx.accounts.user_positions.load_mut()?.set_lot_method(lot_method);
         Ok(())
     }
 
-    fn init_jupiter_accounts(
-        route: AccountInfo,
-        owner: Pubkey,
+    // Orders, LP positions and routes all count against the strategy's cap, which limit orders and
+    // perp hedges opening a position are held to as well; `dex` is None for those. The event still
+    // shows up in the logs of the failed transaction.
+    pub(crate) fn check_open_position_cap(
+        positions: &UserPositions,
+        strategy: Pubkey,
+        max_open_positions: u32,
+        dex: Option<DexType>,
+        market_id: Pubkey,
     ) -> Result<()> {
-        // Initialize Jupiter route accounts
-        jupiter_core::instruction::init_route(
-            route,
-            owner,
-        )?;
-        Ok(())
+        let open_positions = positions.open_positions();
+        if max_open_positions == 0 || open_positions < max_open_positions as usize {
+            return Ok(());
+        }
+        emit!(PositionRejected {
+            owner: positions.owner,
+            strategy,
+            dex,
+            market_id,
+            open_positions: open_positions as u32,
+            max_open_positions,
+        });
+        Err(TradingBotError::MaxOpenPositionsExceeded.into())
     }
 
     // Helper functions for position management
@@ -174,21 +174,18 @@ impl DexAccountManager {
     ) -> Result<()> {
         match update.action {
             PositionAction::Open => {
-                positions.serum_positions.push(SerumPosition {
+                positions.push_serum_position(SerumPosition {
                     market_id: update.market_id,
                     size: update.amount,
+                    opened: update.amount,
                     timestamp: Clock::get()?.unix_timestamp,
-                });
+                })?;
             },
             PositionAction::Close => {
-                positions.serum_positions.retain(|p| p.market_id != update.market_id);
+                positions.remove_positions(DexType::Serum, update.market_id);
             },
             PositionAction::Modify => {
-                if let Some(position) = positions.serum_positions
-                    .iter_mut()
-                    .find(|p| p.market_id == update.market_id) {
-                    position.size = update.amount;
-                }
+                positions.resize_position(DexType::Serum, update.market_id, update.amount);
             },
         }
         Ok(())
@@ -198,7 +195,22 @@ impl DexAccountManager {
         positions: &mut UserPositions,
         update: PositionUpdate,
     ) -> Result<()> {
-        // Similar to update_serum_position but for Raydium
+        match update.action {
+            PositionAction::Open => {
+                positions.push_raydium_position(RaydiumPosition {
+                    pool_id: update.market_id,
+                    liquidity: update.amount,
+                    opened: update.amount,
+                    timestamp: Clock::get()?.unix_timestamp,
+                })?;
+            },
+            PositionAction::Close => {
+                positions.remove_positions(DexType::Raydium, update.market_id);
+            },
+            PositionAction::Modify => {
+                positions.resize_position(DexType::Raydium, update.market_id, update.amount);
+            },
+        }
         Ok(())
     }
 
@@ -206,29 +218,330 @@ impl DexAccountManager {
         positions: &mut UserPositions,
         update: PositionUpdate,
     ) -> Result<()> {
-        // Similar to update_serum_position but for Jupiter
+        match update.action {
+            PositionAction::Open => {
+                positions.push_jupiter_position(JupiterPosition {
+                    route_id: update.market_id,
+                    amount: update.amount,
+                    opened: update.amount,
+                    timestamp: Clock::get()?.unix_timestamp,
+                })?;
+            },
+            PositionAction::Close => {
+                positions.remove_positions(DexType::Jupiter, update.market_id);
+            },
+            PositionAction::Modify => {
+                positions.resize_position(DexType::Jupiter, update.market_id, update.amount);
+            },
+        }
         Ok(())
     }
 }
 
-// Position structs for each DEX
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+// Position structs for each DEX. `opened` is the size at open or the last resize; partial
+// closes only lower the live size, so scaled exits can take fixed shares of the opening size.
+#[zero_copy]
 pub struct SerumPosition {
     pub market_id: Pubkey,
     pub size: u64,
+    pub opened: u64,
     pub timestamp: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[zero_copy]
 pub struct RaydiumPosition {
     pub pool_id: Pubkey,
     pub liquidity: u64,
+    pub opened: u64,
     pub timestamp: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[zero_copy]
 pub struct JupiterPosition {
     pub route_id: Pubkey,
     pub amount: u64,
+    pub opened: u64,
     pub timestamp: i64,
-} 
\ No newline at end of file
+}
+
+pub const MAX_TAX_LOTS: usize = 256;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LotMethod {
+    Fifo,
+    Lifo,
+}
+
+// Cost is in quote units for the whole remaining amount of the lot
+#[zero_copy]
+pub struct TaxLot {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub cost: u64,
+    pub acquired_at: i64,
+}
+
+// `value` is the cost of a buy or the proceeds of a sell, in quote units
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LotTrade {
+    pub mint: Pubkey,
+    pub is_buy: bool,
+    pub amount: u64,
+    pub value: u64,
+}
+
+// Drops the entries `keep` rejects from the first `count`, keeping the rest in order
+fn retain_entries<T: Copy>(entries: &mut [T], count: &mut u16, keep: impl Fn(&T) -> bool) {
+    let mut kept = 0;
+    for i in 0..*count as usize {
+        if keep(&entries[i]) {
+            entries[kept] = entries[i];
+            kept += 1;
+        }
+    }
+    *count = kept as u16;
+}
+
+impl UserPositions {
+    pub const LEN: usize = 8 + std::mem::size_of::<UserPositions>();
+
+    pub fn serum_positions(&self) -> &[SerumPosition] {
+        &self.serum_positions[..self.serum_count as usize]
+    }
+
+    pub fn raydium_positions(&self) -> &[RaydiumPosition] {
+        &self.raydium_positions[..self.raydium_count as usize]
+    }
+
+    pub fn jupiter_positions(&self) -> &[JupiterPosition] {
+        &self.jupiter_positions[..self.jupiter_count as usize]
+    }
+
+    pub fn lots(&self) -> &[TaxLot] {
+        &self.lots[..self.lot_count as usize]
+    }
+
+    pub fn lot_method(&self) -> Option<LotMethod> {
+        match self.lot_method {
+            1 => Some(LotMethod::Fifo),
+            2 => Some(LotMethod::Lifo),
+            _ => None,
+        }
+    }
+
+    pub fn set_lot_method(&mut self, lot_method: Option<LotMethod>) {
+        self.lot_method = lot_method.map_or(0, |method| method as u8 + 1);
+    }
+
+    pub fn push_serum_position(&mut self, position: SerumPosition) -> Result<()> {
+        let count = self.serum_count as usize;
+        require!(
+            count < MAX_DEX_POSITIONS,
+            TradingBotError::MaxOpenPositionsExceeded
+        );
+        self.serum_positions[count] = position;
+        self.serum_count += 1;
+        Ok(())
+    }
+
+    pub fn push_raydium_position(&mut self, position: RaydiumPosition) -> Result<()> {
+        let count = self.raydium_count as usize;
+        require!(
+            count < MAX_DEX_POSITIONS,
+            TradingBotError::MaxOpenPositionsExceeded
+        );
+        self.raydium_positions[count] = position;
+        self.raydium_count += 1;
+        Ok(())
+    }
+
+    pub fn push_jupiter_position(&mut self, position: JupiterPosition) -> Result<()> {
+        let count = self.jupiter_count as usize;
+        require!(
+            count < MAX_DEX_POSITIONS,
+            TradingBotError::MaxOpenPositionsExceeded
+        );
+        self.jupiter_positions[count] = position;
+        self.jupiter_count += 1;
+        Ok(())
+    }
+
+    pub fn remove_positions(&mut self, dex: DexType, id: Pubkey) {
+        match dex {
+            DexType::Serum => {
+                retain_entries(&mut self.serum_positions, &mut self.serum_count, |p| {
+                    p.market_id != id
+                });
+            },
+            DexType::Raydium => {
+                retain_entries(&mut self.raydium_positions, &mut self.raydium_count, |p| {
+                    p.pool_id != id
+                });
+            },
+            DexType::Jupiter => {
+                retain_entries(&mut self.jupiter_positions, &mut self.jupiter_count, |p| {
+                    p.route_id != id
+                });
+            },
+        }
+    }
+
+    // The live size and opening size of the position on `dex` with `id`: order size, LP
+    // liquidity or routed amount
+    pub fn position_size_mut(
+        &mut self,
+        dex: DexType,
+        id: Pubkey,
+    ) -> Option<(&mut u64, &mut u64)> {
+        match dex {
+            DexType::Serum => self.serum_positions[..self.serum_count as usize]
+                .iter_mut()
+                .find(|p| p.market_id == id)
+                .map(|p| (&mut p.size, &mut p.opened)),
+            DexType::Raydium => self.raydium_positions[..self.raydium_count as usize]
+                .iter_mut()
+                .find(|p| p.pool_id == id)
+                .map(|p| (&mut p.liquidity, &mut p.opened)),
+            DexType::Jupiter => self.jupiter_positions[..self.jupiter_count as usize]
+                .iter_mut()
+                .find(|p| p.route_id == id)
+                .map(|p| (&mut p.amount, &mut p.opened)),
+        }
+    }
+
+    pub fn resize_position(&mut self, dex: DexType, id: Pubkey, size: u64) {
+        if let Some((live, opened)) = self.position_size_mut(dex, id) {
+            *live = size;
+            *opened = size;
+        }
+    }
+
+    // `pct_bps` of the position's opening size, or of what is still open, capped at the latter
+    pub fn position_share(
+        &mut self,
+        dex: DexType,
+        id: Pubkey,
+        pct_bps: u16,
+        of_opened: bool,
+    ) -> Result<u64> {
+        require!(
+            pct_bps > 0 && pct_bps <= 10000,
+            TradingBotError::InvalidPositionReduction
+        );
+        let (live, opened) = self
+            .position_size_mut(dex, id)
+            .ok_or(TradingBotError::PositionNotFound)?;
+        let basis = if of_opened { *opened } else { *live };
+        let share = math::checked_as_u64(math::checked_div(
+            math::checked_mul(basis as u128, pct_bps as u128)?,
+            10000,
+        )?)?;
+        Ok(share.min(*live))
+    }
+
+    // Closes `amount` of the position, dropping it once nothing is left. Returns what remains.
+    pub fn reduce_position(&mut self, dex: DexType, id: Pubkey, amount: u64) -> Result<u64> {
+        let (live, _) = self
+            .position_size_mut(dex, id)
+            .ok_or(TradingBotError::PositionNotFound)?;
+        *live = math::checked_sub(*live, amount)?;
+        let remaining = *live;
+        if remaining == 0 {
+            self.remove_positions(dex, id);
+        }
+        Ok(remaining)
+    }
+
+    pub fn open_positions(&self) -> usize {
+        (self.serum_count + self.raydium_count + self.jupiter_count) as usize
+    }
+
+    pub fn apply_lot_trade(&mut self, trade: LotTrade, now: i64) -> Result<()> {
+        let Some(method) = self.lot_method() else {
+            return Ok(());
+        };
+        if trade.is_buy {
+            let count = self.lot_count as usize;
+            require!(
+                count < MAX_TAX_LOTS,
+                TradingBotError::TooManyTaxLots
+            );
+            self.lots[count] = TaxLot {
+                mint: trade.mint,
+                amount: trade.amount,
+                cost: trade.value,
+                acquired_at: now,
+            };
+            self.lot_count += 1;
+            Ok(())
+        } else {
+            self.consume_lots(method, trade.mint, trade.amount, trade.value)
+                .map(|_| ())
+        }
+    }
+
+    // Sells draw down lots of the mint oldest-first (FIFO) or newest-first (LIFO). Each lot
+    // touched realizes its share of the proceeds against its share of cost; the last slice takes
+    // the rounding remainder so the realized proceeds add up exactly. Returns the total gain.
+    pub fn consume_lots(
+        &mut self,
+        method: LotMethod,
+        mint: Pubkey,
+        amount: u64,
+        proceeds: u64,
+    ) -> Result<i64> {
+        let mut indices: Vec<usize> = (0..self.lot_count as usize)
+            .filter(|i| self.lots[*i].mint == mint)
+            .collect();
+        if method == LotMethod::Lifo {
+            indices.reverse();
+        }
+        let held = indices
+            .iter()
+            .try_fold(0u64, |acc, i| math::checked_add(acc, self.lots[*i].amount))?;
+        require_gte!(held, amount, TradingBotError::InsufficientTaxLots);
+
+        let mut remaining = amount;
+        let mut proceeds_left = proceeds;
+        let mut total_gain: i64 = 0;
+        for i in indices {
+            if remaining == 0 {
+                break;
+            }
+            let lot = &mut self.lots[i];
+            let used = remaining.min(lot.amount);
+            let cost_basis = math::checked_as_u64(math::checked_div(
+                math::checked_mul(lot.cost as u128, used as u128)?,
+                lot.amount as u128,
+            )?)?;
+            remaining = math::checked_sub(remaining, used)?;
+            let lot_proceeds = if remaining == 0 {
+                proceeds_left
+            } else {
+                math::checked_as_u64(math::checked_div(
+                    math::checked_mul(proceeds as u128, used as u128)?,
+                    amount as u128,
+                )?)?
+            };
+            proceeds_left = math::checked_sub(proceeds_left, lot_proceeds)?;
+
+            let gain = math::checked_sub(lot_proceeds as i64, cost_basis as i64)?;
+            total_gain = math::checked_add(total_gain, gain)?;
+            emit!(LotRealized {
+                owner: self.owner,
+                mint,
+                acquired_at: lot.acquired_at,
+                amount: used,
+                cost_basis,
+                proceeds: lot_proceeds,
+                gain,
+            });
+
+            lot.amount = math::checked_sub(lot.amount, used)?;
+            lot.cost = math::checked_sub(lot.cost, cost_basis)?;
+        }
+
+        retain_entries(&mut self.lots, &mut self.lot_count, |lot| lot.amount > 0);
+        Ok(total_gain)
+    }
+}
@@ -1,7 +1,16 @@
 use anchor_lang::prelude::*;
-use crate::dex::{raydium::*, jupiter::*, serum::*};
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::dex::serum::{SerumDex, SERUM_DEX_V3_PROGRAM_ID};
+use crate::dex::{forward_call, swap_program_id, VenueCall, VenueCalls};
+use crate::constants::{EXECUTION_METRICS_SEED, POOL_REGISTRY_SEED};
 use crate::errors::TradingBotError;
-use crate::types::{TokenPair, PriceData};
+use crate::events::ArbitrageExecuted;
+use crate::introspection;
+use crate::math;
+use crate::state::{ExecutionMetrics, PoolRegistry, PriceBand};
+use crate::types::{DexType, TokenPair};
+use crate::utils::validate_deadline;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct ArbitrageRoute {
@@ -15,18 +24,19 @@ pub struct ArbitrageRoute {
     pub deadline: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
 pub enum RouteType {
     RaydiumJupiter,
     JupiterSerum,
     SerumRaydium,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum DexType {
-    Raydium,
-    Jupiter,
-    Serum,
+// What base costs to buy and fetches when sold on a venue, quote per base scaled by
+// PRICE_PRECISION. The AMM venues quote their reserve ratio both ways.
+#[derive(Clone, Copy)]
+struct VenueQuote {
+    bid: u64,
+    ask: u64,
 }
 
 pub struct ArbitrageManager;
@@ -38,98 +48,196 @@ impl ArbitrageManager {
         token_pair: TokenPair,
         min_profit: u64,
     ) -> Result<Vec<ArbitrageRoute>> {
-        // Get prices from all DEXs
-        let prices = Self::get_dex_prices(ctx.accounts)?;
+        // Only price the pair's registered pools
+        let accounts = &ctx.accounts;
+        let markets = [&accounts.raydium_market, &accounts.jupiter_market, &accounts.serum_market];
+        let registry = &accounts.pool_registry;
+        for dex in [DexType::Raydium, DexType::Jupiter, DexType::Serum] {
+            Self::require_registered_market(registry, &token_pair, dex, markets)?;
+        }
 
-        // Validate price data
-        Self::validate_price_data(&prices)?;
+        // Quote every venue off its own reserves or book
+        let quotes = Self::get_dex_quotes(ctx.accounts, &token_pair)?;
 
         // Calculate potential arbitrage routes
         let routes = Self::calculate_arbitrage_routes(
-            prices,
+            quotes,
             min_profit,
             token_pair,
         )?;
 
-        // Filter profitable routes
+        // Filter profitable routes, skipping venues banned for poor execution
         let profitable_routes = routes.into_iter()
             .filter(|route| route.expected_profit >= route.min_profit)
+            .filter(|route| {
+                Self::route_enabled(ctx.accounts.execution_metrics.as_deref(), route)
+            })
             .collect();
 
         Ok(profitable_routes)
     }
 
-    // Execute arbitrage trade
-    pub fn execute_arbitrage(
-        ctx: Context<ExecuteArbitrage>,
+    // Execute arbitrage trade: the entry leg buys base with quote, the exit leg sells it back.
+    // Both legs are built off-chain and forwarded to the venues' programs; what counts is the
+    // quote the owner ends up with.
+    pub fn execute_arbitrage<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteArbitrage<'info>>,
         route: ArbitrageRoute,
+        entry_call: VenueCall,
+        exit_call: VenueCall,
     ) -> Result<()> {
         // Verify deadline
+        validate_deadline(route.deadline)?;
+        introspection::require_isolated(&ctx.accounts.instructions_sysvar)?;
+
         require!(
-            Clock::get()?.unix_timestamp <= route.deadline,
-            TradingBotError::DeadlineExceeded
+            route.entry_dex == Self::get_entry_dex(&route.route_type)
+                && route.exit_dex == Self::get_exit_dex(&route.route_type),
+            TradingBotError::InvalidDexType
         );
-
-        // Execute trades based on route type
-        match route.route_type {
-            RouteType::RaydiumJupiter => {
-                Self::execute_raydium_jupiter_arb(ctx, &route)?;
-            },
-            RouteType::JupiterSerum => {
-                Self::execute_jupiter_serum_arb(ctx, &route)?;
-            },
-            RouteType::SerumRaydium => {
-                Self::execute_serum_raydium_arb(ctx, &route)?;
-            },
+        require!(
+            Self::route_enabled(ctx.accounts.execution_metrics.as_deref(), &route),
+            TradingBotError::VenueBanned
+        );
+        let accounts = &ctx.accounts;
+        let markets = [&accounts.raydium_market, &accounts.jupiter_market, &accounts.serum_market];
+        let registry = &accounts.pool_registry;
+        for dex in [route.entry_dex, route.exit_dex] {
+            Self::require_registered_market(registry, &route.token_pair, dex, markets)?;
         }
+        require_keys_eq!(
+            accounts.user_base_account.mint,
+            route.token_pair.base_mint,
+            TradingBotError::TradeSideMismatch
+        );
+        require_keys_eq!(
+            accounts.user_token_account.mint,
+            route.token_pair.quote_mint,
+            TradingBotError::TradeSideMismatch
+        );
+
+        let accounts = &mut *ctx.accounts;
+        let mut calls = VenueCalls::new(vec![entry_call, exit_call]);
+        let (base_before, quote_before) =
+            (accounts.user_base_account.amount, accounts.user_token_account.amount);
+
+        let (call_accounts, data) = calls.next(ctx.remaining_accounts)?;
+        forward_call(swap_program_id(route.entry_dex), call_accounts, data, None, &[])?;
+        accounts.user_base_account.reload()?;
+        accounts.user_token_account.reload()?;
+        let spent = math::checked_sub(quote_before, accounts.user_token_account.amount)?;
+        require!(
+            spent > 0 && accounts.user_base_account.amount > base_before,
+            TradingBotError::InvalidTradeConditions
+        );
+        let quote_mid = accounts.user_token_account.amount;
+
+        let (call_accounts, data) = calls.next(ctx.remaining_accounts)?;
+        forward_call(swap_program_id(route.exit_dex), call_accounts, data, None, &[])?;
+        accounts.user_base_account.reload()?;
+        accounts.user_token_account.reload()?;
+        // Everything bought on entry has to be sold on exit
+        require_gte!(
+            accounts.user_base_account.amount,
+            base_before,
+            TradingBotError::InvalidTradeConditions
+        );
+        let received = math::checked_sub(accounts.user_token_account.amount, quote_mid)?;
+
+        // Realised profit in bps of the quote spent, allowed to trail the quote by the slippage
+        let profit = math::checked_sub(received, spent)?;
+        let profit_bps = math::checked_as_u64(math::checked_div(
+            math::checked_mul(profit as u128, 10_000)?,
+            spent as u128,
+        )?)?;
+        let floor = route
+            .expected_profit
+            .saturating_sub(route.max_slippage as u64)
+            .max(route.min_profit);
+        require_gte!(profit_bps, floor, TradingBotError::SlippageExceeded);
+
+        emit!(ArbitrageExecuted {
+            owner: accounts.owner.key(),
+            entry_dex: route.entry_dex,
+            exit_dex: route.exit_dex,
+            expected_profit: route.expected_profit,
+            deadline: route.deadline,
+        });
 
         Ok(())
     }
 
     // Helper functions
-    fn get_dex_prices(accounts: &ArbitrageSearch) -> Result<DexPrices> {
-        // Get Raydium price
-        let raydium_price = RaydiumDex::get_price(
-            accounts.raydium_market,
-            accounts.price_feed,
-        )?;
+    // `markets` in DexType order; the venue's has to be the pair's registered pool there
+    fn require_registered_market(
+        registry: &PoolRegistry,
+        pair: &TokenPair,
+        dex: DexType,
+        markets: [&AccountInfo; DexType::COUNT],
+    ) -> Result<()> {
+        registry.require_pool(pair, dex, markets[dex.index()].key)
+    }
 
-        // Get Jupiter price
-        let jupiter_price = JupiterDex::get_price(
-            accounts.jupiter_market,
-            accounts.price_feed,
+    fn route_enabled(metrics: Option<&ExecutionMetrics>, route: &ArbitrageRoute) -> bool {
+        metrics.map_or(true, |metrics| {
+            metrics.is_enabled(route.entry_dex) && metrics.is_enabled(route.exit_dex)
+        })
+    }
+
+    fn get_dex_quotes(accounts: &ArbitrageSearch, pair: &TokenPair) -> Result<DexQuotes> {
+        let registry = &accounts.pool_registry;
+
+        // Raydium and Jupiter quote the ratio of their registered reserves
+        let raydium = Self::reserve_quote(
+            registry,
+            pair,
+            DexType::Raydium,
+            &accounts.raydium_base_reserve,
+            &accounts.raydium_quote_reserve,
+        )?;
+        let jupiter = Self::reserve_quote(
+            registry,
+            pair,
+            DexType::Jupiter,
+            &accounts.jupiter_base_reserve,
+            &accounts.jupiter_quote_reserve,
         )?;
 
-        // Get Serum price
-        let serum_price = SerumDex::get_price(
-            accounts.serum_market,
-            accounts.price_feed,
+        // Serum's best bid and ask
+        let (bid, ask) = SerumDex::top_of_book(
+            &accounts.serum_market,
+            &accounts.serum_bids,
+            &accounts.serum_asks,
+            &SERUM_DEX_V3_PROGRAM_ID.parse::<Pubkey>().unwrap(),
         )?;
 
-        Ok(DexPrices {
-            raydium: raydium_price,
-            jupiter: jupiter_price,
-            serum: serum_price,
+        Ok(DexQuotes {
+            raydium,
+            jupiter,
+            serum: VenueQuote { bid, ask },
         })
     }
 
-    fn validate_price_data(prices: &DexPrices) -> Result<()> {
-        // Check price staleness
-        let current_time = Clock::get()?.unix_timestamp;
-        let max_staleness = 60; // 60 seconds
-
+    fn reserve_quote(
+        registry: &PoolRegistry,
+        pair: &TokenPair,
+        dex: DexType,
+        base_reserve: &Account<TokenAccount>,
+        quote_reserve: &Account<TokenAccount>,
+    ) -> Result<VenueQuote> {
+        registry.require_reserve(pair, dex, &base_reserve.key())?;
+        registry.require_reserve(pair, dex, &quote_reserve.key())?;
         require!(
-            current_time - prices.raydium.timestamp <= max_staleness &&
-            current_time - prices.jupiter.timestamp <= max_staleness &&
-            current_time - prices.serum.timestamp <= max_staleness,
-            TradingBotError::StalePriceFeed
+            base_reserve.mint == pair.base_mint && quote_reserve.mint == pair.quote_mint,
+            TradingBotError::PoolMismatch
         );
-
-        Ok(())
+        require!(base_reserve.amount > 0, TradingBotError::InsufficientLiquidity);
+        let price = PriceBand::implied_price(base_reserve.amount, quote_reserve.amount)?;
+        Ok(VenueQuote { bid: price, ask: price })
     }
 
     fn calculate_arbitrage_routes(
-        prices: DexPrices,
+        quotes: DexQuotes,
         min_profit: u64,
         token_pair: TokenPair,
     ) -> Result<Vec<ArbitrageRoute>> {
@@ -137,30 +245,30 @@ impl ArbitrageManager {
 
         // Check Raydium -> Jupiter arbitrage
         if let Some(route) = Self::check_route_profitability(
-            prices.raydium,
-            prices.jupiter,
+            quotes.raydium,
+            quotes.jupiter,
             min_profit,
             RouteType::RaydiumJupiter,
-            token_pair.clone(),
+            token_pair,
         )? {
             routes.push(route);
         }
 
         // Check Jupiter -> Serum arbitrage
         if let Some(route) = Self::check_route_profitability(
-            prices.jupiter,
-            prices.serum,
+            quotes.jupiter,
+            quotes.serum,
             min_profit,
             RouteType::JupiterSerum,
-            token_pair.clone(),
+            token_pair,
         )? {
             routes.push(route);
         }
 
         // Check Serum -> Raydium arbitrage
         if let Some(route) = Self::check_route_profitability(
-            prices.serum,
-            prices.raydium,
+            quotes.serum,
+            quotes.raydium,
             min_profit,
             RouteType::SerumRaydium,
             token_pair,
@@ -171,15 +279,16 @@ impl ArbitrageManager {
         Ok(routes)
     }
 
+    // Buys at the entry venue's ask and sells at the exit venue's bid
     fn check_route_profitability(
-        price_a: PriceData,
-        price_b: PriceData,
+        entry: VenueQuote,
+        exit: VenueQuote,
         min_profit: u64,
         route_type: RouteType,
         token_pair: TokenPair,
     ) -> Result<Option<ArbitrageRoute>> {
-        let price_diff = if price_b.price > price_a.price {
-            price_b.price - price_a.price
+        let price_diff = if exit.bid > entry.ask {
+            exit.bid - entry.ask
         } else {
             return Ok(None);
         };
@@ -187,7 +296,7 @@ impl ArbitrageManager {
         let expected_profit = price_diff
             .checked_mul(10000)
             .ok_or(TradingBotError::Overflow)?
-            .checked_div(price_a.price)
+            .checked_div(entry.ask)
             .ok_or(TradingBotError::Overflow)?;
 
         if expected_profit >= min_profit {
@@ -225,33 +334,65 @@ impl ArbitrageManager {
 
 #[derive(Accounts)]
 pub struct ArbitrageSearch<'info> {
-    #[account(mut)]
+    /// CHECK: Matched against the pair's registered Raydium pool
     pub raydium_market: AccountInfo<'info>,
-    #[account(mut)]
+    /// CHECK: Matched against the pair's registered Jupiter pool
     pub jupiter_market: AccountInfo<'info>,
-    #[account(mut)]
+    /// CHECK: Matched against the pair's registered Serum market, loaded by the Serum program
     pub serum_market: AccountInfo<'info>,
-    /// CHECK: Verified in program
-    pub price_feed: AccountInfo<'info>,
+    /// CHECK: Loaded through the Serum market, which checks it is its bids
+    pub serum_bids: AccountInfo<'info>,
+    /// CHECK: Loaded through the Serum market, which checks it is its asks
+    pub serum_asks: AccountInfo<'info>,
+    pub raydium_base_reserve: Account<'info, TokenAccount>,
+    pub raydium_quote_reserve: Account<'info, TokenAccount>,
+    pub jupiter_base_reserve: Account<'info, TokenAccount>,
+    pub jupiter_quote_reserve: Account<'info, TokenAccount>,
+    #[account(seeds = [POOL_REGISTRY_SEED], bump = pool_registry.bump)]
+    pub pool_registry: Box<Account<'info, PoolRegistry>>,
+    #[account(
+        seeds = [EXECUTION_METRICS_SEED, owner.key().as_ref()],
+        bump = execution_metrics.bump,
+    )]
+    pub execution_metrics: Option<Account<'info, ExecutionMetrics>>,
     pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct ExecuteArbitrage<'info> {
-    #[account(mut)]
+    // the quote side, spent on entry and paid back on exit
+    #[account(
+        mut,
+        constraint = user_token_account.owner == owner.key() @ TradingBotError::TradeSideMismatch,
+    )]
     pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_base_account.owner == owner.key() @ TradingBotError::TradeSideMismatch,
+    )]
+    pub user_base_account: Account<'info, TokenAccount>,
+    /// CHECK: Matched against the pair's registered Raydium pool
     pub raydium_market: AccountInfo<'info>,
-    #[account(mut)]
+    /// CHECK: Matched against the pair's registered Jupiter pool
     pub jupiter_market: AccountInfo<'info>,
-    #[account(mut)]
+    /// CHECK: Matched against the pair's registered Serum market
     pub serum_market: AccountInfo<'info>,
+    #[account(seeds = [POOL_REGISTRY_SEED], bump = pool_registry.bump)]
+    pub pool_registry: Box<Account<'info, PoolRegistry>>,
+    #[account(
+        seeds = [EXECUTION_METRICS_SEED, owner.key().as_ref()],
+        bump = execution_metrics.bump,
+    )]
+    pub execution_metrics: Option<Account<'info, ExecutionMetrics>>,
     pub token_program: Program<'info, Token>,
     pub owner: Signer<'info>,
+    /// CHECK: The instructions sysvar, read to keep the route alone in its transaction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
 }
 
-struct DexPrices {
-    raydium: PriceData,
-    jupiter: PriceData,
-    serum: PriceData,
-} 
\ No newline at end of file
+struct DexQuotes {
+    raydium: VenueQuote,
+    jupiter: VenueQuote,
+    serum: VenueQuote,
+}
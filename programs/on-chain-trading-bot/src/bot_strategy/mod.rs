@@ -1,75 +1,81 @@
 use anchor_lang::prelude::*;
-use crate::dex::{raydium::*, jupiter::*, serum::*};
-use crate::oracles::PythOracle;
-use crate::state::{Strategy, StrategyConfig};
-use crate::errors::TradingBotError;
-use std::collections::HashMap;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::dex::{depth, forward_call, swap_program_id, VenueCall, VenueCalls};
+use crate::account_manager::UserPositions;
+use crate::events::{
+    BlockFallbackTriggered, BlockRetryScheduled, KeeperViolationRecorded, PositionReduced,
+    StopMoved, SwapDeferred,
+};
+use crate::oracles::{
+    lending_reserve::{LendingMetric, ReserveRates},
+    stake_pool::ExchangeRate,
+    PythOracle,
+};
+use crate::math;
+use crate::constants::{
+    CORRELATION_MATRIX_SEED, EXECUTION_AUCTION_SEED, EXECUTION_METRICS_SEED, FEED_REGISTRY_SEED,
+    KEEPER_SEED, MAX_LOOP_COUNT, POOL_REGISTRY_SEED, POSITION_STOP_SEED, SESSION_SEED, STRATEGY_SEED,
+    TRADE_APPROVAL_SEED,
+};
+use crate::risk::{PortfolioTrade, RiskManager};
+use crate::state::{
+    AuctionPhase, CorrelationMatrix, ExecutionAuction, ExecutionMetrics, FeedRegistry,
+    KeeperRecord, KeeperViolation, LimitUnit, MinOutMode, NotionalRate, PoolRegistry, PositionStop,
+    Session, Strategy, TradeApproval, TradeMiningEpoch, UserEpochVolume,
+};
+use crate::errors::{ErrorContext, ExecutionStage, TradingBotError};
+use crate::instructions::{PollVolatilityHalt, SettleExecutionAuction};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use crate::introspection;
+use crate::strategy_seeds;
+use crate::types::{DexType, TokenPair, TradeSide};
+use crate::utils::{validate_deadline_in, validate_slippage};
+use crate::valuation::Valuation;
+use std::collections::{BTreeMap, HashMap};
 
 pub struct BotStrategy;
 
+// Strategies are created by `instructions::initialize_bot`
 impl BotStrategy {
-    // Initialize a new bot strategy
-    pub fn initialize(
-        ctx: Context<InitializeBot>,
-        config: StrategyConfig,
-        strategy_id: String,
-    ) -> Result<()> {
-        let strategy = &mut ctx.accounts.strategy;
-        strategy.owner = ctx.accounts.owner.key();
-        strategy.strategy_id = strategy_id;
-        strategy.config = config;
-        strategy.is_active = false;
-        strategy.created_at = Clock::get()?.unix_timestamp;
-
-        Ok(())
-    }
-
-    // Execute strategy based on frontend configuration
-    pub fn execute_strategy(
-        ctx: Context<ExecuteStrategy>,
-        blocks: Vec<StrategyBlock>,
-    ) -> Result<()> {
-        let strategy = &mut ctx.accounts.strategy;
-        require!(strategy.is_active, TradingBotError::StrategyInactive);
-
-        // Execute each block in the strategy
-        for block in blocks {
-            match block.block_type {
-                BlockType::Trigger => {
-                    Self::execute_trigger(ctx.accounts.clone(), &block)?;
-                },
-                BlockType::Action => {
-                    Self::execute_action(ctx.accounts.clone(), &block)?;
-                },
-                BlockType::Condition => {
-                    Self::execute_condition(ctx.accounts.clone(), &block)?;
-                },
-            }
-        }
-
-        Ok(())
-    }
-
     // Execute trigger block (e.g., price conditions)
     fn execute_trigger(
-        accounts: ExecuteStrategy,
+        accounts: &ExecuteStrategy,
         block: &StrategyBlock,
+        state: &mut ExecutionState,
     ) -> Result<()> {
         match block.trigger_type {
             TriggerType::Price => {
-                // Get price from Pyth oracle
-                let price = PythOracle::get_price(
-                    &accounts.price_feed,
-                    60, // 60 seconds max staleness
-                )?;
+                let price = Self::current_price(accounts, state)?;
 
                 // Check price condition
                 Self::verify_price_condition(
-                    price.price,
-                    block.config.price_threshold,
-                    block.config.condition_type,
+                    price,
+                    state.param(block.config.price_threshold, "price_threshold")?,
+                    state.param(block.config.condition_type.clone(), "condition_type")?,
                 )?;
             },
+            TriggerType::PegDeviation => {
+                let threshold_bps =
+                    state.param(block.config.peg_deviation_bps, "peg_deviation_bps")?;
+                let dex = state.param(block.config.parameters.dex_type, "dex_type")?;
+                require!(
+                    Self::peg_deviation_bps(accounts, dex)? >= threshold_bps as u64,
+                    TradingBotError::ConditionNotMet
+                );
+            },
+            TriggerType::LendingRate => {
+                let metric = state.param(block.config.lending_metric, "lending_metric")?;
+                let reserve = state.param(block.config.lending_reserve, "lending_reserve")?;
+                let threshold = state.param(block.config.rate_threshold_bps, "rate_threshold_bps")?;
+                let condition_type =
+                    state.param(block.config.condition_type.clone(), "condition_type")?;
+                let rate = Self::lending_rate_bps(accounts, block, metric, reserve)?;
+                Self::verify_price_condition(rate, threshold, condition_type)?;
+            },
             TriggerType::Volume => {
                 // Implement volume trigger
             },
@@ -82,39 +88,66 @@ impl BotStrategy {
     }
 
     // Execute action block (e.g., trades)
-    fn execute_action(
-        accounts: ExecuteStrategy,
+    fn execute_action<'info>(
+        accounts: &mut ExecuteStrategy<'info>,
+        remaining_accounts: &[AccountInfo<'info>],
         block: &StrategyBlock,
         state: &mut ExecutionState,
     ) -> Result<()> {
         match block.config.action_type {
             Some(ActionType::Swap) => {
-                match block.config.parameters.dex_type {
-                    Some(DexType::Raydium) => {
-                        RaydiumDex::swap(
-                            accounts.into(),
-                            block.config.parameters.amount.unwrap(),
-                            block.config.parameters.token_address.unwrap(),
-                            block.config.parameters.slippage_bps.unwrap(),
-                        )?;
-                    },
-                    Some(DexType::Jupiter) => {
-                        JupiterDex::execute_swap(
-                            accounts.into(),
-                            block.config.parameters.amount.unwrap(),
-                            block.config.parameters.token_address.unwrap(),
-                            block.config.parameters.slippage_bps.unwrap(),
-                        )?;
-                    },
-                    Some(DexType::Serum) => {
-                        SerumDex::place_market_order(
-                            accounts.into(),
-                            block.config.parameters.amount.unwrap(),
-                            block.config.parameters.token_address.unwrap(),
-                        )?;
-                    },
-                    None => return Err(TradingBotError::InvalidDexType.into()),
+                Self::check_trade_risk(accounts, block, state)?;
+                let dex = state.param(block.config.parameters.dex_type, "dex_type")?;
+                let amount = state.param(block.config.parameters.amount, "amount")?;
+                let amount = Self::exit_tranche(accounts, block, amount)?;
+                let amount = state.take_volume(amount)?;
+                let total = amount;
+                let mut deferred = 0;
+
+                // size to the venue's depth up front instead of failing on slippage afterwards
+                let (amount, remainder) = match (
+                    accounts.pool_reserve_in.as_ref(),
+                    block.config.max_price_impact,
+                ) {
+                    (Some(reserve_in), Some(max_impact_bps)) => {
+                        Self::require_registered_reserve(accounts, dex, &reserve_in.key())?;
+                        depth::split_for_depth(amount, reserve_in.amount, max_impact_bps)?
+                    }
+                    _ => (amount, 0),
+                };
+                Self::swap_on(accounts, remaining_accounts, dex, amount, block, state)?;
+
+                if remainder > 0 {
+                    match block.config.parameters.fallback_dex {
+                        Some(fallback) if fallback != dex => {
+                            msg!("Routing {} to {:?}", remainder, fallback);
+                            Self::swap_on(
+                                accounts,
+                                remaining_accounts,
+                                fallback,
+                                remainder,
+                                block,
+                                state,
+                            )?;
+                        }
+                        _ => {
+                            msg!("Deferring {} beyond the depth limit", remainder);
+                            deferred = remainder;
+                            state.return_volume(remainder);
+                            emit!(SwapDeferred {
+                                strategy: accounts.strategy.key(),
+                                block_id: block.id.clone(),
+                                dex,
+                                amount: remainder,
+                                deadline: state.deadline,
+                            });
+                        }
+                    }
                 }
+                Self::record_exit(accounts, block, total - deferred)?;
+            },
+            Some(ActionType::BreakevenStop) => {
+                Self::move_stop(accounts, block, state)?;
             },
             // Add other action types
             _ => return Err(TradingBotError::InvalidActionType.into()),
@@ -124,23 +157,455 @@ impl BotStrategy {
         Ok(())
     }
 
+    // Depth is only read from a reserve of the pair's registered pool on the venue
+    fn require_registered_reserve(
+        accounts: &ExecuteStrategy,
+        dex: DexType,
+        reserve: &Pubkey,
+    ) -> Result<()> {
+        let registry = accounts
+            .pool_registry
+            .as_ref()
+            .ok_or(TradingBotError::UnregisteredPool)?;
+        registry.require_reserve(&accounts.strategy.config.pair, dex, reserve)
+    }
+
+    // The swap is the crank's next venue call, built off-chain and forwarded to the venue's
+    // program. It is held to the block on the balances: no more than `amount` spent from the token
+    // account and at least the minimum out received in the output account.
+    fn swap_on<'info>(
+        accounts: &mut ExecuteStrategy<'info>,
+        remaining_accounts: &[AccountInfo<'info>],
+        dex: DexType,
+        amount: u64,
+        block: &StrategyBlock,
+        state: &mut ExecutionState,
+    ) -> Result<()> {
+        if let Some(metrics) = accounts.execution_metrics.as_ref() {
+            require!(metrics.is_enabled(dex), TradingBotError::VenueBanned);
+        }
+        let side = state.param(block.config.side, "side")?;
+        validate_slippage(state.param(block.config.parameters.slippage_bps, "slippage_bps")?)?;
+        let minimum_out = Self::minimum_out(accounts, block, side, amount)?;
+        Self::judge_keeper(accounts, side, amount, minimum_out, state)?;
+
+        let output_account = accounts
+            .output_account
+            .as_mut()
+            .ok_or(TradingBotError::TradeSideMismatch)?;
+        require_keys_eq!(
+            output_account.mint,
+            side.output_mint(&accounts.strategy.config.pair),
+            TradingBotError::TradeSideMismatch
+        );
+        let (input_before, output_before) = (accounts.token_account.amount, output_account.amount);
+        let (call_accounts, data) = state.venue_calls.next(remaining_accounts)?;
+        let strategy = &accounts.strategy;
+        forward_call(
+            swap_program_id(dex),
+            call_accounts,
+            data,
+            Some(strategy.key()),
+            &[strategy_seeds!(strategy)],
+        )?;
+        accounts.token_account.reload()?;
+        output_account.reload()?;
+
+        let spent = math::checked_sub(input_before, accounts.token_account.amount)?;
+        let received = math::checked_sub(output_account.amount, output_before)?;
+        require!(
+            spent <= amount && received >= minimum_out,
+            TradingBotError::SlippageExceeded
+        );
+        state.trade_results.push(TradeResult {
+            dex,
+            input_mint: accounts.token_account.mint,
+            amount_in: spent,
+            output_mint: output_account.mint,
+            amount_out: received,
+        });
+        Ok(())
+    }
+
+    // Trails the position's stop and, past the block's profit trigger, lifts it to breakeven.
+    // The moved stop is carried in the execution state and written back once the blocks ran.
+    fn move_stop(
+        accounts: &ExecuteStrategy,
+        block: &StrategyBlock,
+        state: &mut ExecutionState,
+    ) -> Result<()> {
+        let trigger_bps =
+            state.param(block.config.breakeven_trigger_bps, "breakeven_trigger_bps")?;
+        let fee_bps = block.config.breakeven_fee_bps.unwrap_or(0);
+        let mut stop = match state.position_stop.take() {
+            Some(stop) => stop,
+            None => {
+                let position_stop = accounts
+                    .position_stop
+                    .as_ref()
+                    .ok_or(TradingBotError::InvalidPositionStop)?;
+                (**position_stop).clone()
+            }
+        };
+        let price = Self::pair_price(accounts)?;
+
+        stop.trail(price)?;
+        if stop.apply_breakeven(price, trigger_bps, fee_bps)? {
+            msg!("Stop moved to breakeven at {}", stop.stop_price);
+        }
+        state.position_stop = Some(stop);
+        Ok(())
+    }
+
+    // A scaled exit swaps its tranche of the tracked position, no more than the block's amount
+    fn exit_tranche(
+        accounts: &ExecuteStrategy,
+        block: &StrategyBlock,
+        amount: u64,
+    ) -> Result<u64> {
+        let Some(exit) = block.config.parameters.scaled_exit else {
+            return Ok(amount);
+        };
+        let user_positions = accounts
+            .user_positions
+            .as_ref()
+            .ok_or(TradingBotError::PositionNotFound)?;
+        let tranche = user_positions.load_mut()?.position_share(
+            exit.dex,
+            exit.position_id,
+            exit.pct_bps,
+            true,
+        )?;
+        require_gt!(tranche, 0, TradingBotError::PositionNotFound);
+        Ok(amount.min(tranche))
+    }
+
+    // Takes what a scaled exit swapped off the position; a deferred part stays open
+    fn record_exit(
+        accounts: &ExecuteStrategy,
+        block: &StrategyBlock,
+        swapped: u64,
+    ) -> Result<()> {
+        let (Some(exit), Some(user_positions)) = (
+            block.config.parameters.scaled_exit,
+            accounts.user_positions.as_ref(),
+        ) else {
+            return Ok(());
+        };
+        let mut positions = user_positions.load_mut()?;
+        let remaining = positions.reduce_position(exit.dex, exit.position_id, swapped)?;
+        let now = Clock::get()?.unix_timestamp;
+        positions.last_update = now;
+        emit!(PositionReduced {
+            owner: positions.owner,
+            dex: exit.dex,
+            position_id: exit.position_id,
+            reduced: swapped,
+            remaining,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    // The block's `minimum_out` covers its whole amount, so each part swapped gets its share. In
+    // oracle mode the strategy's configured slippage off the oracle price sets the floor instead
+    // of whatever the keeper passed.
+    fn minimum_out(
+        accounts: &ExecuteStrategy,
+        block: &StrategyBlock,
+        side: TradeSide,
+        amount: u64,
+    ) -> Result<u64> {
+        let block_amount = block.config.parameters.amount.unwrap_or(amount);
+        let requested = match block.config.minimum_out {
+            Some(minimum_out) if block_amount > 0 => math::checked_as_u64(math::checked_div(
+                math::checked_mul(minimum_out as u128, amount as u128)?,
+                block_amount as u128,
+            )?)?,
+            _ => 0,
+        };
+
+        let config = &accounts.strategy.config;
+        let price_feed = match config.min_out_mode {
+            MinOutMode::Caller => return Ok(requested),
+            MinOutMode::Oracle { price_feed } => price_feed,
+        };
+        require_keys_eq!(
+            accounts.price_feed.key(),
+            price_feed,
+            TradingBotError::PriceFeedMismatch
+        );
+        let price = Self::pair_price(accounts)?;
+        let oracle_min_out =
+            RiskManager::oracle_min_out(amount, price, side.is_buy(), config.slippage_bps)?;
+        if requested < oracle_min_out {
+            msg!("Raising minimum out from {} to {}", requested, oracle_min_out);
+        }
+        Ok(requested.max(oracle_min_out))
+    }
+
+    // A keeper's swap is held to the oracle floor at the strategy's slippage, read from the feed
+    // its keeper allowlist pins. One below it still runs, as caller mode allows, but goes on the
+    // keeper's record for the owner to slash.
+    fn judge_keeper(
+        accounts: &ExecuteStrategy,
+        side: TradeSide,
+        amount: u64,
+        minimum_out: u64,
+        state: &mut ExecutionState,
+    ) -> Result<()> {
+        let allowlist = &accounts.strategy.keeper_allowlist;
+        if accounts.keeper_record.is_none()
+            || allowlist.price_feed == Pubkey::default()
+            || accounts.price_feed.key() != allowlist.price_feed
+        {
+            return Ok(());
+        }
+        let price = Self::pair_price(accounts)?;
+        let oracle_min_out = RiskManager::oracle_min_out(
+            amount,
+            price,
+            side.is_buy(),
+            accounts.strategy.config.slippage_bps,
+        )?;
+        if minimum_out < oracle_min_out {
+            msg!("Keeper minimum out {} under floor {}", minimum_out, oracle_min_out);
+            state.keeper_violations.push(KeeperViolation {
+                strategy: accounts.strategy.key(),
+                minimum_out,
+                oracle_min_out,
+                slot: Clock::get()?.slot,
+            });
+        }
+        Ok(())
+    }
+
+    // Swaps must pass the strategy's risk limits, including correlated exposure held elsewhere
+    // when the owner keeps a correlation matrix. The side decides which of the pair's mints is
+    // spent and which way the block's limit price binds.
+    fn check_trade_risk(
+        accounts: &ExecuteStrategy,
+        block: &StrategyBlock,
+        state: &mut ExecutionState,
+    ) -> Result<()> {
+        let amount = state.param(block.config.parameters.amount, "amount")?;
+        let mint = state.param(block.config.parameters.token_address, "token_address")?;
+        let price = Self::pair_price(accounts)?;
+        let side = state.param(block.config.side, "side")?;
+        let is_buy = side.is_buy();
+        require_keys_eq!(
+            accounts.token_account.mint,
+            side.input_mint(&accounts.strategy.config.pair),
+            TradingBotError::TradeSideMismatch
+        );
+        if let Some(limit_price) = block.config.limit_price {
+            require!(
+                side.within_limit(price, limit_price),
+                TradingBotError::LimitPriceNotMet
+            );
+        }
+
+        // a halt after a large print blocks entries only, exits stay open
+        if is_buy
+            && accounts
+                .strategy
+                .volatility_halt
+                .entries_halted(Clock::get()?.unix_timestamp)
+        {
+            return Err(TradingBotError::EntriesHalted.into());
+        }
+
+        let portfolio = match accounts.correlation_matrix.as_ref() {
+            Some(matrix) => Some(PortfolioTrade {
+                matrix,
+                mint,
+                notional: RiskManager::trade_notional(amount, price, is_buy)?,
+            }),
+            None => None,
+        };
+        let trade_size = match accounts.strategy.risk_parameters.limit_unit {
+            LimitUnit::Token => {
+                state.volume_rate = None;
+                Self::accounting_size(accounts, amount, price, is_buy)?
+            }
+            LimitUnit::Usd { quote_usd_feed } => {
+                let quote_mint = &accounts.strategy.config.pair.quote_mint;
+                let quote_usd = Self::usd_price(
+                    accounts,
+                    &accounts.quote_usd_feed,
+                    quote_usd_feed,
+                    quote_mint,
+                )?;
+                let notional = PythOracle::usd_value(
+                    Self::quote_value(amount, price, is_buy)?,
+                    accounts.feed_registry.decimals(quote_mint)?,
+                    quote_usd,
+                )?;
+                state.volume_rate = Some(NotionalRate { amount, notional });
+                notional
+            }
+        };
+        require!(
+            RiskManager::validate_trade(&accounts.strategy, trade_size, price, portfolio)?,
+            TradingBotError::PortfolioRiskExceeded
+        );
+        Ok(())
+    }
+
+    // What a swap of `amount` of the input mint counts as against the limits: its value in the
+    // strategy's accounting currency, or `amount` itself while none is set
+    fn accounting_size(
+        accounts: &ExecuteStrategy,
+        amount: u64,
+        price: u64,
+        is_buy: bool,
+    ) -> Result<u64> {
+        let accounting = &accounts.strategy.accounting;
+        if !accounting.is_set() {
+            return Ok(amount);
+        }
+        let quote_value = Self::quote_value(amount, price, is_buy)?;
+        if accounting.is_quote(&accounts.strategy.config.pair) {
+            return Ok(quote_value);
+        }
+
+        let quote_usd = Self::usd_price(
+            accounts,
+            &accounts.quote_usd_feed,
+            accounting.quote_usd_feed,
+            &accounts.strategy.config.pair.quote_mint,
+        )?;
+        let mint_usd = Self::usd_price(
+            accounts,
+            &accounts.accounting_usd_feed,
+            accounting.mint_usd_feed,
+            &accounting.mint,
+        )?;
+        accounting.from_quote(quote_value, quote_usd, mint_usd)
+    }
+
+    // A swap spends quote on buys and base on sells
+    fn quote_value(amount: u64, price: u64, is_buy: bool) -> Result<u64> {
+        if is_buy {
+            Ok(amount)
+        } else {
+            Valuation::quote_value(amount, price)
+        }
+    }
+
+    // USD price of `mint` in PRICE_PRECISION, off a feed that has to be the one the strategy
+    // configured and the one the registry holds for `mint`
+    fn usd_price(
+        accounts: &ExecuteStrategy,
+        feed: &Option<UncheckedAccount>,
+        expected: Pubkey,
+        mint: &Pubkey,
+    ) -> Result<u64> {
+        let feed = feed.as_ref().ok_or(TradingBotError::PriceFeedMismatch)?;
+        require_keys_eq!(feed.key(), expected, TradingBotError::PriceFeedMismatch);
+        PythOracle::get_usd_price(&accounts.feed_registry, mint, feed, u64::MAX)
+    }
+
+    // The pair's price from `price_feed`, registered as the feed of its base mint, in native
+    // quote per native base scaled by PRICE_PRECISION
+    fn pair_price(accounts: &ExecuteStrategy) -> Result<u64> {
+        Self::base_price(
+            &accounts.feed_registry,
+            &accounts.strategy.config.pair,
+            &accounts.price_feed,
+            accounts.stake_pool.as_deref(),
+        )
+    }
+
+    // An LST base is valued through the stake pool passed with it, `price_feed` then being the
+    // SOL feed; without one the registry refuses an LST rather than price it off SOL alone
+    fn base_price(
+        registry: &FeedRegistry,
+        pair: &TokenPair,
+        price_feed: &AccountInfo,
+        stake_pool: Option<&AccountInfo>,
+    ) -> Result<u64> {
+        let mint = &pair.base_mint;
+        let price = match stake_pool {
+            Some(stake_pool) => {
+                PythOracle::get_lst_price(registry, mint, price_feed, stake_pool, u64::MAX)?
+            }
+            None => PythOracle::get_price_with_confidence(registry, mint, price_feed, u64::MAX)?,
+        };
+        PythOracle::pair_price(registry, pair, &price)
+    }
+
+    // How far the pair's pool prices its LST base against SOL, the quote reserve over the base
+    // reserve, from the stake pool's exchange rate. Both reserves have to be the registered
+    // pool's; LSTs carry SOL's 9 decimals, so the reserves compare as they are.
+    fn peg_deviation_bps(accounts: &ExecuteStrategy, dex: DexType) -> Result<u64> {
+        let pair = &accounts.strategy.config.pair;
+        let (Some(reserve_in), Some(reserve_out)) = (
+            accounts.pool_reserve_in.as_ref(),
+            accounts.pool_reserve_out.as_ref(),
+        ) else {
+            return Err(TradingBotError::PoolMismatch.into());
+        };
+        Self::require_registered_reserve(accounts, dex, &reserve_in.key())?;
+        Self::require_registered_reserve(accounts, dex, &reserve_out.key())?;
+        let (base, quote) = if reserve_in.mint == pair.base_mint {
+            (reserve_in, reserve_out)
+        } else {
+            (reserve_out, reserve_in)
+        };
+        require!(
+            base.mint == pair.base_mint && quote.mint == pair.quote_mint,
+            TradingBotError::PoolMismatch
+        );
+
+        let feed = accounts.feed_registry.resolve(&pair.base_mint, accounts.price_feed.key)?;
+        let source = feed.stake_pool.ok_or(TradingBotError::InvalidStakePool)?;
+        let stake_pool = accounts
+            .stake_pool
+            .as_ref()
+            .ok_or(TradingBotError::StakePoolRequired)?;
+        let rate = ExchangeRate::read(&source, stake_pool, &pair.base_mint)?;
+        rate.deviation_bps(quote.amount as u128, base.amount as u128)
+    }
+
+    // The block's metric on its reserve; with a `compare_reserve`, less the same metric there, so
+    // e.g. a rotation fires once one reserve's deposit APY beats another's by the threshold
+    fn lending_rate_bps(
+        accounts: &ExecuteStrategy,
+        block: &StrategyBlock,
+        metric: LendingMetric,
+        reserve: Pubkey,
+    ) -> Result<i64> {
+        let read = |expected: Pubkey, account: &Option<UncheckedAccount>| -> Result<i64> {
+            let account = account
+                .as_ref()
+                .ok_or(TradingBotError::InvalidLendingReserve)?;
+            require_keys_eq!(account.key(), expected, TradingBotError::InvalidLendingReserve);
+            let bps = ReserveRates::read(account)?.metric(metric)?;
+            i64::try_from(bps).map_err(|_| TradingBotError::InvalidCalculation.into())
+        };
+        let rate = read(reserve, &accounts.lending_reserve)?;
+        match block.config.compare_reserve {
+            Some(other) => math::checked_sub(rate, read(other, &accounts.compare_reserve)?),
+            None => Ok(rate),
+        }
+    }
+
     // Execute condition block
     fn execute_condition(
-        accounts: ExecuteStrategy,
+        accounts: &ExecuteStrategy,
         block: &StrategyBlock,
+        state: &mut ExecutionState,
     ) -> Result<()> {
         match block.condition_type {
             ConditionType::Balance => {
                 Self::verify_balance_condition(
                     &accounts.token_account,
-                    block.config.minimum_balance,
+                    state.param(block.config.minimum_balance, "minimum_balance")?,
                 )?;
             },
             ConditionType::PriceImpact => {
-                Self::verify_price_impact(
-                    accounts.clone(),
-                    block.config.max_price_impact,
-                )?;
+                Self::verify_price_impact(accounts, block, state)?;
             },
             ConditionType::Custom => {
                 // Implement custom conditions
@@ -151,74 +616,640 @@ impl BotStrategy {
     }
 
     // Helper functions
+
+    // The pair's oracle price, or the one a dry run injected
+    fn current_price(accounts: &ExecuteStrategy, state: &ExecutionState) -> Result<i64> {
+        match state.price_override {
+            Some(price) => Ok(price),
+            None => Self::signed_price(Self::pair_price(accounts)?),
+        }
+    }
+
+    // Thresholds and dry-run prices are signed
+    fn signed_price(price: u64) -> Result<i64> {
+        i64::try_from(price).map_err(|_| TradingBotError::InvalidCalculation.into())
+    }
+
+    // The token account is reloaded after each swap, so this sees what earlier blocks left
+    fn verify_balance_condition(token_account: &TokenAccount, minimum_balance: u64) -> Result<()> {
+        require_gte!(
+            token_account.amount,
+            minimum_balance,
+            TradingBotError::ConditionNotMet
+        );
+        Ok(())
+    }
+
+    // Swapping the block's amount into the registered pool on its venue has to stay within the
+    // impact limit
+    fn verify_price_impact(
+        accounts: &ExecuteStrategy,
+        block: &StrategyBlock,
+        state: &mut ExecutionState,
+    ) -> Result<()> {
+        let max_impact_bps = state.param(block.config.max_price_impact, "max_price_impact")?;
+        let dex = state.param(block.config.parameters.dex_type, "dex_type")?;
+        let amount = state.param(block.config.parameters.amount, "amount")?;
+        let reserve_in = accounts
+            .pool_reserve_in
+            .as_ref()
+            .ok_or(TradingBotError::PoolMismatch)?;
+        Self::require_registered_reserve(accounts, dex, &reserve_in.key())?;
+        require_gte!(
+            max_impact_bps as u64,
+            depth::price_impact_bps(amount, reserve_in.amount)?,
+            TradingBotError::ConditionNotMet
+        );
+        Ok(())
+    }
+
+    // An exit ends the sequence once its price condition holds, or right away without one
+    fn should_exit(
+        accounts: &ExecuteStrategy,
+        block: &StrategyBlock,
+        state: &mut ExecutionState,
+    ) -> Result<bool> {
+        if block.config.price_threshold.is_none() && block.config.condition_type.is_none() {
+            return Ok(true);
+        }
+        let threshold = state.param(block.config.price_threshold, "price_threshold")?;
+        let condition_type = state.param(block.config.condition_type.clone(), "condition_type")?;
+        let price = Self::current_price(accounts, state)?;
+        Ok(Self::price_condition_met(price, threshold, condition_type))
+    }
+
+    // Runs the swap right before the loop `loop_count` more times, each pass sized, risk-checked
+    // and taken from the daily volume like the first
+    fn execute_loop<'info>(
+        accounts: &mut ExecuteStrategy<'info>,
+        remaining_accounts: &[AccountInfo<'info>],
+        blocks: &[StrategyBlock],
+        index: usize,
+        state: &mut ExecutionState,
+    ) -> Result<()> {
+        let block = &blocks[index];
+        let loop_count = state.param(block.config.loop_count, "loop_count")?;
+        let swap = Self::loop_target(blocks, index)?;
+        for _ in 0..loop_count {
+            Self::execute_action(accounts, remaining_accounts, swap, state)?;
+            let passes = state.loop_counters.entry(block.id.clone()).or_insert(0);
+            *passes = math::checked_add(*passes, 1)?;
+        }
+        Ok(())
+    }
+
+    // The swap action a loop repeats, the block right before it
+    fn loop_target(blocks: &[StrategyBlock], index: usize) -> Result<&StrategyBlock> {
+        index
+            .checked_sub(1)
+            .map(|previous| &blocks[previous])
+            .filter(|previous| {
+                matches!(previous.block_type, BlockType::Action)
+                    && matches!(previous.config.action_type, Some(ActionType::Swap))
+            })
+            .ok_or_else(|| TradingBotError::InvalidLoopBlock.into())
+    }
+
+    fn validate_loops(blocks: &[StrategyBlock]) -> Result<()> {
+        for (index, block) in blocks.iter().enumerate() {
+            if matches!(block.block_type, BlockType::Loop) {
+                Self::loop_target(blocks, index)?;
+                require_gte!(
+                    MAX_LOOP_COUNT,
+                    block.config.loop_count.unwrap_or(0),
+                    TradingBotError::InvalidLoopBlock
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Every swap the blocks ask for, loops' repeats included, for the session's caps
+    fn swap_amounts(blocks: &[StrategyBlock]) -> Vec<u64> {
+        let mut amounts = Vec::new();
+        for (index, block) in blocks.iter().enumerate() {
+            let (swap, repeats) = match block.block_type {
+                BlockType::Action => (block, 1),
+                BlockType::Loop => match Self::loop_target(blocks, index) {
+                    Ok(swap) => (swap, block.config.loop_count.unwrap_or(0)),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            if let (Some(ActionType::Swap), Some(amount)) =
+                (&swap.config.action_type, swap.config.parameters.amount)
+            {
+                amounts.extend(std::iter::repeat(amount).take(repeats as usize));
+            }
+        }
+        amounts
+    }
+
     fn verify_price_condition(
         current_price: i64,
         threshold: i64,
         condition_type: PriceConditionType,
     ) -> Result<()> {
+        require!(
+            Self::price_condition_met(current_price, threshold, condition_type),
+            TradingBotError::ConditionNotMet
+        );
+
+        Ok(())
+    }
+
+    fn price_condition_met(
+        current_price: i64,
+        threshold: i64,
+        condition_type: PriceConditionType,
+    ) -> bool {
         match condition_type {
-            PriceConditionType::Above => {
-                require!(
-                    current_price > threshold,
-                    TradingBotError::ConditionNotMet
-                );
-            },
-            PriceConditionType::Below => {
-                require!(
-                    current_price < threshold,
-                    TradingBotError::ConditionNotMet
-                );
-            },
-            PriceConditionType::Equal => {
+            PriceConditionType::Above => current_price > threshold,
+            PriceConditionType::Below => current_price < threshold,
+            // Allow small deviation
+            PriceConditionType::Equal => (current_price - threshold).abs() < 100,
+        }
+    }
+
+    // The owner may run anything; a session key only what fits its remaining caps, and no one but
+    // the owner alongside other instructions
+    fn authorize_execution(
+        accounts: &mut ExecuteStrategy,
+        largest_swap: u64,
+        swap_amounts: &[u64],
+    ) -> Result<()> {
+        if accounts.authority.key() == accounts.strategy.owner {
+            return Ok(());
+        }
+        let instructions_sysvar = accounts
+            .instructions_sysvar
+            .as_ref()
+            .ok_or(TradingBotError::UntrustedInstruction)?;
+        introspection::require_isolated(instructions_sysvar)?;
+        if let Some(keeper_record) = accounts.keeper_record.as_ref() {
+            require_eq!(
+                keeper_record.exit_requested_at,
+                0,
+                TradingBotError::KeeperNotAllowed
+            );
+            keeper_record.check_attempt(&accounts.strategy.key(), Clock::get()?.slot)?;
+        }
+        if accounts.strategy.auctioned_execution {
+            Self::claim_auctioned_execution(accounts)?;
+        }
+        let allowlist = &accounts.strategy.keeper_allowlist;
+        if allowlist.enforced {
+            require!(
+                allowlist.contains(&accounts.authority.key())
+                    && accounts.keeper_record.is_some()
+                    && accounts.price_feed.key() == allowlist.price_feed,
+                TradingBotError::KeeperNotAllowed
+            );
+        }
+        let session = accounts
+            .session
+            .as_mut()
+            .ok_or(TradingBotError::UnauthorizedExecutor)?;
+        // sessions opened by a key that recovery has since replaced are dead
+        require!(
+            session.owner == accounts.strategy.owner
+                && session.is_live(accounts.strategy.clock_now()?),
+            TradingBotError::SessionLimitExceeded
+        );
+        let total_volume = swap_amounts
+            .iter()
+            .try_fold(0u64, |total, amount| math::checked_add(total, *amount))?;
+        require!(
+            session.spend(largest_swap, total_volume)?,
+            TradingBotError::SessionLimitExceeded
+        );
+        Ok(())
+    }
+
+    // While executions are auctioned only the winner runs during its exclusivity, paying its bid to
+    // the owner as it does; the first keeper past a lapsed window forfeits the winner's bid instead
+    fn claim_auctioned_execution(accounts: &mut ExecuteStrategy) -> Result<()> {
+        let auction = accounts
+            .execution_auction
+            .as_mut()
+            .ok_or(TradingBotError::ExecutionAuctionActive)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            auction.allows(&accounts.authority.key(), now),
+            TradingBotError::ExecutionAuctionActive
+        );
+        if auction.best_bid > 0 {
+            let executed = auction.phase(now) == AuctionPhase::Exclusive;
+            let owner = accounts.owner.to_account_info();
+            SettleExecutionAuction::pay_rebate(auction, &owner, executed)?;
+        }
+        Ok(())
+    }
+
+    // Swaps above the large-trade threshold need the co-signer: signing this execution, or through
+    // an approved request covering the largest swap, which is consumed here
+    fn check_large_trade_approval(accounts: &ExecuteStrategy, amount: u64) -> Result<()> {
+        let policy = accounts.strategy.approval_policy;
+        if !policy.requires_approval(amount) {
+            return Ok(());
+        }
+        if let Some(co_signer) = accounts.co_signer.as_ref() {
+            require_keys_eq!(
+                co_signer.key(),
+                policy.co_signer,
+                TradingBotError::LargeTradeNotApproved
+            );
+            return Ok(());
+        }
+
+        let approval = accounts
+            .trade_approval
+            .as_ref()
+            .ok_or(TradingBotError::LargeTradeNotApproved)?;
+        require!(
+            approval.covers(amount, accounts.strategy.clock_now()?),
+            TradingBotError::LargeTradeNotApproved
+        );
+        msg!("Consuming approval for trades up to {}", approval.max_amount);
+        approval.close(accounts.owner.to_account_info())
+    }
+
+    fn execute_block<'info>(
+        accounts: &mut ExecuteStrategy<'info>,
+        remaining_accounts: &[AccountInfo<'info>],
+        blocks: &[StrategyBlock],
+        index: usize,
+        state: &mut ExecutionState,
+    ) -> (ExecutionStage, Result<()>) {
+        let block = &blocks[index];
+        match block.block_type {
+            BlockType::Trigger => (
+                ExecutionStage::Trigger,
+                Self::execute_trigger(accounts, block, state),
+            ),
+            BlockType::Action => (
+                ExecutionStage::Action,
+                Self::execute_action(accounts, remaining_accounts, block, state),
+            ),
+            BlockType::Condition => (
+                ExecutionStage::Condition,
+                Self::execute_condition(accounts, block, state),
+            ),
+            BlockType::Loop => (
+                ExecutionStage::Loop,
+                Self::execute_loop(accounts, remaining_accounts, blocks, index, state),
+            ),
+            // exits steer the sequence and are resolved by the caller
+            BlockType::Exit => (ExecutionStage::Exit, Ok(())),
+        }
+    }
+
+    // A fallback must point at another block that does work
+    fn validate_fallbacks(blocks: &[StrategyBlock]) -> Result<()> {
+        for (index, block) in blocks.iter().enumerate() {
+            if let Some(fallback) = block.config.fallback_block {
+                let target = blocks
+                    .get(fallback as usize)
+                    .ok_or(TradingBotError::InvalidBlockRetry)?;
                 require!(
-                    (current_price - threshold).abs() < 100, // Allow small deviation
-                    TradingBotError::ConditionNotMet
+                    fallback as usize != index && !matches!(target.block_type, BlockType::Exit),
+                    TradingBotError::InvalidBlockRetry
                 );
-            },
+            }
         }
-
         Ok(())
     }
 
-    // Execute strategy with block sequence
-    pub fn execute_strategy_blocks(
-        ctx: Context<ExecuteStrategy>,
+    // A failed block with `max_retries` set is retried on the next crank: the failure is counted
+    // and this crank ends cleanly so the count persists. Once the retries are used up its fallback
+    // block runs in its place. Only failures the program sees can be counted, a CPI that aborts
+    // the transaction leaves nothing behind. Returns the fallback to run, if any.
+    fn recover_block(
+        accounts: &ExecuteStrategy,
+        blocks: &[StrategyBlock],
+        index: usize,
+        error: Error,
+        state: &mut ExecutionState,
+    ) -> Result<Option<usize>> {
+        let block = &blocks[index];
+        let max_retries = match block.config.max_retries {
+            Some(max_retries) => max_retries,
+            None => return Err(error),
+        };
+        let failures = state.record_failure(index as u16)?;
+        if failures <= max_retries {
+            // ending early would commit the actions already taken and repeat them next crank
+            if !state.executed_blocks.is_empty() {
+                return Err(error);
+            }
+            msg!(
+                "Block {} failed {} of {} times, retrying next crank",
+                block.id,
+                failures,
+                max_retries
+            );
+            emit!(BlockRetryScheduled {
+                strategy: accounts.strategy.key(),
+                block_id: block.id.clone(),
+                failures,
+                max_retries,
+            });
+            state.terminal = true;
+            return Ok(None);
+        }
+
+        let fallback = block.config.fallback_block.ok_or(error)? as usize;
+        state.clear_failures(index as u16);
+        msg!("Block {} out of retries, falling back to {}", block.id, blocks[fallback].id);
+        emit!(BlockFallbackTriggered {
+            strategy: accounts.strategy.key(),
+            block_id: block.id.clone(),
+            fallback_block_id: blocks[fallback].id.clone(),
+        });
+        Ok(Some(fallback))
+    }
+
+    // Execute strategy with block sequence; a crank landing after `deadline` runs nothing. Swaps
+    // take `venue_calls` in order, their accounts from the remaining accounts.
+    pub fn execute_strategy_blocks<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteStrategy<'info>>,
         blocks: Vec<StrategyBlock>,
+        venue_calls: Vec<VenueCall>,
+        deadline: i64,
     ) -> Result<()> {
-        let strategy = &mut ctx.accounts.strategy;
-        require!(strategy.is_active, TradingBotError::StrategyInactive);
+        validate_deadline_in(deadline, &ctx.accounts.strategy.config.time_basis)?;
+        require!(ctx.accounts.strategy.is_active, TradingBotError::StrategyInactive);
+        ctx.accounts.upgrade_gate.require_healthy()?;
+        // accounts are only serialized on exit, so the lock is written out now where a nested
+        // execution would read it
+        ctx.accounts.strategy.lock_execution()?;
+        ctx.accounts.strategy.exit(&crate::ID)?;
+
+        // feed the volatility halt before any block can trade
+        if ctx.accounts.price_feed.key() == ctx.accounts.strategy.volatility_halt.price_feed {
+            PollVolatilityHalt::observe(
+                &mut ctx.accounts.strategy,
+                &ctx.accounts.feed_registry,
+                &ctx.accounts.price_feed,
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
+        Self::validate_loops(&blocks)?;
+        let swap_amounts = Self::swap_amounts(&blocks);
+        let largest_swap = swap_amounts.iter().copied().max().unwrap_or(0);
+        Self::authorize_execution(ctx.accounts, largest_swap, &swap_amounts)?;
+        Self::check_large_trade_approval(ctx.accounts, largest_swap)?;
+        Self::validate_fallbacks(&blocks)?;
 
         // Track block execution state
         let mut execution_state = ExecutionState::new();
+        execution_state.deadline = deadline;
+        execution_state.venue_calls = VenueCalls::new(venue_calls);
+        execution_state.volume_remaining = ctx
+            .accounts
+            .strategy
+            .remaining_daily_volume(ctx.accounts.strategy.clock_now()?);
+        execution_state.retry_counts = ctx.accounts.strategy.block_retries.counts();
         
-        // Execute blocks in sequence
-        for block in blocks {
-            match block.block_type {
-                BlockType::Trigger => {
-                    Self::execute_trigger(ctx.accounts.clone(), &block, &mut execution_state)?;
-                },
-                BlockType::Action => {
-                    Self::execute_action(ctx.accounts.clone(), &block, &mut execution_state)?;
-                },
-                BlockType::Condition => {
-                    Self::execute_condition(ctx.accounts.clone(), &block, &mut execution_state)?;
-                },
-                BlockType::Loop => {
-                    Self::execute_loop(ctx.accounts.clone(), &block, &mut execution_state)?;
-                },
+        // Execute blocks in sequence; failures carry the block and stage they came from
+        for (index, block) in blocks.iter().enumerate() {
+            if execution_state.terminal {
+                break;
+            }
+            execution_state.failed_param = None;
+
+            let checkpoint = execution_state.volume_checkpoint();
+            let (mut stage, mut result) = match block.block_type {
                 BlockType::Exit => {
-                    if Self::should_exit(&block, &execution_state)? {
-                        break;
+                    match Self::should_exit(ctx.accounts, block, &mut execution_state) {
+                        Ok(true) => break,
+                        Ok(false) => continue,
+                        Err(e) => (ExecutionStage::Exit, Err(e)),
                     }
                 }
-            }
+                _ => Self::execute_block(
+                    ctx.accounts,
+                    ctx.remaining_accounts,
+                    &blocks,
+                    index,
+                    &mut execution_state,
+                ),
+            };
+
+            // errors are reported against the fallback once it has taken the block's place
+            let (mut ran_index, mut ran_block) = (index, block);
+            result = match result {
+                Ok(()) => {
+                    execution_state.clear_failures(index as u16);
+                    Ok(())
+                }
+                Err(e) => {
+                    execution_state.restore_volume(checkpoint);
+                    let recovery =
+                        Self::recover_block(ctx.accounts, &blocks, index, e, &mut execution_state);
+                    match recovery {
+                        Ok(Some(fallback)) => {
+                            (ran_index, ran_block) = (fallback, &blocks[fallback]);
+                            execution_state.failed_param = None;
+                            let (fallback_stage, fallback_result) = Self::execute_block(
+                                ctx.accounts,
+                                ctx.remaining_accounts,
+                                &blocks,
+                                fallback,
+                                &mut execution_state,
+                            );
+                            stage = fallback_stage;
+                            fallback_result
+                        }
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+            result.map_err(|e| {
+                ErrorContext::new(ran_index as u16, &ran_block.id, stage)
+                    .with_param(execution_state.failed_param.as_deref())
+                    .attach(e)
+            })?;
         }
 
         // Update strategy metrics
-        strategy.update_metrics(&execution_state)?;
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(keeper_record) = ctx.accounts.keeper_record.as_mut() {
+            if ctx.accounts.authority.key() != ctx.accounts.strategy.owner {
+                keeper_record.record_execution(ctx.accounts.strategy.execution_delay(now))?;
+                for violation in execution_state.keeper_violations.drain(..) {
+                    keeper_record.record_violation(violation)?;
+                    emit!(KeeperViolationRecorded {
+                        keeper: keeper_record.keeper,
+                        strategy: violation.strategy,
+                        minimum_out: violation.minimum_out,
+                        oracle_min_out: violation.oracle_min_out,
+                        slot: violation.slot,
+                    });
+                }
+            }
+        }
+        let strategy = &mut ctx.accounts.strategy;
+        strategy.block_retries.store(&execution_state.retry_counts)?;
+        let strategy_now = strategy.clock_now()?;
+        strategy.record_volume(execution_state.volume_traded, strategy_now)?;
+        strategy.record_execution(execution_state.trade_results.len() as u64, now)?;
+        strategy.unlock_execution();
+        let (owner, strategy_key) = (strategy.owner, strategy.key());
+        for trade in execution_state.trade_results.iter() {
+            UserEpochVolume::record_fill(
+                ctx.accounts.mining_epoch.as_deref_mut(),
+                ctx.accounts.user_volume.as_deref_mut(),
+                owner,
+                strategy_key,
+                [
+                    (trade.input_mint, trade.amount_in),
+                    (trade.output_mint, trade.amount_out),
+                ],
+                0,
+            )?;
+        }
+
+        if let (Some(stop), Some(position_stop)) = (
+            execution_state.position_stop.take(),
+            ctx.accounts.position_stop.as_mut(),
+        ) {
+            let previous_stop = position_stop.stop_price;
+            **position_stop = PositionStop {
+                updated_at: Clock::get()?.unix_timestamp,
+                ..stop
+            };
+            if position_stop.stop_price != previous_stop {
+                emit!(StopMoved {
+                    strategy: position_stop.strategy,
+                    position_id: position_stop.position_id,
+                    previous_stop,
+                    stop_price: position_stop.stop_price,
+                    at_breakeven: position_stop.at_breakeven,
+                    timestamp: position_stop.updated_at,
+                });
+            }
+        }
 
         Ok(())
     }
+
+    // Cheap pre-flight for keepers polling many strategies: evaluates the price triggers leading
+    // the sequence, the ones execute_strategy checks before anything runs, and fails with
+    // NotTriggered while any is unmet, so the executor is only sent once it would get past them.
+    // Reads one price at most and writes nothing.
+    pub fn check_triggers(ctx: Context<CheckTriggers>, blocks: Vec<StrategyBlock>) -> Result<()> {
+        let strategy = &ctx.accounts.strategy;
+        require!(strategy.is_active && !strategy.executing, TradingBotError::NotTriggered);
+
+        let mut price = None;
+        for block in blocks
+            .iter()
+            .take_while(|block| matches!(block.block_type, BlockType::Trigger))
+            .filter(|block| matches!(block.trigger_type, TriggerType::Price))
+        {
+            let current_price = match price {
+                Some(price) => price,
+                None => *price.insert(Self::signed_price(Self::base_price(
+                    &ctx.accounts.feed_registry,
+                    &strategy.config.pair,
+                    &ctx.accounts.price_feed,
+                    ctx.accounts.stake_pool.as_deref(),
+                )?)?),
+            };
+            let threshold = block
+                .config
+                .price_threshold
+                .ok_or(TradingBotError::MissingBlockParameter)?;
+            let condition_type = block
+                .config
+                .condition_type
+                .clone()
+                .ok_or(TradingBotError::MissingBlockParameter)?;
+            require!(
+                Self::price_condition_met(current_price, threshold, condition_type),
+                TradingBotError::NotTriggered
+            );
+        }
+
+        Ok(())
+    }
+
+    // Walks the blocks against `price` instead of the oracle, evaluating triggers, conditions and
+    // exits and reporting where the sequence would stop. Actions and loops are listed but never
+    // run, and nothing is written back to the strategy. Anchor can't leave an instruction out of
+    // a build, so only a `what-if` build lets it run.
+    pub fn dry_run_strategy_blocks(
+        ctx: Context<ExecuteStrategy>,
+        blocks: Vec<StrategyBlock>,
+        price: i64,
+    ) -> Result<DryRunReport> {
+        require!(cfg!(feature = "what-if"), TradingBotError::FeatureDisabled);
+        let mut execution_state = ExecutionState::new();
+        execution_state.price_override = Some(price);
+        execution_state.volume_remaining = ctx
+            .accounts
+            .strategy
+            .remaining_daily_volume(ctx.accounts.strategy.clock_now()?);
+
+        let mut report = DryRunReport {
+            price,
+            evaluated_blocks: 0,
+            halted_at: None,
+            would_run: Vec::new(),
+        };
+        for (index, block) in blocks.iter().enumerate() {
+            let (stage, result) = match block.block_type {
+                BlockType::Exit => {
+                    match Self::should_exit(ctx.accounts, block, &mut execution_state) {
+                        Ok(true) => break,
+                        Ok(false) => continue,
+                        Err(e) => (ExecutionStage::Exit, Err(e)),
+                    }
+                }
+                BlockType::Action | BlockType::Loop => {
+                    report.would_run.push(block.id.clone());
+                    continue;
+                }
+                _ => Self::execute_block(ctx.accounts, &[], &blocks, index, &mut execution_state),
+            };
+            report.evaluated_blocks += 1;
+            if let Err(e) = result {
+                msg!("Dry run halted at block {}: {}", block.id, e);
+                report.halted_at = Some(
+                    ErrorContext::new(index as u16, &block.id, stage)
+                        .with_param(execution_state.failed_param.as_deref())
+                        .with_error(&e),
+                );
+                break;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+// What a dry run at an injected price would have done
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DryRunReport {
+    pub price: i64,
+    // trigger, condition and exit blocks evaluated before stopping
+    pub evaluated_blocks: u16,
+    // the block whose trigger or condition failed, if any
+    pub halted_at: Option<ErrorContext>,
+    // ids of the actions and loops reached, in order
+    pub would_run: Vec<String>,
+}
+
+// What a swap spent and received, read off the token accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct TradeResult {
+    pub dex: DexType,
+    pub input_mint: Pubkey,
+    pub amount_in: u64,
+    pub output_mint: Pubkey,
+    pub amount_out: u64,
 }
 
 // Add execution state tracking
@@ -228,6 +1259,31 @@ pub struct ExecutionState {
     pub loop_counters: HashMap<String, u64>,
     pub last_prices: HashMap<String, u64>,
     pub trade_results: Vec<TradeResult>,
+    pub terminal: bool,
+    pub failed_param: Option<String>,
+    // what the daily volume limit still allows, None without a limit; both in the limit's unit
+    pub volume_remaining: Option<u64>,
+    pub volume_traded: u64,
+    // converts the current swap's size into USD while the limits are in USD
+    pub volume_rate: Option<NotionalRate>,
+    // failures of retrying blocks so far, loaded from and stored back to the strategy
+    pub retry_counts: BTreeMap<u16, u8>,
+    // the crank's deadline, carried into the events it emits
+    pub deadline: i64,
+    // price triggers read this instead of the oracle when set, only by dry runs
+    pub price_override: Option<i64>,
+    // the position stop as moved by breakeven blocks, written back after the last block
+    pub position_stop: Option<PositionStop>,
+    // keeper swaps under the oracle floor, written to the keeper's record after the last block
+    pub keeper_violations: Vec<KeeperViolation>,
+    // the crank's venue calls, taken by swaps in order
+    pub venue_calls: VenueCalls,
+}
+
+impl Default for ExecutionState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ExecutionState {
@@ -237,6 +1293,88 @@ impl ExecutionState {
             loop_counters: HashMap::new(),
             last_prices: HashMap::new(),
             trade_results: Vec::new(),
+            terminal: false,
+            failed_param: None,
+            volume_remaining: None,
+            volume_traded: 0,
+            volume_rate: None,
+            retry_counts: BTreeMap::new(),
+            deadline: 0,
+            price_override: None,
+            position_stop: None,
+            keeper_violations: Vec::new(),
+            venue_calls: VenueCalls::default(),
+        }
+    }
+
+    pub fn volume_checkpoint(&self) -> (Option<u64>, u64) {
+        (self.volume_remaining, self.volume_traded)
+    }
+
+    // Rolls back volume taken by a block that failed without trading
+    pub fn restore_volume(&mut self, checkpoint: (Option<u64>, u64)) {
+        (self.volume_remaining, self.volume_traded) = checkpoint;
+    }
+
+    pub fn record_failure(&mut self, block_index: u16) -> Result<u8> {
+        let failures = self.retry_counts.entry(block_index).or_insert(0);
+        *failures = math::checked_add(*failures, 1)?;
+        Ok(*failures)
+    }
+
+    pub fn clear_failures(&mut self, block_index: u16) {
+        self.retry_counts.remove(&block_index);
+    }
+
+    fn volume_units(&self, amount: u64) -> Result<u64> {
+        match self.volume_rate {
+            Some(rate) => rate.to_notional(amount),
+            None => Ok(amount),
+        }
+    }
+
+    // Down-sizes a swap to what is left of the daily volume limit; fails once nothing is left
+    pub fn take_volume(&mut self, amount: u64) -> Result<u64> {
+        let volume = self.volume_units(amount)?;
+        let (amount, volume) = match self.volume_remaining.as_mut() {
+            Some(remaining) if volume > *remaining => {
+                let take = *remaining;
+                let amount = match self.volume_rate {
+                    Some(rate) => rate.to_amount(take)?,
+                    None => take,
+                };
+                require_gt!(amount, 0, TradingBotError::DailyVolumeExceeded);
+                *remaining = 0;
+                (amount, take)
+            }
+            Some(remaining) => {
+                require_gt!(amount, 0, TradingBotError::DailyVolumeExceeded);
+                *remaining -= volume;
+                (amount, volume)
+            }
+            None => (amount, volume),
+        };
+        self.volume_traded = math::checked_add(self.volume_traded, volume)?;
+        Ok(amount)
+    }
+
+    // Gives back volume taken for a swap that was deferred instead
+    pub fn return_volume(&mut self, amount: u64) {
+        let volume = self.volume_units(amount).unwrap_or(amount);
+        if let Some(remaining) = self.volume_remaining.as_mut() {
+            *remaining = remaining.saturating_add(volume);
+        }
+        self.volume_traded = self.volume_traded.saturating_sub(volume);
+    }
+
+    // Unwraps a block parameter, remembering its name for the error context when it is missing
+    pub fn param<T>(&mut self, value: Option<T>, name: &str) -> Result<T> {
+        match value {
+            Some(value) => Ok(value),
+            None => {
+                self.failed_param = Some(name.to_string());
+                Err(TradingBotError::MissingBlockParameter.into())
+            }
         }
     }
 
@@ -248,24 +1386,140 @@ impl ExecutionState {
 
 // Account structures
 #[derive(Accounts)]
-pub struct InitializeBot<'info> {
-    #[account(init, payer = owner, space = Strategy::LEN)]
+pub struct ExecuteStrategy<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+        bump = strategy.bump,
+    )]
     pub strategy: Account<'info, Strategy>,
+    // held by the strategy PDA, which signs the swaps out of it
+    #[account(
+        mut,
+        constraint = token_account.owner == strategy.key() @ TradingBotError::TradeSideMismatch,
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    // where swaps pay out, required for them
+    #[account(
+        mut,
+        constraint = output_account.owner == token_account.owner @ TradingBotError::TradeSideMismatch,
+    )]
+    pub output_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Resolved through the feed registry on read
+    pub price_feed: AccountInfo<'info>,
+    #[account(seeds = [FEED_REGISTRY_SEED], bump = feed_registry.bump)]
+    pub feed_registry: Box<Account<'info, FeedRegistry>>,
+    #[account(
+        seeds = [CORRELATION_MATRIX_SEED, strategy.owner.as_ref()],
+        bump = correlation_matrix.bump,
+    )]
+    pub correlation_matrix: Option<Account<'info, CorrelationMatrix>>,
+    // the primary venue's reserve of the input token, for depth-based sizing
+    pub pool_reserve_in: Option<Account<'info, TokenAccount>>,
+    // the same pool's other reserve, for the LST peg trigger
+    pub pool_reserve_out: Option<Account<'info, TokenAccount>>,
+    // required with pool_reserve_in, which has to belong to the pair's registered pool
+    #[account(seeds = [POOL_REGISTRY_SEED], bump = pool_registry.bump)]
+    pub pool_registry: Option<Box<Account<'info, PoolRegistry>>>,
+    #[account(
+        seeds = [EXECUTION_METRICS_SEED, strategy.owner.as_ref()],
+        bump = execution_metrics.bump,
+    )]
+    pub execution_metrics: Option<Account<'info, ExecutionMetrics>>,
+    // either one clears swaps above the strategy's large-trade threshold
+    pub co_signer: Option<Signer<'info>>,
+    #[account(
+        mut,
+        has_one = strategy,
+        seeds = [TRADE_APPROVAL_SEED, strategy.key().as_ref()],
+        bump = trade_approval.bump,
+    )]
+    pub trade_approval: Option<Account<'info, TradeApproval>>,
+    pub token_program: Program<'info, Token>,
+    // the owner, or the key of one of its live sessions
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = strategy,
+        seeds = [SESSION_SEED, strategy.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump,
+    )]
+    pub session: Option<Account<'info, Session>>,
+    // the owner's tracked positions, reduced by scaled exits
+    #[account(mut, has_one = owner)]
+    pub user_positions: Option<AccountLoader<'info, UserPositions>>,
+    #[account(
+        mut,
+        has_one = strategy,
+        seeds = [POSITION_STOP_SEED, strategy.key().as_ref(), position_stop.position_id.as_ref()],
+        bump = position_stop.bump,
+    )]
+    pub position_stop: Option<Account<'info, PositionStop>>,
+    /// CHECK: USD feed of the pair's quote, for USD limits or a non-quote accounting currency;
+    /// checked against the configured feed when read
+    pub quote_usd_feed: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        has_one = strategy,
+        seeds = [EXECUTION_AUCTION_SEED, strategy.key().as_ref()],
+        bump = execution_auction.bump,
+    )]
+    pub execution_auction: Option<Account<'info, ExecutionAuction>>,
+    // the executing keeper's record, required while the keeper allowlist is enforced
+    #[account(
+        mut,
+        seeds = [KEEPER_SEED, authority.key().as_ref()],
+        bump = keeper_record.bump,
+    )]
+    pub keeper_record: Option<Account<'info, KeeperRecord>>,
+    /// CHECK: USD feed of the accounting currency
+    #[account(address = strategy.accounting.mint_usd_feed)]
+    pub accounting_usd_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: Stake pool of an LST base, checked against the feed registry when read
+    pub stake_pool: Option<UncheckedAccount<'info>>,
+    /// CHECK: Solend or Kamino reserve a lending rate trigger reads, checked against the block
+    pub lending_reserve: Option<UncheckedAccount<'info>>,
+    /// CHECK: The reserve a lending rate trigger compares against, checked against the block
+    pub compare_reserve: Option<UncheckedAccount<'info>>,
+    /// CHECK: Checked against the strategy, receives the rent of a consumed trade approval
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: The instructions sysvar, required for anyone but the owner to execute
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    // the owner's trade-mining accounts for the current epoch, to have the swaps counted
     #[account(mut)]
-    pub owner: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub mining_epoch: Option<Box<Account<'info, TradeMiningEpoch>>>,
+    #[account(mut)]
+    pub user_volume: Option<Box<Account<'info, UserEpochVolume>>>,
+    pub upgrade_gate: UpgradeGate<'info>,
 }
 
+// Just what check_triggers reads, so polling stays cheap
 #[derive(Accounts)]
-pub struct ExecuteStrategy<'info> {
-    #[account(mut)]
+pub struct CheckTriggers<'info> {
+    #[account(
+        seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+        bump = strategy.bump,
+    )]
     pub strategy: Account<'info, Strategy>,
-    #[account(mut)]
-    pub token_account: Account<'info, TokenAccount>,
-    /// CHECK: Verified in program
+    /// CHECK: Resolved through the feed registry on read
     pub price_feed: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
-    pub owner: Signer<'info>,
+    #[account(seeds = [FEED_REGISTRY_SEED], bump = feed_registry.bump)]
+    pub feed_registry: Box<Account<'info, FeedRegistry>>,
+    /// CHECK: Stake pool of an LST base, checked against the feed registry when read
+    pub stake_pool: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StrategyBlock {
+    pub id: String,
+    pub block_type: BlockType,
+    // only read for trigger and condition blocks respectively
+    pub trigger_type: TriggerType,
+    pub condition_type: ConditionType,
+    pub config: BlockConfig,
 }
 
 // Strategy block types
@@ -283,6 +1537,10 @@ pub enum TriggerType {
     Price,
     Volume,
     Time,
+    // the pair's LST base trading off its stake pool's exchange rate
+    PegDeviation,
+    // a lending reserve's utilization or rates against a threshold
+    LendingRate,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -290,6 +1548,15 @@ pub enum ActionType {
     Swap,
     LiquidityProvision,
     Stake,
+    BreakevenStop,
+}
+
+impl ActionType {
+    pub const COUNT: usize = 4;
+
+    pub fn index(&self) -> usize {
+        self.clone() as usize
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -299,16 +1566,99 @@ pub enum ConditionType {
     Custom,
 }
 
+// Field docs end up in the IDL, where `anchor run catalog` reads the `Required by:` and `Used by:`
+// tags to build the block catalog for the frontend builder. Keep them in step with the `param`
+// calls in the executors.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct BlockConfig {
     pub amount: Option<u64>,
+    /// Minimum output for the block's full amount, scaled down when a swap is down-sized
+    /// Used by: Action.Swap
     pub minimum_out: Option<u64>,
     pub slippage_bps: Option<u16>,
+    /// Oracle price the trigger compares against; an exit with one ends the sequence once the
+    /// price meets it
+    /// Required by: Trigger.Price
+    /// Used by: Exit
     pub price_threshold: Option<i64>,
+    /// Whether the price, or the lending rate, must be above, below or near the threshold
+    /// Required by: Trigger.Price, Trigger.LendingRate
+    /// Used by: Exit
     pub condition_type: Option<PriceConditionType>,
+    /// Gap between the LST's pool price and its stake pool's exchange rate, in bps, at which
+    /// the trigger fires; the pool is the registered one on `dex_type`
+    /// Required by: Trigger.PegDeviation
+    pub peg_deviation_bps: Option<u16>,
+    /// Solend or Kamino reserve whose rates the trigger reads
+    /// Required by: Trigger.LendingRate
+    pub lending_reserve: Option<Pubkey>,
+    /// Utilization, borrow APR or deposit APR; Kamino reserves only report utilization
+    /// Required by: Trigger.LendingRate
+    pub lending_metric: Option<LendingMetric>,
+    /// Value of the metric, in bps, the trigger compares against
+    /// Required by: Trigger.LendingRate
+    pub rate_threshold_bps: Option<i64>,
+    /// Reserve whose metric is subtracted before comparing, to act on the spread between two
+    /// Used by: Trigger.LendingRate
+    pub compare_reserve: Option<Pubkey>,
+    /// Required by: Condition.Balance
     pub minimum_balance: Option<u64>,
+    /// Largest price impact in bps; swaps are sized to the pool's depth to stay within it
+    /// Required by: Condition.PriceImpact
+    /// Used by: Action.Swap
     pub max_price_impact: Option<u16>,
-    pub side: Option<Side>,
+    /// Required by: Action.Swap
+    pub side: Option<TradeSide>,
+    /// Worst oracle price a swap may trade at: a ceiling for buys, a floor for sells. Like every
+    /// pair price here, native quote per native base in PRICE_PRECISION
+    /// Used by: Action.Swap
+    pub limit_price: Option<u64>,
+    /// Required by: Action
+    pub action_type: Option<ActionType>,
+    /// Profit over the position's entry, in bps, at which its stop moves to breakeven
+    /// Required by: Action.BreakevenStop
+    pub breakeven_trigger_bps: Option<u16>,
+    /// Fees in bps the breakeven stop sits past the entry to still cover; zero if unset
+    /// Used by: Action.BreakevenStop
+    pub breakeven_fee_bps: Option<u16>,
+    pub parameters: ActionParameters,
+    /// Failures tolerated across cranks before the fallback runs; unset fails at once
+    /// Used by: Trigger, Condition, Action, Loop
+    pub max_retries: Option<u8>,
+    /// Index of the block run in place of this one once its retries are used up
+    /// Used by: Trigger, Condition, Action, Loop
+    pub fallback_block: Option<u16>,
+    /// Times the swap right before the loop is run again, at most MAX_LOOP_COUNT
+    /// Required by: Loop
+    pub loop_count: Option<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ActionParameters {
+    /// Required by: Action.Swap, Trigger.PegDeviation, Condition.PriceImpact
+    pub dex_type: Option<DexType>,
+    /// Takes whatever the primary venue can't absorb within max_price_impact; deferred if unset
+    /// Used by: Action.Swap
+    pub fallback_dex: Option<DexType>,
+    /// Required by: Action.Swap, Condition.PriceImpact
+    pub amount: Option<u64>,
+    /// Required by: Action.Swap
+    pub token_address: Option<Pubkey>,
+    /// Required by: Action.Swap
+    pub slippage_bps: Option<u16>,
+    /// Sizes the swap as a share of a tracked position's opening size, capped at `amount` and at
+    /// what is still open; the position is reduced by what is swapped
+    /// Used by: Action.Swap
+    pub scaled_exit: Option<ScaledExit>,
+}
+
+// One tranche of a scaled exit, e.g. 5000 bps at a first target and 5000 at a second. The
+// position's size is in the swap's input token.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ScaledExit {
+    pub dex: DexType,
+    pub position_id: Pubkey,
+    pub pct_bps: u16,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -0,0 +1,330 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use crate::errors::TradingBotError;
+use crate::math;
+
+// Official Wormhole program IDs (mainnet)
+pub const WORMHOLE_CORE_PROGRAM_ID: &str = "worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth";
+pub const WORMHOLE_TOKEN_BRIDGE_PROGRAM_ID: &str = "wormDTUJ6AWPNvk59vGQbDvGJmqbDTdgWgAqcLBCgUb";
+
+pub const WORMHOLE_CHAIN_ID_SOLANA: u16 = 1;
+
+// Token bridge instruction indices
+const COMPLETE_NATIVE_IX: u8 = 2;
+const COMPLETE_WRAPPED_IX: u8 = 3;
+const TRANSFER_WRAPPED_IX: u8 = 4;
+const TRANSFER_NATIVE_IX: u8 = 5;
+
+// Position of the posted message in TransferNative/TransferWrapped's accounts, and of the posted
+// VAA in CompleteNative/CompleteWrapped's
+pub const TRANSFER_MESSAGE_ACCOUNT: usize = 7;
+pub const COMPLETE_VAA_ACCOUNT: usize = 2;
+
+// Core bridge accounts share one layout after their magic: a message the core posted for an
+// emitter on this chain, or a VAA whose guardian signatures it has verified
+const POSTED_MESSAGE_MAGIC: &[u8] = b"msg";
+const POSTED_VAA_MAGIC: &[u8] = b"vaa";
+const MESSAGE_SEQUENCE: usize = 49;
+const MESSAGE_EMITTER_CHAIN: usize = 57;
+const MESSAGE_EMITTER_ADDRESS: usize = 59;
+const MESSAGE_PAYLOAD: usize = 91;
+
+// Token bridge Transfer payload, big-endian: id, amount, token address and chain, recipient and
+// its chain, fee
+const PAYLOAD_ID_TRANSFER: u8 = 1;
+const TRANSFER_PAYLOAD_LEN: usize = 133;
+
+// The token bridge carries amounts with at most 8 decimals
+const MAX_BRIDGE_DECIMALS: u8 = 8;
+
+// What a core bridge message or VAA says about a token bridge transfer
+pub struct BridgeMessage {
+    pub sequence: u64,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub amount: u64,
+    pub token_address: [u8; 32],
+    pub token_chain: u16,
+    pub to: [u8; 32],
+    pub to_chain: u16,
+    pub fee: u64,
+}
+
+pub struct WormholeBridge;
+
+impl WormholeBridge {
+    pub fn token_bridge_program_id() -> Pubkey {
+        WORMHOLE_TOKEN_BRIDGE_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    pub fn core_program_id() -> Pubkey {
+        WORMHOLE_CORE_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    // The token bridge's emitter, which every transfer message from this chain comes from
+    pub fn token_bridge_emitter() -> Pubkey {
+        Pubkey::find_program_address(&[b"emitter"], &Self::token_bridge_program_id()).0
+    }
+
+    // The core bridge's sequence tracker for the token bridge emitter
+    pub fn token_bridge_sequence() -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"Sequence", Self::token_bridge_emitter().as_ref()],
+            &Self::core_program_id(),
+        )
+        .0
+    }
+
+    // The mint a token goes by here: its own address if it's native to this chain, otherwise the
+    // token bridge's wrapped mint for its origin chain and address
+    pub fn origin_mint(token_chain: u16, token_address: [u8; 32]) -> Pubkey {
+        if token_chain == WORMHOLE_CHAIN_ID_SOLANA {
+            Pubkey::new_from_array(token_address)
+        } else {
+            Pubkey::find_program_address(
+                &[b"wrapped", &token_chain.to_be_bytes(), &token_address],
+                &Self::token_bridge_program_id(),
+            )
+            .0
+        }
+    }
+
+    // `amount` as the token bridge carries it, truncated to 8 decimals
+    pub fn normalize(amount: u64, decimals: u8) -> u64 {
+        if decimals > MAX_BRIDGE_DECIMALS {
+            amount / 10u64.pow((decimals - MAX_BRIDGE_DECIMALS) as u32)
+        } else {
+            amount
+        }
+    }
+
+    pub fn denormalize(amount: u64, decimals: u8) -> Result<u64> {
+        if decimals > MAX_BRIDGE_DECIMALS {
+            math::checked_mul(amount, 10u64.pow((decimals - MAX_BRIDGE_DECIMALS) as u32))
+        } else {
+            Ok(amount)
+        }
+    }
+
+    // A transfer message the core bridge posted on this chain
+    pub fn read_posted_message(message: &AccountInfo) -> Result<BridgeMessage> {
+        Self::read_message(message, POSTED_MESSAGE_MAGIC)
+    }
+
+    // A transfer VAA the core bridge has verified the guardian signatures of
+    pub fn read_posted_vaa(vaa: &AccountInfo) -> Result<BridgeMessage> {
+        Self::read_message(vaa, POSTED_VAA_MAGIC)
+    }
+
+    fn read_message(account: &AccountInfo, magic: &[u8]) -> Result<BridgeMessage> {
+        require_keys_eq!(
+            *account.owner,
+            Self::core_program_id(),
+            TradingBotError::InvalidWormholeMessage
+        );
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() >= MESSAGE_PAYLOAD + 4 && data[..3] == *magic,
+            TradingBotError::InvalidWormholeMessage
+        );
+        let payload_len = u32::from_le_bytes(
+            data[MESSAGE_PAYLOAD..MESSAGE_PAYLOAD + 4].try_into().unwrap(),
+        ) as usize;
+        let payload = data
+            .get(MESSAGE_PAYLOAD + 4..MESSAGE_PAYLOAD + 4 + payload_len)
+            .ok_or(TradingBotError::InvalidWormholeMessage)?;
+        require!(
+            payload.len() == TRANSFER_PAYLOAD_LEN && payload[0] == PAYLOAD_ID_TRANSFER,
+            TradingBotError::InvalidWormholeMessage
+        );
+        Ok(BridgeMessage {
+            sequence: u64::from_le_bytes(
+                data[MESSAGE_SEQUENCE..MESSAGE_SEQUENCE + 8].try_into().unwrap(),
+            ),
+            emitter_chain: u16::from_le_bytes(
+                data[MESSAGE_EMITTER_CHAIN..MESSAGE_EMITTER_CHAIN + 2].try_into().unwrap(),
+            ),
+            emitter_address: data[MESSAGE_EMITTER_ADDRESS..MESSAGE_EMITTER_ADDRESS + 32]
+                .try_into()
+                .unwrap(),
+            amount: read_u256_be(&payload[1..33])?,
+            token_address: payload[33..65].try_into().unwrap(),
+            token_chain: u16::from_be_bytes(payload[65..67].try_into().unwrap()),
+            to: payload[67..99].try_into().unwrap(),
+            to_chain: u16::from_be_bytes(payload[99..101].try_into().unwrap()),
+            fee: read_u256_be(&payload[101..133])?,
+        })
+    }
+
+    // The account at `index` of a token bridge CPI, which has to be the one verified
+    pub fn require_cpi_account(accounts: &[AccountInfo], index: usize, key: &Pubkey) -> Result<()> {
+        let account = accounts
+            .get(index)
+            .ok_or(TradingBotError::InvalidWormholeMessage)?;
+        require_keys_eq!(
+            account.key(),
+            *key,
+            TradingBotError::InvalidWormholeMessage
+        );
+        Ok(())
+    }
+
+    pub fn is_complete_transfer(data: &[u8]) -> bool {
+        matches!(data.first(), Some(&COMPLETE_NATIVE_IX) | Some(&COMPLETE_WRAPPED_IX))
+    }
+
+    pub fn validate_target_chain(target_chain: u16) -> Result<()> {
+        require!(
+            target_chain != 0 && target_chain != WORMHOLE_CHAIN_ID_SOLANA,
+            TradingBotError::UnsupportedBridge
+        );
+        Ok(())
+    }
+
+    // Borsh layout of the token bridge TransferNative/TransferWrapped payload
+    pub fn transfer_ix_data(
+        wrapped: bool,
+        nonce: u32,
+        amount: u64,
+        fee: u64,
+        target_address: [u8; 32],
+        target_chain: u16,
+    ) -> Vec<u8> {
+        let mut data = Vec::with_capacity(1 + 4 + 8 + 8 + 32 + 2);
+        data.push(if wrapped { TRANSFER_WRAPPED_IX } else { TRANSFER_NATIVE_IX });
+        data.extend_from_slice(&nonce.to_le_bytes());
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&fee.to_le_bytes());
+        data.extend_from_slice(&target_address);
+        data.extend_from_slice(&target_chain.to_le_bytes());
+        data
+    }
+
+    // Invoke the token bridge with the caller-ordered account list
+    pub fn invoke_token_bridge(
+        token_bridge_program: &AccountInfo,
+        accounts: &[AccountInfo],
+        data: Vec<u8>,
+    ) -> Result<()> {
+        require_keys_eq!(
+            token_bridge_program.key(),
+            Self::token_bridge_program_id(),
+            TradingBotError::UnsupportedBridge
+        );
+
+        let ix = Instruction {
+            program_id: token_bridge_program.key(),
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), a.is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), a.is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        invoke(&ix, accounts)?;
+
+        Ok(())
+    }
+
+    // The emitter sequence account stores the next sequence number as a little-endian u64
+    pub fn read_sequence(sequence_account: &AccountInfo) -> Result<u64> {
+        let data = sequence_account.try_borrow_data()?;
+        require!(data.len() >= 8, TradingBotError::InvalidCalculation);
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&data[..8]);
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+// Token bridge amounts are uint256 but always fit 8 decimals of a u64 supply
+fn read_u256_be(bytes: &[u8]) -> Result<u64> {
+    require!(
+        bytes[..24].iter().all(|b| *b == 0),
+        TradingBotError::InvalidWormholeMessage
+    );
+    Ok(u64::from_be_bytes(bytes[24..32].try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A verified VAA as the core bridge stores it, carrying a token bridge Transfer
+    fn posted_vaa(token_address: [u8; 32], token_chain: u16, to: [u8; 32]) -> Vec<u8> {
+        let mut data = vec![0u8; MESSAGE_PAYLOAD];
+        data[..3].copy_from_slice(POSTED_VAA_MAGIC);
+        data[MESSAGE_SEQUENCE..MESSAGE_SEQUENCE + 8].copy_from_slice(&42u64.to_le_bytes());
+        data[MESSAGE_EMITTER_CHAIN..MESSAGE_EMITTER_CHAIN + 2].copy_from_slice(&2u16.to_le_bytes());
+        data[MESSAGE_EMITTER_ADDRESS..MESSAGE_EMITTER_ADDRESS + 32].copy_from_slice(&[7; 32]);
+        data.extend_from_slice(&(TRANSFER_PAYLOAD_LEN as u32).to_le_bytes());
+        let mut payload = vec![0u8; TRANSFER_PAYLOAD_LEN];
+        payload[0] = PAYLOAD_ID_TRANSFER;
+        payload[25..33].copy_from_slice(&123_456_789u64.to_be_bytes());
+        payload[33..65].copy_from_slice(&token_address);
+        payload[65..67].copy_from_slice(&token_chain.to_be_bytes());
+        payload[67..99].copy_from_slice(&to);
+        payload[99..101].copy_from_slice(&WORMHOLE_CHAIN_ID_SOLANA.to_be_bytes());
+        payload[125..133].copy_from_slice(&1_000u64.to_be_bytes());
+        data.extend_from_slice(&payload);
+        data
+    }
+
+    #[test]
+    fn reads_transfer_vaa() {
+        let key = Pubkey::new_unique();
+        let owner = WormholeBridge::core_program_id();
+        let mut lamports = 0;
+        let mut data = posted_vaa([3; 32], 2, [9; 32]);
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+
+        let message = WormholeBridge::read_posted_vaa(&info).unwrap();
+        assert_eq!(message.sequence, 42);
+        assert_eq!(message.emitter_chain, 2);
+        assert_eq!(message.emitter_address, [7; 32]);
+        assert_eq!(message.amount, 123_456_789);
+        assert_eq!(message.token_address, [3; 32]);
+        assert_eq!(message.token_chain, 2);
+        assert_eq!(message.to, [9; 32]);
+        assert_eq!(message.to_chain, WORMHOLE_CHAIN_ID_SOLANA);
+        assert_eq!(message.fee, 1_000);
+
+        // a posted message isn't a verified VAA
+        assert!(WormholeBridge::read_posted_message(&info).is_err());
+    }
+
+    #[test]
+    fn rejects_vaa_not_owned_by_core_bridge() {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = posted_vaa([3; 32], 2, [9; 32]);
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert!(WormholeBridge::read_posted_vaa(&info).is_err());
+    }
+
+    #[test]
+    fn origin_mint_of_native_and_wrapped_tokens() {
+        let mint = Pubkey::new_unique();
+        assert_eq!(
+            WormholeBridge::origin_mint(WORMHOLE_CHAIN_ID_SOLANA, mint.to_bytes()),
+            mint
+        );
+        // a foreign token resolves to the token bridge's wrapped mint, distinct per origin chain
+        let wrapped = WormholeBridge::origin_mint(2, mint.to_bytes());
+        assert_ne!(wrapped, mint);
+        assert_ne!(wrapped, WormholeBridge::origin_mint(4, mint.to_bytes()));
+    }
+
+    #[test]
+    fn normalizes_to_eight_decimals() {
+        assert_eq!(WormholeBridge::normalize(1_234_567_891, 9), 123_456_789);
+        assert_eq!(WormholeBridge::denormalize(123_456_789, 9).unwrap(), 1_234_567_890);
+        assert_eq!(WormholeBridge::normalize(1_234_567, 6), 1_234_567);
+        assert_eq!(WormholeBridge::denormalize(1_234_567, 6).unwrap(), 1_234_567);
+    }
+}
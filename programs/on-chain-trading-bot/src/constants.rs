@@ -1,12 +1,111 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::pubkey;
+
 pub const PRICE_PRECISION: u64 = 1_000_000; // 6 decimals
+pub const PRICE_DECIMALS: u8 = 6;
+pub const USD_DECIMALS: u8 = 6;
 pub const MAX_SLIPPAGE_BPS: u16 = 1000; // 10%
 pub const MIN_TICK: i32 = -443636;
 pub const MAX_TICK: i32 = 443636;
 pub const TICK_SPACING: i32 = 1;
 pub const MAX_ROUTES: u8 = 5;
+pub const MAX_LOOP_COUNT: u8 = 8; // repeats of one swap per crank
 pub const MIN_LIQUIDITY: u64 = 1000;
 pub const MAX_DEADLINE: i64 = 3600; // 1 hour
 pub const STALE_PRICE_THRESHOLD: i64 = 60; // 60 seconds 
+pub const MAX_QUOTE_LOCK_SLOTS: u64 = 150; // ~1 minute
+pub const MAX_CANCEL_PENALTY_BPS: u16 = 2000; // 20%
+pub const MAX_CROSSING_FEE_BPS: u16 = 100; // 1%
+pub const MAX_FILL_ORACLE_DEVIATION_BPS: u16 = 300; // 3%
+pub const MIN_SNAPSHOT_INTERVAL: i64 = 3600; // 1 hour
+pub const SUBSCRIPTION_PERIOD: i64 = 30 * 24 * 3600; // 30 days
+pub const SUBSCRIPTION_PROTOCOL_FEE_BPS: u16 = 1000; // 10%
+pub const MAX_SESSION_DURATION: i64 = 24 * 3600; // 1 day
+pub const MIN_RECOVERY_TIMELOCK: i64 = 2 * 24 * 3600; // 2 days
+pub const WITHDRAWAL_ACTIVATION_DELAY: i64 = 24 * 3600; // 1 day
+pub const DEFAULT_DUST_THRESHOLD_USD: u64 = 1_000_000; // $1
+pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 3600;
+pub const MIN_KEEPER_BOND: u64 = 1_000_000_000; // 1 SOL
+pub const KEEPER_SLASH_BPS: u16 = 1000; // 10% of the bond per violation
+pub const KEEPER_UNBONDING_PERIOD: i64 = 7 * 24 * 3600; // 7 days
+pub const FORCED_EXIT_FLOOR_DISCOUNT_BPS: u16 = 500; // 5%
+pub const FORCED_EXIT_AUCTION_DURATION: i64 = 3600; // 1 hour
 
 pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const MARKET_MAKER_SEED: &[u8] = b"market-maker";
+pub const MARKET_MAKER_CLAIM_SEED: &[u8] = b"market-maker-claim";
+pub const BRIDGE_TRANSFER_SEED: &[u8] = b"bridge-transfer";
+pub const CONSUMED_SEQUENCE_SEED: &[u8] = b"consumed-sequence";
+pub const OTC_OFFER_SEED: &[u8] = b"otc-offer";
+pub const LIQUIDATION_AUCTION_SEED: &[u8] = b"liquidation-auction";
+pub const REWARDS_DISTRIBUTOR_SEED: &[u8] = b"rewards-distributor";
+pub const CLAIM_STATUS_SEED: &[u8] = b"claim-status";
+pub const TRADE_MINING_EPOCH_SEED: &[u8] = b"trade-mining-epoch";
+pub const USER_EPOCH_VOLUME_SEED: &[u8] = b"user-epoch-volume";
+pub const TRADE_HISTORY_SEED: &[u8] = b"trade-history";
+pub const LIMIT_ORDER_SEED: &[u8] = b"limit-order";
+pub const TRADING_VAULT_SEED: &[u8] = b"trading-vault";
+pub const QUOTE_LOCK_SEED: &[u8] = b"quote-lock";
+pub const OUTBOX_SEED: &[u8] = b"outbox";
+pub const STRATEGY_SEED: &[u8] = b"strategy";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const RECURRING_TRANSFER_SEED: &[u8] = b"recurring-transfer";
+pub const DEPEG_GUARD_SEED: &[u8] = b"depeg-guard";
+pub const REGIME_SWITCH_SEED: &[u8] = b"regime-switch";
+pub const CORRELATION_MATRIX_SEED: &[u8] = b"correlation-matrix";
+pub const PUBLIC_PROFILE_SEED: &[u8] = b"public-profile";
+pub const STRATEGY_REGISTRY_SEED: &[u8] = b"strategy-registry";
+pub const SUBSCRIPTION_PLAN_SEED: &[u8] = b"subscription-plan";
+pub const SUBSCRIPTION_SEED: &[u8] = b"subscription";
+pub const FILL_TRACKER_SEED: &[u8] = b"fill-tracker";
+pub const EXECUTION_METRICS_SEED: &[u8] = b"execution-metrics";
+pub const TRADE_APPROVAL_SEED: &[u8] = b"trade-approval";
+pub const SESSION_SEED: &[u8] = b"session";
+pub const RECOVERY_CONFIG_SEED: &[u8] = b"recovery-config";
+pub const PRICE_BAND_SEED: &[u8] = b"price-band";
+pub const POOL_SAMPLER_SEED: &[u8] = b"pool-sampler";
+pub const MIGRATION_RULE_SEED: &[u8] = b"migration-rule";
+pub const TOKEN_LAUNCH_SEED: &[u8] = b"token-launch";
+pub const TREASURY_PLAN_SEED: &[u8] = b"treasury-plan";
+pub const EXPIRY_CURSOR_SEED: &[u8] = b"expiry-cursor";
+pub const COMPUTE_PROFILE_SEED: &[u8] = b"compute-profile";
+pub const FEE_ORACLE_SEED: &[u8] = b"fee-oracle";
+pub const FAUCET_SEED: &[u8] = b"faucet";
+pub const MOCK_POOL_SEED: &[u8] = b"mock-pool";
+pub const MOCK_PRICE_FEED_SEED: &[u8] = b"mock-price-feed";
+pub const EPOCH_HISTORY_SEED: &[u8] = b"epoch-history";
+pub const PORTFOLIO_SEED: &[u8] = b"portfolio";
+pub const INTERNAL_ORDER_BOOK_SEED: &[u8] = b"internal-order-book";
+pub const POSITION_STOP_SEED: &[u8] = b"position-stop";
+pub const KEEPER_SEED: &[u8] = b"keeper";
+pub const EXECUTION_AUCTION_SEED: &[u8] = b"execution-auction";
+pub const CALLBACK_CONFIG_SEED: &[u8] = b"callback-config";
+pub const CALLBACK_REQUEST_SEED: &[u8] = b"callback-request";
+pub const FEED_REGISTRY_SEED: &[u8] = b"feed-registry";
+pub const POOL_REGISTRY_SEED: &[u8] = b"pool-registry";
+pub const PEG_ARB_SEED: &[u8] = b"peg-arb";
+pub const LEVERAGE_LOOP_SEED: &[u8] = b"leverage-loop";
+pub const COVERED_CALL_SEED: &[u8] = b"covered-call";
+pub const PRINCIPAL_NOTE_SEED: &[u8] = b"principal-note";
+pub const INDEX_FUND_SEED: &[u8] = b"index-fund";
+pub const NAV_FEED_SEED: &[u8] = b"nav-feed";
+pub const SHARE_MINT_SEED: &[u8] = b"share-mint";
+pub const INDEX_REDEMPTION_SEED: &[u8] = b"index-redemption";
+pub const INDEX_DEPOSITOR_SEED: &[u8] = b"index-depositor";
+pub const UPGRADE_GUARD_SEED: &[u8] = b"upgrade-guard";
+pub const VAULT_LOG_SEED: &[u8] = b"vault-log";
+
+// mints a strategy may keep its books in besides its pair's quote: USDC, USDT and wrapped SOL
+pub const ACCOUNTING_MINTS: [Pubkey; 3] = [
+    pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+    pubkey!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY9NRL8LZDgeMMrE"),
+    pubkey!("So11111111111111111111111111111111111111112"),
+];
+
+// the only program whose instructions may sit beside a guarded execution, see `introspection`
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+
+// keeps a batch of quotes inside the 1024 bytes of return data
+pub const MAX_BATCH_QUOTES: usize = 32;
 pub const AIRDROP_BPS: u64 = 500; // 5%
\ No newline at end of file
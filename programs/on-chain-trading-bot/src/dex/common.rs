@@ -113,12 +113,7 @@ impl DexUtils {
 
     // Validate deadline
     pub fn validate_deadline(deadline: i64) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp;
-        require!(
-            current_time <= deadline,
-            TradingBotError::DeadlineExceeded
-        );
-        Ok(())
+        crate::utils::validate_deadline(deadline)
     }
 
     // Calculate optimal swap amounts
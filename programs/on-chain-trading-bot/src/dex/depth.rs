@@ -0,0 +1,50 @@
+use crate::math;
+use anchor_lang::prelude::*;
+
+// Constant-product impact: selling `amount` into a pool holding `reserve_in` of the input token
+// moves the price by amount / (reserve_in + amount)
+pub fn price_impact_bps(amount: u64, reserve_in: u64) -> Result<u64> {
+    let depth = math::checked_add(reserve_in as u128, amount as u128)?;
+    if depth == 0 {
+        return Ok(0);
+    }
+    math::checked_as_u64(math::checked_div(
+        math::checked_mul(amount as u128, 10000)?,
+        depth,
+    )?)
+}
+
+// Constant-product output for `amount` in after the venue's fee, the x*y=k quote Raydium-style
+// pools fill at
+pub fn amount_out(amount: u64, reserve_in: u64, reserve_out: u64, fee_bps: u16) -> Result<u64> {
+    let amount_with_fee = math::checked_div(
+        math::checked_mul(amount as u128, 10000u128.saturating_sub(fee_bps as u128))?,
+        10000,
+    )?;
+    let depth = math::checked_add(reserve_in as u128, amount_with_fee)?;
+    if depth == 0 {
+        return Ok(0);
+    }
+    math::checked_as_u64(math::checked_div(
+        math::checked_mul(amount_with_fee, reserve_out as u128)?,
+        depth,
+    )?)
+}
+
+// Largest input that keeps price_impact_bps at or below `max_impact_bps`
+pub fn max_amount_for_impact(reserve_in: u64, max_impact_bps: u16) -> Result<u64> {
+    if max_impact_bps >= 10000 {
+        return Ok(u64::MAX);
+    }
+    let amount = math::checked_div(
+        math::checked_mul(reserve_in as u128, max_impact_bps as u128)?,
+        (10000 - max_impact_bps) as u128,
+    )?;
+    Ok(u64::try_from(amount).unwrap_or(u64::MAX))
+}
+
+// Splits `amount` into what the venue can absorb within the impact limit and the remainder
+pub fn split_for_depth(amount: u64, reserve_in: u64, max_impact_bps: u16) -> Result<(u64, u64)> {
+    let fill = amount.min(max_amount_for_impact(reserve_in, max_impact_bps)?);
+    Ok((fill, amount - fill))
+}
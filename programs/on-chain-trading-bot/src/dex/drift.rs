@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::TradingBotError;
+use crate::math;
+
+pub const DRIFT_V2_PROGRAM_ID: &str = "dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH";
+
+// Drift sizes perps with 9 decimals and quotes them in USDC with 6
+pub const DRIFT_BASE_PRECISION: u64 = 1_000_000_000;
+pub const DRIFT_QUOTE_PRECISION: u64 = 1_000_000;
+
+// Drift v2 User: discriminator, authority, delegate and name, then 8 spot positions of 40 bytes
+// ahead of 8 perp positions of 96
+const USER_AUTHORITY_OFFSET: usize = 8;
+const USER_PERP_POSITIONS_OFFSET: usize = 8 + 32 + 32 + 32 + 8 * 40;
+const PERP_POSITION_LEN: usize = 96;
+const PERP_POSITION_COUNT: usize = 8;
+const PERP_BASE_OFFSET: usize = 8;
+const PERP_QUOTE_OFFSET: usize = 16;
+const PERP_MARKET_INDEX_OFFSET: usize = 92;
+// Drift v2 UserStats: discriminator, authority and referrer, then the fee totals
+const USER_STATS_AUTHORITY_OFFSET: usize = 8;
+const USER_STATS_TOTAL_FEE_PAID_OFFSET: usize = 8 + 32 + 32;
+
+// Drift perp orders are opaque to us; the keeper builds the instruction and we only check the program
+pub fn drift_program_id() -> Pubkey {
+    DRIFT_V2_PROGRAM_ID.parse::<Pubkey>().unwrap()
+}
+
+// What Drift has booked on one perp market of a user: base in DRIFT_BASE_PRECISION, and the quote
+// paid or received for it, fees included, in DRIFT_QUOTE_PRECISION
+#[derive(Clone, Copy, Default)]
+pub struct DriftPerpPosition {
+    pub base_asset_amount: i64,
+    pub quote_asset_amount: i64,
+}
+
+impl DriftPerpPosition {
+    // `user` has to be a Drift user of `authority`; a market without a position reads as flat
+    pub fn read(user: &AccountInfo, authority: &Pubkey, market_index: u16) -> Result<Self> {
+        let data = Self::drift_data(user, authority, USER_AUTHORITY_OFFSET)?;
+        let end = USER_PERP_POSITIONS_OFFSET + PERP_POSITION_COUNT * PERP_POSITION_LEN;
+        let positions = data
+            .get(USER_PERP_POSITIONS_OFFSET..end)
+            .ok_or(TradingBotError::InvalidDriftAccount)?;
+        for position in positions.chunks(PERP_POSITION_LEN) {
+            let index = u16::from_le_bytes(read_bytes(position, PERP_MARKET_INDEX_OFFSET)?);
+            let entry = Self {
+                base_asset_amount: i64::from_le_bytes(read_bytes(position, PERP_BASE_OFFSET)?),
+                quote_asset_amount: i64::from_le_bytes(read_bytes(position, PERP_QUOTE_OFFSET)?),
+            };
+            let open = entry.base_asset_amount != 0 || entry.quote_asset_amount != 0;
+            if index == market_index && open {
+                return Ok(entry);
+            }
+        }
+        Ok(Self::default())
+    }
+
+    // Lifetime fees `authority` has paid on Drift, in DRIFT_QUOTE_PRECISION
+    pub fn total_fee_paid(user_stats: &AccountInfo, authority: &Pubkey) -> Result<u64> {
+        let data = Self::drift_data(user_stats, authority, USER_STATS_AUTHORITY_OFFSET)?;
+        Ok(u64::from_le_bytes(read_bytes(&data, USER_STATS_TOTAL_FEE_PAID_OFFSET)?))
+    }
+
+    fn drift_data<'a>(
+        account: &'a AccountInfo,
+        authority: &Pubkey,
+        authority_offset: usize,
+    ) -> Result<std::cell::Ref<'a, &'a mut [u8]>> {
+        require_keys_eq!(
+            *account.owner,
+            drift_program_id(),
+            TradingBotError::InvalidDriftAccount
+        );
+        let data = account.try_borrow_data()?;
+        let owner = Pubkey::new_from_array(read_bytes(&data, authority_offset)?);
+        require_keys_eq!(owner, *authority, TradingBotError::InvalidDriftAccount);
+        Ok(data)
+    }
+}
+
+// Converts between Drift's fixed precision and a mint's native units
+pub fn rescale(amount: u64, from_precision: u64, decimals: u8) -> Result<u64> {
+    math::checked_as_u64(math::checked_div(
+        math::checked_mul(amount as u128, 10u128.pow(decimals as u32))?,
+        from_precision as u128,
+    )?)
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| TradingBotError::InvalidDriftAccount.into())
+}
@@ -1,7 +1,16 @@
+pub mod depth;
+pub mod drift;
 pub mod jupiter;
+pub mod orca;
+pub mod psyoptions;
 pub mod raydium;
+pub mod serum;
+pub mod serum_events;
 
+use crate::errors::TradingBotError;
+use crate::types::DexType;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
 // Common DEX traits and types
 pub trait DexSwap {
     fn execute_swap(
@@ -15,3 +24,85 @@ pub trait DexSwap {
 pub trait SwapAccounts {
     fn validate(&self) -> Result<()>;
 }
+
+// A venue instruction built off-chain, forwarded over the next `accounts_len` of the
+// instruction's remaining accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VenueCall {
+    pub accounts_len: u8,
+    pub data: Vec<u8>,
+}
+
+// Hands out a crank's venue calls in order, each with its share of the remaining accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct VenueCalls {
+    calls: Vec<VenueCall>,
+    next: u16,
+    accounts_used: u16,
+}
+
+impl VenueCalls {
+    pub fn new(calls: Vec<VenueCall>) -> Self {
+        Self {
+            calls,
+            next: 0,
+            accounts_used: 0,
+        }
+    }
+
+    pub fn next<'a, 'info>(
+        &mut self,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<(&'a [AccountInfo<'info>], Vec<u8>)> {
+        let call = self
+            .calls
+            .get(self.next as usize)
+            .ok_or(TradingBotError::MissingVenueCall)?;
+        let start = self.accounts_used as usize;
+        let end = start + call.accounts_len as usize;
+        let accounts = remaining_accounts
+            .get(start..end)
+            .ok_or(TradingBotError::MissingVenueCall)?;
+        self.next += 1;
+        self.accounts_used = end as u16;
+        Ok((accounts, call.data.clone()))
+    }
+}
+
+// The program a swap on the venue is forwarded to
+pub fn swap_program_id(dex: DexType) -> Pubkey {
+    let program_id = match dex {
+        DexType::Raydium => raydium::RAYDIUM_AMM_PROGRAM_ID,
+        DexType::Jupiter => jupiter::JUPITER_V6_PROGRAM_ID,
+        DexType::Serum => serum::SERUM_DEX_V3_PROGRAM_ID,
+    };
+    program_id.parse::<Pubkey>().unwrap()
+}
+
+// Forwards `data` to `program_id` over `accounts`. `signer` is flagged as signing in the metas, for
+// a PDA signing through `signer_seeds`.
+pub fn forward_call<'info>(
+    program_id: Pubkey,
+    accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+    signer: Option<Pubkey>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let ix = Instruction {
+        program_id,
+        accounts: accounts
+            .iter()
+            .map(|a| {
+                let is_signer = a.is_signer || Some(a.key()) == signer;
+                if a.is_writable {
+                    AccountMeta::new(a.key(), is_signer)
+                } else {
+                    AccountMeta::new_readonly(a.key(), is_signer)
+                }
+            })
+            .collect(),
+        data,
+    };
+    invoke_signed(&ix, accounts, signer_seeds)?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use crate::errors::TradingBotError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+pub const PSY_AMERICAN_PROGRAM_ID: Pubkey = pubkey!("R2y9ip6mxmWUj4pt54jP2hz2dgvMozy9VTSwMWE7evs");
+
+// PsyOptions American OptionMarket: the option and writer mints, the assets and per-contract
+// amounts that fix the strike, then the expiry
+const OPTION_MINT: usize = 8;
+const WRITER_TOKEN_MINT: usize = 40;
+const UNDERLYING_ASSET_MINT: usize = 72;
+const QUOTE_ASSET_MINT: usize = 104;
+const UNDERLYING_AMOUNT_PER_CONTRACT: usize = 136;
+const QUOTE_AMOUNT_PER_CONTRACT: usize = 144;
+const EXPIRATION_UNIX_TIMESTAMP: usize = 152;
+
+// Writing and settling are opaque to us: the keeper builds the mint, close and burn instructions
+// and we check the market and what moved
+#[derive(Clone, Copy, Debug)]
+pub struct OptionMarket {
+    pub option_mint: Pubkey,
+    pub writer_token_mint: Pubkey,
+    pub underlying_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    // a call's strike is quote_amount_per_contract per underlying_amount_per_contract
+    pub underlying_amount_per_contract: u64,
+    pub quote_amount_per_contract: u64,
+    pub expiration: i64,
+}
+
+impl OptionMarket {
+    pub fn read(market: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(
+            *market.owner,
+            PSY_AMERICAN_PROGRAM_ID,
+            TradingBotError::InvalidOptionMarket
+        );
+        let data = market.try_borrow_data()?;
+        Ok(Self {
+            option_mint: read_pubkey(&data, OPTION_MINT)?,
+            writer_token_mint: read_pubkey(&data, WRITER_TOKEN_MINT)?,
+            underlying_mint: read_pubkey(&data, UNDERLYING_ASSET_MINT)?,
+            quote_mint: read_pubkey(&data, QUOTE_ASSET_MINT)?,
+            underlying_amount_per_contract: read_u64(&data, UNDERLYING_AMOUNT_PER_CONTRACT)?,
+            quote_amount_per_contract: read_u64(&data, QUOTE_AMOUNT_PER_CONTRACT)?,
+            expiration: read_u64(&data, EXPIRATION_UNIX_TIMESTAMP)? as i64,
+        })
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + 8)
+        .ok_or(TradingBotError::InvalidOptionMarket)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    let bytes = data
+        .get(offset..offset + 32)
+        .ok_or(TradingBotError::InvalidOptionMarket)?;
+    Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+}
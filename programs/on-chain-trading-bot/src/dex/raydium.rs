@@ -1,10 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
 
+use crate::errors::TradingBotError;
+
 // Official Raydium Program IDs from docs
 pub const RAYDIUM_V3_PROGRAM_ID: &str = "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK"; // CLMM Program
 pub const RAYDIUM_AMM_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"; // OpenBook AMM
 pub const RAYDIUM_ROUTER_PROGRAM_ID: &str = "routeUGWgWzqBWFcrCfv8tritsqukccJPu3q5GPP3xS"; // AMM Router
+pub const RAYDIUM_FARM_PROGRAM_ID: &str = "9KEPoZmtHUrBbhWN1v1KWLMkkvwY6WLtAVUCPRtRjP4z"; // Farm V5
 
 #[derive(Accounts)]
 pub struct RaydiumSwap<'info> {
@@ -86,13 +89,3 @@ impl<'info> RaydiumSwap<'info> {
         Ok(())
     }
 }
-
-#[error_code]
-pub enum TradingBotError {
-    #[msg("Invalid trade conditions")]
-    InvalidTradeConditions,
-    #[msg("Slippage exceeded")]
-    SlippageExceeded,
-    #[msg("Price out of range")]
-    PriceOutOfRange,
-} 
\ No newline at end of file
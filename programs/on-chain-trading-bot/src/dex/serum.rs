@@ -1,173 +1,186 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
 use anchor_spl::token::{Token, TokenAccount};
-use serum_dex::state::{Market, MarketState, OpenOrders};
-use serum_dex::matching::{Side, OrderType};
+use serum_dex::instruction::SelfTradeBehavior;
+use serum_dex::matching::{OrderType, Side};
+use serum_dex::state::Market;
+use std::num::NonZeroU64;
+
+use crate::constants::PRICE_PRECISION;
+use crate::errors::TradingBotError;
+
+// Serum DEX v3, the program strategy swaps and arbitrage legs on Serum are forwarded to
+pub const SERUM_DEX_V3_PROGRAM_ID: &str = "srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX";
 
 pub struct SerumDex;
 
 impl SerumDex {
     // Initialize user's OpenOrders account for Serum market
-    pub fn initialize_open_orders(
-        ctx: Context<InitializeOpenOrders>,
-    ) -> Result<()> {
-        // Create PDA for OpenOrders account
-        let (open_orders_pda, _) = Pubkey::find_program_address(
+    pub fn initialize_open_orders(ctx: Context<InitializeOpenOrders>) -> Result<()> {
+        let ix = serum_dex::instruction::init_open_orders(
+            ctx.accounts.serum_program.key,
+            ctx.accounts.open_orders.key,
+            ctx.accounts.owner.key,
+            ctx.accounts.market.key,
+            None,
+        )
+        .map_err(ProgramError::from)?;
+
+        invoke(
+            &ix,
             &[
-                b"open-orders",
-                ctx.accounts.market.key().as_ref(),
-                ctx.accounts.owner.key().as_ref(),
+                ctx.accounts.open_orders.to_account_info(),
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.market.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
             ],
-            ctx.program_id,
-        );
-
-        // Initialize OpenOrders account
-        serum_dex::instruction::init_open_orders(
-            ctx.accounts.market.to_account_info(),
-            ctx.accounts.open_orders.to_account_info(),
-            ctx.accounts.owner.to_account_info(),
-            ctx.accounts.rent.to_account_info(),
-            ctx.accounts.serum_program.to_account_info(),
         )?;
 
         Ok(())
     }
 
-    // Place limit order on Serum orderbook
-    pub fn place_limit_order(
-        ctx: Context<SerumOrder>,
-        side: Side,
-        price: u64,
-        size: u64,
-    ) -> Result<()> {
-        // Verify market state
-        let market = Market::load(
-            &ctx.accounts.market,
-            ctx.accounts.serum_program.key,
-        )?;
+    // Settle funds after trades
+    pub fn settle_funds(ctx: Context<SerumSettle>) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let ix = serum_dex::instruction::settle_funds(
+            accounts.serum_program.key,
+            accounts.market.key,
+            accounts.token_program.key,
+            accounts.open_orders.key,
+            accounts.owner.key,
+            accounts.base_vault.key,
+            &accounts.base_wallet.key(),
+            accounts.quote_vault.key,
+            &accounts.quote_wallet.key(),
+            None,
+            accounts.vault_signer.key,
+        )
+        .map_err(ProgramError::from)?;
 
-        // Place order
-        serum_dex::instruction::new_order(
-            market,
-            ctx.accounts.open_orders.to_account_info(),
-            ctx.accounts.request_queue.to_account_info(),
-            ctx.accounts.event_queue.to_account_info(),
-            ctx.accounts.bids.to_account_info(),
-            ctx.accounts.asks.to_account_info(),
-            ctx.accounts.base_vault.to_account_info(),
-            ctx.accounts.quote_vault.to_account_info(),
-            ctx.accounts.owner.to_account_info(),
-            side,
-            price,
-            size,
-            OrderType::Limit,
+        invoke(
+            &ix,
+            &[
+                accounts.market.to_account_info(),
+                accounts.open_orders.to_account_info(),
+                accounts.owner.to_account_info(),
+                accounts.base_vault.to_account_info(),
+                accounts.quote_vault.to_account_info(),
+                accounts.base_wallet.to_account_info(),
+                accounts.quote_wallet.to_account_info(),
+                accounts.vault_signer.to_account_info(),
+                accounts.token_program.to_account_info(),
+            ],
         )?;
 
         Ok(())
     }
 
-    // Cancel order
-    pub fn cancel_order(
-        ctx: Context<SerumOrder>,
-        order_id: u128,
-    ) -> Result<()> {
-        let market = Market::load(
-            &ctx.accounts.market,
-            ctx.accounts.serum_program.key,
-        )?;
+    // Match orders in orderbook
+    pub fn match_orders(ctx: Context<SerumMatch>, limit: u16) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let ix = serum_dex::instruction::match_orders(
+            accounts.serum_program.key,
+            accounts.market.key,
+            accounts.request_queue.key,
+            accounts.bids.key,
+            accounts.asks.key,
+            accounts.event_queue.key,
+            accounts.base_fee_receivable.key,
+            accounts.quote_fee_receivable.key,
+            limit,
+        )
+        .map_err(ProgramError::from)?;
 
-        serum_dex::instruction::cancel_order(
-            market,
-            ctx.accounts.open_orders.to_account_info(),
-            ctx.accounts.owner.to_account_info(),
-            Side::Buy,
-            order_id,
+        invoke(
+            &ix,
+            &[
+                accounts.market.to_account_info(),
+                accounts.request_queue.to_account_info(),
+                accounts.bids.to_account_info(),
+                accounts.asks.to_account_info(),
+                accounts.event_queue.to_account_info(),
+                accounts.base_fee_receivable.to_account_info(),
+                accounts.quote_fee_receivable.to_account_info(),
+            ],
         )?;
 
         Ok(())
     }
 
-    // Settle funds after trades
-    pub fn settle_funds(
-        ctx: Context<SerumSettle>,
-    ) -> Result<()> {
-        let market = Market::load(
-            &ctx.accounts.market,
-            ctx.accounts.serum_program.key,
-        )?;
-
-        serum_dex::instruction::settle_funds(
-            market,
-            ctx.accounts.open_orders.to_account_info(),
-            ctx.accounts.owner.to_account_info(),
-            ctx.accounts.base_vault.to_account_info(),
-            ctx.accounts.quote_vault.to_account_info(),
-            ctx.accounts.base_wallet.to_account_info(),
-            ctx.accounts.quote_wallet.to_account_info(),
-            ctx.accounts.vault_signer.to_account_info(),
-        )?;
-
-        Ok(())
+    // Serum prices orders in quote lots per base lot and sizes them in base lots
+    pub fn lot_sizes(market: &AccountInfo, serum_program: &Pubkey) -> Result<(u64, u64)> {
+        let market = Market::load(market, serum_program).map_err(ProgramError::from)?;
+        let (coin_lot_size, pc_lot_size) = (market.coin_lot_size, market.pc_lot_size);
+        Ok((coin_lot_size, pc_lot_size))
     }
 
-    // Match orders in orderbook
-    pub fn match_orders(
-        ctx: Context<SerumMatch>,
-        limit: u16,
-    ) -> Result<()> {
-        let market = Market::load(
-            &ctx.accounts.market,
-            ctx.accounts.serum_program.key,
-        )?;
-
-        serum_dex::instruction::match_orders(
-            market,
-            ctx.accounts.request_queue.to_account_info(),
-            ctx.accounts.event_queue.to_account_info(),
-            ctx.accounts.bids.to_account_info(),
-            ctx.accounts.asks.to_account_info(),
-            limit,
-        )?;
+    // Converts a price in native quote per native base, scaled by PRICE_PRECISION, and a native
+    // base size into Serum's lot units
+    pub fn to_lots(
+        price: u64,
+        size: u64,
+        coin_lot_size: u64,
+        pc_lot_size: u64,
+    ) -> Result<(NonZeroU64, NonZeroU64)> {
+        let lot_price = (price as u128)
+            .checked_mul(coin_lot_size as u128)
+            .and_then(|v| {
+                v.checked_div((pc_lot_size as u128).checked_mul(PRICE_PRECISION as u128)?)
+            })
+            .ok_or(TradingBotError::Overflow)?;
+        let lots = size
+            .checked_div(coin_lot_size)
+            .ok_or(TradingBotError::Overflow)?;
 
-        Ok(())
+        let lot_price = u64::try_from(lot_price)
+            .ok()
+            .and_then(NonZeroU64::new)
+            .ok_or(TradingBotError::InvalidOrderParams)?;
+        let lots = NonZeroU64::new(lots).ok_or(TradingBotError::InvalidOrderParams)?;
+        Ok((lot_price, lots))
     }
 
-    // Market order execution
-    pub fn place_market_order(
-        ctx: Context<SerumOrder>,
-        side: Side,
-        size: u64,
-    ) -> Result<()> {
-        // Verify market state
-        let market = Market::load(
-            &ctx.accounts.market,
-            ctx.accounts.serum_program.key,
-        )?;
+    // Best bid and ask as PRICE_PRECISION prices, read off the market's own book
+    pub fn top_of_book(
+        market: &AccountInfo,
+        bids: &AccountInfo,
+        asks: &AccountInfo,
+        serum_program: &Pubkey,
+    ) -> Result<(u64, u64)> {
+        let market = Market::load(market, serum_program).map_err(ProgramError::from)?;
+        let best_bid = {
+            let bids = market.load_bids_mut(bids).map_err(ProgramError::from)?;
+            let leaf = bids.find_max().and_then(|handle| bids.get(handle)?.as_leaf());
+            leaf.map(|leaf| leaf.price().get())
+        };
+        let best_ask = {
+            let asks = market.load_asks_mut(asks).map_err(ProgramError::from)?;
+            let leaf = asks.find_min().and_then(|handle| asks.get(handle)?.as_leaf());
+            leaf.map(|leaf| leaf.price().get())
+        };
+        let (best_bid, best_ask) = best_bid
+            .zip(best_ask)
+            .ok_or(TradingBotError::InsufficientLiquidity)?;
 
-        // Place order
-        serum_dex::instruction::new_order(
-            market,
-            ctx.accounts.open_orders.to_account_info(),
-            ctx.accounts.request_queue.to_account_info(),
-            ctx.accounts.event_queue.to_account_info(),
-            ctx.accounts.bids.to_account_info(),
-            ctx.accounts.asks.to_account_info(),
-            ctx.accounts.base_vault.to_account_info(),
-            ctx.accounts.quote_vault.to_account_info(),
-            ctx.accounts.owner.to_account_info(),
-            side,
-            0,
-            size,
-            OrderType::Market,
-        )?;
-
-        Ok(())
+        let (coin_lot_size, pc_lot_size) = (market.coin_lot_size, market.pc_lot_size);
+        let to_price = |lot_price: u64| {
+            (lot_price as u128)
+                .checked_mul(pc_lot_size as u128)
+                .and_then(|v| v.checked_mul(PRICE_PRECISION as u128))
+                .and_then(|v| v.checked_div(coin_lot_size as u128))
+                .and_then(|v| u64::try_from(v).ok())
+                .ok_or(TradingBotError::Overflow)
+        };
+        Ok((to_price(best_bid)?, to_price(best_ask)?))
     }
 }
 
 #[derive(Accounts)]
 pub struct InitializeOpenOrders<'info> {
+    /// CHECK: Serum market, validated by the DEX
     #[account(mut)]
     pub market: AccountInfo<'info>,
+    /// CHECK: Open orders account, initialised by the DEX
     #[account(mut)]
     pub open_orders: AccountInfo<'info>,
     pub owner: Signer<'info>,
@@ -178,38 +191,220 @@ pub struct InitializeOpenOrders<'info> {
 
 #[derive(Accounts)]
 pub struct SerumOrder<'info> {
+    /// CHECK: Serum market, validated by the DEX
     #[account(mut)]
-    pub market: Account<'info, MarketState>,
+    pub market: AccountInfo<'info>,
+    /// CHECK: Owner's open orders account on the market, validated by the DEX
     #[account(mut)]
-    pub open_orders: Account<'info, OpenOrders>,
+    pub open_orders: AccountInfo<'info>,
+    /// CHECK: Market request queue
     #[account(mut)]
     pub request_queue: AccountInfo<'info>,
+    /// CHECK: Market event queue
     #[account(mut)]
     pub event_queue: AccountInfo<'info>,
+    /// CHECK: Market bids
     #[account(mut)]
     pub bids: AccountInfo<'info>,
+    /// CHECK: Market asks
     #[account(mut)]
     pub asks: AccountInfo<'info>,
+    /// CHECK: Market base vault
+    #[account(mut)]
+    pub base_vault: AccountInfo<'info>,
+    /// CHECK: Market quote vault
     #[account(mut)]
-    pub base_vault: Account<'info, TokenAccount>,
+    pub quote_vault: AccountInfo<'info>,
+    // Asks are paid from the base wallet and bids from the quote wallet
     #[account(mut)]
-    pub quote_vault: Account<'info, TokenAccount>,
+    pub base_wallet: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub quote_wallet: Account<'info, TokenAccount>,
     pub owner: Signer<'info>,
     /// CHECK: Verified in CPI
     pub serum_program: AccountInfo<'info>,
     pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SerumOrder<'info> {
+    // Place a post-only limit order tagged with a client order id so it can be cancelled later
+    pub fn new_limit_order(
+        &self,
+        side: Side,
+        price: u64,
+        size: u64,
+        client_order_id: u64,
+    ) -> Result<()> {
+        let (coin_lot_size, pc_lot_size) =
+            SerumDex::lot_sizes(&self.market, self.serum_program.key)?;
+        let (lot_price, lots) = SerumDex::to_lots(price, size, coin_lot_size, pc_lot_size)?;
+        // post-only orders never take, so the quote they lock up carries no taker fee
+        let max_quote = lot_price
+            .get()
+            .checked_mul(lots.get())
+            .and_then(|v| v.checked_mul(pc_lot_size))
+            .and_then(NonZeroU64::new)
+            .ok_or(TradingBotError::Overflow)?;
+
+        self.new_order(
+            side,
+            lot_price,
+            lots,
+            max_quote,
+            OrderType::PostOnly,
+            client_order_id,
+        )
+    }
+
+    // Take up to `size` base at `price` or better; whatever does not fill right away is dropped
+    pub fn new_market_order(
+        &self,
+        side: Side,
+        price: u64,
+        size: u64,
+        max_quote: u64,
+    ) -> Result<()> {
+        let (coin_lot_size, pc_lot_size) =
+            SerumDex::lot_sizes(&self.market, self.serum_program.key)?;
+        let (lot_price, lots) = SerumDex::to_lots(price, size, coin_lot_size, pc_lot_size)?;
+        let max_quote = NonZeroU64::new(max_quote).ok_or(TradingBotError::InvalidOrderParams)?;
+
+        self.new_order(
+            side,
+            lot_price,
+            lots,
+            max_quote,
+            OrderType::ImmediateOrCancel,
+            0,
+        )
+    }
+
+    fn new_order(
+        &self,
+        side: Side,
+        lot_price: NonZeroU64,
+        lots: NonZeroU64,
+        max_quote: NonZeroU64,
+        order_type: OrderType,
+        client_order_id: u64,
+    ) -> Result<()> {
+        let payer = match side {
+            Side::Bid => self.quote_wallet.to_account_info(),
+            Side::Ask => self.base_wallet.to_account_info(),
+        };
+        let ix = serum_dex::instruction::new_order(
+            self.market.key,
+            self.open_orders.key,
+            self.request_queue.key,
+            self.event_queue.key,
+            self.bids.key,
+            self.asks.key,
+            payer.key,
+            self.owner.key,
+            self.base_vault.key,
+            self.quote_vault.key,
+            self.token_program.key,
+            self.rent.to_account_info().key,
+            None,
+            self.serum_program.key,
+            side,
+            lot_price,
+            lots,
+            order_type,
+            client_order_id,
+            SelfTradeBehavior::DecrementTake,
+            u16::MAX,
+            max_quote,
+        )
+        .map_err(ProgramError::from)?;
+
+        invoke(
+            &ix,
+            &[
+                self.market.to_account_info(),
+                self.open_orders.to_account_info(),
+                self.request_queue.to_account_info(),
+                self.event_queue.to_account_info(),
+                self.bids.to_account_info(),
+                self.asks.to_account_info(),
+                payer,
+                self.owner.to_account_info(),
+                self.base_vault.to_account_info(),
+                self.quote_vault.to_account_info(),
+                self.token_program.to_account_info(),
+                self.rent.to_account_info(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    // Cancel a resting order by the client order id it was placed with
+    pub fn cancel_order_by_client_id(&self, client_order_id: u64) -> Result<()> {
+        let ix = serum_dex::instruction::cancel_order_by_client_order_id(
+            self.serum_program.key,
+            self.market.key,
+            self.bids.key,
+            self.asks.key,
+            self.open_orders.key,
+            self.owner.key,
+            self.event_queue.key,
+            client_order_id,
+        )
+        .map_err(ProgramError::from)?;
+
+        invoke(&ix, &self.cancel_account_infos())?;
+
+        Ok(())
+    }
+
+    fn cancel_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.market.to_account_info(),
+            self.bids.to_account_info(),
+            self.asks.to_account_info(),
+            self.open_orders.to_account_info(),
+            self.owner.to_account_info(),
+            self.event_queue.to_account_info(),
+        ]
+    }
+
+    // Cancel a resting order by the DEX order id
+    pub fn cancel_order(&self, side: Side, order_id: u128) -> Result<()> {
+        let ix: Instruction = serum_dex::instruction::cancel_order(
+            self.serum_program.key,
+            self.market.key,
+            self.bids.key,
+            self.asks.key,
+            self.open_orders.key,
+            self.owner.key,
+            self.event_queue.key,
+            side,
+            order_id,
+        )
+        .map_err(ProgramError::from)?;
+
+        invoke(&ix, &self.cancel_account_infos())?;
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
 pub struct SerumSettle<'info> {
+    /// CHECK: Serum market, validated by the DEX
     #[account(mut)]
-    pub market: Account<'info, MarketState>,
+    pub market: AccountInfo<'info>,
+    /// CHECK: Owner's open orders account on the market, validated by the DEX
     #[account(mut)]
-    pub open_orders: Account<'info, OpenOrders>,
+    pub open_orders: AccountInfo<'info>,
+    /// CHECK: Market base vault
     #[account(mut)]
-    pub base_vault: Account<'info, TokenAccount>,
+    pub base_vault: AccountInfo<'info>,
+    /// CHECK: Market quote vault
     #[account(mut)]
-    pub quote_vault: Account<'info, TokenAccount>,
+    pub quote_vault: AccountInfo<'info>,
     #[account(mut)]
     pub base_wallet: Account<'info, TokenAccount>,
     #[account(mut)]
@@ -224,16 +419,27 @@ pub struct SerumSettle<'info> {
 
 #[derive(Accounts)]
 pub struct SerumMatch<'info> {
+    /// CHECK: Serum market, validated by the DEX
     #[account(mut)]
-    pub market: Account<'info, MarketState>,
+    pub market: AccountInfo<'info>,
+    /// CHECK: Market request queue
     #[account(mut)]
     pub request_queue: AccountInfo<'info>,
+    /// CHECK: Market event queue
     #[account(mut)]
     pub event_queue: AccountInfo<'info>,
+    /// CHECK: Market bids
     #[account(mut)]
     pub bids: AccountInfo<'info>,
+    /// CHECK: Market asks
     #[account(mut)]
     pub asks: AccountInfo<'info>,
+    /// CHECK: Receives the base side of matching fees
+    #[account(mut)]
+    pub base_fee_receivable: AccountInfo<'info>,
+    /// CHECK: Receives the quote side of matching fees
+    #[account(mut)]
+    pub quote_fee_receivable: AccountInfo<'info>,
     /// CHECK: Verified in CPI
     pub serum_program: AccountInfo<'info>,
-} 
\ No newline at end of file
+}
@@ -0,0 +1,100 @@
+use crate::errors::TradingBotError;
+use anchor_lang::prelude::*;
+
+// Serum/OpenBook accounts start with 5 bytes of "serum" padding and end with 7 bytes of "padding"
+const ACCOUNT_HEAD_PADDING: usize = 5;
+const ACCOUNT_TAIL_PADDING: usize = 7;
+// account flags, head, count, seq_num
+const EVENT_QUEUE_HEADER_LEN: usize = 32;
+const EVENT_LEN: usize = 88;
+
+const EVENT_FLAG_FILL: u8 = 0x1;
+const EVENT_FLAG_BID: u8 = 0x4;
+const EVENT_FLAG_MAKER: u8 = 0x8;
+
+#[derive(Clone, Copy)]
+pub struct FillEvent {
+    pub seq: u64,
+    pub is_bid: bool,
+    pub is_maker: bool,
+    pub owner: Pubkey,
+    pub native_qty_paid: u64,
+    pub native_qty_received: u64,
+    pub native_fee_or_rebate: u64,
+    pub client_order_id: u64,
+}
+
+// Read-only view over the raw event queue ring. Events stay on the queue until the market's crank
+// consumes them, so each carries its absolute sequence number for callers to skip what they've seen.
+pub struct EventQueueView<'a> {
+    events: &'a [u8],
+    capacity: u64,
+    head: u64,
+    count: u64,
+    seq_num: u64,
+}
+
+impl<'a> EventQueueView<'a> {
+    pub fn load(data: &'a [u8]) -> Result<Self> {
+        let body_start = ACCOUNT_HEAD_PADDING + EVENT_QUEUE_HEADER_LEN;
+        require!(
+            data.len() >= body_start + ACCOUNT_TAIL_PADDING + EVENT_LEN,
+            TradingBotError::InvalidEventQueue
+        );
+        let header = &data[ACCOUNT_HEAD_PADDING..body_start];
+        let events = &data[body_start..data.len() - ACCOUNT_TAIL_PADDING];
+        let capacity = (events.len() / EVENT_LEN) as u64;
+        let view = Self {
+            events,
+            capacity,
+            head: Self::read_u64(header, 8),
+            count: Self::read_u64(header, 16),
+            seq_num: Self::read_u64(header, 24),
+        };
+        require!(
+            view.head < capacity && view.count <= capacity && view.count <= view.seq_num,
+            TradingBotError::InvalidEventQueue
+        );
+        Ok(view)
+    }
+
+    fn read_u64(data: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+    }
+
+    // Sequence number the next pushed event will get
+    pub fn next_seq(&self) -> u64 {
+        self.seq_num
+    }
+
+    // Fill events for `owner` with a sequence number at or above `from_seq`, oldest first
+    pub fn fills_for(&self, owner: Pubkey, from_seq: u64) -> impl Iterator<Item = FillEvent> + '_ {
+        let first_seq = self.seq_num - self.count;
+        (0..self.count).filter_map(move |i| {
+            let seq = first_seq + i;
+            if seq < from_seq {
+                return None;
+            }
+            let slot = ((self.head + i) % self.capacity) as usize;
+            let event = &self.events[slot * EVENT_LEN..(slot + 1) * EVENT_LEN];
+            let flags = event[0];
+            if flags & EVENT_FLAG_FILL == 0 {
+                return None;
+            }
+            let event_owner = Pubkey::new_from_array(event[48..80].try_into().unwrap());
+            if event_owner != owner {
+                return None;
+            }
+            Some(FillEvent {
+                seq,
+                is_bid: flags & EVENT_FLAG_BID != 0,
+                is_maker: flags & EVENT_FLAG_MAKER != 0,
+                owner: event_owner,
+                native_qty_received: Self::read_u64(event, 8),
+                native_qty_paid: Self::read_u64(event, 16),
+                native_fee_or_rebate: Self::read_u64(event, 24),
+                client_order_id: Self::read_u64(event, 80),
+            })
+        })
+    }
+}
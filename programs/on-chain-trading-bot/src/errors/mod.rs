@@ -1,7 +1,205 @@
 use anchor_lang::prelude::*;
 
 #[error_code]
-pub enum EscrowErrors {
+pub enum TradingBotError {
+    #[msg("Strategy is not active")]
+    StrategyInactive,
+    #[msg("Invalid DEX type")]
+    InvalidDexType,
+    #[msg("Invalid action type")]
+    InvalidActionType,
+    #[msg("Condition not met")]
+    ConditionNotMet,
+    #[msg("Deadline exceeded")]
+    DeadlineExceeded,
+    #[msg("Slippage exceeded")]
+    SlippageExceeded,
+    #[msg("Invalid calculation")]
+    InvalidCalculation,
+    #[msg("Invalid tick range")]
+    InvalidTickRange,
+    #[msg("Invalid liquidity ratios")]
+    InvalidRatios,
+    #[msg("Overflow")]
+    Overflow,
+    #[msg("Price feed is stale")]
+    StalePriceFeed,
+    #[msg("Price unavailable")]
+    PriceUnavailable,
+    #[msg("Insufficient price data")]
+    InsufficientPriceData,
+    #[msg("Price confidence interval too high")]
+    LowConfidence,
+    #[msg("Insufficient price confidence")]
+    InsufficientConfidence,
+    #[msg("Excessive confidence interval")]
+    ExcessiveConfidenceInterval,
+    #[msg("Too frequent price updates")]
+    TooFrequentUpdates,
+    #[msg("Invalid market making parameters")]
+    InvalidMarketMakingParams,
+    #[msg("Quotes are still within the requote threshold")]
+    RequoteNotNeeded,
+    #[msg("Inventory is within the hedge band")]
+    InventoryWithinBand,
+    #[msg("Unsupported bridge or target chain")]
+    UnsupportedBridge,
+    #[msg("Bridge transfer already completed")]
+    BridgeTransferCompleted,
+    #[msg("OTC offer has expired")]
+    OtcOfferExpired,
+    #[msg("OTC offer has not expired")]
+    OtcOfferNotExpired,
+    #[msg("Signer may not take or cancel this OTC offer")]
+    UnauthorizedOtcParty,
+    #[msg("Invalid liquidation auction parameters")]
+    InvalidAuctionParams,
+    #[msg("Auction price is above the taker's limit")]
+    AuctionPriceAboveLimit,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Nothing left to claim")]
+    NothingToClaim,
+    #[msg("Claim exceeds the distributor's total")]
+    ClaimExceedsMaximum,
+    #[msg("Trade mining epoch is not active")]
+    EpochNotActive,
+    #[msg("Trade mining epoch has not ended")]
+    EpochNotEnded,
+    #[msg("Epoch volume account does not belong to this epoch")]
+    EpochMismatch,
+    #[msg("Invalid limit order parameters")]
+    InvalidOrderParams,
+    #[msg("Order is no longer open")]
+    OrderNotOpen,
+    #[msg("Fill price does not meet the order's limit")]
+    LimitPriceNotMet,
+    #[msg("Order has expired")]
+    OrderExpired,
+    #[msg("Order has not expired")]
+    OrderNotExpired,
+    #[msg("Amount exceeds the vault's unreserved balance")]
+    InsufficientAvailableBalance,
+    #[msg("Quote lock has expired")]
+    QuoteLockExpired,
+    #[msg("Realized swap is outside the locked quote's tolerance")]
+    QuoteOutsideTolerance,
+    #[msg("Strategy block is missing a required parameter")]
+    MissingBlockParameter,
+    #[msg("Invalid strategy configuration")]
+    InvalidStrategyConfig,
+    #[msg("Invalid recurring transfer schedule")]
+    InvalidSchedule,
+    #[msg("Next cycle is not due yet")]
+    CycleNotDue,
+    #[msg("Too many open tax lots")]
+    TooManyTaxLots,
+    #[msg("Sell exceeds the tracked tax lots")]
+    InsufficientTaxLots,
+    #[msg("Invalid depeg guard parameters")]
+    InvalidDepegParams,
+    #[msg("Rotation condition has not held long enough")]
+    RotationNotDue,
+    #[msg("Invalid volatility regime parameters")]
+    InvalidRegimeParams,
+    #[msg("Sample interval has not elapsed")]
+    SampleTooEarly,
+    #[msg("Invalid correlation data")]
+    InvalidCorrelationData,
+    #[msg("Trade exceeds portfolio risk limits")]
+    PortfolioRiskExceeded,
+    #[msg("Invalid profile name")]
+    InvalidProfileName,
+    #[msg("Strategy registry is full")]
+    RegistryFull,
+    #[msg("Profile is not in the registry")]
+    ProfileNotRegistered,
+    #[msg("Invalid subscription parameters")]
+    InvalidSubscriptionParams,
+    #[msg("Subscription is not active")]
+    SubscriptionInactive,
+    #[msg("Release earned fees before renewing a lapsed subscription")]
+    SubscriptionFeesUnsettled,
+    #[msg("New entries are halted after a volatile print")]
+    EntriesHalted,
+    #[msg("Invalid event queue")]
+    InvalidEventQueue,
+    #[msg("Venue is excluded from routing")]
+    VenueBanned,
+    #[msg("Invalid approval policy")]
+    InvalidApprovalPolicy,
+    #[msg("Trade above the large-trade threshold needs co-signer approval")]
+    LargeTradeNotApproved,
+    #[msg("Invalid session parameters")]
+    InvalidSessionParams,
+    #[msg("Session key is expired or over its spend limits")]
+    SessionLimitExceeded,
+    #[msg("Signer is neither the owner nor a session key")]
+    UnauthorizedExecutor,
+    #[msg("Invalid recovery configuration")]
+    InvalidRecoveryConfig,
+    #[msg("Signer is not a guardian")]
+    NotAGuardian,
+    #[msg("Recovery lacks approvals or is still timelocked")]
+    RecoveryNotReady,
+    #[msg("Invalid withdrawal address")]
+    InvalidWithdrawalAddress,
+    #[msg("Withdrawal allowlist is full")]
+    WithdrawalAllowlistFull,
+    #[msg("Destination is not an active allowlisted address")]
+    WithdrawalAddressNotAllowed,
+    #[msg("Invalid price band")]
+    InvalidPriceBand,
+    #[msg("Pool price is still inside its band")]
+    PriceWithinBand,
+    #[msg("Invalid pool sample")]
+    InvalidPoolSample,
+    #[msg("Invalid migration rule")]
+    InvalidMigrationRule,
+    #[msg("No pool qualifies for migration")]
+    MigrationNotDue,
+    #[msg("Daily migration cap reached")]
+    MigrationCapExceeded,
+    #[msg("Strategy is at its open position cap")]
+    MaxOpenPositionsExceeded,
+    #[msg("Daily volume limit reached")]
+    DailyVolumeExceeded,
+    #[msg("Position would exceed the leverage cap")]
+    LeverageExceeded,
+    #[msg("Invalid block retry or fallback configuration")]
+    InvalidBlockRetry,
+    #[msg("Deadline is too far in the future")]
+    InvalidDeadline,
+    #[msg("Price feed does not match the strategy's oracle")]
+    PriceFeedMismatch,
+    #[msg("Token account does not match the block's trade side")]
+    TradeSideMismatch,
+    #[msg("Invalid token launch parameters")]
+    InvalidLaunchParams,
+    #[msg("Token launch is not live")]
+    LaunchNotLive,
+    #[msg("Token launch interval cap reached")]
+    LaunchIntervalCapReached,
+    #[msg("Invalid treasury plan parameters")]
+    InvalidTreasuryPlan,
+    #[msg("No treasury slice is due")]
+    SliceNotDue,
+    #[msg("Page size must be positive")]
+    InvalidPageSize,
+    #[msg("Start index does not match the persisted cursor")]
+    StaleCursor,
+    #[msg("Accounts do not match the page's entries")]
+    InvalidPageAccounts,
+    #[msg("Invalid fee oracle configuration or observation")]
+    InvalidFeeOracle,
+    #[msg("Signer is not a registered fee keeper")]
+    NotAFeeKeeper,
+    #[msg("Fee oracle has no fresh observations")]
+    StaleFeeOracle,
+    #[msg("Too many quote candidates for one batch")]
+    TooManyQuotes,
+    #[msg("Accounts do not match the quote candidates")]
+    InvalidQuoteAccounts,
     #[msg("DCA not closed")]
     DCANotClosed,
     #[msg("Unexpected balance")]
@@ -14,8 +212,264 @@ pub enum EscrowErrors {
     UnexpectedAirdropAmount,
     #[msg("Insufficient balance")]
     InsufficientBalance,
-     #[msg("Overflow")]
-    MathOverflow,
+    #[msg("Escrow configuration is locked")]
+    EscrowConfigLocked,
+    #[msg("DCA already completed")]
+    DCACompleted,
+    #[msg("Invalid cancellation penalty")]
+    InvalidPenalty,
+    #[msg("Invalid faucet or mock pool parameters")]
+    InvalidDevnetParams,
+    #[msg("Mock pool has too little liquidity for the swap")]
+    InsufficientLiquidity,
+    #[msg("Portfolio is full")]
+    PortfolioFull,
+    #[msg("Strategy is not in the portfolio")]
+    NotAPortfolioMember,
+    #[msg("Invalid netting order")]
+    InvalidNettingOrder,
+    #[msg("Internal order book is full")]
+    OrderBookFull,
+    #[msg("Order is not listed in the internal order book")]
+    OrderNotListed,
+    #[msg("No tracked position with that id")]
+    PositionNotFound,
+    #[msg("Reduction must be between 1 and 10000 bps")]
+    InvalidPositionReduction,
+    #[msg("Invalid or missing position stop")]
+    InvalidPositionStop,
+    #[msg("Keeper is not allowed to execute this strategy")]
+    KeeperNotAllowed,
+    #[msg("Keeper allowlist is full")]
+    KeeperAllowlistFull,
+    #[msg("Keeper bond is too small or still locked")]
+    InvalidKeeperBond,
+    #[msg("Keeper has too many open violations to execute")]
+    KeeperViolationsOutstanding,
+    #[msg("Keeper has no open violation on this strategy")]
+    KeeperViolationNotFound,
+    #[msg("Execution is reserved by the strategy's execution auction")]
+    ExecutionAuctionActive,
+    #[msg("Bid is too low or the auction is not taking bids")]
+    InvalidAuctionBid,
+    #[msg("Callback rule slot is empty or out of range")]
+    InvalidCallbackRule,
+    #[msg("Callback rule is not armed or its condition does not hold")]
+    CallbackConditionNotMet,
+    #[msg("Transaction has instructions a guarded execution can't run beside")]
+    UntrustedInstruction,
+    #[msg("Strategy is already executing")]
+    ExecutionInProgress,
+    #[msg("Mint has no feed in the registry")]
+    UnregisteredMint,
+    #[msg("Feed registry is full")]
+    FeedRegistryFull,
+    #[msg("Invalid registered feed")]
+    InvalidRegisteredFeed,
+    #[msg("Pair has no pool on this venue in the registry")]
+    UnregisteredPool,
+    #[msg("Pool registry is full")]
+    PoolRegistryFull,
+    #[msg("Invalid registered pool")]
+    InvalidRegisteredPool,
+    #[msg("Pool account is not the registered one")]
+    PoolMismatch,
+    #[msg("Not a dust account of the vault")]
+    InvalidDustAccount,
+    #[msg("Balance is worth more than the vault's dust threshold")]
+    DustAboveThreshold,
+    #[msg("Strategy triggers are not met")]
+    NotTriggered,
+    #[msg("Not the SlotHashes sysvar")]
+    InvalidSlotHashes,
+    #[msg("Mint is an LST and is priced through its stake pool")]
+    StakePoolRequired,
+    #[msg("Not the stake pool registered for the mint")]
+    InvalidStakePool,
+    #[msg("Stake pool has not been updated this epoch")]
+    StaleStakePool,
+    #[msg("Invalid peg arbitrage parameters")]
+    InvalidPegArbParams,
+    #[msg("Peg arbitrage is not at this step")]
+    PegArbNotReady,
+    #[msg("LST bought at less than the entry discount")]
+    DiscountTooSmall,
+    #[msg("LST discount has not closed to the exit threshold")]
+    DiscountNotClosed,
+    #[msg("Unstaked SOL is still unbonding")]
+    UnbondingNotOver,
+    #[msg("Not a Solend or Kamino reserve, or not the block's")]
+    InvalidLendingReserve,
+    #[msg("Lending metric is not read from this reserve's program")]
+    UnsupportedLendingMetric,
+    #[msg("Invalid leverage loop parameters")]
+    InvalidLeverageParams,
+    #[msg("Not a Solend obligation of the vault")]
+    InvalidObligation,
+    #[msg("Obligation has not been refreshed in this slot")]
+    StaleObligation,
+    #[msg("Loop would take the obligation past its target LTV")]
+    LeverageLimitExceeded,
+    #[msg("Obligation would be too close to liquidation")]
+    ObligationUnhealthy,
+    #[msg("Not a PsyOptions market on the vaults' assets, or expiring outside the tenor")]
+    InvalidOptionMarket,
+    #[msg("Invalid covered call parameters")]
+    InvalidCoveredCallParams,
+    #[msg("Call series is not in a state to be written or settled")]
+    CallNotReady,
+    #[msg("Calls would cover more than the allowed share of the vault")]
+    CoverageExceeded,
+    #[msg("Strike is not far enough out of the money")]
+    StrikeTooLow,
+    #[msg("Premium is below the minimum")]
+    PremiumTooLow,
+    #[msg("Invalid principal note parameters")]
+    InvalidNoteParams,
+    #[msg("Principal note is not in a state to be opened, valued or closed")]
+    NoteNotReady,
+    #[msg("Deposit is not projected to return the principal floor by maturity")]
+    PrincipalNotProtected,
+    #[msg("Calls cost more than the principal left over from the deposit")]
+    UpsideBudgetExceeded,
+    #[msg("Invalid index fund parameters")]
+    InvalidIndexParams,
+    #[msg("Index fund has no room for another component")]
+    IndexFull,
+    #[msg("Not one of the index fund's components, or accounts out of order")]
+    InvalidIndexComponent,
+    #[msg("Index reconstitution is not due yet")]
+    ReconstitutionNotDue,
+    #[msg("Index fund is outside a rebalance window")]
+    RebalanceWindowClosed,
+    #[msg("Component is within its drift tolerance")]
+    IndexBalanced,
+    #[msg("Rebalance did not bring the component closer to its target")]
+    RebalanceNotCloser,
+    #[msg("Base vault does not hold enough cash for the withdrawal")]
+    InsufficientIndexCash,
+    #[msg("Fund has no units to price")]
+    NoFundUnits,
+    #[msg("Deposit is below the fund's minimum")]
+    DepositBelowMinimum,
+    #[msg("Deposit would take the fund past its maximum TVL")]
+    FundCapacityExceeded,
+    #[msg("Deposit would take the wallet past its limit")]
+    WalletLimitExceeded,
+    #[msg("Wallet is not allowed to deposit into the fund")]
+    DepositorNotAllowed,
+    #[msg("Index fund is in emergency mode")]
+    IndexInEmergency,
+    #[msg("Index fund is not in emergency mode")]
+    IndexNotInEmergency,
+    #[msg("Emergency liquidations can only sell a component into the base asset")]
+    NotALiquidation,
+    #[msg("Program state hasn't passed post_upgrade_check since the last deploy")]
+    UpgradeCheckPending,
+    #[msg("Vault's token account doesn't match its accounting")]
+    VaultAccountingMismatch,
+    #[msg("Index fund's share supply doesn't match its units")]
+    ShareSupplyMismatch,
+    #[msg("Invalid trade conditions")]
+    InvalidTradeConditions,
+    #[msg("Instruction isn't enabled in this build")]
+    FeatureDisabled,
+    #[msg("Venue call or its accounts missing")]
+    MissingVenueCall,
+    #[msg("Loop must follow a swap and repeat it at most MAX_LOOP_COUNT times")]
+    InvalidLoopBlock,
+    #[msg("Fill price is too far from the oracle price")]
+    FillOffOracle,
+    #[msg("Not the owner's Drift account, or not one Drift can have written")]
+    InvalidDriftAccount,
+    #[msg("Not the vault's log account")]
+    InvalidVaultLog,
+    #[msg("Not the trading vault's token account")]
+    InvalidVaultTokenAccount,
+    #[msg("The owner's strategy is required")]
+    MissingStrategy,
+    #[msg("post_upgrade_check has to cover vaults and index funds in order, each once")]
+    UpgradeCheckOutOfOrder,
+    #[msg("Wormhole message doesn't match the bridge transfer")]
+    InvalidWormholeMessage,
+    #[msg("Not one of the strategy's token accounts for its pair")]
+    InvalidStrategyWallet,
+    #[msg("Summary needs one market maker or strategy and only its own accounts")]
+    InvalidSummaryAccounts,
+    #[msg("Fill is too small to move any quote")]
+    FillTooSmall,
+    #[msg("Price feed publishes at an exponent other than the registered one")]
+    UnexpectedPriceExponent,
+    #[msg("Keeper has no attempt open on this strategy from an earlier slot")]
+    KeeperAttemptNotOpen,
+    #[msg("Position is within the strategy's position size limit")]
+    PositionWithinLimits,
+    #[msg("Token account's mint isn't the bridged token")]
+    BridgeTokenMismatch,
+    #[msg("Only the owner can cancel an auction, or anyone once its strategy has recovered")]
+    AuctionNotCancellable,
+    #[msg("Index fund's NAV per unit moved past the upgrade check's tolerance")]
+    NavOutOfTolerance,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExecutionStage {
+    Trigger,
+    Condition,
+    Action,
+    Loop,
+    Exit,
+}
+
+// Where a strategy run failed. Logged and published as return data so simulations can point the
+// author at the exact block and parameter instead of a bare error code.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ErrorContext {
+    pub block_index: u16,
+    pub block_id: String,
+    pub stage: ExecutionStage,
+    pub param: Option<String>,
+    pub error_code: u64,
+}
+
+impl ErrorContext {
+    pub fn new(block_index: u16, block_id: &str, stage: ExecutionStage) -> Self {
+        Self {
+            block_index,
+            block_id: block_id.to_string(),
+            stage,
+            param: None,
+            error_code: 0,
+        }
+    }
 
+    pub fn with_param(mut self, param: Option<&str>) -> Self {
+        self.param = param.map(str::to_string);
+        self
+    }
+
+    pub fn with_error(mut self, error: &Error) -> Self {
+        self.error_code = match error {
+            Error::AnchorError(e) => e.error_code_number as u64,
+            Error::ProgramError(e) => u64::from(e.program_error.clone()),
+        };
+        self
+    }
+
+    // Returns the original error unchanged so callers still see the real code
+    pub fn attach(self, error: Error) -> Error {
+        let context = self.with_error(&error);
+        msg!(
+            "Block #{} ({}) failed at {:?} stage, param {:?}, code {}",
+            context.block_index,
+            context.block_id,
+            context.stage,
+            context.param,
+            context.error_code
+        );
+        if let Ok(data) = context.try_to_vec() {
+            anchor_lang::solana_program::program::set_return_data(&data);
+        }
+        error
+    }
+}
@@ -0,0 +1,569 @@
+use crate::regime::VolatilityRegime;
+use crate::state::{DiversificationMode, NotificationKind, OrderStatus, VaultLogEntry};
+use crate::types::{DexType, TokenPair, TradeSide};
+use anchor_lang::prelude::*;
+
+/// A fill appended to an owner's trade history, with the running hash accumulator
+#[event]
+pub struct FillRecorded {
+    pub owner: Pubkey,
+    pub index: u64,
+    pub timestamp: i64,
+    pub pair: Pubkey,
+    pub is_buy: bool,
+    pub size: u64,
+    pub price: u64,
+    pub fee: u64,
+    pub accumulator: [u8; 32],
+}
+
+/// An entry appended to a vault's replay log, with the hash that chains the next entry to it
+#[event]
+pub struct VaultLogAppended {
+    pub trading_vault: Pubkey,
+    pub index: u64,
+    pub entry: VaultLogEntry,
+    pub hash: [u8; 32],
+}
+
+/// A limit order was filled in part or full, cancelled or expired
+#[event]
+pub struct LimitOrderUpdated {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub status: OrderStatus,
+    pub fill_size: u64,
+    pub fill_price: u64,
+    pub filled_size: u64,
+    pub remaining_size: u64,
+    pub avg_fill_price: u64,
+}
+
+/// A strategy notification for off-chain subscribers
+#[event]
+pub struct NotificationPosted {
+    pub strategy: Pubkey,
+    pub seq: u64,
+    pub kind: NotificationKind,
+    pub timestamp: i64,
+    pub subject: Pubkey,
+    pub value: u64,
+}
+
+/// A tax lot closed by a sale, with its cost basis and gain
+#[event]
+pub struct LotRealized {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub acquired_at: i64,
+    pub amount: u64,
+    pub cost_basis: u64,
+    pub proceeds: u64,
+    pub gain: i64,
+}
+
+/// A tracked position was partly or fully closed, by reduce_position or a scaled exit
+#[event]
+pub struct PositionReduced {
+    pub owner: Pubkey,
+    pub dex: DexType,
+    pub position_id: Pubkey,
+    pub reduced: u64,
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+/// A depeg guard rotated between the stablecoin and its haven asset
+#[event]
+pub struct DepegRotation {
+    pub guard: Pubkey,
+    pub to_haven: bool,
+    pub stable_price: u64,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub deadline: i64,
+}
+
+/// A market maker switched volatility regime
+#[event]
+pub struct RegimeChanged {
+    pub market_maker: Pubkey,
+    pub from: VolatilityRegime,
+    pub to: VolatilityRegime,
+    pub volatility_bps: u64,
+    pub timestamp: i64,
+}
+
+/// A public profile recorded its strategy's NAV and return
+#[event]
+pub struct ProfileSnapshotTaken {
+    pub profile: Pubkey,
+    pub strategy: Pubkey,
+    pub nav: u64,
+    pub return_bps: i64,
+    pub max_drawdown_bps: u16,
+    pub alpha_vs_hodl_bps: i64,
+    // None without a SOL benchmark
+    pub alpha_vs_sol_bps: Option<i64>,
+    pub timestamp: i64,
+}
+
+/// Consumed fills against one of a market maker's quotes
+#[event]
+pub struct QuoteFilled {
+    pub market_maker: Pubkey,
+    pub seq: u64,
+    pub client_order_id: u64,
+    pub is_bid: bool,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    // against the quoted price, positive when worse; None for quotes already replaced
+    pub slippage_bps: Option<i64>,
+}
+
+/// Execution metrics banned a venue for its owner's strategies
+#[event]
+pub struct VenueBanned {
+    pub owner: Pubkey,
+    pub dex: DexType,
+    pub avg_slippage_bps: u64,
+    pub avg_deviation_bps: u64,
+    pub failure_rate_bps: u64,
+    pub timestamp: i64,
+}
+
+/// A reported fill's cost split into latency, spread and impact against the decision mid
+#[event]
+pub struct FillAttributed {
+    pub owner: Pubkey,
+    pub dex: DexType,
+    pub decision_mid: u64,
+    pub quoted_price: u64,
+    pub realized_price: u64,
+    pub latency_bps: i64,
+    pub spread_bps: i64,
+    pub impact_bps: i64,
+    pub timestamp: i64,
+}
+
+/// A swap remainder past the depth limit was left for a later crank
+#[event]
+pub struct SwapDeferred {
+    pub strategy: Pubkey,
+    pub block_id: String,
+    pub dex: DexType,
+    pub amount: u64,
+    pub deadline: i64,
+}
+
+/// Guardians moved a strategy and its vaults to a new owner
+#[event]
+pub struct OwnershipRecovered {
+    pub strategy: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub vaults: u8,
+    pub timestamp: i64,
+}
+
+/// A concentrated liquidity position left its price band
+#[event]
+pub struct PriceBandExited {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub dex: DexType,
+    pub price: u64,
+    pub lower_price: u64,
+    pub upper_price: u64,
+    pub timestamp: i64,
+}
+
+/// A vault sold its dust balances into its own mint and closed the emptied accounts
+#[event]
+pub struct DustSwept {
+    pub trading_vault: Pubkey,
+    pub accounts_closed: u32,
+    pub received: u64,
+    // USD value of the balances sold, with USD_DECIMALS
+    pub swept_value: u64,
+    pub timestamp: i64,
+}
+
+/// A peg arbitrage bought its LST below the stake pool's redemption rate
+#[event]
+pub struct PegArbEntered {
+    pub peg_arb: Pubkey,
+    pub paid: u64,
+    pub bought: u64,
+    pub discount_bps: u64,
+    pub deadline: i64,
+}
+
+/// A peg arbitrage unstaked its LST and waits for the SOL to unbond
+#[event]
+pub struct PegArbUnstaked {
+    pub peg_arb: Pubkey,
+    pub unstaked: u64,
+    pub unbonding_lamports: u64,
+    pub claimable_epoch: u64,
+}
+
+/// A peg arbitrage round ended back in SOL, sold or unstaked
+#[event]
+pub struct PegArbExited {
+    pub peg_arb: Pubkey,
+    pub received: u64,
+    // lamports over the SOL the round paid
+    pub pnl: i64,
+    pub unstaked: bool,
+}
+
+/// A leverage loop levered up or unwound its obligation by one pass
+#[event]
+pub struct LeverageLoopRun {
+    pub leverage_loop: Pubkey,
+    pub unwind: bool,
+    pub ltv_bps: u64,
+    pub health_bps: u64,
+    pub timestamp: i64,
+}
+
+/// Covered calls were written on a vault and sold for premium
+#[event]
+pub struct CoveredCallWritten {
+    pub covered_call: Pubkey,
+    pub option_market: Pubkey,
+    pub contracts: u64,
+    pub collateral: u64,
+    pub premium: u64,
+    pub expiration: i64,
+}
+
+/// A covered call series settled after expiry, either expired or assigned
+#[event]
+pub struct CoveredCallSettled {
+    pub covered_call: Pubkey,
+    pub option_market: Pubkey,
+    pub assigned: bool,
+    pub underlying_returned: u64,
+    pub quote_received: u64,
+}
+
+/// A principal-protected note lent out its principal and bought its calls
+#[event]
+pub struct PrincipalNoteOpened {
+    pub principal_note: Pubkey,
+    pub principal: u64,
+    pub deposit: u64,
+    pub premium: u64,
+    pub options: u64,
+    pub maturity: i64,
+}
+
+/// A principal-protected note's lending and call legs were marked to market
+#[event]
+pub struct PrincipalNoteValued {
+    pub principal_note: Pubkey,
+    pub principal_value: u64,
+    pub upside_value: u64,
+    pub timestamp: i64,
+}
+
+/// A principal-protected note was closed at or before maturity, with each leg's result
+#[event]
+pub struct PrincipalNoteClosed {
+    pub principal_note: Pubkey,
+    pub early: bool,
+    pub principal_proceeds: u64,
+    pub upside_proceeds: u64,
+    pub principal_pnl: i64,
+    pub upside_pnl: i64,
+}
+
+/// Base asset was deposited into or withdrawn from an index fund for units at its NAV
+#[event]
+pub struct IndexFlow {
+    pub index_fund: Pubkey,
+    pub deposit: bool,
+    pub amount: u64,
+    pub units: u64,
+    pub nav: u64,
+}
+
+/// A holder asked to withdraw locked-up index units and left `fee_units` of them to the fund
+#[event]
+pub struct IndexExitFeeCharged {
+    pub index_fund: Pubkey,
+    pub holder: Pubkey,
+    pub units: u64,
+    pub fee_units: u64,
+}
+
+/// An index fund's owner turned emergency mode on or off
+#[event]
+pub struct IndexEmergencySet {
+    pub index_fund: Pubkey,
+    pub emergency: bool,
+    pub slippage_bps: u16,
+}
+
+/// A holder took their pro-rata share of an index fund's base cash in emergency mode
+#[event]
+pub struct IndexEmergencyWithdrawal {
+    pub index_fund: Pubkey,
+    pub holder: Pubkey,
+    pub units: u64,
+    pub amount: u64,
+}
+
+/// An index fund's component targets were reset from its weight rule
+#[event]
+pub struct IndexReconstituted {
+    pub index_fund: Pubkey,
+    pub targets_bps: Vec<u16>,
+    pub timestamp: i64,
+}
+
+/// An index fund component was traded back toward its target
+#[event]
+pub struct IndexRebalanced {
+    pub index_fund: Pubkey,
+    pub mint: Pubkey,
+    pub value_before: u64,
+    pub value_after: u64,
+    pub nav: u64,
+}
+
+/// A fund's NAV per unit was republished to its feed
+#[event]
+pub struct NavPublished {
+    pub nav_feed: Pubkey,
+    pub fund: Pubkey,
+    pub price: u64,
+    pub expo: i32,
+    pub nav: u64,
+    pub units: u64,
+    pub publish_time: i64,
+}
+
+/// Program state passed post_upgrade_check for the deploy at `deploy_slot`, across every vault
+/// and index fund
+#[event]
+pub struct UpgradeChecked {
+    pub deploy_slot: u64,
+    pub vaults: u32,
+    pub index_funds: u32,
+    pub timestamp: i64,
+}
+
+/// Liquidity moved to a pool with a better realized APR
+#[event]
+pub struct LiquidityMigrated {
+    pub owner: Pubkey,
+    pub from_pool: Pubkey,
+    pub to_pool: Pubkey,
+    pub amount: u64,
+    pub from_apr_bps: u64,
+    pub to_apr_bps: u64,
+    pub base_out: u64,
+    pub quote_out: u64,
+    pub lp_out: u64,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+/// An owner moved a position from one venue's pool to another's
+#[event]
+pub struct PositionMigrated {
+    pub owner: Pubkey,
+    pub from_dex: DexType,
+    pub from_pool: Pubkey,
+    pub to_dex: DexType,
+    pub to_pool: Pubkey,
+    pub lp_amount: u64,
+    pub base_out: u64,
+    pub quote_out: u64,
+    pub lp_out: u64,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+/// A position was refused for exceeding the strategy's open position limit
+#[event]
+pub struct PositionRejected {
+    pub owner: Pubkey,
+    pub strategy: Pubkey,
+    // None for a limit order, keyed by the order, or a perp hedge, keyed by the market
+    pub dex: Option<DexType>,
+    pub market_id: Pubkey,
+    pub open_positions: u32,
+    pub max_open_positions: u32,
+}
+
+/// A failed block will be retried on the next crank
+#[event]
+pub struct BlockRetryScheduled {
+    pub strategy: Pubkey,
+    pub block_id: String,
+    pub failures: u8,
+    pub max_retries: u8,
+}
+
+/// A block out of retries handed over to its fallback block
+#[event]
+pub struct BlockFallbackTriggered {
+    pub strategy: Pubkey,
+    pub block_id: String,
+    pub fallback_block_id: String,
+}
+
+/// An arbitrage route was executed across two venues
+#[event]
+pub struct ArbitrageExecuted {
+    pub owner: Pubkey,
+    pub entry_dex: DexType,
+    pub exit_dex: DexType,
+    pub expected_profit: u64,
+    pub deadline: i64,
+}
+
+/// A buyer purchased from a token launch sale
+#[event]
+pub struct TokenLaunchPurchase {
+    pub launch: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub price: u64,
+    pub payment: u64,
+}
+
+/// A treasury plan moved funds towards one of its basket targets
+#[event]
+pub struct TreasuryDiversified {
+    pub plan: Pubkey,
+    pub mode: DiversificationMode,
+    pub target_mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// A strategy archived its performance metrics and started a new epoch
+#[event]
+pub struct EpochRolledOver {
+    pub strategy: Pubkey,
+    pub epoch: u32,
+    pub started_at: i64,
+    pub total_profit_loss: i64,
+    pub win_rate_bps: u64,
+    pub max_drawdown: u64,
+    pub timestamp: i64,
+}
+
+/// A strategy switched the currency its P&L and limits are kept in, restarting its metrics
+#[event]
+pub struct AccountingCurrencySet {
+    pub strategy: Pubkey,
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+/// A portfolio member's order was crossed internally against opposing members at the oracle mid
+#[event]
+pub struct OrderNetted {
+    pub portfolio: Pubkey,
+    pub strategy: Pubkey,
+    pub side: TradeSide,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub mid: u64,
+    pub timestamp: i64,
+}
+
+/// A netting order was closed; `residual` is what is left to route through a DEX
+#[event]
+pub struct NettingOrderClosed {
+    pub portfolio: Pubkey,
+    pub strategy: Pubkey,
+    pub side: TradeSide,
+    pub residual: u64,
+    pub filled_in: u64,
+    pub filled_out: u64,
+    pub timestamp: i64,
+}
+
+/// A vault's swap crossed listed limit orders; `remainder` is left for DEX routing
+#[event]
+pub struct InternalOrdersCrossed {
+    pub book: Pubkey,
+    pub taker: Pubkey,
+    pub taker_buys: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub remainder: u64,
+    pub timestamp: i64,
+}
+
+/// A breakeven block or trailing move tightened a position's stop
+#[event]
+pub struct StopMoved {
+    pub strategy: Pubkey,
+    pub position_id: Pubkey,
+    pub previous_stop: u64,
+    pub stop_price: u64,
+    pub at_breakeven: bool,
+    pub timestamp: i64,
+}
+
+/// A keeper swap's minimum out fell below the strategy's oracle floor and went on its record
+#[event]
+pub struct KeeperViolationRecorded {
+    pub keeper: Pubkey,
+    pub strategy: Pubkey,
+    pub minimum_out: u64,
+    pub oracle_min_out: u64,
+    pub slot: u64,
+}
+
+/// A strategy owner slashed part of a keeper's bond for a recorded violation
+#[event]
+pub struct KeeperSlashed {
+    pub keeper: Pubkey,
+    pub strategy: Pubkey,
+    pub amount: u64,
+    pub remaining_bond: u64,
+    pub timestamp: i64,
+}
+
+/// A keeper bid a rebate for the right to run a strategy's next execution
+#[event]
+pub struct ExecutionBidPlaced {
+    pub strategy: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub round_started_at: i64,
+    pub timestamp: i64,
+}
+
+/// An execution auction round ended and its winning bid went to the strategy owner
+#[event]
+pub struct ExecutionAuctionSettled {
+    pub strategy: Pubkey,
+    pub winner: Pubkey,
+    pub rebate: u64,
+    // false when the winner let its exclusivity lapse and forfeited the bid
+    pub executed: bool,
+    pub timestamp: i64,
+}
+
+/// A keeper sampled a pool's fees, TVL and mid price
+#[event]
+pub struct PoolSampled {
+    pub pool: Pubkey,
+    pub dex: DexType,
+    pub pair: TokenPair,
+    pub price: u64,
+    pub tvl: u64,
+    pub apr_bps: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,84 @@
+use crate::constants::{ACCOUNTING_MINTS, STRATEGY_SEED};
+use crate::events::AccountingCurrencySet;
+use crate::{
+    errors::TradingBotError,
+    state::{AccountingCurrency, PerformanceMetrics, RiskParameters, Strategy},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct SetAccountingCurrency<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=strategy.config.pair.quote_mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: USD feed of the pair's quote, validated on read; ignored when `mint` is the quote
+    quote_usd_feed: UncheckedAccount<'info>,
+
+    /// CHECK: USD feed of `mint`, validated on read; ignored when `mint` is the quote
+    mint_usd_feed: UncheckedAccount<'info>,
+}
+
+// Switches the currency the strategy keeps its books in. P&L kept in the old one can't be carried
+// over, so the performance metrics restart and the limits are replaced with ones in the new
+// currency. Only while the strategy is paused, so no execution straddles the switch.
+pub fn set_accounting_currency(
+    ctx: Context<SetAccountingCurrency>,
+    risk_parameters: RiskParameters,
+) -> Result<()> {
+    let strategy = &ctx.accounts.strategy;
+    let mint = ctx.accounts.mint.key();
+    let is_quote = mint == strategy.config.pair.quote_mint;
+    require!(!strategy.is_active, TradingBotError::InvalidStrategyConfig);
+    require!(
+        is_quote || ACCOUNTING_MINTS.contains(&mint),
+        TradingBotError::InvalidStrategyConfig
+    );
+    require!(
+        risk_parameters.is_valid(),
+        TradingBotError::InvalidStrategyConfig
+    );
+
+    let (quote_usd_feed, mint_usd_feed) = if is_quote {
+        (Pubkey::default(), Pubkey::default())
+    } else {
+        (
+            ctx.accounts.quote_usd_feed.key(),
+            ctx.accounts.mint_usd_feed.key(),
+        )
+    };
+    let now = Clock::get()?.unix_timestamp;
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.accounting = AccountingCurrency {
+        mint,
+        decimals: ctx.accounts.mint.decimals,
+        quote_decimals: ctx.accounts.quote_mint.decimals,
+        quote_usd_feed,
+        mint_usd_feed,
+    };
+    strategy.risk_parameters = risk_parameters;
+    strategy.performance_metrics = PerformanceMetrics::default();
+    strategy.updated_at = now;
+
+    emit!(AccountingCurrencySet {
+        strategy: strategy.key(),
+        mint,
+        timestamp: now,
+    });
+
+    Ok(())
+}
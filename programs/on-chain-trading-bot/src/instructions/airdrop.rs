@@ -1,4 +1,5 @@
-use crate::{errors::EscrowErrors, state::Escrow};
+use crate::constants::ESCROW_SEED;
+use crate::{errors::TradingBotError, state::Escrow};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -16,6 +17,8 @@ pub struct Airdrop<'info> {
     #[account(
       mut,
       constraint=escrow.user==user.key(),
+      seeds = [ESCROW_SEED, escrow.user.as_ref(), escrow.input_mint.as_ref(), escrow.output_mint.as_ref(), escrow.idx.to_le_bytes().as_ref()],
+      bump = escrow.bump,
     )]
     escrow: Box<Account<'info, Escrow>>,
 
@@ -26,7 +29,7 @@ pub struct Airdrop<'info> {
 
     #[account(
       mut,
-      associated_token::authority=user,
+      associated_token::authority=admin,
       associated_token::mint=output_mint,
     )]
     admin_token_account: Box<Account<'info, TokenAccount>>,
@@ -49,22 +52,22 @@ pub fn airdrop(ctx: Context<Airdrop>) -> Result<()> {
     require_eq!(
         ctx.accounts.escrow.completed,
         true,
-        EscrowErrors::DCANotComplete
+        TradingBotError::DCANotComplete
     );
     require_eq!(
         ctx.accounts.escrow.airdropped,
         false,
-        EscrowErrors::Airdropped
+        TradingBotError::Airdropped
     );
     require_gt!(
         ctx.accounts.escrow.airdrop_amount,
         0,
-        EscrowErrors::UnexpectedAirdropAmount
+        TradingBotError::UnexpectedAirdropAmount
     );
     require_gte!(
         ctx.accounts.admin_token_account.amount,
         ctx.accounts.escrow.airdrop_amount,
-        EscrowErrors::InsufficientBalance
+        TradingBotError::InsufficientBalance
     );
 
     msg!("Recording airdrop");
@@ -0,0 +1,504 @@
+use crate::bridge::{
+    BridgeMessage, WormholeBridge, COMPLETE_VAA_ACCOUNT, TRANSFER_MESSAGE_ACCOUNT,
+    WORMHOLE_CHAIN_ID_SOLANA,
+};
+use crate::constants::{BRIDGE_TRANSFER_SEED, CONSUMED_SEQUENCE_SEED, STRATEGY_SEED};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        BridgeDirection, BridgeStatus, BridgeTransfer, BridgeTransferParams, ConsumedSequence,
+        Strategy,
+    },
+    strategy_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Approve, Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(params: BridgeTransferParams)]
+pub struct InitiateBridgeTransfer<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      token::authority=strategy,
+    )]
+    from_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      address=from_token_account.mint
+    )]
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = BridgeTransfer::LEN,
+      seeds = [
+        BRIDGE_TRANSFER_SEED,
+        strategy.key().as_ref(),
+        params.nonce.to_le_bytes().as_ref(),
+      ],
+      bump
+    )]
+    bridge_transfer: Box<Account<'info, BridgeTransfer>>,
+
+    /// CHECK: Checked against the Wormhole token bridge program id
+    token_bridge_program: UncheckedAccount<'info>,
+
+    /// CHECK: Token bridge authority PDA, delegated the transfer amount
+    authority_signer: UncheckedAccount<'info>,
+
+    /// CHECK: The token bridge emitter's Wormhole sequence, read before the transfer is posted
+    #[account(
+      address=WormholeBridge::token_bridge_sequence()
+    )]
+    wormhole_sequence: UncheckedAccount<'info>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: BridgeTransferParams)]
+pub struct RegisterInboundTransfer<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      token::authority=strategy,
+    )]
+    to_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = BridgeTransfer::LEN,
+      seeds = [
+        BRIDGE_TRANSFER_SEED,
+        strategy.key().as_ref(),
+        params.nonce.to_le_bytes().as_ref(),
+      ],
+      bump
+    )]
+    bridge_transfer: Box<Account<'info, BridgeTransfer>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(sequence: u64)]
+pub struct CompleteBridgeTransfer<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [
+        BRIDGE_TRANSFER_SEED,
+        bridge_transfer.strategy.as_ref(),
+        bridge_transfer.nonce.to_le_bytes().as_ref(),
+      ],
+      bump = bridge_transfer.bump,
+    )]
+    bridge_transfer: Box<Account<'info, BridgeTransfer>>,
+
+    // one per emitting chain and sequence, so a message completes a single transfer
+    #[account(
+      init,
+      payer = owner,
+      space = ConsumedSequence::LEN,
+      seeds = [
+        CONSUMED_SEQUENCE_SEED,
+        bridge_transfer.emitter_chain().to_le_bytes().as_ref(),
+        sequence.to_le_bytes().as_ref(),
+      ],
+      bump
+    )]
+    consumed_sequence: Box<Account<'info, ConsumedSequence>>,
+
+    #[account(
+      mut,
+      address=bridge_transfer.token_account
+    )]
+    token_account: Box<Account<'info, TokenAccount>>,
+
+    // its decimals normalize amounts the way the token bridge carries them
+    #[account(
+      address=bridge_transfer.mint
+    )]
+    mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: The transfer's VAA as posted by the core bridge, read and matched on completion
+    posted_vaa: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the Wormhole token bridge program id
+    token_bridge_program: UncheckedAccount<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+// A transfer message has to carry the recorded token, amount and fee, as the token bridge
+// truncates them, and go where the transfer said: an outbound one from the token bridge here to
+// the foreign address, an inbound one from the recorded chain to the token account here
+fn check_transfer_message(
+    bridge_transfer: &BridgeTransfer,
+    message: &BridgeMessage,
+    decimals: u8,
+) -> Result<()> {
+    require!(
+        message.token_chain == bridge_transfer.token_chain
+            && message.token_address == bridge_transfer.token_address
+            && message.amount == WormholeBridge::normalize(bridge_transfer.amount, decimals)
+            && message.fee == WormholeBridge::normalize(bridge_transfer.relayer_fee, decimals),
+        TradingBotError::InvalidWormholeMessage
+    );
+    let matches = match bridge_transfer.direction {
+        BridgeDirection::Outbound => {
+            message.emitter_chain == WORMHOLE_CHAIN_ID_SOLANA
+                && message.emitter_address == WormholeBridge::token_bridge_emitter().to_bytes()
+                && message.sequence == bridge_transfer.sequence
+                && message.to == bridge_transfer.foreign_address
+                && message.to_chain == bridge_transfer.chain
+        }
+        BridgeDirection::Inbound => {
+            message.emitter_chain == bridge_transfer.chain
+                && message.to == bridge_transfer.token_account.to_bytes()
+                && message.to_chain == WORMHOLE_CHAIN_ID_SOLANA
+        }
+    };
+    require!(matches, TradingBotError::InvalidWormholeMessage);
+    Ok(())
+}
+
+// Bridges move a strategy's own funds: its PDA delegates the amount out of one of its token
+// accounts, and the transfer is tracked until its message is signed
+pub fn initiate_bridge_transfer(
+    ctx: Context<InitiateBridgeTransfer>,
+    params: BridgeTransferParams,
+) -> Result<()> {
+    WormholeBridge::validate_target_chain(params.chain)?;
+    require!(
+        !ctx.accounts.strategy.executing,
+        TradingBotError::ExecutionInProgress
+    );
+    require_keys_eq!(
+        ctx.accounts.mint.key(),
+        WormholeBridge::origin_mint(params.token_chain, params.token_address),
+        TradingBotError::BridgeTokenMismatch
+    );
+    require_gt!(params.amount, 0, TradingBotError::InvalidCalculation);
+    require_gt!(
+        params.amount,
+        params.relayer_fee,
+        TradingBotError::InvalidCalculation
+    );
+    require_gte!(
+        ctx.accounts.from_token_account.amount,
+        params.amount,
+        TradingBotError::InvalidCalculation
+    );
+
+    msg!("Approve token bridge");
+    let strategy = &ctx.accounts.strategy;
+    anchor_spl::token::approve(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.from_token_account.to_account_info(),
+                delegate: ctx.accounts.authority_signer.to_account_info(),
+                authority: strategy.to_account_info(),
+            },
+            &[strategy_seeds!(strategy)],
+        ),
+        params.amount,
+    )?;
+
+    let sequence = WormholeBridge::read_sequence(&ctx.accounts.wormhole_sequence)?;
+    let message = ctx
+        .remaining_accounts
+        .get(TRANSFER_MESSAGE_ACCOUNT)
+        .ok_or(TradingBotError::InvalidWormholeMessage)?;
+
+    msg!("CPI call to token bridge transfer");
+    WormholeBridge::invoke_token_bridge(
+        &ctx.accounts.token_bridge_program,
+        ctx.remaining_accounts,
+        WormholeBridge::transfer_ix_data(
+            params.token_chain != WORMHOLE_CHAIN_ID_SOLANA,
+            params.nonce,
+            params.amount,
+            params.relayer_fee,
+            params.foreign_address,
+            params.chain,
+        ),
+    )?;
+
+    let bridge_transfer = &mut ctx.accounts.bridge_transfer;
+    bridge_transfer.owner = ctx.accounts.owner.key();
+    bridge_transfer.strategy = ctx.accounts.strategy.key();
+    bridge_transfer.token_account = ctx.accounts.from_token_account.key();
+    bridge_transfer.mint = ctx.accounts.mint.key();
+    bridge_transfer.token_chain = params.token_chain;
+    bridge_transfer.token_address = params.token_address;
+    bridge_transfer.nonce = params.nonce;
+    bridge_transfer.direction = BridgeDirection::Outbound;
+    bridge_transfer.status = BridgeStatus::Pending;
+    bridge_transfer.chain = params.chain;
+    bridge_transfer.foreign_address = params.foreign_address;
+    bridge_transfer.amount = params.amount;
+    bridge_transfer.relayer_fee = params.relayer_fee;
+    bridge_transfer.sequence = sequence;
+    bridge_transfer.balance_before = ctx.accounts.from_token_account.amount;
+    bridge_transfer.created_at = Clock::get()?.unix_timestamp;
+    bridge_transfer.completed_at = 0;
+    bridge_transfer.bump = *ctx.bumps.get("bridge_transfer").unwrap();
+
+    // the message the token bridge just posted is what the guardians will sign
+    check_transfer_message(
+        bridge_transfer,
+        &WormholeBridge::read_posted_message(message)?,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    msg!("Bridge transfer posted with sequence {}", sequence);
+    Ok(())
+}
+
+// Inbound funds land back in one of the strategy's token accounts, which has to hold the token
+// the transfer names
+pub fn register_inbound_transfer(
+    ctx: Context<RegisterInboundTransfer>,
+    params: BridgeTransferParams,
+) -> Result<()> {
+    WormholeBridge::validate_target_chain(params.chain)?;
+    require_gt!(params.amount, 0, TradingBotError::InvalidCalculation);
+    require_keys_eq!(
+        ctx.accounts.to_token_account.mint,
+        WormholeBridge::origin_mint(params.token_chain, params.token_address),
+        TradingBotError::BridgeTokenMismatch
+    );
+
+    let bridge_transfer = &mut ctx.accounts.bridge_transfer;
+    bridge_transfer.owner = ctx.accounts.owner.key();
+    bridge_transfer.strategy = ctx.accounts.strategy.key();
+    bridge_transfer.token_account = ctx.accounts.to_token_account.key();
+    bridge_transfer.mint = ctx.accounts.to_token_account.mint;
+    bridge_transfer.token_chain = params.token_chain;
+    bridge_transfer.token_address = params.token_address;
+    bridge_transfer.nonce = params.nonce;
+    bridge_transfer.direction = BridgeDirection::Inbound;
+    bridge_transfer.status = BridgeStatus::Pending;
+    bridge_transfer.chain = params.chain;
+    bridge_transfer.foreign_address = params.foreign_address;
+    bridge_transfer.amount = params.amount;
+    bridge_transfer.relayer_fee = params.relayer_fee;
+    bridge_transfer.sequence = 0;
+    bridge_transfer.balance_before = ctx.accounts.to_token_account.amount;
+    bridge_transfer.created_at = Clock::get()?.unix_timestamp;
+    bridge_transfer.completed_at = 0;
+    bridge_transfer.bump = *ctx.bumps.get("bridge_transfer").unwrap();
+
+    Ok(())
+}
+
+// Both directions complete against a VAA the core bridge has verified the guardian signatures of.
+// An outbound transfer's VAA is its own posted message, which the target chain can now redeem.
+// An inbound transfer is redeemed here through the token bridge, which has to be handed that same
+// VAA, and the token account has to receive its amount less the fee. The VAA's sequence is
+// recorded as consumed either way.
+pub fn complete_bridge_transfer(
+    ctx: Context<CompleteBridgeTransfer>,
+    sequence: u64,
+    redeem_data: Vec<u8>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.bridge_transfer.status == BridgeStatus::Pending,
+        TradingBotError::BridgeTransferCompleted
+    );
+    let message = WormholeBridge::read_posted_vaa(&ctx.accounts.posted_vaa)?;
+    require_eq!(
+        message.sequence,
+        sequence,
+        TradingBotError::InvalidWormholeMessage
+    );
+    let decimals = ctx.accounts.mint.decimals;
+    check_transfer_message(&ctx.accounts.bridge_transfer, &message, decimals)?;
+
+    if ctx.accounts.bridge_transfer.direction == BridgeDirection::Inbound {
+        require!(
+            WormholeBridge::is_complete_transfer(&redeem_data),
+            TradingBotError::InvalidWormholeMessage
+        );
+        WormholeBridge::require_cpi_account(
+            ctx.remaining_accounts,
+            COMPLETE_VAA_ACCOUNT,
+            ctx.accounts.posted_vaa.key,
+        )?;
+        let balance_before = ctx.accounts.token_account.amount;
+
+        msg!("CPI call to token bridge complete transfer");
+        WormholeBridge::invoke_token_bridge(
+            &ctx.accounts.token_bridge_program,
+            ctx.remaining_accounts,
+            redeem_data,
+        )?;
+
+        ctx.accounts.token_account.reload()?;
+        let received = math::checked_sub(ctx.accounts.token_account.amount, balance_before)?;
+        let expected = WormholeBridge::denormalize(
+            math::checked_sub(message.amount, message.fee)?,
+            decimals,
+        )?;
+        require_gte!(received, expected, TradingBotError::InvalidCalculation);
+        ctx.accounts.bridge_transfer.sequence = message.sequence;
+    }
+
+    let bridge_transfer = &mut ctx.accounts.bridge_transfer;
+    bridge_transfer.status = BridgeStatus::Completed;
+    bridge_transfer.completed_at = Clock::get()?.unix_timestamp;
+
+    let consumed_sequence = &mut ctx.accounts.consumed_sequence;
+    consumed_sequence.bridge_transfer = bridge_transfer.key();
+    consumed_sequence.bump = *ctx.bumps.get("consumed_sequence").unwrap();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ETHEREUM: u16 = 2;
+
+    fn inbound() -> BridgeTransfer {
+        BridgeTransfer {
+            owner: Pubkey::new_unique(),
+            strategy: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            token_chain: ETHEREUM,
+            token_address: [3; 32],
+            nonce: 1,
+            direction: BridgeDirection::Inbound,
+            status: BridgeStatus::Pending,
+            chain: ETHEREUM,
+            foreign_address: [5; 32],
+            // 12.5 of a 9-decimal token, less a 0.01 fee
+            amount: 12_500_000_000,
+            relayer_fee: 10_000_000,
+            sequence: 0,
+            balance_before: 0,
+            created_at: 0,
+            completed_at: 0,
+            bump: 255,
+        }
+    }
+
+    // As the token bridge would carry `transfer`, in 8 decimals
+    fn message(transfer: &BridgeTransfer) -> BridgeMessage {
+        BridgeMessage {
+            sequence: 42,
+            emitter_chain: ETHEREUM,
+            emitter_address: [7; 32],
+            amount: 1_250_000_000,
+            token_address: transfer.token_address,
+            token_chain: transfer.token_chain,
+            to: transfer.token_account.to_bytes(),
+            to_chain: WORMHOLE_CHAIN_ID_SOLANA,
+            fee: 1_000_000,
+        }
+    }
+
+    #[test]
+    fn inbound_message_matches_transfer() {
+        let transfer = inbound();
+        assert!(check_transfer_message(&transfer, &message(&transfer), 9).is_ok());
+    }
+
+    #[test]
+    fn inbound_message_for_another_token() {
+        let transfer = inbound();
+        let mut other_address = message(&transfer);
+        other_address.token_address = [4; 32];
+        assert!(check_transfer_message(&transfer, &other_address, 9).is_err());
+
+        // the same address on another chain is a different token
+        let mut other_chain = message(&transfer);
+        other_chain.token_chain = 4;
+        assert!(check_transfer_message(&transfer, &other_chain, 9).is_err());
+    }
+
+    #[test]
+    fn inbound_message_to_another_recipient_or_from_another_chain() {
+        let transfer = inbound();
+        let mut recipient = message(&transfer);
+        recipient.to = Pubkey::new_unique().to_bytes();
+        assert!(check_transfer_message(&transfer, &recipient, 9).is_err());
+
+        let mut to_chain = message(&transfer);
+        to_chain.to_chain = ETHEREUM;
+        assert!(check_transfer_message(&transfer, &to_chain, 9).is_err());
+
+        let mut emitter = message(&transfer);
+        emitter.emitter_chain = 4;
+        assert!(check_transfer_message(&transfer, &emitter, 9).is_err());
+    }
+
+    #[test]
+    fn amounts_compare_in_bridge_decimals() {
+        let transfer = inbound();
+        // the untruncated 9-decimal amount isn't what the token bridge carries
+        let mut native = message(&transfer);
+        native.amount = transfer.amount;
+        assert!(check_transfer_message(&transfer, &native, 9).is_err());
+
+        // a 6-decimal token is carried as is
+        let mut six = inbound();
+        six.amount = 12_500_000;
+        six.relayer_fee = 10_000;
+        let mut carried = message(&six);
+        carried.amount = 12_500_000;
+        carried.fee = 10_000;
+        assert!(check_transfer_message(&six, &carried, 6).is_ok());
+        carried.fee = 1;
+        assert!(check_transfer_message(&six, &carried, 6).is_err());
+    }
+
+    #[test]
+    fn outbound_message_matches_posted_sequence() {
+        let mut transfer = inbound();
+        transfer.direction = BridgeDirection::Outbound;
+        transfer.token_chain = WORMHOLE_CHAIN_ID_SOLANA;
+        transfer.token_address = transfer.mint.to_bytes();
+        transfer.sequence = 42;
+        assert_eq!(transfer.emitter_chain(), WORMHOLE_CHAIN_ID_SOLANA);
+
+        let mut posted = message(&transfer);
+        posted.emitter_chain = WORMHOLE_CHAIN_ID_SOLANA;
+        posted.emitter_address = WormholeBridge::token_bridge_emitter().to_bytes();
+        posted.to = transfer.foreign_address;
+        posted.to_chain = ETHEREUM;
+        assert!(check_transfer_message(&transfer, &posted, 9).is_ok());
+
+        posted.sequence = 43;
+        assert!(check_transfer_message(&transfer, &posted, 9).is_err());
+    }
+}
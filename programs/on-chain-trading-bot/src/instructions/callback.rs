@@ -0,0 +1,195 @@
+use crate::constants::{CALLBACK_CONFIG_SEED, CALLBACK_REQUEST_SEED, STRATEGY_SEED};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        CallbackCondition, CallbackConfig, CallbackRequest, CallbackRule, NotifyChannel, Strategy,
+        MAX_CALLBACK_RULES,
+    },
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitCallbackConfig<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = CallbackConfig::LEN,
+      seeds = [CALLBACK_CONFIG_SEED, strategy.key().as_ref()],
+      bump
+    )]
+    config: Box<Account<'info, CallbackConfig>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageCallbackConfig<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [CALLBACK_CONFIG_SEED, config.strategy.as_ref()],
+      bump = config.bump,
+    )]
+    config: Box<Account<'info, CallbackConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct FireCallback<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    #[account(
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      has_one=strategy,
+      seeds = [CALLBACK_CONFIG_SEED, strategy.key().as_ref()],
+      bump = config.bump,
+    )]
+    config: Box<Account<'info, CallbackConfig>>,
+
+    #[account(
+      init,
+      payer = payer,
+      space = CallbackRequest::LEN,
+      seeds = [CALLBACK_REQUEST_SEED, config.key().as_ref(), config.next_seq.to_le_bytes().as_ref()],
+      bump
+    )]
+    request: Box<Account<'info, CallbackRequest>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RearmCallbacks<'info> {
+    #[account(
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      has_one=strategy,
+      seeds = [CALLBACK_CONFIG_SEED, strategy.key().as_ref()],
+      bump = config.bump,
+    )]
+    config: Box<Account<'info, CallbackConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct AckCallbackRequest<'info> {
+    relay: Signer<'info>,
+
+    #[account(
+      has_one=relay,
+      seeds = [CALLBACK_CONFIG_SEED, config.strategy.as_ref()],
+      bump = config.bump,
+    )]
+    config: Box<Account<'info, CallbackConfig>>,
+
+    #[account(
+      mut,
+      close=payer,
+      has_one=config,
+      has_one=payer,
+      seeds = [CALLBACK_REQUEST_SEED, config.key().as_ref(), request.seq.to_le_bytes().as_ref()],
+      bump = request.bump,
+    )]
+    request: Box<Account<'info, CallbackRequest>>,
+
+    /// CHECK: Whoever fired the request, refunded its rent
+    #[account(mut)]
+    payer: UncheckedAccount<'info>,
+}
+
+pub fn init_callback_config(ctx: Context<InitCallbackConfig>, relay: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.strategy = ctx.accounts.strategy.key();
+    config.owner = ctx.accounts.owner.key();
+    config.relay = relay;
+    config.rules = [None; MAX_CALLBACK_RULES];
+    config.next_seq = 0;
+    config.bump = *ctx.bumps.get("config").unwrap();
+
+    Ok(())
+}
+
+// `condition` None clears the slot; a set rule starts armed
+pub fn set_callback_rule(
+    ctx: Context<ManageCallbackConfig>,
+    rule_index: u8,
+    condition: Option<CallbackCondition>,
+    channel: NotifyChannel,
+) -> Result<()> {
+    let slot = ctx
+        .accounts
+        .config
+        .rules
+        .get_mut(rule_index as usize)
+        .ok_or(TradingBotError::InvalidCallbackRule)?;
+    *slot = condition.map(|condition| CallbackRule {
+        condition,
+        channel,
+        armed: true,
+    });
+    Ok(())
+}
+
+pub fn set_callback_relay(ctx: Context<ManageCallbackConfig>, relay: Pubkey) -> Result<()> {
+    ctx.accounts.config.relay = relay;
+    Ok(())
+}
+
+// Writes a request for an armed rule whose condition holds now and disarms the rule
+pub fn fire_callback(ctx: Context<FireCallback>, rule_index: u8) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &mut ctx.accounts.config;
+    let (rule, value) = config.fire(rule_index as usize, &ctx.accounts.strategy, now)?;
+    let seq = config.next_seq;
+    config.next_seq = math::checked_add(seq, 1)?;
+
+    let request = &mut ctx.accounts.request;
+    request.config = config.key();
+    request.strategy = config.strategy;
+    request.seq = seq;
+    request.rule_index = rule_index;
+    request.condition = rule.condition;
+    request.channel = rule.channel;
+    request.value = value;
+    request.payer = ctx.accounts.payer.key();
+    request.created_at = now;
+    request.bump = *ctx.bumps.get("request").unwrap();
+
+    msg!("Callback #{} for {:?} at {}", seq, rule.condition, value);
+    Ok(())
+}
+
+pub fn rearm_callbacks(ctx: Context<RearmCallbacks>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let rearmed = ctx.accounts.config.rearm(&ctx.accounts.strategy, now)?;
+    msg!("Re-armed {} callback rules", rearmed);
+    Ok(())
+}
+
+// Called by the relay once the notification is delivered
+pub fn ack_callback_request(_ctx: Context<AckCallbackRequest>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use crate::constants::{FEED_REGISTRY_SEED, MARKET_MAKER_CLAIM_SEED, MARKET_MAKER_SEED};
+use crate::instructions::InitMarketMaker;
+use crate::{
+    errors::TradingBotError,
+    state::{FeedRegistry, MarketMaker, MarketMakerClaim, MarketMakerOverrides},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+#[instruction(variant: u16)]
+pub struct CloneMarketMaker<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [MARKET_MAKER_SEED, source.owner.as_ref(), source.market.as_ref(), source.variant.to_le_bytes().as_ref()],
+      bump = source.bump,
+    )]
+    source: Box<Account<'info, MarketMaker>>,
+
+    /// CHECK: Variant's own open orders account on the source market, claimed for the variant
+    open_orders: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the source's feed
+    #[account(
+      address=source.price_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    // the source's wallets, whose mints the variant's have to hold
+    #[account(
+      address=source.base_wallet
+    )]
+    source_base_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      address=source.quote_wallet
+    )]
+    source_quote_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      token::mint=source_base_wallet.mint,
+      token::authority=owner,
+    )]
+    base_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      token::mint=source_quote_wallet.mint,
+      token::authority=owner,
+    )]
+    quote_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = MarketMaker::LEN,
+      seeds = [MARKET_MAKER_SEED, owner.key().as_ref(), source.market.as_ref(), variant.to_le_bytes().as_ref()],
+      bump
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    // held from the template's init on, so no variant reuses another's open orders or wallets
+    #[account(
+      init,
+      payer = owner,
+      space = MarketMakerClaim::LEN,
+      seeds = [MARKET_MAKER_CLAIM_SEED, open_orders.key().as_ref()],
+      bump
+    )]
+    open_orders_claim: Box<Account<'info, MarketMakerClaim>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = MarketMakerClaim::LEN,
+      seeds = [MARKET_MAKER_CLAIM_SEED, base_wallet.key().as_ref()],
+      bump
+    )]
+    base_wallet_claim: Box<Account<'info, MarketMakerClaim>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = MarketMakerClaim::LEN,
+      seeds = [MARKET_MAKER_CLAIM_SEED, quote_wallet.key().as_ref()],
+      bump
+    )]
+    quote_wallet_claim: Box<Account<'info, MarketMakerClaim>>,
+
+    system_program: Program<'info, System>,
+}
+
+impl<'info> CloneMarketMaker<'info> {
+    // Clones of clones still point at the original template
+    pub fn template_of(source: &MarketMaker, source_key: Pubkey) -> Pubkey {
+        if source.variant == MarketMaker::TEMPLATE_VARIANT {
+            source_key
+        } else {
+            source.template
+        }
+    }
+}
+
+// A/B variant of an existing market maker: same market, feed and strategy, with its own
+// wallets, open orders and runtime state, so variants never trade against each other's funds.
+// Only market makers are cloned; block strategies keep their programs to themselves.
+pub fn clone_market_maker(
+    ctx: Context<CloneMarketMaker>,
+    variant: u16,
+    overrides: MarketMakerOverrides,
+) -> Result<()> {
+    require!(
+        variant != MarketMaker::TEMPLATE_VARIANT,
+        TradingBotError::InvalidMarketMakingParams
+    );
+
+    let source = &ctx.accounts.source;
+    let mut params = source.params();
+    overrides.apply(&mut params);
+    InitMarketMaker::validate_params(&params)?;
+
+    let initial_nav = InitMarketMaker::initial_nav(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.price_feed,
+        params.max_confidence,
+        &ctx.accounts.base_wallet,
+        &ctx.accounts.quote_wallet,
+    )?;
+    let template = CloneMarketMaker::template_of(source, source.key());
+    msg!("Cloning {} as variant {}", template, variant);
+
+    let market_maker = &mut ctx.accounts.market_maker;
+    market_maker.owner = source.owner;
+    market_maker.strategy_type = source.strategy_type;
+    market_maker.market = source.market;
+    market_maker.variant = variant;
+    market_maker.template = template;
+    market_maker.open_orders = ctx.accounts.open_orders.key();
+    market_maker.price_feed = source.price_feed;
+    market_maker.base_wallet = ctx.accounts.base_wallet.key();
+    market_maker.quote_wallet = ctx.accounts.quote_wallet.key();
+    market_maker.apply_params(&params);
+    market_maker.last_quote_mid = 0;
+    market_maker.last_bid_price = 0;
+    market_maker.last_ask_price = 0;
+    market_maker.last_quote_at = 0;
+    market_maker.quote_expires_at = 0;
+    market_maker.quote_seq = 0;
+    market_maker.perp_position = 0;
+    market_maker.perp_entry_price = 0;
+    market_maker.hedge_count = 0;
+    market_maker.hedge_volume = 0;
+    market_maker.hedge_cost = 0;
+    market_maker.hedge_fees = 0;
+    market_maker.initial_nav = initial_nav;
+    market_maker.net_contributions = initial_nav as i64;
+    market_maker.is_active = true;
+    market_maker.bump = *ctx.bumps.get("market_maker").unwrap();
+
+    let market_maker = market_maker.key();
+    let bumps = &ctx.bumps;
+    ctx.accounts.open_orders_claim.claim(market_maker, *bumps.get("open_orders_claim").unwrap());
+    ctx.accounts.base_wallet_claim.claim(market_maker, *bumps.get("base_wallet_claim").unwrap());
+    ctx.accounts.quote_wallet_claim.claim(market_maker, *bumps.get("quote_wallet_claim").unwrap());
+
+    Ok(())
+}
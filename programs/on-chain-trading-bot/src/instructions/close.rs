@@ -1,5 +1,5 @@
 use crate::constants::{AIRDROP_BPS, ESCROW_SEED};
-use crate::{errors::EscrowErrors, escrow_seeds, math, state::Escrow};
+use crate::{errors::TradingBotError, escrow_seeds, math, state::Escrow};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -21,17 +21,25 @@ pub struct Close<'info> {
     #[account(mut)]
     user: Signer<'info>,
 
+    /// CHECK: Receives the DCA output, checked against the escrow
+    #[account(
+      address=escrow.beneficiary
+    )]
+    beneficiary: UncheckedAccount<'info>,
+
     #[account(
       init_if_needed,
       payer=user,
-      associated_token::authority=user,
+      associated_token::authority=beneficiary,
       associated_token::mint=output_mint,
     )]
-    user_token_account: Box<Account<'info, TokenAccount>>,
+    beneficiary_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(
       mut,
       constraint=escrow.user==user.key(),
+      seeds = [ESCROW_SEED, escrow.user.as_ref(), escrow.input_mint.as_ref(), escrow.output_mint.as_ref(), escrow.idx.to_le_bytes().as_ref()],
+      bump = escrow.bump,
     )]
     escrow: Box<Account<'info, Escrow>>,
 
@@ -74,12 +82,12 @@ impl<'info> Close<'info> {
 
 pub fn close(ctx: Context<Close>) -> Result<()> {
     // Checks that the DCA account is done and closed before closing escrow account
-    require_eq!(ctx.accounts.dca.lamports(), 0, EscrowErrors::DCANotClosed);
+    require_eq!(ctx.accounts.dca.lamports(), 0, TradingBotError::DCANotClosed);
 
     require_eq!(
         ctx.accounts.escrow_in_ata.amount,
         0,
-        EscrowErrors::UnexpectedBalance
+        TradingBotError::UnexpectedBalance
     );
 
     let escrow = &mut ctx.accounts.escrow;
@@ -90,7 +98,7 @@ pub fn close(ctx: Context<Close>) -> Result<()> {
     let idx_bytes = ctx.accounts.escrow.idx.to_le_bytes();
     let signer_seeds: &[&[&[u8]]] = &[escrow_seeds!(ctx.accounts.escrow, idx_bytes)];
 
-    // transfer out tokens to user
+    // transfer out tokens to the beneficiary
     // if it's native SOL, it will get returned to user when the account is closed
     if ctx.accounts.escrow_out_ata.amount > 0 {
         anchor_spl::token::transfer(
@@ -98,7 +106,7 @@ pub fn close(ctx: Context<Close>) -> Result<()> {
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.escrow_out_ata.to_account_info(),
-                    to: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.beneficiary_token_account.to_account_info(),
                     authority: ctx.accounts.escrow.to_account_info(),
                 },
                 signer_seeds,
@@ -0,0 +1,172 @@
+use crate::bot_strategy::StrategyBlock;
+use crate::constants::{COMPUTE_PROFILE_SEED, FEE_ORACLE_SEED, STRATEGY_SEED};
+use crate::{
+    errors::TradingBotError,
+    state::{
+        ComputeProfile, ComputeSample, ComputeStats, FeeOracle, FeePercentile, Strategy,
+        BLOCK_KINDS, MAX_COMPUTE_UNITS,
+    },
+};
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ComputeEstimate {
+    pub strategy: Pubkey,
+    // what to pass to ComputeBudget's SetComputeUnitLimit
+    pub compute_units: u64,
+    // blocks priced at the default because their kind has no samples yet
+    pub unprofiled_blocks: u8,
+    // most the keeper should be reimbursed in priority fees, in lamports, when the profile
+    // references a FeeOracle
+    pub max_priority_fee: Option<u64>,
+}
+
+#[derive(Accounts)]
+pub struct InitComputeProfile<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = ComputeProfile::LEN,
+      seeds = [COMPUTE_PROFILE_SEED, strategy.key().as_ref()],
+      bump
+    )]
+    compute_profile: Box<Account<'info, ComputeProfile>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordComputeUsage<'info> {
+    reporter: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=reporter,
+      seeds = [COMPUTE_PROFILE_SEED, compute_profile.strategy.as_ref()],
+      bump = compute_profile.bump,
+    )]
+    compute_profile: Box<Account<'info, ComputeProfile>>,
+}
+
+#[derive(Accounts)]
+pub struct SetComputeFeeOracle<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [COMPUTE_PROFILE_SEED, compute_profile.strategy.as_ref()],
+      bump = compute_profile.bump,
+    )]
+    compute_profile: Box<Account<'info, ComputeProfile>>,
+
+    #[account(
+      seeds = [FEE_ORACLE_SEED, fee_oracle.authority.as_ref()],
+      bump = fee_oracle.bump,
+    )]
+    fee_oracle: Option<Box<Account<'info, FeeOracle>>>,
+}
+
+#[derive(Accounts)]
+pub struct ViewComputeEstimate<'info> {
+    #[account(
+      seeds = [COMPUTE_PROFILE_SEED, compute_profile.strategy.as_ref()],
+      bump = compute_profile.bump,
+    )]
+    compute_profile: Box<Account<'info, ComputeProfile>>,
+
+    #[account(address = compute_profile.fee_oracle)]
+    fee_oracle: Option<Box<Account<'info, FeeOracle>>>,
+}
+
+pub fn init_compute_profile(
+    ctx: Context<InitComputeProfile>,
+    reporter: Pubkey,
+    default_block_cu: u64,
+    margin_bps: u16,
+) -> Result<()> {
+    require!(
+        default_block_cu > 0 && default_block_cu <= MAX_COMPUTE_UNITS,
+        TradingBotError::InvalidStrategyConfig
+    );
+    require_gte!(10000, margin_bps, TradingBotError::InvalidStrategyConfig);
+
+    let compute_profile = &mut ctx.accounts.compute_profile;
+    compute_profile.strategy = ctx.accounts.strategy.key();
+    compute_profile.owner = ctx.accounts.owner.key();
+    compute_profile.reporter = reporter;
+    compute_profile.overhead = ComputeStats::default();
+    compute_profile.blocks = [ComputeStats::default(); BLOCK_KINDS];
+    compute_profile.default_block_cu = default_block_cu;
+    compute_profile.margin_bps = margin_bps;
+    compute_profile.fee_oracle = Pubkey::default();
+    compute_profile.fee_percentile = FeePercentile::P75;
+    compute_profile.bump = *ctx.bumps.get("compute_profile").unwrap();
+
+    Ok(())
+}
+
+// One profiled execution: its overhead outside the blocks and what each block used
+pub fn record_compute_usage(
+    ctx: Context<RecordComputeUsage>,
+    overhead_cu: u64,
+    samples: Vec<ComputeSample>,
+) -> Result<()> {
+    for sample in samples.iter() {
+        require_gte!(
+            MAX_COMPUTE_UNITS,
+            sample.compute_units,
+            TradingBotError::InvalidCalculation
+        );
+    }
+    ctx.accounts.compute_profile.record(overhead_cu, &samples)
+}
+
+// Omitting the fee oracle clears the reference
+pub fn set_compute_fee_oracle(
+    ctx: Context<SetComputeFeeOracle>,
+    fee_percentile: FeePercentile,
+) -> Result<()> {
+    let compute_profile = &mut ctx.accounts.compute_profile;
+    compute_profile.fee_oracle = ctx
+        .accounts
+        .fee_oracle
+        .as_ref()
+        .map_or(Pubkey::default(), |fee_oracle| fee_oracle.key());
+    compute_profile.fee_percentile = fee_percentile;
+
+    Ok(())
+}
+
+// Read-only, like view_strategy_summary. `blocks` are the ones the keeper is about to execute.
+pub fn estimated_cu_for_next_execution(
+    ctx: Context<ViewComputeEstimate>,
+    blocks: Vec<StrategyBlock>,
+) -> Result<ComputeEstimate> {
+    let compute_profile = &ctx.accounts.compute_profile;
+    let (compute_units, unprofiled_blocks) = compute_profile.estimate(&blocks)?;
+    let max_priority_fee = match ctx.accounts.fee_oracle.as_ref() {
+        Some(fee_oracle) => Some(fee_oracle.max_priority_fee(
+            compute_profile.fee_percentile,
+            compute_units,
+            Clock::get()?.unix_timestamp,
+        )?),
+        None => None,
+    };
+    Ok(ComputeEstimate {
+        strategy: compute_profile.strategy,
+        compute_units,
+        unprofiled_blocks,
+        max_priority_fee,
+    })
+}
@@ -0,0 +1,187 @@
+use crate::constants::{FILL_TRACKER_SEED, MARKET_MAKER_SEED};
+use crate::dex::serum_events::EventQueueView;
+use crate::events::QuoteFilled;
+use crate::{
+    errors::TradingBotError,
+    state::{FillTracker, MarketMaker},
+};
+use anchor_lang::prelude::*;
+use serum_dex::state::Market;
+
+// Bounds compute per call; a backlog is worked through over several cranks
+const MAX_FILLS_PER_CALL: usize = 32;
+
+#[derive(Accounts)]
+pub struct InitFillTracker<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = FillTracker::LEN,
+      seeds = [FILL_TRACKER_SEED, market_maker.key().as_ref()],
+      bump
+    )]
+    fill_tracker: Box<Account<'info, FillTracker>>,
+
+    /// CHECK: Checked against the market maker
+    #[account(
+      address=market_maker.market
+    )]
+    market: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the market's event queue
+    event_queue: UncheckedAccount<'info>,
+
+    /// CHECK: Owner of the market and event queue
+    #[account(
+      constraint=*market.owner==serum_program.key() @ TradingBotError::InvalidEventQueue,
+      constraint=*event_queue.owner==serum_program.key() @ TradingBotError::InvalidEventQueue,
+    )]
+    serum_program: UncheckedAccount<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeFills<'info> {
+    #[account(
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    #[account(
+      mut,
+      has_one=market_maker,
+      seeds = [FILL_TRACKER_SEED, market_maker.key().as_ref()],
+      bump = fill_tracker.bump,
+    )]
+    fill_tracker: Box<Account<'info, FillTracker>>,
+
+    /// CHECK: Checked against the market maker
+    #[account(
+      address=market_maker.market
+    )]
+    market: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the market's event queue
+    event_queue: UncheckedAccount<'info>,
+
+    /// CHECK: Owner of the market and event queue
+    #[account(
+      constraint=*market.owner==serum_program.key() @ TradingBotError::InvalidEventQueue,
+      constraint=*event_queue.owner==serum_program.key() @ TradingBotError::InvalidEventQueue,
+    )]
+    serum_program: UncheckedAccount<'info>,
+}
+
+impl<'info> ConsumeFills<'info> {
+    pub fn check_event_queue(
+        market: &AccountInfo,
+        event_queue: &AccountInfo,
+        serum_program: &Pubkey,
+    ) -> Result<()> {
+        let market = Market::load(market, serum_program).map_err(ProgramError::from)?;
+        // MarketState is packed, so the field is copied out before iterating
+        let event_q_words = market.event_q;
+        let mut event_q = [0u8; 32];
+        for (i, word) in event_q_words.iter().enumerate() {
+            event_q[i * 8..(i + 1) * 8].copy_from_slice(&word.to_le_bytes());
+        }
+        require_keys_eq!(
+            Pubkey::new_from_array(event_q),
+            event_queue.key(),
+            TradingBotError::InvalidEventQueue
+        );
+        Ok(())
+    }
+}
+
+// Only fills pushed after the tracker exists are counted
+pub fn init_fill_tracker(ctx: Context<InitFillTracker>) -> Result<()> {
+    ConsumeFills::check_event_queue(
+        &ctx.accounts.market,
+        &ctx.accounts.event_queue,
+        ctx.accounts.serum_program.key,
+    )?;
+    let next_event_seq = {
+        let data = ctx.accounts.event_queue.try_borrow_data()?;
+        EventQueueView::load(&data)?.next_seq()
+    };
+
+    let fill_tracker = &mut ctx.accounts.fill_tracker;
+    fill_tracker.market_maker = ctx.accounts.market_maker.key();
+    fill_tracker.next_event_seq = next_event_seq;
+    fill_tracker.bid = Default::default();
+    fill_tracker.ask = Default::default();
+    fill_tracker.fill_count = 0;
+    fill_tracker.base_bought = 0;
+    fill_tracker.base_sold = 0;
+    fill_tracker.quote_spent = 0;
+    fill_tracker.quote_received = 0;
+    fill_tracker.realized_slippage = 0;
+    fill_tracker.net_fees = 0;
+    fill_tracker.unmatched_fills = 0;
+    fill_tracker.bump = *ctx.bumps.get("fill_tracker").unwrap();
+    fill_tracker.track_quotes(&ctx.accounts.market_maker);
+
+    Ok(())
+}
+
+// Permissionless crank reading the market maker's fills off the event queue, so partial fills
+// and slippage against the quoted prices come from the book rather than from balance diffs
+pub fn consume_fills(ctx: Context<ConsumeFills>) -> Result<()> {
+    ConsumeFills::check_event_queue(
+        &ctx.accounts.market,
+        &ctx.accounts.event_queue,
+        ctx.accounts.serum_program.key,
+    )?;
+
+    let market_maker = &ctx.accounts.market_maker;
+    let fill_tracker = &mut ctx.accounts.fill_tracker;
+    fill_tracker.track_quotes(market_maker);
+
+    let data = ctx.accounts.event_queue.try_borrow_data()?;
+    let event_queue = EventQueueView::load(&data)?;
+    let fills = event_queue
+        .fills_for(market_maker.open_orders, fill_tracker.next_event_seq)
+        .take(MAX_FILLS_PER_CALL);
+    for fill in fills {
+        let slippage_bps = fill_tracker.apply_fill(&fill)?;
+        emit!(QuoteFilled {
+            market_maker: market_maker.key(),
+            seq: fill.seq,
+            client_order_id: fill.client_order_id,
+            is_bid: fill.is_bid,
+            base_amount: if fill.is_bid {
+                fill.native_qty_received
+            } else {
+                fill.native_qty_paid
+            },
+            quote_amount: if fill.is_bid {
+                fill.native_qty_paid
+            } else {
+                fill.native_qty_received
+            },
+            slippage_bps,
+        });
+    }
+
+    msg!(
+        "Bid filled {}/{}, ask filled {}/{}",
+        fill_tracker.bid.filled_base,
+        fill_tracker.bid.size,
+        fill_tracker.ask.filled_base,
+        fill_tracker.ask.size
+    );
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::constants::CORRELATION_MATRIX_SEED;
+use crate::{
+    errors::TradingBotError,
+    state::{CorrelationMatrix, CORRELATION_PAIRS, MAX_CORRELATED_ASSETS},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitCorrelationMatrix<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = CorrelationMatrix::LEN,
+      seeds = [CORRELATION_MATRIX_SEED, owner.key().as_ref()],
+      bump
+    )]
+    correlation_matrix: Box<Account<'info, CorrelationMatrix>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCorrelationMatrix<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=keeper,
+      seeds = [CORRELATION_MATRIX_SEED, correlation_matrix.owner.as_ref()],
+      bump = correlation_matrix.bump,
+    )]
+    correlation_matrix: Box<Account<'info, CorrelationMatrix>>,
+}
+
+pub fn init_correlation_matrix(
+    ctx: Context<InitCorrelationMatrix>,
+    keeper: Pubkey,
+    assets: Vec<Pubkey>,
+    max_age: i64,
+) -> Result<()> {
+    require!(
+        assets.len() >= 2 && assets.len() <= MAX_CORRELATED_ASSETS,
+        TradingBotError::InvalidCorrelationData
+    );
+    require_gt!(max_age, 0, TradingBotError::InvalidCorrelationData);
+    for (i, asset) in assets.iter().enumerate() {
+        require!(
+            !assets[..i].contains(asset),
+            TradingBotError::InvalidCorrelationData
+        );
+    }
+
+    let correlation_matrix = &mut ctx.accounts.correlation_matrix;
+    correlation_matrix.owner = ctx.accounts.owner.key();
+    correlation_matrix.keeper = keeper;
+    correlation_matrix.assets = [Pubkey::default(); MAX_CORRELATED_ASSETS];
+    correlation_matrix.assets[..assets.len()].copy_from_slice(&assets);
+    correlation_matrix.asset_count = assets.len() as u8;
+    correlation_matrix.correlations = [0; CORRELATION_PAIRS];
+    correlation_matrix.exposures = [0; MAX_CORRELATED_ASSETS];
+    correlation_matrix.max_age = max_age;
+    // stale until the keeper's first update
+    correlation_matrix.updated_at = 0;
+    correlation_matrix.bump = *ctx.bumps.get("correlation_matrix").unwrap();
+
+    Ok(())
+}
+
+// Keepers estimate correlations off-chain from price history and publish them, row by row over
+// the upper triangle, together with the current signed quote exposure to each asset
+pub fn update_correlation_matrix(
+    ctx: Context<UpdateCorrelationMatrix>,
+    correlations: Vec<i16>,
+    exposures: Vec<i64>,
+) -> Result<()> {
+    let correlation_matrix = &mut ctx.accounts.correlation_matrix;
+    let count = correlation_matrix.asset_count as usize;
+    require_eq!(
+        correlations.len(),
+        count * (count - 1) / 2,
+        TradingBotError::InvalidCorrelationData
+    );
+    require_eq!(
+        exposures.len(),
+        count,
+        TradingBotError::InvalidCorrelationData
+    );
+
+    let mut values = correlations.iter();
+    for i in 0..count {
+        for j in i + 1..count {
+            let value = *values.next().unwrap();
+            require!(
+                (-10000..=10000).contains(&value),
+                TradingBotError::InvalidCorrelationData
+            );
+            correlation_matrix.set_correlation(i, j, value);
+        }
+    }
+    correlation_matrix.exposures[..count].copy_from_slice(&exposures);
+    correlation_matrix.updated_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
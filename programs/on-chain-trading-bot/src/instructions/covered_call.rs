@@ -0,0 +1,543 @@
+use crate::constants::{COVERED_CALL_SEED, FEED_REGISTRY_SEED, TRADING_VAULT_SEED, VAULT_LOG_SEED};
+use crate::dex::psyoptions::{OptionMarket, PSY_AMERICAN_PROGRAM_ID};
+use crate::dex::{jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID};
+use crate::events::{CoveredCallSettled, CoveredCallWritten};
+use crate::oracles::PythOracle;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{CallStatus, CoveredCall, FeedRegistry, TradingVault, VaultLog, VaultOp},
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitCoveredCall<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, underlying_vault.creator.as_ref(), underlying_vault.mint.as_ref()],
+      bump = underlying_vault.bump,
+    )]
+    underlying_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      has_one=owner,
+      constraint=quote_vault.mint!=underlying_vault.mint @ TradingBotError::InvalidCoveredCallParams,
+      seeds = [TRADING_VAULT_SEED, quote_vault.creator.as_ref(), quote_vault.mint.as_ref()],
+      bump = quote_vault.bump,
+    )]
+    quote_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = CoveredCall::LEN,
+      seeds = [COVERED_CALL_SEED, underlying_vault.key().as_ref()],
+      bump
+    )]
+    covered_call: Box<Account<'info, CoveredCall>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WriteCoveredCall<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [COVERED_CALL_SEED, covered_call.underlying_vault.as_ref()],
+      bump = covered_call.bump,
+    )]
+    covered_call: Box<Account<'info, CoveredCall>>,
+
+    #[account(
+      address=covered_call.underlying_vault
+    )]
+    underlying_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=covered_call.quote_vault
+    )]
+    quote_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=underlying_vault.token_account
+    )]
+    underlying_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      address=quote_vault.token_account
+    )]
+    quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, underlying_vault.key().as_ref()],
+      bump,
+    )]
+    underlying_vault_log: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, quote_vault.key().as_ref()],
+      bump,
+    )]
+    quote_vault_log: UncheckedAccount<'info>,
+
+    // receive the written options and writer tokens; checked against the market's mints
+    #[account(
+      mut,
+      token::authority=underlying_vault
+    )]
+    option_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=underlying_vault
+    )]
+    writer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: A PsyOptions market, parsed and checked against the vaults
+    option_market: UncheckedAccount<'info>,
+
+    #[account(
+      address=underlying_vault.mint
+    )]
+    underlying_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=quote_vault.mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Resolved through the feed registry on read
+    underlying_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Resolved through the feed registry on read
+    quote_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: PsyOptions, which mints the options
+    #[account(
+      address=PSY_AMERICAN_PROGRAM_ID
+    )]
+    options_program: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the supported swap programs
+    swap_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleCoveredCall<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [COVERED_CALL_SEED, covered_call.underlying_vault.as_ref()],
+      bump = covered_call.bump,
+    )]
+    covered_call: Box<Account<'info, CoveredCall>>,
+
+    #[account(
+      address=covered_call.underlying_vault
+    )]
+    underlying_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=covered_call.quote_vault
+    )]
+    quote_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=underlying_vault.token_account
+    )]
+    underlying_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      address=quote_vault.token_account
+    )]
+    quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, underlying_vault.key().as_ref()],
+      bump,
+    )]
+    underlying_vault_log: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, quote_vault.key().as_ref()],
+      bump,
+    )]
+    quote_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      mut,
+      token::authority=underlying_vault
+    )]
+    writer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The open series' market
+    #[account(
+      address=covered_call.option_market
+    )]
+    option_market: UncheckedAccount<'info>,
+
+    /// CHECK: PsyOptions, which returns the collateral or the strike
+    #[account(
+      address=PSY_AMERICAN_PROGRAM_ID
+    )]
+    options_program: UncheckedAccount<'info>,
+}
+
+impl<'info> WriteCoveredCall<'info> {
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    // USD value of `amount` of `mint`, with USD_DECIMALS
+    pub fn usd_value(&self, amount: u64, mint: &Account<Mint>, feed: &AccountInfo) -> Result<u64> {
+        let usd_price =
+            PythOracle::get_usd_price(&self.feed_registry, &mint.key(), feed, u64::MAX)?;
+        PythOracle::usd_value(amount, mint.decimals, usd_price)
+    }
+}
+
+// The underlying vault signs both the mint and the sale, so its PDA is flagged as a signer in the
+// forwarded metas
+fn invoke_as_vault<'info>(
+    program_id: Pubkey,
+    vault: &Account<'info, TradingVault>,
+    accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+) -> Result<()> {
+    let vault_key = vault.key();
+    let ix = Instruction {
+        program_id,
+        accounts: accounts
+            .iter()
+            .map(|a| {
+                let is_signer = a.is_signer || a.key() == vault_key;
+                if a.is_writable {
+                    AccountMeta::new(a.key(), is_signer)
+                } else {
+                    AccountMeta::new_readonly(a.key(), is_signer)
+                }
+            })
+            .collect(),
+        data,
+    };
+    invoke_signed(&ix, accounts, &[trading_vault_seeds!(vault)])?;
+    Ok(())
+}
+
+fn bps_of(value: u64, bps: u16) -> Result<u64> {
+    math::checked_as_u64(math::checked_div(
+        math::checked_mul(value as u128, bps as u128)?,
+        10_000,
+    )?)
+}
+
+pub fn init_covered_call(
+    ctx: Context<InitCoveredCall>,
+    max_coverage_bps: u16,
+    min_otm_bps: u16,
+    min_premium_bps: u16,
+    max_tenor: i64,
+) -> Result<()> {
+    require!(
+        max_coverage_bps > 0 && max_coverage_bps <= 10000,
+        TradingBotError::InvalidCoveredCallParams
+    );
+    require_gt!(max_tenor, 0, TradingBotError::InvalidCoveredCallParams);
+
+    let covered_call = &mut ctx.accounts.covered_call;
+    covered_call.owner = ctx.accounts.owner.key();
+    covered_call.underlying_vault = ctx.accounts.underlying_vault.key();
+    covered_call.quote_vault = ctx.accounts.quote_vault.key();
+    covered_call.max_coverage_bps = max_coverage_bps;
+    covered_call.min_otm_bps = min_otm_bps;
+    covered_call.min_premium_bps = min_premium_bps;
+    covered_call.max_tenor = max_tenor;
+    covered_call.status = CallStatus::Idle;
+    covered_call.option_market = Pubkey::default();
+    covered_call.contracts = 0;
+    covered_call.collateral = 0;
+    covered_call.expiration = 0;
+    covered_call.metrics = Default::default();
+    covered_call.bump = *ctx.bumps.get("covered_call").unwrap();
+
+    Ok(())
+}
+
+// Keepers write `contracts` calls on a PsyOptions market against the underlying vault and sell
+// them into the quote vault. The market has to expire within `max_tenor` at a strike at least
+// `min_otm_bps` over the oracle price, the collateral fit within `max_coverage_bps` of the
+// unreserved balance, and the sale bring in `min_premium_bps` of the collateral's value. The
+// first `write_accounts_len` remaining accounts go to the mint, the rest to the sale.
+pub fn write_covered_call<'info>(
+    ctx: Context<'_, '_, '_, 'info, WriteCoveredCall<'info>>,
+    contracts: u64,
+    write_accounts_len: u8,
+    write_data: Vec<u8>,
+    sell_data: Vec<u8>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let covered_call = &accounts.covered_call;
+    require!(
+        covered_call.status == CallStatus::Idle,
+        TradingBotError::CallNotReady
+    );
+    require!(
+        WriteCoveredCall::is_supported_swap_program(accounts.swap_program.key),
+        TradingBotError::InvalidDexType
+    );
+    let market = OptionMarket::read(&accounts.option_market)?;
+    require!(
+        market.underlying_mint == accounts.underlying_vault.mint
+            && market.quote_mint == accounts.quote_vault.mint
+            && accounts.option_token_account.mint == market.option_mint
+            && accounts.writer_token_account.mint == market.writer_token_mint,
+        TradingBotError::InvalidOptionMarket
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        market.expiration > now
+            && market.expiration <= math::checked_add(now, covered_call.max_tenor)?,
+        TradingBotError::InvalidOptionMarket
+    );
+
+    let collateral = math::checked_mul(contracts, market.underlying_amount_per_contract)?;
+    let available = accounts
+        .underlying_vault
+        .available(accounts.underlying_token_account.amount);
+    require_gte!(
+        bps_of(available, covered_call.max_coverage_bps)?,
+        collateral,
+        TradingBotError::CoverageExceeded
+    );
+
+    let contract_value = accounts.usd_value(
+        market.underlying_amount_per_contract,
+        &accounts.underlying_mint,
+        &accounts.underlying_price_feed,
+    )?;
+    let strike_value = accounts.usd_value(
+        market.quote_amount_per_contract,
+        &accounts.quote_mint,
+        &accounts.quote_price_feed,
+    )?;
+    require_gte!(
+        strike_value,
+        math::checked_add(
+            contract_value,
+            bps_of(contract_value, covered_call.min_otm_bps)?
+        )?,
+        TradingBotError::StrikeTooLow
+    );
+
+    let write_accounts_len = write_accounts_len as usize;
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        write_accounts_len,
+        TradingBotError::InvalidCalculation
+    );
+    let (write_accounts, sell_accounts) = ctx.remaining_accounts.split_at(write_accounts_len);
+
+    let underlying_before = accounts.underlying_token_account.amount;
+    let quote_before = accounts.quote_token_account.amount;
+    let options_before = accounts.option_token_account.amount;
+    let writers_before = accounts.writer_token_account.amount;
+    invoke_as_vault(
+        accounts.options_program.key(),
+        &accounts.underlying_vault,
+        write_accounts,
+        write_data,
+    )?;
+    ctx.accounts.underlying_token_account.reload()?;
+    ctx.accounts.option_token_account.reload()?;
+    ctx.accounts.writer_token_account.reload()?;
+    let accounts = &ctx.accounts;
+    // exactly the collateral goes in, and a call and a writer token come out per contract
+    require!(
+        math::checked_sub(underlying_before, accounts.underlying_token_account.amount)?
+            == collateral
+            && math::checked_sub(accounts.option_token_account.amount, options_before)?
+                == contracts
+            && math::checked_sub(accounts.writer_token_account.amount, writers_before)?
+                == contracts,
+        TradingBotError::InvalidOptionMarket
+    );
+
+    invoke_as_vault(
+        accounts.swap_program.key(),
+        &accounts.underlying_vault,
+        sell_accounts,
+        sell_data,
+    )?;
+    ctx.accounts.underlying_token_account.reload()?;
+    ctx.accounts.quote_token_account.reload()?;
+    ctx.accounts.option_token_account.reload()?;
+    let accounts = &ctx.accounts;
+    require_eq!(
+        accounts.option_token_account.amount,
+        options_before,
+        TradingBotError::InvalidCalculation
+    );
+    require_gte!(
+        accounts.underlying_token_account.amount,
+        math::checked_sub(underlying_before, collateral)?,
+        TradingBotError::InvalidCalculation
+    );
+    accounts
+        .underlying_vault
+        .check_invariant(accounts.underlying_token_account.amount)?;
+    accounts
+        .quote_vault
+        .check_invariant(accounts.quote_token_account.amount)?;
+
+    let premium = math::checked_sub(accounts.quote_token_account.amount, quote_before)?;
+    let premium_value =
+        accounts.usd_value(premium, &accounts.quote_mint, &accounts.quote_price_feed)?;
+    let collateral_value = math::checked_mul(contract_value, contracts)?;
+    require_gte!(
+        premium_value,
+        bps_of(collateral_value, accounts.covered_call.min_premium_bps)?,
+        TradingBotError::PremiumTooLow
+    );
+
+    VaultLog::append_if_initialized(
+        &accounts.underlying_vault_log,
+        VaultOp::Preset,
+        accounts.covered_call.key(),
+        underlying_before.abs_diff(accounts.underlying_token_account.amount),
+        &accounts.underlying_vault,
+        Some(accounts.underlying_token_account.amount),
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.quote_vault_log,
+        VaultOp::Preset,
+        accounts.covered_call.key(),
+        quote_before.abs_diff(accounts.quote_token_account.amount),
+        &accounts.quote_vault,
+        Some(accounts.quote_token_account.amount),
+    )?;
+
+    let option_market = accounts.option_market.key();
+    let covered_call = &mut ctx.accounts.covered_call;
+    covered_call.open(
+        option_market,
+        contracts,
+        collateral,
+        market.expiration,
+        premium,
+    )?;
+    emit!(CoveredCallWritten {
+        covered_call: covered_call.key(),
+        option_market,
+        contracts,
+        collateral,
+        premium,
+        expiration: market.expiration,
+    });
+
+    Ok(())
+}
+
+// After expiry keepers burn the series' writer tokens through PsyOptions: for the collateral if
+// the calls expired, for the strike in quote where they were exercised. Whatever comes back lands
+// in the vaults and the series is booked as expired or assigned.
+pub fn settle_covered_call<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleCoveredCall<'info>>,
+    settle_data: Vec<u8>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    require!(
+        accounts.covered_call.status == CallStatus::Open,
+        TradingBotError::CallNotReady
+    );
+    require_gte!(
+        Clock::get()?.unix_timestamp,
+        accounts.covered_call.expiration,
+        TradingBotError::CallNotReady
+    );
+    let market = OptionMarket::read(&accounts.option_market)?;
+    require_keys_eq!(
+        accounts.writer_token_account.mint,
+        market.writer_token_mint,
+        TradingBotError::InvalidOptionMarket
+    );
+
+    let underlying_before = accounts.underlying_token_account.amount;
+    let quote_before = accounts.quote_token_account.amount;
+    let writers_before = accounts.writer_token_account.amount;
+    invoke_as_vault(
+        accounts.options_program.key(),
+        &accounts.underlying_vault,
+        ctx.remaining_accounts,
+        settle_data,
+    )?;
+    ctx.accounts.underlying_token_account.reload()?;
+    ctx.accounts.quote_token_account.reload()?;
+    ctx.accounts.writer_token_account.reload()?;
+    let accounts = &ctx.accounts;
+    require_eq!(
+        math::checked_sub(writers_before, accounts.writer_token_account.amount)?,
+        accounts.covered_call.contracts,
+        TradingBotError::InvalidCalculation
+    );
+    let underlying_returned =
+        math::checked_sub(accounts.underlying_token_account.amount, underlying_before)?;
+    let quote_received = math::checked_sub(accounts.quote_token_account.amount, quote_before)?;
+
+    VaultLog::append_if_initialized(
+        &accounts.underlying_vault_log,
+        VaultOp::Preset,
+        accounts.covered_call.key(),
+        underlying_before.abs_diff(accounts.underlying_token_account.amount),
+        &accounts.underlying_vault,
+        Some(accounts.underlying_token_account.amount),
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.quote_vault_log,
+        VaultOp::Preset,
+        accounts.covered_call.key(),
+        quote_before.abs_diff(accounts.quote_token_account.amount),
+        &accounts.quote_vault,
+        Some(accounts.quote_token_account.amount),
+    )?;
+
+    let option_market = accounts.option_market.key();
+    let covered_call = &mut ctx.accounts.covered_call;
+    let assigned = covered_call.settle(underlying_returned, quote_received)?;
+    emit!(CoveredCallSettled {
+        covered_call: covered_call.key(),
+        option_market,
+        assigned,
+        underlying_returned,
+        quote_received,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,392 @@
+use crate::constants::{
+    DEPEG_GUARD_SEED, FEED_REGISTRY_SEED, MAX_SLIPPAGE_BPS, PRICE_PRECISION, TRADING_VAULT_SEED,
+    VAULT_LOG_SEED,
+};
+use crate::dex::{jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID};
+use crate::events::DepegRotation;
+use crate::oracles::PythOracle;
+use crate::utils::validate_deadline;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{DepegGuard, FeedRegistry, GuardStatus, TradingVault, VaultLog, VaultOp},
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitDepegGuard<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, stable_vault.creator.as_ref(), stable_vault.mint.as_ref()],
+      bump = stable_vault.bump,
+    )]
+    stable_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      has_one=owner,
+      constraint=haven_vault.mint!=stable_vault.mint @ TradingBotError::InvalidDepegParams,
+      seeds = [TRADING_VAULT_SEED, haven_vault.creator.as_ref(), haven_vault.mint.as_ref()],
+      bump = haven_vault.bump,
+    )]
+    haven_vault: Box<Account<'info, TradingVault>>,
+
+    /// CHECK: Pyth feed for the stablecoin, validated on read
+    stable_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth feed for the haven asset, validated on read
+    haven_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = DepegGuard::LEN,
+      seeds = [DEPEG_GUARD_SEED, stable_vault.key().as_ref()],
+      bump
+    )]
+    depeg_guard: Box<Account<'info, DepegGuard>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PollDepegGuard<'info> {
+    #[account(
+      mut,
+      seeds = [DEPEG_GUARD_SEED, depeg_guard.stable_vault.as_ref()],
+      bump = depeg_guard.bump,
+    )]
+    depeg_guard: Box<Account<'info, DepegGuard>>,
+
+    #[account(
+      address=depeg_guard.stable_vault
+    )]
+    stable_vault: Box<Account<'info, TradingVault>>,
+
+    /// CHECK: Checked against the guard, validated on read
+    #[account(
+      address=depeg_guard.stable_price_feed
+    )]
+    stable_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct RotateDepegGuard<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [DEPEG_GUARD_SEED, depeg_guard.stable_vault.as_ref()],
+      bump = depeg_guard.bump,
+    )]
+    depeg_guard: Box<Account<'info, DepegGuard>>,
+
+    #[account(
+      address=depeg_guard.stable_vault
+    )]
+    stable_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=depeg_guard.haven_vault
+    )]
+    haven_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=stable_vault.token_account
+    )]
+    stable_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      address=haven_vault.token_account
+    )]
+    haven_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, stable_vault.key().as_ref()],
+      bump,
+    )]
+    stable_vault_log: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, haven_vault.key().as_ref()],
+      bump,
+    )]
+    haven_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      address=stable_vault.mint
+    )]
+    stable_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=haven_vault.mint
+    )]
+    haven_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Checked against the guard, validated on read
+    #[account(
+      address=depeg_guard.stable_price_feed
+    )]
+    stable_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the guard, validated on read
+    #[account(
+      address=depeg_guard.haven_price_feed
+    )]
+    haven_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Checked against the supported swap programs
+    swap_program: UncheckedAccount<'info>,
+}
+
+impl<'info> RotateDepegGuard<'info> {
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    // USD per whole token in PRICE_PRECISION, the unit the thresholds are set in
+    pub fn read_price(&self, mint: &Pubkey, price_feed: &AccountInfo) -> Result<u64> {
+        PythOracle::get_usd_price(
+            &self.feed_registry,
+            mint,
+            price_feed,
+            self.depeg_guard.max_confidence,
+        )
+    }
+
+    // The source vault signs the swap, so its PDA is flagged as a signer in the forwarded metas
+    pub fn invoke_swap(
+        &self,
+        source: &TradingVault,
+        accounts: &[AccountInfo<'info>],
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let source_key = if source.mint == self.stable_vault.mint {
+            self.stable_vault.key()
+        } else {
+            self.haven_vault.key()
+        };
+        let ix = Instruction {
+            program_id: self.swap_program.key(),
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    let is_signer = a.is_signer || a.key() == source_key;
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        invoke_signed(&ix, accounts, &[trading_vault_seeds!(source)])?;
+        Ok(())
+    }
+}
+
+pub fn init_depeg_guard(
+    ctx: Context<InitDepegGuard>,
+    depeg_threshold: u64,
+    restore_threshold: u64,
+    min_duration: i64,
+    max_confidence: u64,
+    max_slippage_bps: u16,
+) -> Result<()> {
+    require!(
+        depeg_threshold > 0 && depeg_threshold < PRICE_PRECISION,
+        TradingBotError::InvalidDepegParams
+    );
+    require_gte!(
+        restore_threshold,
+        depeg_threshold,
+        TradingBotError::InvalidDepegParams
+    );
+    require_gte!(min_duration, 0, TradingBotError::InvalidDepegParams);
+    require_gte!(
+        MAX_SLIPPAGE_BPS,
+        max_slippage_bps,
+        TradingBotError::InvalidDepegParams
+    );
+
+    let depeg_guard = &mut ctx.accounts.depeg_guard;
+    depeg_guard.owner = ctx.accounts.owner.key();
+    depeg_guard.stable_vault = ctx.accounts.stable_vault.key();
+    depeg_guard.haven_vault = ctx.accounts.haven_vault.key();
+    depeg_guard.stable_price_feed = ctx.accounts.stable_price_feed.key();
+    depeg_guard.haven_price_feed = ctx.accounts.haven_price_feed.key();
+    depeg_guard.depeg_threshold = depeg_threshold;
+    depeg_guard.restore_threshold = restore_threshold;
+    depeg_guard.min_duration = min_duration;
+    depeg_guard.max_confidence = max_confidence;
+    depeg_guard.max_slippage_bps = max_slippage_bps;
+    depeg_guard.status = GuardStatus::Pegged;
+    depeg_guard.condition_since = 0;
+    depeg_guard.rotation_count = 0;
+    depeg_guard.bump = *ctx.bumps.get("depeg_guard").unwrap();
+
+    Ok(())
+}
+
+// Permissionless crank recording how long the depeg (or restore) condition has held. Any
+// observation outside the condition resets the timer.
+pub fn poll_depeg_guard(ctx: Context<PollDepegGuard>) -> Result<()> {
+    let price = PythOracle::get_usd_price(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.stable_vault.mint,
+        &ctx.accounts.stable_price_feed,
+        ctx.accounts.depeg_guard.max_confidence,
+    )?;
+    let depeg_guard = &mut ctx.accounts.depeg_guard;
+    depeg_guard.observe(price, Clock::get()?.unix_timestamp);
+    msg!(
+        "Stable at {}, condition since {}",
+        price,
+        depeg_guard.condition_since
+    );
+
+    Ok(())
+}
+
+// Keepers rotate the unreserved balance of the stable vault into the haven once the depeg has
+// held for `min_duration`, and back again after the peg restores. The swap is forwarded from the
+// remaining accounts and must return at least the oracle-implied amount less slippage.
+pub fn rotate_depeg_guard<'info>(
+    ctx: Context<'_, '_, '_, 'info, RotateDepegGuard<'info>>,
+    swap_data: Vec<u8>,
+    deadline: i64,
+) -> Result<()> {
+    validate_deadline(deadline)?;
+    require!(
+        RotateDepegGuard::is_supported_swap_program(ctx.accounts.swap_program.key),
+        TradingBotError::InvalidDexType
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let accounts = &ctx.accounts;
+    let stable_price =
+        accounts.read_price(&accounts.stable_vault.mint, &accounts.stable_price_feed)?;
+    let haven_price =
+        accounts.read_price(&accounts.haven_vault.mint, &accounts.haven_price_feed)?;
+    ctx.accounts.depeg_guard.observe(stable_price, now);
+    require!(
+        ctx.accounts.depeg_guard.rotation_due(now),
+        TradingBotError::RotationNotDue
+    );
+
+    let to_haven = ctx.accounts.depeg_guard.status == GuardStatus::Pegged;
+    let (source, source_balance, price_in, price_out, decimals_in, decimals_out) = if to_haven {
+        (
+            &ctx.accounts.stable_vault,
+            ctx.accounts.stable_token_account.amount,
+            stable_price,
+            haven_price,
+            ctx.accounts.stable_mint.decimals,
+            ctx.accounts.haven_mint.decimals,
+        )
+    } else {
+        (
+            &ctx.accounts.haven_vault,
+            ctx.accounts.haven_token_account.amount,
+            haven_price,
+            stable_price,
+            ctx.accounts.haven_mint.decimals,
+            ctx.accounts.stable_mint.decimals,
+        )
+    };
+    let amount_in = source.available(source_balance);
+    let min_out = DepegGuard::min_out(
+        amount_in,
+        price_in,
+        price_out,
+        decimals_in,
+        decimals_out,
+        ctx.accounts.depeg_guard.max_slippage_bps,
+    )?;
+    msg!("Rotating {} with min out {}", amount_in, min_out);
+
+    let stable_before = ctx.accounts.stable_token_account.amount;
+    let haven_before = ctx.accounts.haven_token_account.amount;
+    if amount_in > 0 {
+        ctx.accounts
+            .invoke_swap(source, ctx.remaining_accounts, swap_data)?;
+    }
+    ctx.accounts.stable_token_account.reload()?;
+    ctx.accounts.haven_token_account.reload()?;
+    let (stable_after, haven_after) = (
+        ctx.accounts.stable_token_account.amount,
+        ctx.accounts.haven_token_account.amount,
+    );
+
+    // reserved funds must stay put, and the swap may only move the source into the target
+    ctx.accounts.stable_vault.check_invariant(stable_after)?;
+    ctx.accounts.haven_vault.check_invariant(haven_after)?;
+    let (spent, received) = if to_haven {
+        (
+            math::checked_sub(stable_before, stable_after)?,
+            math::checked_sub(haven_after, haven_before)?,
+        )
+    } else {
+        (
+            math::checked_sub(haven_before, haven_after)?,
+            math::checked_sub(stable_after, stable_before)?,
+        )
+    };
+    require_gte!(amount_in, spent, TradingBotError::InvalidCalculation);
+    require_gte!(received, min_out, TradingBotError::SlippageExceeded);
+
+    let accounts = &ctx.accounts;
+    VaultLog::append_if_initialized(
+        &accounts.stable_vault_log,
+        VaultOp::Preset,
+        accounts.depeg_guard.key(),
+        stable_before.abs_diff(stable_after),
+        &accounts.stable_vault,
+        Some(stable_after),
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.haven_vault_log,
+        VaultOp::Preset,
+        accounts.depeg_guard.key(),
+        haven_before.abs_diff(haven_after),
+        &accounts.haven_vault,
+        Some(haven_after),
+    )?;
+
+    let depeg_guard = &mut ctx.accounts.depeg_guard;
+    depeg_guard.complete_rotation()?;
+    emit!(DepegRotation {
+        guard: depeg_guard.key(),
+        to_haven,
+        stable_price,
+        amount_in: spent,
+        amount_out: received,
+        deadline,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,355 @@
+use crate::constants::{FAUCET_SEED, MOCK_POOL_SEED, MOCK_PRICE_FEED_SEED};
+use crate::dex::depth;
+use crate::{
+    errors::TradingBotError,
+    faucet_seeds, mock_pool_seeds,
+    state::{Faucet, MockPool, MockPriceFeed},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, MintTo, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+#[instruction(decimals: u8)]
+pub struct InitFaucet<'info> {
+    #[account(mut)]
+    authority: Signer<'info>,
+
+    #[account(
+      init,
+      payer = authority,
+      mint::decimals = decimals,
+      mint::authority = faucet,
+    )]
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = authority,
+      space = Faucet::LEN,
+      seeds = [FAUCET_SEED, mint.key().as_ref()],
+      bump
+    )]
+    faucet: Box<Account<'info, Faucet>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RequestFaucetTokens<'info> {
+    #[account(mut)]
+    recipient: Signer<'info>,
+
+    #[account(
+      mut,
+      address=faucet.mint
+    )]
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      seeds = [FAUCET_SEED, faucet.mint.as_ref()],
+      bump = faucet.bump,
+    )]
+    faucet: Box<Account<'info, Faucet>>,
+
+    #[account(
+      init_if_needed,
+      payer=recipient,
+      associated_token::authority=recipient,
+      associated_token::mint=mint,
+    )]
+    recipient_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct InitMockPool<'info> {
+    #[account(mut)]
+    authority: Signer<'info>,
+
+    mint_a: Box<Account<'info, Mint>>,
+    mint_b: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = authority,
+      space = MockPool::LEN,
+      seeds = [MOCK_POOL_SEED, mint_a.key().as_ref(), mint_b.key().as_ref()],
+      bump
+    )]
+    pool: Box<Account<'info, MockPool>>,
+
+    #[account(
+      init,
+      payer=authority,
+      associated_token::authority=pool,
+      associated_token::mint=mint_a,
+    )]
+    reserve_a: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer=authority,
+      associated_token::authority=pool,
+      associated_token::mint=mint_b,
+    )]
+    reserve_b: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct MockPoolTrade<'info> {
+    user: Signer<'info>,
+
+    #[account(
+      seeds = [MOCK_POOL_SEED, pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+      bump = pool.bump,
+    )]
+    pool: Box<Account<'info, MockPool>>,
+
+    #[account(
+      mut,
+      associated_token::authority=pool,
+      associated_token::mint=pool.mint_a,
+    )]
+    reserve_a: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      associated_token::authority=pool,
+      associated_token::mint=pool.mint_b,
+    )]
+    reserve_b: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=user,
+      token::mint=pool.mint_a,
+    )]
+    user_account_a: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=user,
+      token::mint=pool.mint_b,
+    )]
+    user_account_b: Box<Account<'info, TokenAccount>>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct InitMockPriceFeed<'info> {
+    #[account(mut)]
+    authority: Signer<'info>,
+
+    #[account(
+      init,
+      payer = authority,
+      space = MockPriceFeed::LEN,
+      seeds = [MOCK_PRICE_FEED_SEED, authority.key().as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    price_feed: Box<Account<'info, MockPriceFeed>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMockPrice<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=authority,
+      seeds = [MOCK_PRICE_FEED_SEED, authority.key().as_ref(), price_feed.idx.to_le_bytes().as_ref()],
+      bump = price_feed.bump,
+    )]
+    price_feed: Box<Account<'info, MockPriceFeed>>,
+}
+
+impl<'info> MockPoolTrade<'info> {
+    fn deposit(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+}
+
+// Anchor's #[program] can't leave instructions out of a build, so every build carries these
+// entrypoints and only a `devnet` build lets them run
+fn require_devnet() -> Result<()> {
+    require!(cfg!(feature = "devnet"), TradingBotError::FeatureDisabled);
+    Ok(())
+}
+
+pub fn init_faucet(ctx: Context<InitFaucet>, _decimals: u8, max_per_request: u64) -> Result<()> {
+    require_devnet()?;
+    require_gt!(max_per_request, 0, TradingBotError::InvalidDevnetParams);
+
+    let faucet = &mut ctx.accounts.faucet;
+    faucet.authority = ctx.accounts.authority.key();
+    faucet.mint = ctx.accounts.mint.key();
+    faucet.max_per_request = max_per_request;
+    faucet.bump = *ctx.bumps.get("faucet").unwrap();
+
+    Ok(())
+}
+
+pub fn request_faucet_tokens(ctx: Context<RequestFaucetTokens>, amount: u64) -> Result<()> {
+    require_devnet()?;
+    let faucet = &ctx.accounts.faucet;
+    require!(
+        amount > 0 && amount <= faucet.max_per_request,
+        TradingBotError::InvalidDevnetParams
+    );
+
+    let signer_seeds: &[&[&[u8]]] = &[faucet_seeds!(faucet)];
+    anchor_spl::token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.recipient_account.to_account_info(),
+                authority: faucet.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+pub fn init_mock_pool(ctx: Context<InitMockPool>, fee_bps: u16) -> Result<()> {
+    require_devnet()?;
+    require_gt!(10000, fee_bps, TradingBotError::InvalidDevnetParams);
+    require_keys_neq!(
+        ctx.accounts.mint_a.key(),
+        ctx.accounts.mint_b.key(),
+        TradingBotError::InvalidDevnetParams
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    pool.authority = ctx.accounts.authority.key();
+    pool.mint_a = ctx.accounts.mint_a.key();
+    pool.mint_b = ctx.accounts.mint_b.key();
+    pool.fee_bps = fee_bps;
+    pool.bump = *ctx.bumps.get("pool").unwrap();
+
+    Ok(())
+}
+
+// Anyone may seed the pool; there are no LP shares, the liquidity stays for the demo
+pub fn add_mock_liquidity(ctx: Context<MockPoolTrade>, amount_a: u64, amount_b: u64) -> Result<()> {
+    require_devnet()?;
+    let accounts = &ctx.accounts;
+    accounts.deposit(&accounts.user_account_a, &accounts.reserve_a, amount_a)?;
+    accounts.deposit(&accounts.user_account_b, &accounts.reserve_b, amount_b)
+}
+
+// Swaps at the x*y=k price after the pool's fee, the same quote view_batch_quotes gives
+pub fn mock_swap(
+    ctx: Context<MockPoolTrade>,
+    amount_in: u64,
+    minimum_out: u64,
+    a_to_b: bool,
+) -> Result<()> {
+    require_devnet()?;
+    let accounts = &ctx.accounts;
+    let (user_in, reserve_in, reserve_out, user_out) = if a_to_b {
+        (
+            &accounts.user_account_a,
+            &accounts.reserve_a,
+            &accounts.reserve_b,
+            &accounts.user_account_b,
+        )
+    } else {
+        (
+            &accounts.user_account_b,
+            &accounts.reserve_b,
+            &accounts.reserve_a,
+            &accounts.user_account_a,
+        )
+    };
+    let amount_out = depth::amount_out(
+        amount_in,
+        reserve_in.amount,
+        reserve_out.amount,
+        accounts.pool.fee_bps,
+    )?;
+    require_gt!(amount_out, 0, TradingBotError::InsufficientLiquidity);
+    require_gte!(amount_out, minimum_out, TradingBotError::SlippageExceeded);
+    msg!("Mock swap {} for {}", amount_in, amount_out);
+
+    accounts.deposit(user_in, reserve_in, amount_in)?;
+    let signer_seeds: &[&[&[u8]]] = &[mock_pool_seeds!(accounts.pool)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: reserve_out.to_account_info(),
+                to: user_out.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount_out,
+    )
+}
+
+pub fn init_mock_price_feed(ctx: Context<InitMockPriceFeed>, idx: u64, expo: i32) -> Result<()> {
+    require_devnet()?;
+    let price_feed = &mut ctx.accounts.price_feed;
+    price_feed.authority = ctx.accounts.authority.key();
+    price_feed.idx = idx;
+    price_feed.price = 0;
+    price_feed.conf = 0;
+    price_feed.expo = expo;
+    price_feed.publish_time = 0;
+    price_feed.bump = *ctx.bumps.get("price_feed").unwrap();
+
+    Ok(())
+}
+
+// One step of a scripted price path. `publish_time` defaults to now; pass an older one to make
+// the feed stale.
+pub fn set_mock_price(
+    ctx: Context<SetMockPrice>,
+    price: i64,
+    conf: u64,
+    publish_time: Option<i64>,
+) -> Result<()> {
+    require_devnet()?;
+    let now = Clock::get()?.unix_timestamp;
+    let publish_time = publish_time.unwrap_or(now);
+    require_gte!(now, publish_time, TradingBotError::InvalidDevnetParams);
+
+    let price_feed = &mut ctx.accounts.price_feed;
+    price_feed.price = price;
+    price_feed.conf = conf;
+    price_feed.publish_time = publish_time;
+
+    Ok(())
+}
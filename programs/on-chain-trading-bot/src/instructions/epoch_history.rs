@@ -0,0 +1,65 @@
+use crate::constants::{EPOCH_HISTORY_SEED, STRATEGY_SEED};
+use crate::events::EpochRolledOver;
+use crate::state::{EpochHistory, PerformanceMetrics, Strategy};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RolloverEpoch<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      init_if_needed,
+      payer = owner,
+      space = EpochHistory::LEN,
+      seeds = [EPOCH_HISTORY_SEED, strategy.key().as_ref()],
+      bump
+    )]
+    epoch_history: Box<Account<'info, EpochHistory>>,
+
+    system_program: Program<'info, System>,
+}
+
+// Archives the strategy's performance metrics as the running epoch and starts the next one from
+// zero, e.g. after a configuration change. The first epoch runs from the strategy's creation.
+pub fn rollover_epoch(ctx: Context<RolloverEpoch>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let strategy = &mut ctx.accounts.strategy;
+    let epoch_history = &mut ctx.accounts.epoch_history;
+    if epoch_history.strategy == Pubkey::default() {
+        epoch_history.strategy = strategy.key();
+        epoch_history.epoch_started_at = strategy.created_at;
+        epoch_history.bump = *ctx.bumps.get("epoch_history").unwrap();
+    }
+
+    let summary = epoch_history.archive(strategy.performance_metrics, now)?;
+    strategy.performance_metrics = PerformanceMetrics::default();
+    strategy.updated_at = now;
+
+    msg!(
+        "Epoch {} closed with {} P&L over {} wins and {} losses",
+        summary.epoch,
+        summary.metrics.total_profit_loss,
+        summary.metrics.win_count,
+        summary.metrics.loss_count
+    );
+    emit!(EpochRolledOver {
+        strategy: strategy.key(),
+        epoch: summary.epoch,
+        started_at: summary.started_at,
+        total_profit_loss: summary.metrics.total_profit_loss,
+        win_rate_bps: summary.metrics.win_rate_bps()?,
+        max_drawdown: summary.metrics.max_drawdown,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use crate::constants::{ESCROW_SEED, MAX_CANCEL_PENALTY_BPS, TREASURY_SEED};
+use crate::{
+    errors::TradingBotError,
+    escrow_seeds, math,
+    state::{CancelPolicy, Escrow},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+use jupiter_dca::cpi::{self};
+
+#[derive(Accounts)]
+pub struct ConfigureEscrow<'info> {
+    user: Signer<'info>,
+
+    #[account(
+      mut,
+      constraint=escrow.user==user.key(),
+      seeds = [ESCROW_SEED, escrow.user.as_ref(), escrow.input_mint.as_ref(), escrow.output_mint.as_ref(), escrow.idx.to_le_bytes().as_ref()],
+      bump = escrow.bump,
+    )]
+    escrow: Box<Account<'info, Escrow>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelDca<'info> {
+    #[account(mut)]
+    user: Signer<'info>,
+
+    #[account(
+      address=escrow.input_mint
+    )]
+    input_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=escrow.output_mint
+    )]
+    output_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      constraint=escrow.user==user.key(),
+      seeds = [ESCROW_SEED, escrow.user.as_ref(), escrow.input_mint.as_ref(), escrow.output_mint.as_ref(), escrow.idx.to_le_bytes().as_ref()],
+      bump = escrow.bump,
+    )]
+    escrow: Box<Account<'info, Escrow>>,
+
+    #[account(
+      mut,
+      associated_token::authority=escrow,
+      associated_token::mint=input_mint,
+    )]
+    escrow_in_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      associated_token::authority=escrow,
+      associated_token::mint=output_mint,
+    )]
+    escrow_out_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Jup DCA program
+    #[account(
+      address=jupiter_dca::ID
+    )]
+    jup_dca_program: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the escrow, Jup DCA will check the rest
+    #[account(
+      mut,
+      address=escrow.dca
+    )]
+    jup_dca: UncheckedAccount<'info>,
+
+    /// CHECK: Jup DCA will check
+    #[account(mut)]
+    jup_dca_in_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Jup DCA will check
+    #[account(mut)]
+    jup_dca_out_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Jup DCA will check
+    jup_dca_event_authority: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=user,
+      associated_token::authority=user,
+      associated_token::mint=input_mint,
+    )]
+    user_in_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Receives the DCA output, checked against the escrow
+    #[account(
+      address=escrow.beneficiary
+    )]
+    beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=user,
+      associated_token::authority=beneficiary,
+      associated_token::mint=output_mint,
+    )]
+    beneficiary_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Protocol treasury PDA, only used as a token authority
+    #[account(
+      seeds = [TREASURY_SEED],
+      bump
+    )]
+    treasury: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=user,
+      associated_token::authority=treasury,
+      associated_token::mint=input_mint,
+    )]
+    treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> CancelDca<'info> {
+    pub fn compute_penalty(refund: u64, penalty_bps: u16) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(refund as u128, penalty_bps as u128)?,
+            10000,
+        )?)
+    }
+}
+
+// Beneficiary and cancel policy can be set once, before the DCA completes, so a gifted or
+// vesting-style DCA cannot be redirected afterwards
+pub fn configure_escrow(
+    ctx: Context<ConfigureEscrow>,
+    beneficiary: Pubkey,
+    cancel_policy: CancelPolicy,
+) -> Result<()> {
+    let escrow = &mut ctx.accounts.escrow;
+    require!(!escrow.completed, TradingBotError::DCACompleted);
+    require!(!escrow.config_locked, TradingBotError::EscrowConfigLocked);
+    require!(
+        cancel_policy.penalty_bps() <= MAX_CANCEL_PENALTY_BPS,
+        TradingBotError::InvalidPenalty
+    );
+
+    escrow.beneficiary = beneficiary;
+    escrow.cancel_policy = cancel_policy;
+    escrow.config_locked = true;
+
+    Ok(())
+}
+
+// Closes the Jup DCA early: output bought so far goes to the beneficiary, unspent input back to
+// the user less any penalty, which goes to the protocol treasury. Cancelled escrows earn no airdrop.
+pub fn cancel_dca(ctx: Context<CancelDca>) -> Result<()> {
+    require!(!ctx.accounts.escrow.completed, TradingBotError::DCACompleted);
+
+    let idx_bytes = ctx.accounts.escrow.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[escrow_seeds!(ctx.accounts.escrow, idx_bytes)];
+
+    msg!("CPI call to close dca");
+    let close_dca_accounts = cpi::accounts::CloseDca {
+        user: ctx.accounts.escrow.to_account_info(),
+        dca: ctx.accounts.jup_dca.to_account_info(),
+        input_mint: ctx.accounts.input_mint.to_account_info(),
+        output_mint: ctx.accounts.output_mint.to_account_info(),
+        in_ata: ctx.accounts.jup_dca_in_ata.to_account_info(),
+        out_ata: ctx.accounts.jup_dca_out_ata.to_account_info(),
+        user_in_ata: ctx.accounts.escrow_in_ata.to_account_info(),
+        user_out_ata: ctx.accounts.escrow_out_ata.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        event_authority: ctx.accounts.jup_dca_event_authority.to_account_info(),
+        program: ctx.accounts.jup_dca_program.to_account_info(),
+    };
+    cpi::close_dca(CpiContext::new_with_signer(
+        ctx.accounts.jup_dca_program.to_account_info(),
+        close_dca_accounts,
+        signer_seeds,
+    ))?;
+
+    ctx.accounts.escrow_in_ata.reload()?;
+    ctx.accounts.escrow_out_ata.reload()?;
+    let refund = ctx.accounts.escrow_in_ata.amount;
+    let output = ctx.accounts.escrow_out_ata.amount;
+    let penalty =
+        CancelDca::compute_penalty(refund, ctx.accounts.escrow.cancel_policy.penalty_bps())?;
+    msg!(
+        "Refunding {} with penalty {}, output {}",
+        refund,
+        penalty,
+        output
+    );
+
+    let transfers = [
+        (
+            &ctx.accounts.escrow_in_ata,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            penalty,
+        ),
+        (
+            &ctx.accounts.escrow_in_ata,
+            ctx.accounts.user_in_token_account.to_account_info(),
+            math::checked_sub(refund, penalty)?,
+        ),
+        (
+            &ctx.accounts.escrow_out_ata,
+            ctx.accounts.beneficiary_token_account.to_account_info(),
+            output,
+        ),
+    ];
+    for (from, to, amount) in transfers {
+        if amount == 0 {
+            continue;
+        }
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to,
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+    }
+
+    for account in [&ctx.accounts.escrow_in_ata, &ctx.accounts.escrow_out_ata] {
+        anchor_spl::token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::CloseAccount {
+                account: account.to_account_info(),
+                destination: ctx.accounts.user.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.output_amount = output;
+    escrow.airdrop_amount = 0;
+    escrow.completed = true;
+    escrow.cancelled = true;
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use crate::constants::{EXECUTION_AUCTION_SEED, STRATEGY_SEED};
+use crate::events::{ExecutionAuctionSettled, ExecutionBidPlaced};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{AuctionPhase, ExecutionAuction, Strategy},
+};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+#[derive(Accounts)]
+pub struct ConfigureExecutionAuction<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      init_if_needed,
+      payer = owner,
+      space = ExecutionAuction::LEN,
+      seeds = [EXECUTION_AUCTION_SEED, strategy.key().as_ref()],
+      bump
+    )]
+    auction: Box<Account<'info, ExecutionAuction>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BidExecution<'info> {
+    #[account(mut)]
+    bidder: Signer<'info>,
+
+    #[account(
+      constraint=strategy.auctioned_execution @ TradingBotError::InvalidAuctionBid,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      has_one=strategy,
+      seeds = [EXECUTION_AUCTION_SEED, strategy.key().as_ref()],
+      bump = auction.bump,
+    )]
+    auction: Box<Account<'info, ExecutionAuction>>,
+
+    /// CHECK: The current best bidder, refunded when outbid
+    #[account(
+      mut,
+      address=auction.best_bidder
+    )]
+    outbid_bidder: Option<UncheckedAccount<'info>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleExecutionAuction<'info> {
+    #[account(
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      has_one=strategy,
+      seeds = [EXECUTION_AUCTION_SEED, strategy.key().as_ref()],
+      bump = auction.bump,
+    )]
+    auction: Box<Account<'info, ExecutionAuction>>,
+
+    /// CHECK: The strategy's current owner, receives the forfeited bid
+    #[account(
+      mut,
+      address=strategy.owner
+    )]
+    owner: UncheckedAccount<'info>,
+}
+
+impl<'info> SettleExecutionAuction<'info> {
+    // Moves the round's bid from the auction to the owner and ends the round
+    pub fn pay_rebate(
+        auction: &mut Account<'info, ExecutionAuction>,
+        owner: &AccountInfo<'info>,
+        executed: bool,
+    ) -> Result<()> {
+        let winner = auction.best_bidder;
+        let rebate = auction.settle();
+        let auction_info = auction.to_account_info();
+        **auction_info.try_borrow_mut_lamports()? =
+            math::checked_sub(auction_info.lamports(), rebate)?;
+        **owner.try_borrow_mut_lamports()? = math::checked_add(owner.lamports(), rebate)?;
+
+        emit!(ExecutionAuctionSettled {
+            strategy: auction.strategy,
+            winner,
+            rebate,
+            executed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+// Turns auctioned execution on or off; windows take effect from the next round. Turning it off
+// while a bid is held is refused so none is stranded.
+pub fn configure_execution_auction(
+    ctx: Context<ConfigureExecutionAuction>,
+    enabled: bool,
+    bid_window: i64,
+    exclusivity_window: i64,
+    min_bid: u64,
+) -> Result<()> {
+    require!(
+        bid_window > 0 && exclusivity_window > 0,
+        TradingBotError::InvalidStrategyConfig
+    );
+    let auction = &mut ctx.accounts.auction;
+    if auction.strategy == Pubkey::default() {
+        auction.strategy = ctx.accounts.strategy.key();
+        auction.bump = *ctx.bumps.get("auction").unwrap();
+    }
+    require!(
+        enabled || auction.best_bid == 0,
+        TradingBotError::InvalidAuctionBid
+    );
+    auction.bid_window = bid_window;
+    auction.exclusivity_window = exclusivity_window;
+    auction.min_bid = min_bid;
+    ctx.accounts.strategy.auctioned_execution = enabled;
+
+    Ok(())
+}
+
+// The first bid after a round ends opens the next one; the bid is escrowed in the auction
+pub fn bid_execution(ctx: Context<BidExecution>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let bidder = ctx.accounts.bidder.key();
+    let outbid = ctx.accounts.auction.bid(bidder, amount, now)?;
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bidder.to_account_info(),
+                to: ctx.accounts.auction.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    if let Some(refund) = outbid {
+        let outbid_info = ctx
+            .accounts
+            .outbid_bidder
+            .as_ref()
+            .ok_or(TradingBotError::InvalidAuctionBid)?
+            .to_account_info();
+        let auction_info = ctx.accounts.auction.to_account_info();
+        **auction_info.try_borrow_mut_lamports()? =
+            math::checked_sub(auction_info.lamports(), refund)?;
+        **outbid_info.try_borrow_mut_lamports()? =
+            math::checked_add(outbid_info.lamports(), refund)?;
+    }
+
+    emit!(ExecutionBidPlaced {
+        strategy: ctx.accounts.auction.strategy,
+        bidder,
+        amount,
+        round_started_at: ctx.accounts.auction.round_started_at,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+// Permissionless; forfeits the bid of a winner that let its exclusivity lapse to the owner
+pub fn settle_execution_auction(ctx: Context<SettleExecutionAuction>) -> Result<()> {
+    let phase = ctx.accounts.auction.phase(Clock::get()?.unix_timestamp);
+    require!(
+        phase == AuctionPhase::Expired,
+        TradingBotError::ExecutionAuctionActive
+    );
+    let owner = ctx.accounts.owner.to_account_info();
+    SettleExecutionAuction::pay_rebate(&mut ctx.accounts.auction, &owner, false)
+}
@@ -0,0 +1,142 @@
+use crate::constants::EXECUTION_METRICS_SEED;
+use crate::events::{FillAttributed, VenueBanned};
+use crate::types::DexType;
+use crate::{
+    errors::TradingBotError,
+    state::{ExecutionMetrics, FillPrices, SlippageAttribution, VenueBanThresholds, VenueStats},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitExecutionMetrics<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = ExecutionMetrics::LEN,
+      seeds = [EXECUTION_METRICS_SEED, owner.key().as_ref()],
+      bump
+    )]
+    execution_metrics: Box<Account<'info, ExecutionMetrics>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordVenueExecution<'info> {
+    reporter: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=reporter,
+      seeds = [EXECUTION_METRICS_SEED, execution_metrics.owner.as_ref()],
+      bump = execution_metrics.bump,
+    )]
+    execution_metrics: Box<Account<'info, ExecutionMetrics>>,
+}
+
+#[derive(Accounts)]
+pub struct ManageVenue<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [EXECUTION_METRICS_SEED, execution_metrics.owner.as_ref()],
+      bump = execution_metrics.bump,
+    )]
+    execution_metrics: Box<Account<'info, ExecutionMetrics>>,
+}
+
+pub fn init_execution_metrics(
+    ctx: Context<InitExecutionMetrics>,
+    reporter: Pubkey,
+    thresholds: VenueBanThresholds,
+) -> Result<()> {
+    require!(
+        thresholds.max_failure_rate_bps <= 10000,
+        TradingBotError::InvalidStrategyConfig
+    );
+
+    let execution_metrics = &mut ctx.accounts.execution_metrics;
+    execution_metrics.owner = ctx.accounts.owner.key();
+    execution_metrics.reporter = reporter;
+    execution_metrics.thresholds = thresholds;
+    execution_metrics.venues = [VenueStats::default(); DexType::COUNT];
+    execution_metrics.bump = *ctx.bumps.get("execution_metrics").unwrap();
+
+    Ok(())
+}
+
+// Slippage is against the quoted minimum out, deviation against the oracle price at execution
+pub fn record_venue_execution(
+    ctx: Context<RecordVenueExecution>,
+    dex: DexType,
+    success: bool,
+    slippage_bps: u64,
+    deviation_bps: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let execution_metrics = &mut ctx.accounts.execution_metrics;
+    let banned =
+        execution_metrics.record_execution(dex, success, slippage_bps, deviation_bps, now)?;
+    if banned {
+        let stats = &execution_metrics.venues[dex.index()];
+        msg!("Banning {:?} from routing", dex);
+        emit!(VenueBanned {
+            owner: execution_metrics.owner,
+            dex,
+            avg_slippage_bps: stats.avg_slippage_bps,
+            avg_deviation_bps: stats.avg_deviation_bps,
+            failure_rate_bps: stats.failure_rate_bps,
+            timestamp: now,
+        });
+    }
+
+    Ok(())
+}
+
+// Attributes a fill's cost against the oracle mid the strategy decided on, so execution quality
+// can be compared per venue by where it was lost rather than as one slippage number
+pub fn record_fill_attribution(
+    ctx: Context<RecordVenueExecution>,
+    dex: DexType,
+    prices: FillPrices,
+) -> Result<()> {
+    let attribution = SlippageAttribution::from_prices(&prices)?;
+    let execution_metrics = &mut ctx.accounts.execution_metrics;
+    execution_metrics.record_attribution(dex, &attribution)?;
+
+    emit!(FillAttributed {
+        owner: execution_metrics.owner,
+        dex,
+        decision_mid: prices.decision_mid,
+        quoted_price: prices.quoted_price,
+        realized_price: prices.realized_price,
+        latency_bps: attribution.latency_bps,
+        spread_bps: attribution.spread_bps,
+        impact_bps: attribution.impact_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn reenable_venue(ctx: Context<ManageVenue>, dex: DexType) -> Result<()> {
+    ctx.accounts.execution_metrics.reenable(dex);
+    Ok(())
+}
+
+pub fn set_venue_ban_thresholds(
+    ctx: Context<ManageVenue>,
+    thresholds: VenueBanThresholds,
+) -> Result<()> {
+    require!(
+        thresholds.max_failure_rate_bps <= 10000,
+        TradingBotError::InvalidStrategyConfig
+    );
+    ctx.accounts.execution_metrics.thresholds = thresholds;
+    Ok(())
+}
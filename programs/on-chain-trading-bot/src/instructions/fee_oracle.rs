@@ -0,0 +1,138 @@
+use crate::constants::FEE_ORACLE_SEED;
+use crate::{
+    errors::TradingBotError,
+    state::{FeeObservation, FeeOracle, FeePercentile, FEE_PERCENTILES, MAX_FEE_KEEPERS},
+};
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PriorityFeeQuote {
+    pub fee_oracle: Pubkey,
+    // median micro-lamports per compute unit, indexed by FeePercentile
+    pub fees: [u64; FEE_PERCENTILES],
+    // total priority fee in lamports for the requested compute units at each percentile
+    pub totals: [u64; FEE_PERCENTILES],
+    pub observations: u8,
+}
+
+#[derive(Accounts)]
+pub struct InitFeeOracle<'info> {
+    #[account(mut)]
+    authority: Signer<'info>,
+
+    #[account(
+      init,
+      payer = authority,
+      space = FeeOracle::LEN,
+      seeds = [FEE_ORACLE_SEED, authority.key().as_ref()],
+      bump
+    )]
+    fee_oracle: Box<Account<'info, FeeOracle>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageFeeKeepers<'info> {
+    authority: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=authority,
+      seeds = [FEE_ORACLE_SEED, fee_oracle.authority.as_ref()],
+      bump = fee_oracle.bump,
+    )]
+    fee_oracle: Box<Account<'info, FeeOracle>>,
+}
+
+#[derive(Accounts)]
+pub struct PostPriorityFees<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [FEE_ORACLE_SEED, fee_oracle.authority.as_ref()],
+      bump = fee_oracle.bump,
+    )]
+    fee_oracle: Box<Account<'info, FeeOracle>>,
+}
+
+#[derive(Accounts)]
+pub struct ViewPriorityFees<'info> {
+    #[account(
+      seeds = [FEE_ORACLE_SEED, fee_oracle.authority.as_ref()],
+      bump = fee_oracle.bump,
+    )]
+    fee_oracle: Box<Account<'info, FeeOracle>>,
+}
+
+pub fn init_fee_oracle(ctx: Context<InitFeeOracle>, max_age: i64) -> Result<()> {
+    require_gt!(max_age, 0, TradingBotError::InvalidFeeOracle);
+
+    let fee_oracle = &mut ctx.accounts.fee_oracle;
+    fee_oracle.authority = ctx.accounts.authority.key();
+    fee_oracle.observations = [FeeObservation::default(); MAX_FEE_KEEPERS];
+    fee_oracle.keeper_count = 0;
+    fee_oracle.max_age = max_age;
+    fee_oracle.bump = *ctx.bumps.get("fee_oracle").unwrap();
+
+    Ok(())
+}
+
+pub fn add_fee_keeper(ctx: Context<ManageFeeKeepers>, keeper: Pubkey) -> Result<()> {
+    ctx.accounts.fee_oracle.add_keeper(keeper)
+}
+
+pub fn remove_fee_keeper(ctx: Context<ManageFeeKeepers>, keeper: Pubkey) -> Result<()> {
+    ctx.accounts.fee_oracle.remove_keeper(&keeper)
+}
+
+// Percentiles of the priority fees the keeper saw land recently, e.g. from
+// getRecentPrioritizationFees, in micro-lamports per compute unit
+pub fn post_priority_fees(
+    ctx: Context<PostPriorityFees>,
+    fees: [u64; FEE_PERCENTILES],
+) -> Result<()> {
+    require!(
+        fees.windows(2).all(|pair| pair[0] <= pair[1]),
+        TradingBotError::InvalidFeeOracle
+    );
+    let fee_oracle = &mut ctx.accounts.fee_oracle;
+    let index = fee_oracle
+        .keeper_index(ctx.accounts.keeper.key)
+        .ok_or(TradingBotError::NotAFeeKeeper)?;
+    let clock = Clock::get()?;
+    let observation = &mut fee_oracle.observations[index];
+    observation.fees = fees;
+    observation.slot = clock.slot;
+    observation.posted_at = clock.unix_timestamp;
+
+    Ok(())
+}
+
+// Read-only, like view_strategy_summary. Pass the transaction's compute unit limit, e.g. from
+// estimated_cu_for_next_execution, to get the totals in lamports.
+pub fn view_priority_fees(
+    ctx: Context<ViewPriorityFees>,
+    compute_units: u64,
+) -> Result<PriorityFeeQuote> {
+    let fee_oracle = &ctx.accounts.fee_oracle;
+    let now = Clock::get()?.unix_timestamp;
+    let mut quote = PriorityFeeQuote {
+        fee_oracle: fee_oracle.key(),
+        fees: [0; FEE_PERCENTILES],
+        totals: [0; FEE_PERCENTILES],
+        observations: 0,
+    };
+    for percentile in [FeePercentile::P50, FeePercentile::P75, FeePercentile::P90] {
+        let (fee, observations) = fee_oracle.median_fee(percentile, now);
+        quote.fees[percentile.index()] = fee;
+        quote.observations = observations;
+        if observations > 0 {
+            quote.totals[percentile.index()] =
+                fee_oracle.max_priority_fee(percentile, compute_units, now)?;
+        }
+    }
+
+    Ok(quote)
+}
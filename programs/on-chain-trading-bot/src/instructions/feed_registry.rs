@@ -0,0 +1,108 @@
+use crate::constants::FEED_REGISTRY_SEED;
+use crate::oracles::PythOracle;
+use crate::program::OnChainTradingBot;
+use crate::{
+    errors::TradingBotError,
+    state::{FeedRegistry, RegisteredFeed},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct InitFeedRegistry<'info> {
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    #[account(
+      init,
+      payer = admin,
+      space = FeedRegistry::LEN,
+      seeds = [FEED_REGISTRY_SEED],
+      bump
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      constraint=program.programdata_address()? == Some(program_data.key())
+    )]
+    program: Program<'info, OnChainTradingBot>,
+
+    // only the upgrade authority can claim the registry
+    #[account(
+      constraint=program_data.upgrade_authority_address == Some(admin.key()) @ TradingBotError::UnauthorizedExecutor
+    )]
+    program_data: Box<Account<'info, ProgramData>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageFeedRegistry<'info> {
+    admin: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=admin,
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+#[derive(Accounts)]
+#[instruction(feed: RegisteredFeed)]
+pub struct SetRegisteredFeed<'info> {
+    admin: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=admin,
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      address=feed.mint,
+      constraint = mint.decimals == feed.decimals @ TradingBotError::InvalidRegisteredFeed,
+    )]
+    mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: The feed being registered; its current exponent has to be the one registered
+    #[account(
+      address=feed.pyth_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+}
+
+pub fn init_feed_registry(ctx: Context<InitFeedRegistry>) -> Result<()> {
+    let feed_registry = &mut ctx.accounts.feed_registry;
+    feed_registry.admin = ctx.accounts.admin.key();
+    feed_registry.feeds = Vec::new();
+    feed_registry.bump = *ctx.bumps.get("feed_registry").unwrap();
+
+    Ok(())
+}
+
+// Adds the mint's feeds or replaces them; readers pick up the change on their next read. The
+// mint's decimals and the feed's exponent are checked against the accounts, since every price
+// read scales by them.
+pub fn set_registered_feed(ctx: Context<SetRegisteredFeed>, feed: RegisteredFeed) -> Result<()> {
+    feed.validate()?;
+    feed.check_expo(PythOracle::feed_expo(&ctx.accounts.price_feed)?)?;
+    ctx.accounts.feed_registry.set(feed)?;
+    msg!("Registered feed {} for {}", feed.pyth_feed, feed.mint);
+
+    Ok(())
+}
+
+// Every price read of the mint fails until it is registered again
+pub fn remove_registered_feed(ctx: Context<ManageFeedRegistry>, mint: Pubkey) -> Result<()> {
+    ctx.accounts.feed_registry.remove(&mint)
+}
+
+pub fn set_feed_registry_admin(ctx: Context<ManageFeedRegistry>, admin: Pubkey) -> Result<()> {
+    ctx.accounts.feed_registry.admin = admin;
+
+    Ok(())
+}
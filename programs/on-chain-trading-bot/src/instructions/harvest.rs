@@ -0,0 +1,157 @@
+use crate::constants::STRATEGY_SEED;
+use crate::dex::{
+    jupiter::JUPITER_V6_PROGRAM_ID,
+    orca::ORCA_WHIRLPOOL_PROGRAM_ID,
+    raydium::{RAYDIUM_AMM_PROGRAM_ID, RAYDIUM_FARM_PROGRAM_ID, RAYDIUM_V3_PROGRAM_ID},
+};
+use crate::{errors::TradingBotError, math, state::Strategy};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct HarvestRewards<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+    )]
+    reward_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+      token::mint=strategy.config.pair.base_mint,
+    )]
+    base_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Checked against the supported farms
+    farm_program: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the supported swap programs when a swap is requested
+    swap_program: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> HarvestRewards<'info> {
+    pub fn is_supported_farm(program_id: &Pubkey) -> bool {
+        [
+            RAYDIUM_FARM_PROGRAM_ID,
+            RAYDIUM_V3_PROGRAM_ID,
+            ORCA_WHIRLPOOL_PROGRAM_ID,
+        ]
+        .iter()
+        .any(|id| *program_id == id.parse::<Pubkey>().unwrap())
+    }
+
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    pub fn invoke_passthrough(
+        program_id: Pubkey,
+        accounts: &[AccountInfo<'info>],
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let ix = Instruction {
+            program_id,
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), a.is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), a.is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        invoke(&ix, accounts)?;
+        Ok(())
+    }
+}
+
+// Claims farm emissions and, when the reward isn't the strategy's base asset, swaps them into it.
+// The first `claim_accounts_len` remaining accounts go to the claim, the rest to the swap. Income
+// is booked in base units under the strategy's reward metrics, separate from trading P&L.
+pub fn harvest_rewards<'info>(
+    ctx: Context<'_, '_, '_, 'info, HarvestRewards<'info>>,
+    claim_data: Vec<u8>,
+    claim_accounts_len: u8,
+    swap_data: Option<Vec<u8>>,
+    min_base_out: u64,
+) -> Result<()> {
+    require!(
+        HarvestRewards::is_supported_farm(ctx.accounts.farm_program.key),
+        TradingBotError::InvalidDexType
+    );
+    let claim_accounts_len = claim_accounts_len as usize;
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        claim_accounts_len,
+        TradingBotError::InvalidCalculation
+    );
+    let (claim_accounts, swap_accounts) = ctx.remaining_accounts.split_at(claim_accounts_len);
+
+    let reward_before = ctx.accounts.reward_account.amount;
+    HarvestRewards::invoke_passthrough(
+        ctx.accounts.farm_program.key(),
+        claim_accounts,
+        claim_data,
+    )?;
+    ctx.accounts.reward_account.reload()?;
+    let claimed = math::checked_sub(ctx.accounts.reward_account.amount, reward_before)?;
+    msg!("Claimed {} rewards", claimed);
+
+    let income = match swap_data {
+        Some(swap_data) => {
+            let swap_program = ctx
+                .accounts
+                .swap_program
+                .as_ref()
+                .ok_or(TradingBotError::InvalidDexType)?;
+            require!(
+                HarvestRewards::is_supported_swap_program(swap_program.key),
+                TradingBotError::InvalidDexType
+            );
+
+            let base_before = ctx.accounts.base_account.amount;
+            HarvestRewards::invoke_passthrough(swap_program.key(), swap_accounts, swap_data)?;
+            ctx.accounts.reward_account.reload()?;
+            ctx.accounts.base_account.reload()?;
+
+            // only the freshly claimed rewards may be sold
+            require_gte!(
+                ctx.accounts.reward_account.amount,
+                reward_before,
+                TradingBotError::InvalidCalculation
+            );
+            let received = math::checked_sub(ctx.accounts.base_account.amount, base_before)?;
+            require_gte!(received, min_base_out, TradingBotError::SlippageExceeded);
+            received
+        }
+        None => {
+            require_keys_eq!(
+                ctx.accounts.reward_account.mint,
+                ctx.accounts.base_account.mint,
+                TradingBotError::InvalidCalculation
+            );
+            claimed
+        }
+    };
+
+    msg!("Booking {} base as reward income", income);
+    ctx.accounts
+        .strategy
+        .record_reward_income(income, Clock::get()?.unix_timestamp)
+}
@@ -0,0 +1,1358 @@
+use crate::constants::{
+    FEED_REGISTRY_SEED, INDEX_DEPOSITOR_SEED, INDEX_FUND_SEED, INDEX_REDEMPTION_SEED,
+    MAX_SLIPPAGE_BPS, PRICE_PRECISION, SHARE_MINT_SEED, TRADING_VAULT_SEED, UPGRADE_GUARD_SEED,
+    USD_DECIMALS, VAULT_LOG_SEED,
+};
+use crate::dex::{jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID};
+use crate::events::{
+    IndexEmergencySet, IndexEmergencyWithdrawal, IndexExitFeeCharged, IndexFlow, IndexRebalanced,
+    IndexReconstituted,
+};
+use crate::oracles::PythOracle;
+use crate::valuation::{PositionValuation, Valuation};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        DepositLimits, FeedRegistry, IndexComponent, IndexDepositor, IndexFund, IndexRedemption,
+        TradingVault, UpgradeGuard, VaultLog, VaultOp, WeightRule,
+        DEFAULT_RECONSTITUTION_INTERVAL, MAX_EARLY_EXIT_FEE_BPS, MAX_EMERGENCY_SLIPPAGE_BPS,
+    },
+    trading_vault_seeds,
+};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::Instruction,
+    program::{invoke, invoke_signed},
+};
+use anchor_lang::system_program::CreateAccount;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::spl_token_2022::{self, extension::ExtensionType};
+use anchor_spl::token_2022::{Burn, InitializeMint2, MintTo, Token2022};
+
+#[derive(Accounts)]
+pub struct InitIndexFund<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, base_vault.creator.as_ref(), base_vault.mint.as_ref()],
+      bump = base_vault.bump,
+    )]
+    base_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = IndexFund::LEN,
+      seeds = [INDEX_FUND_SEED, base_vault.key().as_ref()],
+      bump
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    /// CHECK: Created here as a Token-2022 mint, with the non-transferable extension if asked for
+    #[account(
+      mut,
+      seeds = [SHARE_MINT_SEED, index_fund.key().as_ref()],
+      bump
+    )]
+    share_mint: UncheckedAccount<'info>,
+
+    // numbers the fund for post_upgrade_check
+    #[account(
+      mut,
+      seeds = [UPGRADE_GUARD_SEED],
+      bump = upgrade_guard.bump,
+    )]
+    upgrade_guard: Box<Account<'info, UpgradeGuard>>,
+
+    system_program: Program<'info, System>,
+    share_token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct AddIndexComponent<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    #[account(
+      has_one=owner,
+      constraint=component_vault.key()!=index_fund.base_vault @ TradingBotError::InvalidIndexComponent,
+      seeds = [TRADING_VAULT_SEED, component_vault.creator.as_ref(), component_vault.mint.as_ref()],
+      bump = component_vault.bump,
+    )]
+    component_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=component_vault.mint
+    )]
+    mint: Box<Account<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestIndexWithdrawal<'info> {
+    #[account(mut)]
+    holder: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    /// CHECK: The fund's share mint
+    #[account(
+      mut,
+      address=index_fund.share_mint
+    )]
+    share_mint: UncheckedAccount<'info>,
+
+    /// CHECK: The holder's shares; the token program checks the mint and the holder's signature
+    #[account(mut)]
+    holder_share_account: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer = holder,
+      space = IndexRedemption::LEN,
+      seeds = [INDEX_REDEMPTION_SEED, index_fund.key().as_ref(), holder.key().as_ref()],
+      bump
+    )]
+    redemption: Box<Account<'info, IndexRedemption>>,
+
+    // the holder's lock-up; holders who never deposited get an unlocked record
+    #[account(
+      init_if_needed,
+      payer = holder,
+      space = IndexDepositor::LEN,
+      seeds = [INDEX_DEPOSITOR_SEED, index_fund.key().as_ref(), holder.key().as_ref()],
+      bump
+    )]
+    index_depositor: Box<Account<'info, IndexDepositor>>,
+
+    system_program: Program<'info, System>,
+    share_token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct IndexTransfer<'info> {
+    // any depositor within the fund's limits; the units are minted to them as shares
+    #[account(mut)]
+    depositor: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    #[account(
+      init_if_needed,
+      payer = depositor,
+      space = IndexDepositor::LEN,
+      seeds = [INDEX_DEPOSITOR_SEED, index_fund.key().as_ref(), depositor.key().as_ref()],
+      bump
+    )]
+    index_depositor: Box<Account<'info, IndexDepositor>>,
+
+    #[account(
+      address=index_fund.base_vault
+    )]
+    base_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=base_vault.token_account
+    )]
+    base_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, base_vault.key().as_ref()],
+      bump,
+    )]
+    base_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      address=base_vault.mint
+    )]
+    base_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Resolved through the feed registry on read
+    base_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      mut,
+      token::authority=depositor,
+      token::mint=base_vault.mint,
+    )]
+    depositor_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The fund's share mint
+    #[account(
+      mut,
+      address=index_fund.share_mint
+    )]
+    share_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the shares; the token program checks its mint
+    #[account(mut)]
+    share_account: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    share_token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawIndex<'info> {
+    #[account(mut)]
+    holder: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    #[account(
+      mut,
+      has_one=holder,
+      close=holder,
+      seeds = [INDEX_REDEMPTION_SEED, index_fund.key().as_ref(), holder.key().as_ref()],
+      bump = redemption.bump,
+    )]
+    redemption: Box<Account<'info, IndexRedemption>>,
+
+    // the holder's deposits, if they made any, which the payout is taken off
+    #[account(
+      mut,
+      seeds = [INDEX_DEPOSITOR_SEED, index_fund.key().as_ref(), holder.key().as_ref()],
+      bump = index_depositor.bump,
+    )]
+    index_depositor: Option<Box<Account<'info, IndexDepositor>>>,
+
+    #[account(
+      address=index_fund.base_vault
+    )]
+    base_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=base_vault.token_account
+    )]
+    base_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, base_vault.key().as_ref()],
+      bump,
+    )]
+    base_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      address=base_vault.mint
+    )]
+    base_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Resolved through the feed registry on read
+    base_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      mut,
+      token::authority=holder,
+      token::mint=base_vault.mint,
+    )]
+    holder_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ManageIndexFund<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct SetIndexDepositor<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    #[account(
+      init_if_needed,
+      payer = owner,
+      space = IndexDepositor::LEN,
+      seeds = [INDEX_DEPOSITOR_SEED, index_fund.key().as_ref(), wallet.as_ref()],
+      bump
+    )]
+    index_depositor: Box<Account<'info, IndexDepositor>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyWithdrawIndex<'info> {
+    #[account(mut)]
+    holder: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    /// CHECK: The fund's share mint
+    #[account(
+      mut,
+      address=index_fund.share_mint
+    )]
+    share_mint: UncheckedAccount<'info>,
+
+    /// CHECK: The holder's shares; the token program checks the mint and the holder's signature
+    #[account(mut)]
+    holder_share_account: UncheckedAccount<'info>,
+
+    // units the holder already asked to withdraw, paid out alongside
+    #[account(
+      mut,
+      has_one=holder,
+      close=holder,
+      seeds = [INDEX_REDEMPTION_SEED, index_fund.key().as_ref(), holder.key().as_ref()],
+      bump = redemption.bump,
+    )]
+    redemption: Option<Box<Account<'info, IndexRedemption>>>,
+
+    #[account(
+      address=index_fund.base_vault
+    )]
+    base_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=base_vault.token_account
+    )]
+    base_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, base_vault.key().as_ref()],
+      bump,
+    )]
+    base_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      mut,
+      token::authority=holder,
+      token::mint=base_vault.mint,
+    )]
+    holder_token_account: Box<Account<'info, TokenAccount>>,
+
+    token_program: Program<'info, Token>,
+    share_token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct ReconstituteIndex<'info> {
+    #[account(
+      mut,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct RebalanceIndex<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    #[account(
+      address=index_fund.base_vault
+    )]
+    base_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=base_vault.token_account
+    )]
+    base_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, base_vault.key().as_ref()],
+      bump,
+    )]
+    base_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      address=base_vault.mint
+    )]
+    base_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Resolved through the feed registry on read
+    base_price_feed: UncheckedAccount<'info>,
+
+    // checked against the rebalanced component
+    component_vault: Box<Account<'info, TradingVault>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, component_vault.key().as_ref()],
+      bump,
+    )]
+    component_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Checked against the supported swap programs
+    swap_program: UncheckedAccount<'info>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+fn usd_value(
+    registry: &FeedRegistry,
+    amount: u64,
+    mint: &Pubkey,
+    decimals: u8,
+    price_feed: &AccountInfo,
+) -> Result<u64> {
+    let usd_price = PythOracle::get_usd_price(registry, mint, price_feed, u64::MAX)?;
+    PythOracle::usd_value(amount, decimals, usd_price)
+}
+
+// USD value of each component's vault; `accounts` are each component's token account and price
+// feed, in order
+fn component_values(
+    index_fund: &IndexFund,
+    registry: &FeedRegistry,
+    accounts: &[AccountInfo],
+) -> Result<Vec<u64>> {
+    require_eq!(
+        accounts.len(),
+        index_fund.components().len() * 2,
+        TradingBotError::InvalidIndexComponent
+    );
+    index_fund
+        .components()
+        .iter()
+        .zip(accounts.chunks(2))
+        .map(|(component, accounts)| {
+            require_keys_eq!(
+                accounts[0].key(),
+                component.token_account,
+                TradingBotError::InvalidIndexComponent
+            );
+            let token_account = Account::<TokenAccount>::try_from(&accounts[0])?;
+            usd_value(
+                registry,
+                token_account.amount,
+                &component.mint,
+                component.decimals,
+                &accounts[1],
+            )
+        })
+        .collect()
+}
+
+macro_rules! index_fund_seeds {
+    ( $fund:expr ) => {
+        &[INDEX_FUND_SEED, $fund.base_vault.as_ref(), &[$fund.bump]]
+    };
+}
+
+impl<'info> InitIndexFund<'info> {
+    // Shares carry USD_DECIMALS, as the first deposit prices a unit at one USD, and are minted by
+    // the fund's PDA. Non-transferable shares can only be minted and burned.
+    pub fn create_share_mint(&self, non_transferable: bool, mint_bump: u8) -> Result<()> {
+        let extensions = if non_transferable {
+            vec![ExtensionType::NonTransferable]
+        } else {
+            vec![]
+        };
+        let space = ExtensionType::get_account_len::<spl_token_2022::state::Mint>(&extensions);
+        let index_fund = self.index_fund.key();
+        let mint_seeds: &[&[u8]] = &[SHARE_MINT_SEED, index_fund.as_ref(), &[mint_bump]];
+        anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                CreateAccount {
+                    from: self.owner.to_account_info(),
+                    to: self.share_mint.to_account_info(),
+                },
+                &[mint_seeds],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::ID,
+        )?;
+        if non_transferable {
+            invoke(
+                &spl_token_2022::instruction::initialize_non_transferable_mint(
+                    &spl_token_2022::ID,
+                    self.share_mint.key,
+                )?,
+                &[self.share_mint.to_account_info()],
+            )?;
+        }
+        anchor_spl::token_2022::initialize_mint2(
+            CpiContext::new(
+                self.share_token_program.to_account_info(),
+                InitializeMint2 {
+                    mint: self.share_mint.to_account_info(),
+                },
+            ),
+            USD_DECIMALS,
+            &index_fund,
+            None,
+        )
+    }
+}
+
+impl<'info> IndexTransfer<'info> {
+    pub fn base_value(&self, amount: u64) -> Result<u64> {
+        usd_value(
+            &self.feed_registry,
+            amount,
+            &self.base_mint.key(),
+            self.base_mint.decimals,
+            &self.base_price_feed,
+        )
+    }
+
+    // Component values and NAV of a fund whose base vault holds `base_amount`; `accounts` are
+    // each component's token account and price feed, in order. The NAV goes through the same
+    // valuation as every other position, with each holding already in USD.
+    pub fn fund_nav(
+        index_fund: &IndexFund,
+        registry: &FeedRegistry,
+        base_amount: u64,
+        base_mint: &Account<Mint>,
+        base_price_feed: &AccountInfo,
+        accounts: &[AccountInfo],
+    ) -> Result<(Vec<u64>, u64)> {
+        let components = component_values(index_fund, registry, accounts)?;
+        let base = usd_value(
+            registry,
+            base_amount,
+            &base_mint.key(),
+            base_mint.decimals,
+            base_price_feed,
+        )?;
+        let holdings: Vec<PositionValuation> = std::iter::once(base)
+            .chain(components.iter().copied())
+            .map(|amount| PositionValuation::Token {
+                amount,
+                price: PRICE_PRECISION,
+            })
+            .collect();
+        let nav = Valuation::nav(&holdings)?;
+        Ok((components, nav))
+    }
+
+    pub fn nav(&self, accounts: &[AccountInfo]) -> Result<u64> {
+        let (_, nav) = Self::fund_nav(
+            &self.index_fund,
+            &self.feed_registry,
+            self.base_token_account.amount,
+            &self.base_mint,
+            &self.base_price_feed,
+            accounts,
+        )?;
+        Ok(nav)
+    }
+}
+
+impl<'info> WithdrawIndex<'info> {
+    pub fn base_value(&self, amount: u64) -> Result<u64> {
+        usd_value(
+            &self.feed_registry,
+            amount,
+            &self.base_mint.key(),
+            self.base_mint.decimals,
+            &self.base_price_feed,
+        )
+    }
+
+    pub fn nav(&self, accounts: &[AccountInfo]) -> Result<u64> {
+        let (_, nav) = IndexTransfer::fund_nav(
+            &self.index_fund,
+            &self.feed_registry,
+            self.base_token_account.amount,
+            &self.base_mint,
+            &self.base_price_feed,
+            accounts,
+        )?;
+        Ok(nav)
+    }
+}
+
+impl<'info> RebalanceIndex<'info> {
+    // logs what the swap moved in and out of the base and component vaults
+    fn log_swap(
+        &self,
+        base_before: u64,
+        component_before: u64,
+        component_after: u64,
+    ) -> Result<()> {
+        VaultLog::append_if_initialized(
+            &self.base_vault_log,
+            VaultOp::Preset,
+            self.index_fund.key(),
+            base_before.abs_diff(self.base_token_account.amount),
+            &self.base_vault,
+            Some(self.base_token_account.amount),
+        )?;
+        VaultLog::append_if_initialized(
+            &self.component_vault_log,
+            VaultOp::Preset,
+            self.index_fund.key(),
+            component_before.abs_diff(component_after),
+            &self.component_vault,
+            Some(component_after),
+        )
+    }
+
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    pub fn value(&self, accounts: &[AccountInfo]) -> Result<(Vec<u64>, u64)> {
+        IndexTransfer::fund_nav(
+            &self.index_fund,
+            &self.feed_registry,
+            self.base_token_account.amount,
+            &self.base_mint,
+            &self.base_price_feed,
+            accounts,
+        )
+    }
+
+    // Either vault may spend, so both PDAs are flagged as signers in the forwarded metas
+    pub fn invoke_swap(&self, accounts: &[AccountInfo<'info>], data: Vec<u8>) -> Result<()> {
+        let vault_keys = [self.base_vault.key(), self.component_vault.key()];
+        let ix = Instruction {
+            program_id: self.swap_program.key(),
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    let is_signer = a.is_signer || vault_keys.contains(&a.key());
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        invoke_signed(
+            &ix,
+            accounts,
+            &[
+                trading_vault_seeds!(self.base_vault),
+                trading_vault_seeds!(self.component_vault),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+pub fn init_index_fund(
+    ctx: Context<InitIndexFund>,
+    weight_rule: WeightRule,
+    non_transferable: bool,
+    rebalance_window: i64,
+    drift_bps: u16,
+    max_slippage_bps: u16,
+) -> Result<()> {
+    require_gt!(rebalance_window, 0, TradingBotError::InvalidIndexParams);
+    require!(
+        drift_bps > 0 && drift_bps < 10000,
+        TradingBotError::InvalidIndexParams
+    );
+    require_gte!(
+        MAX_SLIPPAGE_BPS,
+        max_slippage_bps,
+        TradingBotError::InvalidIndexParams
+    );
+
+    ctx.accounts
+        .create_share_mint(non_transferable, *ctx.bumps.get("share_mint").unwrap())?;
+
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.owner = ctx.accounts.owner.key();
+    index_fund.base_vault = ctx.accounts.base_vault.key();
+    index_fund.share_mint = ctx.accounts.share_mint.key();
+    index_fund.non_transferable = non_transferable;
+    index_fund.weight_rule = weight_rule;
+    index_fund.components = Default::default();
+    index_fund.component_count = 0;
+    index_fund.reconstitution_interval = DEFAULT_RECONSTITUTION_INTERVAL;
+    index_fund.rebalance_window = rebalance_window;
+    index_fund.last_reconstitution = 0;
+    index_fund.rebalance_until = 0;
+    index_fund.drift_bps = drift_bps;
+    index_fund.max_slippage_bps = max_slippage_bps;
+    index_fund.limits = DepositLimits::default();
+    index_fund.units = 0;
+    index_fund.pending_withdrawal_units = 0;
+    index_fund.nav_per_unit = 0;
+    index_fund.reconstitutions = 0;
+    index_fund.seq = ctx.accounts.upgrade_guard.next_index_fund_seq()?;
+    index_fund.bump = *ctx.bumps.get("index_fund").unwrap();
+
+    Ok(())
+}
+
+// A new component gets no target until the next reconstitution
+pub fn add_index_component(
+    ctx: Context<AddIndexComponent>,
+    static_weight_bps: u16,
+    max_weight_bps: u16,
+) -> Result<()> {
+    require!(
+        max_weight_bps > 0 && max_weight_bps <= 10000,
+        TradingBotError::InvalidIndexParams
+    );
+    let component_vault = &ctx.accounts.component_vault;
+    ctx.accounts.index_fund.add_component(IndexComponent {
+        trading_vault: component_vault.key(),
+        token_account: component_vault.token_account,
+        mint: component_vault.mint,
+        decimals: ctx.accounts.mint.decimals,
+        static_weight_bps,
+        max_weight_bps,
+        target_bps: 0,
+    })
+}
+
+// Anyone can deposit `amount` of the base asset for shares at the current NAV, within the fund's
+// deposit limits; keepers then invest it over the rebalance window. Remaining accounts are each
+// component's token account and price feed.
+pub fn deposit_index<'info>(
+    ctx: Context<'_, '_, '_, 'info, IndexTransfer<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    require!(
+        !accounts.index_fund.emergency,
+        TradingBotError::IndexInEmergency
+    );
+    let nav = accounts.nav(ctx.remaining_accounts)?;
+    let value = accounts.base_value(amount)?;
+    accounts.index_fund.limits.check(
+        value,
+        nav,
+        accounts.index_depositor.deposited,
+        accounts.index_depositor.allowed,
+    )?;
+    let units = accounts.index_fund.units_for(value, nav)?;
+    require_gt!(units, 0, TradingBotError::InvalidCalculation);
+
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.depositor_token_account.to_account_info(),
+                to: accounts.base_token_account.to_account_info(),
+                authority: accounts.depositor.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    anchor_spl::token_2022::mint_to(
+        CpiContext::new_with_signer(
+            accounts.share_token_program.to_account_info(),
+            MintTo {
+                mint: accounts.share_mint.to_account_info(),
+                to: accounts.share_account.to_account_info(),
+                authority: accounts.index_fund.to_account_info(),
+            },
+            &[index_fund_seeds!(accounts.index_fund)],
+        ),
+        units,
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.base_vault_log,
+        VaultOp::Preset,
+        accounts.index_fund.key(),
+        amount,
+        &accounts.base_vault,
+        Some(math::checked_add(accounts.base_token_account.amount, amount)?),
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let index_fund = &ctx.accounts.index_fund;
+    let index_depositor = &mut ctx.accounts.index_depositor;
+    index_depositor.fund = index_fund.key();
+    index_depositor.wallet = ctx.accounts.depositor.key();
+    index_depositor.deposited = math::checked_add(index_depositor.deposited, value)?;
+    // each deposit restarts the lock-up on the wallet's shares
+    index_depositor.locked_until = math::checked_add(now, index_fund.lockup_period)?;
+    index_depositor.exit_fee_bps = index_fund.early_exit_fee_bps;
+    index_depositor.bump = *ctx.bumps.get("index_depositor").unwrap();
+
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.units = math::checked_add(index_fund.units, units)?;
+    index_fund.mark_nav(math::checked_add(nav, value)?)?;
+    index_fund.open_window(now)?;
+    emit!(IndexFlow {
+        index_fund: index_fund.key(),
+        deposit: true,
+        amount,
+        units,
+        nav,
+    });
+
+    Ok(())
+}
+
+pub fn set_index_limits(ctx: Context<ManageIndexFund>, limits: DepositLimits) -> Result<()> {
+    ctx.accounts.index_fund.limits = limits;
+    Ok(())
+}
+
+// Applies to deposits made from now on; earlier deposits keep the terms they were made under. The
+// lock-up is kept on the depositor's record, so it only holds for funds whose shares can't be
+// moved to a fresh wallet.
+pub fn set_index_lockup(
+    ctx: Context<ManageIndexFund>,
+    lockup_period: i64,
+    early_exit_fee_bps: u16,
+) -> Result<()> {
+    require_gte!(lockup_period, 0, TradingBotError::InvalidIndexParams);
+    require!(
+        lockup_period == 0 || ctx.accounts.index_fund.non_transferable,
+        TradingBotError::InvalidIndexParams
+    );
+    require_gte!(
+        MAX_EARLY_EXIT_FEE_BPS,
+        early_exit_fee_bps,
+        TradingBotError::InvalidIndexParams
+    );
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.lockup_period = lockup_period;
+    index_fund.early_exit_fee_bps = early_exit_fee_bps;
+    Ok(())
+}
+
+// Allows or disallows `wallet` to deposit while the allowlist is enforced
+pub fn set_index_depositor(
+    ctx: Context<SetIndexDepositor>,
+    wallet: Pubkey,
+    allowed: bool,
+) -> Result<()> {
+    let index_depositor = &mut ctx.accounts.index_depositor;
+    index_depositor.fund = ctx.accounts.index_fund.key();
+    index_depositor.wallet = wallet;
+    index_depositor.allowed = allowed;
+    index_depositor.bump = *ctx.bumps.get("index_depositor").unwrap();
+    Ok(())
+}
+
+// Withdrawals take two steps: the request burns the holder's shares and sets their units aside so
+// rebalancing sells their part of the components into base cash, and withdraw_index pays them out
+// once it's there. Burned units keep counting toward the fund's units until then, so their value
+// follows the NAV. Units requested while the holder's deposit is locked up pay the early-exit fee.
+pub fn request_index_withdrawal(ctx: Context<RequestIndexWithdrawal>, units: u64) -> Result<()> {
+    require_gt!(units, 0, TradingBotError::InvalidIndexParams);
+    let now = Clock::get()?.unix_timestamp;
+    let index_depositor = &mut ctx.accounts.index_depositor;
+    index_depositor.fund = ctx.accounts.index_fund.key();
+    index_depositor.wallet = ctx.accounts.holder.key();
+    index_depositor.bump = *ctx.bumps.get("index_depositor").unwrap();
+    let fee_units = ctx
+        .accounts
+        .index_fund
+        .exit_fee(units, &ctx.accounts.index_depositor, now)?;
+    let net_units = math::checked_sub(units, fee_units)?;
+    anchor_spl::token_2022::burn(
+        CpiContext::new(
+            ctx.accounts.share_token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.holder_share_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        units,
+    )?;
+
+    let redemption = &mut ctx.accounts.redemption;
+    redemption.fund = ctx.accounts.index_fund.key();
+    redemption.holder = ctx.accounts.holder.key();
+    redemption.units = math::checked_add(redemption.units, net_units)?;
+    redemption.bump = *ctx.bumps.get("redemption").unwrap();
+
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.units = math::checked_sub(index_fund.units, fee_units)?;
+    index_fund.pending_withdrawal_units =
+        math::checked_add(index_fund.pending_withdrawal_units, net_units)?;
+    if fee_units > 0 {
+        emit!(IndexExitFeeCharged {
+            index_fund: index_fund.key(),
+            holder: ctx.accounts.holder.key(),
+            units,
+            fee_units,
+        });
+    }
+    index_fund.open_window(now)
+}
+
+// Pays a holder's requested units out of the base vault at the current NAV and closes the
+// request. Remaining accounts as for deposit_index.
+pub fn withdraw_index<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawIndex<'info>>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let index_fund = &accounts.index_fund;
+    let units = accounts.redemption.units;
+    require_gt!(units, 0, TradingBotError::InvalidIndexParams);
+    let nav = accounts.nav(ctx.remaining_accounts)?;
+    let value = index_fund.value_of(units, nav)?;
+    let balance = accounts.base_token_account.amount;
+    let base_value = accounts.base_value(balance)?;
+    require_gt!(base_value, 0, TradingBotError::InsufficientIndexCash);
+    let amount = math::checked_as_u64(math::checked_div(
+        math::checked_mul(value as u128, balance as u128)?,
+        base_value as u128,
+    )?)?;
+    require_gte!(
+        accounts.base_vault.available(balance),
+        amount,
+        TradingBotError::InsufficientIndexCash
+    );
+
+    let signer_seeds: &[&[&[u8]]] = &[trading_vault_seeds!(accounts.base_vault)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.base_token_account.to_account_info(),
+                to: accounts.holder_token_account.to_account_info(),
+                authority: accounts.base_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.base_vault_log,
+        VaultOp::Preset,
+        index_fund.key(),
+        amount,
+        &accounts.base_vault,
+        Some(math::checked_sub(balance, amount)?),
+    )?;
+
+    if let Some(index_depositor) = ctx.accounts.index_depositor.as_mut() {
+        index_depositor.deposited = index_depositor.deposited.saturating_sub(value);
+    }
+
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.units = math::checked_sub(index_fund.units, units)?;
+    index_fund.pending_withdrawal_units =
+        math::checked_sub(index_fund.pending_withdrawal_units, units)?;
+    index_fund.mark_nav(nav.saturating_sub(value))?;
+    emit!(IndexFlow {
+        index_fund: index_fund.key(),
+        deposit: false,
+        amount,
+        units,
+        nav,
+    });
+
+    Ok(())
+}
+
+// Resets the targets once the interval has passed and opens a rebalance window. Anyone can call
+// it; for market-cap weights remaining accounts are each component's mint and price feed, in
+// order.
+pub fn reconstitute_index(ctx: Context<ReconstituteIndex>) -> Result<()> {
+    let index_fund = &ctx.accounts.index_fund;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        index_fund.reconstitution_due(now),
+        TradingBotError::ReconstitutionNotDue
+    );
+    require_gt!(
+        index_fund.component_count,
+        0,
+        TradingBotError::InvalidIndexComponent
+    );
+
+    let mut market_caps = Vec::new();
+    if index_fund.weight_rule == WeightRule::MarketCap {
+        require_eq!(
+            ctx.remaining_accounts.len(),
+            index_fund.components().len() * 2,
+            TradingBotError::InvalidIndexComponent
+        );
+        for (component, accounts) in index_fund
+            .components()
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(2))
+        {
+            require_keys_eq!(
+                accounts[0].key(),
+                component.mint,
+                TradingBotError::InvalidIndexComponent
+            );
+            let mint = Account::<Mint>::try_from(&accounts[0])?;
+            market_caps.push(usd_value(
+                &ctx.accounts.feed_registry,
+                mint.supply,
+                &component.mint,
+                component.decimals,
+                &accounts[1],
+            )?);
+        }
+    }
+
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.reconstitute(&market_caps, now)?;
+    emit!(IndexReconstituted {
+        index_fund: index_fund.key(),
+        targets_bps: index_fund
+            .components()
+            .iter()
+            .map(|c| c.target_bps)
+            .collect(),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+// Trades one component against the base asset during a rebalance window. The component has to
+// have drifted past `drift_bps` of NAV from its target and end up closer to it, and the swap may
+// cost the fund at most `max_slippage_bps` of the value moved. Remaining accounts are each
+// component's token account and price feed, in order, followed by the swap's accounts.
+pub fn rebalance_index<'info>(
+    ctx: Context<'_, '_, '_, 'info, RebalanceIndex<'info>>,
+    component: u8,
+    swap_data: Vec<u8>,
+) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    let accounts = &ctx.accounts;
+    let index_fund = &accounts.index_fund;
+    require!(!index_fund.emergency, TradingBotError::IndexInEmergency);
+    require_gte!(
+        index_fund.rebalance_until,
+        Clock::get()?.unix_timestamp,
+        TradingBotError::RebalanceWindowClosed
+    );
+    require!(
+        RebalanceIndex::is_supported_swap_program(accounts.swap_program.key),
+        TradingBotError::InvalidDexType
+    );
+    let index = component as usize;
+    require_gt!(
+        index_fund.components().len(),
+        index,
+        TradingBotError::InvalidIndexComponent
+    );
+    require_keys_eq!(
+        accounts.component_vault.key(),
+        index_fund.components[index].trading_vault,
+        TradingBotError::InvalidIndexComponent
+    );
+
+    let valuation_len = index_fund.components().len() * 2;
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        valuation_len,
+        TradingBotError::InvalidIndexComponent
+    );
+    let (valuation_accounts, swap_accounts) = ctx.remaining_accounts.split_at(valuation_len);
+
+    let base_before = accounts.base_token_account.amount;
+    let component_before =
+        Account::<TokenAccount>::try_from(&valuation_accounts[index * 2])?.amount;
+    let (values_before, nav_before) = accounts.value(valuation_accounts)?;
+    let deviation_before = index_fund.deviation(index, values_before[index], nav_before)?;
+    accounts.invoke_swap(swap_accounts, swap_data)?;
+
+    ctx.accounts.base_token_account.reload()?;
+    let accounts = &ctx.accounts;
+    let index_fund = &accounts.index_fund;
+    accounts
+        .base_vault
+        .check_invariant(accounts.base_token_account.amount)?;
+    let component_account = Account::<TokenAccount>::try_from(&valuation_accounts[index * 2])?;
+    accounts
+        .component_vault
+        .check_invariant(component_account.amount)?;
+    accounts.log_swap(base_before, component_before, component_account.amount)?;
+
+    let (values_after, nav_after) = accounts.value(valuation_accounts)?;
+    let deviation_after = index_fund.deviation(index, values_after[index], nav_after)?;
+    index_fund.check_rebalanced(deviation_before, deviation_after, nav_before)?;
+    let moved = values_before[index].abs_diff(values_after[index]);
+    let max_loss = math::checked_as_u64(math::checked_div(
+        math::checked_mul(moved as u128, index_fund.max_slippage_bps as u128)?,
+        10000,
+    )?)?;
+    require_gte!(
+        nav_after,
+        nav_before.saturating_sub(max_loss),
+        TradingBotError::SlippageExceeded
+    );
+
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.mark_nav(nav_after)?;
+    emit!(IndexRebalanced {
+        index_fund: index_fund.key(),
+        mint: index_fund.components[index].mint,
+        value_before: values_before[index],
+        value_after: values_after[index],
+        nav: nav_after,
+    });
+
+    Ok(())
+}
+
+// Emergency mode is for when deposits, withdrawals or rebalancing are broken, e.g. after an upgrade
+// or when a venue or oracle they depend on fails. It stops deposits and rebalancing until the
+// owner turns it off.
+pub fn set_index_emergency(
+    ctx: Context<ManageIndexFund>,
+    emergency: bool,
+    slippage_bps: u16,
+) -> Result<()> {
+    require_gte!(
+        MAX_EMERGENCY_SLIPPAGE_BPS,
+        slippage_bps,
+        TradingBotError::InvalidIndexParams
+    );
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.emergency = emergency;
+    index_fund.emergency_slippage_bps = slippage_bps;
+    emit!(IndexEmergencySet {
+        index_fund: index_fund.key(),
+        emergency,
+        slippage_bps,
+    });
+
+    Ok(())
+}
+
+// Sells a component into the base asset at market in emergency mode, outside any rebalance window
+// and regardless of targets. Only the owner can call it, and the sale may cost the fund at most
+// `emergency_slippage_bps` of the value sold. Accounts as for rebalance_index.
+pub fn emergency_liquidate_index<'info>(
+    ctx: Context<'_, '_, '_, 'info, RebalanceIndex<'info>>,
+    component: u8,
+    swap_data: Vec<u8>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let index_fund = &accounts.index_fund;
+    require!(index_fund.emergency, TradingBotError::IndexNotInEmergency);
+    require_keys_eq!(
+        accounts.keeper.key(),
+        index_fund.owner,
+        TradingBotError::UnauthorizedExecutor
+    );
+    require!(
+        RebalanceIndex::is_supported_swap_program(accounts.swap_program.key),
+        TradingBotError::InvalidDexType
+    );
+    let index = component as usize;
+    require_gt!(
+        index_fund.components().len(),
+        index,
+        TradingBotError::InvalidIndexComponent
+    );
+    require_keys_eq!(
+        accounts.component_vault.key(),
+        index_fund.components[index].trading_vault,
+        TradingBotError::InvalidIndexComponent
+    );
+
+    let valuation_len = index_fund.components().len() * 2;
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        valuation_len,
+        TradingBotError::InvalidIndexComponent
+    );
+    let (valuation_accounts, swap_accounts) = ctx.remaining_accounts.split_at(valuation_len);
+
+    let base_before = accounts.base_token_account.amount;
+    let component_before =
+        Account::<TokenAccount>::try_from(&valuation_accounts[index * 2])?.amount;
+    let (values_before, nav_before) = accounts.value(valuation_accounts)?;
+    accounts.invoke_swap(swap_accounts, swap_data)?;
+
+    ctx.accounts.base_token_account.reload()?;
+    let accounts = &ctx.accounts;
+    let index_fund = &accounts.index_fund;
+    accounts
+        .base_vault
+        .check_invariant(accounts.base_token_account.amount)?;
+    let component_account = Account::<TokenAccount>::try_from(&valuation_accounts[index * 2])?;
+    accounts
+        .component_vault
+        .check_invariant(component_account.amount)?;
+    accounts.log_swap(base_before, component_before, component_account.amount)?;
+
+    let (values_after, nav_after) = accounts.value(valuation_accounts)?;
+    require!(
+        accounts.base_token_account.amount > base_before
+            && values_before[index] > values_after[index],
+        TradingBotError::NotALiquidation
+    );
+    let sold = values_before[index] - values_after[index];
+    let max_loss = math::checked_as_u64(math::checked_div(
+        math::checked_mul(sold as u128, index_fund.emergency_slippage_bps as u128)?,
+        10000,
+    )?)?;
+    require_gte!(
+        nav_after,
+        nav_before.saturating_sub(max_loss),
+        TradingBotError::SlippageExceeded
+    );
+
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.mark_nav(nav_after)?;
+    emit!(IndexRebalanced {
+        index_fund: index_fund.key(),
+        mint: index_fund.components[index].mint,
+        value_before: values_before[index],
+        value_after: values_after[index],
+        nav: nav_after,
+    });
+
+    Ok(())
+}
+
+// In emergency mode a holder can burn `units` of their shares, plus any units they already asked
+// to withdraw, for the same share of the base vault's cash. The payout is pro rata to units and
+// doesn't read the oracles, and skips the lock-up fee; holders who wait for the owner to liquidate
+// the components get their share of those too.
+pub fn emergency_withdraw_index(ctx: Context<EmergencyWithdrawIndex>, units: u64) -> Result<()> {
+    let accounts = &ctx.accounts;
+    require!(
+        accounts.index_fund.emergency,
+        TradingBotError::IndexNotInEmergency
+    );
+    if units > 0 {
+        anchor_spl::token_2022::burn(
+            CpiContext::new(
+                accounts.share_token_program.to_account_info(),
+                Burn {
+                    mint: accounts.share_mint.to_account_info(),
+                    from: accounts.holder_share_account.to_account_info(),
+                    authority: accounts.holder.to_account_info(),
+                },
+            ),
+            units,
+        )?;
+    }
+    let requested = accounts.redemption.as_ref().map_or(0, |r| r.units);
+    let total = math::checked_add(units, requested)?;
+    require_gt!(total, 0, TradingBotError::NoFundUnits);
+
+    let balance = accounts.base_token_account.amount;
+    let amount = accounts
+        .index_fund
+        .pro_rata(total, accounts.base_vault.available(balance))?;
+    let signer_seeds: &[&[&[u8]]] = &[trading_vault_seeds!(accounts.base_vault)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.base_token_account.to_account_info(),
+                to: accounts.holder_token_account.to_account_info(),
+                authority: accounts.base_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.base_vault_log,
+        VaultOp::Preset,
+        accounts.index_fund.key(),
+        amount,
+        &accounts.base_vault,
+        Some(math::checked_sub(balance, amount)?),
+    )?;
+
+    let index_fund = &mut ctx.accounts.index_fund;
+    index_fund.units = math::checked_sub(index_fund.units, total)?;
+    index_fund.pending_withdrawal_units =
+        math::checked_sub(index_fund.pending_withdrawal_units, requested)?;
+    emit!(IndexEmergencyWithdrawal {
+        index_fund: index_fund.key(),
+        holder: ctx.accounts.holder.key(),
+        units: total,
+        amount,
+    });
+
+    Ok(())
+}
@@ -1,26 +1,93 @@
+use crate::constants::{MAX_SLIPPAGE_BPS, STRATEGY_SEED};
+use crate::types::StrategyType;
+use crate::{
+    errors::TradingBotError,
+    state::{
+        AccountingCurrency, ApprovalPolicy, BlockRetries, KeeperAllowlist, MinOutMode,
+        PerformanceMetrics, RiskParameters, Strategy, StrategyConfig, VolatilityHalt, VolumeWindow,
+        MAX_STRATEGY_ID_LEN,
+    },
+};
 use anchor_lang::prelude::*;
-use crate::state::*;
 
 #[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(init, payer = owner, space = TradingStrategy::LEN)]
-    pub strategy: Account<'info, TradingStrategy>,
+#[instruction(strategy_id: String)]
+pub struct InitializeBot<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    owner: Signer<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = Strategy::LEN,
+      seeds = [STRATEGY_SEED, owner.key().as_ref(), strategy_id.as_bytes()],
+      bump
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    system_program: Program<'info, System>,
 }
 
-pub fn handler(
-    ctx: Context<Initialize>,
+// Strategies start inactive until the owner has reviewed the configuration
+pub fn initialize_bot(
+    ctx: Context<InitializeBot>,
     strategy_id: String,
     config: StrategyConfig,
+    risk_parameters: RiskParameters,
 ) -> Result<()> {
+    require!(
+        !strategy_id.is_empty() && strategy_id.len() <= MAX_STRATEGY_ID_LEN,
+        TradingBotError::InvalidStrategyConfig
+    );
+    require!(
+        config.slippage_bps <= MAX_SLIPPAGE_BPS,
+        TradingBotError::InvalidStrategyConfig
+    );
+    require_gte!(
+        config.execution_interval,
+        0,
+        TradingBotError::InvalidStrategyConfig
+    );
+    require!(
+        config.time_basis.is_valid(),
+        TradingBotError::InvalidStrategyConfig
+    );
+    if let MinOutMode::Oracle { price_feed } = config.min_out_mode {
+        require_keys_neq!(
+            price_feed,
+            Pubkey::default(),
+            TradingBotError::InvalidStrategyConfig
+        );
+    }
+    require!(
+        risk_parameters.is_valid(),
+        TradingBotError::InvalidStrategyConfig
+    );
+
+    let now = Clock::get()?.unix_timestamp;
     let strategy = &mut ctx.accounts.strategy;
     strategy.owner = ctx.accounts.owner.key();
+    strategy.creator = ctx.accounts.owner.key();
     strategy.strategy_id = strategy_id;
+    strategy.strategy_type = StrategyType::Custom;
     strategy.config = config;
+    strategy.risk_parameters = risk_parameters;
+    strategy.performance_metrics = PerformanceMetrics::default();
+    strategy.accounting = AccountingCurrency::default();
+    strategy.volatility_halt = VolatilityHalt::default();
+    strategy.approval_policy = ApprovalPolicy::default();
+    strategy.volume_window = VolumeWindow::default();
+    strategy.block_retries = BlockRetries::default();
+    strategy.keeper_allowlist = KeeperAllowlist::default();
+    strategy.auctioned_execution = false;
     strategy.is_active = false;
+    strategy.executing = false;
+    strategy.net_contributions = 0;
     strategy.total_trades = 0;
-    strategy.created_at = Clock::get()?.unix_timestamp;
+    strategy.last_executed_at = 0;
+    strategy.created_at = now;
+    strategy.updated_at = now;
+    strategy.bump = *ctx.bumps.get("strategy").unwrap();
+
     Ok(())
-} 
\ No newline at end of file
+}
@@ -0,0 +1,400 @@
+use crate::constants::{
+    FEED_REGISTRY_SEED, INTERNAL_ORDER_BOOK_SEED, LIMIT_ORDER_SEED, MAX_FILL_ORACLE_DEVIATION_BPS,
+    PRICE_PRECISION, TRADING_VAULT_SEED, VAULT_LOG_SEED,
+};
+use crate::events::InternalOrdersCrossed;
+use crate::instructions::{oracle_quote, FillLimitOrder};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        BookEntry, FeedRegistry, InternalOrderBook, LimitOrder, OrderStatus, TradingVault,
+        VaultLog, VaultOp,
+    },
+    trading_vault_seeds,
+};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct ListInternalOrder<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [LIMIT_ORDER_SEED, order.owner.as_ref(), order.idx.to_le_bytes().as_ref()],
+      bump = order.bump,
+    )]
+    order: Box<Account<'info, LimitOrder>>,
+
+    #[account(
+      init_if_needed,
+      payer = owner,
+      space = InternalOrderBook::LEN,
+      seeds = [INTERNAL_ORDER_BOOK_SEED, order.base_mint.as_ref(), order.quote_mint.as_ref()],
+      bump
+    )]
+    book: Box<Account<'info, InternalOrderBook>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlistInternalOrder<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [INTERNAL_ORDER_BOOK_SEED, book.base_mint.as_ref(), book.quote_mint.as_ref()],
+      bump = book.bump,
+    )]
+    book: Box<Account<'info, InternalOrderBook>>,
+}
+
+#[derive(Accounts)]
+pub struct CrossInternalOrders<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [INTERNAL_ORDER_BOOK_SEED, book.base_mint.as_ref(), book.quote_mint.as_ref()],
+      bump = book.bump,
+    )]
+    book: Box<Account<'info, InternalOrderBook>>,
+
+    // quote vault for buys, base vault for sells
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, source_vault.creator.as_ref(), source_vault.mint.as_ref()],
+      bump = source_vault.bump,
+    )]
+    source_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=source_vault.token_account
+    )]
+    source_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, target_vault.creator.as_ref(), target_vault.mint.as_ref()],
+      bump = target_vault.bump,
+    )]
+    target_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=target_vault.token_account
+    )]
+    target_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, source_vault.key().as_ref()],
+      bump,
+    )]
+    source_vault_log: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, target_vault.key().as_ref()],
+      bump,
+    )]
+    target_vault_log: UncheckedAccount<'info>,
+
+    // crossings have to be priced within MAX_FILL_ORACLE_DEVIATION_BPS of the pair's oracles
+    #[account(
+      address=book.base_mint
+    )]
+    base_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=book.quote_mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Resolved through the feed registry on read
+    base_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Resolved through the feed registry on read
+    quote_price_feed: UncheckedAccount<'info>,
+
+    token_program: Program<'info, Token>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+impl<'info> CrossInternalOrders<'info> {
+    fn transfer_from_vault(
+        &self,
+        vault: &Account<'info, TradingVault>,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[trading_vault_seeds!(vault)];
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from,
+                    to,
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
+
+// Opts a resting limit order in to being crossed by other vaults. Its reservation is unchanged.
+pub fn list_internal_order(ctx: Context<ListInternalOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    require!(!order.status.is_terminal(), TradingBotError::OrderNotOpen);
+
+    let book = &mut ctx.accounts.book;
+    if book.base_mint == Pubkey::default() {
+        book.base_mint = order.base_mint;
+        book.quote_mint = order.quote_mint;
+        book.bump = *ctx.bumps.get("book").unwrap();
+    }
+    book.list(BookEntry {
+        order: order.key(),
+        owner: order.owner,
+        is_buy: order.is_buy,
+        limit_price: order.limit_price,
+    })
+}
+
+// Takes the order by key so entries of cancelled or closed orders can be cleared too
+pub fn unlist_internal_order(ctx: Context<UnlistInternalOrder>, order: Pubkey) -> Result<()> {
+    let entry = ctx.accounts.book.unlist(&order)?;
+    require_keys_eq!(
+        entry.owner,
+        ctx.accounts.owner.key(),
+        TradingBotError::OrderNotListed
+    );
+    Ok(())
+}
+
+// Fills up to `amount` of the source vault's mint against listed orders on the other side, each
+// at its own limit price as long as that is no worse than `limit_price`, before the rest is
+// routed to a DEX. Remaining accounts come in groups of [order, maker vault, maker vault token
+// account, maker payment account, maker, maker vault log], best price first; the maker payment
+// account is the maker's account for what the order receives. Unfilled `amount` is reported in
+// the event.
+pub fn cross_internal_orders<'info>(
+    ctx: Context<'_, '_, '_, 'info, CrossInternalOrders<'info>>,
+    amount: u64,
+    limit_price: u64,
+) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    require_gt!(amount, 0, TradingBotError::InvalidOrderParams);
+    require_eq!(
+        ctx.remaining_accounts.len() % 6,
+        0,
+        TradingBotError::InvalidOrderParams
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let accounts = &ctx.accounts;
+    let (base_mint, quote_mint) = (accounts.book.base_mint, accounts.book.quote_mint);
+    let taker_buys = accounts.source_vault.mint == quote_mint;
+    let receive_mint = if taker_buys { base_mint } else { quote_mint };
+    require!(
+        (taker_buys || accounts.source_vault.mint == base_mint)
+            && accounts.target_vault.mint == receive_mint,
+        TradingBotError::InvalidOrderParams
+    );
+    require_gte!(
+        accounts
+            .source_vault
+            .available(accounts.source_vault_token_account.amount),
+        amount,
+        TradingBotError::InsufficientAvailableBalance
+    );
+
+    let mut remaining = amount;
+    let mut amount_out = 0u64;
+    for maker in ctx.remaining_accounts.chunks(6) {
+        if remaining == 0 {
+            break;
+        }
+        let mut order = Account::<LimitOrder>::try_from(&maker[0])?;
+        let order_key = order.key();
+        require!(
+            ctx.accounts.book.entry_index(&order_key).is_some() && order.is_buy != taker_buys,
+            TradingBotError::OrderNotListed
+        );
+        let price = order.limit_price;
+        let compatible = if taker_buys {
+            price <= limit_price
+        } else {
+            price >= limit_price
+        };
+        if order.status.is_terminal() || order.is_expired(now) || !compatible {
+            continue;
+        }
+
+        let fill_size = if taker_buys {
+            order
+                .remaining_size()
+                .min(math::checked_as_u64(math::checked_div(
+                    math::checked_mul(remaining as u128, PRICE_PRECISION as u128)?,
+                    price as u128,
+                )?)?)
+        } else {
+            order.remaining_size().min(remaining)
+        };
+        let fill_quote = order.fill_quote(fill_size, price)?;
+        if fill_size == 0 || fill_quote == 0 {
+            continue;
+        }
+        // both sides can be the same owner's, so neither vault may pay away value off-market
+        let accounts = &ctx.accounts;
+        let oracle_quote = oracle_quote(
+            &accounts.feed_registry,
+            &accounts.base_mint,
+            &accounts.base_price_feed,
+            &accounts.quote_mint,
+            &accounts.quote_price_feed,
+            fill_size,
+        )?;
+        let band = MAX_FILL_ORACLE_DEVIATION_BPS;
+        require!(
+            LimitOrder::within_oracle_band(fill_quote, oracle_quote, band)?,
+            TradingBotError::FillOffOracle
+        );
+        let (spend, receive) = if taker_buys {
+            (fill_quote, fill_size)
+        } else {
+            (fill_size, fill_quote)
+        };
+
+        require_keys_eq!(
+            maker[1].key(),
+            order.trading_vault,
+            TradingBotError::InvalidOrderParams
+        );
+        require_keys_eq!(
+            maker[4].key(),
+            order.owner,
+            TradingBotError::InvalidOrderParams
+        );
+        let mut maker_vault = Account::<TradingVault>::try_from(&maker[1])?;
+        let mut maker_vault_token_account = Account::<TokenAccount>::try_from(&maker[2])?;
+        require_keys_eq!(
+            maker_vault_token_account.key(),
+            maker_vault.token_account,
+            TradingBotError::InvalidOrderParams
+        );
+        let maker_payment_account = Account::<TokenAccount>::try_from(&maker[3])?;
+        require!(
+            maker_payment_account.owner == order.owner
+                && maker_payment_account.mint == order.payment_mint(),
+            TradingBotError::InvalidOrderParams
+        );
+
+        order.apply_fill(fill_size, fill_quote, now)?;
+        let filled = order.status == OrderStatus::Filled;
+        let release = if filled {
+            order.reserved
+        } else {
+            LimitOrder::reserve_amount(order.is_buy, fill_size, order.limit_price)?
+        };
+        order.reserved = math::checked_sub(order.reserved, release)?;
+        maker_vault.release(release, filled)?;
+
+        let accounts = &ctx.accounts;
+        accounts.transfer_from_vault(
+            &accounts.source_vault,
+            accounts.source_vault_token_account.to_account_info(),
+            maker[3].clone(),
+            spend,
+        )?;
+        accounts.transfer_from_vault(
+            &maker_vault,
+            maker[2].clone(),
+            accounts.target_vault_token_account.to_account_info(),
+            receive,
+        )?;
+        maker_vault_token_account.reload()?;
+        maker_vault.check_invariant(maker_vault_token_account.amount)?;
+        maker_vault.exit(ctx.program_id)?;
+        VaultLog::require_address(&maker[5], &order.trading_vault)?;
+        VaultLog::append_if_initialized(
+            &maker[5],
+            VaultOp::FillOrder,
+            order_key,
+            receive,
+            &maker_vault,
+            Some(maker_vault_token_account.amount),
+        )?;
+
+        remaining = math::checked_sub(remaining, spend)?;
+        amount_out = math::checked_add(amount_out, receive)?;
+        msg!(
+            "Crossed {} at {} with order #{}",
+            fill_size,
+            price,
+            order.idx
+        );
+        FillLimitOrder::emit_update(&order, order_key, fill_size, price);
+        if filled {
+            ctx.accounts.book.unlist(&order_key)?;
+            order.close(maker[4].clone())?;
+        } else {
+            order.exit(ctx.program_id)?;
+        }
+    }
+
+    ctx.accounts.source_vault_token_account.reload()?;
+    ctx.accounts.target_vault_token_account.reload()?;
+    ctx.accounts
+        .source_vault
+        .check_invariant(ctx.accounts.source_vault_token_account.amount)?;
+    let accounts = &ctx.accounts;
+    let amount_in = math::checked_sub(amount, remaining)?;
+    VaultLog::append_if_initialized(
+        &accounts.source_vault_log,
+        VaultOp::Cross,
+        accounts.book.key(),
+        amount_in,
+        &accounts.source_vault,
+        Some(accounts.source_vault_token_account.amount),
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.target_vault_log,
+        VaultOp::Cross,
+        accounts.book.key(),
+        amount_out,
+        &accounts.target_vault,
+        Some(accounts.target_vault_token_account.amount),
+    )?;
+
+    emit!(InternalOrdersCrossed {
+        book: ctx.accounts.book.key(),
+        taker: ctx.accounts.owner.key(),
+        taker_buys,
+        amount_in,
+        amount_out,
+        remainder: remaining,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use crate::constants::{KEEPER_SEED, KEEPER_UNBONDING_PERIOD, MIN_KEEPER_BOND, STRATEGY_SEED};
+use crate::events::KeeperSlashed;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{KeeperRecord, KeeperViolation, Strategy, MAX_OPEN_VIOLATIONS},
+};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+#[derive(Accounts)]
+pub struct RegisterKeeper<'info> {
+    #[account(mut)]
+    keeper: Signer<'info>,
+
+    #[account(
+      init,
+      payer = keeper,
+      space = KeeperRecord::LEN,
+      seeds = [KEEPER_SEED, keeper.key().as_ref()],
+      bump
+    )]
+    keeper_record: Box<Account<'info, KeeperRecord>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageKeeperBond<'info> {
+    #[account(mut)]
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=keeper,
+      seeds = [KEEPER_SEED, keeper.key().as_ref()],
+      bump = keeper_record.bump,
+    )]
+    keeper_record: Box<Account<'info, KeeperRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawKeeperBond<'info> {
+    #[account(mut)]
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      close=keeper,
+      has_one=keeper,
+      seeds = [KEEPER_SEED, keeper.key().as_ref()],
+      bump = keeper_record.bump,
+    )]
+    keeper_record: Box<Account<'info, KeeperRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct JudgeKeeper<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      seeds = [KEEPER_SEED, keeper_record.keeper.as_ref()],
+      bump = keeper_record.bump,
+    )]
+    keeper_record: Box<Account<'info, KeeperRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct OpenExecutionAttempt<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=keeper,
+      seeds = [KEEPER_SEED, keeper.key().as_ref()],
+      bump = keeper_record.bump,
+    )]
+    keeper_record: Box<Account<'info, KeeperRecord>>,
+
+    #[account(
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+}
+
+#[derive(Accounts)]
+pub struct ManageKeeperAllowlist<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+}
+
+// The bond is moved into the record itself, on top of its rent
+pub fn register_keeper(ctx: Context<RegisterKeeper>, bond: u64) -> Result<()> {
+    require_gte!(bond, MIN_KEEPER_BOND, TradingBotError::InvalidKeeperBond);
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.keeper.to_account_info(),
+                to: ctx.accounts.keeper_record.to_account_info(),
+            },
+        ),
+        bond,
+    )?;
+
+    let keeper_record = &mut ctx.accounts.keeper_record;
+    keeper_record.keeper = ctx.accounts.keeper.key();
+    keeper_record.bond = bond;
+    keeper_record.executions = 0;
+    keeper_record.attempts = 0;
+    keeper_record.reverted = 0;
+    keeper_record.open_attempt = Pubkey::default();
+    keeper_record.open_attempt_slot = 0;
+    keeper_record.total_delay = 0;
+    keeper_record.delay_samples = 0;
+    keeper_record.violations = [KeeperViolation::default(); MAX_OPEN_VIOLATIONS];
+    keeper_record.violation_count = 0;
+    keeper_record.slash_count = 0;
+    keeper_record.slashed = 0;
+    keeper_record.exit_requested_at = 0;
+    keeper_record.registered_at = Clock::get()?.unix_timestamp;
+    keeper_record.bump = *ctx.bumps.get("keeper_record").unwrap();
+
+    Ok(())
+}
+
+// Stops the keeper executing and starts the unbonding period, during which open violations can
+// still be slashed
+pub fn request_keeper_exit(ctx: Context<ManageKeeperBond>) -> Result<()> {
+    let keeper_record = &mut ctx.accounts.keeper_record;
+    require_eq!(
+        keeper_record.exit_requested_at,
+        0,
+        TradingBotError::InvalidKeeperBond
+    );
+    keeper_record.exit_requested_at = Clock::get()?.unix_timestamp;
+    Ok(())
+}
+
+// Closing the record returns the bond along with its rent
+pub fn withdraw_keeper_bond(ctx: Context<WithdrawKeeperBond>) -> Result<()> {
+    let keeper_record = &ctx.accounts.keeper_record;
+    require!(
+        keeper_record.exit_requested_at > 0
+            && Clock::get()?.unix_timestamp
+                >= math::checked_add(keeper_record.exit_requested_at, KEEPER_UNBONDING_PERIOD)?,
+        TradingBotError::InvalidKeeperBond
+    );
+    require_eq!(
+        keeper_record.violation_count,
+        0,
+        TradingBotError::KeeperViolationsOutstanding
+    );
+    Ok(())
+}
+
+// Sent in its own transaction ahead of execute_strategy, which checks for it. An attempt still
+// open from before was never executed and counts as a revert.
+pub fn open_execution_attempt(ctx: Context<OpenExecutionAttempt>) -> Result<()> {
+    let keeper_record = &mut ctx.accounts.keeper_record;
+    require_eq!(
+        keeper_record.exit_requested_at,
+        0,
+        TradingBotError::KeeperNotAllowed
+    );
+    keeper_record.open_attempt(ctx.accounts.strategy.key(), Clock::get()?.slot)
+}
+
+// Pays KEEPER_SLASH_BPS of the keeper's bond to the owner of the strategy it misbehaved on, once
+// per recorded violation
+pub fn slash_keeper(ctx: Context<JudgeKeeper>) -> Result<()> {
+    let strategy = ctx.accounts.strategy.key();
+    let keeper_record = &mut ctx.accounts.keeper_record;
+    let violation = keeper_record.take_violation(&strategy)?;
+    let amount = keeper_record.slash_amount()?;
+    keeper_record.bond = math::checked_sub(keeper_record.bond, amount)?;
+    keeper_record.slashed = math::checked_add(keeper_record.slashed, amount)?;
+    keeper_record.slash_count = math::checked_add(keeper_record.slash_count, 1)?;
+
+    let record_info = keeper_record.to_account_info();
+    let owner_info = ctx.accounts.owner.to_account_info();
+    **record_info.try_borrow_mut_lamports()? = math::checked_sub(record_info.lamports(), amount)?;
+    **owner_info.try_borrow_mut_lamports()? = math::checked_add(owner_info.lamports(), amount)?;
+
+    msg!(
+        "Slashed {} for min out {} under {} at slot {}",
+        amount,
+        violation.minimum_out,
+        violation.oracle_min_out,
+        violation.slot
+    );
+    emit!(KeeperSlashed {
+        keeper: keeper_record.keeper,
+        strategy,
+        amount,
+        remaining_bond: keeper_record.bond,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn add_allowed_keeper(ctx: Context<ManageKeeperAllowlist>, keeper: Pubkey) -> Result<()> {
+    ctx.accounts.strategy.keeper_allowlist.add(keeper)
+}
+
+pub fn remove_allowed_keeper(ctx: Context<ManageKeeperAllowlist>, keeper: Pubkey) -> Result<()> {
+    ctx.accounts.strategy.keeper_allowlist.remove(&keeper)
+}
+
+// Enforcing needs the feed keeper swaps are judged against
+pub fn set_keeper_allowlist_enforced(
+    ctx: Context<ManageKeeperAllowlist>,
+    enforced: bool,
+    price_feed: Pubkey,
+) -> Result<()> {
+    require!(
+        !enforced || price_feed != Pubkey::default(),
+        TradingBotError::InvalidStrategyConfig
+    );
+    let allowlist = &mut ctx.accounts.strategy.keeper_allowlist;
+    allowlist.enforced = enforced;
+    allowlist.price_feed = price_feed;
+    Ok(())
+}
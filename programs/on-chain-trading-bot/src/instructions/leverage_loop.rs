@@ -0,0 +1,287 @@
+use crate::constants::{LEVERAGE_LOOP_SEED, MAX_SLIPPAGE_BPS, TRADING_VAULT_SEED, VAULT_LOG_SEED};
+use crate::dex::{jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID};
+use crate::events::LeverageLoopRun;
+use crate::oracles::{lending_obligation::ObligationHealth, lending_reserve::SOLEND_PROGRAM_ID};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{LeverageLoop, Strategy, TradingVault, VaultLog, VaultOp},
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::TokenAccount;
+
+// One forwarded instruction of a pass: a lending instruction (deposit, borrow, repay, withdraw,
+// refresh) or, with `swap`, a swap between the borrowed asset and the collateral
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LendingStep {
+    pub accounts_len: u8,
+    pub data: Vec<u8>,
+    pub swap: bool,
+}
+
+#[derive(Accounts)]
+pub struct InitLeverageLoop<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    // its risk parameters cap the loop's leverage
+    #[account(
+      has_one=owner,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    /// CHECK: A Solend obligation; its owner is checked against the vault on every read
+    #[account(
+      owner=SOLEND_PROGRAM_ID
+    )]
+    obligation: UncheckedAccount<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = LeverageLoop::LEN,
+      seeds = [LEVERAGE_LOOP_SEED, obligation.key().as_ref()],
+      bump
+    )]
+    leverage_loop: Box<Account<'info, LeverageLoop>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RunLeverageLoop<'info> {
+    // any keeper; only the owner may put the vault's own balance into a pass
+    authority: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [LEVERAGE_LOOP_SEED, leverage_loop.obligation.as_ref()],
+      bump = leverage_loop.bump,
+    )]
+    leverage_loop: Box<Account<'info, LeverageLoop>>,
+
+    #[account(
+      address=leverage_loop.trading_vault
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=leverage_loop.strategy
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      address=trading_vault.token_account
+    )]
+    vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the loop, read after a refresh in this slot
+    #[account(
+      mut,
+      address=leverage_loop.obligation
+    )]
+    obligation: UncheckedAccount<'info>,
+
+    /// CHECK: Solend, which every lending step goes to
+    #[account(
+      address=SOLEND_PROGRAM_ID
+    )]
+    lending_program: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the supported swap programs when a step swaps
+    swap_program: UncheckedAccount<'info>,
+}
+
+impl<'info> RunLeverageLoop<'info> {
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    pub fn health(&self) -> Result<ObligationHealth> {
+        ObligationHealth::read(&self.obligation, &self.trading_vault.key())
+    }
+
+    // Forwards the steps in order, each to its program with the vault's PDA signing. The last
+    // step is expected to refresh the obligation so the pass can be checked.
+    pub fn run_steps(
+        &self,
+        accounts: &[AccountInfo<'info>],
+        steps: Vec<LendingStep>,
+    ) -> Result<()> {
+        let vault_key = self.trading_vault.key();
+        let mut remaining = accounts;
+        for step in steps {
+            let program_id = if step.swap {
+                require!(
+                    Self::is_supported_swap_program(self.swap_program.key),
+                    TradingBotError::InvalidDexType
+                );
+                self.swap_program.key()
+            } else {
+                self.lending_program.key()
+            };
+            let accounts_len = step.accounts_len as usize;
+            require_gte!(
+                remaining.len(),
+                accounts_len,
+                TradingBotError::InvalidCalculation
+            );
+            let (step_accounts, rest) = remaining.split_at(accounts_len);
+            remaining = rest;
+
+            let ix = Instruction {
+                program_id,
+                accounts: step_accounts
+                    .iter()
+                    .map(|a| {
+                        let is_signer = a.is_signer || a.key() == vault_key;
+                        if a.is_writable {
+                            AccountMeta::new(a.key(), is_signer)
+                        } else {
+                            AccountMeta::new_readonly(a.key(), is_signer)
+                        }
+                    })
+                    .collect(),
+                data: step.data,
+            };
+            invoke_signed(
+                &ix,
+                step_accounts,
+                &[trading_vault_seeds!(self.trading_vault)],
+            )?;
+        }
+        require!(remaining.is_empty(), TradingBotError::InvalidCalculation);
+        Ok(())
+    }
+
+    // Only the owner may spend the vault's balance, e.g. to seed the first deposit; a keeper's
+    // pass has to be funded by the obligation alone. The pass is logged with what it spent.
+    pub fn check_balance(&mut self, before: u64) -> Result<()> {
+        self.vault_token_account.reload()?;
+        let after = self.vault_token_account.amount;
+        self.trading_vault.check_invariant(after)?;
+        if self.authority.key() != self.leverage_loop.owner {
+            require_gte!(after, before, TradingBotError::InvalidCalculation);
+        }
+        VaultLog::append_if_initialized(
+            &self.vault_log,
+            VaultOp::Preset,
+            self.leverage_loop.key(),
+            before.saturating_sub(after),
+            &self.trading_vault,
+            Some(after),
+        )
+    }
+}
+
+pub fn init_leverage_loop(
+    ctx: Context<InitLeverageLoop>,
+    target_ltv_bps: u16,
+    min_health_bps: u16,
+    max_slippage_bps: u16,
+) -> Result<()> {
+    require!(
+        target_ltv_bps > 0 && target_ltv_bps < 10000,
+        TradingBotError::InvalidLeverageParams
+    );
+    require_gte!(
+        min_health_bps,
+        10000,
+        TradingBotError::InvalidLeverageParams
+    );
+    require_gte!(
+        MAX_SLIPPAGE_BPS,
+        max_slippage_bps,
+        TradingBotError::InvalidLeverageParams
+    );
+
+    let leverage_loop = &mut ctx.accounts.leverage_loop;
+    leverage_loop.owner = ctx.accounts.owner.key();
+    leverage_loop.strategy = ctx.accounts.strategy.key();
+    leverage_loop.trading_vault = ctx.accounts.trading_vault.key();
+    leverage_loop.obligation = ctx.accounts.obligation.key();
+    leverage_loop.target_ltv_bps = target_ltv_bps;
+    leverage_loop.min_health_bps = min_health_bps;
+    leverage_loop.max_slippage_bps = max_slippage_bps;
+    leverage_loop.loops = 0;
+    leverage_loop.unwinds = 0;
+    leverage_loop.bump = *ctx.bumps.get("leverage_loop").unwrap();
+
+    Ok(())
+}
+
+// One levering pass: deposit, borrow, swap the borrow into collateral and redeposit, as many
+// rounds as the steps hold. Remaining accounts are each step's accounts in order, and the
+// obligation has to be refreshed both before the instruction and by its last step.
+pub fn loop_leverage<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunLeverageLoop<'info>>,
+    steps: Vec<LendingStep>,
+) -> Result<()> {
+    let before = ctx.accounts.health()?;
+    let balance_before = ctx.accounts.vault_token_account.amount;
+    ctx.accounts.run_steps(ctx.remaining_accounts, steps)?;
+    ctx.accounts.check_balance(balance_before)?;
+    let after = ctx.accounts.health()?;
+    ctx.accounts.leverage_loop.check_loop(
+        &before,
+        &after,
+        ctx.accounts.strategy.risk_parameters.max_leverage_bps,
+    )?;
+
+    let leverage_loop = &mut ctx.accounts.leverage_loop;
+    leverage_loop.loops = math::checked_add(leverage_loop.loops, 1)?;
+    emit!(LeverageLoopRun {
+        leverage_loop: leverage_loop.key(),
+        unwind: false,
+        ltv_bps: after.ltv_bps()?,
+        health_bps: after.health_bps()?,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// One unwinding pass: withdraw collateral, swap it into the borrowed asset and repay, leaving the
+// obligation no closer to liquidation. Accounts as for loop_leverage.
+pub fn unwind_leverage<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunLeverageLoop<'info>>,
+    steps: Vec<LendingStep>,
+) -> Result<()> {
+    let before = ctx.accounts.health()?;
+    let balance_before = ctx.accounts.vault_token_account.amount;
+    ctx.accounts.run_steps(ctx.remaining_accounts, steps)?;
+    ctx.accounts.check_balance(balance_before)?;
+    let after = ctx.accounts.health()?;
+    ctx.accounts.leverage_loop.check_unwind(&before, &after)?;
+
+    let leverage_loop = &mut ctx.accounts.leverage_loop;
+    leverage_loop.unwinds = math::checked_add(leverage_loop.unwinds, 1)?;
+    emit!(LeverageLoopRun {
+        leverage_loop: leverage_loop.key(),
+        unwind: true,
+        ltv_bps: after.ltv_bps()?,
+        health_bps: after.health_bps()?,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
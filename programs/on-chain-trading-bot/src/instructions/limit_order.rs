@@ -0,0 +1,585 @@
+use crate::account_manager::{DexAccountManager, UserPositions};
+use crate::constants::{
+    EXPIRY_CURSOR_SEED, FEED_REGISTRY_SEED, LIMIT_ORDER_SEED, MAX_FILL_ORACLE_DEVIATION_BPS,
+    TRADE_HISTORY_SEED, TRADING_VAULT_SEED, VAULT_LOG_SEED,
+};
+use crate::events::LimitOrderUpdated;
+use crate::oracles::PythOracle;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        ExpiryCursor, FeedRegistry, FillLeaf, LimitOrder, OrderStatus, Strategy, TradeHistory,
+        TradeMiningEpoch, TradingVault, UserEpochVolume, VaultLog, VaultOp,
+    },
+    trading_vault_seeds,
+};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct PlaceLimitOrder<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    base_mint: Box<Account<'info, Mint>>,
+    quote_mint: Box<Account<'info, Mint>>,
+
+    // the order counts as a new position against the strategy's cap on open positions
+    #[account(
+      has_one=owner,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      has_one=owner,
+    )]
+    user_positions: AccountLoader<'info, UserPositions>,
+
+    // quote vault for buys, base vault for sells
+    #[account(
+      mut,
+      has_one=owner,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=trading_vault.token_account
+    )]
+    vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = LimitOrder::LEN,
+      seeds = [LIMIT_ORDER_SEED, owner.key().as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    order: Box<Account<'info, LimitOrder>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FillLimitOrder<'info> {
+    #[account(mut)]
+    keeper: Signer<'info>,
+
+    /// CHECK: Receives the fill proceeds and, once filled, the order's rent
+    #[account(
+      mut,
+      address=order.owner
+    )]
+    owner: UncheckedAccount<'info>,
+
+    #[account(
+      address=order.payment_mint()
+    )]
+    payment_mint: Box<Account<'info, Mint>>,
+
+    // fills have to be priced within MAX_FILL_ORACLE_DEVIATION_BPS of the pair's oracles
+    #[account(
+      address=order.base_mint
+    )]
+    base_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=order.quote_mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Resolved through the feed registry on read
+    base_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Resolved through the feed registry on read
+    quote_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      mut,
+      seeds = [LIMIT_ORDER_SEED, order.owner.as_ref(), order.idx.to_le_bytes().as_ref()],
+      bump = order.bump,
+    )]
+    order: Box<Account<'info, LimitOrder>>,
+
+    #[account(
+      mut,
+      address=order.trading_vault,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=trading_vault.token_account
+    )]
+    vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=keeper,
+      token::mint=payment_mint,
+    )]
+    keeper_payment_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=keeper,
+      token::mint=trading_vault.mint,
+    )]
+    keeper_receive_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=keeper,
+      associated_token::authority=owner,
+      associated_token::mint=payment_mint,
+    )]
+    owner_payment_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The owner's TradeHistory PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [TRADE_HISTORY_SEED, order.owner.as_ref()],
+      bump,
+    )]
+    owner_trade_history: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+
+    // the owner's trade-mining accounts for the current epoch, to have the fill counted
+    #[account(mut)]
+    mining_epoch: Option<Box<Account<'info, TradeMiningEpoch>>>,
+
+    #[account(mut)]
+    user_volume: Option<Box<Account<'info, UserEpochVolume>>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct ExpireOrders<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    #[account(
+      init_if_needed,
+      payer = payer,
+      space = ExpiryCursor::LEN,
+      seeds = [EXPIRY_CURSOR_SEED, owner.as_ref()],
+      bump
+    )]
+    expiry_cursor: Box<Account<'info, ExpiryCursor>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLimitOrder<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      close=owner,
+      seeds = [LIMIT_ORDER_SEED, order.owner.as_ref(), order.idx.to_le_bytes().as_ref()],
+      bump = order.bump,
+    )]
+    order: Box<Account<'info, LimitOrder>>,
+
+    #[account(
+      mut,
+      address=order.trading_vault
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+}
+
+// Native quote the registered oracles put on `size` native base
+pub fn oracle_quote(
+    registry: &FeedRegistry,
+    base_mint: &Account<Mint>,
+    base_price_feed: &AccountInfo,
+    quote_mint: &Account<Mint>,
+    quote_price_feed: &AccountInfo,
+    size: u64,
+) -> Result<u64> {
+    let base_usd =
+        PythOracle::get_usd_price(registry, &base_mint.key(), base_price_feed, u64::MAX)?;
+    let quote_usd =
+        PythOracle::get_usd_price(registry, &quote_mint.key(), quote_price_feed, u64::MAX)?;
+    PythOracle::usd_convert(
+        size,
+        base_mint.decimals,
+        base_usd,
+        quote_mint.decimals,
+        quote_usd,
+    )
+}
+
+impl<'info> FillLimitOrder<'info> {
+    pub fn emit_update(order: &LimitOrder, order_key: Pubkey, fill_size: u64, fill_price: u64) {
+        emit!(LimitOrderUpdated {
+            order: order_key,
+            owner: order.owner,
+            status: order.status,
+            fill_size,
+            fill_price,
+            filled_size: order.filled_size,
+            remaining_size: order.remaining_size(),
+            avg_fill_price: order.avg_fill_price,
+        });
+    }
+}
+
+// Funds stay in the owner's trading vault; the order only reserves what it has committed
+pub fn place_limit_order(
+    ctx: Context<PlaceLimitOrder>,
+    idx: u64,
+    is_buy: bool,
+    limit_price: u64,
+    size: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require_gt!(size, 0, TradingBotError::InvalidOrderParams);
+    require_gt!(limit_price, 0, TradingBotError::InvalidOrderParams);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        expires_at == 0 || expires_at > now,
+        TradingBotError::OrderExpired
+    );
+
+    let reserve_mint = if is_buy {
+        ctx.accounts.quote_mint.key()
+    } else {
+        ctx.accounts.base_mint.key()
+    };
+    require_keys_eq!(
+        ctx.accounts.trading_vault.mint,
+        reserve_mint,
+        TradingBotError::InvalidOrderParams
+    );
+    let reserved = LimitOrder::reserve_amount(is_buy, size, limit_price)?;
+    require_gt!(reserved, 0, TradingBotError::InvalidOrderParams);
+    DexAccountManager::check_open_position_cap(
+        &*ctx.accounts.user_positions.load()?,
+        ctx.accounts.strategy.key(),
+        ctx.accounts.strategy.risk_parameters.max_open_positions,
+        None,
+        ctx.accounts.order.key(),
+    )?;
+
+    msg!("Reserving {} in trading vault", reserved);
+    ctx.accounts
+        .trading_vault
+        .reserve(reserved, ctx.accounts.vault_token_account.amount)?;
+
+    let order = &mut ctx.accounts.order;
+    order.idx = idx;
+    order.owner = ctx.accounts.owner.key();
+    order.trading_vault = ctx.accounts.trading_vault.key();
+    order.base_mint = ctx.accounts.base_mint.key();
+    order.quote_mint = ctx.accounts.quote_mint.key();
+    order.is_buy = is_buy;
+    order.limit_price = limit_price;
+    order.size = size;
+    order.reserved = reserved;
+    order.filled_size = 0;
+    order.filled_quote = 0;
+    order.avg_fill_price = 0;
+    order.fill_count = 0;
+    order.status = OrderStatus::Open;
+    order.created_at = now;
+    order.updated_at = now;
+    order.expires_at = expires_at;
+    order.bump = *ctx.bumps.get("order").unwrap();
+
+    FillLimitOrder::emit_update(order, order.key(), 0, 0);
+    VaultLog::append_if_initialized(
+        &ctx.accounts.vault_log,
+        VaultOp::PlaceOrder,
+        ctx.accounts.order.key(),
+        reserved,
+        &ctx.accounts.trading_vault,
+        Some(ctx.accounts.vault_token_account.amount),
+    )?;
+    Ok(())
+}
+
+// Keepers fill part or all of the remaining size at the limit price or better; the order
+// closes itself once fully filled
+pub fn fill_limit_order(
+    ctx: Context<FillLimitOrder>,
+    fill_size: u64,
+    fill_price: u64,
+) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    let now = Clock::get()?.unix_timestamp;
+    let order = &ctx.accounts.order;
+    require!(!order.status.is_terminal(), TradingBotError::OrderNotOpen);
+    require!(!order.is_expired(now), TradingBotError::OrderExpired);
+    require!(
+        order.is_marketable(fill_price),
+        TradingBotError::LimitPriceNotMet
+    );
+    let fill_size = fill_size.min(order.remaining_size());
+    require_gt!(fill_size, 0, TradingBotError::InvalidOrderParams);
+
+    let fill_quote = order.fill_quote(fill_size, fill_price)?;
+    require_gt!(fill_quote, 0, TradingBotError::FillTooSmall);
+    // the owner can't move the vault out at an off-market price by filling their own order
+    let accounts = &ctx.accounts;
+    let oracle_quote = oracle_quote(
+        &accounts.feed_registry,
+        &accounts.base_mint,
+        &accounts.base_price_feed,
+        &accounts.quote_mint,
+        &accounts.quote_price_feed,
+        fill_size,
+    )?;
+    require!(
+        LimitOrder::within_oracle_band(fill_quote, oracle_quote, MAX_FILL_ORACLE_DEVIATION_BPS)?,
+        TradingBotError::FillOffOracle
+    );
+    let (payment, spend) = if order.is_buy {
+        (fill_size, fill_quote)
+    } else {
+        (fill_quote, fill_size)
+    };
+    msg!("Filling {} at {}", fill_size, fill_price);
+
+    let order = &mut ctx.accounts.order;
+    order.apply_fill(fill_size, fill_quote, now)?;
+
+    // buys filled below the limit spend less than they reserved; the difference is freed on the
+    // final fill
+    let filled = order.status == OrderStatus::Filled;
+    let release = if filled {
+        order.reserved
+    } else {
+        LimitOrder::reserve_amount(order.is_buy, fill_size, order.limit_price)?
+    };
+    order.reserved = math::checked_sub(order.reserved, release)?;
+    ctx.accounts.trading_vault.release(release, filled)?;
+
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.keeper_payment_account.to_account_info(),
+                to: ctx.accounts.owner_payment_account.to_account_info(),
+                authority: ctx.accounts.keeper.to_account_info(),
+            },
+        ),
+        payment,
+    )?;
+
+    let signer_seeds: &[&[&[u8]]] = &[trading_vault_seeds!(ctx.accounts.trading_vault)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.keeper_receive_account.to_account_info(),
+                authority: ctx.accounts.trading_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        spend,
+    )?;
+
+    ctx.accounts.vault_token_account.reload()?;
+    ctx.accounts
+        .trading_vault
+        .check_invariant(ctx.accounts.vault_token_account.amount)?;
+
+    TradeHistory::append_if_initialized(
+        &ctx.accounts.owner_trade_history,
+        FillLeaf {
+            timestamp: now,
+            pair: ctx.accounts.order.base_mint,
+            is_buy: ctx.accounts.order.is_buy,
+            size: fill_size,
+            price: fill_price,
+            fee: 0,
+        },
+    )?;
+    let order = &ctx.accounts.order;
+    UserEpochVolume::record_fill(
+        ctx.accounts.mining_epoch.as_deref_mut(),
+        ctx.accounts.user_volume.as_deref_mut(),
+        order.owner,
+        Pubkey::default(),
+        [(order.base_mint, fill_size), (order.quote_mint, fill_quote)],
+        0,
+    )?;
+    VaultLog::append_if_initialized(
+        &ctx.accounts.vault_log,
+        VaultOp::FillOrder,
+        ctx.accounts.order.key(),
+        spend,
+        &ctx.accounts.trading_vault,
+        Some(ctx.accounts.vault_token_account.amount),
+    )?;
+
+    FillLimitOrder::emit_update(
+        &ctx.accounts.order,
+        ctx.accounts.order.key(),
+        fill_size,
+        fill_price,
+    );
+
+    if filled {
+        ctx.accounts
+            .order
+            .close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+    let order = &mut ctx.accounts.order;
+    require!(!order.status.is_terminal(), TradingBotError::OrderNotOpen);
+
+    let released = order.reserved;
+    ctx.accounts.trading_vault.release(released, true)?;
+    order.reserved = 0;
+    order.status = OrderStatus::Cancelled;
+    order.updated_at = Clock::get()?.unix_timestamp;
+    FillLimitOrder::emit_update(order, order.key(), 0, 0);
+    VaultLog::append_if_initialized(
+        &ctx.accounts.vault_log,
+        VaultOp::CancelOrder,
+        ctx.accounts.order.key(),
+        released,
+        &ctx.accounts.trading_vault,
+        None,
+    )?;
+
+    Ok(())
+}
+
+// Permissionless crank over the owner's orders by index, `max_items` per call from where the last
+// call stopped. Remaining accounts come in groups of [order, trading vault, owner, vault log], one
+// per index; an index whose order was closed or never placed is passed as its empty PDA. Orders
+// that are still live are skipped and looked at again on the next pass, started from index zero.
+pub fn expire_orders<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExpireOrders<'info>>,
+    owner: Pubkey,
+    start_index: u64,
+    max_items: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let expiry_cursor = &mut ctx.accounts.expiry_cursor;
+    let page = expiry_cursor.cursor.page(start_index, max_items)?;
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        4 * max_items as usize,
+        TradingBotError::InvalidOrderParams
+    );
+    expiry_cursor.owner = owner;
+    expiry_cursor.bump = *ctx.bumps.get("expiry_cursor").unwrap();
+
+    for (idx, accounts) in page.clone().zip(ctx.remaining_accounts.chunks(4)) {
+        if accounts[0].data_is_empty() {
+            let (address, _) = Pubkey::find_program_address(
+                &[LIMIT_ORDER_SEED, owner.as_ref(), idx.to_le_bytes().as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                accounts[0].key(),
+                address,
+                TradingBotError::InvalidOrderParams
+            );
+            continue;
+        }
+        let mut order = Account::<LimitOrder>::try_from(&accounts[0])?;
+        require!(
+            order.owner == owner && order.idx == idx,
+            TradingBotError::InvalidOrderParams
+        );
+        if order.status.is_terminal() || !order.is_expired(now) {
+            continue;
+        }
+        require_keys_eq!(
+            accounts[1].key(),
+            order.trading_vault,
+            TradingBotError::InvalidOrderParams
+        );
+        require_keys_eq!(
+            accounts[2].key(),
+            order.owner,
+            TradingBotError::InvalidOrderParams
+        );
+
+        VaultLog::require_address(&accounts[3], &order.trading_vault)?;
+
+        let released = order.reserved;
+        let mut trading_vault = Account::<TradingVault>::try_from(&accounts[1])?;
+        trading_vault.release(released, true)?;
+        trading_vault.exit(ctx.program_id)?;
+        VaultLog::append_if_initialized(
+            &accounts[3],
+            VaultOp::ExpireOrder,
+            order.key(),
+            released,
+            &trading_vault,
+            None,
+        )?;
+
+        order.reserved = 0;
+        order.status = OrderStatus::Expired;
+        order.updated_at = now;
+        FillLimitOrder::emit_update(&order, order.key(), 0, 0);
+        msg!("Expired order #{}", order.idx);
+        order.close(accounts[2].clone())?;
+    }
+
+    ctx.accounts
+        .expiry_cursor
+        .cursor
+        .advance(page.end, None, now);
+    Ok(())
+}
@@ -0,0 +1,583 @@
+use crate::constants::{
+    FEED_REGISTRY_SEED, FORCED_EXIT_AUCTION_DURATION, FORCED_EXIT_FLOOR_DISCOUNT_BPS,
+    LIQUIDATION_AUCTION_SEED, PRICE_PRECISION, STRATEGY_SEED, TRADE_HISTORY_SEED,
+};
+use crate::instructions::oracle_quote;
+use crate::risk::RiskManager;
+use crate::{
+    errors::TradingBotError,
+    liquidation_auction_seeds, math,
+    state::{
+        FeedRegistry, FillLeaf, ForcedExitReason, LiquidationAuction, Strategy, TradeHistory,
+    },
+    strategy_seeds,
+};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct StartLiquidationAuction<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    lot_mint: Box<Account<'info, Mint>>,
+    quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+      token::mint=lot_mint,
+    )]
+    owner_lot_account: Box<Account<'info, TokenAccount>>,
+
+    // takes pay into it
+    #[account(
+      token::authority=owner,
+      token::mint=quote_mint,
+    )]
+    owner_quote_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Pyth feed for the lot, resolved through the feed registry on read
+    price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth feed for the quote, resolved through the feed registry on read
+    quote_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = LiquidationAuction::LEN,
+      seeds = [LIQUIDATION_AUCTION_SEED, owner.key().as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    auction: Box<Account<'info, LiquidationAuction>>,
+
+    #[account(
+      init,
+      payer=owner,
+      associated_token::authority=auction,
+      associated_token::mint=lot_mint,
+    )]
+    auction_vault: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct ForceLiquidationAuction<'info> {
+    // any keeper; pays the auction's rent, which goes to the owner once it sells out
+    #[account(mut)]
+    keeper: Signer<'info>,
+
+    #[account(
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    lot_mint: Box<Account<'info, Mint>>,
+    quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      token::authority=strategy,
+      token::mint=lot_mint,
+    )]
+    strategy_lot_account: Box<Account<'info, TokenAccount>>,
+
+    // takes pay back into the strategy
+    #[account(
+      token::authority=strategy,
+      token::mint=quote_mint,
+    )]
+    strategy_quote_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Pyth feed for the lot, resolved through the feed registry on read
+    price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth feed for the quote, resolved through the feed registry on read
+    quote_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      init,
+      payer = keeper,
+      space = LiquidationAuction::LEN,
+      seeds = [LIQUIDATION_AUCTION_SEED, strategy.owner.as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    auction: Box<Account<'info, LiquidationAuction>>,
+
+    #[account(
+      init,
+      payer=keeper,
+      associated_token::authority=auction,
+      associated_token::mint=lot_mint,
+    )]
+    auction_vault: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct TakeLiquidationAuction<'info> {
+    #[account(mut)]
+    taker: Signer<'info>,
+
+    /// CHECK: Receives the closed accounts' rent once sold out
+    #[account(
+      mut,
+      address=auction.owner
+    )]
+    owner: UncheckedAccount<'info>,
+
+    #[account(
+      address=auction.lot_mint
+    )]
+    lot_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=auction.quote_mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      seeds = [LIQUIDATION_AUCTION_SEED, auction.owner.as_ref(), auction.idx.to_le_bytes().as_ref()],
+      bump = auction.bump,
+    )]
+    auction: Box<Account<'info, LiquidationAuction>>,
+
+    #[account(
+      mut,
+      associated_token::authority=auction,
+      associated_token::mint=lot_mint,
+    )]
+    auction_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=taker,
+      token::mint=quote_mint,
+    )]
+    taker_quote_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::authority=taker,
+      associated_token::mint=lot_mint,
+    )]
+    taker_lot_account: Box<Account<'info, TokenAccount>>,
+
+    // the owner's account for their own auctions, the strategy's for a forced exit
+    #[account(
+      mut,
+      address=auction.proceeds_account
+    )]
+    proceeds_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The owner's TradeHistory PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [TRADE_HISTORY_SEED, auction.owner.as_ref()],
+      bump,
+    )]
+    owner_trade_history: UncheckedAccount<'info>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLiquidationAuction<'info> {
+    caller: Signer<'info>,
+
+    /// CHECK: Receives the closed accounts' rent
+    #[account(
+      mut,
+      address=auction.owner
+    )]
+    owner: UncheckedAccount<'info>,
+
+    // a forced exit's strategy, read for whether it's back within its loss limit
+    #[account(
+      address=auction.strategy
+    )]
+    strategy: Option<Box<Account<'info, Strategy>>>,
+
+    #[account(
+      address=auction.lot_mint
+    )]
+    lot_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      close=owner,
+      seeds = [LIQUIDATION_AUCTION_SEED, auction.owner.as_ref(), auction.idx.to_le_bytes().as_ref()],
+      bump = auction.bump,
+    )]
+    auction: Box<Account<'info, LiquidationAuction>>,
+
+    #[account(
+      mut,
+      associated_token::authority=auction,
+      associated_token::mint=lot_mint,
+    )]
+    auction_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      address=auction.lot_account
+    )]
+    lot_account: Box<Account<'info, TokenAccount>>,
+
+    token_program: Program<'info, Token>,
+}
+
+impl<'info> TakeLiquidationAuction<'info> {
+    // Native quote owed for `amount` native lot at `price` whole quote per whole lot in
+    // PRICE_PRECISION, rounded up so a take never underpays the owner
+    pub fn compute_payment(
+        amount: u64,
+        price: u64,
+        lot_decimals: u8,
+        quote_decimals: u8,
+    ) -> Result<u64> {
+        let payment = math::checked_ceil_div(
+            math::checked_mul(
+                math::checked_mul(amount as u128, price as u128)?,
+                10u128.pow(quote_decimals as u32),
+            )?,
+            math::checked_mul(PRICE_PRECISION as u128, 10u128.pow(lot_decimals as u32))?,
+        )?;
+        math::checked_as_u64(payment)
+    }
+}
+
+// Oracle mid of one whole lot token in whole quote, PRICE_PRECISION, through the feeds the
+// registry holds for both mints
+fn oracle_mid(
+    registry: &FeedRegistry,
+    lot_mint: &Account<Mint>,
+    lot_price_feed: &AccountInfo,
+    quote_mint: &Account<Mint>,
+    quote_price_feed: &AccountInfo,
+) -> Result<u64> {
+    let one_lot = 10u64
+        .checked_pow(lot_mint.decimals as u32)
+        .ok_or(TradingBotError::Overflow)?;
+    let quote = oracle_quote(
+        registry,
+        lot_mint,
+        lot_price_feed,
+        quote_mint,
+        quote_price_feed,
+        one_lot,
+    )?;
+    let price = math::checked_as_u64(math::checked_div(
+        math::checked_mul(quote as u128, PRICE_PRECISION as u128)?,
+        10u128.pow(quote_mint.decimals as u32),
+    )?)?;
+    require_gt!(price, 0, TradingBotError::InvalidAuctionParams);
+    Ok(price)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn open_auction(
+    auction: &mut LiquidationAuction,
+    idx: u64,
+    owner: Pubkey,
+    lot_mint: Pubkey,
+    quote_mint: Pubkey,
+    reason: ForcedExitReason,
+    amount: u64,
+    start_price: u64,
+    floor_discount_bps: u16,
+    duration: i64,
+    bump: u8,
+) -> Result<()> {
+    let floor_price = math::checked_as_u64(math::checked_div(
+        math::checked_mul(start_price as u128, (10000 - floor_discount_bps) as u128)?,
+        10000,
+    )?)?;
+
+    auction.idx = idx;
+    auction.owner = owner;
+    auction.lot_mint = lot_mint;
+    auction.quote_mint = quote_mint;
+    auction.reason = reason;
+    auction.initial_amount = amount;
+    auction.remaining_amount = amount;
+    auction.start_price = start_price;
+    auction.floor_price = floor_price;
+    auction.start_at = Clock::get()?.unix_timestamp;
+    auction.duration = duration;
+    auction.proceeds = 0;
+    auction.bump = bump;
+
+    msg!("Auction from {} to floor {}", start_price, floor_price);
+    Ok(())
+}
+
+// Flags a position for forced exit and lists it in a Dutch auction starting at the oracle mid
+pub fn start_liquidation_auction(
+    ctx: Context<StartLiquidationAuction>,
+    idx: u64,
+    amount: u64,
+    floor_discount_bps: u16,
+    duration: i64,
+    reason: ForcedExitReason,
+) -> Result<()> {
+    require_gt!(amount, 0, TradingBotError::InvalidAuctionParams);
+    require_gt!(duration, 0, TradingBotError::InvalidAuctionParams);
+    require!(
+        floor_discount_bps > 0 && floor_discount_bps < 10000,
+        TradingBotError::InvalidAuctionParams
+    );
+
+    let start_price = oracle_mid(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.lot_mint,
+        &ctx.accounts.price_feed,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.quote_price_feed,
+    )?;
+
+    msg!("Lock lot in auction vault");
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_lot_account.to_account_info(),
+                to: ctx.accounts.auction_vault.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.strategy = Pubkey::default();
+    auction.lot_account = ctx.accounts.owner_lot_account.key();
+    auction.proceeds_account = ctx.accounts.owner_quote_account.key();
+    open_auction(
+        auction,
+        idx,
+        ctx.accounts.owner.key(),
+        ctx.accounts.lot_mint.key(),
+        ctx.accounts.quote_mint.key(),
+        reason,
+        amount,
+        start_price,
+        floor_discount_bps,
+        duration,
+        *ctx.bumps.get("auction").unwrap(),
+    )
+}
+
+// Anyone can force a strategy past its loss limit out of a lot its PDA holds. The whole balance
+// goes up on the protocol's fixed floor and duration, so the keeper can't set terms to take it
+// cheaply itself, and takes pay into the strategy's own quote account.
+pub fn force_liquidation_auction(ctx: Context<ForceLiquidationAuction>, idx: u64) -> Result<()> {
+    let strategy = &ctx.accounts.strategy;
+    require!(
+        RiskManager::loss_limit_breached(strategy),
+        TradingBotError::InvalidAuctionParams
+    );
+    require!(!strategy.executing, TradingBotError::ExecutionInProgress);
+    let amount = ctx.accounts.strategy_lot_account.amount;
+    require_gt!(amount, 0, TradingBotError::InvalidAuctionParams);
+
+    let start_price = oracle_mid(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.lot_mint,
+        &ctx.accounts.price_feed,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.quote_price_feed,
+    )?;
+
+    msg!("Lock strategy lot in auction vault");
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.strategy_lot_account.to_account_info(),
+                to: ctx.accounts.auction_vault.to_account_info(),
+                authority: strategy.to_account_info(),
+            },
+            &[strategy_seeds!(strategy)],
+        ),
+        amount,
+    )?;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.strategy = strategy.key();
+    auction.lot_account = ctx.accounts.strategy_lot_account.key();
+    auction.proceeds_account = ctx.accounts.strategy_quote_account.key();
+    open_auction(
+        auction,
+        idx,
+        strategy.owner,
+        ctx.accounts.lot_mint.key(),
+        ctx.accounts.quote_mint.key(),
+        ForcedExitReason::RiskBreach,
+        amount,
+        start_price,
+        FORCED_EXIT_FLOOR_DISCOUNT_BPS,
+        FORCED_EXIT_AUCTION_DURATION,
+        *ctx.bumps.get("auction").unwrap(),
+    )
+}
+
+// Any taker can fill part or all of the remaining lot at the current decayed price
+pub fn take_liquidation_auction(
+    ctx: Context<TakeLiquidationAuction>,
+    amount: u64,
+    max_price: u64,
+) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    let auction = &ctx.accounts.auction;
+    let amount = amount.min(auction.remaining_amount);
+    require_gt!(amount, 0, TradingBotError::InvalidAuctionParams);
+
+    let price = auction.current_price(Clock::get()?.unix_timestamp)?;
+    require_gte!(max_price, price, TradingBotError::AuctionPriceAboveLimit);
+    let payment = TakeLiquidationAuction::compute_payment(
+        amount,
+        price,
+        ctx.accounts.lot_mint.decimals,
+        ctx.accounts.quote_mint.decimals,
+    )?;
+    msg!("Taking {} at {} for {}", amount, price, payment);
+
+    let auction = &mut ctx.accounts.auction;
+    auction.remaining_amount = math::checked_sub(auction.remaining_amount, amount)?;
+    auction.proceeds = math::checked_add(auction.proceeds, payment)?;
+
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.taker_quote_account.to_account_info(),
+                to: ctx.accounts.proceeds_account.to_account_info(),
+                authority: ctx.accounts.taker.to_account_info(),
+            },
+        ),
+        payment,
+    )?;
+
+    let idx_bytes = ctx.accounts.auction.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[liquidation_auction_seeds!(ctx.accounts.auction, idx_bytes)];
+
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.auction_vault.to_account_info(),
+                to: ctx.accounts.taker_lot_account.to_account_info(),
+                authority: ctx.accounts.auction.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    TradeHistory::append_if_initialized(
+        &ctx.accounts.owner_trade_history,
+        FillLeaf {
+            timestamp: Clock::get()?.unix_timestamp,
+            pair: ctx.accounts.auction.lot_mint,
+            is_buy: false,
+            size: amount,
+            price,
+            fee: 0,
+        },
+    )?;
+
+    // close out once fully sold
+    if ctx.accounts.auction.remaining_amount == 0 {
+        anchor_spl::token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::CloseAccount {
+                account: ctx.accounts.auction_vault.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.auction.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        ctx.accounts
+            .auction
+            .close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+// The owner can call an auction off at any point, and anyone can once a forced exit's strategy is
+// back within its loss limit. The unsold lot goes back to the account it came from.
+pub fn cancel_liquidation_auction(ctx: Context<CancelLiquidationAuction>) -> Result<()> {
+    let breached = ctx
+        .accounts
+        .strategy
+        .as_ref()
+        .map(|strategy| RiskManager::loss_limit_breached(strategy));
+    require!(
+        ctx.accounts.auction.can_cancel(ctx.accounts.caller.key, breached),
+        TradingBotError::AuctionNotCancellable
+    );
+    msg!("Returning {} unsold", ctx.accounts.auction.remaining_amount);
+
+    let idx_bytes = ctx.accounts.auction.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[liquidation_auction_seeds!(ctx.accounts.auction, idx_bytes)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.auction_vault.to_account_info(),
+                to: ctx.accounts.lot_account.to_account_info(),
+                authority: ctx.accounts.auction.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        ctx.accounts.auction_vault.amount,
+    )?;
+    anchor_spl::token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        anchor_spl::token::CloseAccount {
+            account: ctx.accounts.auction_vault.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.auction.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    Ok(())
+}
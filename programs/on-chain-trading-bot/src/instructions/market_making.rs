@@ -0,0 +1,971 @@
+use crate::account_manager::{DexAccountManager, UserPositions};
+use crate::constants::{
+    FEED_REGISTRY_SEED, MARKET_MAKER_CLAIM_SEED, MARKET_MAKER_SEED, MAX_SLIPPAGE_BPS, OUTBOX_SEED,
+    PRICE_PRECISION, TRADE_HISTORY_SEED,
+};
+use crate::dex::{
+    drift::{
+        self, drift_program_id, DriftPerpPosition, DRIFT_BASE_PRECISION, DRIFT_QUOTE_PRECISION,
+    },
+    jupiter::JUPITER_V6_PROGRAM_ID,
+    raydium::RAYDIUM_AMM_PROGRAM_ID,
+    serum::{SerumDex, SerumOrder, SerumSettle},
+};
+// the generated client structs of nested account groups resolve relative to this module
+use crate::dex::serum::{
+    __client_accounts_serum_order, __client_accounts_serum_settle,
+    __cpi_client_accounts_serum_order, __cpi_client_accounts_serum_settle,
+};
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use crate::instructions::ViewStrategySummary;
+use crate::oracles::PythOracle;
+use crate::risk::RiskManager;
+use crate::types::{StrategyType, TokenPair};
+use crate::utils::validate_deadline;
+use crate::valuation::{PositionValuation, Valuation};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        FeedRegistry, FillLeaf, HedgeVenue, MarketMaker, MarketMakerClaim, MarketMakerParams,
+        NotificationKind, Outbox, Strategy, TradeHistory,
+    },
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use anchor_spl::token::{Mint, TokenAccount};
+use serum_dex::matching::Side;
+
+#[derive(Accounts)]
+pub struct InitMarketMaker<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    /// CHECK: Serum market, validated by the DEX on every order
+    market: UncheckedAccount<'info>,
+
+    /// CHECK: Owner's open orders account on the market, claimed for this market maker
+    open_orders: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth price feed, resolved through the feed registry on every read
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      token::authority=owner,
+    )]
+    base_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      token::authority=owner,
+    )]
+    quote_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = MarketMaker::LEN,
+      seeds = [MARKET_MAKER_SEED, owner.key().as_ref(), market.key().as_ref(), MarketMaker::TEMPLATE_VARIANT.to_le_bytes().as_ref()],
+      bump
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = MarketMakerClaim::LEN,
+      seeds = [MARKET_MAKER_CLAIM_SEED, open_orders.key().as_ref()],
+      bump
+    )]
+    open_orders_claim: Box<Account<'info, MarketMakerClaim>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = MarketMakerClaim::LEN,
+      seeds = [MARKET_MAKER_CLAIM_SEED, base_wallet.key().as_ref()],
+      bump
+    )]
+    base_wallet_claim: Box<Account<'info, MarketMakerClaim>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = MarketMakerClaim::LEN,
+      seeds = [MARKET_MAKER_CLAIM_SEED, quote_wallet.key().as_ref()],
+      bump
+    )]
+    quote_wallet_claim: Box<Account<'info, MarketMakerClaim>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequoteMarketMaker<'info> {
+    #[account(
+      mut,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+      constraint=market_maker.owner==serum.owner.key(),
+      constraint=market_maker.market==serum.market.key(),
+      constraint=market_maker.open_orders==serum.open_orders.key(),
+      constraint=market_maker.quote_wallet==serum.quote_wallet.key(),
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    /// CHECK: Checked against the feed stored at init
+    #[account(
+      address=market_maker.price_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      address=market_maker.base_wallet
+    )]
+    base_wallet: Box<Account<'info, TokenAccount>>,
+
+    serum: SerumOrder<'info>,
+
+    #[account(
+      mut,
+      seeds = [OUTBOX_SEED, market_maker.key().as_ref()],
+      bump = outbox.bump,
+    )]
+    outbox: Option<Box<Account<'info, Outbox>>>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleMarketMaker<'info> {
+    #[account(
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+      constraint=market_maker.owner==serum.owner.key(),
+      constraint=market_maker.market==serum.market.key(),
+      constraint=market_maker.open_orders==serum.open_orders.key(),
+      constraint=market_maker.base_wallet==serum.base_wallet.key(),
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    serum: SerumSettle<'info>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HedgeMarketMaker<'info> {
+    #[account(
+      mut,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+      constraint=market_maker.owner==owner.key(),
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    owner: Signer<'info>,
+
+    /// CHECK: Checked against the feed stored at init
+    #[account(
+      address=market_maker.price_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      mut,
+      address=market_maker.base_wallet
+    )]
+    base_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      address=market_maker.quote_wallet
+    )]
+    quote_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      address=base_wallet.mint
+    )]
+    base_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=quote_wallet.mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Checked against the configured hedge venue
+    hedge_program: UncheckedAccount<'info>,
+
+    // the owner's Drift user and user stats, read around perp hedges for what Drift booked
+    /// CHECK: Checked to be a Drift account of the owner on read
+    drift_user: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Checked to be a Drift account of the owner on read
+    drift_user_stats: Option<UncheckedAccount<'info>>,
+
+    // the owner's strategy and positions: a perp hedge opening from flat is held to the strategy's
+    // cap on open positions, and one growing the perp to its leverage cap
+    #[account(
+      has_one=owner,
+    )]
+    strategy: Option<Box<Account<'info, Strategy>>>,
+
+    #[account(
+      has_one=owner,
+    )]
+    user_positions: Option<AccountLoader<'info, UserPositions>>,
+
+    /// CHECK: The owner's TradeHistory PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [TRADE_HISTORY_SEED, owner.key().as_ref()],
+      bump,
+    )]
+    trade_history: UncheckedAccount<'info>,
+
+    #[account(
+      mut,
+      seeds = [OUTBOX_SEED, market_maker.key().as_ref()],
+      bump = outbox.bump,
+    )]
+    outbox: Option<Box<Account<'info, Outbox>>>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+impl<'info> RequoteMarketMaker<'info> {
+    // Requote once the oracle mid has moved at least `threshold_bps` away from the last quoted mid
+    pub fn needs_requote(last_mid: u64, mid: u64, threshold_bps: u16) -> Result<bool> {
+        if last_mid == 0 {
+            return Ok(true);
+        }
+
+        let moved = mid.abs_diff(last_mid);
+        let moved_bps = math::checked_div(
+            math::checked_mul(moved as u128, 10000)?,
+            last_mid as u128,
+        )?;
+
+        Ok(moved_bps >= threshold_bps as u128)
+    }
+
+    // Positive skew when long inventory: both quotes shift down to favour selling
+    pub fn compute_skew_bps(
+        deviation: i128,
+        max_inventory_deviation: u64,
+        max_skew_bps: u16,
+    ) -> Result<i64> {
+        let skew = math::checked_div(
+            math::checked_mul(deviation, max_skew_bps as i128)?,
+            max_inventory_deviation as i128,
+        )?;
+        let max_skew = max_skew_bps as i128;
+
+        Ok(skew.clamp(-max_skew, max_skew) as i64)
+    }
+
+    pub fn compute_quotes(mid: u64, spread_bps: u16, skew_bps: i64) -> Result<(u64, u64)> {
+        let half_spread = (spread_bps / 2) as i128;
+        let bid_bps = math::checked_sub(math::checked_sub(10000, half_spread)?, skew_bps as i128)?;
+        let ask_bps = math::checked_sub(math::checked_add(10000, half_spread)?, skew_bps as i128)?;
+
+        let bid = math::checked_div(math::checked_mul(mid as i128, bid_bps)?, 10000)?;
+        let ask = math::checked_div(math::checked_mul(mid as i128, ask_bps)?, 10000)?;
+
+        Ok((math::checked_as_u64(bid)?, math::checked_as_u64(ask)?))
+    }
+}
+
+impl<'info> HedgeMarketMaker<'info> {
+    pub fn venue_program_id(venue: HedgeVenue) -> Pubkey {
+        match venue {
+            HedgeVenue::Raydium => RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap(),
+            HedgeVenue::Jupiter => JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap(),
+            HedgeVenue::DriftPerp { .. } => drift_program_id(),
+        }
+    }
+
+    fn drift_accounts(&self) -> Result<(AccountInfo<'info>, AccountInfo<'info>)> {
+        match (self.drift_user.as_ref(), self.drift_user_stats.as_ref()) {
+            (Some(user), Some(user_stats)) => {
+                Ok((user.to_account_info(), user_stats.to_account_info()))
+            }
+            _ => err!(TradingBotError::InvalidDriftAccount),
+        }
+    }
+
+    fn strategy(&self) -> Result<&Account<'info, Strategy>> {
+        match self.strategy.as_deref() {
+            Some(strategy) => Ok(strategy),
+            None => err!(TradingBotError::MissingStrategy),
+        }
+    }
+
+    fn check_position_cap(&self) -> Result<()> {
+        let strategy = self.strategy()?;
+        let Some(user_positions) = self.user_positions.as_ref() else {
+            return err!(TradingBotError::MissingStrategy);
+        };
+        DexAccountManager::check_open_position_cap(
+            &*user_positions.load()?,
+            strategy.key(),
+            strategy.risk_parameters.max_open_positions,
+            None,
+            self.market_maker.market,
+        )
+    }
+
+    // The owner's booked position on the market and lifetime Drift fees
+    fn read_drift(&self, market_index: u16) -> Result<(DriftPerpPosition, u64)> {
+        let (user, user_stats) = self.drift_accounts()?;
+        let owner = self.owner.key();
+        Ok((
+            DriftPerpPosition::read(&user, &owner, market_index)?,
+            DriftPerpPosition::total_fee_paid(&user_stats, &owner)?,
+        ))
+    }
+
+    // A hedge may cost at most `max_slippage_bps` of its value at the oracle mid
+    pub fn require_within_slippage(
+        cost: i64,
+        base_moved: u64,
+        mid: u64,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        let max_cost = math::checked_div(
+            math::checked_mul(
+                HedgeMarketMaker::quote_value(base_moved, mid)?,
+                max_slippage_bps as u64,
+            )?,
+            10000,
+        )?;
+        require!(cost <= max_cost as i64, TradingBotError::SlippageExceeded);
+        Ok(())
+    }
+
+    // Quote value of `base_amount` at the oracle mid
+    pub fn quote_value(base_amount: u64, mid: u64) -> Result<u64> {
+        let value = math::checked_div(
+            math::checked_mul(base_amount as u128, mid as u128)?,
+            PRICE_PRECISION as u128,
+        )?;
+        math::checked_as_u64(value)
+    }
+
+    // Cost of a spot hedge versus the oracle mid, negative when the venue beat the oracle
+    pub fn spot_hedge_cost(
+        selling_base: bool,
+        base_moved: u64,
+        quote_moved: u64,
+        mid: u64,
+    ) -> Result<i64> {
+        let fair = Self::quote_value(base_moved, mid)? as i128;
+        let cost = if selling_base {
+            math::checked_sub(fair, quote_moved as i128)?
+        } else {
+            math::checked_sub(quote_moved as i128, fair)?
+        };
+        Ok(cost as i64)
+    }
+}
+
+impl<'info> InitMarketMaker<'info> {
+    pub fn validate_params(params: &MarketMakerParams) -> Result<()> {
+        require!(
+            params.spread_bps > 0 && params.spread_bps < 10000,
+            TradingBotError::InvalidMarketMakingParams
+        );
+        require!(
+            (params.spread_bps / 2) as u32 + (params.max_skew_bps as u32) < 10000,
+            TradingBotError::InvalidMarketMakingParams
+        );
+        require_gt!(
+            params.order_size,
+            0,
+            TradingBotError::InvalidMarketMakingParams
+        );
+        require_gt!(
+            params.max_inventory_deviation,
+            0,
+            TradingBotError::InvalidMarketMakingParams
+        );
+        require_gte!(
+            params.quote_ttl,
+            0,
+            TradingBotError::InvalidMarketMakingParams
+        );
+        Ok(())
+    }
+
+    // Oracle mid of the wallets' pair in native quote per native base, scaled by PRICE_PRECISION:
+    // the unit quotes are converted to Serum lots from and inventory is valued at
+    pub fn oracle_mid(
+        feed_registry: &FeedRegistry,
+        price_feed: &AccountInfo,
+        max_confidence: u64,
+        base_mint: Pubkey,
+        quote_mint: Pubkey,
+    ) -> Result<u64> {
+        PythOracle::get_pair_price(
+            feed_registry,
+            &TokenPair::new(base_mint, quote_mint),
+            price_feed,
+            max_confidence,
+        )
+    }
+
+    // Baseline for unrealized P&L reporting, valued like the NAV it's compared against
+    pub fn initial_nav(
+        feed_registry: &FeedRegistry,
+        price_feed: &AccountInfo,
+        max_confidence: u64,
+        base_wallet: &TokenAccount,
+        quote_wallet: &TokenAccount,
+    ) -> Result<u64> {
+        let mid = Self::oracle_mid(
+            feed_registry,
+            price_feed,
+            max_confidence,
+            base_wallet.mint,
+            quote_wallet.mint,
+        )?;
+        Valuation::nav(&[
+            PositionValuation::Token {
+                amount: base_wallet.amount,
+                price: mid,
+            },
+            PositionValuation::Token {
+                amount: quote_wallet.amount,
+                price: PRICE_PRECISION,
+            },
+        ])
+    }
+}
+
+pub fn init_market_maker(ctx: Context<InitMarketMaker>, params: MarketMakerParams) -> Result<()> {
+    InitMarketMaker::validate_params(&params)?;
+    let initial_nav = InitMarketMaker::initial_nav(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.price_feed,
+        params.max_confidence,
+        &ctx.accounts.base_wallet,
+        &ctx.accounts.quote_wallet,
+    )?;
+
+    let market_maker = &mut ctx.accounts.market_maker;
+    market_maker.owner = ctx.accounts.owner.key();
+    market_maker.strategy_type = StrategyType::MarketMaking;
+    market_maker.market = ctx.accounts.market.key();
+    market_maker.variant = MarketMaker::TEMPLATE_VARIANT;
+    market_maker.template = Pubkey::default();
+    market_maker.open_orders = ctx.accounts.open_orders.key();
+    market_maker.price_feed = ctx.accounts.price_feed.key();
+    market_maker.base_wallet = ctx.accounts.base_wallet.key();
+    market_maker.quote_wallet = ctx.accounts.quote_wallet.key();
+    market_maker.apply_params(&params);
+    market_maker.last_quote_mid = 0;
+    market_maker.last_bid_price = 0;
+    market_maker.last_ask_price = 0;
+    market_maker.last_quote_at = 0;
+    market_maker.quote_expires_at = 0;
+    market_maker.quote_seq = 0;
+    market_maker.perp_position = 0;
+    market_maker.perp_entry_price = 0;
+    market_maker.hedge_count = 0;
+    market_maker.hedge_volume = 0;
+    market_maker.hedge_cost = 0;
+    market_maker.hedge_fees = 0;
+    market_maker.initial_nav = initial_nav;
+    market_maker.net_contributions = initial_nav as i64;
+    market_maker.is_active = true;
+    market_maker.bump = *ctx.bumps.get("market_maker").unwrap();
+
+    let market_maker = market_maker.key();
+    let bumps = &ctx.bumps;
+    ctx.accounts.open_orders_claim.claim(market_maker, *bumps.get("open_orders_claim").unwrap());
+    ctx.accounts.base_wallet_claim.claim(market_maker, *bumps.get("base_wallet_claim").unwrap());
+    ctx.accounts.quote_wallet_claim.claim(market_maker, *bumps.get("quote_wallet_claim").unwrap());
+
+    Ok(())
+}
+
+pub fn requote_market_maker(ctx: Context<RequoteMarketMaker>) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    require!(
+        ctx.accounts.market_maker.is_active,
+        TradingBotError::StrategyInactive
+    );
+
+    let mid = InitMarketMaker::oracle_mid(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.price_feed,
+        ctx.accounts.market_maker.max_confidence,
+        ctx.accounts.base_wallet.mint,
+        ctx.accounts.serum.quote_wallet.mint,
+    )?;
+    let now = Clock::get()?.unix_timestamp;
+
+    let market_maker = &ctx.accounts.market_maker;
+    require!(
+        market_maker.quotes_expired(now)
+            || RequoteMarketMaker::needs_requote(
+                market_maker.last_quote_mid,
+                mid,
+                market_maker.requote_threshold_bps,
+            )?,
+        TradingBotError::RequoteNotNeeded
+    );
+
+    // pull the previous pair before quoting again
+    if market_maker.last_quote_mid > 0 {
+        msg!("Cancelling quotes #{}", market_maker.quote_seq);
+        ctx.accounts
+            .serum
+            .cancel_order_by_client_id(market_maker.bid_client_id())?;
+        ctx.accounts
+            .serum
+            .cancel_order_by_client_id(market_maker.ask_client_id())?;
+    }
+
+    let skew_bps = RequoteMarketMaker::compute_skew_bps(
+        market_maker.net_deviation(ctx.accounts.base_wallet.amount),
+        market_maker.max_inventory_deviation,
+        market_maker.max_skew_bps,
+    )?;
+    let (bid, ask) = RequoteMarketMaker::compute_quotes(mid, market_maker.spread_bps, skew_bps)?;
+    msg!("Quoting mid {} skew {} bps: bid {} ask {}", mid, skew_bps, bid, ask);
+
+    let market_maker = &mut ctx.accounts.market_maker;
+    market_maker.quote_seq = math::checked_add(market_maker.quote_seq, 1)?;
+
+    ctx.accounts.serum.new_limit_order(
+        Side::Bid,
+        bid,
+        ctx.accounts.market_maker.order_size,
+        ctx.accounts.market_maker.bid_client_id(),
+    )?;
+    ctx.accounts.serum.new_limit_order(
+        Side::Ask,
+        ask,
+        ctx.accounts.market_maker.order_size,
+        ctx.accounts.market_maker.ask_client_id(),
+    )?;
+
+    let market_maker = &mut ctx.accounts.market_maker;
+    market_maker.last_quote_mid = mid;
+    market_maker.last_bid_price = bid;
+    market_maker.last_ask_price = ask;
+    market_maker.last_quote_at = now;
+    market_maker.quote_expires_at = if market_maker.quote_ttl > 0 {
+        math::checked_add(now, market_maker.quote_ttl)?
+    } else {
+        0
+    };
+
+    Ok(())
+}
+
+// Pulls both quotes off the book once their good-til-time passes, leaving the strategy flat until
+// the next requote. Serum cancels need the open orders owner, so this runs from the owner's keeper.
+pub fn expire_market_maker_quotes(ctx: Context<RequoteMarketMaker>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let market_maker = &ctx.accounts.market_maker;
+    require!(
+        market_maker.quotes_expired(now),
+        TradingBotError::OrderNotExpired
+    );
+
+    msg!("Expiring quotes #{}", market_maker.quote_seq);
+    ctx.accounts
+        .serum
+        .cancel_order_by_client_id(market_maker.bid_client_id())?;
+    ctx.accounts
+        .serum
+        .cancel_order_by_client_id(market_maker.ask_client_id())?;
+
+    let market_maker = &mut ctx.accounts.market_maker;
+    market_maker.last_quote_mid = 0;
+    market_maker.last_bid_price = 0;
+    market_maker.last_ask_price = 0;
+    market_maker.quote_expires_at = 0;
+
+    // quotes only expire when the keeper missed its requote window
+    if let Some(outbox) = ctx.accounts.outbox.as_mut() {
+        outbox.push(
+            NotificationKind::KeeperFailure,
+            ctx.accounts.market_maker.market,
+            ctx.accounts.market_maker.quote_seq,
+            now,
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn settle_market_maker<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleMarketMaker<'info>>,
+) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    msg!("Settling market maker fills");
+    SerumDex::settle_funds(Context::new(
+        ctx.program_id,
+        &mut ctx.accounts.serum,
+        ctx.remaining_accounts,
+        ctx.bumps.clone(),
+    ))
+}
+
+// Brings net inventory back to target once it leaves the band; the keeper supplies the venue
+// instruction data and accounts, and the realised fill is checked against the oracle
+pub fn hedge_market_maker(
+    ctx: Context<HedgeMarketMaker>,
+    hedge_data: Vec<u8>,
+    max_slippage_bps: u16,
+    deadline: i64,
+) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    validate_deadline(deadline)?;
+    require!(
+        max_slippage_bps <= MAX_SLIPPAGE_BPS,
+        TradingBotError::SlippageExceeded
+    );
+
+    let market_maker = &ctx.accounts.market_maker;
+    require_keys_eq!(
+        ctx.accounts.hedge_program.key(),
+        HedgeMarketMaker::venue_program_id(market_maker.hedge_venue),
+        TradingBotError::InvalidDexType
+    );
+
+    // the same native mid spot fills, Drift fills rescaled to the mints' decimals and the
+    // leverage check are all measured against
+    let mid = InitMarketMaker::oracle_mid(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.price_feed,
+        market_maker.max_confidence,
+        ctx.accounts.base_wallet.mint,
+        ctx.accounts.quote_wallet.mint,
+    )?;
+
+    let deviation = market_maker.net_deviation(ctx.accounts.base_wallet.amount);
+    require!(
+        deviation.unsigned_abs() > market_maker.inventory_band as u128,
+        TradingBotError::InventoryWithinBand
+    );
+    let selling_base = deviation > 0;
+    let hedge_size = math::checked_as_u64(deviation.unsigned_abs())?;
+    msg!("Hedging {} base (selling: {})", hedge_size, selling_base);
+    let risk_breached = hedge_size > market_maker.max_inventory_deviation;
+
+    let base_before = ctx.accounts.base_wallet.amount;
+    let quote_before = ctx.accounts.quote_wallet.amount;
+    let drift_before = match market_maker.hedge_venue {
+        HedgeVenue::DriftPerp { market_index } => {
+            if market_maker.perp_position == 0 {
+                ctx.accounts.check_position_cap()?;
+            }
+            Some(ctx.accounts.read_drift(market_index)?)
+        }
+        _ => None,
+    };
+
+    let ix = Instruction {
+        program_id: ctx.accounts.hedge_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|a| {
+                if a.is_writable {
+                    AccountMeta::new(a.key(), a.is_signer)
+                } else {
+                    AccountMeta::new_readonly(a.key(), a.is_signer)
+                }
+            })
+            .collect(),
+        data: hedge_data,
+    };
+    invoke(&ix, ctx.remaining_accounts)?;
+
+    let (base_moved, fill_price, cost, fee) = match ctx.accounts.market_maker.hedge_venue {
+        HedgeVenue::Raydium | HedgeVenue::Jupiter => {
+            ctx.accounts.base_wallet.reload()?;
+            ctx.accounts.quote_wallet.reload()?;
+            let base_after = ctx.accounts.base_wallet.amount;
+            let quote_after = ctx.accounts.quote_wallet.amount;
+
+            let (base_moved, quote_moved) = if selling_base {
+                (
+                    math::checked_sub(base_before, base_after)?,
+                    math::checked_sub(quote_after, quote_before)?,
+                )
+            } else {
+                (
+                    math::checked_sub(base_after, base_before)?,
+                    math::checked_sub(quote_before, quote_after)?,
+                )
+            };
+            require_gte!(hedge_size, base_moved, TradingBotError::InvalidCalculation);
+
+            let cost =
+                HedgeMarketMaker::spot_hedge_cost(selling_base, base_moved, quote_moved, mid)?;
+            HedgeMarketMaker::require_within_slippage(cost, base_moved, mid, max_slippage_bps)?;
+
+            let fill_price = math::checked_as_u64(math::checked_div(
+                math::checked_mul(quote_moved as u128, PRICE_PRECISION as u128)?,
+                base_moved as u128,
+            )?)?;
+
+            (base_moved, fill_price, cost, 0)
+        }
+        HedgeVenue::DriftPerp { market_index } => {
+            // perp hedges offset spot inventory without moving wallet balances; what Drift booked
+            // is read back off the owner's user and user stats
+            let (position_before, fees_before) =
+                drift_before.ok_or(TradingBotError::InvalidDriftAccount)?;
+            let (position_after, fees_after) = ctx.accounts.read_drift(market_index)?;
+            let base_delta = math::checked_sub(
+                position_after.base_asset_amount,
+                position_before.base_asset_amount,
+            )?;
+            let quote_delta = math::checked_sub(
+                position_after.quote_asset_amount,
+                position_before.quote_asset_amount,
+            )?;
+            let fees_paid = math::checked_sub(fees_after, fees_before)?;
+            require!(
+                base_delta != 0 && (base_delta < 0) == selling_base,
+                TradingBotError::InvalidCalculation
+            );
+
+            let (base_decimals, quote_decimals) =
+                (ctx.accounts.base_mint.decimals, ctx.accounts.quote_mint.decimals);
+            let base_moved =
+                drift::rescale(base_delta.unsigned_abs(), DRIFT_BASE_PRECISION, base_decimals)?;
+            require!(
+                base_moved > 0 && base_moved <= hedge_size,
+                TradingBotError::InvalidCalculation
+            );
+            // the quote amount takes the notional and the fee off a buy, and the fee off a sell's
+            // notional
+            let notional = math::checked_add(quote_delta as i128, fees_paid as i128)?;
+            let quote_moved = drift::rescale(
+                math::checked_as_u64(notional.unsigned_abs())?,
+                DRIFT_QUOTE_PRECISION,
+                quote_decimals,
+            )?;
+            let fee = drift::rescale(fees_paid, DRIFT_QUOTE_PRECISION, quote_decimals)?;
+
+            let price_cost =
+                HedgeMarketMaker::spot_hedge_cost(selling_base, base_moved, quote_moved, mid)?;
+            HedgeMarketMaker::require_within_slippage(
+                price_cost,
+                base_moved,
+                mid,
+                max_slippage_bps,
+            )?;
+            let fill_price = math::checked_as_u64(math::checked_div(
+                math::checked_mul(quote_moved as u128, PRICE_PRECISION as u128)?,
+                base_moved as u128,
+            )?)?;
+
+            let signed_size = if selling_base {
+                -(base_moved as i64)
+            } else {
+                base_moved as i64
+            };
+            let perp_before = ctx.accounts.market_maker.perp_position.unsigned_abs();
+            ctx.accounts.market_maker.apply_perp_fill(signed_size, fill_price)?;
+
+            // growing the perp opens exposure and must stay within the leverage cap; shrinking
+            // it only ever deleverages
+            let market_maker = &ctx.accounts.market_maker;
+            if market_maker.perp_position.unsigned_abs() > perp_before {
+                let gross_exposure = HedgeMarketMaker::quote_value(
+                    math::checked_as_u64(market_maker.gross_exposure(base_before))?,
+                    mid,
+                )?;
+                let nav = ViewStrategySummary::nav(market_maker, base_before, quote_before, mid)?;
+                RiskManager::validate_leverage(
+                    gross_exposure,
+                    nav,
+                    ctx.accounts.strategy()?.risk_parameters.max_leverage_bps,
+                )?;
+            }
+
+            let cost = math::checked_add(
+                price_cost,
+                i64::try_from(fee).map_err(|_| TradingBotError::Overflow)?,
+            )?;
+            (base_moved, fill_price, cost, fee)
+        }
+    };
+
+    let market_maker = &mut ctx.accounts.market_maker;
+    market_maker.hedge_count = math::checked_add(market_maker.hedge_count, 1)?;
+    market_maker.hedge_volume = math::checked_add(market_maker.hedge_volume, base_moved)?;
+    market_maker.hedge_cost = math::checked_add(market_maker.hedge_cost, cost)?;
+    market_maker.hedge_fees = math::checked_add(market_maker.hedge_fees, fee)?;
+    msg!("Hedged {} base at cost {}", base_moved, cost);
+
+    let now = Clock::get()?.unix_timestamp;
+    if let Some(outbox) = ctx.accounts.outbox.as_mut() {
+        let market = ctx.accounts.market_maker.market;
+        if risk_breached {
+            outbox.push(NotificationKind::RiskBreach, market, hedge_size, now)?;
+        }
+        outbox.push(NotificationKind::TradeExecuted, market, base_moved, now)?;
+    }
+
+    TradeHistory::append_if_initialized(
+        &ctx.accounts.trade_history,
+        FillLeaf {
+            timestamp: now,
+            pair: ctx.accounts.market_maker.market,
+            is_buy: !selling_base,
+            size: base_moved,
+            price: fill_price,
+            fee,
+        },
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracles::tests::{load, pyth_account_data};
+
+    // SOL/USDC at $150.25 off an expo -8 feed, as native USDC per lamport
+    fn sol_usdc_mid() -> u64 {
+        let price = load(&mut pyth_account_data(15_025_000_000, 1_500_000, -8));
+        PythOracle::native_price(&price, 9, 6).unwrap()
+    }
+
+    #[test]
+    fn quotes_off_the_scaled_mid_land_on_serum_lots() {
+        let mid = sol_usdc_mid();
+        assert_eq!(mid, 150_250);
+        let (bid, ask) = RequoteMarketMaker::compute_quotes(mid, 20, 0).unwrap();
+        assert_eq!((bid, ask), (150_099, 150_400));
+
+        // 0.1 SOL base lots and 0.0001 USDC quote lots: 15.0099 USDC a lot, 10 lots for 1 SOL
+        let (lot_price, lots) = SerumDex::to_lots(bid, 1_000_000_000, 100_000_000, 100).unwrap();
+        assert_eq!((lot_price.get(), lots.get()), (150_099, 10));
+        let (lot_price, _) = SerumDex::to_lots(ask, 1_000_000_000, 100_000_000, 100).unwrap();
+        assert_eq!(lot_price.get(), 150_400);
+    }
+
+    #[test]
+    fn quotes_scale_with_a_quote_of_more_decimals() {
+        // a 6-decimal token at 0.5 of a 9-decimal quote is 500 native quote per native base
+        let price = load(&mut pyth_account_data(50_000_000, 10_000, -8));
+        let mid = PythOracle::native_price(&price, 6, 9).unwrap();
+        assert_eq!(mid, 500_000_000);
+        let (bid, _) = RequoteMarketMaker::compute_quotes(mid, 200, 0).unwrap();
+        assert_eq!(bid, 495_000_000);
+        // whole-token base lots cost 0.495 quote, in quote lots of 1_000 native units
+        let (lot_price, lots) = SerumDex::to_lots(bid, 2_000_000, 1_000_000, 1_000).unwrap();
+        assert_eq!((lot_price.get(), lots.get()), (495_000, 2));
+    }
+
+    #[test]
+    fn long_inventory_skews_both_quotes_down() {
+        let skew = RequoteMarketMaker::compute_skew_bps(500_000_000, 1_000_000_000, 100).unwrap();
+        assert_eq!(skew, 50);
+        assert_eq!(
+            RequoteMarketMaker::compute_skew_bps(-5_000_000_000, 1_000_000_000, 100).unwrap(),
+            -100
+        );
+        let (bid, ask) = RequoteMarketMaker::compute_quotes(sol_usdc_mid(), 20, skew).unwrap();
+        assert_eq!((bid, ask), (149_348, 149_649));
+    }
+
+    #[test]
+    fn requote_threshold_is_relative_to_the_last_mid() {
+        assert!(RequoteMarketMaker::needs_requote(0, 150_250, 50).unwrap());
+        assert!(!RequoteMarketMaker::needs_requote(150_250, 150_900, 50).unwrap());
+        assert!(RequoteMarketMaker::needs_requote(150_250, 151_002, 50).unwrap());
+    }
+
+    #[test]
+    fn spot_hedge_cost_is_measured_against_the_native_mid() {
+        let mid = sol_usdc_mid();
+        // selling 2 SOL for 299.9 USDC against 300.5 fair
+        let cost =
+            HedgeMarketMaker::spot_hedge_cost(true, 2_000_000_000, 299_900_000, mid).unwrap();
+        assert_eq!(cost, 600_000);
+        HedgeMarketMaker::require_within_slippage(cost, 2_000_000_000, mid, 20).unwrap();
+        assert!(HedgeMarketMaker::require_within_slippage(cost, 2_000_000_000, mid, 19).is_err());
+
+        // buying under the mid is a negative cost
+        let cost =
+            HedgeMarketMaker::spot_hedge_cost(false, 1_000_000_000, 150_000_000, mid).unwrap();
+        assert_eq!(cost, -250_000);
+    }
+
+    #[test]
+    fn drift_fills_rescale_onto_the_same_mid() {
+        let mid = sol_usdc_mid();
+        // 1.5 SOL sold on the perp for 225.3 USDC, in Drift's precisions
+        let base_moved = drift::rescale(1_500_000_000, DRIFT_BASE_PRECISION, 9).unwrap();
+        let quote_moved = drift::rescale(225_300_000, DRIFT_QUOTE_PRECISION, 6).unwrap();
+        assert_eq!((base_moved, quote_moved), (1_500_000_000, 225_300_000));
+        let cost = HedgeMarketMaker::spot_hedge_cost(true, base_moved, quote_moved, mid).unwrap();
+        assert_eq!(cost, 75_000);
+
+        // a 6-decimal base comes back in its own units
+        assert_eq!(drift::rescale(1_500_000_000, DRIFT_BASE_PRECISION, 6).unwrap(), 1_500_000);
+    }
+
+    #[test]
+    fn inventory_values_in_native_quote() {
+        // 2 SOL and 300 USDC
+        let nav = Valuation::nav(&[
+            PositionValuation::Token {
+                amount: 2_000_000_000,
+                price: sol_usdc_mid(),
+            },
+            PositionValuation::Token {
+                amount: 300_000_000,
+                price: PRICE_PRECISION,
+            },
+        ])
+        .unwrap();
+        assert_eq!(nav, 600_500_000);
+    }
+
+    #[test]
+    fn sibling_variants_never_share_client_ids() {
+        let template_bid = MarketMaker::client_id(MarketMaker::TEMPLATE_VARIANT, 7, false);
+        let template_ask = MarketMaker::client_id(MarketMaker::TEMPLATE_VARIANT, 7, true);
+        assert_eq!((template_bid, template_ask), (14, 15));
+
+        // the same quote sequence on variant 1 and 2
+        let first = MarketMaker::client_id(1, 7, false);
+        let second = MarketMaker::client_id(2, 7, false);
+        assert_eq!(first, (1 << 48) | 14);
+        assert_ne!(first, second);
+        assert_ne!(first, template_bid);
+        assert_eq!(MarketMaker::client_id(1, 7, true), first | 1);
+    }
+}
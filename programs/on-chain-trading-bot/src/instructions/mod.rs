@@ -1,10 +1,120 @@
-// pub mod initialize;
 pub mod close;
+pub mod initialize;
 pub mod setup_dca;
+pub mod accounting_currency;
 pub mod airdrop;
+pub mod bridge;
+pub mod callback;
+pub mod clone_market_maker;
+pub mod compute_profile;
+pub mod consume_fills;
+pub mod correlation;
+pub mod covered_call;
+pub mod depeg_guard;
+pub mod devnet;
+pub mod epoch_history;
+pub mod escrow_policy;
+pub mod execution_auction;
+pub mod execution_metrics;
+pub mod fee_oracle;
+pub mod feed_registry;
+pub mod harvest;
+pub mod index_fund;
+pub mod internal_order_book;
+pub mod keeper;
+pub mod leverage_loop;
+pub mod limit_order;
+pub mod liquidation_auction;
+pub mod market_making;
+pub mod nav_feed;
+pub mod otc;
+pub mod outbox;
+pub mod pause;
+pub mod peg_arb;
+pub mod pool_registry;
+pub mod pool_sampler;
+pub mod portfolio;
+pub mod position_stop;
+pub mod principal_note;
+pub mod public_profile;
+pub mod quote_lock;
+pub mod recovery;
+pub mod regime;
+pub mod recurring_transfer;
+pub mod rewards;
+pub mod session;
+pub mod soft_unwind;
+pub mod strategy_funds;
+pub mod subscription;
+pub mod sweep_dust;
+pub mod token_launch;
+pub mod trade_approval;
+pub mod trade_history;
+pub mod trade_mining;
+pub mod trading_vault;
+pub mod treasury_plan;
+pub mod upgrade_guard;
+pub mod vault_log;
+pub mod views;
+pub mod volatility_halt;
 
-// pub use initialize::*;
 pub use close::*;
+pub use initialize::*;
 pub use setup_dca::*;
+pub use accounting_currency::*;
 pub use airdrop::*;
+pub use bridge::*;
+pub use callback::*;
+pub use clone_market_maker::*;
+pub use compute_profile::*;
+pub use consume_fills::*;
+pub use correlation::*;
+pub use covered_call::*;
+pub use depeg_guard::*;
+pub use devnet::*;
+pub use epoch_history::*;
+pub use escrow_policy::*;
+pub use execution_auction::*;
+pub use execution_metrics::*;
+pub use fee_oracle::*;
+pub use feed_registry::*;
+pub use harvest::*;
+pub use index_fund::*;
+pub use internal_order_book::*;
+pub use keeper::*;
+pub use leverage_loop::*;
+pub use limit_order::*;
+pub use liquidation_auction::*;
+pub use market_making::*;
+pub use nav_feed::*;
+pub use otc::*;
+pub use outbox::*;
+pub use pause::*;
+pub use peg_arb::*;
+pub use pool_registry::*;
+pub use pool_sampler::*;
+pub use portfolio::*;
+pub use position_stop::*;
+pub use principal_note::*;
+pub use public_profile::*;
+pub use quote_lock::*;
+pub use recovery::*;
+pub use regime::*;
+pub use recurring_transfer::*;
+pub use rewards::*;
+pub use session::*;
+pub use soft_unwind::*;
+pub use strategy_funds::*;
+pub use subscription::*;
+pub use sweep_dust::*;
+pub use token_launch::*;
+pub use trade_approval::*;
+pub use trade_history::*;
+pub use trade_mining::*;
+pub use trading_vault::*;
+pub use treasury_plan::*;
+pub use upgrade_guard::*;
+pub use vault_log::*;
+pub use views::*;
+pub use volatility_halt::*;
 
@@ -0,0 +1,116 @@
+use crate::constants::{FEED_REGISTRY_SEED, INDEX_FUND_SEED, NAV_FEED_SEED};
+use crate::events::NavPublished;
+use crate::instructions::IndexTransfer;
+use crate::state::{FeedRegistry, IndexFund, NavFeed, TradingVault};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitNavFeed<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = NavFeed::LEN,
+      seeds = [NAV_FEED_SEED, index_fund.key().as_ref()],
+      bump
+    )]
+    nav_feed: Box<Account<'info, NavFeed>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PublishNavFeed<'info> {
+    #[account(
+      seeds = [INDEX_FUND_SEED, index_fund.base_vault.as_ref()],
+      bump = index_fund.bump,
+    )]
+    index_fund: Box<Account<'info, IndexFund>>,
+
+    #[account(
+      mut,
+      seeds = [NAV_FEED_SEED, index_fund.key().as_ref()],
+      bump = nav_feed.bump,
+    )]
+    nav_feed: Box<Account<'info, NavFeed>>,
+
+    #[account(
+      address=index_fund.base_vault
+    )]
+    base_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=base_vault.token_account
+    )]
+    base_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      address=base_vault.mint
+    )]
+    base_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Resolved through the feed registry on read
+    base_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+pub fn init_nav_feed(ctx: Context<InitNavFeed>) -> Result<()> {
+    let nav_feed = &mut ctx.accounts.nav_feed;
+    nav_feed.fund = ctx.accounts.index_fund.key();
+    nav_feed.price = 0;
+    nav_feed.expo = NavFeed::EXPO;
+    nav_feed.nav = 0;
+    nav_feed.units = 0;
+    nav_feed.publish_time = 0;
+    nav_feed.publish_slot = 0;
+    nav_feed.bump = *ctx.bumps.get("nav_feed").unwrap();
+
+    Ok(())
+}
+
+// Anyone can republish the feed from the oracles. Remaining accounts are each component's token
+// account and price feed, in order, as for deposit_index.
+pub fn publish_nav_feed<'info>(
+    ctx: Context<'_, '_, '_, 'info, PublishNavFeed<'info>>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let (_, nav) = IndexTransfer::fund_nav(
+        &accounts.index_fund,
+        &accounts.feed_registry,
+        accounts.base_token_account.amount,
+        &accounts.base_mint,
+        &accounts.base_price_feed,
+        ctx.remaining_accounts,
+    )?;
+    let units = accounts.index_fund.units;
+
+    let clock = Clock::get()?;
+    let nav_feed = &mut ctx.accounts.nav_feed;
+    nav_feed.publish(nav, units, &clock)?;
+    emit!(NavPublished {
+        nav_feed: nav_feed.key(),
+        fund: nav_feed.fund,
+        price: nav_feed.price,
+        expo: nav_feed.expo,
+        nav,
+        units,
+        publish_time: nav_feed.publish_time,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,382 @@
+use crate::constants::{OTC_OFFER_SEED, PRICE_PRECISION, TRADE_HISTORY_SEED};
+use crate::{
+    errors::TradingBotError,
+    math, otc_offer_seeds,
+    state::{FillLeaf, OtcOffer, TradeHistory, TradeMiningEpoch, UserEpochVolume},
+};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct CreateOtcOffer<'info> {
+    #[account(mut)]
+    maker: Signer<'info>,
+
+    offer_mint: Box<Account<'info, Mint>>,
+    ask_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      token::authority=maker,
+      token::mint=offer_mint,
+    )]
+    maker_offer_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = maker,
+      space = OtcOffer::LEN,
+      seeds = [OTC_OFFER_SEED, maker.key().as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    offer: Box<Account<'info, OtcOffer>>,
+
+    #[account(
+      init,
+      payer=maker,
+      associated_token::authority=offer,
+      associated_token::mint=offer_mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct FillOtcOffer<'info> {
+    #[account(mut)]
+    taker: Signer<'info>,
+
+    /// CHECK: Receives the ask amount and the closed accounts' rent
+    #[account(
+      mut,
+      address=offer.maker
+    )]
+    maker: UncheckedAccount<'info>,
+
+    #[account(
+      address=offer.offer_mint
+    )]
+    offer_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=offer.ask_mint
+    )]
+    ask_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      close=maker,
+      seeds = [OTC_OFFER_SEED, offer.maker.as_ref(), offer.idx.to_le_bytes().as_ref()],
+      bump = offer.bump,
+    )]
+    offer: Box<Account<'info, OtcOffer>>,
+
+    #[account(
+      mut,
+      associated_token::authority=offer,
+      associated_token::mint=offer_mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=taker,
+      token::mint=ask_mint,
+    )]
+    taker_ask_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::authority=taker,
+      associated_token::mint=offer_mint,
+    )]
+    taker_offer_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::authority=maker,
+      associated_token::mint=ask_mint,
+    )]
+    maker_ask_account: Box<Account<'info, TokenAccount>>,
+
+    // takes back whatever was sent to the vault beyond the offer, so it can be closed
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::authority=maker,
+      associated_token::mint=offer_mint,
+    )]
+    maker_offer_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The owner's TradeHistory PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [TRADE_HISTORY_SEED, offer.maker.as_ref()],
+      bump,
+    )]
+    maker_trade_history: UncheckedAccount<'info>,
+
+    // the maker's trade-mining accounts for the current epoch, to have the fill counted
+    #[account(mut)]
+    mining_epoch: Option<Box<Account<'info, TradeMiningEpoch>>>,
+
+    #[account(mut)]
+    user_volume: Option<Box<Account<'info, UserEpochVolume>>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOtcOffer<'info> {
+    signer: Signer<'info>,
+
+    /// CHECK: Refunded the offered tokens and the closed accounts' rent
+    #[account(
+      mut,
+      address=offer.maker
+    )]
+    maker: UncheckedAccount<'info>,
+
+    #[account(
+      address=offer.offer_mint
+    )]
+    offer_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      close=maker,
+      seeds = [OTC_OFFER_SEED, offer.maker.as_ref(), offer.idx.to_le_bytes().as_ref()],
+      bump = offer.bump,
+    )]
+    offer: Box<Account<'info, OtcOffer>>,
+
+    #[account(
+      mut,
+      associated_token::authority=offer,
+      associated_token::mint=offer_mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      associated_token::authority=maker,
+      associated_token::mint=offer_mint,
+    )]
+    maker_offer_account: Box<Account<'info, TokenAccount>>,
+
+    token_program: Program<'info, Token>,
+}
+
+pub fn create_otc_offer(
+    ctx: Context<CreateOtcOffer>,
+    idx: u64,
+    offer_amount: u64,
+    ask_amount: u64,
+    expires_at: i64,
+    taker: Option<Pubkey>,
+) -> Result<()> {
+    require_gt!(offer_amount, 0, TradingBotError::InvalidCalculation);
+    require_gt!(ask_amount, 0, TradingBotError::InvalidCalculation);
+    require_gt!(
+        expires_at,
+        Clock::get()?.unix_timestamp,
+        TradingBotError::OtcOfferExpired
+    );
+
+    msg!("Lock offer in vault");
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.maker_offer_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.maker.to_account_info(),
+            },
+        ),
+        offer_amount,
+    )?;
+
+    let offer = &mut ctx.accounts.offer;
+    offer.idx = idx;
+    offer.maker = ctx.accounts.maker.key();
+    offer.taker = taker;
+    offer.offer_mint = ctx.accounts.offer_mint.key();
+    offer.ask_mint = ctx.accounts.ask_mint.key();
+    offer.offer_amount = offer_amount;
+    offer.ask_amount = ask_amount;
+    offer.expires_at = expires_at;
+    offer.bump = *ctx.bumps.get("offer").unwrap();
+
+    Ok(())
+}
+
+// Both legs settle in the same instruction, so neither side can be left half-filled
+pub fn fill_otc_offer(ctx: Context<FillOtcOffer>) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    let offer = &ctx.accounts.offer;
+    require!(
+        !offer.is_expired(Clock::get()?.unix_timestamp),
+        TradingBotError::OtcOfferExpired
+    );
+    // a maker filling their own offer moves nothing but would still count as a trade
+    require_keys_neq!(
+        offer.maker,
+        ctx.accounts.taker.key(),
+        TradingBotError::UnauthorizedOtcParty
+    );
+    if let Some(taker) = offer.taker {
+        require_keys_eq!(
+            taker,
+            ctx.accounts.taker.key(),
+            TradingBotError::UnauthorizedOtcParty
+        );
+    }
+
+    msg!("Transfer ask to maker");
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.taker_ask_account.to_account_info(),
+                to: ctx.accounts.maker_ask_account.to_account_info(),
+                authority: ctx.accounts.taker.to_account_info(),
+            },
+        ),
+        offer.ask_amount,
+    )?;
+
+    let idx_bytes = ctx.accounts.offer.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[otc_offer_seeds!(ctx.accounts.offer, idx_bytes)];
+
+    msg!("Release offer to taker");
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.taker_offer_account.to_account_info(),
+                authority: ctx.accounts.offer.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        ctx.accounts.offer.offer_amount,
+    )?;
+
+    ctx.accounts.vault.reload()?;
+    let excess = ctx.accounts.vault.amount;
+    if excess > 0 {
+        msg!("Return {} beyond the offer to maker", excess);
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.maker_offer_account.to_account_info(),
+                    authority: ctx.accounts.offer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            excess,
+        )?;
+    }
+
+    anchor_spl::token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        anchor_spl::token::CloseAccount {
+            account: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.maker.to_account_info(),
+            authority: ctx.accounts.offer.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    let offer = &ctx.accounts.offer;
+    TradeHistory::append_if_initialized(
+        &ctx.accounts.maker_trade_history,
+        FillLeaf {
+            timestamp: Clock::get()?.unix_timestamp,
+            pair: offer.offer_mint,
+            is_buy: false,
+            size: offer.offer_amount,
+            price: math::checked_as_u64(math::checked_div(
+                math::checked_mul(offer.ask_amount as u128, PRICE_PRECISION as u128)?,
+                offer.offer_amount as u128,
+            )?)?,
+            fee: 0,
+        },
+    )?;
+    UserEpochVolume::record_fill(
+        ctx.accounts.mining_epoch.as_deref_mut(),
+        ctx.accounts.user_volume.as_deref_mut(),
+        offer.maker,
+        Pubkey::default(),
+        [
+            (offer.offer_mint, offer.offer_amount),
+            (offer.ask_mint, offer.ask_amount),
+        ],
+        0,
+    )?;
+
+    Ok(())
+}
+
+// Either party may unwind the offer once it has expired; funds always return to the maker
+pub fn cancel_otc_offer(ctx: Context<CancelOtcOffer>) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    require!(
+        offer.is_expired(Clock::get()?.unix_timestamp),
+        TradingBotError::OtcOfferNotExpired
+    );
+    let signer = ctx.accounts.signer.key();
+    require!(
+        signer == offer.maker || offer.taker == Some(signer),
+        TradingBotError::UnauthorizedOtcParty
+    );
+
+    let idx_bytes = ctx.accounts.offer.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[otc_offer_seeds!(ctx.accounts.offer, idx_bytes)];
+
+    if ctx.accounts.vault.amount > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.maker_offer_account.to_account_info(),
+                    authority: ctx.accounts.offer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            ctx.accounts.vault.amount,
+        )?;
+    }
+
+    anchor_spl::token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        anchor_spl::token::CloseAccount {
+            account: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.maker.to_account_info(),
+            authority: ctx.accounts.offer.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use crate::constants::{MARKET_MAKER_SEED, OUTBOX_SEED};
+use crate::state::{MarketMaker, Notification, Outbox, OUTBOX_CAPACITY};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitOutbox<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = Outbox::LEN,
+      seeds = [OUTBOX_SEED, market_maker.key().as_ref()],
+      bump
+    )]
+    outbox: Box<Account<'info, Outbox>>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn init_outbox(ctx: Context<InitOutbox>) -> Result<()> {
+    let outbox = &mut ctx.accounts.outbox;
+    outbox.strategy = ctx.accounts.market_maker.key();
+    outbox.owner = ctx.accounts.owner.key();
+    outbox.next_seq = 0;
+    outbox.head = 0;
+    outbox.records = [Notification::default(); OUTBOX_CAPACITY];
+    outbox.bump = *ctx.bumps.get("outbox").unwrap();
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::constants::{SESSION_SEED, STRATEGY_SEED};
+use crate::{
+    errors::TradingBotError,
+    state::{Session, Strategy},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetStrategyActive<'info> {
+    // the owner, or the key of one of its live sessions
+    authority: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      has_one=strategy,
+      seeds = [SESSION_SEED, strategy.key().as_ref(), authority.key().as_ref()],
+      bump = session.bump,
+    )]
+    session: Option<Box<Account<'info, Session>>>,
+}
+
+// The owner can pause and resume; a live session key can only pause, so a bot holding one can stop
+// a strategy in an emergency but never restart it
+pub fn set_strategy_active(ctx: Context<SetStrategyActive>, active: bool) -> Result<()> {
+    let strategy = &ctx.accounts.strategy;
+    if ctx.accounts.authority.key() != strategy.owner {
+        let session = ctx
+            .accounts
+            .session
+            .as_ref()
+            .ok_or(TradingBotError::UnauthorizedExecutor)?;
+        require!(
+            !active && session.owner == strategy.owner && session.is_live(strategy.clock_now()?),
+            TradingBotError::UnauthorizedExecutor
+        );
+    }
+
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.is_active = active;
+    strategy.updated_at = Clock::get()?.unix_timestamp;
+    msg!(
+        "Strategy {} {}",
+        strategy.strategy_id,
+        if active { "resumed" } else { "paused" }
+    );
+
+    Ok(())
+}
@@ -0,0 +1,458 @@
+use crate::constants::{FEED_REGISTRY_SEED, PEG_ARB_SEED, TRADING_VAULT_SEED, VAULT_LOG_SEED};
+use crate::dex::{jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID};
+use crate::events::{PegArbEntered, PegArbExited, PegArbUnstaked};
+use crate::oracles::stake_pool::ExchangeRate;
+use crate::utils::validate_deadline;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{FeedRegistry, PegArb, PegArbStatus, TradingVault, VaultLog, VaultOp},
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::{spl_token::native_mint, SyncNative, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitPegArb<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      constraint=sol_vault.mint==native_mint::ID @ TradingBotError::InvalidPegArbParams,
+      seeds = [TRADING_VAULT_SEED, sol_vault.creator.as_ref(), sol_vault.mint.as_ref()],
+      bump = sol_vault.bump,
+    )]
+    sol_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, lst_vault.creator.as_ref(), lst_vault.mint.as_ref()],
+      bump = lst_vault.bump,
+    )]
+    lst_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = PegArb::LEN,
+      seeds = [PEG_ARB_SEED, lst_vault.key().as_ref()],
+      bump
+    )]
+    peg_arb: Box<Account<'info, PegArb>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RunPegArb<'info> {
+    // any keeper for swaps; unstaking and claiming need the owner
+    authority: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [PEG_ARB_SEED, peg_arb.lst_vault.as_ref()],
+      bump = peg_arb.bump,
+    )]
+    peg_arb: Box<Account<'info, PegArb>>,
+
+    #[account(
+      address=peg_arb.sol_vault
+    )]
+    sol_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=peg_arb.lst_vault
+    )]
+    lst_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=sol_vault.token_account
+    )]
+    sol_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      address=lst_vault.token_account
+    )]
+    lst_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, sol_vault.key().as_ref()],
+      bump,
+    )]
+    sol_vault_log: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, lst_vault.key().as_ref()],
+      bump,
+    )]
+    lst_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Checked against the LST's registry entry when read
+    stake_pool: UncheckedAccount<'info>,
+
+    /// CHECK: A supported swap program for swaps, the LST's stake pool program for unstaking
+    target_program: UncheckedAccount<'info>,
+
+    token_program: Program<'info, Token>,
+}
+
+impl<'info> RunPegArb<'info> {
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    // The LST's redemption rate from the stake pool the registry holds for it
+    pub fn rate(&self) -> Result<ExchangeRate> {
+        let source = self.feed_registry.stake_pool(&self.lst_vault.mint)?;
+        ExchangeRate::read(&source, &self.stake_pool, &self.lst_vault.mint)
+    }
+
+    pub fn require_stake_pool_program(&self) -> Result<()> {
+        let source = self.feed_registry.stake_pool(&self.lst_vault.mint)?;
+        require_keys_eq!(
+            self.target_program.key(),
+            source.program.program_id(),
+            TradingBotError::InvalidStakePool
+        );
+        Ok(())
+    }
+
+    // The unstake's stake account or ticket is named in its data, which can't be checked here
+    pub fn require_owner(&self) -> Result<()> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.peg_arb.owner,
+            TradingBotError::UnauthorizedExecutor
+        );
+        Ok(())
+    }
+
+    // Either vault may have to sign, so both PDAs are flagged as signers in the forwarded metas
+    pub fn invoke(&self, accounts: &[AccountInfo<'info>], data: Vec<u8>) -> Result<()> {
+        let vault_keys = [self.sol_vault.key(), self.lst_vault.key()];
+        let ix = Instruction {
+            program_id: self.target_program.key(),
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    let is_signer = a.is_signer || vault_keys.contains(&a.key());
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        invoke_signed(
+            &ix,
+            accounts,
+            &[
+                trading_vault_seeds!(self.sol_vault),
+                trading_vault_seeds!(self.lst_vault),
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Unstaked SOL lands as lamports, which only count once the wrapped SOL account is synced
+    pub fn sync_sol(&self) -> Result<()> {
+        anchor_spl::token::sync_native(CpiContext::new(
+            self.token_program.to_account_info(),
+            SyncNative {
+                account: self.sol_token_account.to_account_info(),
+            },
+        ))
+    }
+
+    // Balances after the CPI; reserved funds in either vault must stay put
+    pub fn reload_balances(&mut self) -> Result<(u64, u64)> {
+        self.sol_token_account.reload()?;
+        self.lst_token_account.reload()?;
+        let (sol, lst) = (self.sol_token_account.amount, self.lst_token_account.amount);
+        self.sol_vault.check_invariant(sol)?;
+        self.lst_vault.check_invariant(lst)?;
+        Ok((sol, lst))
+    }
+
+    // Logs what the round moved in each vault, once the balances have been reloaded
+    pub fn log(&self, sol_before: u64, lst_before: u64) -> Result<()> {
+        let key = self.peg_arb.key();
+        let sol = self.sol_token_account.amount;
+        let lst = self.lst_token_account.amount;
+        VaultLog::append_if_initialized(
+            &self.sol_vault_log,
+            VaultOp::Preset,
+            key,
+            sol_before.abs_diff(sol),
+            &self.sol_vault,
+            Some(sol),
+        )?;
+        VaultLog::append_if_initialized(
+            &self.lst_vault_log,
+            VaultOp::Preset,
+            key,
+            lst_before.abs_diff(lst),
+            &self.lst_vault,
+            Some(lst),
+        )
+    }
+}
+
+pub fn init_peg_arb(
+    ctx: Context<InitPegArb>,
+    entry_discount_bps: u16,
+    exit_discount_bps: u16,
+) -> Result<()> {
+    require!(
+        entry_discount_bps > 0 && entry_discount_bps < 10000,
+        TradingBotError::InvalidPegArbParams
+    );
+    require_gt!(
+        entry_discount_bps,
+        exit_discount_bps,
+        TradingBotError::InvalidPegArbParams
+    );
+    ctx.accounts
+        .feed_registry
+        .stake_pool(&ctx.accounts.lst_vault.mint)?;
+
+    let peg_arb = &mut ctx.accounts.peg_arb;
+    peg_arb.owner = ctx.accounts.owner.key();
+    peg_arb.sol_vault = ctx.accounts.sol_vault.key();
+    peg_arb.lst_vault = ctx.accounts.lst_vault.key();
+    peg_arb.entry_discount_bps = entry_discount_bps;
+    peg_arb.exit_discount_bps = exit_discount_bps;
+    peg_arb.status = PegArbStatus::Idle;
+    peg_arb.cost = 0;
+    peg_arb.position = 0;
+    peg_arb.unbonding_lamports = 0;
+    peg_arb.claimable_epoch = 0;
+    peg_arb.realized_pnl = 0;
+    peg_arb.round_count = 0;
+    peg_arb.bump = *ctx.bumps.get("peg_arb").unwrap();
+
+    Ok(())
+}
+
+// Keepers buy the LST with up to `amount_in` of the SOL vault's unreserved balance. The swap is
+// forwarded from the remaining accounts and has to buy at least `entry_discount_bps` below the
+// stake pool's redemption rate, which is what makes it a discount rather than a quote.
+pub fn enter_peg_arb<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunPegArb<'info>>,
+    amount_in: u64,
+    swap_data: Vec<u8>,
+    deadline: i64,
+) -> Result<()> {
+    validate_deadline(deadline)?;
+    let accounts = &ctx.accounts;
+    require!(
+        accounts.peg_arb.status == PegArbStatus::Idle,
+        TradingBotError::PegArbNotReady
+    );
+    require!(
+        RunPegArb::is_supported_swap_program(accounts.target_program.key),
+        TradingBotError::InvalidDexType
+    );
+    require_gte!(
+        accounts
+            .sol_vault
+            .available(accounts.sol_token_account.amount),
+        amount_in,
+        TradingBotError::InsufficientBalance
+    );
+    let rate = accounts.rate()?;
+
+    let sol_before = accounts.sol_token_account.amount;
+    let lst_before = accounts.lst_token_account.amount;
+    ctx.accounts.invoke(ctx.remaining_accounts, swap_data)?;
+    let (sol_after, lst_after) = ctx.accounts.reload_balances()?;
+    ctx.accounts.log(sol_before, lst_before)?;
+    let paid = math::checked_sub(sol_before, sol_after)?;
+    let bought = math::checked_sub(lst_after, lst_before)?;
+    require_gte!(amount_in, paid, TradingBotError::InvalidCalculation);
+
+    let discount_bps = PegArb::discount_bps(paid, rate.apply(bought)?)?;
+    require_gte!(
+        discount_bps,
+        ctx.accounts.peg_arb.entry_discount_bps as u64,
+        TradingBotError::DiscountTooSmall
+    );
+
+    let peg_arb = &mut ctx.accounts.peg_arb;
+    peg_arb.open(paid, bought);
+    emit!(PegArbEntered {
+        peg_arb: peg_arb.key(),
+        paid,
+        bought,
+        discount_bps,
+        deadline,
+    });
+
+    Ok(())
+}
+
+// Keepers sell the whole position back into SOL once the discount has closed to
+// `exit_discount_bps`, checked on what the swap actually returned
+pub fn exit_peg_arb<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunPegArb<'info>>,
+    swap_data: Vec<u8>,
+    deadline: i64,
+) -> Result<()> {
+    validate_deadline(deadline)?;
+    let accounts = &ctx.accounts;
+    require!(
+        accounts.peg_arb.status == PegArbStatus::Holding,
+        TradingBotError::PegArbNotReady
+    );
+    require!(
+        RunPegArb::is_supported_swap_program(accounts.target_program.key),
+        TradingBotError::InvalidDexType
+    );
+    let rate = accounts.rate()?;
+
+    let sol_before = accounts.sol_token_account.amount;
+    let lst_before = accounts.lst_token_account.amount;
+    ctx.accounts.invoke(ctx.remaining_accounts, swap_data)?;
+    let (sol_after, lst_after) = ctx.accounts.reload_balances()?;
+    ctx.accounts.log(sol_before, lst_before)?;
+    let sold = math::checked_sub(lst_before, lst_after)?;
+    let received = math::checked_sub(sol_after, sol_before)?;
+    require_eq!(
+        sold,
+        ctx.accounts.peg_arb.position,
+        TradingBotError::InvalidCalculation
+    );
+
+    let discount_bps = PegArb::discount_bps(received, rate.apply(sold)?)?;
+    require_gte!(
+        ctx.accounts.peg_arb.exit_discount_bps as u64,
+        discount_bps,
+        TradingBotError::DiscountNotClosed
+    );
+
+    let peg_arb = &mut ctx.accounts.peg_arb;
+    let pnl = peg_arb.settle(received)?;
+    emit!(PegArbExited {
+        peg_arb: peg_arb.key(),
+        received,
+        pnl,
+        unstaked: false,
+    });
+
+    Ok(())
+}
+
+// The owner redeems the position at the stake pool instead of selling it. An instant withdrawal
+// closes the round at once; a delayed one leaves the SOL counted at the redemption rate but
+// locked until the unbonding period is over and `claim_peg_arb` collects it.
+pub fn unstake_peg_arb<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunPegArb<'info>>,
+    unstake_data: Vec<u8>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    accounts.require_owner()?;
+    accounts.require_stake_pool_program()?;
+    require!(
+        accounts.peg_arb.status == PegArbStatus::Holding,
+        TradingBotError::PegArbNotReady
+    );
+    let rate = accounts.rate()?;
+
+    let sol_before = accounts.sol_token_account.amount;
+    let lst_before = accounts.lst_token_account.amount;
+    ctx.accounts.invoke(ctx.remaining_accounts, unstake_data)?;
+    ctx.accounts.sync_sol()?;
+    let (sol_after, lst_after) = ctx.accounts.reload_balances()?;
+    ctx.accounts.log(sol_before, lst_before)?;
+    let unstaked = math::checked_sub(lst_before, lst_after)?;
+    let received = math::checked_sub(sol_after, sol_before)?;
+    require_eq!(
+        unstaked,
+        ctx.accounts.peg_arb.position,
+        TradingBotError::InvalidCalculation
+    );
+
+    let peg_arb = &mut ctx.accounts.peg_arb;
+    if received > 0 {
+        let pnl = peg_arb.settle(received)?;
+        emit!(PegArbExited {
+            peg_arb: peg_arb.key(),
+            received,
+            pnl,
+            unstaked: true,
+        });
+        return Ok(());
+    }
+
+    peg_arb.start_unbonding(rate.apply(unstaked)?, Clock::get()?.epoch)?;
+    emit!(PegArbUnstaked {
+        peg_arb: peg_arb.key(),
+        unstaked,
+        unbonding_lamports: peg_arb.unbonding_lamports,
+        claimable_epoch: peg_arb.claimable_epoch,
+    });
+
+    Ok(())
+}
+
+// The owner claims a delayed unstake's SOL into the SOL vault once it has unbonded
+pub fn claim_peg_arb<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunPegArb<'info>>,
+    claim_data: Vec<u8>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    accounts.require_owner()?;
+    accounts.require_stake_pool_program()?;
+    require!(
+        accounts.peg_arb.status == PegArbStatus::Unbonding,
+        TradingBotError::PegArbNotReady
+    );
+    require_gte!(
+        Clock::get()?.epoch,
+        accounts.peg_arb.claimable_epoch,
+        TradingBotError::UnbondingNotOver
+    );
+
+    let sol_before = accounts.sol_token_account.amount;
+    let lst_before = accounts.lst_token_account.amount;
+    ctx.accounts.invoke(ctx.remaining_accounts, claim_data)?;
+    ctx.accounts.sync_sol()?;
+    let (sol_after, _) = ctx.accounts.reload_balances()?;
+    ctx.accounts.log(sol_before, lst_before)?;
+    let received = math::checked_sub(sol_after, sol_before)?;
+    require_gt!(received, 0, TradingBotError::UnbondingNotOver);
+
+    let peg_arb = &mut ctx.accounts.peg_arb;
+    let pnl = peg_arb.settle(received)?;
+    emit!(PegArbExited {
+        peg_arb: peg_arb.key(),
+        received,
+        pnl,
+        unstaked: true,
+    });
+
+    Ok(())
+}
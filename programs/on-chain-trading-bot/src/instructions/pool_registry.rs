@@ -0,0 +1,82 @@
+use crate::constants::POOL_REGISTRY_SEED;
+use crate::program::OnChainTradingBot;
+use crate::types::{DexType, TokenPair};
+use crate::{
+    errors::TradingBotError,
+    state::{PoolRegistry, RegisteredPool},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitPoolRegistry<'info> {
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    #[account(
+      init,
+      payer = admin,
+      space = PoolRegistry::LEN,
+      seeds = [POOL_REGISTRY_SEED],
+      bump
+    )]
+    pool_registry: Box<Account<'info, PoolRegistry>>,
+
+    #[account(
+      constraint=program.programdata_address()? == Some(program_data.key())
+    )]
+    program: Program<'info, OnChainTradingBot>,
+
+    // only the upgrade authority can claim the registry
+    #[account(
+      constraint=program_data.upgrade_authority_address == Some(admin.key()) @ TradingBotError::UnauthorizedExecutor
+    )]
+    program_data: Box<Account<'info, ProgramData>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManagePoolRegistry<'info> {
+    admin: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=admin,
+      seeds = [POOL_REGISTRY_SEED],
+      bump = pool_registry.bump,
+    )]
+    pool_registry: Box<Account<'info, PoolRegistry>>,
+}
+
+pub fn init_pool_registry(ctx: Context<InitPoolRegistry>) -> Result<()> {
+    let pool_registry = &mut ctx.accounts.pool_registry;
+    pool_registry.admin = ctx.accounts.admin.key();
+    pool_registry.pools = Vec::new();
+    pool_registry.bump = *ctx.bumps.get("pool_registry").unwrap();
+
+    Ok(())
+}
+
+// Adds the pair's pool on the venue or replaces it. AMM pools need both reserves, order-book
+// markets neither.
+pub fn set_registered_pool(ctx: Context<ManagePoolRegistry>, pool: RegisteredPool) -> Result<()> {
+    pool.validate()?;
+    ctx.accounts.pool_registry.set(pool)?;
+    msg!("Registered pool {} for {:?}", pool.pool, pool.dex);
+
+    Ok(())
+}
+
+pub fn remove_registered_pool(
+    ctx: Context<ManagePoolRegistry>,
+    pair: TokenPair,
+    dex: DexType,
+) -> Result<()> {
+    ctx.accounts.pool_registry.remove(&pair, dex)
+}
+
+pub fn set_pool_registry_admin(ctx: Context<ManagePoolRegistry>, admin: Pubkey) -> Result<()> {
+    ctx.accounts.pool_registry.admin = admin;
+
+    Ok(())
+}
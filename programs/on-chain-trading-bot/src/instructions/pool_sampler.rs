@@ -0,0 +1,97 @@
+use crate::constants::POOL_SAMPLER_SEED;
+use crate::events::PoolSampled;
+use crate::{
+    errors::TradingBotError,
+    state::{PoolSample, PoolSampler, POOL_SAMPLE_LEN},
+    types::{DexType, TokenPair},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitPoolSampler<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    /// CHECK: Only used as the sampler's key
+    pool: UncheckedAccount<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = PoolSampler::LEN,
+      seeds = [POOL_SAMPLER_SEED, owner.key().as_ref(), pool.key().as_ref()],
+      bump
+    )]
+    pool_sampler: Box<Account<'info, PoolSampler>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordPoolSample<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=keeper,
+      seeds = [POOL_SAMPLER_SEED, pool_sampler.owner.as_ref(), pool_sampler.pool.as_ref()],
+      bump = pool_sampler.bump,
+    )]
+    pool_sampler: Box<Account<'info, PoolSampler>>,
+}
+
+pub fn init_pool_sampler(
+    ctx: Context<InitPoolSampler>,
+    keeper: Pubkey,
+    dex: DexType,
+    pair: TokenPair,
+    min_interval: i64,
+) -> Result<()> {
+    require_gt!(min_interval, 0, TradingBotError::InvalidPoolSample);
+
+    let pool_sampler = &mut ctx.accounts.pool_sampler;
+    pool_sampler.owner = ctx.accounts.owner.key();
+    pool_sampler.keeper = keeper;
+    pool_sampler.pool = ctx.accounts.pool.key();
+    pool_sampler.dex = dex;
+    pool_sampler.pair = pair;
+    pool_sampler.min_interval = min_interval;
+    pool_sampler.samples = [PoolSample::default(); POOL_SAMPLE_LEN];
+    pool_sampler.head = 0;
+    pool_sampler.count = 0;
+    pool_sampler.bump = *ctx.bumps.get("pool_sampler").unwrap();
+
+    Ok(())
+}
+
+// The keeper reads the pool's cumulative fees, TVL and mid price off-chain and records them at
+// most once per `min_interval`
+pub fn record_pool_sample(
+    ctx: Context<RecordPoolSample>,
+    fee_growth: u64,
+    tvl: u64,
+    price: u64,
+) -> Result<()> {
+    require_gt!(price, 0, TradingBotError::InvalidPoolSample);
+    let now = Clock::get()?.unix_timestamp;
+    let pool_sampler = &mut ctx.accounts.pool_sampler;
+    pool_sampler.push(PoolSample {
+        timestamp: now,
+        fee_growth,
+        tvl,
+        price,
+        apr_bps: 0,
+    })?;
+    let apr_bps = pool_sampler.realized_apr_bps()?;
+    msg!("Realized fee APR {} bps", apr_bps);
+    emit!(PoolSampled {
+        pool: pool_sampler.pool,
+        dex: pool_sampler.dex,
+        pair: pool_sampler.pair,
+        price,
+        tvl,
+        apr_bps,
+        timestamp: now,
+    });
+    Ok(())
+}
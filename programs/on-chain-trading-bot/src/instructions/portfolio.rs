@@ -0,0 +1,338 @@
+use crate::constants::{
+    FEED_REGISTRY_SEED, MAX_CROSSING_FEE_BPS, PORTFOLIO_SEED, STRATEGY_SEED, TRADING_VAULT_SEED,
+    TREASURY_SEED, VAULT_LOG_SEED,
+};
+use crate::events::{NettingOrderClosed, OrderNetted};
+use crate::oracles::PythOracle;
+use crate::types::{TokenPair, TradeSide};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        FeedRegistry, NettingOrder, Portfolio, PortfolioMember, Strategy, TradingVault, VaultLog,
+        VaultOp, MAX_PORTFOLIO_STRATEGIES,
+    },
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct InitPortfolio<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = Portfolio::LEN,
+      seeds = [PORTFOLIO_SEED, owner.key().as_ref()],
+      bump
+    )]
+    portfolio: Box<Account<'info, Portfolio>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManagePortfolioStrategy<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [PORTFOLIO_SEED, portfolio.owner.as_ref()],
+      bump = portfolio.bump,
+    )]
+    portfolio: Box<Account<'info, Portfolio>>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+}
+
+#[derive(Accounts)]
+pub struct NetPortfolioOrders<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [PORTFOLIO_SEED, portfolio.owner.as_ref()],
+      bump = portfolio.bump,
+    )]
+    portfolio: Box<Account<'info, Portfolio>>,
+
+    /// CHECK: Resolved through the feed registry for the base mint when read; quote per base in
+    /// PRICE_PRECISION
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, base_vault.creator.as_ref(), base_vault.mint.as_ref()],
+      bump = base_vault.bump,
+    )]
+    base_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=base_vault.token_account
+    )]
+    base_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, quote_vault.creator.as_ref(), quote_vault.mint.as_ref()],
+      bump = quote_vault.bump,
+    )]
+    quote_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=quote_vault.token_account
+    )]
+    quote_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, base_vault.key().as_ref()],
+      bump,
+    )]
+    base_vault_log: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, quote_vault.key().as_ref()],
+      bump,
+    )]
+    quote_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      address=base_vault.mint
+    )]
+    base_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=quote_vault.mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Protocol treasury PDA, only used as a token authority
+    #[account(
+      seeds = [TREASURY_SEED],
+      bump
+    )]
+    treasury: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=owner,
+      associated_token::authority=treasury,
+      associated_token::mint=base_mint,
+    )]
+    treasury_base_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=owner,
+      associated_token::authority=treasury,
+      associated_token::mint=quote_mint,
+    )]
+    treasury_quote_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> NetPortfolioOrders<'info> {
+    // Crossing fees come out of the owner's vault, never out of what open orders reserved
+    fn pay_fee(
+        &self,
+        vault: &Account<'info, TradingVault>,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        fee: u64,
+    ) -> Result<()> {
+        if fee == 0 {
+            return Ok(());
+        }
+        require_gte!(
+            vault.available(from.amount),
+            fee,
+            TradingBotError::InsufficientAvailableBalance
+        );
+        let signer_seeds: &[&[&[u8]]] = &[trading_vault_seeds!(vault)];
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            fee,
+        )
+    }
+}
+
+pub fn init_portfolio(ctx: Context<InitPortfolio>, crossing_fee_bps: u16) -> Result<()> {
+    require!(
+        crossing_fee_bps <= MAX_CROSSING_FEE_BPS,
+        TradingBotError::InvalidNettingOrder
+    );
+
+    let portfolio = &mut ctx.accounts.portfolio;
+    portfolio.owner = ctx.accounts.owner.key();
+    portfolio.crossing_fee_bps = crossing_fee_bps;
+    portfolio.members = [PortfolioMember::default(); MAX_PORTFOLIO_STRATEGIES];
+    portfolio.member_count = 0;
+    portfolio.crossed_volume = 0;
+    portfolio.bump = *ctx.bumps.get("portfolio").unwrap();
+
+    Ok(())
+}
+
+pub fn add_portfolio_strategy(ctx: Context<ManagePortfolioStrategy>) -> Result<()> {
+    let strategy = ctx.accounts.strategy.key();
+    ctx.accounts.portfolio.add_strategy(strategy)
+}
+
+pub fn remove_portfolio_strategy(ctx: Context<ManagePortfolioStrategy>) -> Result<()> {
+    let strategy = ctx.accounts.strategy.key();
+    ctx.accounts.portfolio.remove_strategy(&strategy)
+}
+
+// `amount` is in the side's input mint, on the strategy's configured pair. A member has at most
+// one order open; close it to take the unfilled rest to a DEX.
+pub fn post_netting_order(
+    ctx: Context<ManagePortfolioStrategy>,
+    side: TradeSide,
+    amount: u64,
+) -> Result<()> {
+    require_gt!(amount, 0, TradingBotError::InvalidNettingOrder);
+    let now = Clock::get()?.unix_timestamp;
+    let pair = ctx.accounts.strategy.config.pair;
+    let member = ctx
+        .accounts
+        .portfolio
+        .member(&ctx.accounts.strategy.key())?;
+    require!(member.order.is_none(), TradingBotError::InvalidNettingOrder);
+
+    member.order = Some(NettingOrder {
+        pair,
+        side,
+        remaining: amount,
+        filled_in: 0,
+        filled_out: 0,
+        posted_at: now,
+    });
+
+    Ok(())
+}
+
+pub fn close_netting_order(ctx: Context<ManagePortfolioStrategy>) -> Result<()> {
+    let strategy = ctx.accounts.strategy.key();
+    let portfolio = &mut ctx.accounts.portfolio;
+    let member = portfolio.member(&strategy)?;
+    let order = member
+        .order
+        .take()
+        .ok_or(TradingBotError::InvalidNettingOrder)?;
+
+    emit!(NettingOrderClosed {
+        portfolio: portfolio.key(),
+        strategy,
+        side: order.side,
+        residual: order.remaining,
+        filled_in: order.filled_in,
+        filled_out: order.filled_out,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Crosses the members' opposing orders on the vaults' pair at the oracle mid. Buyers pay the fee
+// in base and sellers in quote, each out of what they receive.
+pub fn net_portfolio_orders(ctx: Context<NetPortfolioOrders>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let accounts = &ctx.accounts;
+    let pair = TokenPair::new(accounts.base_vault.mint, accounts.quote_vault.mint);
+    let mid =
+        PythOracle::get_pair_price(&accounts.feed_registry, &pair, &accounts.price_feed, u64::MAX)?;
+
+    let portfolio = &mut ctx.accounts.portfolio;
+    let fills = portfolio.net(&pair, mid)?;
+    let portfolio_key = portfolio.key();
+    let (mut base_fee, mut quote_fee) = (0u64, 0u64);
+    for fill in fills.iter() {
+        if fill.side.is_buy() {
+            base_fee = math::checked_add(base_fee, fill.fee)?;
+        } else {
+            quote_fee = math::checked_add(quote_fee, fill.fee)?;
+        }
+        emit!(OrderNetted {
+            portfolio: portfolio_key,
+            strategy: fill.strategy,
+            side: fill.side,
+            amount_in: fill.amount_in,
+            amount_out: fill.amount_out,
+            fee: fill.fee,
+            mid,
+            timestamp: now,
+        });
+    }
+    msg!("Netted {} orders at {}", fills.len(), mid);
+
+    let accounts = &ctx.accounts;
+    accounts.pay_fee(
+        &accounts.base_vault,
+        &accounts.base_vault_token_account,
+        &accounts.treasury_base_account,
+        base_fee,
+    )?;
+    accounts.pay_fee(
+        &accounts.quote_vault,
+        &accounts.quote_vault_token_account,
+        &accounts.treasury_quote_account,
+        quote_fee,
+    )?;
+
+    ctx.accounts.base_vault_token_account.reload()?;
+    ctx.accounts.quote_vault_token_account.reload()?;
+    let accounts = &ctx.accounts;
+    VaultLog::append_if_initialized(
+        &accounts.base_vault_log,
+        VaultOp::Cross,
+        portfolio_key,
+        base_fee,
+        &accounts.base_vault,
+        Some(accounts.base_vault_token_account.amount),
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.quote_vault_log,
+        VaultOp::Cross,
+        portfolio_key,
+        quote_fee,
+        &accounts.quote_vault,
+        Some(accounts.quote_vault_token_account.amount),
+    )
+}
@@ -0,0 +1,96 @@
+use crate::constants::{POSITION_STOP_SEED, STRATEGY_SEED};
+use crate::types::TradeSide;
+use crate::{
+    errors::TradingBotError,
+    state::{PositionStop, Strategy},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(position_id: Pubkey)]
+pub struct SetPositionStop<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      init_if_needed,
+      payer = owner,
+      space = PositionStop::LEN,
+      seeds = [POSITION_STOP_SEED, strategy.key().as_ref(), position_id.as_ref()],
+      bump
+    )]
+    position_stop: Box<Account<'info, PositionStop>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePositionStop<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      close=owner,
+      has_one=strategy,
+      seeds = [POSITION_STOP_SEED, strategy.key().as_ref(), position_stop.position_id.as_ref()],
+      bump = position_stop.bump,
+    )]
+    position_stop: Box<Account<'info, PositionStop>>,
+}
+
+// Sets or replaces the stop on a position, re-arming the breakeven rule. A long's stop sits
+// below the entry and a short's above it; zero leaves the position without one until a trailing
+// or breakeven move sets it.
+pub fn set_position_stop(
+    ctx: Context<SetPositionStop>,
+    position_id: Pubkey,
+    side: TradeSide,
+    entry_price: u64,
+    stop_price: u64,
+    trailing_bps: u16,
+) -> Result<()> {
+    require_gt!(entry_price, 0, TradingBotError::InvalidPositionStop);
+    require_gt!(10000, trailing_bps, TradingBotError::InvalidPositionStop);
+    require!(
+        stop_price == 0
+            || if side.is_buy() {
+                stop_price < entry_price
+            } else {
+                stop_price > entry_price
+            },
+        TradingBotError::InvalidPositionStop
+    );
+
+    let position_stop = &mut ctx.accounts.position_stop;
+    position_stop.strategy = ctx.accounts.strategy.key();
+    position_stop.position_id = position_id;
+    position_stop.side = side;
+    position_stop.entry_price = entry_price;
+    position_stop.stop_price = stop_price;
+    position_stop.trailing_bps = trailing_bps;
+    position_stop.best_price = entry_price;
+    position_stop.at_breakeven = false;
+    position_stop.updated_at = Clock::get()?.unix_timestamp;
+    position_stop.bump = *ctx.bumps.get("position_stop").unwrap();
+
+    Ok(())
+}
+
+pub fn close_position_stop(_ctx: Context<ClosePositionStop>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,530 @@
+use crate::constants::{
+    FEED_REGISTRY_SEED, PRINCIPAL_NOTE_SEED, TRADING_VAULT_SEED, VAULT_LOG_SEED,
+};
+use crate::dex::psyoptions::OptionMarket;
+use crate::dex::{jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID};
+use crate::events::{PrincipalNoteClosed, PrincipalNoteOpened, PrincipalNoteValued};
+use crate::oracles::lending_reserve::{
+    CollateralRate, LendingMetric, ReserveRates, SOLEND_PROGRAM_ID,
+};
+use crate::oracles::PythOracle;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{FeedRegistry, NoteStatus, PrincipalNote, TradingVault, VaultLog, VaultOp},
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitPrincipalNote<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    /// CHECK: A Solend reserve for the vault's asset, parsed on init
+    #[account(
+      owner=SOLEND_PROGRAM_ID
+    )]
+    lending_reserve: UncheckedAccount<'info>,
+
+    // holds the reserve's cTokens; its mint is checked against the reserve
+    #[account(
+      token::authority=trading_vault
+    )]
+    collateral_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = PrincipalNote::LEN,
+      seeds = [PRINCIPAL_NOTE_SEED, trading_vault.key().as_ref()],
+      bump
+    )]
+    principal_note: Box<Account<'info, PrincipalNote>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RunPrincipalNote<'info> {
+    // the owner opens notes and can close them early; keepers can close them at maturity
+    authority: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [PRINCIPAL_NOTE_SEED, principal_note.trading_vault.as_ref()],
+      bump = principal_note.bump,
+    )]
+    principal_note: Box<Account<'info, PrincipalNote>>,
+
+    #[account(
+      address=principal_note.trading_vault
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=trading_vault.token_account
+    )]
+    vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      mut,
+      address=principal_note.collateral_account
+    )]
+    collateral_account: Box<Account<'info, TokenAccount>>,
+
+    // holds the calls; checked against the market when opening and against the note after
+    #[account(
+      mut,
+      token::authority=trading_vault
+    )]
+    option_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: A PsyOptions market, parsed when opening and checked against the note after
+    option_market: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the note, parsed for its rates
+    #[account(
+      address=principal_note.lending_reserve
+    )]
+    lending_reserve: UncheckedAccount<'info>,
+
+    /// CHECK: Solend, which takes the deposit and redeems it
+    #[account(
+      address=SOLEND_PROGRAM_ID
+    )]
+    lending_program: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the supported swap programs when the calls are traded
+    swap_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ValuePrincipalNote<'info> {
+    #[account(
+      mut,
+      seeds = [PRINCIPAL_NOTE_SEED, principal_note.trading_vault.as_ref()],
+      bump = principal_note.bump,
+    )]
+    principal_note: Box<Account<'info, PrincipalNote>>,
+
+    #[account(
+      address=principal_note.trading_vault
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=principal_note.collateral_account
+    )]
+    collateral_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      address=principal_note.option_account
+    )]
+    option_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Checked against the note
+    #[account(
+      address=principal_note.lending_reserve
+    )]
+    lending_reserve: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the note
+    #[account(
+      address=principal_note.option_market
+    )]
+    option_market: UncheckedAccount<'info>,
+
+    // checked against the market's underlying
+    underlying_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=trading_vault.mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Resolved through the feed registry on read
+    underlying_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Resolved through the feed registry on read
+    quote_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+impl<'info> RunPrincipalNote<'info> {
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    pub fn is_owner(&self) -> bool {
+        self.authority.key() == self.principal_note.owner
+    }
+
+    // Forwards to Solend or, with `swap`, to the swap program, with the vault's PDA signing
+    pub fn invoke(&self, accounts: &[AccountInfo<'info>], data: Vec<u8>, swap: bool) -> Result<()> {
+        let program_id = if swap {
+            require!(
+                Self::is_supported_swap_program(self.swap_program.key),
+                TradingBotError::InvalidDexType
+            );
+            self.swap_program.key()
+        } else {
+            self.lending_program.key()
+        };
+        let vault_key = self.trading_vault.key();
+        let ix = Instruction {
+            program_id,
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    let is_signer = a.is_signer || a.key() == vault_key;
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        invoke_signed(&ix, accounts, &[trading_vault_seeds!(self.trading_vault)])?;
+        Ok(())
+    }
+
+    pub fn reload_balances(&mut self) -> Result<(u64, u64, u64)> {
+        self.vault_token_account.reload()?;
+        self.collateral_account.reload()?;
+        self.option_account.reload()?;
+        self.trading_vault
+            .check_invariant(self.vault_token_account.amount)?;
+        Ok((
+            self.vault_token_account.amount,
+            self.collateral_account.amount,
+            self.option_account.amount,
+        ))
+    }
+}
+
+impl<'info> ValuePrincipalNote<'info> {
+    pub fn usd_value(&self, amount: u64, mint: &Account<Mint>, feed: &AccountInfo) -> Result<u64> {
+        let usd_price =
+            PythOracle::get_usd_price(&self.feed_registry, &mint.key(), feed, u64::MAX)?;
+        PythOracle::usd_value(amount, mint.decimals, usd_price)
+    }
+
+    // The calls' intrinsic value in the vault's asset: for each contract, what its underlying is
+    // worth over the strike. Time value isn't counted, so this is what exercising now would
+    // return, a floor on what the calls would sell for.
+    pub fn upside_value(&self, market: &OptionMarket, contracts: u64) -> Result<u64> {
+        if contracts == 0 || Clock::get()?.unix_timestamp >= market.expiration {
+            return Ok(0);
+        }
+        let underlying_value = self.usd_value(
+            market.underlying_amount_per_contract,
+            &self.underlying_mint,
+            &self.underlying_price_feed,
+        )?;
+        let strike_value = self.usd_value(
+            market.quote_amount_per_contract,
+            &self.quote_mint,
+            &self.quote_price_feed,
+        )?;
+        if underlying_value <= strike_value || strike_value == 0 {
+            return Ok(0);
+        }
+        let per_contract = math::checked_div(
+            math::checked_mul(
+                market.quote_amount_per_contract as u128,
+                (underlying_value - strike_value) as u128,
+            )?,
+            strike_value as u128,
+        )?;
+        math::checked_as_u64(math::checked_mul(per_contract, contracts as u128)?)
+    }
+}
+
+pub fn init_principal_note(ctx: Context<InitPrincipalNote>, floor_bps: u16) -> Result<()> {
+    require!(
+        floor_bps > 0 && floor_bps <= 10000,
+        TradingBotError::InvalidNoteParams
+    );
+    let rate = CollateralRate::read(&ctx.accounts.lending_reserve)?;
+    require!(
+        rate.liquidity_mint == ctx.accounts.trading_vault.mint
+            && rate.collateral_mint == ctx.accounts.collateral_account.mint,
+        TradingBotError::InvalidLendingReserve
+    );
+
+    let principal_note = &mut ctx.accounts.principal_note;
+    principal_note.owner = ctx.accounts.owner.key();
+    principal_note.trading_vault = ctx.accounts.trading_vault.key();
+    principal_note.lending_reserve = ctx.accounts.lending_reserve.key();
+    principal_note.collateral_account = ctx.accounts.collateral_account.key();
+    principal_note.floor_bps = floor_bps;
+    principal_note.status = NoteStatus::Idle;
+    principal_note.principal = 0;
+    principal_note.maturity = 0;
+    principal_note.option_market = Pubkey::default();
+    principal_note.option_account = Pubkey::default();
+    principal_note.collateral = 0;
+    principal_note.options = 0;
+    principal_note.principal_leg = Default::default();
+    principal_note.upside_leg = Default::default();
+    principal_note.valued_at = 0;
+    principal_note.notes = 0;
+    principal_note.bump = *ctx.bumps.get("principal_note").unwrap();
+
+    Ok(())
+}
+
+// The owner opens a note on `principal` of the vault's balance: the first `deposit_accounts_len`
+// remaining accounts deposit into the reserve, and the deposit has to be projected at the current
+// supply APR to return `floor_bps` of the principal by `maturity`. The rest buy calls on a
+// PsyOptions market quoted in the vault's asset and expiring by maturity, paid for out of what
+// wasn't deposited.
+pub fn open_principal_note<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunPrincipalNote<'info>>,
+    principal: u64,
+    maturity: i64,
+    deposit_accounts_len: u8,
+    deposit_data: Vec<u8>,
+    buy_data: Vec<u8>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    require!(accounts.is_owner(), TradingBotError::UnauthorizedExecutor);
+    require!(
+        accounts.principal_note.status == NoteStatus::Idle,
+        TradingBotError::NoteNotReady
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require_gt!(maturity, now, TradingBotError::InvalidNoteParams);
+    let market = OptionMarket::read(&accounts.option_market)?;
+    require!(
+        market.quote_mint == accounts.trading_vault.mint
+            && accounts.option_account.mint == market.option_mint
+            && market.expiration > now
+            && market.expiration <= maturity,
+        TradingBotError::InvalidOptionMarket
+    );
+    require_gte!(
+        accounts
+            .trading_vault
+            .available(accounts.vault_token_account.amount),
+        principal,
+        TradingBotError::InsufficientAvailableBalance
+    );
+
+    let deposit_accounts_len = deposit_accounts_len as usize;
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        deposit_accounts_len,
+        TradingBotError::InvalidCalculation
+    );
+    let (deposit_accounts, buy_accounts) = ctx.remaining_accounts.split_at(deposit_accounts_len);
+
+    let balance_before = accounts.vault_token_account.amount;
+    let collateral_before = accounts.collateral_account.amount;
+    let options_before = accounts.option_account.amount;
+    accounts.invoke(deposit_accounts, deposit_data, false)?;
+    let (balance_deposited, collateral_after, _) = ctx.accounts.reload_balances()?;
+    let deposit = math::checked_sub(balance_before, balance_deposited)?;
+    let collateral = math::checked_sub(collateral_after, collateral_before)?;
+    require!(
+        deposit > 0 && deposit <= principal && collateral > 0,
+        TradingBotError::InvalidCalculation
+    );
+    let supply_apr_bps =
+        ReserveRates::read(&ctx.accounts.lending_reserve)?.metric(LendingMetric::SupplyApr)?;
+    ctx.accounts.principal_note.check_protected(
+        principal,
+        deposit,
+        supply_apr_bps,
+        maturity - now,
+    )?;
+
+    ctx.accounts.invoke(buy_accounts, buy_data, true)?;
+    let (balance_after, _, options_after) = ctx.accounts.reload_balances()?;
+    let premium = math::checked_sub(balance_deposited, balance_after)?;
+    require_gte!(
+        principal - deposit,
+        premium,
+        TradingBotError::UpsideBudgetExceeded
+    );
+    let options = math::checked_sub(options_after, options_before)?;
+    require_gt!(options, 0, TradingBotError::InvalidCalculation);
+
+    let option_market = ctx.accounts.option_market.key();
+    let option_account = ctx.accounts.option_account.key();
+    let principal_note = &mut ctx.accounts.principal_note;
+    principal_note.principal = principal;
+    principal_note.maturity = maturity;
+    principal_note.option_market = option_market;
+    principal_note.option_account = option_account;
+    principal_note.open(deposit, collateral, premium, options, now);
+    emit!(PrincipalNoteOpened {
+        principal_note: principal_note.key(),
+        principal,
+        deposit,
+        premium,
+        options,
+        maturity,
+    });
+
+    let accounts = &ctx.accounts;
+    VaultLog::append_if_initialized(
+        &accounts.vault_log,
+        VaultOp::Preset,
+        accounts.principal_note.key(),
+        math::checked_add(deposit, premium)?,
+        &accounts.trading_vault,
+        Some(balance_after),
+    )
+}
+
+// Marks both legs to market: the cTokens at the reserve's redemption rate and the calls at their
+// intrinsic value. Anyone can call it.
+pub fn value_principal_note(ctx: Context<ValuePrincipalNote>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    require!(
+        accounts.principal_note.status == NoteStatus::Active,
+        TradingBotError::NoteNotReady
+    );
+    let principal_value = CollateralRate::read(&accounts.lending_reserve)?
+        .liquidity_value(accounts.collateral_account.amount)?;
+    let market = OptionMarket::read(&accounts.option_market)?;
+    require_keys_eq!(
+        accounts.underlying_mint.key(),
+        market.underlying_mint,
+        TradingBotError::InvalidOptionMarket
+    );
+    let contracts = accounts
+        .option_account
+        .amount
+        .min(accounts.principal_note.options);
+    let upside_value = accounts.upside_value(&market, contracts)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let principal_note = &mut ctx.accounts.principal_note;
+    principal_note.principal_leg.value = principal_value;
+    principal_note.upside_leg.value = upside_value;
+    principal_note.valued_at = timestamp;
+    emit!(PrincipalNoteValued {
+        principal_note: principal_note.key(),
+        principal_value,
+        upside_value,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+// Redeems the note's cTokens with the first `redeem_accounts_len` remaining accounts and, given
+// `sell_data`, sells its calls with the rest. Keepers can close a note once it has matured, when
+// its calls have expired and only the redemption is left; before that only the owner can, and
+// calls the owner doesn't sell stay in the option account and are booked at nothing.
+pub fn close_principal_note<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunPrincipalNote<'info>>,
+    redeem_accounts_len: u8,
+    redeem_data: Vec<u8>,
+    sell_data: Vec<u8>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let principal_note = &accounts.principal_note;
+    require!(
+        principal_note.status == NoteStatus::Active,
+        TradingBotError::NoteNotReady
+    );
+    require!(
+        accounts.option_account.key() == principal_note.option_account
+            && accounts.option_market.key() == principal_note.option_market,
+        TradingBotError::InvalidOptionMarket
+    );
+    let early = Clock::get()?.unix_timestamp < principal_note.maturity;
+    if !accounts.is_owner() {
+        require!(!early, TradingBotError::NoteNotReady);
+        require!(sell_data.is_empty(), TradingBotError::UnauthorizedExecutor);
+    }
+
+    let redeem_accounts_len = redeem_accounts_len as usize;
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        redeem_accounts_len,
+        TradingBotError::InvalidCalculation
+    );
+    let (redeem_accounts, sell_accounts) = ctx.remaining_accounts.split_at(redeem_accounts_len);
+
+    let balance_before = accounts.vault_token_account.amount;
+    let collateral_before = accounts.collateral_account.amount;
+    let options_before = accounts.option_account.amount;
+    accounts.invoke(redeem_accounts, redeem_data, false)?;
+    let (balance_redeemed, collateral_after, _) = ctx.accounts.reload_balances()?;
+    require_eq!(
+        math::checked_sub(collateral_before, collateral_after)?,
+        ctx.accounts.principal_note.collateral,
+        TradingBotError::InvalidCalculation
+    );
+    let principal_proceeds = math::checked_sub(balance_redeemed, balance_before)?;
+
+    let upside_proceeds = if sell_data.is_empty() {
+        require!(
+            sell_accounts.is_empty(),
+            TradingBotError::InvalidCalculation
+        );
+        0
+    } else {
+        ctx.accounts.invoke(sell_accounts, sell_data, true)?;
+        let (balance_after, _, options_after) = ctx.accounts.reload_balances()?;
+        require_eq!(
+            math::checked_sub(options_before, options_after)?,
+            ctx.accounts.principal_note.options,
+            TradingBotError::InvalidCalculation
+        );
+        math::checked_sub(balance_after, balance_redeemed)?
+    };
+
+    let accounts = &ctx.accounts;
+    VaultLog::append_if_initialized(
+        &accounts.vault_log,
+        VaultOp::Preset,
+        accounts.principal_note.key(),
+        math::checked_add(principal_proceeds, upside_proceeds)?,
+        &accounts.trading_vault,
+        Some(accounts.vault_token_account.amount),
+    )?;
+    let principal_note = &mut ctx.accounts.principal_note;
+    let (principal_pnl, upside_pnl) = principal_note.settle(principal_proceeds, upside_proceeds)?;
+    emit!(PrincipalNoteClosed {
+        principal_note: principal_note.key(),
+        early,
+        principal_proceeds,
+        upside_proceeds,
+        principal_pnl,
+        upside_pnl,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,427 @@
+use crate::constants::{
+    FEED_REGISTRY_SEED, MARKET_MAKER_SEED, PUBLIC_PROFILE_SEED, STRATEGY_REGISTRY_SEED,
+};
+use crate::events::ProfileSnapshotTaken;
+use crate::instructions::{InitMarketMaker, ViewStrategySummary};
+use crate::oracles::PythOracle;
+use crate::types::TokenPair;
+use crate::valuation::Valuation;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        FeedRegistry, MarketMaker, PageCursor, PublicProfile, StrategyRegistry,
+        MAX_PROFILE_NAME_LEN, REGISTRY_CAPACITY,
+    },
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{spl_token::native_mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitStrategyRegistry<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    #[account(
+      init,
+      payer = payer,
+      space = StrategyRegistry::LEN,
+      seeds = [STRATEGY_REGISTRY_SEED],
+      bump
+    )]
+    registry: Box<Account<'info, StrategyRegistry>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PublishStrategy<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    /// CHECK: Checked against the feed stored at init
+    #[account(
+      address=market_maker.price_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      address=market_maker.base_wallet
+    )]
+    base_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      address=market_maker.quote_wallet
+    )]
+    quote_wallet: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Resolved through the feed registry for wrapped SOL; quote per SOL for the SOL
+    /// benchmark, so the quote has to be registered too
+    sol_price_feed: Option<UncheckedAccount<'info>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = PublicProfile::LEN,
+      seeds = [PUBLIC_PROFILE_SEED, market_maker.key().as_ref()],
+      bump
+    )]
+    profile: Box<Account<'info, PublicProfile>>,
+
+    #[account(
+      mut,
+      seeds = [STRATEGY_REGISTRY_SEED],
+      bump = registry.bump,
+    )]
+    registry: Box<Account<'info, StrategyRegistry>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotPublicProfile<'info> {
+    #[account(
+      mut,
+      seeds = [PUBLIC_PROFILE_SEED, profile.strategy.as_ref()],
+      bump = profile.bump,
+    )]
+    profile: Box<Account<'info, PublicProfile>>,
+
+    #[account(
+      address=profile.strategy
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    /// CHECK: Checked against the feed stored at init
+    #[account(
+      address=market_maker.price_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      address=market_maker.base_wallet
+    )]
+    base_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      address=market_maker.quote_wallet
+    )]
+    quote_wallet: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Checked against the feed stored at publishing
+    #[account(
+      address=profile.sol_price_feed
+    )]
+    sol_price_feed: Option<UncheckedAccount<'info>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotRegistry<'info> {
+    #[account(
+      mut,
+      seeds = [STRATEGY_REGISTRY_SEED],
+      bump = registry.bump,
+    )]
+    registry: Box<Account<'info, StrategyRegistry>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct UnpublishStrategy<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      close=owner,
+      seeds = [PUBLIC_PROFILE_SEED, profile.strategy.as_ref()],
+      bump = profile.bump,
+    )]
+    profile: Box<Account<'info, PublicProfile>>,
+
+    #[account(
+      mut,
+      seeds = [STRATEGY_REGISTRY_SEED],
+      bump = registry.bump,
+    )]
+    registry: Box<Account<'info, StrategyRegistry>>,
+}
+
+impl<'info> SnapshotPublicProfile<'info> {
+    // NAV along with the oracle mid it was valued at
+    pub fn current_nav(
+        feed_registry: &FeedRegistry,
+        market_maker: &MarketMaker,
+        price_feed: &AccountInfo,
+        base_wallet: &TokenAccount,
+        quote_wallet: &TokenAccount,
+    ) -> Result<(u64, u64)> {
+        let mid = InitMarketMaker::oracle_mid(
+            feed_registry,
+            price_feed,
+            market_maker.max_confidence,
+            base_wallet.mint,
+            quote_wallet.mint,
+        )?;
+        let nav =
+            ViewStrategySummary::nav(market_maker, base_wallet.amount, quote_wallet.amount, mid)?;
+        Ok((nav, mid))
+    }
+
+    // Native quote per lamport in PRICE_PRECISION, so NAV in native quote divides into lamports
+    pub fn sol_price(
+        feed_registry: &FeedRegistry,
+        sol_price_feed: &AccountInfo,
+        quote_mint: &Pubkey,
+    ) -> Result<u64> {
+        PythOracle::get_pair_price(
+            feed_registry,
+            &TokenPair::new(native_mint::ID, *quote_mint),
+            sol_price_feed,
+            u64::MAX,
+        )
+    }
+
+    // Values the baseline holdings at `mid` and reads the SOL feed when the profile has one
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        profile: &mut PublicProfile,
+        profile_key: Pubkey,
+        nav: u64,
+        mid: u64,
+        feed_registry: &FeedRegistry,
+        sol_price_feed: Option<&AccountInfo>,
+        quote_mint: &Pubkey,
+        now: i64,
+    ) -> Result<()> {
+        let hodl_nav = math::checked_add(
+            Valuation::quote_value(profile.baseline_base, mid)?,
+            profile.baseline_quote,
+        )?;
+        let sol_price = if profile.has_sol_benchmark() {
+            let sol_price_feed = sol_price_feed.ok_or(TradingBotError::PriceFeedMismatch)?;
+            Some(Self::sol_price(feed_registry, sol_price_feed, quote_mint)?)
+        } else {
+            None
+        };
+        profile.record_snapshot(nav, hodl_nav, sol_price, now)?;
+        emit!(ProfileSnapshotTaken {
+            profile: profile_key,
+            strategy: profile.strategy,
+            nav,
+            return_bps: profile.return_bps()?,
+            max_drawdown_bps: profile.max_drawdown_bps,
+            alpha_vs_hodl_bps: profile.alpha_vs_hodl_bps()?,
+            alpha_vs_sol_bps: profile.alpha_vs_sol_bps()?,
+            timestamp: now,
+        });
+        Ok(())
+    }
+}
+
+pub fn init_strategy_registry(ctx: Context<InitStrategyRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    registry.profiles = [Pubkey::default(); REGISTRY_CAPACITY];
+    registry.count = 0;
+    registry.snapshot_cursor = PageCursor::default();
+    registry.bump = *ctx.bumps.get("registry").unwrap();
+
+    Ok(())
+}
+
+// Opts the strategy into public ranking; its current NAV becomes the baseline returns are
+// measured from, and its current holdings the HODL benchmark. With a SOL price feed the profile
+// also tracks alpha against holding the baseline NAV in SOL.
+pub fn publish_strategy(ctx: Context<PublishStrategy>, name: String) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_PROFILE_NAME_LEN,
+        TradingBotError::InvalidProfileName
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let (nav, _) = SnapshotPublicProfile::current_nav(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.market_maker,
+        &ctx.accounts.price_feed,
+        &ctx.accounts.base_wallet,
+        &ctx.accounts.quote_wallet,
+    )?;
+
+    let profile_key = ctx.accounts.profile.key();
+    let profile = &mut ctx.accounts.profile;
+    profile.owner = ctx.accounts.owner.key();
+    profile.strategy = ctx.accounts.market_maker.key();
+    profile.strategy_type = ctx.accounts.market_maker.strategy_type;
+    profile.name = name;
+    profile.published_at = now;
+    profile.baseline_nav = nav;
+    profile.latest_nav = nav;
+    profile.peak_nav = nav;
+    profile.max_drawdown_bps = 0;
+    profile.baseline_base = ctx.accounts.base_wallet.amount;
+    profile.baseline_quote = ctx.accounts.quote_wallet.amount;
+    profile.latest_hodl_nav = nav;
+    (profile.sol_price_feed, profile.baseline_sol_price) = match &ctx.accounts.sol_price_feed {
+        Some(sol_price_feed) => (
+            sol_price_feed.key(),
+            SnapshotPublicProfile::sol_price(
+                &ctx.accounts.feed_registry,
+                sol_price_feed,
+                &ctx.accounts.quote_wallet.mint,
+            )?,
+        ),
+        None => (Pubkey::default(), 0),
+    };
+    profile.latest_sol_price = profile.baseline_sol_price;
+    profile.snapshot_count = 0;
+    profile.last_snapshot_at = now;
+    profile.bump = *ctx.bumps.get("profile").unwrap();
+
+    ctx.accounts.registry.add(profile_key)
+}
+
+// Permissionless crank, at most once per MIN_SNAPSHOT_INTERVAL
+pub fn snapshot_public_profile(ctx: Context<SnapshotPublicProfile>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        ctx.accounts.profile.snapshot_due(now),
+        TradingBotError::SampleTooEarly
+    );
+    let (nav, mid) = SnapshotPublicProfile::current_nav(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.market_maker,
+        &ctx.accounts.price_feed,
+        &ctx.accounts.base_wallet,
+        &ctx.accounts.quote_wallet,
+    )?;
+
+    let profile_key = ctx.accounts.profile.key();
+    let sol_price_feed = ctx
+        .accounts
+        .sol_price_feed
+        .as_ref()
+        .map(|feed| feed.to_account_info());
+    SnapshotPublicProfile::record(
+        &mut ctx.accounts.profile,
+        profile_key,
+        nav,
+        mid,
+        &ctx.accounts.feed_registry,
+        sol_price_feed.as_ref(),
+        &ctx.accounts.quote_wallet.mint,
+        now,
+    )
+}
+
+// Permissionless crank snapshotting the registry's profiles `max_items` per call, resuming where
+// the last call stopped. Remaining accounts come in groups of [profile, market maker, price feed,
+// base wallet, quote wallet, SOL price feed], one per registry entry in the page, passing the
+// program id for the SOL feed of profiles without one; profiles not yet due are skipped. The
+// cursor rewinds after the last entry.
+pub fn snapshot_registry<'info>(
+    ctx: Context<'_, '_, '_, 'info, SnapshotRegistry<'info>>,
+    start_index: u64,
+    max_items: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let registry = &mut ctx.accounts.registry;
+    let count = registry.count as u64;
+    let page = registry.snapshot_cursor.page(start_index, max_items)?;
+    let page = page.start.min(count)..page.end.min(count);
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        6 * (page.end - page.start) as usize,
+        TradingBotError::InvalidPageAccounts
+    );
+
+    for (i, accounts) in page.clone().zip(ctx.remaining_accounts.chunks(6)) {
+        require_keys_eq!(
+            accounts[0].key(),
+            registry.profiles[i as usize],
+            TradingBotError::ProfileNotRegistered
+        );
+        let mut profile = Account::<PublicProfile>::try_from(&accounts[0])?;
+        if !profile.snapshot_due(now) {
+            continue;
+        }
+        let market_maker = Account::<MarketMaker>::try_from(&accounts[1])?;
+        require_keys_eq!(
+            market_maker.key(),
+            profile.strategy,
+            TradingBotError::InvalidPageAccounts
+        );
+        require_keys_eq!(
+            accounts[2].key(),
+            market_maker.price_feed,
+            TradingBotError::InvalidPageAccounts
+        );
+        let base_wallet = Account::<TokenAccount>::try_from(&accounts[3])?;
+        let quote_wallet = Account::<TokenAccount>::try_from(&accounts[4])?;
+        require!(
+            base_wallet.key() == market_maker.base_wallet
+                && quote_wallet.key() == market_maker.quote_wallet,
+            TradingBotError::InvalidPageAccounts
+        );
+
+        let sol_price_feed = if profile.has_sol_benchmark() {
+            require_keys_eq!(
+                accounts[5].key(),
+                profile.sol_price_feed,
+                TradingBotError::InvalidPageAccounts
+            );
+            Some(&accounts[5])
+        } else {
+            None
+        };
+
+        let (nav, mid) = SnapshotPublicProfile::current_nav(
+            &ctx.accounts.feed_registry,
+            &market_maker,
+            &accounts[2],
+            &base_wallet,
+            &quote_wallet,
+        )?;
+        SnapshotPublicProfile::record(
+            &mut profile,
+            accounts[0].key(),
+            nav,
+            mid,
+            &ctx.accounts.feed_registry,
+            sol_price_feed,
+            &quote_wallet.mint,
+            now,
+        )?;
+        profile.exit(ctx.program_id)?;
+    }
+
+    registry.snapshot_cursor.advance(page.end, Some(count), now);
+    Ok(())
+}
+
+pub fn unpublish_strategy(ctx: Context<UnpublishStrategy>) -> Result<()> {
+    ctx.accounts.registry.remove(&ctx.accounts.profile.key())
+}
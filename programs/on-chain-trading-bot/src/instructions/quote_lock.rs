@@ -0,0 +1,171 @@
+use crate::constants::{MAX_QUOTE_LOCK_SLOTS, MAX_SLIPPAGE_BPS, QUOTE_LOCK_SEED};
+use crate::dex::{jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID};
+use crate::{errors::TradingBotError, math, state::QuoteLock};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct LockQuote<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      token::authority=owner,
+    )]
+    input_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      token::authority=owner,
+    )]
+    output_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Checked against the supported swap programs
+    swap_program: UncheckedAccount<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = QuoteLock::LEN,
+      seeds = [QUOTE_LOCK_SEED, owner.key().as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    quote_lock: Box<Account<'info, QuoteLock>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FillLockedQuote<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      has_one=input_account,
+      has_one=output_account,
+      has_one=swap_program,
+      close=owner,
+      seeds = [QUOTE_LOCK_SEED, owner.key().as_ref(), quote_lock.idx.to_le_bytes().as_ref()],
+      bump = quote_lock.bump,
+    )]
+    quote_lock: Box<Account<'info, QuoteLock>>,
+
+    #[account(mut)]
+    input_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    output_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Matched against the program recorded in the lock
+    swap_program: UncheckedAccount<'info>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+impl<'info> LockQuote<'info> {
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+}
+
+pub fn lock_quote(
+    ctx: Context<LockQuote>,
+    idx: u64,
+    amount_in: u64,
+    quoted_out: u64,
+    max_slots: u64,
+    tolerance_bps: u16,
+) -> Result<()> {
+    require_gt!(amount_in, 0, TradingBotError::InvalidCalculation);
+    require_gt!(quoted_out, 0, TradingBotError::InvalidCalculation);
+    require!(
+        max_slots > 0 && max_slots <= MAX_QUOTE_LOCK_SLOTS,
+        TradingBotError::InvalidCalculation
+    );
+    require!(
+        tolerance_bps <= MAX_SLIPPAGE_BPS,
+        TradingBotError::SlippageExceeded
+    );
+    require!(
+        LockQuote::is_supported_swap_program(ctx.accounts.swap_program.key),
+        TradingBotError::InvalidDexType
+    );
+    require_keys_neq!(
+        ctx.accounts.input_account.mint,
+        ctx.accounts.output_account.mint,
+        TradingBotError::InvalidCalculation
+    );
+
+    let quote_lock = &mut ctx.accounts.quote_lock;
+    quote_lock.idx = idx;
+    quote_lock.owner = ctx.accounts.owner.key();
+    quote_lock.input_account = ctx.accounts.input_account.key();
+    quote_lock.output_account = ctx.accounts.output_account.key();
+    quote_lock.swap_program = ctx.accounts.swap_program.key();
+    quote_lock.amount_in = amount_in;
+    quote_lock.quoted_out = quoted_out;
+    quote_lock.tolerance_bps = tolerance_bps;
+    quote_lock.locked_slot = Clock::get()?.slot;
+    quote_lock.max_slots = max_slots;
+    quote_lock.bump = *ctx.bumps.get("quote_lock").unwrap();
+
+    msg!(
+        "Locked {} -> {} at slot {}",
+        amount_in,
+        quoted_out,
+        quote_lock.locked_slot
+    );
+    Ok(())
+}
+
+// The swap instruction is built off-chain against the locked quote; the realised balances are
+// checked against the lock, which is closed once filled
+pub fn fill_locked_quote(ctx: Context<FillLockedQuote>, swap_data: Vec<u8>) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    let quote_lock = &ctx.accounts.quote_lock;
+    require!(
+        !quote_lock.is_expired(Clock::get()?.slot),
+        TradingBotError::QuoteLockExpired
+    );
+    let min_out = quote_lock.min_out()?;
+    let amount_in = quote_lock.amount_in;
+
+    let input_before = ctx.accounts.input_account.amount;
+    let output_before = ctx.accounts.output_account.amount;
+
+    let ix = Instruction {
+        program_id: ctx.accounts.swap_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|a| {
+                if a.is_writable {
+                    AccountMeta::new(a.key(), a.is_signer)
+                } else {
+                    AccountMeta::new_readonly(a.key(), a.is_signer)
+                }
+            })
+            .collect(),
+        data: swap_data,
+    };
+    invoke(&ix, ctx.remaining_accounts)?;
+
+    ctx.accounts.input_account.reload()?;
+    ctx.accounts.output_account.reload()?;
+    let spent = math::checked_sub(input_before, ctx.accounts.input_account.amount)?;
+    let received = math::checked_sub(ctx.accounts.output_account.amount, output_before)?;
+
+    require_gte!(amount_in, spent, TradingBotError::QuoteOutsideTolerance);
+    require_gte!(received, min_out, TradingBotError::QuoteOutsideTolerance);
+
+    msg!("Filled locked quote: {} in, {} out", spent, received);
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use crate::constants::{MIN_RECOVERY_TIMELOCK, RECOVERY_CONFIG_SEED, STRATEGY_SEED};
+use crate::events::OwnershipRecovered;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{RecoveryConfig, Strategy, TradingVault, VaultLog, VaultOp},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitRecoveryConfig<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = RecoveryConfig::LEN,
+      seeds = [RECOVERY_CONFIG_SEED, strategy.key().as_ref()],
+      bump
+    )]
+    recovery_config: Box<Account<'info, RecoveryConfig>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRecovery<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      has_one=strategy,
+      seeds = [RECOVERY_CONFIG_SEED, strategy.key().as_ref()],
+      bump = recovery_config.bump,
+    )]
+    recovery_config: Box<Account<'info, RecoveryConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    guardian: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [RECOVERY_CONFIG_SEED, recovery_config.strategy.as_ref()],
+      bump = recovery_config.bump,
+    )]
+    recovery_config: Box<Account<'info, RecoveryConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRecovery<'info> {
+    #[account(
+      mut,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      has_one=strategy,
+      seeds = [RECOVERY_CONFIG_SEED, strategy.key().as_ref()],
+      bump = recovery_config.bump,
+    )]
+    recovery_config: Box<Account<'info, RecoveryConfig>>,
+}
+
+pub fn init_recovery_config(
+    ctx: Context<InitRecoveryConfig>,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    timelock: i64,
+) -> Result<()> {
+    require_gte!(
+        timelock,
+        MIN_RECOVERY_TIMELOCK,
+        TradingBotError::InvalidRecoveryConfig
+    );
+    require!(
+        !guardians.contains(&ctx.accounts.owner.key()),
+        TradingBotError::InvalidRecoveryConfig
+    );
+
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    recovery_config.strategy = ctx.accounts.strategy.key();
+    recovery_config.set_guardians(&guardians, threshold, timelock)?;
+    recovery_config.recovery_count = 0;
+    recovery_config.bump = *ctx.bumps.get("recovery_config").unwrap();
+
+    Ok(())
+}
+
+// Replacing the guardians also drops any pending recovery
+pub fn update_recovery_config(
+    ctx: Context<ManageRecovery>,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    timelock: i64,
+) -> Result<()> {
+    require_gte!(
+        timelock,
+        MIN_RECOVERY_TIMELOCK,
+        TradingBotError::InvalidRecoveryConfig
+    );
+    require!(
+        !guardians.contains(&ctx.accounts.owner.key()),
+        TradingBotError::InvalidRecoveryConfig
+    );
+    ctx.accounts
+        .recovery_config
+        .set_guardians(&guardians, threshold, timelock)
+}
+
+// The owner's veto while a recovery sits in its timelock
+pub fn cancel_recovery(ctx: Context<ManageRecovery>) -> Result<()> {
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    if recovery_config.is_pending() {
+        msg!("Cancelling recovery to {}", recovery_config.proposed_owner);
+    }
+    recovery_config.clear_pending();
+    Ok(())
+}
+
+// The first approval for a key proposes it and starts the timelock
+pub fn approve_recovery(ctx: Context<ApproveRecovery>, new_owner: Pubkey) -> Result<()> {
+    require_keys_neq!(
+        new_owner,
+        Pubkey::default(),
+        TradingBotError::InvalidRecoveryConfig
+    );
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let guardian_index = recovery_config
+        .guardian_index(ctx.accounts.guardian.key)
+        .ok_or(TradingBotError::NotAGuardian)?;
+    recovery_config.approve(guardian_index, new_owner, Clock::get()?.unix_timestamp);
+    msg!(
+        "Recovery to {} has {} of {} approvals",
+        new_owner,
+        recovery_config.approvals.count_ones(),
+        recovery_config.threshold
+    );
+    Ok(())
+}
+
+// Permissionless once approved and out of its timelock. The remaining accounts are the old
+// owner's trading vaults to hand over along with the strategy, each followed by its vault log.
+pub fn execute_recovery<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteRecovery<'info>>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    require!(
+        recovery_config.is_ready(now),
+        TradingBotError::RecoveryNotReady
+    );
+
+    let strategy = &mut ctx.accounts.strategy;
+    let old_owner = strategy.owner;
+    let new_owner = recovery_config.proposed_owner;
+
+    require!(
+        ctx.remaining_accounts.len() % 2 == 0,
+        TradingBotError::InvalidVaultLog
+    );
+    for accounts in ctx.remaining_accounts.chunks(2) {
+        let mut trading_vault = Account::<TradingVault>::try_from(&accounts[0])?;
+        require_keys_eq!(
+            trading_vault.owner,
+            old_owner,
+            TradingBotError::InvalidRecoveryConfig
+        );
+        VaultLog::require_address(&accounts[1], &trading_vault.key())?;
+        trading_vault.owner = new_owner;
+        VaultLog::append_if_initialized(
+            &accounts[1],
+            VaultOp::OwnerChange,
+            new_owner,
+            0,
+            &trading_vault,
+            None,
+        )?;
+        trading_vault.exit(ctx.program_id)?;
+    }
+
+    strategy.owner = new_owner;
+    strategy.updated_at = now;
+    recovery_config.clear_pending();
+    recovery_config.recovery_count = math::checked_add(recovery_config.recovery_count, 1)?;
+
+    emit!(OwnershipRecovered {
+        strategy: strategy.key(),
+        old_owner,
+        new_owner,
+        vaults: (ctx.remaining_accounts.len() / 2) as u8,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use crate::constants::RECURRING_TRANSFER_SEED;
+use crate::{
+    errors::TradingBotError,
+    math, recurring_transfer_seeds,
+    state::{CycleSchedule, RecurringTransfer},
+    types::TimeBasis,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct CreateRecurringTransfer<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Any wallet can receive the payments
+    recipient: UncheckedAccount<'info>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+      token::mint=mint,
+    )]
+    owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = RecurringTransfer::LEN,
+      seeds = [RECURRING_TRANSFER_SEED, owner.key().as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    recurring_transfer: Box<Account<'info, RecurringTransfer>>,
+
+    #[account(
+      init,
+      payer=owner,
+      associated_token::authority=recurring_transfer,
+      associated_token::mint=mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRecurringTransfer<'info> {
+    #[account(mut)]
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [RECURRING_TRANSFER_SEED, recurring_transfer.owner.as_ref(), recurring_transfer.idx.to_le_bytes().as_ref()],
+      bump = recurring_transfer.bump,
+    )]
+    recurring_transfer: Box<Account<'info, RecurringTransfer>>,
+
+    #[account(
+      address=recurring_transfer.mint
+    )]
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      associated_token::authority=recurring_transfer,
+      associated_token::mint=mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Checked against the stored recipient
+    #[account(
+      address=recurring_transfer.recipient
+    )]
+    recipient: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=keeper,
+      associated_token::authority=recipient,
+      associated_token::mint=mint,
+    )]
+    recipient_token_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpRecurringTransfer<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [RECURRING_TRANSFER_SEED, recurring_transfer.owner.as_ref(), recurring_transfer.idx.to_le_bytes().as_ref()],
+      bump = recurring_transfer.bump,
+    )]
+    recurring_transfer: Box<Account<'info, RecurringTransfer>>,
+
+    #[account(
+      mut,
+      associated_token::authority=recurring_transfer,
+      associated_token::mint=recurring_transfer.mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+      token::mint=recurring_transfer.mint,
+    )]
+    owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecurringTransfer<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      close=owner,
+      seeds = [RECURRING_TRANSFER_SEED, recurring_transfer.owner.as_ref(), recurring_transfer.idx.to_le_bytes().as_ref()],
+      bump = recurring_transfer.bump,
+    )]
+    recurring_transfer: Box<Account<'info, RecurringTransfer>>,
+
+    #[account(
+      address=recurring_transfer.mint
+    )]
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      associated_token::authority=recurring_transfer,
+      associated_token::mint=mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=owner,
+      associated_token::authority=owner,
+      associated_token::mint=mint,
+    )]
+    owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_recurring_transfer(
+    ctx: Context<CreateRecurringTransfer>,
+    idx: u64,
+    amount_per_cycle: u64,
+    cycle_frequency: i64,
+    start_at: Option<i64>,
+    max_cycles: u32,
+    deposit: u64,
+    basis: TimeBasis,
+) -> Result<()> {
+    require_gt!(amount_per_cycle, 0, TradingBotError::InvalidSchedule);
+    require_gt!(cycle_frequency, 0, TradingBotError::InvalidSchedule);
+    require!(basis.is_valid(), TradingBotError::InvalidSchedule);
+    let now = basis.current()?;
+    let start_at = start_at.unwrap_or(now);
+    require_gte!(start_at, now, TradingBotError::InvalidSchedule);
+
+    if deposit > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+    }
+
+    let recurring_transfer = &mut ctx.accounts.recurring_transfer;
+    recurring_transfer.idx = idx;
+    recurring_transfer.owner = ctx.accounts.owner.key();
+    recurring_transfer.recipient = ctx.accounts.recipient.key();
+    recurring_transfer.mint = ctx.accounts.mint.key();
+    recurring_transfer.amount_per_cycle = amount_per_cycle;
+    recurring_transfer.schedule = CycleSchedule::new(basis, cycle_frequency, start_at, max_cycles);
+    recurring_transfer.total_transferred = 0;
+    recurring_transfer.bump = *ctx.bumps.get("recurring_transfer").unwrap();
+
+    Ok(())
+}
+
+// Permissionless crank paying one due cycle per call. An underfunded vault fails the cycle
+// instead of paying a partial amount, so the owner can top up and the keeper retry.
+pub fn execute_recurring_transfer(ctx: Context<ExecuteRecurringTransfer>) -> Result<()> {
+    let recurring_transfer = &ctx.accounts.recurring_transfer;
+    require!(
+        recurring_transfer
+            .schedule
+            .is_due(recurring_transfer.schedule.basis.current()?),
+        TradingBotError::CycleNotDue
+    );
+    let amount = recurring_transfer.amount_per_cycle;
+    require_gte!(
+        ctx.accounts.vault.amount,
+        amount,
+        TradingBotError::InsufficientAvailableBalance
+    );
+
+    msg!(
+        "Paying cycle #{}: {}",
+        recurring_transfer.schedule.cycles_completed,
+        amount
+    );
+    let idx_bytes = recurring_transfer.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[recurring_transfer_seeds!(recurring_transfer, idx_bytes)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.recurring_transfer.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    let recurring_transfer = &mut ctx.accounts.recurring_transfer;
+    recurring_transfer.schedule.advance()?;
+    recurring_transfer.total_transferred =
+        math::checked_add(recurring_transfer.total_transferred, amount)?;
+
+    Ok(())
+}
+
+pub fn top_up_recurring_transfer(ctx: Context<TopUpRecurringTransfer>, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, TradingBotError::InvalidSchedule);
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+// Refunds whatever is left in the vault and closes the schedule
+pub fn cancel_recurring_transfer(ctx: Context<CancelRecurringTransfer>) -> Result<()> {
+    let idx_bytes = ctx.accounts.recurring_transfer.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[recurring_transfer_seeds!(
+        ctx.accounts.recurring_transfer,
+        idx_bytes
+    )];
+
+    if ctx.accounts.vault.amount > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.recurring_transfer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            ctx.accounts.vault.amount,
+        )?;
+    }
+
+    anchor_spl::token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        anchor_spl::token::CloseAccount {
+            account: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.recurring_transfer.to_account_info(),
+        },
+        signer_seeds,
+    ))
+}
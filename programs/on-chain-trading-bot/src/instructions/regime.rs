@@ -0,0 +1,214 @@
+use crate::constants::{FEED_REGISTRY_SEED, MARKET_MAKER_SEED, REGIME_SWITCH_SEED};
+use crate::events::RegimeChanged;
+use crate::instructions::InitMarketMaker;
+use crate::oracles::PythOracle;
+use crate::regime::{PriceRing, RegimeThresholds, VolatilityRegime, MIN_REGIME_SAMPLES};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{FeedRegistry, MarketMaker, RegimeParams, RegimeSwitch},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct InitRegimeSwitch<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = RegimeSwitch::LEN,
+      seeds = [REGIME_SWITCH_SEED, market_maker.key().as_ref()],
+      bump
+    )]
+    regime_switch: AccountLoader<'info, RegimeSwitch>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRegimeParams<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    #[account(
+      mut,
+      has_one=market_maker,
+      seeds = [REGIME_SWITCH_SEED, market_maker.key().as_ref()],
+      bump = regime_switch.load()?.bump,
+    )]
+    regime_switch: AccountLoader<'info, RegimeSwitch>,
+}
+
+#[derive(Accounts)]
+pub struct SampleRegime<'info> {
+    #[account(
+      mut,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    #[account(
+      mut,
+      has_one=market_maker,
+      seeds = [REGIME_SWITCH_SEED, market_maker.key().as_ref()],
+      bump = regime_switch.load()?.bump,
+    )]
+    regime_switch: AccountLoader<'info, RegimeSwitch>,
+
+    /// CHECK: Checked against the feed stored at init
+    #[account(
+      address=market_maker.price_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+
+    // its mint is the one the feed prices
+    #[account(
+      address=market_maker.base_wallet
+    )]
+    base_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+impl<'info> SetRegimeParams<'info> {
+    // A set is only valid if the market maker would accept it as its live parameters
+    pub fn validate_regime_params(
+        market_maker: &MarketMaker,
+        regime_params: &RegimeParams,
+    ) -> Result<()> {
+        let mut params = market_maker.params();
+        regime_params.apply(&mut params);
+        InitMarketMaker::validate_params(&params)
+    }
+
+    pub fn switch_to(
+        market_maker: &mut MarketMaker,
+        regime_switch: &mut RegimeSwitch,
+        regime: VolatilityRegime,
+    ) {
+        let mut params = market_maker.params();
+        regime_switch.params_for(regime).apply(&mut params);
+        market_maker.apply_params(&params);
+        regime_switch.set_current(regime);
+    }
+}
+
+// Starts in the Medium regime with its parameter set applied right away
+pub fn init_regime_switch(
+    ctx: Context<InitRegimeSwitch>,
+    sample_interval: i64,
+    thresholds: RegimeThresholds,
+    param_sets: [RegimeParams; 3],
+) -> Result<()> {
+    require_gt!(sample_interval, 0, TradingBotError::InvalidRegimeParams);
+    require!(thresholds.is_valid(), TradingBotError::InvalidRegimeParams);
+    for regime_params in param_sets.iter() {
+        SetRegimeParams::validate_regime_params(&ctx.accounts.market_maker, regime_params)?;
+    }
+
+    let mut regime_switch = ctx.accounts.regime_switch.load_init()?;
+    regime_switch.owner = ctx.accounts.owner.key();
+    regime_switch.market_maker = ctx.accounts.market_maker.key();
+    regime_switch.samples = PriceRing::default();
+    regime_switch.sample_interval = sample_interval;
+    regime_switch.last_sample_at = 0;
+    regime_switch.thresholds = thresholds;
+    regime_switch.param_sets = param_sets;
+    regime_switch.last_switch_at = Clock::get()?.unix_timestamp;
+    regime_switch.switch_count = 0;
+    regime_switch.bump = *ctx.bumps.get("regime_switch").unwrap();
+
+    SetRegimeParams::switch_to(
+        &mut ctx.accounts.market_maker,
+        &mut regime_switch,
+        VolatilityRegime::Medium,
+    );
+
+    Ok(())
+}
+
+// Replaces one regime's parameter set; editing the active regime takes effect immediately
+pub fn set_regime_params(
+    ctx: Context<SetRegimeParams>,
+    regime: VolatilityRegime,
+    regime_params: RegimeParams,
+) -> Result<()> {
+    SetRegimeParams::validate_regime_params(&ctx.accounts.market_maker, &regime_params)?;
+
+    let mut regime_switch = ctx.accounts.regime_switch.load_mut()?;
+    regime_switch.param_sets[regime.index()] = regime_params;
+    if regime_switch.current() == regime {
+        SetRegimeParams::switch_to(&mut ctx.accounts.market_maker, &mut regime_switch, regime);
+    }
+
+    Ok(())
+}
+
+// Permissionless crank: records an oracle sample once per interval and, once enough samples
+// are in, moves the market maker to the parameter set of the detected regime
+pub fn sample_regime(ctx: Context<SampleRegime>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        ctx.accounts.regime_switch.load()?.sample_due(now),
+        TradingBotError::SampleTooEarly
+    );
+
+    let price = PythOracle::get_price_with_confidence(
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.base_wallet.mint,
+        &ctx.accounts.price_feed,
+        ctx.accounts.market_maker.max_confidence,
+    )?;
+    let mut regime_switch = ctx.accounts.regime_switch.load_mut()?;
+    regime_switch
+        .samples
+        .push(math::checked_as_u64(price.price)?);
+    regime_switch.last_sample_at = now;
+    if regime_switch.samples.count < MIN_REGIME_SAMPLES {
+        return Ok(());
+    }
+
+    let volatility_bps = regime_switch.samples.volatility_bps()?;
+    let from = regime_switch.current();
+    let to = regime_switch.thresholds.next(from, volatility_bps);
+    msg!("Volatility {} bps", volatility_bps);
+    if to == from {
+        return Ok(());
+    }
+
+    SetRegimeParams::switch_to(&mut ctx.accounts.market_maker, &mut regime_switch, to);
+    regime_switch.last_switch_at = now;
+    regime_switch.switch_count = math::checked_add(regime_switch.switch_count, 1)?;
+    emit!(RegimeChanged {
+        market_maker: ctx.accounts.market_maker.key(),
+        from,
+        to,
+        volatility_bps,
+        timestamp: now,
+    });
+
+    Ok(())
+}
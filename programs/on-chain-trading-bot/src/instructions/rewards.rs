@@ -0,0 +1,201 @@
+use crate::constants::{CLAIM_STATUS_SEED, REWARDS_DISTRIBUTOR_SEED};
+use crate::{
+    errors::TradingBotError,
+    math, merkle, rewards_distributor_seeds,
+    state::{ClaimStatus, RewardsDistributor},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+#[instruction(campaign_id: u64)]
+pub struct NewDistributor<'info> {
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = admin,
+      space = RewardsDistributor::LEN,
+      seeds = [REWARDS_DISTRIBUTOR_SEED, admin.key().as_ref(), campaign_id.to_le_bytes().as_ref()],
+      bump
+    )]
+    distributor: Box<Account<'info, RewardsDistributor>>,
+
+    #[account(
+      init,
+      payer=admin,
+      associated_token::authority=distributor,
+      associated_token::mint=mint,
+    )]
+    rewards_vault: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDistributorRoot<'info> {
+    admin: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=admin,
+    )]
+    distributor: Box<Account<'info, RewardsDistributor>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    claimant: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [REWARDS_DISTRIBUTOR_SEED, distributor.admin.as_ref(), distributor.campaign_id.to_le_bytes().as_ref()],
+      bump = distributor.bump,
+    )]
+    distributor: Box<Account<'info, RewardsDistributor>>,
+
+    #[account(
+      init_if_needed,
+      payer = claimant,
+      space = ClaimStatus::LEN,
+      seeds = [CLAIM_STATUS_SEED, distributor.key().as_ref(), claimant.key().as_ref()],
+      bump
+    )]
+    claim_status: Box<Account<'info, ClaimStatus>>,
+
+    #[account(
+      address=distributor.mint
+    )]
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      associated_token::authority=distributor,
+      associated_token::mint=mint,
+    )]
+    rewards_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=claimant,
+      associated_token::authority=claimant,
+      associated_token::mint=mint,
+    )]
+    claimant_token_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+// Campaigns are funded by transferring reward tokens straight into the rewards vault
+pub fn new_distributor(
+    ctx: Context<NewDistributor>,
+    campaign_id: u64,
+    merkle_root: [u8; 32],
+    max_total_claim: u64,
+) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+    distributor.campaign_id = campaign_id;
+    distributor.admin = ctx.accounts.admin.key();
+    distributor.mint = ctx.accounts.mint.key();
+    distributor.merkle_root = merkle_root;
+    distributor.root_version = 0;
+    distributor.max_total_claim = max_total_claim;
+    distributor.total_claimed = 0;
+    distributor.num_claimants = 0;
+    distributor.bump = *ctx.bumps.get("distributor").unwrap();
+
+    Ok(())
+}
+
+// A new root must keep every leaf's cumulative amount at or above what was already claimed
+pub fn update_distributor_root(
+    ctx: Context<UpdateDistributorRoot>,
+    merkle_root: [u8; 32],
+    max_total_claim: u64,
+) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+    require_gte!(
+        max_total_claim,
+        distributor.total_claimed,
+        TradingBotError::ClaimExceedsMaximum
+    );
+
+    distributor.merkle_root = merkle_root;
+    distributor.max_total_claim = max_total_claim;
+    distributor.root_version = math::checked_add(distributor.root_version, 1)?;
+
+    Ok(())
+}
+
+pub fn claim_rewards(
+    ctx: Context<ClaimRewards>,
+    cumulative_amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let claimant = ctx.accounts.claimant.key();
+    let leaf = merkle::claim_leaf(claimant.as_ref(), cumulative_amount);
+    require!(
+        merkle::verify(&proof, ctx.accounts.distributor.merkle_root, leaf),
+        TradingBotError::InvalidMerkleProof
+    );
+
+    let claim_status = &mut ctx.accounts.claim_status;
+    let first_claim = claim_status.claimant == Pubkey::default();
+    if first_claim {
+        claim_status.distributor = ctx.accounts.distributor.key();
+        claim_status.claimant = claimant;
+        claim_status.amount_claimed = 0;
+        claim_status.bump = *ctx.bumps.get("claim_status").unwrap();
+    }
+
+    require_gt!(
+        cumulative_amount,
+        claim_status.amount_claimed,
+        TradingBotError::NothingToClaim
+    );
+    let amount = math::checked_sub(cumulative_amount, claim_status.amount_claimed)?;
+    claim_status.amount_claimed = cumulative_amount;
+    claim_status.last_claimed_at = Clock::get()?.unix_timestamp;
+
+    let distributor = &mut ctx.accounts.distributor;
+    distributor.total_claimed = math::checked_add(distributor.total_claimed, amount)?;
+    require_gte!(
+        distributor.max_total_claim,
+        distributor.total_claimed,
+        TradingBotError::ClaimExceedsMaximum
+    );
+    if first_claim {
+        distributor.num_claimants = math::checked_add(distributor.num_claimants, 1)?;
+    }
+
+    let campaign_bytes = ctx.accounts.distributor.campaign_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] =
+        &[rewards_distributor_seeds!(ctx.accounts.distributor, campaign_bytes)];
+
+    msg!("Transferring {} rewards", amount);
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.rewards_vault.to_account_info(),
+                to: ctx.accounts.claimant_token_account.to_account_info(),
+                authority: ctx.accounts.distributor.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    Ok(())
+}
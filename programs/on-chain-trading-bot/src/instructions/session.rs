@@ -0,0 +1,94 @@
+use crate::constants::{MAX_SESSION_DURATION, SESSION_SEED, STRATEGY_SEED};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{Session, Strategy},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateSession<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    /// CHECK: Any keypair the frontend holds, only ever used as a signer
+    session_key: UncheckedAccount<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = Session::LEN,
+      seeds = [SESSION_SEED, strategy.key().as_ref(), session_key.key().as_ref()],
+      bump
+    )]
+    session: Box<Account<'info, Session>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      close=owner,
+      seeds = [SESSION_SEED, session.strategy.as_ref(), session.session_key.as_ref()],
+      bump = session.bump,
+    )]
+    session: Box<Account<'info, Session>>,
+}
+
+// Lets `session_key` sign `execute_strategy` for up to `duration` seconds. The key never gets
+// authority over tokens: the strategy's funds move only under its own PDA, within the caps
+// `execute_strategy` books against the session.
+pub fn create_session(
+    ctx: Context<CreateSession>,
+    duration: i64,
+    max_trade_amount: u64,
+    volume_cap: u64,
+) -> Result<()> {
+    require!(
+        duration > 0 && duration <= MAX_SESSION_DURATION,
+        TradingBotError::InvalidSessionParams
+    );
+    require!(
+        max_trade_amount > 0 && max_trade_amount <= volume_cap,
+        TradingBotError::InvalidSessionParams
+    );
+    require_keys_neq!(
+        ctx.accounts.session_key.key(),
+        ctx.accounts.owner.key(),
+        TradingBotError::InvalidSessionParams
+    );
+
+    let session = &mut ctx.accounts.session;
+    session.strategy = ctx.accounts.strategy.key();
+    session.owner = ctx.accounts.owner.key();
+    session.session_key = ctx.accounts.session_key.key();
+    let strategy = &ctx.accounts.strategy;
+    session.expires_at = math::checked_add(
+        strategy.clock_now()?,
+        strategy.config.time_basis.from_seconds(duration),
+    )?;
+    session.max_trade_amount = max_trade_amount;
+    session.volume_cap = volume_cap;
+    session.volume_used = 0;
+    session.bump = *ctx.bumps.get("session").unwrap();
+
+    Ok(())
+}
+
+// Also how an expired session's rent is reclaimed
+pub fn revoke_session(_ctx: Context<RevokeSession>) -> Result<()> {
+    Ok(())
+}
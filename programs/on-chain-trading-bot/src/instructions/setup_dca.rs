@@ -1,5 +1,10 @@
-use crate::constants::ESCROW_SEED;
-use crate::{escrow_seeds, state::Escrow};
+use crate::constants::{ESCROW_SEED, TRADING_VAULT_SEED};
+use crate::{
+    errors::TradingBotError,
+    escrow_seeds,
+    state::{CancelPolicy, Escrow, TradingVault, VaultLog, VaultOp},
+    trading_vault_seeds,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -10,7 +15,10 @@ use jupiter_dca::cpi::{self};
 #[derive(Accounts)]
 #[instruction(application_idx: u64)]
 pub struct SetupDca<'info> {
-    /// CHECK: Jup DCA will check
+    /// CHECK: Jup DCA program
+    #[account(
+      address=jupiter_dca::ID
+    )]
     jup_dca_program: UncheckedAccount<'info>,
 
     /// CHECK: Jup DCA will check
@@ -32,6 +40,22 @@ pub struct SetupDca<'info> {
     )]
     user_token_account: Box<Account<'info, TokenAccount>>,
 
+    // the user's vault for the input mint, to fund the DCA from its unreserved balance instead of
+    // the user's token account
+    #[account(
+      constraint = trading_vault.owner == user.key() @ TradingBotError::UnauthorizedExecutor,
+      constraint = trading_vault.mint == input_mint.key() @ TradingBotError::TradeSideMismatch,
+    )]
+    trading_vault: Option<Box<Account<'info, TradingVault>>>,
+
+    // checked against the trading vault
+    #[account(mut)]
+    vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: The trading vault's VaultLog PDA, checked against the vault
+    #[account(mut)]
+    vault_log: Option<UncheckedAccount<'info>>,
+
     #[account(
       init,
       payer = user,
@@ -57,11 +81,83 @@ pub struct SetupDca<'info> {
     )]
     escrow_out_ata: Box<Account<'info, TokenAccount>>,
 
+    /// CHECK: Jup DCA will check
+    #[account(mut)]
+    jup_dca_in_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Jup DCA will check
+    #[account(mut)]
+    jup_dca_out_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Jup DCA will check
+    jup_dca_event_authority: UncheckedAccount<'info>,
+
     system_program: Program<'info, System>,
     token_program: Program<'info, Token>,
     associated_token_program: Program<'info, AssociatedToken>,
 }
 
+impl<'info> SetupDca<'info> {
+    // The DCA may only take what no resting order has reserved, and its output and refunds leave
+    // the vault for the user, so the user has to be a wallet the vault may withdraw to
+    fn fund_from_vault(&mut self, in_amount: u64) -> Result<()> {
+        let trading_vault = self.trading_vault.as_ref().unwrap();
+        let vault_token_account = self
+            .vault_token_account
+            .as_mut()
+            .ok_or(TradingBotError::InvalidVaultTokenAccount)?;
+        require_keys_eq!(
+            vault_token_account.key(),
+            trading_vault.token_account,
+            TradingBotError::InvalidVaultTokenAccount
+        );
+        let vault_log = self
+            .vault_log
+            .as_ref()
+            .ok_or(TradingBotError::InvalidVaultLog)?;
+        VaultLog::require_address(vault_log, &trading_vault.key())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let allowlist = &trading_vault.withdrawal_allowlist;
+        require!(
+            !allowlist.is_enforced(now) || allowlist.allows(self.user.key, now),
+            TradingBotError::WithdrawalAddressNotAllowed
+        );
+        require_gte!(
+            trading_vault.available(vault_token_account.amount),
+            in_amount,
+            TradingBotError::InsufficientAvailableBalance
+        );
+
+        msg!("Transfer from trading vault");
+        let signer_seeds: &[&[&[u8]]] = &[trading_vault_seeds!(trading_vault)];
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: vault_token_account.to_account_info(),
+                    to: self.escrow_in_ata.to_account_info(),
+                    authority: trading_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            in_amount,
+        )?;
+
+        vault_token_account.reload()?;
+        trading_vault.check_invariant(vault_token_account.amount)?;
+        VaultLog::append_if_initialized(
+            vault_log,
+            VaultOp::Preset,
+            self.escrow.key(),
+            in_amount,
+            trading_vault,
+            Some(vault_token_account.amount),
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn setup_dca(
     ctx: Context<SetupDca>,
     application_idx: u64,
@@ -72,18 +168,29 @@ pub fn setup_dca(
     max_out_amount: Option<u64>,
     start_at: Option<i64>,
 ) -> Result<()> {
-    msg!("Transfer from user");
-    anchor_spl::token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_account.to_account_info(),
-                to: ctx.accounts.escrow_in_ata.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
-        in_amount,
-    )?;
+    require_gt!(in_amount, 0, TradingBotError::InsufficientBalance);
+    if ctx.accounts.trading_vault.is_some() {
+        ctx.accounts.fund_from_vault(in_amount)?;
+    } else {
+        require_gte!(
+            ctx.accounts.user_token_account.amount,
+            in_amount,
+            TradingBotError::InsufficientBalance
+        );
+
+        msg!("Transfer from user");
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_in_ata.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            in_amount,
+        )?;
+    }
 
     let escrow = &mut ctx.accounts.escrow;
     escrow.idx = application_idx;
@@ -97,6 +204,11 @@ pub fn setup_dca(
     escrow.completed = false;
     escrow.airdropped = false;
     escrow.bump = *ctx.bumps.get("escrow").unwrap();
+    escrow.beneficiary = *ctx.accounts.user.key;
+    escrow.cancel_policy = CancelPolicy::FullRefund;
+    // output and refunds of a vault-funded DCA go to the user, so they can't be redirected
+    escrow.config_locked = ctx.accounts.trading_vault.is_some();
+    escrow.cancelled = false;
 
     msg!("Construct open dca ctx");
     let idx_bytes = ctx.accounts.escrow.idx.to_le_bytes();
@@ -117,7 +229,7 @@ pub fn setup_dca(
         associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
     };
     let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.jup_dca.to_account_info(),
+        ctx.accounts.jup_dca_program.to_account_info(),
         open_dca_accounts,
         signer_seeds,
     );
@@ -0,0 +1,188 @@
+use crate::account_manager::UserPositions;
+use crate::constants::{FEED_REGISTRY_SEED, STRATEGY_SEED};
+use crate::dex::{jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID};
+use crate::events::PositionReduced;
+use crate::oracles::PythOracle;
+use crate::risk::RiskManager;
+use crate::types::DexType;
+use crate::utils::validate_deadline;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{FeedRegistry, Strategy},
+    strategy_seeds,
+};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct SoftUnwindPosition<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      constraint = user_positions.load()?.owner == strategy.owner
+        @ TradingBotError::MissingStrategy,
+    )]
+    user_positions: AccountLoader<'info, UserPositions>,
+
+    #[account(
+      mut,
+      token::mint=strategy.config.pair.base_mint,
+      token::authority=strategy,
+    )]
+    base_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::mint=strategy.config.pair.quote_mint,
+      token::authority=strategy,
+    )]
+    quote_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Resolved against the feed registry for the pair's base mint
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Checked against the supported swap programs
+    swap_program: UncheckedAccount<'info>,
+
+    upgrade_gate: UpgradeGate<'info>,
+}
+
+impl<'info> SoftUnwindPosition<'info> {
+    pub fn is_supported_swap_program(program_id: &Pubkey) -> bool {
+        *program_id == JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+            || *program_id == RAYDIUM_AMM_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    }
+
+    // The strategy PDA owns the token accounts, so it's flagged as a signer in the forwarded metas
+    fn invoke_swap(&self, accounts: &[AccountInfo<'info>], data: Vec<u8>) -> Result<()> {
+        let strategy_key = self.strategy.key();
+        let ix = Instruction {
+            program_id: self.swap_program.key(),
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    let is_signer = a.is_signer || a.key() == strategy_key;
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        let strategy = &self.strategy;
+        invoke_signed(&ix, accounts, &[strategy_seeds!(strategy)])?;
+        Ok(())
+    }
+}
+
+// Copy accounts mirror the leader into a strategy of the follower's own, held to the follower's
+// RiskParameters. Instead of refusing later mirrors once a position outgrows `max_position_size`,
+// the owner or a keeper on the strategy's allowlist sells just the excess base back into the
+// quote through a forwarded swap the strategy signs, at no worse than the oracle floor at the
+// strategy's slippage, and the tracked position shrinks by what was sold. Serum orders and
+// Jupiter routes are sized in base; LP liquidity isn't, so Raydium positions are left to the owner.
+pub fn soft_unwind_position<'info>(
+    ctx: Context<'_, '_, '_, 'info, SoftUnwindPosition<'info>>,
+    dex: DexType,
+    position_id: Pubkey,
+    swap_data: Vec<u8>,
+    deadline: i64,
+) -> Result<()> {
+    ctx.accounts.upgrade_gate.require_healthy()?;
+    validate_deadline(deadline)?;
+    require!(
+        SoftUnwindPosition::is_supported_swap_program(ctx.accounts.swap_program.key),
+        TradingBotError::InvalidDexType
+    );
+    require!(
+        dex != DexType::Raydium,
+        TradingBotError::InvalidPositionReduction
+    );
+    let strategy = &ctx.accounts.strategy;
+    let keeper = ctx.accounts.keeper.key();
+    require!(
+        keeper == strategy.owner || strategy.keeper_allowlist.contains(&keeper),
+        TradingBotError::KeeperNotAllowed
+    );
+    require!(!strategy.executing, TradingBotError::ExecutionInProgress);
+    // the limit is compared in quote, the only accounting currency that needs no further feeds
+    require!(
+        !strategy.accounting.is_set() || strategy.accounting.is_quote(&strategy.config.pair),
+        TradingBotError::InvalidStrategyConfig
+    );
+
+    let size = {
+        let mut positions = ctx.accounts.user_positions.load_mut()?;
+        let (live, _) = positions
+            .position_size_mut(dex, position_id)
+            .ok_or(TradingBotError::PositionNotFound)?;
+        *live
+    };
+    let price = PythOracle::get_pair_price(
+        &ctx.accounts.feed_registry,
+        &strategy.config.pair,
+        &ctx.accounts.price_feed,
+        u64::MAX,
+    )?;
+    let excess =
+        RiskManager::soft_unwind_size(size, price, strategy.risk_parameters.max_position_size)?;
+    require_gt!(excess, 0, TradingBotError::PositionWithinLimits);
+    msg!("Unwinding {} of {} base", excess, size);
+
+    let base_before = ctx.accounts.base_account.amount;
+    let quote_before = ctx.accounts.quote_account.amount;
+    ctx.accounts
+        .invoke_swap(ctx.remaining_accounts, swap_data)?;
+    ctx.accounts.base_account.reload()?;
+    ctx.accounts.quote_account.reload()?;
+
+    let sold = math::checked_sub(base_before, ctx.accounts.base_account.amount)?;
+    let received = math::checked_sub(ctx.accounts.quote_account.amount, quote_before)?;
+    require!(
+        sold > 0 && sold <= excess,
+        TradingBotError::InvalidPositionReduction
+    );
+    let min_out = RiskManager::oracle_min_out(
+        sold,
+        price,
+        false,
+        ctx.accounts.strategy.config.slippage_bps,
+    )?;
+    require_gte!(received, min_out, TradingBotError::SlippageExceeded);
+
+    let mut positions = ctx.accounts.user_positions.load_mut()?;
+    let remaining = positions.reduce_position(dex, position_id, sold)?;
+    let now = Clock::get()?.unix_timestamp;
+    positions.last_update = now;
+    emit!(PositionReduced {
+        owner: positions.owner,
+        dex,
+        position_id,
+        reduced: sold,
+        remaining,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use crate::constants::{FEED_REGISTRY_SEED, MARKET_MAKER_SEED, STRATEGY_SEED};
+use crate::errors::TradingBotError;
+use crate::oracles::PythOracle;
+use crate::state::{FeedRegistry, MarketMaker, Strategy};
+use crate::strategy_seeds;
+use crate::valuation::Valuation;
+use crate::{math, types::TokenPair};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct DepositStrategyFunds<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+    )]
+    source: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::mint=source.mint,
+      token::authority=strategy,
+    )]
+    destination: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Resolved against the feed registry for the pair's base mint
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStrategyFunds<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      token::authority=strategy,
+    )]
+    source: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::mint=source.mint,
+      constraint = destination.owner == owner.key() @ TradingBotError::WithdrawalAddressNotAllowed,
+    )]
+    destination: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Resolved against the feed registry for the pair's base mint
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MoveMarketMakerFunds<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Box<Account<'info, MarketMaker>>,
+
+    #[account(
+      address=market_maker.quote_wallet
+    )]
+    quote_wallet: Box<Account<'info, TokenAccount>>,
+
+    // the market maker's base or quote wallet
+    #[account(
+      mut,
+      constraint = wallet.key() == market_maker.base_wallet
+        || wallet.key() == market_maker.quote_wallet @ TradingBotError::InvalidStrategyWallet,
+    )]
+    wallet: Box<Account<'info, TokenAccount>>,
+
+    // where a deposit comes from or a withdrawal goes to
+    #[account(
+      mut,
+      token::mint=wallet.mint,
+      token::authority=owner,
+    )]
+    counterparty: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Checked against the feed stored at init
+    #[account(
+      address=market_maker.price_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    token_program: Program<'info, Token>,
+}
+
+// What `amount` of the pair's base is worth in native quote at the oracle mid
+fn base_value(
+    feed_registry: &FeedRegistry,
+    price_feed: &AccountInfo,
+    pair: &TokenPair,
+    amount: u64,
+) -> Result<i64> {
+    let mid = PythOracle::get_pair_price(feed_registry, pair, price_feed, u64::MAX)?;
+    Ok(Valuation::quote_value(amount, mid)? as i64)
+}
+
+// A strategy's NAV only counts the pair's two mints, so tokens outside the pair (farm rewards, say)
+// move in and out without touching its contributions
+fn pair_value(
+    pair: &TokenPair,
+    mint: &Pubkey,
+    amount: u64,
+    feed_registry: &FeedRegistry,
+    price_feed: &AccountInfo,
+) -> Result<i64> {
+    if *mint == pair.quote_mint {
+        Ok(amount as i64)
+    } else if *mint == pair.base_mint {
+        base_value(feed_registry, price_feed, pair, amount)
+    } else {
+        Ok(0)
+    }
+}
+
+impl<'info> MoveMarketMakerFunds<'info> {
+    fn value(&self, amount: u64) -> Result<i64> {
+        if self.wallet.key() == self.market_maker.quote_wallet {
+            return Ok(amount as i64);
+        }
+        base_value(
+            &self.feed_registry,
+            &self.price_feed,
+            &TokenPair::new(self.wallet.mint, self.quote_wallet.mint),
+            amount,
+        )
+    }
+}
+
+// Deposits are booked into the strategy's net contributions at the oracle mid, so the summary's
+// P&L is its NAV less what the owner put in. Tokens sent to the PDA's accounts directly aren't
+// booked and show up as profit.
+pub fn deposit_strategy_funds(ctx: Context<DepositStrategyFunds>, amount: u64) -> Result<()> {
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.source.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let value = pair_value(
+        &ctx.accounts.strategy.config.pair,
+        &ctx.accounts.source.mint,
+        amount,
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.price_feed,
+    )?;
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.net_contributions = math::checked_add(strategy.net_contributions, value)?;
+    Ok(())
+}
+
+// A strategy's token accounts belong to its PDA, which signs its swaps; only the owner can take
+// the funds back out, and only to their own account. What comes out is taken off the net
+// contributions at the oracle mid.
+pub fn withdraw_strategy_funds(ctx: Context<WithdrawStrategyFunds>, amount: u64) -> Result<()> {
+    let strategy = &ctx.accounts.strategy;
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.source.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: strategy.to_account_info(),
+            },
+            &[strategy_seeds!(strategy)],
+        ),
+        amount,
+    )?;
+
+    let value = pair_value(
+        &strategy.config.pair,
+        &ctx.accounts.source.mint,
+        amount,
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.price_feed,
+    )?;
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.net_contributions = math::checked_sub(strategy.net_contributions, value)?;
+    Ok(())
+}
+
+// The market maker's wallets are the owner's own token accounts; moving funds through these two
+// keeps its net contributions, and so its P&L, in step
+pub fn deposit_market_maker_funds(ctx: Context<MoveMarketMakerFunds>, amount: u64) -> Result<()> {
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.counterparty.to_account_info(),
+                to: ctx.accounts.wallet.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let value = ctx.accounts.value(amount)?;
+    let market_maker = &mut ctx.accounts.market_maker;
+    market_maker.net_contributions = math::checked_add(market_maker.net_contributions, value)?;
+    Ok(())
+}
+
+pub fn withdraw_market_maker_funds(ctx: Context<MoveMarketMakerFunds>, amount: u64) -> Result<()> {
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.wallet.to_account_info(),
+                to: ctx.accounts.counterparty.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let value = ctx.accounts.value(amount)?;
+    let market_maker = &mut ctx.accounts.market_maker;
+    market_maker.net_contributions = math::checked_sub(market_maker.net_contributions, value)?;
+    Ok(())
+}
@@ -0,0 +1,495 @@
+use crate::constants::{
+    PUBLIC_PROFILE_SEED, SUBSCRIPTION_PERIOD, SUBSCRIPTION_PLAN_SEED,
+    SUBSCRIPTION_PROTOCOL_FEE_BPS, SUBSCRIPTION_SEED, TREASURY_SEED,
+};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{PublicProfile, Subscription, SubscriptionPlan},
+    subscription_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct CreateSubscriptionPlan<'info> {
+    #[account(mut)]
+    leader: Signer<'info>,
+
+    #[account(
+      constraint=profile.owner==leader.key(),
+      seeds = [PUBLIC_PROFILE_SEED, profile.strategy.as_ref()],
+      bump = profile.bump,
+    )]
+    profile: Box<Account<'info, PublicProfile>>,
+
+    fee_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = leader,
+      space = SubscriptionPlan::LEN,
+      seeds = [SUBSCRIPTION_PLAN_SEED, profile.key().as_ref()],
+      bump
+    )]
+    plan: Box<Account<'info, SubscriptionPlan>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSubscriptionPlan<'info> {
+    leader: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=leader,
+      seeds = [SUBSCRIPTION_PLAN_SEED, plan.profile.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, SubscriptionPlan>>,
+}
+
+#[derive(Accounts)]
+pub struct Subscribe<'info> {
+    #[account(mut)]
+    follower: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [SUBSCRIPTION_PLAN_SEED, plan.profile.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, SubscriptionPlan>>,
+
+    #[account(
+      address=plan.fee_mint
+    )]
+    fee_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      token::authority=follower,
+      token::mint=fee_mint,
+    )]
+    follower_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = follower,
+      space = Subscription::LEN,
+      seeds = [SUBSCRIPTION_SEED, plan.key().as_ref(), follower.key().as_ref()],
+      bump
+    )]
+    subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+      init,
+      payer=follower,
+      associated_token::authority=subscription,
+      associated_token::mint=fee_mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct RenewSubscription<'info> {
+    follower: Signer<'info>,
+
+    #[account(
+      seeds = [SUBSCRIPTION_PLAN_SEED, plan.profile.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, SubscriptionPlan>>,
+
+    #[account(
+      mut,
+      has_one=plan,
+      has_one=follower,
+      seeds = [SUBSCRIPTION_SEED, plan.key().as_ref(), follower.key().as_ref()],
+      bump = subscription.bump,
+    )]
+    subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+      mut,
+      associated_token::authority=subscription,
+      associated_token::mint=plan.fee_mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=follower,
+      token::mint=plan.fee_mint,
+    )]
+    follower_token_account: Box<Account<'info, TokenAccount>>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseSubscriptionFees<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    #[account(
+      seeds = [SUBSCRIPTION_PLAN_SEED, plan.profile.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, SubscriptionPlan>>,
+
+    #[account(
+      mut,
+      has_one=plan,
+      seeds = [SUBSCRIPTION_SEED, plan.key().as_ref(), subscription.follower.as_ref()],
+      bump = subscription.bump,
+    )]
+    subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+      address=plan.fee_mint
+    )]
+    fee_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      associated_token::authority=subscription,
+      associated_token::mint=fee_mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Checked against the plan
+    #[account(
+      address=plan.leader
+    )]
+    leader: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=payer,
+      associated_token::authority=leader,
+      associated_token::mint=fee_mint,
+    )]
+    leader_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Protocol treasury PDA, only used as a token authority
+    #[account(
+      seeds = [TREASURY_SEED],
+      bump
+    )]
+    treasury: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=payer,
+      associated_token::authority=treasury,
+      associated_token::mint=fee_mint,
+    )]
+    treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(mut)]
+    follower: Signer<'info>,
+
+    #[account(
+      mut,
+      seeds = [SUBSCRIPTION_PLAN_SEED, plan.profile.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, SubscriptionPlan>>,
+
+    #[account(
+      mut,
+      has_one=plan,
+      has_one=follower,
+      close=follower,
+      seeds = [SUBSCRIPTION_SEED, plan.key().as_ref(), follower.key().as_ref()],
+      bump = subscription.bump,
+    )]
+    subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+      address=plan.fee_mint
+    )]
+    fee_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      associated_token::authority=subscription,
+      associated_token::mint=fee_mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=follower,
+      associated_token::authority=follower,
+      associated_token::mint=fee_mint,
+    )]
+    follower_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Checked against the plan
+    #[account(
+      address=plan.leader
+    )]
+    leader: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=follower,
+      associated_token::authority=leader,
+      associated_token::mint=fee_mint,
+    )]
+    leader_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Protocol treasury PDA, only used as a token authority
+    #[account(
+      seeds = [TREASURY_SEED],
+      bump
+    )]
+    treasury: UncheckedAccount<'info>,
+
+    #[account(
+      init_if_needed,
+      payer=follower,
+      associated_token::authority=treasury,
+      associated_token::mint=fee_mint,
+    )]
+    treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> Subscribe<'info> {
+    pub fn prepay(
+        token_program: &Program<'info, Token>,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        authority: &Signer<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: authority.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+}
+
+impl<'info> ReleaseSubscriptionFees<'info> {
+    // Moves `amount` out of the subscription vault to each destination, skipping empty legs
+    pub fn pay_out(
+        token_program: &Program<'info, Token>,
+        subscription: &Account<'info, Subscription>,
+        vault: &Account<'info, TokenAccount>,
+        payments: &[(AccountInfo<'info>, u64)],
+    ) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[subscription_seeds!(subscription)];
+        for (to, amount) in payments {
+            let (to, amount) = (to.clone(), *amount);
+            if amount == 0 {
+                continue;
+            }
+            anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: vault.to_account_info(),
+                        to,
+                        authority: subscription.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn create_subscription_plan(
+    ctx: Context<CreateSubscriptionPlan>,
+    period_fee: u64,
+) -> Result<()> {
+    require_gt!(period_fee, 0, TradingBotError::InvalidSubscriptionParams);
+
+    let plan = &mut ctx.accounts.plan;
+    plan.leader = ctx.accounts.leader.key();
+    plan.profile = ctx.accounts.profile.key();
+    plan.fee_mint = ctx.accounts.fee_mint.key();
+    plan.period_fee = period_fee;
+    plan.subscriber_count = 0;
+    plan.is_active = true;
+    plan.bump = *ctx.bumps.get("plan").unwrap();
+
+    Ok(())
+}
+
+// Fee changes apply to renewals only; what followers already prepaid is untouched
+pub fn update_subscription_plan(
+    ctx: Context<UpdateSubscriptionPlan>,
+    period_fee: u64,
+    is_active: bool,
+) -> Result<()> {
+    require_gt!(period_fee, 0, TradingBotError::InvalidSubscriptionParams);
+    let plan = &mut ctx.accounts.plan;
+    plan.period_fee = period_fee;
+    plan.is_active = is_active;
+
+    Ok(())
+}
+
+pub fn subscribe(ctx: Context<Subscribe>, periods: u16) -> Result<()> {
+    let plan = &ctx.accounts.plan;
+    require!(plan.is_active, TradingBotError::SubscriptionInactive);
+    require_gt!(periods, 0, TradingBotError::InvalidSubscriptionParams);
+    let amount = math::checked_mul(plan.period_fee, periods as u64)?;
+    let duration = math::checked_mul(SUBSCRIPTION_PERIOD, periods as i64)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    msg!("Prepaying {} for {} periods", amount, periods);
+    Subscribe::prepay(
+        &ctx.accounts.token_program,
+        &ctx.accounts.follower_token_account,
+        &ctx.accounts.vault,
+        &ctx.accounts.follower,
+        amount,
+    )?;
+
+    let subscription = &mut ctx.accounts.subscription;
+    subscription.plan = ctx.accounts.plan.key();
+    subscription.follower = ctx.accounts.follower.key();
+    subscription.subscribed_at = now;
+    subscription.paid_until = now;
+    subscription.released_until = now;
+    subscription.escrowed = 0;
+    subscription.total_paid = 0;
+    subscription.bump = *ctx.bumps.get("subscription").unwrap();
+    subscription.extend(amount, duration, now)?;
+
+    let plan = &mut ctx.accounts.plan;
+    plan.subscriber_count = math::checked_add(plan.subscriber_count, 1)?;
+
+    Ok(())
+}
+
+pub fn renew_subscription(ctx: Context<RenewSubscription>, periods: u16) -> Result<()> {
+    let plan = &ctx.accounts.plan;
+    require!(plan.is_active, TradingBotError::SubscriptionInactive);
+    require_gt!(periods, 0, TradingBotError::InvalidSubscriptionParams);
+    let amount = math::checked_mul(plan.period_fee, periods as u64)?;
+    let duration = math::checked_mul(SUBSCRIPTION_PERIOD, periods as i64)?;
+
+    ctx.accounts
+        .subscription
+        .extend(amount, duration, Clock::get()?.unix_timestamp)?;
+    Subscribe::prepay(
+        &ctx.accounts.token_program,
+        &ctx.accounts.follower_token_account,
+        &ctx.accounts.vault,
+        &ctx.accounts.follower,
+        amount,
+    )
+}
+
+// Permissionless crank paying the leader what has been earned so far, less the protocol cut
+pub fn release_subscription_fees(ctx: Context<ReleaseSubscriptionFees>) -> Result<()> {
+    let earned = ctx
+        .accounts
+        .subscription
+        .release(Clock::get()?.unix_timestamp)?;
+    let (leader_share, protocol_share) =
+        SubscriptionPlan::split_fee(earned, SUBSCRIPTION_PROTOCOL_FEE_BPS)?;
+    msg!(
+        "Releasing {} to leader, {} to protocol",
+        leader_share,
+        protocol_share
+    );
+
+    ReleaseSubscriptionFees::pay_out(
+        &ctx.accounts.token_program,
+        &ctx.accounts.subscription,
+        &ctx.accounts.vault,
+        &[
+            (
+                ctx.accounts.leader_token_account.to_account_info(),
+                leader_share,
+            ),
+            (
+                ctx.accounts.treasury_token_account.to_account_info(),
+                protocol_share,
+            ),
+        ],
+    )
+}
+
+// Settles the fees earned up to now and refunds the unused remainder pro rata
+pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+    let earned = ctx
+        .accounts
+        .subscription
+        .release(Clock::get()?.unix_timestamp)?;
+    let refund = ctx.accounts.subscription.escrowed;
+    let (leader_share, protocol_share) =
+        SubscriptionPlan::split_fee(earned, SUBSCRIPTION_PROTOCOL_FEE_BPS)?;
+    msg!("Refunding {}, releasing {}", refund, earned);
+
+    ReleaseSubscriptionFees::pay_out(
+        &ctx.accounts.token_program,
+        &ctx.accounts.subscription,
+        &ctx.accounts.vault,
+        &[
+            (
+                ctx.accounts.leader_token_account.to_account_info(),
+                leader_share,
+            ),
+            (
+                ctx.accounts.treasury_token_account.to_account_info(),
+                protocol_share,
+            ),
+            (
+                ctx.accounts.follower_token_account.to_account_info(),
+                refund,
+            ),
+        ],
+    )?;
+    ctx.accounts.subscription.escrowed = 0;
+
+    let signer_seeds: &[&[&[u8]]] = &[subscription_seeds!(ctx.accounts.subscription)];
+    anchor_spl::token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        anchor_spl::token::CloseAccount {
+            account: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.follower.to_account_info(),
+            authority: ctx.accounts.subscription.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    let plan = &mut ctx.accounts.plan;
+    plan.subscriber_count = math::checked_sub(plan.subscriber_count, 1)?;
+
+    Ok(())
+}
@@ -0,0 +1,228 @@
+use crate::constants::{FEED_REGISTRY_SEED, TRADING_VAULT_SEED, VAULT_LOG_SEED};
+use crate::dex::jupiter::JUPITER_V6_PROGRAM_ID;
+use crate::events::DustSwept;
+use crate::oracles::PythOracle;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{FeedRegistry, TradingVault, VaultLog, VaultOp},
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token::{CloseAccount, Mint, Token, TokenAccount};
+
+// Accounts each swept balance takes ahead of its swap accounts: the vault's token account, its
+// mint and the mint's price feed
+const DUST_ACCOUNTS: usize = 3;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DustSwap {
+    pub swap_accounts_len: u8,
+    // empty for an account that is already empty and only gets closed
+    pub swap_data: Vec<u8>,
+    pub min_base_out: u64,
+}
+
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    // receives the rent of the closed accounts
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=trading_vault.token_account
+    )]
+    vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Only Jupiter is used to sell dust
+    #[account(
+      address=JUPITER_V6_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    )]
+    swap_program: UncheckedAccount<'info>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetDustThreshold<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+}
+
+impl<'info> SweepDust<'info> {
+    // USD value of `dust`'s balance, with USD_DECIMALS
+    pub fn usd_value(
+        &self,
+        dust: &TokenAccount,
+        mint: &Mint,
+        price_feed: &AccountInfo,
+    ) -> Result<u64> {
+        let usd_price =
+            PythOracle::get_usd_price(&self.feed_registry, &dust.mint, price_feed, u64::MAX)?;
+        PythOracle::usd_value(dust.amount, mint.decimals, usd_price)
+    }
+
+    // The vault signs the swap, so its PDA is flagged as a signer in the forwarded metas
+    pub fn invoke_swap(&self, accounts: &[AccountInfo<'info>], data: Vec<u8>) -> Result<()> {
+        let vault_key = self.trading_vault.key();
+        let ix = Instruction {
+            program_id: self.swap_program.key(),
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    let is_signer = a.is_signer || a.key() == vault_key;
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        invoke_signed(&ix, accounts, &[trading_vault_seeds!(self.trading_vault)])?;
+        Ok(())
+    }
+
+    pub fn close(&self, dust: &AccountInfo<'info>) -> Result<()> {
+        anchor_spl::token::close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: dust.clone(),
+                destination: self.owner.to_account_info(),
+                authority: self.trading_vault.to_account_info(),
+            },
+            &[trading_vault_seeds!(self.trading_vault)],
+        ))
+    }
+}
+
+// Sells every listed balance the vault holds outside its own mint into that mint through Jupiter
+// and closes the emptied accounts, returning their rent to the owner. Each balance has to be worth
+// at most the vault's dust threshold. Remaining accounts are, per entry, the token account, its
+// mint and price feed, followed by `swap_accounts_len` swap accounts.
+pub fn sweep_dust<'info>(
+    ctx: Context<'_, '_, '_, 'info, SweepDust<'info>>,
+    swaps: Vec<DustSwap>,
+) -> Result<()> {
+    let vault_key = ctx.accounts.trading_vault.key();
+    let threshold = ctx.accounts.trading_vault.dust_threshold_usd;
+    let base_before = ctx.accounts.vault_token_account.amount;
+    let mut remaining = ctx.remaining_accounts;
+    let mut swept_value = 0u64;
+    let accounts_closed = swaps.len() as u32;
+
+    for swap in swaps {
+        let entry_len = DUST_ACCOUNTS + swap.swap_accounts_len as usize;
+        require_gte!(
+            remaining.len(),
+            entry_len,
+            TradingBotError::InvalidDustAccount
+        );
+        let (entry, rest) = remaining.split_at(entry_len);
+        remaining = rest;
+
+        let mut dust = Account::<TokenAccount>::try_from(&entry[0])?;
+        let mint = Account::<Mint>::try_from(&entry[1])?;
+        require!(
+            dust.owner == vault_key
+                && dust.mint == mint.key()
+                && dust.mint != ctx.accounts.trading_vault.mint,
+            TradingBotError::InvalidDustAccount
+        );
+
+        if dust.amount > 0 {
+            let value = ctx.accounts.usd_value(&dust, &mint, &entry[2])?;
+            require_gte!(threshold, value, TradingBotError::DustAboveThreshold);
+            swept_value = math::checked_add(swept_value, value)?;
+
+            let base_before = ctx.accounts.vault_token_account.amount;
+            ctx.accounts
+                .invoke_swap(&entry[DUST_ACCOUNTS..], swap.swap_data)?;
+            dust.reload()?;
+            ctx.accounts.vault_token_account.reload()?;
+            // the whole balance has to go for the account to close
+            require_eq!(dust.amount, 0, TradingBotError::InvalidDustAccount);
+            let received = math::checked_sub(ctx.accounts.vault_token_account.amount, base_before)?;
+            require_gte!(
+                received,
+                swap.min_base_out,
+                TradingBotError::SlippageExceeded
+            );
+        }
+        ctx.accounts.close(&entry[0])?;
+    }
+    require!(remaining.is_empty(), TradingBotError::InvalidDustAccount);
+
+    let base_after = ctx.accounts.vault_token_account.amount;
+    ctx.accounts.trading_vault.check_invariant(base_after)?;
+    let received = math::checked_sub(base_after, base_before)?;
+    VaultLog::append_if_initialized(
+        &ctx.accounts.vault_log,
+        VaultOp::Preset,
+        ctx.accounts.swap_program.key(),
+        received,
+        &ctx.accounts.trading_vault,
+        Some(base_after),
+    )?;
+    emit!(DustSwept {
+        trading_vault: vault_key,
+        accounts_closed,
+        received,
+        swept_value,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// USD value, with USD_DECIMALS, below which a balance counts as dust
+pub fn set_dust_threshold(ctx: Context<SetDustThreshold>, threshold_usd: u64) -> Result<()> {
+    ctx.accounts.trading_vault.dust_threshold_usd = threshold_usd;
+    VaultLog::append_if_initialized(
+        &ctx.accounts.vault_log,
+        VaultOp::Preset,
+        Pubkey::default(),
+        threshold_usd,
+        &ctx.accounts.trading_vault,
+        None,
+    )
+}
@@ -0,0 +1,291 @@
+use crate::constants::TOKEN_LAUNCH_SEED;
+use crate::events::TokenLaunchPurchase;
+use crate::{errors::TradingBotError, math, state::TokenLaunch, token_launch_seeds};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct StartTokenLaunch<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    sale_mint: Box<Account<'info, Mint>>,
+    quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+      token::mint=sale_mint,
+    )]
+    owner_sale_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = TokenLaunch::LEN,
+      seeds = [TOKEN_LAUNCH_SEED, owner.key().as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+      init,
+      payer=owner,
+      associated_token::authority=launch,
+      associated_token::mint=sale_mint,
+    )]
+    launch_vault: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct BuyTokenLaunch<'info> {
+    #[account(mut)]
+    buyer: Signer<'info>,
+
+    /// CHECK: Receives the proceeds
+    #[account(
+      address=launch.owner
+    )]
+    owner: UncheckedAccount<'info>,
+
+    #[account(
+      address=launch.sale_mint
+    )]
+    sale_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=launch.quote_mint
+    )]
+    quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      mut,
+      seeds = [TOKEN_LAUNCH_SEED, launch.owner.as_ref(), launch.idx.to_le_bytes().as_ref()],
+      bump = launch.bump,
+    )]
+    launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+      mut,
+      associated_token::authority=launch,
+      associated_token::mint=sale_mint,
+    )]
+    launch_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=buyer,
+      token::mint=quote_mint,
+    )]
+    buyer_quote_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=buyer,
+      associated_token::authority=buyer,
+      associated_token::mint=sale_mint,
+    )]
+    buyer_sale_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      init_if_needed,
+      payer=buyer,
+      associated_token::authority=owner,
+      associated_token::mint=quote_mint,
+    )]
+    owner_quote_account: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTokenLaunch<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      close=owner,
+      seeds = [TOKEN_LAUNCH_SEED, owner.key().as_ref(), launch.idx.to_le_bytes().as_ref()],
+      bump = launch.bump,
+    )]
+    launch: Box<Account<'info, TokenLaunch>>,
+
+    #[account(
+      mut,
+      associated_token::authority=launch,
+      associated_token::mint=launch.sale_mint,
+    )]
+    launch_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+      token::mint=launch.sale_mint,
+    )]
+    owner_sale_account: Box<Account<'info, TokenAccount>>,
+
+    token_program: Program<'info, Token>,
+}
+
+// Locks `amount` of the sale mint and lists it from `start_at` for `duration` seconds
+#[allow(clippy::too_many_arguments)]
+pub fn start_token_launch(
+    ctx: Context<StartTokenLaunch>,
+    idx: u64,
+    amount: u64,
+    start_price: u64,
+    floor_price: u64,
+    start_at: i64,
+    duration: i64,
+    interval: i64,
+    interval_cap: u64,
+) -> Result<()> {
+    require_gt!(amount, 0, TradingBotError::InvalidLaunchParams);
+    require!(
+        floor_price > 0 && floor_price <= start_price,
+        TradingBotError::InvalidLaunchParams
+    );
+    require!(
+        interval > 0 && interval <= duration,
+        TradingBotError::InvalidLaunchParams
+    );
+    require_gt!(interval_cap, 0, TradingBotError::InvalidLaunchParams);
+    let start_at = start_at.max(Clock::get()?.unix_timestamp);
+
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_sale_account.to_account_info(),
+                to: ctx.accounts.launch_vault.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let launch = &mut ctx.accounts.launch;
+    launch.idx = idx;
+    launch.owner = ctx.accounts.owner.key();
+    launch.sale_mint = ctx.accounts.sale_mint.key();
+    launch.quote_mint = ctx.accounts.quote_mint.key();
+    launch.total_amount = amount;
+    launch.sold_amount = 0;
+    launch.start_price = start_price;
+    launch.floor_price = floor_price;
+    launch.start_at = start_at;
+    launch.end_at = math::checked_add(start_at, duration)?;
+    launch.interval = interval;
+    launch.interval_cap = interval_cap;
+    launch.current_interval = 0;
+    launch.sold_in_interval = 0;
+    launch.proceeds = 0;
+    launch.bump = *ctx.bumps.get("launch").unwrap();
+
+    msg!("Launch from {} to floor {}", start_price, floor_price);
+    Ok(())
+}
+
+// Buys up to `amount` at the current price, cut to the inventory and the interval's cap left
+pub fn buy_token_launch(ctx: Context<BuyTokenLaunch>, amount: u64, max_price: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let launch = &ctx.accounts.launch;
+    require!(launch.is_live(now), TradingBotError::LaunchNotLive);
+    let interval_remaining = launch.interval_remaining(now);
+    require_gt!(
+        interval_remaining,
+        0,
+        TradingBotError::LaunchIntervalCapReached
+    );
+    let amount = amount.min(launch.remaining()).min(interval_remaining);
+    require_gt!(amount, 0, TradingBotError::InvalidLaunchParams);
+
+    let price = launch.current_price(now)?;
+    require_gte!(max_price, price, TradingBotError::AuctionPriceAboveLimit);
+    let payment = TokenLaunch::payment_for(amount, price)?;
+    msg!("Buying {} at {} for {}", amount, price, payment);
+
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_quote_account.to_account_info(),
+                to: ctx.accounts.owner_quote_account.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        ),
+        payment,
+    )?;
+
+    let idx_bytes = ctx.accounts.launch.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[token_launch_seeds!(ctx.accounts.launch, idx_bytes)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.launch_vault.to_account_info(),
+                to: ctx.accounts.buyer_sale_account.to_account_info(),
+                authority: ctx.accounts.launch.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    ctx.accounts.launch.record_sale(amount, payment, now)?;
+    emit!(TokenLaunchPurchase {
+        launch: ctx.accounts.launch.key(),
+        buyer: ctx.accounts.buyer.key(),
+        amount,
+        price,
+        payment,
+    });
+
+    Ok(())
+}
+
+// The owner can end the sale at any time and take back whatever is unsold
+pub fn close_token_launch(ctx: Context<CloseTokenLaunch>) -> Result<()> {
+    let idx_bytes = ctx.accounts.launch.idx.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[token_launch_seeds!(ctx.accounts.launch, idx_bytes)];
+    let unsold = ctx.accounts.launch_vault.amount;
+    msg!(
+        "Closing launch with {} sold, {} returned",
+        ctx.accounts.launch.sold_amount,
+        unsold
+    );
+
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.launch_vault.to_account_info(),
+                to: ctx.accounts.owner_sale_account.to_account_info(),
+                authority: ctx.accounts.launch.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        unsold,
+    )?;
+    anchor_spl::token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.launch_vault.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.launch.to_account_info(),
+        },
+        signer_seeds,
+    ))
+}
@@ -0,0 +1,164 @@
+use crate::constants::{STRATEGY_SEED, TRADE_APPROVAL_SEED};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{Strategy, TradeApproval},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ConfigureApprovalPolicy<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    // required while a policy is in force, so the owner key alone can't lift it
+    co_signer: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestTradeApproval<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = TradeApproval::LEN,
+      seeds = [TRADE_APPROVAL_SEED, strategy.key().as_ref()],
+      bump
+    )]
+    trade_approval: Box<Account<'info, TradeApproval>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveTrade<'info> {
+    co_signer: Signer<'info>,
+
+    #[account(
+      constraint=strategy.approval_policy.co_signer==co_signer.key() @ TradingBotError::LargeTradeNotApproved,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    #[account(
+      mut,
+      has_one=strategy,
+      seeds = [TRADE_APPROVAL_SEED, strategy.key().as_ref()],
+      bump = trade_approval.bump,
+    )]
+    trade_approval: Box<Account<'info, TradeApproval>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTradeApproval<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      close=owner,
+      seeds = [TRADE_APPROVAL_SEED, trade_approval.strategy.as_ref()],
+      bump = trade_approval.bump,
+    )]
+    trade_approval: Box<Account<'info, TradeApproval>>,
+}
+
+// Sets the co-signer and the size above which swaps need its approval; a zero threshold turns the
+// policy off. Once a policy is in force every change needs the current co-signer's signature too.
+pub fn configure_approval_policy(
+    ctx: Context<ConfigureApprovalPolicy>,
+    co_signer: Pubkey,
+    large_trade_threshold: u64,
+) -> Result<()> {
+    let policy = ctx.accounts.strategy.approval_policy;
+    if policy.is_enabled() {
+        let signer = ctx
+            .accounts
+            .co_signer
+            .as_ref()
+            .ok_or(TradingBotError::LargeTradeNotApproved)?;
+        require_keys_eq!(
+            signer.key(),
+            policy.co_signer,
+            TradingBotError::LargeTradeNotApproved
+        );
+    }
+    if large_trade_threshold > 0 {
+        require!(
+            co_signer != Pubkey::default() && co_signer != ctx.accounts.owner.key(),
+            TradingBotError::InvalidApprovalPolicy
+        );
+    }
+
+    let strategy = &mut ctx.accounts.strategy;
+    strategy.approval_policy.co_signer = co_signer;
+    strategy.approval_policy.large_trade_threshold = large_trade_threshold;
+    strategy.updated_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+// Queues one large execution for the co-signer; only one request per strategy can be pending
+pub fn request_trade_approval(
+    ctx: Context<RequestTradeApproval>,
+    max_amount: u64,
+    ttl: i64,
+) -> Result<()> {
+    require!(
+        ctx.accounts.strategy.approval_policy.is_enabled(),
+        TradingBotError::InvalidApprovalPolicy
+    );
+    require_gt!(max_amount, 0, TradingBotError::InvalidApprovalPolicy);
+    require_gt!(ttl, 0, TradingBotError::InvalidApprovalPolicy);
+
+    let strategy = &ctx.accounts.strategy;
+    let now = strategy.clock_now()?;
+    let expires_at = math::checked_add(now, strategy.config.time_basis.from_seconds(ttl))?;
+    let trade_approval = &mut ctx.accounts.trade_approval;
+    trade_approval.strategy = ctx.accounts.strategy.key();
+    trade_approval.owner = ctx.accounts.owner.key();
+    trade_approval.max_amount = max_amount;
+    trade_approval.requested_at = now;
+    trade_approval.expires_at = expires_at;
+    trade_approval.approved = false;
+    trade_approval.bump = *ctx.bumps.get("trade_approval").unwrap();
+
+    Ok(())
+}
+
+pub fn approve_trade(ctx: Context<ApproveTrade>) -> Result<()> {
+    let now = ctx.accounts.strategy.clock_now()?;
+    let trade_approval = &mut ctx.accounts.trade_approval;
+    require_gte!(
+        trade_approval.expires_at,
+        now,
+        TradingBotError::LargeTradeNotApproved
+    );
+    msg!("Approved trades up to {}", trade_approval.max_amount);
+    trade_approval.approved = true;
+
+    Ok(())
+}
+
+// Withdraws a pending or unused approval, e.g. once it has expired
+pub fn cancel_trade_approval(_ctx: Context<CancelTradeApproval>) -> Result<()> {
+    Ok(())
+}
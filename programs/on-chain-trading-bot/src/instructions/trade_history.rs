@@ -0,0 +1,32 @@
+use crate::constants::TRADE_HISTORY_SEED;
+use crate::state::{FillLeaf, TradeHistory, RECENT_FILLS};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitTradeHistory<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = TradeHistory::LEN,
+      seeds = [TRADE_HISTORY_SEED, owner.key().as_ref()],
+      bump
+    )]
+    trade_history: Box<Account<'info, TradeHistory>>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn init_trade_history(ctx: Context<InitTradeHistory>) -> Result<()> {
+    let trade_history = &mut ctx.accounts.trade_history;
+    trade_history.owner = ctx.accounts.owner.key();
+    trade_history.leaf_count = 0;
+    trade_history.accumulator = [0u8; 32];
+    trade_history.head = 0;
+    trade_history.recent = [FillLeaf::default(); RECENT_FILLS];
+    trade_history.bump = *ctx.bumps.get("trade_history").unwrap();
+
+    Ok(())
+}
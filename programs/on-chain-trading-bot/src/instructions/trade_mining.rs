@@ -0,0 +1,151 @@
+use crate::constants::{TRADE_MINING_EPOCH_SEED, USER_EPOCH_VOLUME_SEED};
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{RewardsDistributor, TradeMiningEpoch, UserEpochVolume},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct StartMiningEpoch<'info> {
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    #[account(
+      has_one=admin,
+    )]
+    distributor: Box<Account<'info, RewardsDistributor>>,
+
+    quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = admin,
+      space = TradeMiningEpoch::LEN,
+      seeds = [TRADE_MINING_EPOCH_SEED, distributor.key().as_ref(), epoch.to_le_bytes().as_ref()],
+      bump
+    )]
+    mining_epoch: Box<Account<'info, TradeMiningEpoch>>,
+
+    system_program: Program<'info, System>,
+}
+
+// Volume is only ever credited by the fills and swaps themselves; a trader opens their account
+// for the epoch and passes it along with the epoch to have their trades counted
+#[derive(Accounts)]
+pub struct OpenEpochVolume<'info> {
+    #[account(mut)]
+    user: Signer<'info>,
+
+    /// CHECK: Strategy the volume will be swapped through, the default key for direct fills
+    strategy: UncheckedAccount<'info>,
+
+    mining_epoch: Box<Account<'info, TradeMiningEpoch>>,
+
+    #[account(
+      init,
+      payer = user,
+      space = UserEpochVolume::LEN,
+      seeds = [USER_EPOCH_VOLUME_SEED, mining_epoch.key().as_ref(), user.key().as_ref(), strategy.key().as_ref()],
+      bump
+    )]
+    user_volume: Box<Account<'info, UserEpochVolume>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AllocateEpochRewards<'info> {
+    #[account(mut)]
+    mining_epoch: Box<Account<'info, TradeMiningEpoch>>,
+}
+
+pub fn start_mining_epoch(
+    ctx: Context<StartMiningEpoch>,
+    epoch: u64,
+    start_at: i64,
+    end_at: i64,
+    reward_pool: u64,
+) -> Result<()> {
+    require_gt!(end_at, start_at, TradingBotError::InvalidCalculation);
+
+    let mining_epoch = &mut ctx.accounts.mining_epoch;
+    mining_epoch.distributor = ctx.accounts.distributor.key();
+    mining_epoch.admin = ctx.accounts.admin.key();
+    mining_epoch.epoch = epoch;
+    mining_epoch.quote_mint = ctx.accounts.quote_mint.key();
+    mining_epoch.start_at = start_at;
+    mining_epoch.end_at = end_at;
+    mining_epoch.reward_pool = reward_pool;
+    mining_epoch.total_fee_volume = 0;
+    mining_epoch.total_fees = 0;
+    mining_epoch.allocated = 0;
+    mining_epoch.num_participants = 0;
+    mining_epoch.bump = *ctx.bumps.get("mining_epoch").unwrap();
+
+    Ok(())
+}
+
+pub fn open_epoch_volume(ctx: Context<OpenEpochVolume>) -> Result<()> {
+    let mining_epoch = &ctx.accounts.mining_epoch;
+    require_gt!(
+        mining_epoch.end_at,
+        Clock::get()?.unix_timestamp,
+        TradingBotError::EpochNotActive
+    );
+
+    let user_volume = &mut ctx.accounts.user_volume;
+    user_volume.epoch = mining_epoch.key();
+    user_volume.user = ctx.accounts.user.key();
+    user_volume.strategy = ctx.accounts.strategy.key();
+    user_volume.fee_volume = 0;
+    user_volume.fees_paid = 0;
+    user_volume.trade_count = 0;
+    user_volume.allocation = 0;
+    user_volume.allocated = false;
+    user_volume.bump = *ctx.bumps.get("user_volume").unwrap();
+
+    Ok(())
+}
+
+// Permissionless once the epoch ends; volume accounts are passed as remaining accounts and can be
+// processed across several transactions. The admin publishes the allocations as the next merkle
+// root of the epoch's distributor.
+pub fn allocate_epoch_rewards<'info>(
+    ctx: Context<'_, '_, '_, 'info, AllocateEpochRewards<'info>>,
+) -> Result<()> {
+    let mining_epoch = &mut ctx.accounts.mining_epoch;
+    require_gte!(
+        Clock::get()?.unix_timestamp,
+        mining_epoch.end_at,
+        TradingBotError::EpochNotEnded
+    );
+
+    for account_info in ctx.remaining_accounts.iter() {
+        let mut user_volume = Account::<UserEpochVolume>::try_from(account_info)?;
+        require_keys_eq!(
+            user_volume.epoch,
+            mining_epoch.key(),
+            TradingBotError::EpochMismatch
+        );
+        if user_volume.allocated {
+            continue;
+        }
+
+        let allocation = mining_epoch.allocation_for(user_volume.fee_volume)?;
+        user_volume.allocation = allocation;
+        user_volume.allocated = true;
+        mining_epoch.allocated = math::checked_add(mining_epoch.allocated, allocation)?;
+        user_volume.exit(ctx.program_id)?;
+    }
+
+    require_gte!(
+        mining_epoch.reward_pool,
+        mining_epoch.allocated,
+        TradingBotError::ClaimExceedsMaximum
+    );
+
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use crate::constants::{
+    DEFAULT_DUST_THRESHOLD_USD, TRADING_VAULT_SEED, UPGRADE_GUARD_SEED, VAULT_LOG_SEED,
+};
+use crate::{
+    errors::TradingBotError,
+    state::{TradingVault, UpgradeGuard, VaultLog, VaultOp, WithdrawalAllowlist},
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct InitTradingVault<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = TradingVault::LEN,
+      seeds = [TRADING_VAULT_SEED, owner.key().as_ref(), mint.key().as_ref()],
+      bump
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      init,
+      payer=owner,
+      associated_token::authority=trading_vault,
+      associated_token::mint=mint,
+    )]
+    vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    // numbers the vault for post_upgrade_check
+    #[account(
+      mut,
+      seeds = [UPGRADE_GUARD_SEED],
+      bump = upgrade_guard.bump,
+    )]
+    upgrade_guard: Box<Account<'info, UpgradeGuard>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct TradingVaultTransfer<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=trading_vault.token_account
+    )]
+    vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      token::authority=owner,
+      token::mint=trading_vault.mint,
+    )]
+    owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTradingVault<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=trading_vault.token_account
+    )]
+    vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    // the owner's own account, or any allowlisted wallet's while the allowlist is enforced
+    #[account(
+      mut,
+      token::mint=trading_vault.mint,
+    )]
+    destination: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ManageWithdrawalAllowlist<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump,
+    )]
+    vault_log: UncheckedAccount<'info>,
+}
+
+impl<'info> ManageWithdrawalAllowlist<'info> {
+    pub fn log(&self, op: VaultOp, key: Pubkey, amount: u64) -> Result<()> {
+        VaultLog::append_if_initialized(&self.vault_log, op, key, amount, &self.trading_vault, None)
+    }
+}
+
+pub fn init_trading_vault(ctx: Context<InitTradingVault>) -> Result<()> {
+    let trading_vault = &mut ctx.accounts.trading_vault;
+    trading_vault.owner = ctx.accounts.owner.key();
+    trading_vault.creator = ctx.accounts.owner.key();
+    trading_vault.mint = ctx.accounts.mint.key();
+    trading_vault.token_account = ctx.accounts.vault_token_account.key();
+    trading_vault.reserved = 0;
+    trading_vault.open_reservations = 0;
+    trading_vault.withdrawal_allowlist = WithdrawalAllowlist::default();
+    trading_vault.dust_threshold_usd = DEFAULT_DUST_THRESHOLD_USD;
+    trading_vault.seq = ctx.accounts.upgrade_guard.next_vault_seq()?;
+    trading_vault.bump = *ctx.bumps.get("trading_vault").unwrap();
+
+    Ok(())
+}
+
+pub fn deposit_trading_vault(ctx: Context<TradingVaultTransfer>, amount: u64) -> Result<()> {
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    ctx.accounts.vault_token_account.reload()?;
+    VaultLog::append_if_initialized(
+        &ctx.accounts.vault_log,
+        VaultOp::Deposit,
+        ctx.accounts.owner.key(),
+        amount,
+        &ctx.accounts.trading_vault,
+        Some(ctx.accounts.vault_token_account.amount),
+    )
+}
+
+// Only the unreserved balance can leave the vault
+pub fn withdraw_trading_vault(ctx: Context<WithdrawTradingVault>, amount: u64) -> Result<()> {
+    let trading_vault = &ctx.accounts.trading_vault;
+    let recipient = ctx.accounts.destination.owner;
+    let now = Clock::get()?.unix_timestamp;
+    if trading_vault.withdrawal_allowlist.is_enforced(now) {
+        require!(
+            trading_vault.withdrawal_allowlist.allows(&recipient, now),
+            TradingBotError::WithdrawalAddressNotAllowed
+        );
+    } else {
+        require_keys_eq!(
+            recipient,
+            trading_vault.owner,
+            TradingBotError::WithdrawalAddressNotAllowed
+        );
+    }
+    require_gte!(
+        trading_vault.available(ctx.accounts.vault_token_account.amount),
+        amount,
+        TradingBotError::InsufficientAvailableBalance
+    );
+
+    let signer_seeds: &[&[&[u8]]] = &[trading_vault_seeds!(ctx.accounts.trading_vault)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.trading_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    ctx.accounts.vault_token_account.reload()?;
+    ctx.accounts
+        .trading_vault
+        .check_invariant(ctx.accounts.vault_token_account.amount)?;
+
+    VaultLog::append_if_initialized(
+        &ctx.accounts.vault_log,
+        VaultOp::Withdraw,
+        recipient,
+        amount,
+        &ctx.accounts.trading_vault,
+        Some(ctx.accounts.vault_token_account.amount),
+    )?;
+
+    Ok(())
+}
+
+// Takes effect after WITHDRAWAL_ACTIVATION_DELAY
+pub fn add_withdrawal_address(
+    ctx: Context<ManageWithdrawalAllowlist>,
+    address: Pubkey,
+) -> Result<()> {
+    ctx.accounts
+        .trading_vault
+        .withdrawal_allowlist
+        .add(address, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.log(VaultOp::AllowlistAdd, address, 0)
+}
+
+pub fn remove_withdrawal_address(
+    ctx: Context<ManageWithdrawalAllowlist>,
+    address: Pubkey,
+) -> Result<()> {
+    ctx.accounts
+        .trading_vault
+        .withdrawal_allowlist
+        .remove(address)?;
+    ctx.accounts.log(VaultOp::AllowlistRemove, address, 0)
+}
+
+// Enforcing applies at once; lifting enforcement is delayed like a new address
+pub fn set_withdrawal_allowlist_enforced(
+    ctx: Context<ManageWithdrawalAllowlist>,
+    enforced: bool,
+) -> Result<()> {
+    ctx.accounts
+        .trading_vault
+        .withdrawal_allowlist
+        .set_enforced(enforced, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.log(
+        VaultOp::AllowlistEnforce,
+        Pubkey::default(),
+        enforced as u64,
+    )
+}
@@ -0,0 +1,660 @@
+use crate::constants::{
+    FEED_REGISTRY_SEED, LIMIT_ORDER_SEED, MAX_SLIPPAGE_BPS, OTC_OFFER_SEED, PRICE_PRECISION,
+    TRADING_VAULT_SEED, TREASURY_PLAN_SEED, VAULT_LOG_SEED,
+};
+use crate::events::TreasuryDiversified;
+use crate::instructions::RotateDepegGuard;
+use crate::jitter;
+use crate::oracles::PythOracle;
+use crate::utils::validate_deadline;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{
+        DepegGuard, DiversificationMode, FeedRegistry, LimitOrder, OrderStatus, OtcOffer,
+        TradingVault, TreasuryPlan, TreasuryPlanParams, VaultLog, VaultOp, MAX_BASKET_TARGETS,
+    },
+    trading_vault_seeds,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed, sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct InitTreasuryPlan<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, source_vault.creator.as_ref(), source_vault.mint.as_ref()],
+      bump = source_vault.bump,
+    )]
+    source_vault: Box<Account<'info, TradingVault>>,
+
+    /// CHECK: Pyth feed for the source holding, validated on read
+    source_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = TreasuryPlan::LEN,
+      seeds = [TREASURY_PLAN_SEED, source_vault.key().as_ref()],
+      bump
+    )]
+    plan: Box<Account<'info, TreasuryPlan>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasuryPlan<'info> {
+    governance: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=governance,
+      seeds = [TREASURY_PLAN_SEED, plan.source_vault.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, TreasuryPlan>>,
+
+    #[account(
+      address=plan.source_vault
+    )]
+    source_vault: Box<Account<'info, TradingVault>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTreasurySlice<'info> {
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=keeper,
+      seeds = [TREASURY_PLAN_SEED, plan.source_vault.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, TreasuryPlan>>,
+
+    #[account(
+      address=plan.source_vault
+    )]
+    source_vault: Box<Account<'info, TradingVault>>,
+
+    // the owner's vault for the target this slice buys
+    #[account(
+      constraint=target_vault.owner==plan.owner @ TradingBotError::InvalidTreasuryPlan,
+      seeds = [TRADING_VAULT_SEED, target_vault.creator.as_ref(), target_vault.mint.as_ref()],
+      bump = target_vault.bump,
+    )]
+    target_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=source_vault.token_account
+    )]
+    source_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      address=target_vault.token_account
+    )]
+    target_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, source_vault.key().as_ref()],
+      bump,
+    )]
+    source_vault_log: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, target_vault.key().as_ref()],
+      bump,
+    )]
+    target_vault_log: UncheckedAccount<'info>,
+
+    #[account(
+      address=source_vault.mint
+    )]
+    source_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      address=target_vault.mint
+    )]
+    target_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Checked against the plan, validated on read
+    #[account(
+      address=plan.source_price_feed
+    )]
+    source_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the target's feed in the plan, validated on read
+    target_price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    /// CHECK: Seeds the slice's jitter
+    #[account(
+      address=sysvar::slot_hashes::ID
+    )]
+    slot_hashes: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the supported swap programs
+    swap_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct PlaceTreasuryLimitOrder<'info> {
+    #[account(mut)]
+    keeper: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=keeper,
+      seeds = [TREASURY_PLAN_SEED, plan.source_vault.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, TreasuryPlan>>,
+
+    #[account(
+      mut,
+      address=plan.source_vault
+    )]
+    source_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      address=source_vault.token_account
+    )]
+    source_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, source_vault.key().as_ref()],
+      bump,
+    )]
+    source_vault_log: UncheckedAccount<'info>,
+
+    target_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = keeper,
+      space = LimitOrder::LEN,
+      seeds = [LIMIT_ORDER_SEED, plan.owner.as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    order: Box<Account<'info, LimitOrder>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(idx: u64)]
+pub struct CreateTreasuryOtcOffer<'info> {
+    #[account(mut)]
+    governance: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=governance,
+      seeds = [TREASURY_PLAN_SEED, plan.source_vault.as_ref()],
+      bump = plan.bump,
+    )]
+    plan: Box<Account<'info, TreasuryPlan>>,
+
+    #[account(
+      address=plan.source_vault
+    )]
+    source_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      mut,
+      address=source_vault.token_account
+    )]
+    source_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      address=source_vault.mint
+    )]
+    source_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: The vault's VaultLog PDA, appended to once initialised
+    #[account(
+      mut,
+      seeds = [VAULT_LOG_SEED, source_vault.key().as_ref()],
+      bump,
+    )]
+    source_vault_log: UncheckedAccount<'info>,
+
+    target_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+      init,
+      payer = governance,
+      space = OtcOffer::LEN,
+      seeds = [OTC_OFFER_SEED, plan.owner.as_ref(), idx.to_le_bytes().as_ref()],
+      bump
+    )]
+    offer: Box<Account<'info, OtcOffer>>,
+
+    #[account(
+      init,
+      payer=governance,
+      associated_token::authority=offer,
+      associated_token::mint=source_mint,
+    )]
+    vault: Box<Account<'info, TokenAccount>>,
+
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> InitTreasuryPlan<'info> {
+    pub fn validate_params(source_mint: &Pubkey, params: &TreasuryPlanParams) -> Result<()> {
+        let targets = &params.targets;
+        require!(
+            !targets.is_empty() && targets.len() <= MAX_BASKET_TARGETS,
+            TradingBotError::InvalidTreasuryPlan
+        );
+        let total_weight = targets
+            .iter()
+            .fold(0u32, |total, target| total + target.weight_bps as u32);
+        require_eq!(total_weight, 10000, TradingBotError::InvalidTreasuryPlan);
+        for (i, target) in targets.iter().enumerate() {
+            require!(
+                target.mint != *source_mint
+                    && targets[..i].iter().all(|other| other.mint != target.mint),
+                TradingBotError::InvalidTreasuryPlan
+            );
+            require_gt!(target.target_price, 0, TradingBotError::InvalidTreasuryPlan);
+        }
+        require_gt!(params.total_amount, 0, TradingBotError::InvalidTreasuryPlan);
+        require_gt!(
+            params.slice_interval,
+            0,
+            TradingBotError::InvalidTreasuryPlan
+        );
+        require_gte!(
+            MAX_SLIPPAGE_BPS,
+            params.max_slippage_bps,
+            TradingBotError::InvalidTreasuryPlan
+        );
+        require_gt!(
+            10000,
+            params.jitter_bps,
+            TradingBotError::InvalidTreasuryPlan
+        );
+        require_gt!(
+            10000,
+            params.limit_band_bps,
+            TradingBotError::InvalidTreasuryPlan
+        );
+        Ok(())
+    }
+}
+
+impl<'info> ExecuteTreasurySlice<'info> {
+    // USD per whole token in PRICE_PRECISION, the unit DepegGuard::min_out converts between
+    pub fn read_price(&self, mint: &Pubkey, price_feed: &AccountInfo) -> Result<u64> {
+        PythOracle::get_usd_price(&self.feed_registry, mint, price_feed, u64::MAX)
+    }
+
+    // The source vault signs the swap, so its PDA is flagged as a signer in the forwarded metas
+    pub fn invoke_swap(&self, accounts: &[AccountInfo<'info>], data: Vec<u8>) -> Result<()> {
+        let source_key = self.source_vault.key();
+        let ix = Instruction {
+            program_id: self.swap_program.key(),
+            accounts: accounts
+                .iter()
+                .map(|a| {
+                    let is_signer = a.is_signer || a.key() == source_key;
+                    if a.is_writable {
+                        AccountMeta::new(a.key(), is_signer)
+                    } else {
+                        AccountMeta::new_readonly(a.key(), is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+        invoke_signed(&ix, accounts, &[trading_vault_seeds!(self.source_vault)])?;
+        Ok(())
+    }
+}
+
+// The plan draws on the owner's vault of the source holding. `governance` then owns the
+// parameters and the OTC blocks, the keeper the drip and limit modes.
+pub fn init_treasury_plan(
+    ctx: Context<InitTreasuryPlan>,
+    governance: Pubkey,
+    params: TreasuryPlanParams,
+) -> Result<()> {
+    InitTreasuryPlan::validate_params(&ctx.accounts.source_vault.mint, &params)?;
+    let now = Clock::get()?.unix_timestamp;
+    require_gt!(params.end_at, now, TradingBotError::InvalidTreasuryPlan);
+
+    let plan = &mut ctx.accounts.plan;
+    plan.owner = ctx.accounts.owner.key();
+    plan.governance = governance;
+    plan.source_vault = ctx.accounts.source_vault.key();
+    plan.source_price_feed = ctx.accounts.source_price_feed.key();
+    plan.target_count = 0;
+    plan.committed = 0;
+    plan.start_at = now;
+    plan.last_slice_at = 0;
+    plan.next_slice_at = now;
+    plan.apply_params(&params);
+    plan.bump = *ctx.bumps.get("plan").unwrap();
+
+    Ok(())
+}
+
+// The total can't drop below what is already committed, nor the schedule end before it started
+pub fn update_treasury_plan(
+    ctx: Context<UpdateTreasuryPlan>,
+    params: TreasuryPlanParams,
+) -> Result<()> {
+    InitTreasuryPlan::validate_params(&ctx.accounts.source_vault.mint, &params)?;
+    let plan = &mut ctx.accounts.plan;
+    require_gte!(
+        params.total_amount,
+        plan.committed,
+        TradingBotError::InvalidTreasuryPlan
+    );
+    require_gt!(
+        params.end_at,
+        plan.start_at,
+        TradingBotError::InvalidTreasuryPlan
+    );
+    plan.apply_params(&params);
+    Ok(())
+}
+
+// Keeper crank swapping the next TWAP slice into the most underweight target. The slice is what
+// the linear schedule has reached but nothing has committed yet, capped at the unreserved balance
+// and cut at random by up to the plan's jitter, and the swap must return the oracle-implied amount
+// less the plan's slippage. The next slice's due time is jittered the same way, both drawn from
+// the slot hashes so they are unknown until the slice lands and verifiable after.
+pub fn execute_treasury_slice<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteTreasurySlice<'info>>,
+    swap_data: Vec<u8>,
+    deadline: i64,
+) -> Result<()> {
+    validate_deadline(deadline)?;
+    require!(
+        RotateDepegGuard::is_supported_swap_program(ctx.accounts.swap_program.key),
+        TradingBotError::InvalidDexType
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let plan = &ctx.accounts.plan;
+    require!(plan.slice_due(now), TradingBotError::SliceNotDue);
+    let index = plan
+        .most_underweight()
+        .ok_or(TradingBotError::InvalidTreasuryPlan)?;
+    let target = plan.targets[index];
+    require_keys_eq!(
+        ctx.accounts.target_vault.mint,
+        target.mint,
+        TradingBotError::InvalidTreasuryPlan
+    );
+    require_keys_eq!(
+        ctx.accounts.target_price_feed.key(),
+        target.price_feed,
+        TradingBotError::PriceFeedMismatch
+    );
+
+    let amount_in = plan.scheduled(now)?.saturating_sub(plan.committed).min(
+        ctx.accounts
+            .source_vault
+            .available(ctx.accounts.source_token_account.amount),
+    );
+    let size_offset = jitter::offset(
+        &ctx.accounts.slot_hashes,
+        &plan.key(),
+        b"size",
+        plan.jitter_bps as u64,
+    )?;
+    let amount_in = plan.jittered_slice(amount_in, size_offset)?;
+    require_gt!(amount_in, 0, TradingBotError::SliceNotDue);
+    let accounts = &ctx.accounts;
+    let source_price =
+        accounts.read_price(&accounts.source_vault.mint, &accounts.source_price_feed)?;
+    let target_price = accounts.read_price(&target.mint, &accounts.target_price_feed)?;
+    let min_out = DepegGuard::min_out(
+        amount_in,
+        source_price,
+        target_price,
+        ctx.accounts.source_mint.decimals,
+        ctx.accounts.target_mint.decimals,
+        plan.max_slippage_bps,
+    )?;
+    msg!(
+        "Slicing {} into {} with min out {}",
+        amount_in,
+        target.mint,
+        min_out
+    );
+
+    let source_before = ctx.accounts.source_token_account.amount;
+    let target_before = ctx.accounts.target_token_account.amount;
+    ctx.accounts
+        .invoke_swap(ctx.remaining_accounts, swap_data)?;
+    ctx.accounts.source_token_account.reload()?;
+    ctx.accounts.target_token_account.reload()?;
+    let source_after = ctx.accounts.source_token_account.amount;
+    let target_after = ctx.accounts.target_token_account.amount;
+
+    ctx.accounts.source_vault.check_invariant(source_after)?;
+    let spent = math::checked_sub(source_before, source_after)?;
+    let received = math::checked_sub(target_after, target_before)?;
+    require_gte!(amount_in, spent, TradingBotError::InvalidCalculation);
+    require_gte!(received, min_out, TradingBotError::SlippageExceeded);
+
+    let plan = &mut ctx.accounts.plan;
+    plan.commit(index, spent)?;
+    plan.last_slice_at = now;
+    let span = plan.jitter_span()?;
+    let offset = jitter::offset(
+        &ctx.accounts.slot_hashes,
+        &plan.key(),
+        b"timing",
+        math::checked_mul(span, 2)? as u64,
+    )?;
+    plan.schedule_next_slice(now, offset)?;
+    let accounts = &ctx.accounts;
+    VaultLog::append_if_initialized(
+        &accounts.source_vault_log,
+        VaultOp::Preset,
+        accounts.plan.key(),
+        spent,
+        &accounts.source_vault,
+        Some(source_after),
+    )?;
+    VaultLog::append_if_initialized(
+        &accounts.target_vault_log,
+        VaultOp::Preset,
+        accounts.plan.key(),
+        received,
+        &accounts.target_vault,
+        Some(target_after),
+    )?;
+    let plan = &ctx.accounts.plan;
+    emit!(TreasuryDiversified {
+        plan: plan.key(),
+        mode: DiversificationMode::Drip,
+        target_mint: target.mint,
+        amount: spent,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+// Rests a sell of the source against one target near its price. The order belongs to the plan's
+// owner like any other, so fills pay them and they alone can cancel it; the size counts as
+// committed from the start.
+pub fn place_treasury_limit_order(
+    ctx: Context<PlaceTreasuryLimitOrder>,
+    idx: u64,
+    limit_price: u64,
+    size: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require_gt!(size, 0, TradingBotError::InvalidOrderParams);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        expires_at == 0 || expires_at > now,
+        TradingBotError::OrderExpired
+    );
+    let plan = &ctx.accounts.plan;
+    let index = plan
+        .target_index(&ctx.accounts.target_mint.key())
+        .ok_or(TradingBotError::InvalidTreasuryPlan)?;
+    require!(
+        plan.within_band(index, limit_price)?,
+        TradingBotError::LimitPriceNotMet
+    );
+
+    msg!("Reserving {} in source vault", size);
+    ctx.accounts
+        .source_vault
+        .reserve(size, ctx.accounts.source_token_account.amount)?;
+    ctx.accounts.plan.commit(index, size)?;
+
+    let order = &mut ctx.accounts.order;
+    order.idx = idx;
+    order.owner = ctx.accounts.plan.owner;
+    order.trading_vault = ctx.accounts.source_vault.key();
+    order.base_mint = ctx.accounts.source_vault.mint;
+    order.quote_mint = ctx.accounts.target_mint.key();
+    order.is_buy = false;
+    order.limit_price = limit_price;
+    order.size = size;
+    order.reserved = size;
+    order.filled_size = 0;
+    order.filled_quote = 0;
+    order.avg_fill_price = 0;
+    order.fill_count = 0;
+    order.status = OrderStatus::Open;
+    order.created_at = now;
+    order.updated_at = now;
+    order.expires_at = expires_at;
+    order.bump = *ctx.bumps.get("order").unwrap();
+
+    VaultLog::append_if_initialized(
+        &ctx.accounts.source_vault_log,
+        VaultOp::PlaceOrder,
+        ctx.accounts.order.key(),
+        size,
+        &ctx.accounts.source_vault,
+        Some(ctx.accounts.source_token_account.amount),
+    )?;
+    emit!(TreasuryDiversified {
+        plan: ctx.accounts.plan.key(),
+        mode: DiversificationMode::Limit,
+        target_mint: ctx.accounts.target_mint.key(),
+        amount: size,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+// Moves a large block out of the source vault into OTC escrow, priced within the band. The plan's
+// owner is the maker, so the ask (or a refund on cancel) goes to them.
+pub fn create_treasury_otc_offer(
+    ctx: Context<CreateTreasuryOtcOffer>,
+    idx: u64,
+    offer_amount: u64,
+    ask_amount: u64,
+    expires_at: i64,
+    taker: Option<Pubkey>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require_gt!(expires_at, now, TradingBotError::OtcOfferExpired);
+    let plan = &ctx.accounts.plan;
+    require!(
+        offer_amount > 0 && offer_amount >= plan.otc_min_block,
+        TradingBotError::InvalidTreasuryPlan
+    );
+    let index = plan
+        .target_index(&ctx.accounts.target_mint.key())
+        .ok_or(TradingBotError::InvalidTreasuryPlan)?;
+    let price = math::checked_as_u64(math::checked_div(
+        math::checked_mul(ask_amount as u128, PRICE_PRECISION as u128)?,
+        offer_amount as u128,
+    )?)?;
+    require!(
+        plan.within_band(index, price)?,
+        TradingBotError::LimitPriceNotMet
+    );
+    require_gte!(
+        ctx.accounts
+            .source_vault
+            .available(ctx.accounts.source_token_account.amount),
+        offer_amount,
+        TradingBotError::InsufficientAvailableBalance
+    );
+
+    msg!("Lock {} block in escrow at {}", offer_amount, price);
+    let signer_seeds: &[&[&[u8]]] = &[trading_vault_seeds!(ctx.accounts.source_vault)];
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.source_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.source_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        offer_amount,
+    )?;
+    ctx.accounts.plan.commit(index, offer_amount)?;
+
+    let offer = &mut ctx.accounts.offer;
+    offer.idx = idx;
+    offer.maker = ctx.accounts.plan.owner;
+    offer.taker = taker;
+    offer.offer_mint = ctx.accounts.source_mint.key();
+    offer.ask_mint = ctx.accounts.target_mint.key();
+    offer.offer_amount = offer_amount;
+    offer.ask_amount = ask_amount;
+    offer.expires_at = expires_at;
+    offer.bump = *ctx.bumps.get("offer").unwrap();
+
+    ctx.accounts.source_token_account.reload()?;
+    VaultLog::append_if_initialized(
+        &ctx.accounts.source_vault_log,
+        VaultOp::Preset,
+        ctx.accounts.offer.key(),
+        offer_amount,
+        &ctx.accounts.source_vault,
+        Some(ctx.accounts.source_token_account.amount),
+    )?;
+    emit!(TreasuryDiversified {
+        plan: ctx.accounts.plan.key(),
+        mode: DiversificationMode::Otc,
+        target_mint: ctx.accounts.target_mint.key(),
+        amount: offer_amount,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use crate::constants::{FEED_REGISTRY_SEED, POOL_REGISTRY_SEED, UPGRADE_GUARD_SEED};
+use crate::events::UpgradeChecked;
+use crate::instructions::IndexTransfer;
+use crate::program::OnChainTradingBot;
+use crate::{
+    errors::TradingBotError,
+    state::{FeedRegistry, IndexFund, PoolRegistry, TradingVault, UpgradeGuard},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+use anchor_spl::token_2022::spl_token_2022::{
+    self, extension::StateWithExtensions, state::Mint as Mint2022,
+};
+
+#[derive(Accounts)]
+pub struct InitUpgradeGuard<'info> {
+    #[account(mut)]
+    admin: Signer<'info>,
+
+    #[account(
+      init,
+      payer = admin,
+      space = UpgradeGuard::LEN,
+      seeds = [UPGRADE_GUARD_SEED],
+      bump
+    )]
+    upgrade_guard: Box<Account<'info, UpgradeGuard>>,
+
+    #[account(
+      constraint=program.programdata_address()? == Some(program_data.key())
+    )]
+    program: Program<'info, OnChainTradingBot>,
+
+    // only the upgrade authority can claim the guard
+    #[account(
+      constraint=program_data.upgrade_authority_address == Some(admin.key()) @ TradingBotError::UnauthorizedExecutor
+    )]
+    program_data: Box<Account<'info, ProgramData>>,
+
+    system_program: Program<'info, System>,
+}
+
+// The guard and program data that fills, rebalances, market making and strategy execution take,
+// so each can refuse to move funds while a deploy is waiting on post_upgrade_check
+#[derive(Accounts)]
+pub struct UpgradeGate<'info> {
+    #[account(
+      seeds = [UPGRADE_GUARD_SEED],
+      bump = upgrade_guard.bump,
+    )]
+    upgrade_guard: Box<Account<'info, UpgradeGuard>>,
+
+    // its slot tells whether the program was deployed since the last post_upgrade_check
+    #[account(
+      address=upgrade_guard.program_data
+    )]
+    program_data: Box<Account<'info, ProgramData>>,
+}
+
+impl<'info> UpgradeGate<'info> {
+    pub fn require_healthy(&self) -> Result<()> {
+        self.upgrade_guard.require_healthy(self.program_data.slot)
+    }
+}
+
+#[derive(Accounts)]
+pub struct PostUpgradeCheck<'info> {
+    admin: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=admin,
+      has_one=program_data,
+      seeds = [UPGRADE_GUARD_SEED],
+      bump = upgrade_guard.bump,
+    )]
+    upgrade_guard: Box<Account<'info, UpgradeGuard>>,
+
+    program_data: Box<Account<'info, ProgramData>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+
+    #[account(
+      seeds = [POOL_REGISTRY_SEED],
+      bump = pool_registry.bump,
+    )]
+    pool_registry: Box<Account<'info, PoolRegistry>>,
+}
+
+// A vault's token account has to be the one it records, owned by the vault, and back what the
+// vault has reserved. Returns the vault's seq.
+fn check_vault(accounts: &[AccountInfo]) -> Result<u32> {
+    let vault = Account::<TradingVault>::try_from(&accounts[0])?;
+    require_keys_eq!(
+        accounts[1].key(),
+        vault.token_account,
+        TradingBotError::VaultAccountingMismatch
+    );
+    let token_account = Account::<TokenAccount>::try_from(&accounts[1])?;
+    require!(
+        token_account.owner == vault.key() && token_account.mint == vault.mint,
+        TradingBotError::VaultAccountingMismatch
+    );
+    vault
+        .check_invariant(token_account.amount)
+        .map_err(|_| error!(TradingBotError::VaultAccountingMismatch))?;
+    Ok(vault.seq)
+}
+
+// Accounts each index fund takes ahead of its components' token accounts and price feeds: the
+// fund, its share mint, base vault, base token account, base mint and base price feed
+const INDEX_FUND_ACCOUNTS: usize = 6;
+
+// A fund's units are its share supply plus the burned units still waiting to be paid out, and
+// its oracle NAV has to price them within `nav_tolerance_bps` of the last mark. Returns the
+// fund's seq and how many accounts it took.
+fn check_index_fund(
+    registry: &FeedRegistry,
+    accounts: &[AccountInfo],
+    nav_tolerance_bps: u16,
+) -> Result<(u32, usize)> {
+    require_gte!(
+        accounts.len(),
+        INDEX_FUND_ACCOUNTS,
+        TradingBotError::ShareSupplyMismatch
+    );
+    let index_fund = Account::<IndexFund>::try_from(&accounts[0])?;
+    require_keys_eq!(
+        accounts[1].key(),
+        index_fund.share_mint,
+        TradingBotError::ShareSupplyMismatch
+    );
+    require_keys_eq!(
+        *accounts[1].owner,
+        spl_token_2022::ID,
+        TradingBotError::ShareSupplyMismatch
+    );
+    let data = accounts[1].try_borrow_data()?;
+    let supply = StateWithExtensions::<Mint2022>::unpack(&data)?.base.supply;
+    require!(
+        index_fund.pending_withdrawal_units <= index_fund.units
+            && supply == index_fund.units - index_fund.pending_withdrawal_units,
+        TradingBotError::ShareSupplyMismatch
+    );
+
+    let used = INDEX_FUND_ACCOUNTS + index_fund.components().len() * 2;
+    require_gte!(accounts.len(), used, TradingBotError::ShareSupplyMismatch);
+    require_keys_eq!(
+        accounts[2].key(),
+        index_fund.base_vault,
+        TradingBotError::ShareSupplyMismatch
+    );
+    let base_vault = Account::<TradingVault>::try_from(&accounts[2])?;
+    require!(
+        accounts[3].key() == base_vault.token_account && accounts[4].key() == base_vault.mint,
+        TradingBotError::ShareSupplyMismatch
+    );
+    let base_token_account = Account::<TokenAccount>::try_from(&accounts[3])?;
+    let base_mint = Account::<Mint>::try_from(&accounts[4])?;
+    let (_, nav) = IndexTransfer::fund_nav(
+        &index_fund,
+        registry,
+        base_token_account.amount,
+        &base_mint,
+        &accounts[5],
+        &accounts[INDEX_FUND_ACCOUNTS..used],
+    )?;
+    index_fund.check_nav(nav, nav_tolerance_bps)?;
+    Ok((index_fund.seq, used))
+}
+
+pub fn init_upgrade_guard(ctx: Context<InitUpgradeGuard>) -> Result<()> {
+    let upgrade_guard = &mut ctx.accounts.upgrade_guard;
+    upgrade_guard.admin = ctx.accounts.admin.key();
+    upgrade_guard.program_data = ctx.accounts.program_data.key();
+    upgrade_guard.healthy = false;
+    upgrade_guard.vault_count = 0;
+    upgrade_guard.index_fund_count = 0;
+    upgrade_guard.checking_slot = 0;
+    upgrade_guard.vaults_checked = 0;
+    upgrade_guard.index_funds_checked = 0;
+    upgrade_guard.bump = *ctx.bumps.get("upgrade_guard").unwrap();
+
+    Ok(())
+}
+
+// Run by the admin after every deploy, a page at a time, before anything can trade again. Each
+// page checks that both registries are intact, then the `vaults` vaults and the index funds in
+// remaining accounts: first each vault and its token account, then each index fund with the
+// accounts in INDEX_FUND_ACCOUNTS and its components' token accounts and price feeds, continuing
+// in seq order from where the last page for this deploy stopped. The guard only turns healthy on
+// the page that brings the checked counts up to every vault and fund created, and any failure
+// leaves it unhealthy.
+pub fn post_upgrade_check(
+    ctx: Context<PostUpgradeCheck>,
+    vaults: u8,
+    nav_tolerance_bps: u16,
+) -> Result<()> {
+    ctx.accounts.feed_registry.check_integrity()?;
+    ctx.accounts.pool_registry.check_integrity()?;
+    let deploy_slot = ctx.accounts.program_data.slot;
+    let upgrade_guard = &mut ctx.accounts.upgrade_guard;
+    upgrade_guard.start_page(deploy_slot);
+
+    let vault_len = vaults as usize * 2;
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        vault_len,
+        TradingBotError::VaultAccountingMismatch
+    );
+    let (vault_accounts, mut fund_accounts) = ctx.remaining_accounts.split_at(vault_len);
+    for accounts in vault_accounts.chunks(2) {
+        upgrade_guard.record_vault(check_vault(accounts)?)?;
+    }
+    while !fund_accounts.is_empty() {
+        let (seq, used) = check_index_fund(
+            &ctx.accounts.feed_registry,
+            fund_accounts,
+            nav_tolerance_bps,
+        )?;
+        upgrade_guard.record_index_fund(seq)?;
+        fund_accounts = &fund_accounts[used..];
+    }
+    msg!(
+        "Checked {}/{} vaults, {}/{} index funds",
+        upgrade_guard.vaults_checked,
+        upgrade_guard.vault_count,
+        upgrade_guard.index_funds_checked,
+        upgrade_guard.index_fund_count
+    );
+    if !upgrade_guard.is_complete() {
+        return Ok(());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    upgrade_guard.checked_slot = deploy_slot;
+    upgrade_guard.healthy = true;
+    upgrade_guard.checked_at = now;
+    emit!(UpgradeChecked {
+        deploy_slot,
+        vaults: upgrade_guard.vault_count,
+        index_funds: upgrade_guard.index_fund_count,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::constants::{TRADING_VAULT_SEED, VAULT_LOG_SEED};
+use crate::state::{TradingVault, VaultLog, VaultLogEntry, RECENT_LOG_ENTRIES};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitVaultLog<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+
+    #[account(
+      has_one=owner,
+      seeds = [TRADING_VAULT_SEED, trading_vault.creator.as_ref(), trading_vault.mint.as_ref()],
+      bump = trading_vault.bump,
+    )]
+    trading_vault: Box<Account<'info, TradingVault>>,
+
+    #[account(
+      init,
+      payer = owner,
+      space = VaultLog::LEN,
+      seeds = [VAULT_LOG_SEED, trading_vault.key().as_ref()],
+      bump
+    )]
+    vault_log: Box<Account<'info, VaultLog>>,
+
+    system_program: Program<'info, System>,
+}
+
+// The chain starts from a zero hash; from then on every instruction that moves the vault's funds
+// or changes its settings has to pass the log and appends to it
+pub fn init_vault_log(ctx: Context<InitVaultLog>) -> Result<()> {
+    let vault_log = &mut ctx.accounts.vault_log;
+    vault_log.trading_vault = ctx.accounts.trading_vault.key();
+    vault_log.entry_count = 0;
+    vault_log.head_hash = [0u8; 32];
+    vault_log.head = 0;
+    vault_log.recent = [VaultLogEntry::default(); RECENT_LOG_ENTRIES];
+    vault_log.bump = *ctx.bumps.get("vault_log").unwrap();
+
+    Ok(())
+}
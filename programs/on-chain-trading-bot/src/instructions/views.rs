@@ -0,0 +1,429 @@
+use crate::constants::{
+    EXECUTION_METRICS_SEED, FEED_REGISTRY_SEED, MARKET_MAKER_SEED, MAX_BATCH_QUOTES,
+    POOL_REGISTRY_SEED, PRICE_PRECISION, STRATEGY_SEED,
+};
+use crate::dex::depth;
+use crate::errors::TradingBotError;
+use crate::instructions::{InitMarketMaker, RequoteMarketMaker};
+use crate::types::{DexType, StrategyType, TokenPair, TradeSide};
+use crate::valuation::{PositionValuation, Valuation};
+use crate::{
+    account_manager::UserPositions,
+    math,
+    state::{ExecutionMetrics, FeedRegistry, MarketMaker, PoolRegistry, PriceBand, Strategy},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum NextAction {
+    Idle,
+    Requote,
+    Hedge,
+    // a generic strategy due for execute_strategy
+    Execute,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StrategySummary {
+    pub strategy: Pubkey,
+    pub strategy_type: StrategyType,
+    pub is_active: bool,
+    pub mid_price: u64,
+    pub nav: u64,
+    pub open_positions: u8,
+    pub perp_position: i64,
+    pub unrealized_pnl: i64,
+    // what hedging has cost against the oracle mid, venue fees included
+    pub hedge_cost: i64,
+    pub risk_utilization_bps: u64,
+    pub next_action: NextAction,
+    // oracle mids at which the next requote fires
+    pub requote_below: u64,
+    pub requote_above: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VolumeUtilization {
+    pub strategy: Pubkey,
+    pub volume_24h: u64,
+    // zero when the strategy has no limit
+    pub daily_volume_limit: u64,
+    pub remaining: Option<u64>,
+    pub utilization_bps: u64,
+}
+
+// One (pair, venue, size) the strategy builder wants priced. Its registered pool's reserve
+// accounts follow in remaining accounts, input side first.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct QuoteCandidate {
+    pub pair: TokenPair,
+    pub dex: DexType,
+    pub side: TradeSide,
+    pub amount_in: u64,
+    pub fee_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchQuote {
+    pub amount_out: u64,
+    // quote per base in PRICE_PRECISION
+    pub price: u64,
+    pub price_impact_bps: u64,
+    // false when the owner's execution metrics have banned the venue
+    pub venue_enabled: bool,
+}
+
+#[derive(Accounts)]
+pub struct ViewBatchQuotes<'info> {
+    #[account(
+      seeds = [EXECUTION_METRICS_SEED, execution_metrics.owner.as_ref()],
+      bump = execution_metrics.bump,
+    )]
+    execution_metrics: Option<Box<Account<'info, ExecutionMetrics>>>,
+
+    #[account(
+      seeds = [POOL_REGISTRY_SEED],
+      bump = pool_registry.bump,
+    )]
+    pool_registry: Box<Account<'info, PoolRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct ViewVolumeUtilization<'info> {
+    #[account(
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+}
+
+#[derive(Accounts)]
+pub struct ViewStrategySummary<'info> {
+    // one of the market maker and the strategy, whose wallets follow
+    #[account(
+      seeds = [MARKET_MAKER_SEED, market_maker.owner.as_ref(), market_maker.market.as_ref(), market_maker.variant.to_le_bytes().as_ref()],
+      bump = market_maker.bump,
+    )]
+    market_maker: Option<Box<Account<'info, MarketMaker>>>,
+
+    #[account(
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Option<Box<Account<'info, Strategy>>>,
+
+    // the strategy owner's positions; without it a strategy reports none open
+    user_positions: Option<AccountLoader<'info, UserPositions>>,
+
+    /// CHECK: The market maker's stored feed, or resolved against the feed registry for the
+    /// strategy's base mint
+    price_feed: UncheckedAccount<'info>,
+
+    base_wallet: Box<Account<'info, TokenAccount>>,
+
+    quote_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+impl<'info> ViewStrategySummary<'info> {
+    pub fn requote_bounds(last_mid: u64, threshold_bps: u16) -> Result<(u64, u64)> {
+        let band = math::checked_as_u64(math::checked_div(
+            math::checked_mul(last_mid as u128, threshold_bps as u128)?,
+            10000,
+        )?)?;
+        Ok((
+            last_mid.saturating_sub(band),
+            math::checked_add(last_mid, band)?,
+        ))
+    }
+
+    // Wallet balances at the oracle mid plus the perp hedge's unrealized P&L, less the fees perp
+    // hedges paid out of Drift collateral
+    pub fn nav(
+        market_maker: &MarketMaker,
+        base_amount: u64,
+        quote_amount: u64,
+        mid: u64,
+    ) -> Result<u64> {
+        let nav = Valuation::nav(&[
+            PositionValuation::Token {
+                amount: base_amount,
+                price: mid,
+            },
+            PositionValuation::Token {
+                amount: quote_amount,
+                price: PRICE_PRECISION,
+            },
+            PositionValuation::Perp {
+                size: market_maker.perp_position,
+                entry_price: market_maker.perp_entry_price,
+                mark_price: mid,
+                collateral: 0,
+            },
+        ])?;
+        Ok(nav.saturating_sub(market_maker.hedge_fees))
+    }
+
+    // Native quote per native base of the two wallets' mints, checked against the summarized
+    // strategy by the caller
+    fn mid(&self) -> Result<u64> {
+        InitMarketMaker::oracle_mid(
+            &self.feed_registry,
+            &self.price_feed,
+            u64::MAX,
+            self.base_wallet.mint,
+            self.quote_wallet.mint,
+        )
+    }
+
+    fn market_maker_summary(
+        &self,
+        market_maker: &Account<'info, MarketMaker>,
+    ) -> Result<StrategySummary> {
+        require_keys_eq!(
+            self.base_wallet.key(),
+            market_maker.base_wallet,
+            TradingBotError::InvalidStrategyWallet
+        );
+        require_keys_eq!(
+            self.quote_wallet.key(),
+            market_maker.quote_wallet,
+            TradingBotError::InvalidStrategyWallet
+        );
+        require_keys_eq!(
+            self.price_feed.key(),
+            market_maker.price_feed,
+            TradingBotError::PriceFeedMismatch
+        );
+        let mid = self.mid()?;
+
+        let nav = Self::nav(
+            market_maker,
+            self.base_wallet.amount,
+            self.quote_wallet.amount,
+            mid,
+        )?;
+        let unrealized_pnl = math::checked_sub(nav as i64, market_maker.net_contributions)?;
+
+        // a live quote leaves a bid and an ask resting on the book
+        let mut open_positions = if market_maker.last_quote_mid > 0 {
+            2
+        } else {
+            0
+        };
+        if market_maker.perp_position != 0 {
+            open_positions += 1;
+        }
+
+        let deviation = market_maker.net_deviation(self.base_wallet.amount);
+        let risk_utilization_bps = math::checked_as_u64(math::checked_div(
+            math::checked_mul(deviation.unsigned_abs(), 10000)?,
+            market_maker.max_inventory_deviation as u128,
+        )?)?;
+
+        let (requote_below, requote_above) = Self::requote_bounds(
+            market_maker.last_quote_mid,
+            market_maker.requote_threshold_bps,
+        )?;
+        let next_action = if !market_maker.is_active {
+            NextAction::Idle
+        } else if deviation.unsigned_abs() > market_maker.inventory_band as u128 {
+            NextAction::Hedge
+        } else if market_maker.quotes_expired(Clock::get()?.unix_timestamp)
+            || RequoteMarketMaker::needs_requote(
+                market_maker.last_quote_mid,
+                mid,
+                market_maker.requote_threshold_bps,
+            )?
+        {
+            NextAction::Requote
+        } else {
+            NextAction::Idle
+        };
+
+        Ok(StrategySummary {
+            strategy: market_maker.key(),
+            strategy_type: market_maker.strategy_type,
+            is_active: market_maker.is_active,
+            mid_price: mid,
+            nav,
+            open_positions,
+            perp_position: market_maker.perp_position,
+            unrealized_pnl,
+            hedge_cost: market_maker.hedge_cost,
+            risk_utilization_bps,
+            next_action,
+            requote_below,
+            requote_above,
+        })
+    }
+
+    // A generic strategy holds the pair in token accounts of its PDA and has no quotes or hedge.
+    // Its risk utilization is the share of its daily volume limit used.
+    fn strategy_summary(&self, strategy: &Account<'info, Strategy>) -> Result<StrategySummary> {
+        let pair = &strategy.config.pair;
+        for (wallet, mint) in [
+            (&self.base_wallet, pair.base_mint),
+            (&self.quote_wallet, pair.quote_mint),
+        ] {
+            require!(
+                wallet.owner == strategy.key() && wallet.mint == mint,
+                TradingBotError::InvalidStrategyWallet
+            );
+        }
+        let mid = self.mid()?;
+
+        let nav = Valuation::nav(&[
+            PositionValuation::Token {
+                amount: self.base_wallet.amount,
+                price: mid,
+            },
+            PositionValuation::Token {
+                amount: self.quote_wallet.amount,
+                price: PRICE_PRECISION,
+            },
+        ])?;
+        let unrealized_pnl = math::checked_sub(nav as i64, strategy.net_contributions)?;
+
+        let open_positions = match &self.user_positions {
+            Some(user_positions) => {
+                let positions = user_positions.load()?;
+                require_keys_eq!(
+                    positions.owner,
+                    strategy.owner,
+                    TradingBotError::InvalidSummaryAccounts
+                );
+                positions.open_positions().min(u8::MAX as usize) as u8
+            }
+            None => 0,
+        };
+
+        let now = strategy.clock_now()?;
+        let due = strategy.last_executed_at == 0
+            || now >= strategy.last_executed_at.saturating_add(strategy.config.execution_interval);
+        let next_action = if strategy.is_active && due {
+            NextAction::Execute
+        } else {
+            NextAction::Idle
+        };
+
+        Ok(StrategySummary {
+            strategy: strategy.key(),
+            strategy_type: strategy.strategy_type,
+            is_active: strategy.is_active,
+            mid_price: mid,
+            nav,
+            open_positions,
+            perp_position: 0,
+            unrealized_pnl,
+            hedge_cost: 0,
+            risk_utilization_bps: strategy.volume_utilization_bps(now)?,
+            next_action,
+            requote_below: 0,
+            requote_above: 0,
+        })
+    }
+}
+
+// Read-only: simulate the transaction and decode the return data to read the summary. Unrealized
+// P&L is the NAV less the net contributions, so deposits and withdrawals don't count as P&L.
+pub fn view_strategy_summary(ctx: Context<ViewStrategySummary>) -> Result<StrategySummary> {
+    let accounts = &ctx.accounts;
+    match (&accounts.market_maker, &accounts.strategy) {
+        (Some(market_maker), None) => accounts.market_maker_summary(market_maker),
+        (None, Some(strategy)) => accounts.strategy_summary(strategy),
+        _ => err!(TradingBotError::InvalidSummaryAccounts),
+    }
+}
+
+// Read-only, like view_strategy_summary
+pub fn view_volume_utilization(ctx: Context<ViewVolumeUtilization>) -> Result<VolumeUtilization> {
+    let strategy = &ctx.accounts.strategy;
+    let now = strategy.clock_now()?;
+    Ok(VolumeUtilization {
+        strategy: strategy.key(),
+        volume_24h: strategy.volume_24h(now),
+        daily_volume_limit: strategy.risk_parameters.daily_volume_limit,
+        remaining: strategy.remaining_daily_volume(now),
+        utilization_bps: strategy.volume_utilization_bps(now)?,
+    })
+}
+
+// Read-only, like view_strategy_summary. Prices every candidate against its pool's reserves in
+// one simulation; order-book venues have no reserves to quote from.
+pub fn view_batch_quotes<'info>(
+    ctx: Context<'_, '_, '_, 'info, ViewBatchQuotes<'info>>,
+    candidates: Vec<QuoteCandidate>,
+) -> Result<Vec<BatchQuote>> {
+    require_gte!(
+        MAX_BATCH_QUOTES,
+        candidates.len(),
+        TradingBotError::TooManyQuotes
+    );
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        candidates.len() * 2,
+        TradingBotError::InvalidQuoteAccounts
+    );
+
+    let mut quotes = Vec::with_capacity(candidates.len());
+    for (candidate, accounts) in candidates.iter().zip(ctx.remaining_accounts.chunks(2)) {
+        require!(
+            candidate.dex != DexType::Serum,
+            TradingBotError::InvalidDexType
+        );
+        let (mint_in, mint_out) = match candidate.side {
+            TradeSide::Buy => (candidate.pair.quote_mint, candidate.pair.base_mint),
+            TradeSide::Sell => (candidate.pair.base_mint, candidate.pair.quote_mint),
+        };
+        for reserve in accounts {
+            ctx.accounts.pool_registry.require_reserve(
+                &candidate.pair,
+                candidate.dex,
+                reserve.key,
+            )?;
+        }
+        let reserve_in = Account::<TokenAccount>::try_from(&accounts[0])?;
+        let reserve_out = Account::<TokenAccount>::try_from(&accounts[1])?;
+        require_keys_eq!(
+            reserve_in.mint,
+            mint_in,
+            TradingBotError::InvalidQuoteAccounts
+        );
+        require_keys_eq!(
+            reserve_out.mint,
+            mint_out,
+            TradingBotError::InvalidQuoteAccounts
+        );
+
+        let amount_out = depth::amount_out(
+            candidate.amount_in,
+            reserve_in.amount,
+            reserve_out.amount,
+            candidate.fee_bps,
+        )?;
+        let price = match (candidate.side, amount_out) {
+            (_, 0) => 0,
+            (TradeSide::Buy, _) => PriceBand::implied_price(amount_out, candidate.amount_in)?,
+            (TradeSide::Sell, _) => PriceBand::implied_price(candidate.amount_in, amount_out)?,
+        };
+        quotes.push(BatchQuote {
+            amount_out,
+            price,
+            price_impact_bps: depth::price_impact_bps(candidate.amount_in, reserve_in.amount)?,
+            venue_enabled: ctx
+                .accounts
+                .execution_metrics
+                .as_ref()
+                .map_or(true, |metrics| metrics.is_enabled(candidate.dex)),
+        });
+    }
+
+    Ok(quotes)
+}
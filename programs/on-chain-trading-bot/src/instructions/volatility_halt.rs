@@ -0,0 +1,113 @@
+use crate::constants::{FEED_REGISTRY_SEED, STRATEGY_SEED};
+use crate::oracles::PythOracle;
+use crate::{
+    errors::TradingBotError,
+    math,
+    state::{FeedRegistry, Strategy},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ConfigureVolatilityHalt<'info> {
+    owner: Signer<'info>,
+
+    #[account(
+      mut,
+      has_one=owner,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    /// CHECK: Pyth feed watched for large prints, validated on read
+    price_feed: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PollVolatilityHalt<'info> {
+    #[account(
+      mut,
+      seeds = [STRATEGY_SEED, strategy.creator.as_ref(), strategy.strategy_id.as_bytes()],
+      bump = strategy.bump,
+    )]
+    strategy: Box<Account<'info, Strategy>>,
+
+    /// CHECK: Checked against the configured feed, validated on read
+    #[account(
+      address=strategy.volatility_halt.price_feed
+    )]
+    price_feed: UncheckedAccount<'info>,
+
+    #[account(
+      seeds = [FEED_REGISTRY_SEED],
+      bump = feed_registry.bump,
+    )]
+    feed_registry: Box<Account<'info, FeedRegistry>>,
+}
+
+impl<'info> PollVolatilityHalt<'info> {
+    // The halt watches the price of the strategy's base mint
+    pub fn observe(
+        strategy: &mut Strategy,
+        feed_registry: &FeedRegistry,
+        price_feed: &AccountInfo,
+        now: i64,
+    ) -> Result<()> {
+        let price = PythOracle::get_price_with_confidence(
+            feed_registry,
+            &strategy.config.pair.base_mint,
+            price_feed,
+            u64::MAX,
+        )?;
+        let halted = strategy.volatility_halt.observe(
+            math::checked_as_u64(price.price)?,
+            price.conf,
+            now,
+        )?;
+        if halted {
+            msg!(
+                "Entries halted until {}",
+                strategy.volatility_halt.halted_until
+            );
+        }
+        Ok(())
+    }
+}
+
+// Passing zero thresholds turns the halt off; an active halt is lifted on reconfiguration
+pub fn configure_volatility_halt(
+    ctx: Context<ConfigureVolatilityHalt>,
+    max_move_bps: u16,
+    max_confidence_bps: u16,
+    window: i64,
+    cooldown: i64,
+) -> Result<()> {
+    require!(
+        max_move_bps <= 10000 && max_confidence_bps <= 10000,
+        TradingBotError::InvalidStrategyConfig
+    );
+    require_gte!(window, 0, TradingBotError::InvalidStrategyConfig);
+    require_gte!(cooldown, 0, TradingBotError::InvalidStrategyConfig);
+
+    let halt = &mut ctx.accounts.strategy.volatility_halt;
+    halt.price_feed = ctx.accounts.price_feed.key();
+    halt.max_move_bps = max_move_bps;
+    halt.max_confidence_bps = max_confidence_bps;
+    halt.window = window;
+    halt.cooldown = cooldown;
+    halt.last_price = 0;
+    halt.last_price_at = 0;
+    halt.halted_until = 0;
+
+    Ok(())
+}
+
+// Permissionless crank so consecutive prints are observed between strategy executions too
+pub fn poll_volatility_halt(ctx: Context<PollVolatilityHalt>) -> Result<()> {
+    PollVolatilityHalt::observe(
+        &mut ctx.accounts.strategy,
+        &ctx.accounts.feed_registry,
+        &ctx.accounts.price_feed,
+        Clock::get()?.unix_timestamp,
+    )
+}
@@ -0,0 +1,36 @@
+use crate::constants::COMPUTE_BUDGET_PROGRAM_ID;
+use crate::errors::TradingBotError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+// Programs whose instructions may share a transaction with a guarded execution: they move no
+// tokens and invoke nothing
+const INERT_PROGRAMS: [Pubkey; 1] = [COMPUTE_BUDGET_PROGRAM_ID];
+
+// Fails unless the current instruction is called directly and everything else in the transaction
+// is inert. Executions a third party assembles can otherwise be wrapped in swaps or transfers that
+// move the price they trade at or take what they just paid out, before or after them.
+pub fn require_isolated(instructions_sysvar: &AccountInfo) -> Result<()> {
+    // also rejects any account but the instructions sysvar, which would end the loop below early
+    let current = load_current_index_checked(instructions_sysvar)? as usize;
+    let mut index = 0;
+    while let Ok(instruction) = load_instruction_at_checked(index, instructions_sysvar) {
+        if index == current {
+            // a top-level instruction of another program means this one was reached by CPI
+            require_keys_eq!(
+                instruction.program_id,
+                crate::ID,
+                TradingBotError::UntrustedInstruction
+            );
+        } else {
+            require!(
+                INERT_PROGRAMS.contains(&instruction.program_id),
+                TradingBotError::UntrustedInstruction
+            );
+        }
+        index += 1;
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::errors::TradingBotError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar;
+
+// SlotHashes is its entry count followed by (slot, hash) pairs, newest first
+const NEWEST_ENTRY: std::ops::Range<usize> = 8..48;
+
+// Pseudo-random value for `key` in the slot the transaction lands in: the newest SlotHashes entry
+// hashed with the key and `salt`. Nobody can know it before that entry's slot is produced, and
+// anyone can recompute it afterwards from the same sysvar and key.
+pub fn random_u64(slot_hashes: &AccountInfo, key: &Pubkey, salt: &[u8]) -> Result<u64> {
+    require_keys_eq!(
+        *slot_hashes.key,
+        sysvar::slot_hashes::ID,
+        TradingBotError::InvalidSlotHashes
+    );
+    let data = slot_hashes.try_borrow_data()?;
+    let newest = data
+        .get(NEWEST_ENTRY)
+        .ok_or(TradingBotError::InvalidSlotHashes)?;
+    let hash = hashv(&[newest, key.as_ref(), salt]).to_bytes();
+    Ok(u64::from_le_bytes(hash[..8].try_into().unwrap()))
+}
+
+// An offset in 0..=max; the modulo bias is negligible at jitter sizes
+pub fn offset(slot_hashes: &AccountInfo, key: &Pubkey, salt: &[u8], max: u64) -> Result<u64> {
+    let value = random_u64(slot_hashes, key, salt)?;
+    Ok(match max.checked_add(1) {
+        Some(range) => value % range,
+        None => value,
+    })
+}
@@ -1,19 +1,42 @@
+#![allow(clippy::result_large_err)]
+
+use account_manager::*;
 use anchor_lang::prelude::*;
+use arbitrage::*;
+use bot_strategy::*;
 use instructions::*;
+use liquidity_manager::*;
 pub mod math;
 
 declare_id!("3seUuDx9nQXF18sEtcyZBkrf4YQjxHJuYFS26JVn1ERK");
 
+pub mod account_manager;
+pub mod arbitrage;
+pub mod bot_strategy;
+pub mod bridge;
 pub mod constants;
+pub mod dex;
+pub mod events;
 pub mod instructions;
+pub mod introspection;
+pub mod jitter;
+pub mod liquidity_manager;
+pub mod merkle;
+pub mod oracles;
+pub mod regime;
+pub mod risk;
 pub mod state;
 pub mod errors;
+pub mod types;
+pub mod utils;
+pub mod valuation;
 
 
 #[program]
 pub mod on_chain_trading_bot {
     use super::*;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn setup_dca(
         ctx: Context<SetupDca>,
         application_idx: u64,
@@ -43,4 +66,1444 @@ pub mod on_chain_trading_bot {
     pub fn airdrop(ctx: Context<Airdrop>) -> Result<()> {
         instructions::airdrop(ctx)
     }
+
+    pub fn init_market_maker(
+        ctx: Context<InitMarketMaker>,
+        params: state::MarketMakerParams,
+    ) -> Result<()> {
+        instructions::init_market_maker(ctx, params)
+    }
+
+    pub fn requote_market_maker(ctx: Context<RequoteMarketMaker>) -> Result<()> {
+        instructions::requote_market_maker(ctx)
+    }
+
+    pub fn settle_market_maker<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleMarketMaker<'info>>,
+    ) -> Result<()> {
+        instructions::settle_market_maker(ctx)
+    }
+
+    pub fn hedge_market_maker(
+        ctx: Context<HedgeMarketMaker>,
+        hedge_data: Vec<u8>,
+        max_slippage_bps: u16,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::hedge_market_maker(ctx, hedge_data, max_slippage_bps, deadline)
+    }
+
+    pub fn initiate_bridge_transfer(
+        ctx: Context<InitiateBridgeTransfer>,
+        params: state::BridgeTransferParams,
+    ) -> Result<()> {
+        instructions::initiate_bridge_transfer(ctx, params)
+    }
+
+    pub fn register_inbound_transfer(
+        ctx: Context<RegisterInboundTransfer>,
+        params: state::BridgeTransferParams,
+    ) -> Result<()> {
+        instructions::register_inbound_transfer(ctx, params)
+    }
+
+    pub fn complete_bridge_transfer(
+        ctx: Context<CompleteBridgeTransfer>,
+        sequence: u64,
+        redeem_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::complete_bridge_transfer(ctx, sequence, redeem_data)
+    }
+
+    pub fn create_otc_offer(
+        ctx: Context<CreateOtcOffer>,
+        idx: u64,
+        offer_amount: u64,
+        ask_amount: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::create_otc_offer(ctx, idx, offer_amount, ask_amount, expires_at, taker)
+    }
+
+    pub fn fill_otc_offer(ctx: Context<FillOtcOffer>) -> Result<()> {
+        instructions::fill_otc_offer(ctx)
+    }
+
+    pub fn cancel_otc_offer(ctx: Context<CancelOtcOffer>) -> Result<()> {
+        instructions::cancel_otc_offer(ctx)
+    }
+
+    pub fn start_liquidation_auction(
+        ctx: Context<StartLiquidationAuction>,
+        idx: u64,
+        amount: u64,
+        floor_discount_bps: u16,
+        duration: i64,
+        reason: state::ForcedExitReason,
+    ) -> Result<()> {
+        instructions::start_liquidation_auction(
+            ctx,
+            idx,
+            amount,
+            floor_discount_bps,
+            duration,
+            reason,
+        )
+    }
+
+    pub fn force_liquidation_auction(
+        ctx: Context<ForceLiquidationAuction>,
+        idx: u64,
+    ) -> Result<()> {
+        instructions::force_liquidation_auction(ctx, idx)
+    }
+
+    pub fn take_liquidation_auction(
+        ctx: Context<TakeLiquidationAuction>,
+        amount: u64,
+        max_price: u64,
+    ) -> Result<()> {
+        instructions::take_liquidation_auction(ctx, amount, max_price)
+    }
+
+    pub fn cancel_liquidation_auction(ctx: Context<CancelLiquidationAuction>) -> Result<()> {
+        instructions::cancel_liquidation_auction(ctx)
+    }
+
+    pub fn new_distributor(
+        ctx: Context<NewDistributor>,
+        campaign_id: u64,
+        merkle_root: [u8; 32],
+        max_total_claim: u64,
+    ) -> Result<()> {
+        instructions::new_distributor(ctx, campaign_id, merkle_root, max_total_claim)
+    }
+
+    pub fn update_distributor_root(
+        ctx: Context<UpdateDistributorRoot>,
+        merkle_root: [u8; 32],
+        max_total_claim: u64,
+    ) -> Result<()> {
+        instructions::update_distributor_root(ctx, merkle_root, max_total_claim)
+    }
+
+    pub fn claim_rewards(
+        ctx: Context<ClaimRewards>,
+        cumulative_amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim_rewards(ctx, cumulative_amount, proof)
+    }
+
+    pub fn start_mining_epoch(
+        ctx: Context<StartMiningEpoch>,
+        epoch: u64,
+        start_at: i64,
+        end_at: i64,
+        reward_pool: u64,
+    ) -> Result<()> {
+        instructions::start_mining_epoch(ctx, epoch, start_at, end_at, reward_pool)
+    }
+
+    pub fn open_epoch_volume(ctx: Context<OpenEpochVolume>) -> Result<()> {
+        instructions::open_epoch_volume(ctx)
+    }
+
+    pub fn allocate_epoch_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, AllocateEpochRewards<'info>>,
+    ) -> Result<()> {
+        instructions::allocate_epoch_rewards(ctx)
+    }
+
+    pub fn init_trade_history(ctx: Context<InitTradeHistory>) -> Result<()> {
+        instructions::init_trade_history(ctx)
+    }
+
+    pub fn view_strategy_summary(
+        ctx: Context<ViewStrategySummary>,
+    ) -> Result<instructions::StrategySummary> {
+        instructions::view_strategy_summary(ctx)
+    }
+
+    pub fn view_volume_utilization(
+        ctx: Context<ViewVolumeUtilization>,
+    ) -> Result<instructions::VolumeUtilization> {
+        instructions::view_volume_utilization(ctx)
+    }
+
+    pub fn place_limit_order(
+        ctx: Context<PlaceLimitOrder>,
+        idx: u64,
+        is_buy: bool,
+        limit_price: u64,
+        size: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::place_limit_order(ctx, idx, is_buy, limit_price, size, expires_at)
+    }
+
+    pub fn fill_limit_order(
+        ctx: Context<FillLimitOrder>,
+        fill_size: u64,
+        fill_price: u64,
+    ) -> Result<()> {
+        instructions::fill_limit_order(ctx, fill_size, fill_price)
+    }
+
+    pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+        instructions::cancel_limit_order(ctx)
+    }
+
+    pub fn expire_orders<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExpireOrders<'info>>,
+        owner: Pubkey,
+        start_index: u64,
+        max_items: u16,
+    ) -> Result<()> {
+        instructions::expire_orders(ctx, owner, start_index, max_items)
+    }
+
+    pub fn expire_market_maker_quotes(ctx: Context<RequoteMarketMaker>) -> Result<()> {
+        instructions::expire_market_maker_quotes(ctx)
+    }
+
+    pub fn init_trading_vault(ctx: Context<InitTradingVault>) -> Result<()> {
+        instructions::init_trading_vault(ctx)
+    }
+
+    pub fn deposit_trading_vault(ctx: Context<TradingVaultTransfer>, amount: u64) -> Result<()> {
+        instructions::deposit_trading_vault(ctx, amount)
+    }
+
+    pub fn withdraw_trading_vault(ctx: Context<WithdrawTradingVault>, amount: u64) -> Result<()> {
+        instructions::withdraw_trading_vault(ctx, amount)
+    }
+
+    pub fn lock_quote(
+        ctx: Context<LockQuote>,
+        idx: u64,
+        amount_in: u64,
+        quoted_out: u64,
+        max_slots: u64,
+        tolerance_bps: u16,
+    ) -> Result<()> {
+        instructions::lock_quote(ctx, idx, amount_in, quoted_out, max_slots, tolerance_bps)
+    }
+
+    pub fn fill_locked_quote(ctx: Context<FillLockedQuote>, swap_data: Vec<u8>) -> Result<()> {
+        instructions::fill_locked_quote(ctx, swap_data)
+    }
+
+    pub fn clone_market_maker(
+        ctx: Context<CloneMarketMaker>,
+        variant: u16,
+        overrides: state::MarketMakerOverrides,
+    ) -> Result<()> {
+        instructions::clone_market_maker(ctx, variant, overrides)
+    }
+
+    pub fn init_outbox(ctx: Context<InitOutbox>) -> Result<()> {
+        instructions::init_outbox(ctx)
+    }
+
+    pub fn initialize_bot(
+        ctx: Context<InitializeBot>,
+        strategy_id: String,
+        config: state::StrategyConfig,
+        risk_parameters: state::RiskParameters,
+    ) -> Result<()> {
+        instructions::initialize_bot(ctx, strategy_id, config, risk_parameters)
+    }
+
+    pub fn execute_strategy<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteStrategy<'info>>,
+        blocks: Vec<StrategyBlock>,
+        venue_calls: Vec<dex::VenueCall>,
+        deadline: i64,
+    ) -> Result<()> {
+        BotStrategy::execute_strategy_blocks(ctx, blocks, venue_calls, deadline)
+    }
+
+    pub fn check_triggers(ctx: Context<CheckTriggers>, blocks: Vec<StrategyBlock>) -> Result<()> {
+        BotStrategy::check_triggers(ctx, blocks)
+    }
+
+    pub fn dry_run_strategy(
+        ctx: Context<ExecuteStrategy>,
+        blocks: Vec<StrategyBlock>,
+        price: i64,
+    ) -> Result<DryRunReport> {
+        BotStrategy::dry_run_strategy_blocks(ctx, blocks, price)
+    }
+
+    pub fn find_arbitrage(
+        ctx: Context<ArbitrageSearch>,
+        token_pair: types::TokenPair,
+        min_profit: u64,
+    ) -> Result<Vec<ArbitrageRoute>> {
+        ArbitrageManager::find_arbitrage_opportunities(ctx, token_pair, min_profit)
+    }
+
+    pub fn execute_arbitrage<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteArbitrage<'info>>,
+        route: ArbitrageRoute,
+        entry_call: dex::VenueCall,
+        exit_call: dex::VenueCall,
+    ) -> Result<()> {
+        ArbitrageManager::execute_arbitrage(ctx, route, entry_call, exit_call)
+    }
+
+    pub fn optimize_liquidity_provision<'info>(
+        ctx: Context<'_, '_, '_, 'info, OptimizeLiquidity<'info>>,
+        pool_configs: Vec<PoolConfig>,
+        total_liquidity: u64,
+    ) -> Result<Vec<u64>> {
+        CrossDexLiquidityManager::optimize_liquidity_provision(ctx, pool_configs, total_liquidity)
+    }
+
+    pub fn register_price_band(
+        ctx: Context<RegisterPriceBand>,
+        dex: types::DexType,
+        lower_price: u64,
+        upper_price: u64,
+        width_bps: u16,
+    ) -> Result<()> {
+        CrossDexLiquidityManager::register_price_band(ctx, dex, lower_price, upper_price, width_bps)
+    }
+
+    pub fn check_and_rebalance(ctx: Context<CheckPriceBand>) -> Result<()> {
+        CrossDexLiquidityManager::check_and_rebalance(ctx)
+    }
+
+    pub fn init_migration_rule<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitMigrationRule<'info>>,
+        keeper: Pubkey,
+        pair: types::TokenPair,
+        apr_floor_bps: u64,
+        min_streak: u8,
+        daily_cap: u64,
+    ) -> Result<()> {
+        CrossDexLiquidityManager::init_migration_rule(
+            ctx,
+            keeper,
+            pair,
+            apr_floor_bps,
+            min_streak,
+            daily_cap,
+        )
+    }
+
+    pub fn migrate_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateLiquidity<'info>>,
+        from: u8,
+        amount: u64,
+        limits: MigrationLimits,
+        calls: MigrationCalls,
+        deadline: i64,
+    ) -> Result<()> {
+        CrossDexLiquidityManager::migrate_liquidity(ctx, from, amount, limits, calls, deadline)
+    }
+
+    pub fn release_migration_funds(
+        ctx: Context<ReleaseMigrationFunds>,
+        amount: u64,
+    ) -> Result<()> {
+        CrossDexLiquidityManager::release_migration_funds(ctx, amount)
+    }
+
+    pub fn migrate_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigratePosition<'info>>,
+        source_dex: types::DexType,
+        target_dex: types::DexType,
+        lp_amount: u64,
+        limits: MigrationLimits,
+        calls: MigrationCalls,
+        deadline: i64,
+    ) -> Result<()> {
+        CrossDexLiquidityManager::migrate_position(
+            ctx,
+            source_dex,
+            target_dex,
+            lp_amount,
+            limits,
+            calls,
+            deadline,
+        )
+    }
+
+    pub fn configure_escrow(
+        ctx: Context<ConfigureEscrow>,
+        beneficiary: Pubkey,
+        cancel_policy: state::CancelPolicy,
+    ) -> Result<()> {
+        instructions::configure_escrow(ctx, beneficiary, cancel_policy)
+    }
+
+    pub fn cancel_dca(ctx: Context<CancelDca>) -> Result<()> {
+        instructions::cancel_dca(ctx)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_recurring_transfer(
+        ctx: Context<CreateRecurringTransfer>,
+        idx: u64,
+        amount_per_cycle: u64,
+        cycle_frequency: i64,
+        start_at: Option<i64>,
+        max_cycles: u32,
+        deposit: u64,
+        basis: types::TimeBasis,
+    ) -> Result<()> {
+        instructions::create_recurring_transfer(
+            ctx,
+            idx,
+            amount_per_cycle,
+            cycle_frequency,
+            start_at,
+            max_cycles,
+            deposit,
+            basis,
+        )
+    }
+
+    pub fn execute_recurring_transfer(ctx: Context<ExecuteRecurringTransfer>) -> Result<()> {
+        instructions::execute_recurring_transfer(ctx)
+    }
+
+    pub fn top_up_recurring_transfer(
+        ctx: Context<TopUpRecurringTransfer>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::top_up_recurring_transfer(ctx, amount)
+    }
+
+    pub fn cancel_recurring_transfer(ctx: Context<CancelRecurringTransfer>) -> Result<()> {
+        instructions::cancel_recurring_transfer(ctx)
+    }
+
+    pub fn harvest_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, HarvestRewards<'info>>,
+        claim_data: Vec<u8>,
+        claim_accounts_len: u8,
+        swap_data: Option<Vec<u8>>,
+        min_base_out: u64,
+    ) -> Result<()> {
+        instructions::harvest_rewards(ctx, claim_data, claim_accounts_len, swap_data, min_base_out)
+    }
+
+    pub fn init_depeg_guard(
+        ctx: Context<InitDepegGuard>,
+        depeg_threshold: u64,
+        restore_threshold: u64,
+        min_duration: i64,
+        max_confidence: u64,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::init_depeg_guard(
+            ctx,
+            depeg_threshold,
+            restore_threshold,
+            min_duration,
+            max_confidence,
+            max_slippage_bps,
+        )
+    }
+
+    pub fn poll_depeg_guard(ctx: Context<PollDepegGuard>) -> Result<()> {
+        instructions::poll_depeg_guard(ctx)
+    }
+
+    pub fn rotate_depeg_guard<'info>(
+        ctx: Context<'_, '_, '_, 'info, RotateDepegGuard<'info>>,
+        swap_data: Vec<u8>,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::rotate_depeg_guard(ctx, swap_data, deadline)
+    }
+
+    pub fn init_regime_switch(
+        ctx: Context<InitRegimeSwitch>,
+        sample_interval: i64,
+        thresholds: regime::RegimeThresholds,
+        param_sets: [state::RegimeParams; 3],
+    ) -> Result<()> {
+        instructions::init_regime_switch(ctx, sample_interval, thresholds, param_sets)
+    }
+
+    pub fn set_regime_params(
+        ctx: Context<SetRegimeParams>,
+        regime: regime::VolatilityRegime,
+        regime_params: state::RegimeParams,
+    ) -> Result<()> {
+        instructions::set_regime_params(ctx, regime, regime_params)
+    }
+
+    pub fn sample_regime(ctx: Context<SampleRegime>) -> Result<()> {
+        instructions::sample_regime(ctx)
+    }
+
+    pub fn init_correlation_matrix(
+        ctx: Context<InitCorrelationMatrix>,
+        keeper: Pubkey,
+        assets: Vec<Pubkey>,
+        max_age: i64,
+    ) -> Result<()> {
+        instructions::init_correlation_matrix(ctx, keeper, assets, max_age)
+    }
+
+    pub fn update_correlation_matrix(
+        ctx: Context<UpdateCorrelationMatrix>,
+        correlations: Vec<i16>,
+        exposures: Vec<i64>,
+    ) -> Result<()> {
+        instructions::update_correlation_matrix(ctx, correlations, exposures)
+    }
+
+    pub fn init_strategy_registry(ctx: Context<InitStrategyRegistry>) -> Result<()> {
+        instructions::init_strategy_registry(ctx)
+    }
+
+    pub fn publish_strategy(ctx: Context<PublishStrategy>, name: String) -> Result<()> {
+        instructions::publish_strategy(ctx, name)
+    }
+
+    pub fn snapshot_public_profile(ctx: Context<SnapshotPublicProfile>) -> Result<()> {
+        instructions::snapshot_public_profile(ctx)
+    }
+
+    pub fn snapshot_registry<'info>(
+        ctx: Context<'_, '_, '_, 'info, SnapshotRegistry<'info>>,
+        start_index: u64,
+        max_items: u16,
+    ) -> Result<()> {
+        instructions::snapshot_registry(ctx, start_index, max_items)
+    }
+
+    pub fn unpublish_strategy(ctx: Context<UnpublishStrategy>) -> Result<()> {
+        instructions::unpublish_strategy(ctx)
+    }
+
+    pub fn create_subscription_plan(
+        ctx: Context<CreateSubscriptionPlan>,
+        period_fee: u64,
+    ) -> Result<()> {
+        instructions::create_subscription_plan(ctx, period_fee)
+    }
+
+    pub fn update_subscription_plan(
+        ctx: Context<UpdateSubscriptionPlan>,
+        period_fee: u64,
+        is_active: bool,
+    ) -> Result<()> {
+        instructions::update_subscription_plan(ctx, period_fee, is_active)
+    }
+
+    pub fn subscribe(ctx: Context<Subscribe>, periods: u16) -> Result<()> {
+        instructions::subscribe(ctx, periods)
+    }
+
+    pub fn renew_subscription(ctx: Context<RenewSubscription>, periods: u16) -> Result<()> {
+        instructions::renew_subscription(ctx, periods)
+    }
+
+    pub fn release_subscription_fees(ctx: Context<ReleaseSubscriptionFees>) -> Result<()> {
+        instructions::release_subscription_fees(ctx)
+    }
+
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        instructions::cancel_subscription(ctx)
+    }
+
+    pub fn configure_volatility_halt(
+        ctx: Context<ConfigureVolatilityHalt>,
+        max_move_bps: u16,
+        max_confidence_bps: u16,
+        window: i64,
+        cooldown: i64,
+    ) -> Result<()> {
+        instructions::configure_volatility_halt(
+            ctx,
+            max_move_bps,
+            max_confidence_bps,
+            window,
+            cooldown,
+        )
+    }
+
+    pub fn poll_volatility_halt(ctx: Context<PollVolatilityHalt>) -> Result<()> {
+        instructions::poll_volatility_halt(ctx)
+    }
+
+    pub fn init_fill_tracker(ctx: Context<InitFillTracker>) -> Result<()> {
+        instructions::init_fill_tracker(ctx)
+    }
+
+    pub fn consume_fills(ctx: Context<ConsumeFills>) -> Result<()> {
+        instructions::consume_fills(ctx)
+    }
+
+    pub fn init_execution_metrics(
+        ctx: Context<InitExecutionMetrics>,
+        reporter: Pubkey,
+        thresholds: state::VenueBanThresholds,
+    ) -> Result<()> {
+        instructions::init_execution_metrics(ctx, reporter, thresholds)
+    }
+
+    pub fn record_venue_execution(
+        ctx: Context<RecordVenueExecution>,
+        dex: types::DexType,
+        success: bool,
+        slippage_bps: u64,
+        deviation_bps: u64,
+    ) -> Result<()> {
+        instructions::record_venue_execution(ctx, dex, success, slippage_bps, deviation_bps)
+    }
+
+    pub fn record_fill_attribution(
+        ctx: Context<RecordVenueExecution>,
+        dex: types::DexType,
+        prices: state::FillPrices,
+    ) -> Result<()> {
+        instructions::record_fill_attribution(ctx, dex, prices)
+    }
+
+    pub fn reenable_venue(ctx: Context<ManageVenue>, dex: types::DexType) -> Result<()> {
+        instructions::reenable_venue(ctx, dex)
+    }
+
+    pub fn set_venue_ban_thresholds(
+        ctx: Context<ManageVenue>,
+        thresholds: state::VenueBanThresholds,
+    ) -> Result<()> {
+        instructions::set_venue_ban_thresholds(ctx, thresholds)
+    }
+
+    pub fn configure_approval_policy(
+        ctx: Context<ConfigureApprovalPolicy>,
+        co_signer: Pubkey,
+        large_trade_threshold: u64,
+    ) -> Result<()> {
+        instructions::configure_approval_policy(ctx, co_signer, large_trade_threshold)
+    }
+
+    pub fn request_trade_approval(
+        ctx: Context<RequestTradeApproval>,
+        max_amount: u64,
+        ttl: i64,
+    ) -> Result<()> {
+        instructions::request_trade_approval(ctx, max_amount, ttl)
+    }
+
+    pub fn approve_trade(ctx: Context<ApproveTrade>) -> Result<()> {
+        instructions::approve_trade(ctx)
+    }
+
+    pub fn cancel_trade_approval(ctx: Context<CancelTradeApproval>) -> Result<()> {
+        instructions::cancel_trade_approval(ctx)
+    }
+
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        duration: i64,
+        max_trade_amount: u64,
+        volume_cap: u64,
+    ) -> Result<()> {
+        instructions::create_session(ctx, duration, max_trade_amount, volume_cap)
+    }
+
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        instructions::revoke_session(ctx)
+    }
+
+    pub fn deposit_strategy_funds(ctx: Context<DepositStrategyFunds>, amount: u64) -> Result<()> {
+        instructions::deposit_strategy_funds(ctx, amount)
+    }
+
+    pub fn withdraw_strategy_funds(ctx: Context<WithdrawStrategyFunds>, amount: u64) -> Result<()> {
+        instructions::withdraw_strategy_funds(ctx, amount)
+    }
+
+    pub fn deposit_market_maker_funds(
+        ctx: Context<MoveMarketMakerFunds>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_market_maker_funds(ctx, amount)
+    }
+
+    pub fn withdraw_market_maker_funds(
+        ctx: Context<MoveMarketMakerFunds>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_market_maker_funds(ctx, amount)
+    }
+
+    pub fn init_recovery_config(
+        ctx: Context<InitRecoveryConfig>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        timelock: i64,
+    ) -> Result<()> {
+        instructions::init_recovery_config(ctx, guardians, threshold, timelock)
+    }
+
+    pub fn update_recovery_config(
+        ctx: Context<ManageRecovery>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        timelock: i64,
+    ) -> Result<()> {
+        instructions::update_recovery_config(ctx, guardians, threshold, timelock)
+    }
+
+    pub fn cancel_recovery(ctx: Context<ManageRecovery>) -> Result<()> {
+        instructions::cancel_recovery(ctx)
+    }
+
+    pub fn approve_recovery(ctx: Context<ApproveRecovery>, new_owner: Pubkey) -> Result<()> {
+        instructions::approve_recovery(ctx, new_owner)
+    }
+
+    pub fn execute_recovery<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteRecovery<'info>>,
+    ) -> Result<()> {
+        instructions::execute_recovery(ctx)
+    }
+
+    pub fn add_withdrawal_address(
+        ctx: Context<ManageWithdrawalAllowlist>,
+        address: Pubkey,
+    ) -> Result<()> {
+        instructions::add_withdrawal_address(ctx, address)
+    }
+
+    pub fn remove_withdrawal_address(
+        ctx: Context<ManageWithdrawalAllowlist>,
+        address: Pubkey,
+    ) -> Result<()> {
+        instructions::remove_withdrawal_address(ctx, address)
+    }
+
+    pub fn set_withdrawal_allowlist_enforced(
+        ctx: Context<ManageWithdrawalAllowlist>,
+        enforced: bool,
+    ) -> Result<()> {
+        instructions::set_withdrawal_allowlist_enforced(ctx, enforced)
+    }
+
+    pub fn init_pool_sampler(
+        ctx: Context<InitPoolSampler>,
+        keeper: Pubkey,
+        dex: types::DexType,
+        pair: types::TokenPair,
+        min_interval: i64,
+    ) -> Result<()> {
+        instructions::init_pool_sampler(ctx, keeper, dex, pair, min_interval)
+    }
+
+    pub fn record_pool_sample(
+        ctx: Context<RecordPoolSample>,
+        fee_growth: u64,
+        tvl: u64,
+        price: u64,
+    ) -> Result<()> {
+        instructions::record_pool_sample(ctx, fee_growth, tvl, price)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn start_token_launch(
+        ctx: Context<StartTokenLaunch>,
+        idx: u64,
+        amount: u64,
+        start_price: u64,
+        floor_price: u64,
+        start_at: i64,
+        duration: i64,
+        interval: i64,
+        interval_cap: u64,
+    ) -> Result<()> {
+        instructions::start_token_launch(
+            ctx,
+            idx,
+            amount,
+            start_price,
+            floor_price,
+            start_at,
+            duration,
+            interval,
+            interval_cap,
+        )
+    }
+
+    pub fn buy_token_launch(
+        ctx: Context<BuyTokenLaunch>,
+        amount: u64,
+        max_price: u64,
+    ) -> Result<()> {
+        instructions::buy_token_launch(ctx, amount, max_price)
+    }
+
+    pub fn close_token_launch(ctx: Context<CloseTokenLaunch>) -> Result<()> {
+        instructions::close_token_launch(ctx)
+    }
+
+    pub fn init_treasury_plan(
+        ctx: Context<InitTreasuryPlan>,
+        governance: Pubkey,
+        params: state::TreasuryPlanParams,
+    ) -> Result<()> {
+        instructions::init_treasury_plan(ctx, governance, params)
+    }
+
+    pub fn update_treasury_plan(
+        ctx: Context<UpdateTreasuryPlan>,
+        params: state::TreasuryPlanParams,
+    ) -> Result<()> {
+        instructions::update_treasury_plan(ctx, params)
+    }
+
+    pub fn execute_treasury_slice<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteTreasurySlice<'info>>,
+        swap_data: Vec<u8>,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::execute_treasury_slice(ctx, swap_data, deadline)
+    }
+
+    pub fn place_treasury_limit_order(
+        ctx: Context<PlaceTreasuryLimitOrder>,
+        idx: u64,
+        limit_price: u64,
+        size: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::place_treasury_limit_order(ctx, idx, limit_price, size, expires_at)
+    }
+
+    pub fn create_treasury_otc_offer(
+        ctx: Context<CreateTreasuryOtcOffer>,
+        idx: u64,
+        offer_amount: u64,
+        ask_amount: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::create_treasury_otc_offer(
+            ctx,
+            idx,
+            offer_amount,
+            ask_amount,
+            expires_at,
+            taker,
+        )
+    }
+
+    pub fn init_compute_profile(
+        ctx: Context<InitComputeProfile>,
+        reporter: Pubkey,
+        default_block_cu: u64,
+        margin_bps: u16,
+    ) -> Result<()> {
+        instructions::init_compute_profile(ctx, reporter, default_block_cu, margin_bps)
+    }
+
+    pub fn record_compute_usage(
+        ctx: Context<RecordComputeUsage>,
+        overhead_cu: u64,
+        samples: Vec<state::ComputeSample>,
+    ) -> Result<()> {
+        instructions::record_compute_usage(ctx, overhead_cu, samples)
+    }
+
+    pub fn estimated_cu_for_next_execution(
+        ctx: Context<ViewComputeEstimate>,
+        blocks: Vec<StrategyBlock>,
+    ) -> Result<instructions::ComputeEstimate> {
+        instructions::estimated_cu_for_next_execution(ctx, blocks)
+    }
+
+    pub fn set_compute_fee_oracle(
+        ctx: Context<SetComputeFeeOracle>,
+        fee_percentile: state::FeePercentile,
+    ) -> Result<()> {
+        instructions::set_compute_fee_oracle(ctx, fee_percentile)
+    }
+
+    pub fn init_fee_oracle(ctx: Context<InitFeeOracle>, max_age: i64) -> Result<()> {
+        instructions::init_fee_oracle(ctx, max_age)
+    }
+
+    pub fn add_fee_keeper(ctx: Context<ManageFeeKeepers>, keeper: Pubkey) -> Result<()> {
+        instructions::add_fee_keeper(ctx, keeper)
+    }
+
+    pub fn remove_fee_keeper(ctx: Context<ManageFeeKeepers>, keeper: Pubkey) -> Result<()> {
+        instructions::remove_fee_keeper(ctx, keeper)
+    }
+
+    pub fn post_priority_fees(
+        ctx: Context<PostPriorityFees>,
+        fees: [u64; state::FEE_PERCENTILES],
+    ) -> Result<()> {
+        instructions::post_priority_fees(ctx, fees)
+    }
+
+    pub fn view_priority_fees(
+        ctx: Context<ViewPriorityFees>,
+        compute_units: u64,
+    ) -> Result<instructions::PriorityFeeQuote> {
+        instructions::view_priority_fees(ctx, compute_units)
+    }
+
+    pub fn view_batch_quotes<'info>(
+        ctx: Context<'_, '_, '_, 'info, ViewBatchQuotes<'info>>,
+        candidates: Vec<instructions::QuoteCandidate>,
+    ) -> Result<Vec<instructions::BatchQuote>> {
+        instructions::view_batch_quotes(ctx, candidates)
+    }
+
+    pub fn init_faucet(ctx: Context<InitFaucet>, decimals: u8, max_per_request: u64) -> Result<()> {
+        instructions::init_faucet(ctx, decimals, max_per_request)
+    }
+
+    pub fn request_faucet_tokens(ctx: Context<RequestFaucetTokens>, amount: u64) -> Result<()> {
+        instructions::request_faucet_tokens(ctx, amount)
+    }
+
+    pub fn init_mock_pool(ctx: Context<InitMockPool>, fee_bps: u16) -> Result<()> {
+        instructions::init_mock_pool(ctx, fee_bps)
+    }
+
+    pub fn add_mock_liquidity(
+        ctx: Context<MockPoolTrade>,
+        amount_a: u64,
+        amount_b: u64,
+    ) -> Result<()> {
+        instructions::add_mock_liquidity(ctx, amount_a, amount_b)
+    }
+
+    pub fn mock_swap(
+        ctx: Context<MockPoolTrade>,
+        amount_in: u64,
+        minimum_out: u64,
+        a_to_b: bool,
+    ) -> Result<()> {
+        instructions::mock_swap(ctx, amount_in, minimum_out, a_to_b)
+    }
+
+    pub fn init_mock_price_feed(
+        ctx: Context<InitMockPriceFeed>,
+        idx: u64,
+        expo: i32,
+    ) -> Result<()> {
+        instructions::init_mock_price_feed(ctx, idx, expo)
+    }
+
+    pub fn set_mock_price(
+        ctx: Context<SetMockPrice>,
+        price: i64,
+        conf: u64,
+        publish_time: Option<i64>,
+    ) -> Result<()> {
+        instructions::set_mock_price(ctx, price, conf, publish_time)
+    }
+
+    pub fn rollover_epoch(ctx: Context<RolloverEpoch>) -> Result<()> {
+        instructions::rollover_epoch(ctx)
+    }
+
+    pub fn init_portfolio(ctx: Context<InitPortfolio>, crossing_fee_bps: u16) -> Result<()> {
+        instructions::init_portfolio(ctx, crossing_fee_bps)
+    }
+
+    pub fn add_portfolio_strategy(ctx: Context<ManagePortfolioStrategy>) -> Result<()> {
+        instructions::add_portfolio_strategy(ctx)
+    }
+
+    pub fn remove_portfolio_strategy(ctx: Context<ManagePortfolioStrategy>) -> Result<()> {
+        instructions::remove_portfolio_strategy(ctx)
+    }
+
+    pub fn post_netting_order(
+        ctx: Context<ManagePortfolioStrategy>,
+        side: types::TradeSide,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::post_netting_order(ctx, side, amount)
+    }
+
+    pub fn close_netting_order(ctx: Context<ManagePortfolioStrategy>) -> Result<()> {
+        instructions::close_netting_order(ctx)
+    }
+
+    pub fn net_portfolio_orders(ctx: Context<NetPortfolioOrders>) -> Result<()> {
+        instructions::net_portfolio_orders(ctx)
+    }
+
+    pub fn list_internal_order(ctx: Context<ListInternalOrder>) -> Result<()> {
+        instructions::list_internal_order(ctx)
+    }
+
+    pub fn unlist_internal_order(ctx: Context<UnlistInternalOrder>, order: Pubkey) -> Result<()> {
+        instructions::unlist_internal_order(ctx, order)
+    }
+
+    pub fn cross_internal_orders<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrossInternalOrders<'info>>,
+        amount: u64,
+        limit_price: u64,
+    ) -> Result<()> {
+        instructions::cross_internal_orders(ctx, amount, limit_price)
+    }
+
+    pub fn reduce_position(
+        ctx: Context<ManagePositions>,
+        dex: types::DexType,
+        position_id: Pubkey,
+        pct_bps: u16,
+    ) -> Result<()> {
+        DexAccountManager::reduce_position(ctx, dex, position_id, pct_bps)
+    }
+
+    pub fn soft_unwind_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, SoftUnwindPosition<'info>>,
+        dex: types::DexType,
+        position_id: Pubkey,
+        swap_data: Vec<u8>,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::soft_unwind_position(ctx, dex, position_id, swap_data, deadline)
+    }
+
+    pub fn set_position_stop(
+        ctx: Context<SetPositionStop>,
+        position_id: Pubkey,
+        side: types::TradeSide,
+        entry_price: u64,
+        stop_price: u64,
+        trailing_bps: u16,
+    ) -> Result<()> {
+        instructions::set_position_stop(ctx, position_id, side, entry_price, stop_price, trailing_bps)
+    }
+
+    pub fn close_position_stop(ctx: Context<ClosePositionStop>) -> Result<()> {
+        instructions::close_position_stop(ctx)
+    }
+
+    pub fn set_accounting_currency(
+        ctx: Context<SetAccountingCurrency>,
+        risk_parameters: state::RiskParameters,
+    ) -> Result<()> {
+        instructions::set_accounting_currency(ctx, risk_parameters)
+    }
+
+    pub fn register_keeper(ctx: Context<RegisterKeeper>, bond: u64) -> Result<()> {
+        instructions::register_keeper(ctx, bond)
+    }
+
+    pub fn request_keeper_exit(ctx: Context<ManageKeeperBond>) -> Result<()> {
+        instructions::request_keeper_exit(ctx)
+    }
+
+    pub fn withdraw_keeper_bond(ctx: Context<WithdrawKeeperBond>) -> Result<()> {
+        instructions::withdraw_keeper_bond(ctx)
+    }
+
+    pub fn open_execution_attempt(ctx: Context<OpenExecutionAttempt>) -> Result<()> {
+        instructions::open_execution_attempt(ctx)
+    }
+
+    pub fn slash_keeper(ctx: Context<JudgeKeeper>) -> Result<()> {
+        instructions::slash_keeper(ctx)
+    }
+
+    pub fn add_allowed_keeper(ctx: Context<ManageKeeperAllowlist>, keeper: Pubkey) -> Result<()> {
+        instructions::add_allowed_keeper(ctx, keeper)
+    }
+
+    pub fn remove_allowed_keeper(
+        ctx: Context<ManageKeeperAllowlist>,
+        keeper: Pubkey,
+    ) -> Result<()> {
+        instructions::remove_allowed_keeper(ctx, keeper)
+    }
+
+    pub fn set_keeper_allowlist_enforced(
+        ctx: Context<ManageKeeperAllowlist>,
+        enforced: bool,
+        price_feed: Pubkey,
+    ) -> Result<()> {
+        instructions::set_keeper_allowlist_enforced(ctx, enforced, price_feed)
+    }
+
+    pub fn configure_execution_auction(
+        ctx: Context<ConfigureExecutionAuction>,
+        enabled: bool,
+        bid_window: i64,
+        exclusivity_window: i64,
+        min_bid: u64,
+    ) -> Result<()> {
+        instructions::configure_execution_auction(
+            ctx,
+            enabled,
+            bid_window,
+            exclusivity_window,
+            min_bid,
+        )
+    }
+
+    pub fn bid_execution(ctx: Context<BidExecution>, amount: u64) -> Result<()> {
+        instructions::bid_execution(ctx, amount)
+    }
+
+    pub fn settle_execution_auction(ctx: Context<SettleExecutionAuction>) -> Result<()> {
+        instructions::settle_execution_auction(ctx)
+    }
+
+    pub fn init_callback_config(ctx: Context<InitCallbackConfig>, relay: Pubkey) -> Result<()> {
+        instructions::init_callback_config(ctx, relay)
+    }
+
+    pub fn set_callback_rule(
+        ctx: Context<ManageCallbackConfig>,
+        rule_index: u8,
+        condition: Option<state::CallbackCondition>,
+        channel: state::NotifyChannel,
+    ) -> Result<()> {
+        instructions::set_callback_rule(ctx, rule_index, condition, channel)
+    }
+
+    pub fn set_callback_relay(ctx: Context<ManageCallbackConfig>, relay: Pubkey) -> Result<()> {
+        instructions::set_callback_relay(ctx, relay)
+    }
+
+    pub fn fire_callback(ctx: Context<FireCallback>, rule_index: u8) -> Result<()> {
+        instructions::fire_callback(ctx, rule_index)
+    }
+
+    pub fn rearm_callbacks(ctx: Context<RearmCallbacks>) -> Result<()> {
+        instructions::rearm_callbacks(ctx)
+    }
+
+    pub fn ack_callback_request(ctx: Context<AckCallbackRequest>) -> Result<()> {
+        instructions::ack_callback_request(ctx)
+    }
+
+    pub fn set_strategy_active(ctx: Context<SetStrategyActive>, active: bool) -> Result<()> {
+        instructions::set_strategy_active(ctx, active)
+    }
+
+    pub fn init_feed_registry(ctx: Context<InitFeedRegistry>) -> Result<()> {
+        instructions::init_feed_registry(ctx)
+    }
+
+    pub fn set_registered_feed(
+        ctx: Context<SetRegisteredFeed>,
+        feed: state::RegisteredFeed,
+    ) -> Result<()> {
+        instructions::set_registered_feed(ctx, feed)
+    }
+
+    pub fn remove_registered_feed(ctx: Context<ManageFeedRegistry>, mint: Pubkey) -> Result<()> {
+        instructions::remove_registered_feed(ctx, mint)
+    }
+
+    pub fn set_feed_registry_admin(ctx: Context<ManageFeedRegistry>, admin: Pubkey) -> Result<()> {
+        instructions::set_feed_registry_admin(ctx, admin)
+    }
+
+    pub fn init_pool_registry(ctx: Context<InitPoolRegistry>) -> Result<()> {
+        instructions::init_pool_registry(ctx)
+    }
+
+    pub fn set_registered_pool(
+        ctx: Context<ManagePoolRegistry>,
+        pool: state::RegisteredPool,
+    ) -> Result<()> {
+        instructions::set_registered_pool(ctx, pool)
+    }
+
+    pub fn remove_registered_pool(
+        ctx: Context<ManagePoolRegistry>,
+        pair: types::TokenPair,
+        dex: types::DexType,
+    ) -> Result<()> {
+        instructions::remove_registered_pool(ctx, pair, dex)
+    }
+
+    pub fn set_pool_registry_admin(ctx: Context<ManagePoolRegistry>, admin: Pubkey) -> Result<()> {
+        instructions::set_pool_registry_admin(ctx, admin)
+    }
+
+    pub fn sweep_dust<'info>(
+        ctx: Context<'_, '_, '_, 'info, SweepDust<'info>>,
+        swaps: Vec<instructions::DustSwap>,
+    ) -> Result<()> {
+        instructions::sweep_dust(ctx, swaps)
+    }
+
+    pub fn set_dust_threshold(ctx: Context<SetDustThreshold>, threshold_usd: u64) -> Result<()> {
+        instructions::set_dust_threshold(ctx, threshold_usd)
+    }
+
+    pub fn init_peg_arb(
+        ctx: Context<InitPegArb>,
+        entry_discount_bps: u16,
+        exit_discount_bps: u16,
+    ) -> Result<()> {
+        instructions::init_peg_arb(ctx, entry_discount_bps, exit_discount_bps)
+    }
+
+    pub fn enter_peg_arb<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunPegArb<'info>>,
+        amount_in: u64,
+        swap_data: Vec<u8>,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::enter_peg_arb(ctx, amount_in, swap_data, deadline)
+    }
+
+    pub fn exit_peg_arb<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunPegArb<'info>>,
+        swap_data: Vec<u8>,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::exit_peg_arb(ctx, swap_data, deadline)
+    }
+
+    pub fn unstake_peg_arb<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunPegArb<'info>>,
+        unstake_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::unstake_peg_arb(ctx, unstake_data)
+    }
+
+    pub fn claim_peg_arb<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunPegArb<'info>>,
+        claim_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::claim_peg_arb(ctx, claim_data)
+    }
+
+    pub fn init_leverage_loop(
+        ctx: Context<InitLeverageLoop>,
+        target_ltv_bps: u16,
+        min_health_bps: u16,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::init_leverage_loop(ctx, target_ltv_bps, min_health_bps, max_slippage_bps)
+    }
+
+    pub fn loop_leverage<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunLeverageLoop<'info>>,
+        steps: Vec<instructions::LendingStep>,
+    ) -> Result<()> {
+        instructions::loop_leverage(ctx, steps)
+    }
+
+    pub fn unwind_leverage<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunLeverageLoop<'info>>,
+        steps: Vec<instructions::LendingStep>,
+    ) -> Result<()> {
+        instructions::unwind_leverage(ctx, steps)
+    }
+
+    pub fn init_covered_call(
+        ctx: Context<InitCoveredCall>,
+        max_coverage_bps: u16,
+        min_otm_bps: u16,
+        min_premium_bps: u16,
+        max_tenor: i64,
+    ) -> Result<()> {
+        instructions::init_covered_call(
+            ctx,
+            max_coverage_bps,
+            min_otm_bps,
+            min_premium_bps,
+            max_tenor,
+        )
+    }
+
+    pub fn write_covered_call<'info>(
+        ctx: Context<'_, '_, '_, 'info, WriteCoveredCall<'info>>,
+        contracts: u64,
+        write_accounts_len: u8,
+        write_data: Vec<u8>,
+        sell_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::write_covered_call(ctx, contracts, write_accounts_len, write_data, sell_data)
+    }
+
+    pub fn settle_covered_call<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleCoveredCall<'info>>,
+        settle_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::settle_covered_call(ctx, settle_data)
+    }
+
+    pub fn init_principal_note(ctx: Context<InitPrincipalNote>, floor_bps: u16) -> Result<()> {
+        instructions::init_principal_note(ctx, floor_bps)
+    }
+
+    pub fn open_principal_note<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunPrincipalNote<'info>>,
+        principal: u64,
+        maturity: i64,
+        deposit_accounts_len: u8,
+        deposit_data: Vec<u8>,
+        buy_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::open_principal_note(
+            ctx,
+            principal,
+            maturity,
+            deposit_accounts_len,
+            deposit_data,
+            buy_data,
+        )
+    }
+
+    pub fn value_principal_note(ctx: Context<ValuePrincipalNote>) -> Result<()> {
+        instructions::value_principal_note(ctx)
+    }
+
+    pub fn close_principal_note<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunPrincipalNote<'info>>,
+        redeem_accounts_len: u8,
+        redeem_data: Vec<u8>,
+        sell_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::close_principal_note(ctx, redeem_accounts_len, redeem_data, sell_data)
+    }
+
+    pub fn init_index_fund(
+        ctx: Context<InitIndexFund>,
+        weight_rule: state::WeightRule,
+        non_transferable: bool,
+        rebalance_window: i64,
+        drift_bps: u16,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::init_index_fund(
+            ctx,
+            weight_rule,
+            non_transferable,
+            rebalance_window,
+            drift_bps,
+            max_slippage_bps,
+        )
+    }
+
+    pub fn add_index_component(
+        ctx: Context<AddIndexComponent>,
+        static_weight_bps: u16,
+        max_weight_bps: u16,
+    ) -> Result<()> {
+        instructions::add_index_component(ctx, static_weight_bps, max_weight_bps)
+    }
+
+    pub fn deposit_index<'info>(
+        ctx: Context<'_, '_, '_, 'info, IndexTransfer<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_index(ctx, amount)
+    }
+
+    pub fn request_index_withdrawal(
+        ctx: Context<RequestIndexWithdrawal>,
+        units: u64,
+    ) -> Result<()> {
+        instructions::request_index_withdrawal(ctx, units)
+    }
+
+    pub fn withdraw_index<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawIndex<'info>>,
+    ) -> Result<()> {
+        instructions::withdraw_index(ctx)
+    }
+
+    pub fn reconstitute_index(ctx: Context<ReconstituteIndex>) -> Result<()> {
+        instructions::reconstitute_index(ctx)
+    }
+
+    pub fn rebalance_index<'info>(
+        ctx: Context<'_, '_, '_, 'info, RebalanceIndex<'info>>,
+        component: u8,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::rebalance_index(ctx, component, swap_data)
+    }
+
+    pub fn init_nav_feed(ctx: Context<InitNavFeed>) -> Result<()> {
+        instructions::init_nav_feed(ctx)
+    }
+
+    pub fn publish_nav_feed<'info>(
+        ctx: Context<'_, '_, '_, 'info, PublishNavFeed<'info>>,
+    ) -> Result<()> {
+        instructions::publish_nav_feed(ctx)
+    }
+
+    pub fn set_index_limits(
+        ctx: Context<ManageIndexFund>,
+        limits: state::DepositLimits,
+    ) -> Result<()> {
+        instructions::set_index_limits(ctx, limits)
+    }
+
+    pub fn set_index_depositor(
+        ctx: Context<SetIndexDepositor>,
+        wallet: Pubkey,
+        allowed: bool,
+    ) -> Result<()> {
+        instructions::set_index_depositor(ctx, wallet, allowed)
+    }
+
+    pub fn set_index_lockup(
+        ctx: Context<ManageIndexFund>,
+        lockup_period: i64,
+        early_exit_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::set_index_lockup(ctx, lockup_period, early_exit_fee_bps)
+    }
+
+    pub fn set_index_emergency(
+        ctx: Context<ManageIndexFund>,
+        emergency: bool,
+        slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::set_index_emergency(ctx, emergency, slippage_bps)
+    }
+
+    pub fn emergency_liquidate_index<'info>(
+        ctx: Context<'_, '_, '_, 'info, RebalanceIndex<'info>>,
+        component: u8,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::emergency_liquidate_index(ctx, component, swap_data)
+    }
+
+    pub fn emergency_withdraw_index(
+        ctx: Context<EmergencyWithdrawIndex>,
+        units: u64,
+    ) -> Result<()> {
+        instructions::emergency_withdraw_index(ctx, units)
+    }
+
+    pub fn init_upgrade_guard(ctx: Context<InitUpgradeGuard>) -> Result<()> {
+        instructions::init_upgrade_guard(ctx)
+    }
+
+    pub fn post_upgrade_check(
+        ctx: Context<PostUpgradeCheck>,
+        vaults: u8,
+        nav_tolerance_bps: u16,
+    ) -> Result<()> {
+        instructions::post_upgrade_check(ctx, vaults, nav_tolerance_bps)
+    }
+
+    pub fn init_vault_log(ctx: Context<InitVaultLog>) -> Result<()> {
+        instructions::init_vault_log(ctx)
+    }
 }
\ No newline at end of file
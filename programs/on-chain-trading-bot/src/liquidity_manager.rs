@@ -1,268 +1,645 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
-use crate::dex::{raydium::*, jupiter::*, serum::*};
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct LiquidityRatio {
-    pub dex: DexType,
-    pub pool_id: Pubkey,
-    pub target_ratio: u8, // Percentage (0-100)
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::constants::{
+    MAX_SLIPPAGE_BPS, MIGRATION_RULE_SEED, POOL_REGISTRY_SEED, PRICE_BAND_SEED,
+};
+use crate::dex::{
+    forward_call, jupiter::JUPITER_V6_PROGRAM_ID, raydium::RAYDIUM_AMM_PROGRAM_ID, VenueCall,
+    VenueCalls,
+};
+use crate::errors::TradingBotError;
+use crate::events::{LiquidityMigrated, PositionMigrated, PriceBandExited};
+use crate::math;
+use crate::migration_rule_seeds;
+use crate::state::{
+    MigrationRule, PoolRegistry, PoolSampler, PriceBand, MAX_MIGRATION_POOLS,
+};
+// the generated client structs of the nested upgrade gate resolve relative to this module
+use crate::instructions::upgrade_guard::{
+    UpgradeGate, __client_accounts_upgrade_gate, __cpi_client_accounts_upgrade_gate,
+};
+use crate::types::{DexType, TokenPair};
+use crate::utils::validate_deadline;
+
+// Guards on moving a position between pools. The minimums are on what the withdrawal pays out and
+// the LP tokens the deposit mints; `max_leftover_bps` caps how much of each withdrawn side the
+// target pool may hand back for not matching its ratio.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct MigrationLimits {
+    pub min_base_out: u64,
+    pub min_quote_out: u64,
+    pub min_lp_out: u64,
+    pub max_leftover_bps: u16,
 }
 
+// The venue instructions a migration forwards: the withdrawal from the source pool, then the
+// deposit into the target
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct LiquidityHealth {
-    pub total_value_locked: u64,
-    pub utilization_rate: u8,
-    pub imbalance_ratio: u8,
-    pub risk_score: u8,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub enum DexType {
-    Raydium,
-    Jupiter,
-    Serum,
+pub struct MigrationCalls {
+    pub withdraw: VenueCall,
+    pub deposit: VenueCall,
 }
 
 pub struct CrossDexLiquidityManager;
 
 impl CrossDexLiquidityManager {
-    // Rebalance liquidity across DEXs
-    pub fn rebalance_liquidity(
-        ctx: Context<RebalanceLiquidity>,
-        target_ratios: Vec<LiquidityRatio>,
-    ) -> Result<()> {
-        // Verify total ratio equals 100%
-        let total_ratio: u8 = target_ratios.iter().map(|r| r.target_ratio).sum();
-        require!(total_ratio == 100, TradingBotError::InvalidRatios);
+    // Plans how `total_liquidity` should be spread: the pools with the highest realized fee APR are
+    // filled first. Remaining accounts are the owner's PoolSampler for each config, in the same
+    // order. Returns the amount per config; the moves themselves go through migrate_position.
+    pub fn optimize_liquidity_provision<'info>(
+        ctx: Context<'_, '_, '_, 'info, OptimizeLiquidity<'info>>,
+        pool_configs: Vec<PoolConfig>,
+        total_liquidity: u64,
+    ) -> Result<Vec<u64>> {
+        require_eq!(
+            ctx.remaining_accounts.len(),
+            pool_configs.len(),
+            TradingBotError::InvalidPoolSample
+        );
+        let mut aprs = Vec::with_capacity(pool_configs.len());
+        for (config, account_info) in pool_configs.iter().zip(ctx.remaining_accounts.iter()) {
+            let sampler = Account::<PoolSampler>::try_from(account_info)?;
+            require!(
+                sampler.owner == ctx.accounts.owner.key()
+                    && sampler.pool == config.pool_id
+                    && sampler.dex == config.dex
+                    && config.min_liquidity <= config.max_liquidity,
+                TradingBotError::InvalidPoolSample
+            );
+            aprs.push(sampler.realized_apr_bps()?);
+        }
 
-        // Get current liquidity distribution
-        let current_distribution = Self::get_current_distribution(ctx.accounts)?;
+        Ok(Self::allocate_by_yield(&pool_configs, &aprs, total_liquidity))
+    }
 
-        // Calculate required moves
-        let moves = Self::calculate_rebalance_moves(
-            current_distribution,
-            target_ratios.clone(),
-        )?;
+    // Registers the price range a pool's liquidity is sized for; `width_bps` is the half-width
+    // the band is recentered with once the price leaves it
+    pub fn register_price_band(
+        ctx: Context<RegisterPriceBand>,
+        dex: DexType,
+        lower_price: u64,
+        upper_price: u64,
+        width_bps: u16,
+    ) -> Result<()> {
+        require!(
+            lower_price > 0 && lower_price < upper_price && width_bps > 0 && width_bps < 10000,
+            TradingBotError::InvalidPriceBand
+        );
+
+        let price_band = &mut ctx.accounts.price_band;
+        price_band.owner = ctx.accounts.owner.key();
+        price_band.pool = ctx.accounts.pool.key();
+        price_band.dex = dex;
+        price_band.base_reserve = ctx.accounts.base_reserve.key();
+        price_band.quote_reserve = ctx.accounts.quote_reserve.key();
+        price_band.lower_price = lower_price;
+        price_band.upper_price = upper_price;
+        price_band.width_bps = width_bps;
+        price_band.last_triggered_price = 0;
+        price_band.last_triggered_at = 0;
+        price_band.trigger_count = 0;
+        price_band.bump = *ctx.bumps.get("price_band").unwrap();
 
-        // Execute rebalancing moves
-        for move_action in moves {
-            Self::execute_liquidity_move(
-                ctx.accounts.into(),
-                move_action,
-            )?;
-        }
+        Ok(())
+    }
 
-        // Update liquidity tracking
-        Self::update_liquidity_tracking(
-            ctx.accounts.liquidity_tracker,
-            target_ratios,
+    // Permissionless and cheap for keepers to poll: fails while the pool's implied price is inside
+    // the band. Once it exits, the band is recentered on the new price and PriceBandExited tells
+    // the owner to move the liquidity with migrate_position.
+    pub fn check_and_rebalance(ctx: Context<CheckPriceBand>) -> Result<()> {
+        ctx.accounts.upgrade_gate.require_healthy()?;
+        let price = PriceBand::implied_price(
+            ctx.accounts.base_reserve.amount,
+            ctx.accounts.quote_reserve.amount,
         )?;
+        let price_band = &mut ctx.accounts.price_band;
+        require!(!price_band.contains(price), TradingBotError::PriceWithinBand);
+
+        let now = Clock::get()?.unix_timestamp;
+        emit!(PriceBandExited {
+            owner: price_band.owner,
+            pool: price_band.pool,
+            dex: price_band.dex,
+            price,
+            lower_price: price_band.lower_price,
+            upper_price: price_band.upper_price,
+            timestamp: now,
+        });
+
+        price_band.recenter(price)?;
+        price_band.last_triggered_price = price;
+        price_band.last_triggered_at = now;
+        price_band.trigger_count = price_band.trigger_count.saturating_add(1);
 
         Ok(())
     }
 
-    // Monitor and adjust positions
-    pub fn monitor_liquidity_health(
-        ctx: Context<MonitorLiquidity>,
-    ) -> Result<LiquidityHealth> {
-        // Get current liquidity metrics
-        let total_value = Self::calculate_total_value(ctx.accounts)?;
-        let utilization = Self::calculate_utilization(ctx.accounts)?;
-        let imbalance = Self::calculate_imbalance(ctx.accounts)?;
-
-        // Calculate risk score
-        let risk_score = Self::calculate_risk_score(
-            utilization,
-            imbalance,
-            ctx.accounts.market_volatility,
-        )?;
-
-        // Check if rebalancing is needed
-        if risk_score > ctx.accounts.risk_threshold {
-            Self::trigger_rebalancing(ctx.accounts.clone())?;
+    // Remaining accounts are the candidate pools' samplers, all on `pair` and owned by the signer
+    pub fn init_migration_rule<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitMigrationRule<'info>>,
+        keeper: Pubkey,
+        pair: TokenPair,
+        apr_floor_bps: u64,
+        min_streak: u8,
+        daily_cap: u64,
+    ) -> Result<()> {
+        let sampler_count = ctx.remaining_accounts.len();
+        require!(
+            (2..=MAX_MIGRATION_POOLS).contains(&sampler_count) && min_streak > 0 && daily_cap > 0,
+            TradingBotError::InvalidMigrationRule
+        );
+
+        let rule = &mut ctx.accounts.migration_rule;
+        for (i, account_info) in ctx.remaining_accounts.iter().enumerate() {
+            let sampler = Account::<PoolSampler>::try_from(account_info)?;
+            require!(
+                sampler.owner == ctx.accounts.owner.key()
+                    && sampler.pair == pair
+                    && !rule.samplers[..i].contains(&sampler.key()),
+                TradingBotError::InvalidMigrationRule
+            );
+            rule.samplers[i] = sampler.key();
         }
+        rule.owner = ctx.accounts.owner.key();
+        rule.keeper = keeper;
+        rule.pair = pair;
+        rule.sampler_count = sampler_count as u8;
+        rule.apr_floor_bps = apr_floor_bps;
+        rule.min_streak = min_streak;
+        rule.daily_cap = daily_cap;
+        rule.day_start = 0;
+        rule.migrated_today = 0;
+        rule.migration_count = 0;
+        rule.bump = *ctx.bumps.get("migration_rule").unwrap();
 
-        Ok(LiquidityHealth {
-            total_value_locked: total_value,
-            utilization_rate: utilization,
-            imbalance_ratio: imbalance,
-            risk_score,
-        })
+        Ok(())
     }
 
-    // Optimize liquidity provision
-    pub fn optimize_liquidity_provision(
-        ctx: Context<OptimizeLiquidity>,
-        pool_configs: Vec<PoolConfig>,
+    // Keeper crank moving up to `amount` out of the pool at `from` once its APR has been below the
+    // floor for the rule's streak. The target is the best other pool, which must clear the floor;
+    // the amount is cut to what is left of today's cap. The liquidity is held by the rule, which
+    // signs the venue calls, and the keeper's limits are raised to the position's fair share of
+    // the pools' reserves. Remaining accounts are the rule's samplers followed by the calls'
+    // accounts; the position's pools have to be the source's and the chosen target's.
+    pub fn migrate_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateLiquidity<'info>>,
+        from: u8,
+        amount: u64,
+        limits: MigrationLimits,
+        calls: MigrationCalls,
+        deadline: i64,
     ) -> Result<()> {
-        for config in pool_configs {
-            // Calculate optimal liquidity amount
-            let optimal_amount = Self::calculate_optimal_liquidity(
-                config.clone(),
-                ctx.accounts.market_data.clone(),
-            )?;
-
-            // Adjust liquidity if needed
-            if Self::needs_adjustment(config.clone(), optimal_amount)? {
-                Self::adjust_pool_liquidity(
-                    ctx.accounts.into(),
-                    config,
-                    optimal_amount,
-                )?;
+        validate_deadline(deadline)?;
+        let rule = &ctx.accounts.migration_rule;
+        let sampler_count = rule.samplers().len();
+        require_gte!(
+            ctx.remaining_accounts.len(),
+            sampler_count,
+            TradingBotError::InvalidMigrationRule
+        );
+        let (sampler_accounts, call_accounts) = ctx.remaining_accounts.split_at(sampler_count);
+        let mut samplers = Vec::with_capacity(sampler_count);
+        for (key, account_info) in rule.samplers().iter().zip(sampler_accounts.iter()) {
+            require_keys_eq!(*key, account_info.key(), TradingBotError::InvalidMigrationRule);
+            samplers.push(Account::<PoolSampler>::try_from(account_info)?);
+        }
+
+        let source = samplers
+            .get(from as usize)
+            .ok_or(TradingBotError::InvalidMigrationRule)?;
+        require_gte!(
+            source.below_floor_streak(rule.apr_floor_bps),
+            rule.min_streak,
+            TradingBotError::MigrationNotDue
+        );
+        let source_apr = source.realized_apr_bps()?;
+        let mut target: Option<(&Account<PoolSampler>, u64)> = None;
+        for (i, sampler) in samplers.iter().enumerate() {
+            let apr = sampler.realized_apr_bps()?;
+            if i != from as usize && target.map_or(true, |(_, best)| apr > best) {
+                target = Some((sampler, apr));
             }
         }
+        let (target, target_apr) = target.ok_or(TradingBotError::MigrationNotDue)?;
+        require!(
+            target_apr >= rule.apr_floor_bps && target_apr > source_apr,
+            TradingBotError::MigrationNotDue
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let amount = amount.min(rule.remaining_today(now));
+        require_gt!(amount, 0, TradingBotError::MigrationCapExceeded);
+        let (source_dex, source_pool) = (source.dex, source.pool);
+        let (target_dex, target_pool) = (target.dex, target.pool);
+
+        let accounts = &mut *ctx.accounts;
+        require!(
+            accounts.position.source_pool.key() == source_pool
+                && accounts.position.target_pool.key() == target_pool,
+            TradingBotError::InvalidMigrationRule
+        );
+        let limits = accounts.fair_limits(source_dex, target_dex, amount, limits)?;
+        let rule = accounts.migration_rule.clone();
+        let (base_out, quote_out, lp_out) = accounts.position.migrate(
+            call_accounts,
+            (source_dex, target_dex),
+            amount,
+            limits,
+            calls,
+            &[migration_rule_seeds!(rule)],
+        )?;
+
+        let rule = &mut ctx.accounts.migration_rule;
+        rule.record_migration(amount, now)?;
+        emit!(LiquidityMigrated {
+            owner: rule.owner,
+            from_pool: source_pool,
+            to_pool: target_pool,
+            amount,
+            from_apr_bps: source_apr,
+            to_apr_bps: target_apr,
+            base_out,
+            quote_out,
+            lp_out,
+            deadline,
+            timestamp: now,
+        });
 
         Ok(())
     }
 
-    // Helper functions
-    fn get_current_distribution(
-        accounts: &RebalanceLiquidity,
-    ) -> Result<Vec<(DexType, u64)>> {
-        let mut distribution = Vec::new();
-
-        // Get Raydium liquidity
-        let raydium_tvl = accounts.raydium_pools
-            .iter()
-            .map(|p| p.total_value_locked())
-            .sum::<u64>();
-        distribution.push((DexType::Raydium, raydium_tvl));
-
-        // Get Jupiter liquidity
-        let jupiter_tvl = accounts.jupiter_pools
-            .iter()
-            .map(|p| p.total_value_locked())
-            .sum::<u64>();
-        distribution.push((DexType::Jupiter, jupiter_tvl));
-
-        // Get Serum liquidity
-        let serum_tvl = accounts.serum_markets
-            .iter()
-            .map(|m| m.total_value_locked())
-            .sum::<u64>();
-        distribution.push((DexType::Serum, serum_tvl));
-
-        Ok(distribution)
+    // Owner-initiated move of `lp_amount` of a position from the source pool to the target, e.g.
+    // to chase better fees, under the same guards the migration crank uses. Remaining accounts are
+    // the calls' accounts.
+    pub fn migrate_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigratePosition<'info>>,
+        source_dex: DexType,
+        target_dex: DexType,
+        lp_amount: u64,
+        limits: MigrationLimits,
+        calls: MigrationCalls,
+        deadline: i64,
+    ) -> Result<()> {
+        validate_deadline(deadline)?;
+        let position = &mut ctx.accounts.position;
+        let (base_out, quote_out, lp_out) = position.migrate(
+            ctx.remaining_accounts,
+            (source_dex, target_dex),
+            lp_amount,
+            limits,
+            calls,
+            &[],
+        )?;
+
+        emit!(PositionMigrated {
+            owner: ctx.accounts.owner.key(),
+            from_dex: source_dex,
+            from_pool: position.source_pool.key(),
+            to_dex: target_dex,
+            to_pool: position.target_pool.key(),
+            lp_amount,
+            base_out,
+            quote_out,
+            lp_out,
+            deadline,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Hands the owner back tokens held by their migration rule, LP tokens included
+    pub fn release_migration_funds(ctx: Context<ReleaseMigrationFunds>, amount: u64) -> Result<()> {
+        let rule = &ctx.accounts.migration_rule;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: rule.to_account_info(),
+                },
+                &[migration_rule_seeds!(rule)],
+            ),
+            amount,
+        )
     }
 
-    fn calculate_rebalance_moves(
-        current: Vec<(DexType, u64)>,
-        target: Vec<LiquidityRatio>,
-    ) -> Result<Vec<LiquidityMove>> {
-        let total_value: u64 = current.iter().map(|(_, v)| v).sum();
-        let mut moves = Vec::new();
-
-        for ratio in target {
-            let target_amount = (total_value as u128 * ratio.target_ratio as u128 / 100) as u64;
-            let current_amount = current
-                .iter()
-                .find(|(dex, _)| *dex == ratio.dex)
-                .map(|(_, v)| *v)
-                .unwrap_or(0);
-
-            if current_amount < target_amount {
-                moves.push(LiquidityMove {
-                    dex: ratio.dex,
-                    pool_id: ratio.pool_id,
-                    amount: target_amount - current_amount,
-                    direction: MoveDirection::Add,
-                });
-            } else if current_amount > target_amount {
-                moves.push(LiquidityMove {
-                    dex: ratio.dex,
-                    pool_id: ratio.pool_id,
-                    amount: current_amount - target_amount,
-                    direction: MoveDirection::Remove,
-                });
+    // Helper functions
+
+    // Fills pools in descending APR order up to their max; a pool that can't get its minimum
+    // gets nothing and the budget moves on to the next
+    fn allocate_by_yield(pool_configs: &[PoolConfig], aprs: &[u64], total_liquidity: u64) -> Vec<u64> {
+        let mut order: Vec<usize> = (0..pool_configs.len()).collect();
+        order.sort_by(|a, b| aprs[*b].cmp(&aprs[*a]));
+
+        let mut allocations = vec![0; pool_configs.len()];
+        let mut remaining = total_liquidity;
+        for i in order {
+            let amount = pool_configs[i].max_liquidity.min(remaining);
+            if amount == 0 || amount < pool_configs[i].min_liquidity {
+                continue;
             }
+            allocations[i] = amount;
+            remaining -= amount;
         }
-
-        Ok(moves)
+        allocations
     }
+}
 
-    fn execute_liquidity_move(
-        ctx: Context<ExecuteMove>,
-        move_action: LiquidityMove,
-    ) -> Result<()> {
-        match move_action.direction {
-            MoveDirection::Add => {
-                match move_action.dex {
-                    DexType::Raydium => {
-                        RaydiumDex::add_liquidity(
-                            ctx.accounts.into(),
-                            move_action.amount,
-                            move_action.pool_id,
-                        )?;
-                    },
-                    DexType::Jupiter => {
-                        JupiterDex::add_liquidity(
-                            ctx.accounts.into(),
-                            move_action.amount,
-                            move_action.pool_id,
-                        )?;
-                    },
-                    DexType::Serum => {
-                        SerumDex::add_liquidity(
-                            ctx.accounts.into(),
-                            move_action.amount,
-                            move_action.pool_id,
-                        )?;
-                    },
-                }
-            },
-            MoveDirection::Remove => {
-                // Similar implementation for removing liquidity
-            },
+#[derive(Accounts)]
+pub struct OptimizeLiquidity<'info> {
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterPriceBand<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: Only used as the band's key, the reserves carry the price
+    pub pool: UncheckedAccount<'info>,
+    #[account(constraint = base_reserve.mint != quote_reserve.mint @ TradingBotError::InvalidPriceBand)]
+    pub base_reserve: Account<'info, TokenAccount>,
+    pub quote_reserve: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        space = PriceBand::LEN,
+        seeds = [PRICE_BAND_SEED, owner.key().as_ref(), pool.key().as_ref()],
+        bump,
+    )]
+    pub price_band: Account<'info, PriceBand>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckPriceBand<'info> {
+    #[account(
+        mut,
+        seeds = [PRICE_BAND_SEED, price_band.owner.as_ref(), price_band.pool.as_ref()],
+        bump = price_band.bump,
+    )]
+    pub price_band: Account<'info, PriceBand>,
+    #[account(address = price_band.base_reserve)]
+    pub base_reserve: Account<'info, TokenAccount>,
+    #[account(address = price_band.quote_reserve)]
+    pub quote_reserve: Account<'info, TokenAccount>,
+
+    pub upgrade_gate: UpgradeGate<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(keeper: Pubkey, pair: TokenPair)]
+pub struct InitMigrationRule<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = MigrationRule::LEN,
+        seeds = [MIGRATION_RULE_SEED, owner.key().as_ref(), pair.base_mint.as_ref(), pair.quote_mint.as_ref()],
+        bump,
+    )]
+    pub migration_rule: Account<'info, MigrationRule>,
+    pub system_program: Program<'info, System>,
+}
+
+// The rule holds the position the crank moves. The LP mints and the pools' reserves price its fair
+// share of each pool.
+#[derive(Accounts)]
+pub struct MigrateLiquidity<'info> {
+    #[account(
+        mut,
+        has_one = keeper,
+        seeds = [MIGRATION_RULE_SEED, migration_rule.owner.as_ref(), migration_rule.pair.base_mint.as_ref(), migration_rule.pair.quote_mint.as_ref()],
+        bump = migration_rule.bump,
+        constraint = position.base_wallet.owner == migration_rule.key() @ TradingBotError::InvalidMigrationRule,
+        constraint = position.pair() == migration_rule.pair @ TradingBotError::InvalidMigrationRule,
+    )]
+    pub migration_rule: Account<'info, MigrationRule>,
+    pub position: PositionMigration<'info>,
+    #[account(address = position.source_lp.mint)]
+    pub source_lp_mint: Box<Account<'info, Mint>>,
+    #[account(address = position.target_lp.mint)]
+    pub target_lp_mint: Box<Account<'info, Mint>>,
+    #[account(constraint = source_base_reserve.mint == position.base_wallet.mint @ TradingBotError::PoolMismatch)]
+    pub source_base_reserve: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = source_quote_reserve.mint == position.quote_wallet.mint @ TradingBotError::PoolMismatch)]
+    pub source_quote_reserve: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = target_base_reserve.mint == position.base_wallet.mint @ TradingBotError::PoolMismatch)]
+    pub target_base_reserve: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = target_quote_reserve.mint == position.quote_wallet.mint @ TradingBotError::PoolMismatch)]
+    pub target_quote_reserve: Box<Account<'info, TokenAccount>>,
+    pub keeper: Signer<'info>,
+}
+
+impl<'info> MigrateLiquidity<'info> {
+    // Raises the keeper's limits to what moving `lp_amount` is worth at the pools' reserves, less
+    // MAX_SLIPPAGE_BPS, so the crank can't route the rule's liquidity anywhere but back to it
+    fn fair_limits(
+        &self,
+        source_dex: DexType,
+        target_dex: DexType,
+        lp_amount: u64,
+        limits: MigrationLimits,
+    ) -> Result<MigrationLimits> {
+        let pair = self.migration_rule.pair;
+        let registry = &self.position.pool_registry;
+        for (dex, reserve) in [
+            (source_dex, &self.source_base_reserve),
+            (source_dex, &self.source_quote_reserve),
+            (target_dex, &self.target_base_reserve),
+            (target_dex, &self.target_quote_reserve),
+        ] {
+            registry.require_reserve(&pair, dex, &reserve.key())?;
         }
 
-        Ok(())
+        let (source_supply, target_supply) = (self.source_lp_mint.supply, self.target_lp_mint.supply);
+        let min_base_out =
+            less_slippage(share_of(lp_amount, self.source_base_reserve.amount, source_supply)?)?;
+        let min_quote_out =
+            less_slippage(share_of(lp_amount, self.source_quote_reserve.amount, source_supply)?)?;
+        let lp_share = share_of(min_base_out, target_supply, self.target_base_reserve.amount)?
+            .min(share_of(min_quote_out, target_supply, self.target_quote_reserve.amount)?);
+
+        Ok(MigrationLimits {
+            min_base_out: limits.min_base_out.max(min_base_out),
+            min_quote_out: limits.min_quote_out.max(min_quote_out),
+            min_lp_out: limits.min_lp_out.max(less_slippage(lp_share)?),
+            max_leftover_bps: limits.max_leftover_bps.min(MAX_SLIPPAGE_BPS),
+        })
     }
 }
 
 #[derive(Accounts)]
-pub struct RebalanceLiquidity<'info> {
-    #[account(mut)]
-    pub liquidity_tracker: Account<'info, LiquidityTracker>,
-    #[account(mut)]
-    pub raydium_pools: Vec<Account<'info, RaydiumPool>>,
-    #[account(mut)]
-    pub jupiter_pools: Vec<Account<'info, JupiterPool>>,
-    #[account(mut)]
-    pub serum_markets: Vec<Account<'info, SerumMarket>>,
-    pub token_program: Program<'info, Token>,
+pub struct ReleaseMigrationFunds<'info> {
+    #[account(
+        has_one = owner,
+        seeds = [MIGRATION_RULE_SEED, migration_rule.owner.as_ref(), migration_rule.pair.base_mint.as_ref(), migration_rule.pair.quote_mint.as_ref()],
+        bump = migration_rule.bump,
+    )]
+    pub migration_rule: Account<'info, MigrationRule>,
+    #[account(mut, constraint = source.owner == migration_rule.key() @ TradingBotError::InvalidMigrationRule)]
+    pub source: Account<'info, TokenAccount>,
+    #[account(mut, constraint = destination.mint == source.mint @ TradingBotError::InvalidMigrationRule)]
+    pub destination: Account<'info, TokenAccount>,
     pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct MonitorLiquidity<'info> {
-    #[account(mut)]
-    pub liquidity_tracker: Account<'info, LiquidityTracker>,
-    pub market_volatility: Account<'info, MarketVolatility>,
-    pub risk_threshold: u8,
+pub struct MigratePosition<'info> {
+    #[account(constraint = position.base_wallet.owner == owner.key() @ TradingBotError::InvalidTradeConditions)]
     pub owner: Signer<'info>,
+    pub position: PositionMigration<'info>,
 }
 
+// The pools a position moves between and the owner's accounts the liquidity passes through. Both
+// pools have to be registered for their venue on the wallets' pair, and the venue programs are
+// pinned to their DexType.
 #[derive(Accounts)]
-pub struct OptimizeLiquidity<'info> {
+pub struct PositionMigration<'info> {
+    #[account(seeds = [POOL_REGISTRY_SEED], bump = pool_registry.bump)]
+    pub pool_registry: Box<Account<'info, PoolRegistry>>,
+    /// CHECK: Checked against the pool registry
     #[account(mut)]
-    pub liquidity_tracker: Account<'info, LiquidityTracker>,
-    pub market_data: Account<'info, MarketData>,
-    pub owner: Signer<'info>,
+    pub source_pool: UncheckedAccount<'info>,
+    /// CHECK: Checked against the pool registry
+    #[account(mut)]
+    pub target_pool: UncheckedAccount<'info>,
+    #[account(mut, constraint = source_lp.owner == base_wallet.owner @ TradingBotError::InvalidTradeConditions)]
+    pub source_lp: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = target_lp.owner == base_wallet.owner @ TradingBotError::InvalidTradeConditions)]
+    pub target_lp: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub base_wallet: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = quote_wallet.owner == base_wallet.owner @ TradingBotError::InvalidTradeConditions,
+        constraint = quote_wallet.mint != base_wallet.mint @ TradingBotError::InvalidTradeConditions,
+    )]
+    pub quote_wallet: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Checked against the source venue in `migrate`
+    pub source_program: UncheckedAccount<'info>,
+    /// CHECK: Checked against the target venue in `migrate`
+    pub target_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct LiquidityMove {
-    pub dex: DexType,
-    pub pool_id: Pubkey,
-    pub amount: u64,
-    pub direction: MoveDirection,
+impl<'info> PositionMigration<'info> {
+    pub fn pair(&self) -> TokenPair {
+        TokenPair {
+            base_mint: self.base_wallet.mint,
+            quote_mint: self.quote_wallet.mint,
+        }
+    }
+
+    // Withdraws `lp_amount` from the source pool and deposits what it paid out into the target, in
+    // the same transaction, so either both legs clear their limits or neither happens. The wallets'
+    // owner has to be a signer of the transaction or, with `signer_seeds`, the program. Returns
+    // the base and quote withdrawn and the LP tokens minted by the target.
+    fn migrate(
+        &mut self,
+        accounts: &[AccountInfo<'info>],
+        (source_dex, target_dex): (DexType, DexType),
+        lp_amount: u64,
+        limits: MigrationLimits,
+        calls: MigrationCalls,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<(u64, u64, u64)> {
+        require!(
+            lp_amount > 0
+                && limits.max_leftover_bps <= 10000
+                && self.source_pool.key() != self.target_pool.key(),
+            TradingBotError::InvalidTradeConditions
+        );
+        let pair = self.pair();
+        self.pool_registry.require_pool(&pair, source_dex, &self.source_pool.key())?;
+        self.pool_registry.require_pool(&pair, target_dex, &self.target_pool.key())?;
+        require_keys_eq!(
+            self.source_program.key(),
+            liquidity_program_id(source_dex)?,
+            TradingBotError::InvalidDexType
+        );
+        require_keys_eq!(
+            self.target_program.key(),
+            liquidity_program_id(target_dex)?,
+            TradingBotError::InvalidDexType
+        );
+        let mut calls = VenueCalls::new(vec![calls.withdraw, calls.deposit]);
+
+        let (base_before, quote_before) = (self.base_wallet.amount, self.quote_wallet.amount);
+        let (source_lp_before, lp_before) = (self.source_lp.amount, self.target_lp.amount);
+        let (withdraw_accounts, withdraw_data) = calls.next(accounts)?;
+        let authority = Some(self.base_wallet.owner);
+        forward_call(
+            self.source_program.key(),
+            withdraw_accounts,
+            withdraw_data,
+            authority,
+            signer_seeds,
+        )?;
+        self.base_wallet.reload()?;
+        self.quote_wallet.reload()?;
+        self.source_lp.reload()?;
+        require_eq!(
+            math::checked_sub(source_lp_before, self.source_lp.amount)?,
+            lp_amount,
+            TradingBotError::InvalidCalculation
+        );
+        let base_out = math::checked_sub(self.base_wallet.amount, base_before)?;
+        let quote_out = math::checked_sub(self.quote_wallet.amount, quote_before)?;
+        require!(
+            base_out >= limits.min_base_out && quote_out >= limits.min_quote_out,
+            TradingBotError::SlippageExceeded
+        );
+
+        let (deposit_accounts, deposit_data) = calls.next(accounts)?;
+        forward_call(
+            self.target_program.key(),
+            deposit_accounts,
+            deposit_data,
+            authority,
+            signer_seeds,
+        )?;
+        self.base_wallet.reload()?;
+        self.quote_wallet.reload()?;
+        self.target_lp.reload()?;
+        let lp_out = math::checked_sub(self.target_lp.amount, lp_before)?;
+        require_gte!(lp_out, limits.min_lp_out, TradingBotError::SlippageExceeded);
+
+        // what the target pool didn't take at its ratio is back in the wallets
+        let max_leftover_bps = limits.max_leftover_bps as u128;
+        let base_left = self.base_wallet.amount.saturating_sub(base_before) as u128;
+        let quote_left = self.quote_wallet.amount.saturating_sub(quote_before) as u128;
+        require!(
+            base_left * 10000 <= base_out as u128 * max_leftover_bps
+                && quote_left * 10000 <= quote_out as u128 * max_leftover_bps,
+            TradingBotError::SlippageExceeded
+        );
+
+        Ok((base_out, quote_out, lp_out))
+    }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub enum MoveDirection {
-    Add,
-    Remove,
+// The program LP is withdrawn and deposited through on each venue; order-book markets hold none
+fn liquidity_program_id(dex: DexType) -> Result<Pubkey> {
+    let program_id = match dex {
+        DexType::Raydium => RAYDIUM_AMM_PROGRAM_ID,
+        DexType::Jupiter => JUPITER_V6_PROGRAM_ID,
+        DexType::Serum => return err!(TradingBotError::InvalidDexType),
+    };
+    Ok(program_id.parse::<Pubkey>().unwrap())
+}
+
+// `amount` of `total` is worth this much of `reserve`
+fn share_of(amount: u64, reserve: u64, total: u64) -> Result<u64> {
+    require_gt!(total, 0, TradingBotError::InvalidCalculation);
+    math::checked_as_u64(amount as u128 * reserve as u128 / total as u128)
+}
+
+fn less_slippage(amount: u64) -> Result<u64> {
+    math::checked_as_u64(amount as u128 * (10000 - MAX_SLIPPAGE_BPS) as u128 / 10000)
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
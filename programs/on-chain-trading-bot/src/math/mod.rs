@@ -1,4 +1,6 @@
-use {crate::errors::EscrowErrors, anchor_lang::prelude::*, std::fmt::Display};
+pub mod tick;
+
+use {crate::errors::TradingBotError, anchor_lang::prelude::*, std::fmt::Display};
 
 pub fn checked_add<T>(arg1: T, arg2: T) -> Result<T>
 where
@@ -8,7 +10,7 @@ where
         Ok(res)
     } else {
         msg!("Error: Overflow in {} + {}", arg1, arg2);
-        err!(EscrowErrors::MathOverflow)
+        err!(TradingBotError::Overflow)
     }
 }
 
@@ -20,7 +22,7 @@ where
         Ok(res)
     } else {
         msg!("Error: Overflow in {} - {}", arg1, arg2);
-        err!(EscrowErrors::MathOverflow)
+        err!(TradingBotError::Overflow)
     }
 }
 
@@ -32,7 +34,7 @@ where
         Ok(res)
     } else {
         msg!("Error: Overflow in {} * {}", arg1, arg2);
-        err!(EscrowErrors::MathOverflow)
+        err!(TradingBotError::Overflow)
     }
 }
 
@@ -44,7 +46,20 @@ where
         Ok(res)
     } else {
         msg!("Error: Overflow in {} / {}", arg1, arg2);
-        err!(EscrowErrors::MathOverflow)
+        err!(TradingBotError::Overflow)
+    }
+}
+
+// Division rounding up, for amounts owed to the protocol or a counterparty
+pub fn checked_ceil_div<T>(arg1: T, arg2: T) -> Result<T>
+where
+    T: num_traits::PrimInt + Display,
+{
+    let quotient = checked_div(arg1, arg2)?;
+    if quotient * arg2 == arg1 {
+        Ok(quotient)
+    } else {
+        checked_add(quotient, T::one())
     }
 }
 
@@ -57,6 +72,6 @@ where
         Ok(res)
     } else {
         msg!("Error: Overflow in {} as u64", arg);
-        err!(EscrowErrors::MathOverflow)
+        err!(TradingBotError::Overflow)
     }
 }
\ No newline at end of file
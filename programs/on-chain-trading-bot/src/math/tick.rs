@@ -0,0 +1,388 @@
+// Concentrated liquidity math. Sqrt prices and fee growth are Q64.64 fixed point, matching the
+// Raydium CLMM and Orca Whirlpool account layouts.
+use super::{checked_add, checked_as_u64, checked_div, checked_mul, checked_sub};
+use crate::constants::{MAX_TICK, MIN_TICK, PRICE_PRECISION};
+use crate::errors::TradingBotError;
+use anchor_lang::prelude::*;
+
+pub const Q64: u128 = 1 << 64;
+
+// 2^64 * sqrt(1.0001^-(2^i)) for i in 0..19
+const NEGATIVE_TICK_FACTORS: [u128; 19] = [
+    18445821805675392311,
+    18444899583751176498,
+    18443055278223354162,
+    18439367220385604838,
+    18431993317065449817,
+    18417254355718160513,
+    18387811781193591352,
+    18329067761203520168,
+    18212142134806087854,
+    17980523815641551639,
+    17526086738831147013,
+    16651378430235024244,
+    15030750278693429944,
+    12247334978882834399,
+    8131365268884726200,
+    3584323654723342297,
+    696457651847595233,
+    26294789957452057,
+    37481735321082,
+];
+
+// 2^96 * sqrt(1.0001^(2^i)) for i in 0..19. Positive ticks start from Q32.96 as on Whirlpool, so
+// the ratio keeps its precision as it grows instead of losing it to an inversion.
+const POSITIVE_TICK_FACTORS: [u128; 19] = [
+    79232123823359799118286999567,
+    79236085330515764027303304731,
+    79244008939048815603706035061,
+    79259858533276714757314932305,
+    79291567232598584799939703904,
+    79355022692464371645785046466,
+    79482085999252804386437311141,
+    79736823300114093921829183326,
+    80248749790819932309965073892,
+    81282483887344747381513967011,
+    83390072131320151908154831281,
+    87770609709833776024991924138,
+    97234110755111693312479820773,
+    119332217159966728226237229890,
+    179736315981702064433883588727,
+    407748233172238350107850275304,
+    2098478828474011932436660412517,
+    55581415166113811149459800483533,
+    38992368544603139932233054999993551,
+];
+
+const Q96: u128 = 1 << 96;
+
+// sqrt(1.0001^tick) as Q64.64, via the binary decomposition of |tick|
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    require!(
+        (MIN_TICK..=MAX_TICK).contains(&tick),
+        TradingBotError::InvalidTickRange
+    );
+
+    let abs_tick = tick.unsigned_abs();
+    if tick > 0 {
+        let mut ratio = Q96;
+        for (i, factor) in POSITIVE_TICK_FACTORS.iter().enumerate() {
+            if abs_tick & (1 << i) != 0 {
+                ratio = mul_shr(ratio, *factor, 96)?;
+            }
+        }
+        return Ok(ratio >> 32);
+    }
+
+    let mut ratio = Q64;
+    for (i, factor) in NEGATIVE_TICK_FACTORS.iter().enumerate() {
+        if abs_tick & (1 << i) != 0 {
+            ratio = (ratio * factor) >> 64;
+        }
+    }
+    Ok(ratio)
+}
+
+// Greatest tick whose sqrt price is at or below `sqrt_price`
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> Result<i32> {
+    require!(
+        sqrt_price >= sqrt_price_at_tick(MIN_TICK)? && sqrt_price <= sqrt_price_at_tick(MAX_TICK)?,
+        TradingBotError::InvalidTickRange
+    );
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+// sqrt(price / PRICE_PRECISION) as Q64.64, with 32 fractional bits of precision
+pub fn sqrt_price_from_price(price: u64) -> Result<u128> {
+    let price_q64 = checked_div((price as u128) << 64, PRICE_PRECISION as u128)?;
+    Ok(isqrt(price_q64) << 32)
+}
+
+// Inverse of `sqrt_price_from_price`, rounded down
+pub fn price_from_sqrt_price(sqrt_price: u128) -> Result<u64> {
+    let price_q64 = mul_shr_64(sqrt_price, sqrt_price)?;
+    checked_as_u64(mul_shr_64(price_q64, PRICE_PRECISION as u128)?)
+}
+
+// Token amounts held by `liquidity` over [sqrt_lower, sqrt_upper] at `sqrt_current`:
+// all token A below the range, all token B above it, a mix inside
+pub fn amounts_for_liquidity(
+    liquidity: u128,
+    sqrt_current: u128,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+) -> Result<(u64, u64)> {
+    require_gt!(sqrt_upper, sqrt_lower, TradingBotError::InvalidTickRange);
+    let sqrt_current = sqrt_current.clamp(sqrt_lower, sqrt_upper);
+
+    let amount_a = mul_shr_64(
+        liquidity,
+        checked_sub(inverse(sqrt_current)?, inverse(sqrt_upper)?)?,
+    )?;
+    let amount_b = mul_shr_64(liquidity, checked_sub(sqrt_current, sqrt_lower)?)?;
+
+    Ok((checked_as_u64(amount_a)?, checked_as_u64(amount_b)?))
+}
+
+// Largest liquidity that both amounts can fund over the range at `sqrt_current`
+pub fn liquidity_for_amounts(
+    amount_a: u64,
+    amount_b: u64,
+    sqrt_current: u128,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+) -> Result<u128> {
+    require_gt!(sqrt_upper, sqrt_lower, TradingBotError::InvalidTickRange);
+    let sqrt_current = sqrt_current.clamp(sqrt_lower, sqrt_upper);
+
+    let liquidity_a = if sqrt_current < sqrt_upper {
+        Some(checked_div(
+            (amount_a as u128) << 64,
+            checked_sub(inverse(sqrt_current)?, inverse(sqrt_upper)?)?,
+        )?)
+    } else {
+        None
+    };
+    let liquidity_b = if sqrt_current > sqrt_lower {
+        Some(checked_div(
+            (amount_b as u128) << 64,
+            checked_sub(sqrt_current, sqrt_lower)?,
+        )?)
+    } else {
+        None
+    };
+
+    Ok(match (liquidity_a, liquidity_b) {
+        (Some(a), Some(b)) => a.min(b),
+        (Some(a), None) => a,
+        (None, Some(b)) => b,
+        (None, None) => 0,
+    })
+}
+
+// Fee growth per unit of liquidity inside [tick_lower, tick_upper]. Growth counters wrap by
+// design, so differences are taken with wrapping arithmetic as on the pool program.
+pub fn fee_growth_inside(
+    tick_current: i32,
+    tick_lower: i32,
+    tick_upper: i32,
+    fee_growth_global: u128,
+    fee_growth_outside_lower: u128,
+    fee_growth_outside_upper: u128,
+) -> u128 {
+    let below = if tick_current >= tick_lower {
+        fee_growth_outside_lower
+    } else {
+        fee_growth_global.wrapping_sub(fee_growth_outside_lower)
+    };
+    let above = if tick_current < tick_upper {
+        fee_growth_outside_upper
+    } else {
+        fee_growth_global.wrapping_sub(fee_growth_outside_upper)
+    };
+    fee_growth_global.wrapping_sub(below).wrapping_sub(above)
+}
+
+// Fees earned by `liquidity` since the position last checkpointed `fee_growth_inside_last`
+pub fn fees_owed(
+    liquidity: u128,
+    fee_growth_inside: u128,
+    fee_growth_inside_last: u128,
+) -> Result<u64> {
+    checked_as_u64(mul_shr_64(
+        liquidity,
+        fee_growth_inside.wrapping_sub(fee_growth_inside_last),
+    )?)
+}
+
+// 1 / sqrt_price as Q64.64
+fn inverse(sqrt_price: u128) -> Result<u128> {
+    checked_div(u128::MAX, sqrt_price)
+}
+
+// (a * b) >> 64 without overflowing the 256-bit intermediate product
+pub fn mul_shr_64(a: u128, b: u128) -> Result<u128> {
+    let (a_hi, a_lo) = (a >> 64, a & u64::MAX as u128);
+    let (b_hi, b_lo) = (b >> 64, b & u64::MAX as u128);
+
+    let high = checked_mul(checked_mul(a_hi, b_hi)?, Q64)?;
+    let cross = checked_add(a_hi * b_lo, a_lo * b_hi)?;
+    let low = (a_lo * b_lo) >> 64;
+    checked_add(checked_add(high, cross)?, low)
+}
+
+// (a * b) >> shift over the full 256-bit product, for 0 < shift < 128
+fn mul_shr(a: u128, b: u128, shift: u32) -> Result<u128> {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & LOW);
+    let (b_hi, b_lo) = (b >> 64, b & LOW);
+
+    let low = a_lo * b_lo;
+    let (cross_a, cross_b) = (a_lo * b_hi, a_hi * b_lo);
+    let mid = (low >> 64) + (cross_a & LOW) + (cross_b & LOW);
+    let product_lo = (low & LOW) | (mid << 64);
+    let product_hi = a_hi * b_hi + (cross_a >> 64) + (cross_b >> 64) + (mid >> 64);
+
+    require!(product_hi >> shift == 0, TradingBotError::Overflow);
+    Ok((product_hi << (128 - shift)) | (product_lo >> shift))
+}
+
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x / 2 + (x & 1);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Whirlpool's sqrt_price_from_tick_index at the same ticks
+    const REFERENCE: [(i32, u128); 13] = [
+        (MIN_TICK, 4295048016),
+        (-221818, 281477621741252),
+        (-100000, 124324258982887573),
+        (-1000, 17547129613991598777),
+        (-10, 18437523468038800957),
+        (-1, 18445821805675392311),
+        (0, 18446744073709551616),
+        (1, 18447666387855959850),
+        (10, 18455969290605290427),
+        (1000, 19392480388906836277),
+        (100000, 2737055259406582257880),
+        (221818, 1208914459401470658967143),
+        (MAX_TICK, 79226673515401279992447579055),
+    ];
+
+    #[test]
+    fn sqrt_price_matches_reference() {
+        for (tick, sqrt_price) in REFERENCE {
+            assert_eq!(sqrt_price_at_tick(tick).unwrap(), sqrt_price, "tick {}", tick);
+        }
+    }
+
+    #[test]
+    fn sqrt_price_rejects_out_of_range_ticks() {
+        assert!(sqrt_price_at_tick(MIN_TICK - 1).is_err());
+        assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn sqrt_price_is_monotonic_and_inverts() {
+        let mut previous = 0;
+        for tick in (MIN_TICK..=MAX_TICK).step_by(7919) {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert!(sqrt_price > previous, "tick {}", tick);
+            assert_eq!(tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+            assert_eq!(tick_at_sqrt_price(sqrt_price + 1).unwrap(), tick);
+            previous = sqrt_price;
+        }
+        for (tick, sqrt_price) in REFERENCE {
+            assert_eq!(tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+        }
+    }
+
+    #[test]
+    fn amounts_round_trip_through_liquidity() {
+        let liquidity = 1_000_000_000_000u128;
+        for (lower, current, upper) in [(-1000, 0, 1000), (-60, 37, 60), (50000, 60000, 80000)] {
+            let sqrt_lower = sqrt_price_at_tick(lower).unwrap();
+            let sqrt_current = sqrt_price_at_tick(current).unwrap();
+            let sqrt_upper = sqrt_price_at_tick(upper).unwrap();
+
+            let (amount_a, amount_b) =
+                amounts_for_liquidity(liquidity, sqrt_current, sqrt_lower, sqrt_upper).unwrap();
+            assert!(amount_a > 0 && amount_b > 0);
+            let funded =
+                liquidity_for_amounts(amount_a, amount_b, sqrt_current, sqrt_lower, sqrt_upper)
+                    .unwrap();
+            // amounts round down, so they never fund more than they came from
+            assert!(funded <= liquidity);
+            assert!(funded >= liquidity - liquidity / 1_000_000_000);
+
+            let (again_a, again_b) =
+                amounts_for_liquidity(funded, sqrt_current, sqrt_lower, sqrt_upper).unwrap();
+            assert!(again_a <= amount_a && again_b <= amount_b);
+        }
+    }
+
+    #[test]
+    fn amounts_outside_the_range_are_one_sided() {
+        let liquidity = 1_000_000_000_000u128;
+        let sqrt_lower = sqrt_price_at_tick(-100).unwrap();
+        let sqrt_upper = sqrt_price_at_tick(100).unwrap();
+
+        let below = sqrt_price_at_tick(-200).unwrap();
+        let (amount_a, amount_b) =
+            amounts_for_liquidity(liquidity, below, sqrt_lower, sqrt_upper).unwrap();
+        assert!(amount_a > 0);
+        assert_eq!(amount_b, 0);
+        let funded = liquidity_for_amounts(amount_a, 0, below, sqrt_lower, sqrt_upper).unwrap();
+        assert!(funded <= liquidity && funded >= liquidity - liquidity / 1_000_000_000);
+
+        let above = sqrt_price_at_tick(200).unwrap();
+        let (amount_a, amount_b) =
+            amounts_for_liquidity(liquidity, above, sqrt_lower, sqrt_upper).unwrap();
+        assert_eq!(amount_a, 0);
+        assert!(amount_b > 0);
+        let funded = liquidity_for_amounts(0, amount_b, above, sqrt_lower, sqrt_upper).unwrap();
+        assert!(funded <= liquidity && funded >= liquidity - liquidity / 1_000_000_000);
+
+        assert!(amounts_for_liquidity(liquidity, below, sqrt_upper, sqrt_lower).is_err());
+    }
+
+    #[test]
+    fn fee_growth_inside_wraps() {
+        // the lower tick was crossed just before the global counter wrapped
+        let global = 50;
+        let outside_lower = u128::MAX - 100;
+        let outside_upper = 20;
+        assert_eq!(
+            fee_growth_inside(0, -10, 10, global, outside_lower, outside_upper),
+            131
+        );
+
+        // below the range the inside growth is what the lower tick saw beyond the upper one
+        assert_eq!(
+            fee_growth_inside(-20, -10, 10, global, outside_lower, outside_upper),
+            outside_lower - outside_upper
+        );
+        // above it, the other way round
+        assert_eq!(
+            fee_growth_inside(20, -10, 10, global, 30, u128::MAX - 9),
+            u128::MAX - 9 - 30
+        );
+    }
+
+    #[test]
+    fn fees_owed_across_a_wrapped_checkpoint() {
+        let last = u128::MAX - 5 * Q64 + 1;
+        let inside = 5 * Q64;
+        assert_eq!(fees_owed(7, inside, last).unwrap(), 70);
+    }
+
+    #[test]
+    fn mul_shr_keeps_the_full_product() {
+        let (a, b) = (u128::MAX / 3, 12345678901234567890);
+        assert_eq!(mul_shr(a, b, 64).ok(), mul_shr_64(a, b).ok());
+        assert_eq!(mul_shr(Q96, Q96, 96).unwrap(), Q96);
+        assert!(mul_shr(u128::MAX, u128::MAX, 96).is_err());
+    }
+}
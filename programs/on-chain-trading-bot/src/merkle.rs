@@ -0,0 +1,19 @@
+use anchor_lang::solana_program::keccak;
+
+// Leaves are hashed as keccak(claimant || cumulative amount); pairs are sorted before hashing so
+// proofs don't need to carry left/right flags
+pub fn claim_leaf(claimant: &[u8], amount: u64) -> [u8; 32] {
+    keccak::hashv(&[claimant, &amount.to_le_bytes()]).0
+}
+
+pub fn verify(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed = leaf;
+    for node in proof {
+        computed = if computed <= *node {
+            keccak::hashv(&[&computed, node]).0
+        } else {
+            keccak::hashv(&[node, &computed]).0
+        };
+    }
+    computed == root
+}
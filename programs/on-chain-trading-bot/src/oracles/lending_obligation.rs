@@ -0,0 +1,107 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::oracles::lending_reserve::SOLEND_PROGRAM_ID;
+use anchor_lang::prelude::*;
+
+// Solend obligation, packed without a discriminator: last_update's slot and stale flag, the
+// owner, then the USD values RefreshObligation computes, in wads
+const OBLIGATION_LAST_UPDATE_SLOT: usize = 1;
+const OBLIGATION_STALE: usize = 9;
+const OBLIGATION_OWNER: usize = 42;
+const OBLIGATION_DEPOSITED_VALUE: usize = 74;
+const OBLIGATION_BORROWED_VALUE: usize = 90;
+const OBLIGATION_UNHEALTHY_BORROW_VALUE: usize = 122;
+
+// A Solend obligation's collateral and debt, in USD wads as of its last refresh
+#[derive(Clone, Copy, Debug)]
+pub struct ObligationHealth {
+    pub deposited_value: u128,
+    pub borrowed_value: u128,
+    // the debt at which the obligation can be liquidated
+    pub unhealthy_borrow_value: u128,
+}
+
+impl ObligationHealth {
+    // Reads an obligation of `owner`, which has to have been refreshed in this slot so the values
+    // reflect current prices and interest
+    pub fn read(obligation: &AccountInfo, owner: &Pubkey) -> Result<Self> {
+        require_keys_eq!(
+            *obligation.owner,
+            SOLEND_PROGRAM_ID,
+            TradingBotError::InvalidObligation
+        );
+        let data = obligation.try_borrow_data()?;
+        let owner_bytes = data
+            .get(OBLIGATION_OWNER..OBLIGATION_OWNER + 32)
+            .ok_or(TradingBotError::InvalidObligation)?;
+        require_keys_eq!(
+            Pubkey::new_from_array(owner_bytes.try_into().unwrap()),
+            *owner,
+            TradingBotError::InvalidObligation
+        );
+        let last_update_slot = read_u64(&data, OBLIGATION_LAST_UPDATE_SLOT)?;
+        let stale = *data
+            .get(OBLIGATION_STALE)
+            .ok_or(TradingBotError::InvalidObligation)?
+            != 0;
+        require!(
+            !stale && last_update_slot == Clock::get()?.slot,
+            TradingBotError::StaleObligation
+        );
+        Ok(Self {
+            deposited_value: read_u128(&data, OBLIGATION_DEPOSITED_VALUE)?,
+            borrowed_value: read_u128(&data, OBLIGATION_BORROWED_VALUE)?,
+            unhealthy_borrow_value: read_u128(&data, OBLIGATION_UNHEALTHY_BORROW_VALUE)?,
+        })
+    }
+
+    // Debt over collateral, in bps
+    pub fn ltv_bps(&self) -> Result<u64> {
+        if self.deposited_value == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.borrowed_value, 10_000)?,
+            self.deposited_value,
+        )?)
+    }
+
+    // Collateral over equity, in bps, 10000 = 1x. Debt at or past the collateral is unbounded
+    // leverage.
+    pub fn leverage_bps(&self) -> Result<u64> {
+        if self.borrowed_value >= self.deposited_value {
+            return Ok(if self.deposited_value == 0 { 0 } else { u64::MAX });
+        }
+        let leverage = math::checked_div(
+            math::checked_mul(self.deposited_value, 10_000)?,
+            self.deposited_value - self.borrowed_value,
+        )?;
+        Ok(u64::try_from(leverage).unwrap_or(u64::MAX))
+    }
+
+    // Liquidation debt over debt, in bps: below 10000 the obligation can be liquidated
+    pub fn health_bps(&self) -> Result<u64> {
+        if self.borrowed_value == 0 {
+            return Ok(u64::MAX);
+        }
+        let health = math::checked_div(
+            math::checked_mul(self.unhealthy_borrow_value, 10_000)?,
+            self.borrowed_value,
+        )?;
+        Ok(u64::try_from(health).unwrap_or(u64::MAX))
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + 8)
+        .ok_or(TradingBotError::InvalidObligation)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u128(data: &[u8], offset: usize) -> Result<u128> {
+    let bytes = data
+        .get(offset..offset + 16)
+        .ok_or(TradingBotError::InvalidObligation)?;
+    Ok(u128::from_le_bytes(bytes.try_into().unwrap()))
+}
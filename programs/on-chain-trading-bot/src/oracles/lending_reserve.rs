@@ -0,0 +1,203 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::valuation::WAD;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+pub const SOLEND_PROGRAM_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+pub const KAMINO_LEND_PROGRAM_ID: Pubkey = pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+// Solend reserve, packed without a discriminator: the liquidity's mint, available amount and
+// borrowed wads, the collateral (cToken) mint and supply, and the config's utilization kink and
+// borrow rates in whole percent
+const SOLEND_LIQUIDITY_MINT: usize = 42;
+const SOLEND_AVAILABLE_AMOUNT: usize = 171;
+const SOLEND_BORROWED_AMOUNT_WADS: usize = 179;
+const SOLEND_COLLATERAL_MINT: usize = 227;
+const SOLEND_COLLATERAL_SUPPLY: usize = 259;
+const SOLEND_OPTIMAL_UTILIZATION_RATE: usize = 299;
+const SOLEND_MIN_BORROW_RATE: usize = 303;
+const SOLEND_OPTIMAL_BORROW_RATE: usize = 304;
+const SOLEND_MAX_BORROW_RATE: usize = 305;
+
+// Kamino reserve: the liquidity's available amount and borrowed amount in 68.60 fixed point. Its
+// borrow curve is not read, so only utilization is available there.
+const KAMINO_AVAILABLE_AMOUNT: usize = 224;
+const KAMINO_BORROWED_AMOUNT_SF: usize = 232;
+const KAMINO_FRACTION_BITS: u32 = 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LendingMetric {
+    // borrowed share of the reserve's liquidity
+    Utilization,
+    BorrowApr,
+    // what depositors earn: the borrow APR on the utilized share, before the protocol's cut
+    SupplyApr,
+}
+
+// A lending reserve's rates, all in bps
+#[derive(Clone, Copy, Debug)]
+pub struct ReserveRates {
+    pub utilization_bps: u64,
+    // none where the program's rate curve isn't read
+    pub borrow_apr_bps: Option<u64>,
+}
+
+impl ReserveRates {
+    // Reads a Solend or Kamino reserve, telling the two apart by the account's owner
+    pub fn read(reserve: &AccountInfo) -> Result<Self> {
+        let data = reserve.try_borrow_data()?;
+        if *reserve.owner == SOLEND_PROGRAM_ID {
+            let available = read_u64(&data, SOLEND_AVAILABLE_AMOUNT)? as u128;
+            let borrowed = read_u128(&data, SOLEND_BORROWED_AMOUNT_WADS)? / WAD;
+            let utilization_bps = utilization_bps(borrowed, available)?;
+            let borrow_apr_bps = solend_borrow_apr_bps(
+                utilization_bps,
+                read_u8(&data, SOLEND_OPTIMAL_UTILIZATION_RATE)?,
+                read_u8(&data, SOLEND_MIN_BORROW_RATE)?,
+                read_u8(&data, SOLEND_OPTIMAL_BORROW_RATE)?,
+                read_u8(&data, SOLEND_MAX_BORROW_RATE)?,
+            )?;
+            Ok(Self {
+                utilization_bps,
+                borrow_apr_bps: Some(borrow_apr_bps),
+            })
+        } else if *reserve.owner == KAMINO_LEND_PROGRAM_ID {
+            let available = read_u64(&data, KAMINO_AVAILABLE_AMOUNT)? as u128;
+            let borrowed = read_u128(&data, KAMINO_BORROWED_AMOUNT_SF)? >> KAMINO_FRACTION_BITS;
+            Ok(Self {
+                utilization_bps: utilization_bps(borrowed, available)?,
+                borrow_apr_bps: None,
+            })
+        } else {
+            Err(TradingBotError::InvalidLendingReserve.into())
+        }
+    }
+
+    pub fn metric(&self, metric: LendingMetric) -> Result<u64> {
+        match metric {
+            LendingMetric::Utilization => Ok(self.utilization_bps),
+            LendingMetric::BorrowApr => self
+                .borrow_apr_bps
+                .ok_or_else(|| TradingBotError::UnsupportedLendingMetric.into()),
+            LendingMetric::SupplyApr => {
+                let borrow_apr_bps = self
+                    .borrow_apr_bps
+                    .ok_or(TradingBotError::UnsupportedLendingMetric)?;
+                math::checked_div(
+                    math::checked_mul(borrow_apr_bps, self.utilization_bps)?,
+                    10_000,
+                )
+            }
+        }
+    }
+}
+
+// What a Solend reserve's collateral tokens redeem for: its total liquidity, lent out or not, over
+// the cTokens in circulation. Protocol fees not yet claimed are counted in, so values read from it
+// run slightly high.
+#[derive(Clone, Copy, Debug)]
+pub struct CollateralRate {
+    pub liquidity_mint: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub total_liquidity: u128,
+    pub collateral_supply: u64,
+}
+
+impl CollateralRate {
+    pub fn read(reserve: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(
+            *reserve.owner,
+            SOLEND_PROGRAM_ID,
+            TradingBotError::InvalidLendingReserve
+        );
+        let data = reserve.try_borrow_data()?;
+        let available = read_u64(&data, SOLEND_AVAILABLE_AMOUNT)? as u128;
+        let borrowed = read_u128(&data, SOLEND_BORROWED_AMOUNT_WADS)? / WAD;
+        Ok(Self {
+            liquidity_mint: read_pubkey(&data, SOLEND_LIQUIDITY_MINT)?,
+            collateral_mint: read_pubkey(&data, SOLEND_COLLATERAL_MINT)?,
+            total_liquidity: math::checked_add(available, borrowed)?,
+            collateral_supply: read_u64(&data, SOLEND_COLLATERAL_SUPPLY)?,
+        })
+    }
+
+    // Liquidity `collateral` cTokens redeem for
+    pub fn liquidity_value(&self, collateral: u64) -> Result<u64> {
+        if self.collateral_supply == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(collateral as u128, self.total_liquidity)?,
+            self.collateral_supply as u128,
+        )?)
+    }
+}
+
+fn utilization_bps(borrowed: u128, available: u128) -> Result<u64> {
+    let total = math::checked_add(borrowed, available)?;
+    if total == 0 {
+        return Ok(0);
+    }
+    math::checked_as_u64(math::checked_div(
+        math::checked_mul(borrowed, 10_000)?,
+        total,
+    )?)
+}
+
+// Solend's two-slope curve: from the minimum to the optimal rate up to the kink, then on to the
+// maximum rate at full utilization
+fn solend_borrow_apr_bps(
+    utilization_bps: u64,
+    optimal_utilization_pct: u8,
+    min_rate_pct: u8,
+    optimal_rate_pct: u8,
+    max_rate_pct: u8,
+) -> Result<u64> {
+    let kink_bps = optimal_utilization_pct as u64 * 100;
+    let (min_rate, optimal_rate, max_rate) = (
+        min_rate_pct as u64 * 100,
+        optimal_rate_pct as u64 * 100,
+        max_rate_pct as u64 * 100,
+    );
+    if utilization_bps <= kink_bps || kink_bps == 10_000 {
+        if kink_bps == 0 {
+            return Ok(optimal_rate);
+        }
+        let slope =
+            math::checked_mul(utilization_bps, optimal_rate.saturating_sub(min_rate))? / kink_bps;
+        return math::checked_add(min_rate, slope);
+    }
+    let slope = math::checked_mul(
+        utilization_bps - kink_bps,
+        max_rate.saturating_sub(optimal_rate),
+    )? / (10_000 - kink_bps);
+    math::checked_add(optimal_rate, slope)
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Result<u8> {
+    data.get(offset)
+        .copied()
+        .ok_or_else(|| TradingBotError::InvalidLendingReserve.into())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + 8)
+        .ok_or(TradingBotError::InvalidLendingReserve)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u128(data: &[u8], offset: usize) -> Result<u128> {
+    let bytes = data
+        .get(offset..offset + 16)
+        .ok_or(TradingBotError::InvalidLendingReserve)?;
+    Ok(u128::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    let bytes = data
+        .get(offset..offset + 32)
+        .ok_or(TradingBotError::InvalidLendingReserve)?;
+    Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+}
@@ -1,6 +1,17 @@
 use anchor_lang::prelude::*;
 use pyth_sdk_solana::{load_price_feed_from_account_info, Price, PriceFeed};
+use crate::constants::{PRICE_DECIMALS, PRICE_PRECISION, USD_DECIMALS};
 use crate::errors::TradingBotError;
+use crate::math;
+use crate::state::FeedRegistry;
+use crate::types::TokenPair;
+use self::stake_pool::ExchangeRate;
+#[cfg(feature = "devnet")]
+use crate::state::MockPriceFeed;
+
+pub mod lending_obligation;
+pub mod lending_reserve;
+pub mod stake_pool;
 
 pub struct PythOracle;
 
@@ -87,14 +98,15 @@ impl PythOracle {
         subscription.bump = *ctx.bumps.get("subscription").unwrap();
 
         // Verify initial price data
-        let initial_price = Self::get_price_with_confidence(
+        let initial_price = Self::read_price(
             &ctx.accounts.feed_id,
             confidence_interval,
+            100, // 1% maximum relative confidence
             60, // 60 seconds max staleness for initial price
         )?;
 
         subscription.last_price = initial_price.price;
-        subscription.last_confidence = initial_price.confidence;
+        subscription.last_confidence = initial_price.conf;
 
         Ok(())
     }
@@ -113,33 +125,198 @@ impl PythOracle {
         );
 
         // Get and validate new price
-        let price_data = Self::get_price_with_confidence(
+        let price_data = Self::read_price(
             &ctx.accounts.price_feed,
             subscription.confidence_interval,
+            100, // 1% maximum relative confidence
             60, // 60 seconds max staleness
         )?;
 
         // Update subscription data
         subscription.last_price = price_data.price;
-        subscription.last_confidence = price_data.confidence;
+        subscription.last_confidence = price_data.conf;
         subscription.last_update = current_time;
         
         Ok(())
     }
 
-    // Get price with enhanced confidence validation
+    // Price of `mint`, read from the feed the registry approves for it and held to the registry's
+    // staleness and relative confidence limits as well as `max_confidence_interval`
     pub fn get_price_with_confidence(
+        registry: &FeedRegistry,
+        mint: &Pubkey,
         price_feed_account: &AccountInfo,
         max_confidence_interval: u64,
+    ) -> Result<Price> {
+        let feed = registry.resolve(mint, price_feed_account.key)?;
+        require!(feed.stake_pool.is_none(), TradingBotError::StakePoolRequired);
+        let price = Self::read_price(
+            price_feed_account,
+            max_confidence_interval,
+            feed.max_confidence_bps,
+            feed.max_staleness,
+        )?;
+        feed.check_expo(price.expo)?;
+        Ok(price)
+    }
+
+    // USD price of one whole `mint` in PRICE_PRECISION, whatever exponent its feed publishes at
+    pub fn get_usd_price(
+        registry: &FeedRegistry,
+        mint: &Pubkey,
+        price_feed_account: &AccountInfo,
+        max_confidence_interval: u64,
+    ) -> Result<u64> {
+        let price = Self::get_price_with_confidence(
+            registry,
+            mint,
+            price_feed_account,
+            max_confidence_interval,
+        )?;
+        Self::usd_price(&price)
+    }
+
+    // The pair's price from its base mint's feed, in native quote per native base scaled by
+    // PRICE_PRECISION: the unit Valuation::quote_value, limit prices, triggers and min outs are
+    // all kept in. The base feed prices in the quote, and both mints' decimals come from the
+    // registry, so the quote has to be registered as well.
+    pub fn get_pair_price(
+        registry: &FeedRegistry,
+        pair: &TokenPair,
+        price_feed_account: &AccountInfo,
+        max_confidence_interval: u64,
+    ) -> Result<u64> {
+        let price = Self::get_price_with_confidence(
+            registry,
+            &pair.base_mint,
+            price_feed_account,
+            max_confidence_interval,
+        )?;
+        Self::pair_price(registry, pair, &price)
+    }
+
+    // `price` read from the pair's base feed (or an LST's SOL feed times its rate) in the unit
+    // get_pair_price returns
+    pub fn pair_price(registry: &FeedRegistry, pair: &TokenPair, price: &Price) -> Result<u64> {
+        Self::native_price(
+            price,
+            registry.decimals(&pair.base_mint)?,
+            registry.decimals(&pair.quote_mint)?,
+        )
+    }
+
+    // Mantissa at the feed's exponent, per whole token, to PRICE_PRECISION
+    pub fn usd_price(price: &Price) -> Result<u64> {
+        Self::scale_price(price, 0)
+    }
+
+    // Mantissa at the feed's exponent, whole quote per whole base, to native quote per native
+    // base in PRICE_PRECISION
+    pub fn native_price(price: &Price, base_decimals: u8, quote_decimals: u8) -> Result<u64> {
+        Self::scale_price(price, quote_decimals as i32 - base_decimals as i32)
+    }
+
+    // USD value of `amount` of `mint`, with USD_DECIMALS, at its registered feed and decimals
+    pub fn get_usd_value(
+        registry: &FeedRegistry,
+        mint: &Pubkey,
+        price_feed_account: &AccountInfo,
+        max_confidence_interval: u64,
+        amount: u64,
+    ) -> Result<u64> {
+        let usd_price =
+            Self::get_usd_price(registry, mint, price_feed_account, max_confidence_interval)?;
+        Self::usd_value(amount, registry.decimals(mint)?, usd_price)
+    }
+
+    // USD value of `amount` native units of a token at `usd_price`, with USD_DECIMALS
+    pub fn usd_value(amount: u64, decimals: u8, usd_price: u64) -> Result<u64> {
+        Self::usd_convert(amount, decimals, usd_price, USD_DECIMALS, PRICE_PRECISION)
+    }
+
+    // `amount` native units of a token at `usd_price` in native units of one at `to_usd_price`,
+    // both USD per whole token in PRICE_PRECISION as get_usd_price returns them, rounded down
+    pub fn usd_convert(
+        amount: u64,
+        decimals: u8,
+        usd_price: u64,
+        to_decimals: u8,
+        to_usd_price: u64,
+    ) -> Result<u64> {
+        require_gt!(to_usd_price, 0, TradingBotError::InvalidCalculation);
+        let shift = to_decimals as i32 - decimals as i32;
+        let scale = 10u128
+            .checked_pow(shift.unsigned_abs())
+            .ok_or_else(|| error!(TradingBotError::Overflow))?;
+        let (numerator_scale, denominator_scale) = if shift >= 0 { (scale, 1) } else { (1, scale) };
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(
+                math::checked_mul(amount as u128, usd_price as u128)?,
+                numerator_scale,
+            )?,
+            math::checked_mul(to_usd_price as u128, denominator_scale)?,
+        )?)
+    }
+
+    // mantissa * 10^(expo + shift + PRICE_DECIMALS), rounded down
+    fn scale_price(price: &Price, shift: i32) -> Result<u64> {
+        let mantissa = math::checked_as_u64(price.price)? as u128;
+        let exponent = price.expo + shift + PRICE_DECIMALS as i32;
+        let scale = 10u128
+            .checked_pow(exponent.unsigned_abs())
+            .ok_or_else(|| error!(TradingBotError::Overflow))?;
+        let scaled = if exponent >= 0 {
+            math::checked_mul(mantissa, scale)?
+        } else {
+            math::checked_div(mantissa, scale)?
+        };
+        math::checked_as_u64(scaled)
+    }
+
+    // The exponent a feed currently publishes at, checked against the registry when it's set
+    pub fn feed_expo(price_feed_account: &AccountInfo) -> Result<i32> {
+        Ok(Self::load_current_price(price_feed_account)?.expo)
+    }
+
+    // Price of an LST: the SOL price from its registered feed, held to the same limits, times the
+    // lamports one token redeems for in `stake_pool`
+    pub fn get_lst_price(
+        registry: &FeedRegistry,
+        mint: &Pubkey,
+        sol_feed_account: &AccountInfo,
+        stake_pool: &AccountInfo,
+        max_confidence_interval: u64,
+    ) -> Result<Price> {
+        let feed = registry.resolve(mint, sol_feed_account.key)?;
+        let source = feed.stake_pool.ok_or(TradingBotError::InvalidStakePool)?;
+        let rate = ExchangeRate::read(&source, stake_pool, mint)?;
+        let sol = Self::read_price(
+            sol_feed_account,
+            max_confidence_interval,
+            feed.max_confidence_bps,
+            feed.max_staleness,
+        )?;
+        feed.check_expo(sol.expo)?;
+        Ok(Price {
+            price: i64::try_from(rate.apply(crate::math::checked_as_u64(sol.price)?)?)
+                .map_err(|_| TradingBotError::InvalidCalculation)?,
+            conf: rate.apply(sol.conf)?,
+            expo: sol.expo,
+            publish_time: sol.publish_time,
+        })
+    }
+
+    // Get price with enhanced confidence validation
+    fn read_price(
+        price_feed_account: &AccountInfo,
+        max_confidence_interval: u64,
+        max_confidence_bps: u16,
         max_staleness: i64,
     ) -> Result<Price> {
-        // Load price feed
-        let price_feed: PriceFeed = load_price_feed_from_account_info(price_feed_account)?;
         let current_timestamp = Clock::get()?.unix_timestamp;
-        
+
         // Get current price
-        let price = price_feed.get_current_price()
-            .ok_or(TradingBotError::PriceUnavailable)?;
+        let price = Self::load_current_price(price_feed_account)?;
 
         // Validate staleness
         require!(
@@ -150,18 +327,42 @@ impl PythOracle {
         // Enhanced confidence validations
         Self::validate_confidence_metrics(
             price.price,
-            price.confidence,
+            price.conf,
             max_confidence_interval,
+            max_confidence_bps,
         )?;
 
         Ok(price)
     }
 
+    // Loads the feed's current price; with the `devnet` feature a MockPriceFeed owned by this
+    // program is read in place of a Pyth account
+    fn load_current_price(price_feed_account: &AccountInfo) -> Result<Price> {
+        #[cfg(feature = "devnet")]
+        {
+            if price_feed_account.owner == &crate::ID {
+                let mock = Account::<MockPriceFeed>::try_from(price_feed_account)?;
+                return Ok(Price {
+                    price: mock.price,
+                    conf: mock.conf,
+                    expo: mock.expo,
+                    publish_time: mock.publish_time,
+                });
+            }
+        }
+
+        let price_feed: PriceFeed = load_price_feed_from_account_info(price_feed_account)
+            .map_err(|_| TradingBotError::PriceUnavailable)?;
+        // staleness is checked by the caller against its own window
+        Ok(price_feed.get_price_unchecked())
+    }
+
     // Validate confidence metrics
     fn validate_confidence_metrics(
         price: i64,
         confidence: u64,
         max_confidence_interval: u64,
+        max_confidence_bps: u16,
     ) -> Result<()> {
         // Basic confidence check
         require!(
@@ -170,9 +371,9 @@ impl PythOracle {
         );
 
         // Relative confidence check (confidence should be within percentage of price)
-        let relative_confidence = (confidence as f64 / price.abs() as f64) * 100.0;
+        let relative_confidence = (confidence as f64 / price.abs() as f64) * 10000.0;
         require!(
-            relative_confidence <= 1.0, // 1% maximum relative confidence
+            relative_confidence <= max_confidence_bps as f64,
             TradingBotError::ExcessiveConfidenceInterval
         );
 
@@ -187,27 +388,150 @@ impl PythOracle {
 
     // Get exponential moving average price
     pub fn get_ema_price(
-        price_feed: &AccountInfo,
-        period: u64,
+        _price_feed: &AccountInfo,
+        _period: u64,
     ) -> Result<i64> {
         // Implementation as before
         Ok(0)
     }
 }
 
-// Add to errors.rs
-#[error_code]
-pub enum OracleError {
-    #[msg("Price feed is stale")]
-    StalePriceFeed,
-    #[msg("Price confidence interval too high")]
-    LowConfidence,
-    #[msg("Insufficient price confidence")]
-    InsufficientConfidence,
-    #[msg("Excessive confidence interval")]
-    ExcessiveConfidenceInterval,
-    #[msg("Too frequent price updates")]
-    TooFrequentUpdates,
-    #[msg("Price unavailable")]
-    PriceUnavailable,
-} 
\ No newline at end of file
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::state::RegisteredFeed;
+    use pyth_sdk_solana::state::{AccountType, PriceAccount, PriceStatus, MAGIC, VERSION_2};
+
+    // A Pyth price account as the oracle program lays it out, trading at `price` * 10^`expo`
+    pub(crate) fn pyth_account_data(price: i64, conf: u64, expo: i32) -> Vec<u8> {
+        let mut account: PriceAccount = bytemuck::Zeroable::zeroed();
+        account.magic = MAGIC;
+        account.ver = VERSION_2;
+        account.atype = AccountType::Price as u32;
+        account.expo = expo;
+        account.timestamp = 1_700_000_000;
+        account.agg.price = price;
+        account.agg.conf = conf;
+        account.agg.status = PriceStatus::Trading;
+        bytemuck::bytes_of(&account).to_vec()
+    }
+
+    // Reads `data` through the same loader price reads go through
+    pub(crate) fn load(data: &mut [u8]) -> Price {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, data, &owner, false, 0);
+        PythOracle::load_current_price(&info).unwrap()
+    }
+
+    pub(crate) fn registered(mint: Pubkey, decimals: u8, expo: i32) -> RegisteredFeed {
+        RegisteredFeed {
+            mint,
+            pyth_feed: Pubkey::new_unique(),
+            switchboard_feed: Pubkey::default(),
+            max_staleness: 60,
+            max_confidence_bps: 100,
+            expo,
+            decimals,
+            stake_pool: None,
+        }
+    }
+
+    // SOL/USD at $150.25 from an expo -8 feed, against 9-decimal SOL and 6-decimal USDC
+    pub(crate) fn sol_usdc() -> (FeedRegistry, TokenPair, Price) {
+        let pair = TokenPair::new(Pubkey::new_unique(), Pubkey::new_unique());
+        let registry = FeedRegistry {
+            admin: Pubkey::new_unique(),
+            feeds: vec![
+                registered(pair.base_mint, 9, -8),
+                registered(pair.quote_mint, 6, -8),
+            ],
+            bump: 255,
+        };
+        let price = load(&mut pyth_account_data(15_025_000_000, 1_500_000, -8));
+        (registry, pair, price)
+    }
+
+    #[test]
+    fn loads_the_price_a_real_feed_layout_carries() {
+        let price = load(&mut pyth_account_data(15_025_000_000, 1_500_000, -8));
+        assert_eq!((price.price, price.conf, price.expo), (15_025_000_000, 1_500_000, -8));
+        assert_eq!(price.publish_time, 1_700_000_000);
+    }
+
+    #[test]
+    fn usd_price_applies_the_exponent() {
+        let (_, _, price) = sol_usdc();
+        assert_eq!(PythOracle::usd_price(&price).unwrap(), 150_250_000);
+
+        // the same price published at another exponent reads the same
+        let coarse = load(&mut pyth_account_data(15_025_000, 1_500, -5));
+        assert_eq!(PythOracle::usd_price(&coarse).unwrap(), 150_250_000);
+    }
+
+    #[test]
+    fn usd_value_applies_the_token_decimals() {
+        let (_, _, price) = sol_usdc();
+        let sol_usd = PythOracle::usd_price(&price).unwrap();
+        // 2.5 SOL in lamports is $375.625
+        assert_eq!(PythOracle::usd_value(2_500_000_000, 9, sol_usd).unwrap(), 375_625_000);
+        // the same value held in a 6-decimal token at the same price
+        assert_eq!(PythOracle::usd_value(2_500_000, 6, sol_usd).unwrap(), 375_625_000);
+        // a lamport is worth less than a USD_DECIMALS unit
+        assert_eq!(PythOracle::usd_value(1, 9, sol_usd).unwrap(), 0);
+    }
+
+    #[test]
+    fn usd_convert_moves_between_decimals() {
+        let (_, _, price) = sol_usdc();
+        let sol_usd = PythOracle::usd_price(&price).unwrap();
+        // 150.25 USDC buys one SOL, and back
+        let lamports = PythOracle::usd_convert(150_250_000, 6, PRICE_PRECISION, 9, sol_usd);
+        assert_eq!(lamports.unwrap(), 1_000_000_000);
+        let usdc = PythOracle::usd_convert(1_000_000_000, 9, sol_usd, 6, PRICE_PRECISION);
+        assert_eq!(usdc.unwrap(), 150_250_000);
+        assert!(PythOracle::usd_convert(1, 6, PRICE_PRECISION, 9, 0).is_err());
+    }
+
+    #[test]
+    fn pair_price_is_native_quote_per_native_base() {
+        let (registry, pair, price) = sol_usdc();
+        let native = PythOracle::pair_price(&registry, &pair, &price).unwrap();
+        assert_eq!(native, 150_250);
+        // one SOL in lamports is worth 150.25 USDC in its 6-decimal units
+        assert_eq!(
+            crate::valuation::Valuation::quote_value(1_000_000_000, native).unwrap(),
+            150_250_000
+        );
+
+        // with the decimals the other way round the price scales up instead
+        let flipped = PythOracle::native_price(&price, 6, 9).unwrap();
+        assert_eq!(flipped, 150_250_000_000);
+    }
+
+    #[test]
+    fn pair_price_needs_the_quote_registered() {
+        let (mut registry, pair, price) = sol_usdc();
+        registry.remove(&pair.quote_mint).unwrap();
+        assert!(PythOracle::pair_price(&registry, &pair, &price).is_err());
+    }
+
+    #[test]
+    fn registry_refuses_a_feed_at_another_exponent() {
+        let (registry, pair, _) = sol_usdc();
+        let feed = registry.feeds.iter().find(|f| f.mint == pair.base_mint).unwrap();
+        feed.check_expo(-8).unwrap();
+        assert!(feed.check_expo(-5).is_err());
+
+        let mut positive = *feed;
+        positive.expo = 2;
+        assert!(positive.validate().is_err());
+    }
+
+    #[test]
+    fn negative_prices_are_rejected() {
+        let price = load(&mut pyth_account_data(-1, 1, -8));
+        assert!(PythOracle::usd_price(&price).is_err());
+    }
+}
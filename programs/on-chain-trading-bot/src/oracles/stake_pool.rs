@@ -0,0 +1,142 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+pub const MARINADE_PROGRAM_ID: Pubkey = pubkey!("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD");
+
+// SPL stake pool account: pool_mint, then total_lamports, pool_token_supply and
+// last_update_epoch back to back (jitoSOL, bSOL and most other LSTs)
+const SPL_POOL_MINT: usize = 162;
+const SPL_TOTAL_LAMPORTS: usize = 258;
+const SPL_POOL_TOKEN_SUPPLY: usize = 266;
+const SPL_LAST_UPDATE_EPOCH: usize = 274;
+
+// Marinade state (mSOL): msol_mint right after the discriminator, msol_price as lamports per
+// mSOL in 32.32 fixed point
+const MARINADE_MSOL_MINT: usize = 8;
+const MARINADE_MSOL_PRICE: usize = 568;
+const MARINADE_PRICE_DENOMINATOR: u128 = 1 << 32;
+
+// The program whose account holds an LST's exchange rate
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum StakePoolProgram {
+    SplStakePool,
+    Marinade,
+}
+
+impl StakePoolProgram {
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            StakePoolProgram::SplStakePool => SPL_STAKE_POOL_PROGRAM_ID,
+            StakePoolProgram::Marinade => MARINADE_PROGRAM_ID,
+        }
+    }
+}
+
+// Where the registry values an LST from: SOL's price times the lamports one token redeems for
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct StakePoolSource {
+    pub program: StakePoolProgram,
+    pub pool: Pubkey,
+}
+
+// Lamports one LST redeems for, as a ratio so nothing is lost before it is applied
+#[derive(Clone, Copy, Debug)]
+pub struct ExchangeRate {
+    pub lamports: u128,
+    pub tokens: u128,
+}
+
+impl ExchangeRate {
+    // Reads the rate of `mint` from its pool account, which has to be owned by the program and
+    // mint it. An SPL pool is only trusted once updated this epoch, since its total lags the
+    // rewards until then.
+    pub fn read(source: &StakePoolSource, pool: &AccountInfo, mint: &Pubkey) -> Result<Self> {
+        require_keys_eq!(pool.key(), source.pool, TradingBotError::InvalidStakePool);
+        let data = pool.try_borrow_data()?;
+        match source.program {
+            StakePoolProgram::SplStakePool => {
+                require_keys_eq!(
+                    *pool.owner,
+                    SPL_STAKE_POOL_PROGRAM_ID,
+                    TradingBotError::InvalidStakePool
+                );
+                require_keys_eq!(
+                    read_pubkey(&data, SPL_POOL_MINT)?,
+                    *mint,
+                    TradingBotError::InvalidStakePool
+                );
+                require_eq!(
+                    read_u64(&data, SPL_LAST_UPDATE_EPOCH)?,
+                    Clock::get()?.epoch,
+                    TradingBotError::StaleStakePool
+                );
+                Self::new(
+                    read_u64(&data, SPL_TOTAL_LAMPORTS)? as u128,
+                    read_u64(&data, SPL_POOL_TOKEN_SUPPLY)? as u128,
+                )
+            }
+            StakePoolProgram::Marinade => {
+                require_keys_eq!(
+                    *pool.owner,
+                    MARINADE_PROGRAM_ID,
+                    TradingBotError::InvalidStakePool
+                );
+                require_keys_eq!(
+                    read_pubkey(&data, MARINADE_MSOL_MINT)?,
+                    *mint,
+                    TradingBotError::InvalidStakePool
+                );
+                Self::new(
+                    read_u64(&data, MARINADE_MSOL_PRICE)? as u128,
+                    MARINADE_PRICE_DENOMINATOR,
+                )
+            }
+        }
+    }
+
+    fn new(lamports: u128, tokens: u128) -> Result<Self> {
+        require!(
+            lamports > 0 && tokens > 0,
+            TradingBotError::InvalidStakePool
+        );
+        Ok(Self { lamports, tokens })
+    }
+
+    // `sol_value` of SOL converted to the LST's worth of it
+    pub fn apply(&self, sol_value: u64) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(sol_value as u128, self.lamports)?,
+            self.tokens,
+        )?)
+    }
+
+    // How far a market price of `lamports` per `tokens`, e.g. a pool's reserves, sits from the
+    // rate, in bps of the rate
+    pub fn deviation_bps(&self, lamports: u128, tokens: u128) -> Result<u64> {
+        let market = math::checked_mul(lamports, self.tokens)?;
+        let fair = math::checked_mul(self.lamports, tokens)?;
+        require!(fair > 0, TradingBotError::InvalidStakePool);
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(market.abs_diff(fair), 10_000)?,
+            fair,
+        )?)
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + 8)
+        .ok_or(TradingBotError::InvalidStakePool)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    let bytes = data
+        .get(offset..offset + 32)
+        .ok_or(TradingBotError::InvalidStakePool)?;
+    Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+}
@@ -0,0 +1,125 @@
+use crate::math;
+use anchor_lang::prelude::*;
+
+pub const PRICE_RING_LEN: usize = 32;
+// fewer samples than this say nothing useful about volatility
+pub const MIN_REGIME_SAMPLES: u8 = 8;
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, InitSpace,
+)]
+pub enum VolatilityRegime {
+    Low,
+    Medium,
+    High,
+}
+
+impl VolatilityRegime {
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn from_index(index: u8) -> Self {
+        match index {
+            0 => VolatilityRegime::Low,
+            1 => VolatilityRegime::Medium,
+            _ => VolatilityRegime::High,
+        }
+    }
+}
+
+// Oracle samples taken at a fixed interval, oldest overwritten first
+#[zero_copy]
+pub struct PriceRing {
+    pub prices: [u64; PRICE_RING_LEN],
+    pub head: u8,
+    pub count: u8,
+    pub padding: [u8; 6],
+}
+
+impl Default for PriceRing {
+    fn default() -> Self {
+        Self {
+            prices: [0; PRICE_RING_LEN],
+            head: 0,
+            count: 0,
+            padding: [0; 6],
+        }
+    }
+}
+
+impl PriceRing {
+    pub fn push(&mut self, price: u64) {
+        self.prices[self.head as usize] = price;
+        self.head = ((self.head as usize + 1) % PRICE_RING_LEN) as u8;
+        if (self.count as usize) < PRICE_RING_LEN {
+            self.count += 1;
+        }
+    }
+
+    // Samples from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        let start = (self.head as usize + PRICE_RING_LEN - self.count as usize) % PRICE_RING_LEN;
+        (0..self.count as usize).map(move |i| self.prices[(start + i) % PRICE_RING_LEN])
+    }
+
+    // Mean absolute sample-to-sample return, in bps
+    pub fn volatility_bps(&self) -> Result<u64> {
+        if self.count < 2 {
+            return Ok(0);
+        }
+        let mut total: u128 = 0;
+        let mut previous: Option<u64> = None;
+        for price in self.iter() {
+            if let Some(previous) = previous.filter(|p| *p > 0) {
+                let change = (price as i128 - previous as i128).unsigned_abs();
+                total = math::checked_add(
+                    total,
+                    math::checked_div(math::checked_mul(change, 10000)?, previous as u128)?,
+                )?;
+            }
+            previous = Some(price);
+        }
+        math::checked_as_u64(math::checked_div(total, (self.count - 1) as u128)?)
+    }
+}
+
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RegimeThresholds {
+    // volatility at or above which the regime is at least Medium / High
+    pub medium_bps: u64,
+    pub high_bps: u64,
+    // how far volatility must fall below a boundary before stepping down
+    pub hysteresis_bps: u64,
+}
+
+impl RegimeThresholds {
+    pub fn is_valid(&self) -> bool {
+        self.medium_bps > 0
+            && self.high_bps > self.medium_bps
+            && self.hysteresis_bps < self.medium_bps
+    }
+
+    pub fn classify(&self, volatility_bps: u64) -> VolatilityRegime {
+        if volatility_bps >= self.high_bps {
+            VolatilityRegime::High
+        } else if volatility_bps >= self.medium_bps {
+            VolatilityRegime::Medium
+        } else {
+            VolatilityRegime::Low
+        }
+    }
+
+    // Rising volatility switches at once; falling volatility only once it clears the boundary by
+    // the hysteresis margin, so a regime doesn't flap around a threshold
+    pub fn next(&self, current: VolatilityRegime, volatility_bps: u64) -> VolatilityRegime {
+        let raw = self.classify(volatility_bps);
+        if raw >= current {
+            raw
+        } else {
+            self.classify(volatility_bps.saturating_add(self.hysteresis_bps))
+                .min(current)
+        }
+    }
+}
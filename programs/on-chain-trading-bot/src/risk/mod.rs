@@ -1,13 +1,26 @@
 use anchor_lang::prelude::*;
-use crate::state::{TradingStrategy, RiskParameters};
+use crate::constants::PRICE_PRECISION;
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::state::{CorrelationMatrix, Strategy};
 
 pub struct RiskManager;
 
+// The asset a trade moves and its signed quote notional, checked against the owner's
+// correlation matrix
+pub struct PortfolioTrade<'a> {
+    pub matrix: &'a CorrelationMatrix,
+    pub mint: Pubkey,
+    pub notional: i64,
+}
+
 impl RiskManager {
+    // `trade_size` is in the strategy's accounting currency, like its limits and P&L
     pub fn validate_trade(
-        strategy: &TradingStrategy,
+        strategy: &Strategy,
         trade_size: u64,
-        current_price: u64,
+        _current_price: u64,
+        portfolio: Option<PortfolioTrade>,
     ) -> Result<bool> {
         let risk_params = &strategy.risk_parameters;
 
@@ -17,18 +30,128 @@ impl RiskManager {
         }
 
         // Check daily loss limit
-        if strategy.performance_metrics.total_profit_loss < -(risk_params.daily_loss_limit as i64) {
+        if Self::loss_limit_breached(strategy) {
             return Ok(false);
         }
 
-        // Check position limit
-        // Add more risk checks
+        // Check position limit, counting correlated positions elsewhere in the portfolio
+        if let Some(portfolio) = portfolio {
+            return Self::validate_marginal_risk(&portfolio, risk_params.max_position_size);
+        }
 
         Ok(true)
     }
 
+    // Past its loss limit a strategy can't trade, and its held lots can be forced into auction
+    pub fn loss_limit_breached(strategy: &Strategy) -> bool {
+        let limit = strategy.risk_parameters.daily_loss_limit as i64;
+        strategy.performance_metrics.total_profit_loss < -limit
+    }
+
+    // Trades that lower portfolio risk always pass; otherwise the resulting risk must fit the
+    // position limit. Unknown assets and stale estimates fail closed.
+    pub fn validate_marginal_risk(
+        portfolio: &PortfolioTrade,
+        max_position_size: u64,
+    ) -> Result<bool> {
+        let matrix = portfolio.matrix;
+        if matrix.is_stale(Clock::get()?.unix_timestamp) {
+            return Ok(false);
+        }
+        let asset = match matrix.asset_index(&portfolio.mint) {
+            Some(asset) => asset,
+            None => return Ok(false),
+        };
+
+        let (before, after) = matrix.marginal_risk(asset, portfolio.notional)?;
+        msg!("Portfolio risk {} -> {}", before, after);
+        Ok(after <= before || after <= max_position_size)
+    }
+
+    // Gross exposure over NAV in bps, 10000 = 1x. Any exposure against a wiped out NAV is
+    // unbounded leverage.
+    pub fn leverage_bps(gross_exposure: u64, nav: u64) -> Result<u64> {
+        if nav == 0 {
+            return Ok(if gross_exposure == 0 { 0 } else { u64::MAX });
+        }
+        let leverage = math::checked_div(
+            math::checked_mul(gross_exposure as u128, 10000)?,
+            nav as u128,
+        )?;
+        Ok(u64::try_from(leverage).unwrap_or(u64::MAX))
+    }
+
+    // Zero max_leverage_bps disables the cap
+    pub fn validate_leverage(gross_exposure: u64, nav: u64, max_leverage_bps: u32) -> Result<()> {
+        if max_leverage_bps == 0 {
+            return Ok(());
+        }
+        let leverage = Self::leverage_bps(gross_exposure, nav)?;
+        msg!("Leverage {} bps, cap {}", leverage, max_leverage_bps);
+        require_gte!(
+            max_leverage_bps as u64,
+            leverage,
+            TradingBotError::LeverageExceeded
+        );
+        Ok(())
+    }
+
+    // Signed quote notional of a trade, positive for buys. `current_price` is native quote per
+    // native base in PRICE_PRECISION, as PythOracle::pair_price reads it.
+    pub fn trade_notional(trade_size: u64, current_price: u64, is_buy: bool) -> Result<i64> {
+        let notional = math::checked_as_u64(math::checked_div(
+            math::checked_mul(trade_size as u128, current_price as u128)?,
+            PRICE_PRECISION as u128,
+        )?)?;
+        let notional =
+            i64::try_from(notional).map_err(|_| error!(TradingBotError::InvalidCalculation))?;
+        Ok(if is_buy { notional } else { -notional })
+    }
+
+    // Base to sell so a `position` of base is worth no more than `max_position_size` in quote at
+    // `current_price`; zero while it's within the limit
+    pub fn soft_unwind_size(
+        position: u64,
+        current_price: u64,
+        max_position_size: u64,
+    ) -> Result<u64> {
+        require_gt!(current_price, 0, TradingBotError::InvalidCalculation);
+        let allowed = math::checked_div(
+            math::checked_mul(max_position_size as u128, PRICE_PRECISION as u128)?,
+            current_price as u128,
+        )?;
+        Ok(position.saturating_sub(u64::try_from(allowed).unwrap_or(u64::MAX)))
+    }
+
+    // Oracle-implied output of swapping `amount_in`, less `slippage_bps`. Buys spend quote for
+    // base, sells base for quote; `current_price` is in the unit trade_notional takes.
+    pub fn oracle_min_out(
+        amount_in: u64,
+        current_price: u64,
+        is_buy: bool,
+        slippage_bps: u16,
+    ) -> Result<u64> {
+        require_gt!(current_price, 0, TradingBotError::InvalidCalculation);
+        let expected = if is_buy {
+            math::checked_div(
+                math::checked_mul(amount_in as u128, PRICE_PRECISION as u128)?,
+                current_price as u128,
+            )?
+        } else {
+            math::checked_div(
+                math::checked_mul(amount_in as u128, current_price as u128)?,
+                PRICE_PRECISION as u128,
+            )?
+        };
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(expected, 10000u128.saturating_sub(slippage_bps as u128))?,
+            10000,
+        )?)
+    }
+
+    // `trade_result` is in the strategy's accounting currency
     pub fn update_metrics(
-        strategy: &mut TradingStrategy,
+        strategy: &mut Strategy,
         trade_result: i64,
     ) -> Result<()> {
         let metrics = &mut strategy.performance_metrics;
@@ -41,7 +164,92 @@ impl RiskManager {
             metrics.loss_count += 1;
             metrics.largest_loss = metrics.largest_loss.max((-trade_result) as u64);
         }
+        metrics.peak_profit_loss = metrics.peak_profit_loss.max(metrics.total_profit_loss);
+        metrics.max_drawdown = metrics
+            .max_drawdown
+            .max(metrics.peak_profit_loss.abs_diff(metrics.total_profit_loss));
 
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracles::tests::{load, pyth_account_data};
+    use crate::oracles::PythOracle;
+
+    // SOL/USD at $150.25 off an expo -8 feed, for 9-decimal SOL against 6-decimal USDC
+    fn sol_usdc_price() -> u64 {
+        let price = load(&mut pyth_account_data(15_025_000_000, 1_500_000, -8));
+        PythOracle::native_price(&price, 9, 6).unwrap()
+    }
+
+    #[test]
+    fn buy_floor_is_the_base_the_quote_buys() {
+        // 150.25 USDC buys one SOL, less 1%
+        let min_out = RiskManager::oracle_min_out(150_250_000, sol_usdc_price(), true, 100);
+        assert_eq!(min_out.unwrap(), 990_000_000);
+    }
+
+    #[test]
+    fn sell_floor_is_the_quote_the_base_fetches() {
+        // two SOL fetch 300.50 USDC, less 1%
+        let min_out = RiskManager::oracle_min_out(2_000_000_000, sol_usdc_price(), false, 100);
+        assert_eq!(min_out.unwrap(), 297_495_000);
+    }
+
+    #[test]
+    fn floors_hold_with_a_six_decimal_base_and_nine_decimal_quote() {
+        // USDC priced in SOL at 0.00665556 off an expo -8 feed
+        let price = load(&mut pyth_account_data(665_556, 100, -8));
+        let price = PythOracle::native_price(&price, 6, 9).unwrap();
+        assert_eq!(price, 6_655_560);
+
+        // selling one USDC fetches 0.00665556 SOL in lamports
+        assert_eq!(
+            RiskManager::oracle_min_out(1_000_000, price, false, 0).unwrap(),
+            6_655_560
+        );
+        // and that many lamports buy it back
+        assert_eq!(
+            RiskManager::oracle_min_out(6_655_560, price, true, 0).unwrap(),
+            1_000_000
+        );
+    }
+
+    #[test]
+    fn notional_is_signed_native_quote() {
+        let price = sol_usdc_price();
+        assert_eq!(
+            RiskManager::trade_notional(1_000_000_000, price, true).unwrap(),
+            150_250_000
+        );
+        assert_eq!(
+            RiskManager::trade_notional(1_000_000_000, price, false).unwrap(),
+            -150_250_000
+        );
+    }
+
+    #[test]
+    fn soft_unwind_sells_only_the_excess() {
+        let price = sol_usdc_price();
+        // 3 SOL against a 300 USDC limit: 1.0033 SOL fits 150.25 each, the rest goes
+        let excess = RiskManager::soft_unwind_size(3_000_000_000, price, 300_000_000).unwrap();
+        assert_eq!(excess, 1_003_327_788);
+        let kept = 3_000_000_000 - excess;
+        assert!(crate::valuation::Valuation::quote_value(kept, price).unwrap() <= 300_000_000);
+
+        // within the limit there's nothing to unwind
+        assert_eq!(
+            RiskManager::soft_unwind_size(1_000_000_000, price, 300_000_000).unwrap(),
+            0
+        );
+        assert!(RiskManager::soft_unwind_size(1, 0, 1).is_err());
+    }
+
+    #[test]
+    fn zero_price_has_no_floor() {
+        assert!(RiskManager::oracle_min_out(1_000, 0, true, 100).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use crate::bridge::WORMHOLE_CHAIN_ID_SOLANA;
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct BridgeTransfer {
+    pub owner: Pubkey,
+    pub strategy: Pubkey,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    // the token's origin as the token bridge names it
+    pub token_chain: u16,
+    pub token_address: [u8; 32],
+    pub nonce: u32,
+    pub direction: BridgeDirection,
+    pub status: BridgeStatus,
+    pub chain: u16,
+    pub foreign_address: [u8; 32],
+    pub amount: u64,
+    pub relayer_fee: u64,
+    pub sequence: u64,
+    pub balance_before: u64,
+    pub created_at: i64,
+    pub completed_at: i64,
+    pub bump: u8,
+}
+
+impl BridgeTransfer {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // The chain whose token bridge emits the transfer's message
+    pub fn emitter_chain(&self) -> u16 {
+        match self.direction {
+            BridgeDirection::Outbound => WORMHOLE_CHAIN_ID_SOLANA,
+            BridgeDirection::Inbound => self.chain,
+        }
+    }
+}
+
+// Marks a token bridge message as used by a transfer, so no other transfer completes on it
+#[account]
+#[derive(InitSpace)]
+pub struct ConsumedSequence {
+    pub bridge_transfer: Pubkey,
+    pub bump: u8,
+}
+
+impl ConsumedSequence {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum BridgeDirection {
+    Outbound,
+    Inbound,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum BridgeStatus {
+    Pending,
+    Completed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BridgeTransferParams {
+    pub nonce: u32,
+    pub amount: u64,
+    pub relayer_fee: u64,
+    pub chain: u16,
+    pub foreign_address: [u8; 32],
+    pub token_chain: u16,
+    pub token_address: [u8; 32],
+}
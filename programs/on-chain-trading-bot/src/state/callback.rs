@@ -0,0 +1,129 @@
+use crate::errors::TradingBotError;
+use crate::state::Strategy;
+use anchor_lang::prelude::*;
+
+pub const MAX_CALLBACK_RULES: usize = 4;
+
+// Amounts are in the strategy's accounting currency, like its P&L
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum CallbackCondition {
+    // deepest fall from the epoch's P&L peak
+    DrawdownAbove { amount: u64 },
+    // total P&L below minus `amount`
+    LossAbove { amount: u64 },
+    // rolling 24h volume against the daily volume limit
+    VolumeUtilizationAbove { bps: u64 },
+    EntriesHalted,
+}
+
+impl CallbackCondition {
+    // The observed value while the condition holds, None while it doesn't
+    pub fn observe(&self, strategy: &Strategy, now: i64) -> Result<Option<u64>> {
+        let metrics = &strategy.performance_metrics;
+        let observed = match *self {
+            CallbackCondition::DrawdownAbove { amount } => {
+                (metrics.max_drawdown > amount).then_some(metrics.max_drawdown)
+            }
+            CallbackCondition::LossAbove { amount } => {
+                let loss = metrics.total_profit_loss.min(0).unsigned_abs();
+                (loss > amount).then_some(loss)
+            }
+            CallbackCondition::VolumeUtilizationAbove { bps } => {
+                let utilization = strategy.volume_utilization_bps(strategy.clock_now()?)?;
+                (utilization > bps).then_some(utilization)
+            }
+            CallbackCondition::EntriesHalted => strategy
+                .volatility_halt
+                .entries_halted(now)
+                .then_some(strategy.volatility_halt.halted_until as u64),
+        };
+        Ok(observed)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum NotifyChannel {
+    Telegram,
+    Discord,
+}
+
+// A rule fires once when its condition starts holding and is re-armed once it has cleared, so a
+// lasting breach sends one notification rather than one per crank
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct CallbackRule {
+    pub condition: CallbackCondition,
+    pub channel: NotifyChannel,
+    pub armed: bool,
+}
+
+// An owner's notification rules for one strategy and the relay allowed to acknowledge what they
+// raise. Firing and re-arming are permissionless: the program checks the condition itself.
+#[account]
+#[derive(InitSpace)]
+pub struct CallbackConfig {
+    pub strategy: Pubkey,
+    pub owner: Pubkey,
+    pub relay: Pubkey,
+    pub rules: [Option<CallbackRule>; MAX_CALLBACK_RULES],
+    // seed of the next CallbackRequest
+    pub next_seq: u64,
+    pub bump: u8,
+}
+
+impl CallbackConfig {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Disarms the rule and returns it with the observed value; fails unless it is armed and holds
+    pub fn fire(
+        &mut self,
+        rule_index: usize,
+        strategy: &Strategy,
+        now: i64,
+    ) -> Result<(CallbackRule, u64)> {
+        let rule = self
+            .rules
+            .get_mut(rule_index)
+            .and_then(|rule| rule.as_mut())
+            .ok_or(TradingBotError::InvalidCallbackRule)?;
+        require!(rule.armed, TradingBotError::CallbackConditionNotMet);
+        let value = rule
+            .condition
+            .observe(strategy, now)?
+            .ok_or(TradingBotError::CallbackConditionNotMet)?;
+        rule.armed = false;
+        Ok((*rule, value))
+    }
+
+    // Re-arms fired rules whose condition has cleared, returning how many were
+    pub fn rearm(&mut self, strategy: &Strategy, now: i64) -> Result<u8> {
+        let mut rearmed = 0;
+        for rule in self.rules.iter_mut().flatten() {
+            if !rule.armed && rule.condition.observe(strategy, now)?.is_none() {
+                rule.armed = true;
+                rearmed += 1;
+            }
+        }
+        Ok(rearmed)
+    }
+}
+
+// A notification waiting for the relay. The relay pushes it to the rule's channel and then
+// acknowledges it, which closes the account and refunds its rent to whoever fired it.
+#[account]
+#[derive(InitSpace)]
+pub struct CallbackRequest {
+    pub config: Pubkey,
+    pub strategy: Pubkey,
+    pub seq: u64,
+    pub rule_index: u8,
+    pub condition: CallbackCondition,
+    pub channel: NotifyChannel,
+    pub value: u64,
+    pub payer: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl CallbackRequest {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
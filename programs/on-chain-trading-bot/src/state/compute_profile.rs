@@ -0,0 +1,109 @@
+use crate::bot_strategy::{ActionType, BlockType, StrategyBlock};
+use crate::math;
+use crate::state::{FeePercentile, VenueStats};
+use anchor_lang::prelude::*;
+
+// Trigger, condition, loop and exit blocks, then action blocks by ActionType
+pub const BLOCK_KINDS: usize = 4 + ActionType::COUNT;
+// what a transaction can request from the ComputeBudget program
+pub const MAX_COMPUTE_UNITS: u64 = 1_400_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ComputeStats {
+    pub samples: u64,
+    pub avg_cu: u64,
+    pub max_cu: u64,
+}
+
+impl ComputeStats {
+    pub fn record(&mut self, compute_units: u64) -> Result<()> {
+        self.avg_cu = if self.samples == 0 {
+            compute_units
+        } else {
+            VenueStats::rolling(self.avg_cu, compute_units)?
+        };
+        self.max_cu = self.max_cu.max(compute_units);
+        self.samples = math::checked_add(self.samples, 1)?;
+        Ok(())
+    }
+}
+
+// One block's measured cost, as profiled off-chain by the reporter, e.g. by simulating it alone
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ComputeSample {
+    pub block_type: BlockType,
+    pub action_type: Option<ActionType>,
+    pub compute_units: u64,
+}
+
+// Compute units a strategy's executions have used, per block kind plus the fixed overhead of an
+// execution, so keepers can request a budget that fits instead of the maximum. Samples come from
+// the owner's designated reporter.
+#[account]
+#[derive(InitSpace)]
+pub struct ComputeProfile {
+    pub strategy: Pubkey,
+    pub owner: Pubkey,
+    pub reporter: Pubkey,
+    pub overhead: ComputeStats,
+    // indexed by `block_kind`
+    pub blocks: [ComputeStats; BLOCK_KINDS],
+    // assumed for a block kind with no samples yet
+    pub default_block_cu: u64,
+    // headroom added on top of the averages
+    pub margin_bps: u16,
+    // FeeOracle bounding keeper priority-fee reimbursements, default when unset
+    pub fee_oracle: Pubkey,
+    pub fee_percentile: FeePercentile,
+    pub bump: u8,
+}
+
+impl ComputeProfile {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn block_kind(block_type: &BlockType, action_type: Option<&ActionType>) -> usize {
+        match block_type {
+            BlockType::Trigger => 0,
+            BlockType::Condition => 1,
+            BlockType::Loop => 2,
+            BlockType::Exit => 3,
+            BlockType::Action => 4 + action_type.map_or(0, ActionType::index),
+        }
+    }
+
+    pub fn record(&mut self, overhead_cu: u64, samples: &[ComputeSample]) -> Result<()> {
+        self.overhead.record(overhead_cu)?;
+        for sample in samples {
+            self.blocks[Self::block_kind(&sample.block_type, sample.action_type.as_ref())]
+                .record(sample.compute_units)?;
+        }
+        Ok(())
+    }
+
+    // Overhead plus each block's average, or the default where a kind is unprofiled, with the
+    // margin on top. Returns the estimate, capped at MAX_COMPUTE_UNITS, and how many blocks were
+    // unprofiled.
+    pub fn estimate(&self, blocks: &[StrategyBlock]) -> Result<(u64, u8)> {
+        let mut total = self.overhead.avg_cu;
+        let mut unprofiled = 0u8;
+        for block in blocks {
+            let kind = Self::block_kind(&block.block_type, block.config.action_type.as_ref());
+            let stats = &self.blocks[kind];
+            let cu = if stats.samples == 0 {
+                unprofiled = unprofiled.saturating_add(1);
+                self.default_block_cu
+            } else {
+                stats.avg_cu
+            };
+            total = math::checked_add(total, cu)?;
+        }
+        let total = math::checked_div(
+            math::checked_mul(total as u128, (10000 + self.margin_bps as u64) as u128)?,
+            10000,
+        )?;
+        Ok((
+            math::checked_as_u64(total)?.min(MAX_COMPUTE_UNITS),
+            unprofiled,
+        ))
+    }
+}
@@ -0,0 +1,83 @@
+use crate::math::{self, tick::isqrt};
+use anchor_lang::prelude::*;
+
+pub const MAX_CORRELATED_ASSETS: usize = 8;
+pub const CORRELATION_PAIRS: usize = MAX_CORRELATED_ASSETS * (MAX_CORRELATED_ASSETS - 1) / 2;
+// correlations are stored in bps, -10000..=10000
+pub const CORRELATION_SCALE: i128 = 10000;
+
+// Keeper-maintained pairwise correlations between the assets an owner's strategies hold, plus the
+// signed quote exposure to each, so risk checks can see how positions add up across strategies
+#[account]
+#[derive(InitSpace)]
+pub struct CorrelationMatrix {
+    pub owner: Pubkey,
+    pub keeper: Pubkey,
+    pub assets: [Pubkey; MAX_CORRELATED_ASSETS],
+    pub asset_count: u8,
+    // upper triangle, row by row
+    pub correlations: [i16; CORRELATION_PAIRS],
+    pub exposures: [i64; MAX_CORRELATED_ASSETS],
+    pub max_age: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl CorrelationMatrix {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn asset_index(&self, mint: &Pubkey) -> Option<usize> {
+        self.assets[..self.asset_count as usize]
+            .iter()
+            .position(|asset| asset == mint)
+    }
+
+    fn pair_index(i: usize, j: usize) -> usize {
+        let (i, j) = if i < j { (i, j) } else { (j, i) };
+        i * (2 * MAX_CORRELATED_ASSETS - i - 1) / 2 + (j - i - 1)
+    }
+
+    pub fn correlation(&self, i: usize, j: usize) -> i128 {
+        if i == j {
+            CORRELATION_SCALE
+        } else {
+            self.correlations[Self::pair_index(i, j)] as i128
+        }
+    }
+
+    pub fn set_correlation(&mut self, i: usize, j: usize, value: i16) {
+        self.correlations[Self::pair_index(i, j)] = value;
+    }
+
+    pub fn is_stale(&self, now: i64) -> bool {
+        now.saturating_sub(self.updated_at) > self.max_age
+    }
+
+    // sqrt(e' C e) over the signed exposures, i.e. the quote exposure the portfolio behaves like.
+    // Keeper estimates needn't be positive semi-definite, so negative variance is floored at zero.
+    pub fn portfolio_risk(&self, exposures: &[i64; MAX_CORRELATED_ASSETS]) -> Result<u64> {
+        let count = self.asset_count as usize;
+        let mut variance: i128 = 0;
+        for i in 0..count {
+            for j in 0..count {
+                let weighted = math::checked_div(
+                    math::checked_mul(exposures[i] as i128, self.correlation(i, j))?,
+                    CORRELATION_SCALE,
+                )?;
+                variance = math::checked_add(
+                    variance,
+                    math::checked_mul(weighted, exposures[j] as i128)?,
+                )?;
+            }
+        }
+        math::checked_as_u64(isqrt(variance.max(0) as u128))
+    }
+
+    // Portfolio risk before and after adding `delta` quote exposure to `asset`
+    pub fn marginal_risk(&self, asset: usize, delta: i64) -> Result<(u64, u64)> {
+        let before = self.portfolio_risk(&self.exposures)?;
+        let mut exposures = self.exposures;
+        exposures[asset] = math::checked_add(exposures[asset], delta)?;
+        Ok((before, self.portfolio_risk(&exposures)?))
+    }
+}
@@ -0,0 +1,91 @@
+use crate::math;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CallStatus {
+    Idle,
+    // calls written against the vault are outstanding
+    Open,
+}
+
+// Sells covered calls on an underlying vault's holdings, one series at a time: keepers write
+// calls on a PsyOptions market against up to `max_coverage_bps` of the unreserved balance, sell
+// them for premium into the quote vault, and settle after expiry. Premium and each series'
+// outcome are kept here as the strategy's option yield.
+#[account]
+#[derive(InitSpace)]
+pub struct CoveredCall {
+    pub owner: Pubkey,
+    pub underlying_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub max_coverage_bps: u16,
+    // how far above the oracle price a strike has to be
+    pub min_otm_bps: u16,
+    // premium a series has to bring in, in bps of the collateral's value
+    pub min_premium_bps: u16,
+    // longest a series may run, in seconds
+    pub max_tenor: i64,
+    pub status: CallStatus,
+    // the open series
+    pub option_market: Pubkey,
+    pub contracts: u64,
+    pub collateral: u64,
+    pub expiration: i64,
+    pub metrics: CallMetrics,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct CallMetrics {
+    pub written: u32,
+    pub expired: u32,
+    pub assigned: u32,
+    // quote received as premium
+    pub premium: u64,
+    // underlying given up to exercise, and the quote received at the strike for it
+    pub underlying_assigned: u64,
+    pub quote_assigned: u64,
+}
+
+impl CoveredCall {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn open(
+        &mut self,
+        option_market: Pubkey,
+        contracts: u64,
+        collateral: u64,
+        expiration: i64,
+        premium: u64,
+    ) -> Result<()> {
+        self.status = CallStatus::Open;
+        self.option_market = option_market;
+        self.contracts = contracts;
+        self.collateral = collateral;
+        self.expiration = expiration;
+        self.metrics.written = math::checked_add(self.metrics.written, 1)?;
+        self.metrics.premium = math::checked_add(self.metrics.premium, premium)?;
+        Ok(())
+    }
+
+    // A series is assigned when any of its collateral didn't come back; returns whether it was
+    pub fn settle(&mut self, underlying_returned: u64, quote_received: u64) -> Result<bool> {
+        let underlying_assigned = self.collateral.saturating_sub(underlying_returned);
+        let assigned = underlying_assigned > 0;
+        if assigned {
+            self.metrics.assigned = math::checked_add(self.metrics.assigned, 1)?;
+            self.metrics.underlying_assigned =
+                math::checked_add(self.metrics.underlying_assigned, underlying_assigned)?;
+            self.metrics.quote_assigned =
+                math::checked_add(self.metrics.quote_assigned, quote_received)?;
+        } else {
+            self.metrics.expired = math::checked_add(self.metrics.expired, 1)?;
+        }
+        self.status = CallStatus::Idle;
+        self.option_market = Pubkey::default();
+        self.contracts = 0;
+        self.collateral = 0;
+        self.expiration = 0;
+        Ok(assigned)
+    }
+}
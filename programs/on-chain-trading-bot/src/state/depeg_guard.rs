@@ -0,0 +1,158 @@
+use crate::math;
+use crate::oracles::PythOracle;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum GuardStatus {
+    // funds sit in the stable vault
+    Pegged,
+    // funds were moved to the haven vault and wait for the peg to restore
+    Rotated,
+}
+
+// Watches a stablecoin's oracle price and rotates the owner's stable vault into a safe haven once
+// the price stays below `depeg_threshold` for `min_duration` seconds, rotating back after it holds
+// above `restore_threshold` for as long. The gap between the two thresholds avoids flapping.
+#[account]
+#[derive(InitSpace)]
+pub struct DepegGuard {
+    pub owner: Pubkey,
+    pub stable_vault: Pubkey,
+    pub haven_vault: Pubkey,
+    pub stable_price_feed: Pubkey,
+    pub haven_price_feed: Pubkey,
+    pub depeg_threshold: u64,
+    pub restore_threshold: u64,
+    pub min_duration: i64,
+    pub max_confidence: u64,
+    pub max_slippage_bps: u16,
+    pub status: GuardStatus,
+    // when the current rotation condition started holding, zero if it doesn't
+    pub condition_since: i64,
+    pub rotation_count: u32,
+    pub bump: u8,
+}
+
+impl DepegGuard {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn condition_holds(&self, stable_price: u64) -> bool {
+        match self.status {
+            GuardStatus::Pegged => stable_price < self.depeg_threshold,
+            GuardStatus::Rotated => stable_price >= self.restore_threshold,
+        }
+    }
+
+    pub fn observe(&mut self, stable_price: u64, now: i64) {
+        if !self.condition_holds(stable_price) {
+            self.condition_since = 0;
+        } else if self.condition_since == 0 {
+            self.condition_since = now;
+        }
+    }
+
+    pub fn rotation_due(&self, now: i64) -> bool {
+        self.condition_since > 0 && now.saturating_sub(self.condition_since) >= self.min_duration
+    }
+
+    pub fn complete_rotation(&mut self) -> Result<()> {
+        self.status = match self.status {
+            GuardStatus::Pegged => GuardStatus::Rotated,
+            GuardStatus::Rotated => GuardStatus::Pegged,
+        };
+        self.condition_since = 0;
+        self.rotation_count = math::checked_add(self.rotation_count, 1)?;
+        Ok(())
+    }
+
+    // Oracle-implied output for `amount_in`, less the allowed slippage
+    pub fn min_out(
+        amount_in: u64,
+        price_in: u64,
+        price_out: u64,
+        decimals_in: u8,
+        decimals_out: u8,
+        slippage_bps: u16,
+    ) -> Result<u64> {
+        let expected =
+            PythOracle::usd_convert(amount_in, decimals_in, price_in, decimals_out, price_out)?;
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(expected as u128, (10000 - slippage_bps) as u128)?,
+            10000,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracles::tests::{load, pyth_account_data};
+    use crate::oracles::PythOracle;
+
+    fn guard() -> DepegGuard {
+        DepegGuard {
+            owner: Pubkey::new_unique(),
+            stable_vault: Pubkey::new_unique(),
+            haven_vault: Pubkey::new_unique(),
+            stable_price_feed: Pubkey::new_unique(),
+            haven_price_feed: Pubkey::new_unique(),
+            depeg_threshold: 980_000,
+            restore_threshold: 995_000,
+            min_duration: 600,
+            max_confidence: u64::MAX,
+            max_slippage_bps: 100,
+            status: GuardStatus::Pegged,
+            condition_since: 0,
+            rotation_count: 0,
+            bump: 255,
+        }
+    }
+
+    // USD price of a stablecoin feed laid out as Pyth publishes it, at expo -8
+    fn stable_price(mantissa: i64) -> u64 {
+        PythOracle::usd_price(&load(&mut pyth_account_data(mantissa, 10_000, -8))).unwrap()
+    }
+
+    #[test]
+    fn thresholds_compare_against_the_scaled_feed_price() {
+        let mut guard = guard();
+        // $0.9999 is pegged even though its mantissa is far above the threshold
+        let pegged = stable_price(99_990_000);
+        assert_eq!(pegged, 999_900);
+        assert!(!guard.condition_holds(pegged));
+
+        let depegged = stable_price(97_000_000);
+        assert_eq!(depegged, 970_000);
+        guard.observe(depegged, 1_000);
+        assert!(!guard.rotation_due(1_599));
+        guard.observe(depegged, 1_300);
+        assert!(guard.rotation_due(1_600));
+
+        // one pegged observation resets the timer
+        guard.observe(pegged, 1_400);
+        assert!(!guard.rotation_due(2_000));
+    }
+
+    #[test]
+    fn restores_only_above_the_restore_threshold() {
+        let mut guard = guard();
+        guard.status = GuardStatus::Rotated;
+        assert!(!guard.condition_holds(stable_price(99_000_000)));
+        guard.observe(stable_price(99_600_000), 5_000);
+        assert!(guard.rotation_due(5_600));
+        guard.complete_rotation().unwrap();
+        assert!(guard.status == GuardStatus::Pegged && guard.condition_since == 0);
+        assert_eq!(guard.rotation_count, 1);
+    }
+
+    #[test]
+    fn min_out_converts_between_decimals() {
+        // 1,000 USDC at $0.97 into SOL at $150 is 6.4666 SOL, less 1%
+        let out = DepegGuard::min_out(1_000_000_000, 970_000, 150_000_000, 6, 9, 100).unwrap();
+        assert_eq!(out, 6_401_999_999);
+
+        // and 6.4 SOL back into USDC at $1
+        let back = DepegGuard::min_out(6_400_000_000, 150_000_000, 1_000_000, 9, 6, 0).unwrap();
+        assert_eq!(back, 960_000_000);
+    }
+}
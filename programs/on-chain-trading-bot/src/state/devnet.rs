@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+// A mint this program is the authority of, handing out up to `max_per_request` per call so
+// demos can fund wallets without an external faucet. Only compiled with the `devnet` feature.
+#[account]
+#[derive(InitSpace)]
+pub struct Faucet {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub max_per_request: u64,
+    pub bump: u8,
+}
+
+impl Faucet {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+// A constant-product pool owned by this program, standing in for external DEX liquidity on
+// devnet. Its reserves are the pool's associated token accounts, so they can be passed wherever
+// a venue's reserve is read, e.g. `pool_reserve_in` or view_batch_quotes.
+#[account]
+#[derive(InitSpace)]
+pub struct MockPool {
+    pub authority: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub fee_bps: u16,
+    pub bump: u8,
+}
+
+impl MockPool {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+// A price feed whose price, confidence and publish time are set by its authority, so tests can
+// script price paths: spikes, stale publishes, wide confidence. PythOracle reads it in place of
+// a Pyth account when the feed is owned by this program.
+#[account]
+#[derive(InitSpace)]
+pub struct MockPriceFeed {
+    pub authority: Pubkey,
+    pub idx: u64,
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub bump: u8,
+}
+
+impl MockPriceFeed {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+#[macro_export]
+macro_rules! faucet_seeds {
+    ( $faucet:expr ) => {
+        &[FAUCET_SEED, $faucet.mint.as_ref(), &[$faucet.bump]]
+    };
+}
+
+#[macro_export]
+macro_rules! mock_pool_seeds {
+    ( $pool:expr ) => {
+        &[
+            MOCK_POOL_SEED,
+            $pool.mint_a.as_ref(),
+            $pool.mint_b.as_ref(),
+            &[$pool.bump],
+        ]
+    };
+}
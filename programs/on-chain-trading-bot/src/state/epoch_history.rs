@@ -0,0 +1,57 @@
+use crate::math;
+use crate::state::PerformanceMetrics;
+use anchor_lang::prelude::*;
+
+pub const EPOCH_HISTORY_LEN: usize = 12;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct EpochSummary {
+    pub epoch: u32,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub metrics: PerformanceMetrics,
+}
+
+// A strategy's performance metrics per epoch, oldest overwritten first. Rolling over archives the
+// current metrics here and resets them, so win rate and drawdown describe the running epoch
+// instead of every configuration the strategy has had.
+#[account]
+#[derive(InitSpace)]
+pub struct EpochHistory {
+    pub strategy: Pubkey,
+    // the running epoch, not yet archived
+    pub epoch: u32,
+    pub epoch_started_at: i64,
+    pub epochs: [EpochSummary; EPOCH_HISTORY_LEN],
+    pub head: u8,
+    pub count: u8,
+    pub bump: u8,
+}
+
+impl EpochHistory {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // `age` 0 is the most recently archived epoch
+    pub fn archived(&self, age: usize) -> Option<&EpochSummary> {
+        (age < self.count as usize).then(|| {
+            &self.epochs[(self.head as usize + EPOCH_HISTORY_LEN - 1 - age) % EPOCH_HISTORY_LEN]
+        })
+    }
+
+    pub fn archive(&mut self, metrics: PerformanceMetrics, now: i64) -> Result<EpochSummary> {
+        let summary = EpochSummary {
+            epoch: self.epoch,
+            started_at: self.epoch_started_at,
+            ended_at: now,
+            metrics,
+        };
+        self.epochs[self.head as usize] = summary;
+        self.head = ((self.head as usize + 1) % EPOCH_HISTORY_LEN) as u8;
+        if (self.count as usize) < EPOCH_HISTORY_LEN {
+            self.count += 1;
+        }
+        self.epoch = math::checked_add(self.epoch, 1)?;
+        self.epoch_started_at = now;
+        Ok(summary)
+    }
+}
@@ -14,12 +14,33 @@ pub struct Escrow {
     pub completed: bool,
     pub airdropped: bool,
     pub bump: u8,
+    // receives the DCA output; the user by default
+    pub beneficiary: Pubkey,
+    pub cancel_policy: CancelPolicy,
+    pub config_locked: bool,
+    pub cancelled: bool,
 }
 
 impl Escrow {
     pub const LEN: usize = 8 + Self::INIT_SPACE;
 }
 
+// What happens to unspent input when the user cancels before the DCA completes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CancelPolicy {
+    FullRefund,
+    Penalty { bps: u16 },
+}
+
+impl CancelPolicy {
+    pub fn penalty_bps(&self) -> u16 {
+        match self {
+            CancelPolicy::FullRefund => 0,
+            CancelPolicy::Penalty { bps } => *bps,
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! escrow_seeds {
     ( $escrow:expr, $idx_bytes:expr ) => {
@@ -0,0 +1,90 @@
+use crate::errors::TradingBotError;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuctionPhase {
+    // no round running; the first bid opens one
+    Idle,
+    // bids are taken and keepers can't execute
+    Bidding,
+    // only the best bidder executes
+    Exclusive,
+    // exclusivity lapsed with the bid unsettled, or the round drew no bids
+    Expired,
+}
+
+// Sells a strategy's next execution to keepers. Bids are rebates in lamports, held here, paid to
+// the strategy owner when the winner executes inside its exclusivity window and forfeited to the
+// owner when it doesn't. Outbid keepers are refunded as they are outbid.
+#[account]
+#[derive(InitSpace)]
+pub struct ExecutionAuction {
+    pub strategy: Pubkey,
+    pub bid_window: i64,
+    pub exclusivity_window: i64,
+    pub min_bid: u64,
+    // zero while idle
+    pub round_started_at: i64,
+    pub best_bidder: Pubkey,
+    pub best_bid: u64,
+    // rebates paid or forfeited to the owner over the auction's life
+    pub total_rebates: u64,
+    pub bump: u8,
+}
+
+impl ExecutionAuction {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn phase(&self, now: i64) -> AuctionPhase {
+        if self.round_started_at == 0 {
+            return AuctionPhase::Idle;
+        }
+        let bidding_ends = self.round_started_at.saturating_add(self.bid_window);
+        if now < bidding_ends {
+            AuctionPhase::Bidding
+        } else if self.best_bid > 0 && now < bidding_ends.saturating_add(self.exclusivity_window) {
+            AuctionPhase::Exclusive
+        } else {
+            AuctionPhase::Expired
+        }
+    }
+
+    // Whether a keeper may execute now, which the owner always may. Past exclusivity any keeper
+    // can again, settling a lapsed winner's bid as it does.
+    pub fn allows(&self, keeper: &Pubkey, now: i64) -> bool {
+        match self.phase(now) {
+            AuctionPhase::Bidding => false,
+            AuctionPhase::Exclusive => *keeper == self.best_bidder,
+            AuctionPhase::Idle | AuctionPhase::Expired => true,
+        }
+    }
+
+    // Takes a bid, opening a round when none is running. Returns the lamports owed back to the
+    // outbid keeper, if there was one.
+    pub fn bid(&mut self, bidder: Pubkey, amount: u64, now: i64) -> Result<Option<u64>> {
+        match self.phase(now) {
+            AuctionPhase::Idle => self.round_started_at = now,
+            AuctionPhase::Expired if self.best_bid == 0 => self.round_started_at = now,
+            AuctionPhase::Bidding => {}
+            _ => return Err(TradingBotError::InvalidAuctionBid.into()),
+        }
+        require!(
+            amount >= self.min_bid && amount > self.best_bid,
+            TradingBotError::InvalidAuctionBid
+        );
+        let outbid = (self.best_bid > 0).then_some(self.best_bid);
+        self.best_bidder = bidder;
+        self.best_bid = amount;
+        Ok(outbid)
+    }
+
+    // Ends the round, returning the winning bid owed to the owner
+    pub fn settle(&mut self) -> u64 {
+        let rebate = self.best_bid;
+        self.total_rebates = self.total_rebates.saturating_add(rebate);
+        self.round_started_at = 0;
+        self.best_bidder = Pubkey::default();
+        self.best_bid = 0;
+        rebate
+    }
+}
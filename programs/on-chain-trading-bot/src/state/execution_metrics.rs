@@ -0,0 +1,187 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::types::{DexType, TradeSide};
+use anchor_lang::prelude::*;
+
+// Rolling averages weight each new sample 1/ROLLING_WINDOW
+pub const ROLLING_WINDOW: u64 = 20;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct VenueStats {
+    pub attempts: u64,
+    pub failures: u64,
+    pub avg_slippage_bps: u64,
+    pub avg_deviation_bps: u64,
+    pub failure_rate_bps: u64,
+    pub banned: bool,
+    pub banned_at: i64,
+    // rolling cost breakdown of attributed fills, positive when it went against the trade
+    pub attributed_fills: u64,
+    pub avg_latency_bps: i64,
+    pub avg_spread_bps: i64,
+    pub avg_impact_bps: i64,
+}
+
+impl VenueStats {
+    pub fn rolling(average: u64, sample: u64) -> Result<u64> {
+        let weighted = math::checked_add(
+            math::checked_mul(average as u128, (ROLLING_WINDOW - 1) as u128)?,
+            sample as u128,
+        )?;
+        math::checked_as_u64(math::checked_div(weighted, ROLLING_WINDOW as u128)?)
+    }
+
+    pub fn rolling_signed(average: i64, sample: i64) -> Result<i64> {
+        let weighted = math::checked_add(
+            math::checked_mul(average as i128, (ROLLING_WINDOW - 1) as i128)?,
+            sample as i128,
+        )?;
+        i64::try_from(math::checked_div(weighted, ROLLING_WINDOW as i128)?)
+            .map_err(|_| error!(TradingBotError::InvalidCalculation))
+    }
+}
+
+// The prices along one fill, quote per base in PRICE_PRECISION: the oracle mid when the strategy
+// decided to trade, the oracle mid when the swap landed, the price the venue quoted and the
+// price the fill realized
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct FillPrices {
+    pub side: TradeSide,
+    pub decision_mid: u64,
+    pub execution_mid: u64,
+    pub quoted_price: u64,
+    pub realized_price: u64,
+}
+
+// Splits what a fill cost against the decision mid, in bps of it, positive when against the
+// trade: the mid moving before the swap landed, the venue quoting away from that mid, and the
+// fill realizing worse than the quote. The three add up to the realized price's distance from
+// the decision mid.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SlippageAttribution {
+    pub latency_bps: i64,
+    pub spread_bps: i64,
+    pub impact_bps: i64,
+}
+
+impl SlippageAttribution {
+    // How far `price` is from `reference`, in bps of it, positive when worse for `side`
+    fn adverse_bps(side: TradeSide, reference: u64, price: u64) -> Result<i64> {
+        let diff = math::checked_sub(price as i128, reference as i128)?;
+        let diff = if side.is_buy() { diff } else { -diff };
+        let bps = math::checked_div(math::checked_mul(diff, 10000)?, reference as i128)?;
+        i64::try_from(bps).map_err(|_| error!(TradingBotError::InvalidCalculation))
+    }
+
+    pub fn from_prices(prices: &FillPrices) -> Result<Self> {
+        require!(
+            prices.decision_mid > 0
+                && prices.execution_mid > 0
+                && prices.quoted_price > 0
+                && prices.realized_price > 0,
+            TradingBotError::InvalidCalculation
+        );
+        let to_execution =
+            Self::adverse_bps(prices.side, prices.decision_mid, prices.execution_mid)?;
+        let to_quote = Self::adverse_bps(prices.side, prices.decision_mid, prices.quoted_price)?;
+        let to_fill = Self::adverse_bps(prices.side, prices.decision_mid, prices.realized_price)?;
+        Ok(Self {
+            latency_bps: to_execution,
+            spread_bps: math::checked_sub(to_quote, to_execution)?,
+            impact_bps: math::checked_sub(to_fill, to_quote)?,
+        })
+    }
+}
+
+// Past this, a venue is dropped from routing until the owner re-enables it. Zero disables a limit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct VenueBanThresholds {
+    pub max_avg_slippage_bps: u64,
+    pub max_avg_deviation_bps: u64,
+    pub max_failure_rate_bps: u64,
+    // a venue isn't judged on fewer attempts than this
+    pub min_samples: u64,
+}
+
+// Per-venue execution quality for an owner's strategies. Failed swaps revert on-chain, so
+// outcomes are reported by the owner's designated keeper.
+#[account]
+#[derive(InitSpace)]
+pub struct ExecutionMetrics {
+    pub owner: Pubkey,
+    pub reporter: Pubkey,
+    pub thresholds: VenueBanThresholds,
+    // indexed by DexType
+    pub venues: [VenueStats; DexType::COUNT],
+    pub bump: u8,
+}
+
+impl ExecutionMetrics {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn is_enabled(&self, dex: DexType) -> bool {
+        !self.venues[dex.index()].banned
+    }
+
+    // Returns true when this execution gets the venue banned
+    pub fn record_execution(
+        &mut self,
+        dex: DexType,
+        success: bool,
+        slippage_bps: u64,
+        deviation_bps: u64,
+        now: i64,
+    ) -> Result<bool> {
+        let thresholds = self.thresholds;
+        let stats = &mut self.venues[dex.index()];
+        stats.attempts = math::checked_add(stats.attempts, 1)?;
+        stats.failure_rate_bps =
+            VenueStats::rolling(stats.failure_rate_bps, if success { 0 } else { 10000 })?;
+        if success {
+            stats.avg_slippage_bps = VenueStats::rolling(stats.avg_slippage_bps, slippage_bps)?;
+            stats.avg_deviation_bps = VenueStats::rolling(stats.avg_deviation_bps, deviation_bps)?;
+        } else {
+            stats.failures = math::checked_add(stats.failures, 1)?;
+        }
+
+        if stats.banned || stats.attempts < thresholds.min_samples {
+            return Ok(false);
+        }
+        let exceeds = |value: u64, limit: u64| limit > 0 && value > limit;
+        if exceeds(stats.avg_slippage_bps, thresholds.max_avg_slippage_bps)
+            || exceeds(stats.avg_deviation_bps, thresholds.max_avg_deviation_bps)
+            || exceeds(stats.failure_rate_bps, thresholds.max_failure_rate_bps)
+        {
+            stats.banned = true;
+            stats.banned_at = now;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    pub fn record_attribution(
+        &mut self,
+        dex: DexType,
+        attribution: &SlippageAttribution,
+    ) -> Result<()> {
+        let stats = &mut self.venues[dex.index()];
+        let first = stats.attributed_fills == 0;
+        let roll = |average: i64, sample: i64| {
+            if first {
+                Ok(sample)
+            } else {
+                VenueStats::rolling_signed(average, sample)
+            }
+        };
+        stats.avg_latency_bps = roll(stats.avg_latency_bps, attribution.latency_bps)?;
+        stats.avg_spread_bps = roll(stats.avg_spread_bps, attribution.spread_bps)?;
+        stats.avg_impact_bps = roll(stats.avg_impact_bps, attribution.impact_bps)?;
+        stats.attributed_fills = math::checked_add(stats.attributed_fills, 1)?;
+        Ok(())
+    }
+
+    // Re-enabled venues start from clean stats so the old averages can't re-ban them at once
+    pub fn reenable(&mut self, dex: DexType) {
+        self.venues[dex.index()] = VenueStats::default();
+    }
+}
@@ -0,0 +1,109 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+
+pub const MAX_FEE_KEEPERS: usize = 8;
+pub const FEE_PERCENTILES: usize = 3;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum FeePercentile {
+    P50,
+    P75,
+    P90,
+}
+
+impl FeePercentile {
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// A keeper's latest view of recent priority fees, in micro-lamports per compute unit, indexed
+// by FeePercentile
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FeeObservation {
+    pub keeper: Pubkey,
+    pub fees: [u64; FEE_PERCENTILES],
+    pub slot: u64,
+    pub posted_at: i64,
+}
+
+// Recent priority fees posted by registered keepers. Readers take the median of each keeper's
+// latest observation that is at most `max_age` old, so no single keeper can move the price.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeOracle {
+    pub authority: Pubkey,
+    pub observations: [FeeObservation; MAX_FEE_KEEPERS],
+    pub keeper_count: u8,
+    pub max_age: i64,
+    pub bump: u8,
+}
+
+impl FeeOracle {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn keeper_index(&self, keeper: &Pubkey) -> Option<usize> {
+        self.observations[..self.keeper_count as usize]
+            .iter()
+            .position(|observation| observation.keeper == *keeper)
+    }
+
+    pub fn add_keeper(&mut self, keeper: Pubkey) -> Result<()> {
+        let count = self.keeper_count as usize;
+        require!(
+            count < MAX_FEE_KEEPERS && self.keeper_index(&keeper).is_none(),
+            TradingBotError::InvalidFeeOracle
+        );
+        self.observations[count] = FeeObservation {
+            keeper,
+            ..Default::default()
+        };
+        self.keeper_count += 1;
+        Ok(())
+    }
+
+    // The last keeper takes the removed one's slot
+    pub fn remove_keeper(&mut self, keeper: &Pubkey) -> Result<()> {
+        let index = self
+            .keeper_index(keeper)
+            .ok_or(TradingBotError::NotAFeeKeeper)?;
+        let last = self.keeper_count as usize - 1;
+        self.observations[index] = self.observations[last];
+        self.observations[last] = FeeObservation::default();
+        self.keeper_count -= 1;
+        Ok(())
+    }
+
+    // Median of the fresh observations at `percentile`, with how many there were
+    pub fn median_fee(&self, percentile: FeePercentile, now: i64) -> (u64, u8) {
+        let mut fees: Vec<u64> = self.observations[..self.keeper_count as usize]
+            .iter()
+            .filter(|observation| {
+                observation.posted_at > 0
+                    && now.saturating_sub(observation.posted_at) <= self.max_age
+            })
+            .map(|observation| observation.fees[percentile.index()])
+            .collect();
+        if fees.is_empty() {
+            return (0, 0);
+        }
+        fees.sort_unstable();
+        (fees[fees.len() / 2], fees.len() as u8)
+    }
+
+    // Most a keeper should be reimbursed in priority fees for `compute_units` at `percentile`
+    pub fn max_priority_fee(
+        &self,
+        percentile: FeePercentile,
+        compute_units: u64,
+        now: i64,
+    ) -> Result<u64> {
+        let (fee, observations) = self.median_fee(percentile, now);
+        require_gt!(observations, 0, TradingBotError::StaleFeeOracle);
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(fee as u128, compute_units as u128)?,
+            1_000_000,
+        )?)
+    }
+}
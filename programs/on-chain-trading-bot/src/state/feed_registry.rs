@@ -0,0 +1,158 @@
+use crate::errors::TradingBotError;
+use crate::oracles::stake_pool::StakePoolSource;
+use anchor_lang::prelude::*;
+
+pub const MAX_REGISTERED_FEEDS: usize = 32;
+
+// The feeds approved to price one mint, in the quote its pairs trade against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct RegisteredFeed {
+    pub mint: Pubkey,
+    pub pyth_feed: Pubkey,
+    // recorded for off-chain readers, default when there is none; prices are only read from Pyth
+    pub switchboard_feed: Pubkey,
+    pub max_staleness: i64,
+    // widest confidence interval accepted, in bps of the price
+    pub max_confidence_bps: u16,
+    // exponent `pyth_feed` publishes at; a read at any other exponent is refused rather than
+    // misread by orders of magnitude
+    pub expo: i32,
+    // the mint's decimals, which turn the feed's price per whole token into one per native unit
+    pub decimals: u8,
+    // set for an LST, whose price is `pyth_feed`'s SOL price times the pool's exchange rate
+    // rather than whatever a thin DEX pool quotes for it
+    pub stake_pool: Option<StakePoolSource>,
+}
+
+impl RegisteredFeed {
+    pub fn validate(&self) -> Result<()> {
+        require_keys_neq!(
+            self.pyth_feed,
+            Pubkey::default(),
+            TradingBotError::InvalidRegisteredFeed
+        );
+        require_gt!(
+            self.max_staleness,
+            0,
+            TradingBotError::InvalidRegisteredFeed
+        );
+        require!(
+            self.max_confidence_bps > 0 && self.max_confidence_bps <= 10000,
+            TradingBotError::InvalidRegisteredFeed
+        );
+        require!(
+            (-18..=0).contains(&self.expo),
+            TradingBotError::InvalidRegisteredFeed
+        );
+        if let Some(stake_pool) = self.stake_pool {
+            require_keys_neq!(
+                stake_pool.pool,
+                Pubkey::default(),
+                TradingBotError::InvalidRegisteredFeed
+            );
+        }
+        Ok(())
+    }
+
+    pub fn check_expo(&self, expo: i32) -> Result<()> {
+        require_eq!(expo, self.expo, TradingBotError::UnexpectedPriceExponent);
+        Ok(())
+    }
+}
+
+// The program-wide mint -> feed map every price read resolves its feed through, so an
+// instruction can't be handed a feed for another asset. Kept by the admin, which starts as the
+// program's upgrade authority.
+#[account]
+#[derive(InitSpace)]
+pub struct FeedRegistry {
+    pub admin: Pubkey,
+    // MAX_REGISTERED_FEEDS; InitSpace only takes a literal
+    #[max_len(32)]
+    pub feeds: Vec<RegisteredFeed>,
+    pub bump: u8,
+}
+
+impl FeedRegistry {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // The entry for `mint`, provided `price_feed` is the feed registered for it
+    pub fn resolve(&self, mint: &Pubkey, price_feed: &Pubkey) -> Result<&RegisteredFeed> {
+        let feed = self
+            .feeds
+            .iter()
+            .find(|feed| feed.mint == *mint)
+            .ok_or(TradingBotError::UnregisteredMint)?;
+        require_keys_eq!(
+            *price_feed,
+            feed.pyth_feed,
+            TradingBotError::PriceFeedMismatch
+        );
+        Ok(feed)
+    }
+
+    // Decimals of a registered mint; a pair's quote has to be registered for its price to be
+    // read in native units
+    pub fn decimals(&self, mint: &Pubkey) -> Result<u8> {
+        self.feeds
+            .iter()
+            .find(|feed| feed.mint == *mint)
+            .map(|feed| feed.decimals)
+            .ok_or_else(|| TradingBotError::UnregisteredMint.into())
+    }
+
+    // The stake pool an LST mint is valued through
+    pub fn stake_pool(&self, mint: &Pubkey) -> Result<StakePoolSource> {
+        self.feeds
+            .iter()
+            .find(|feed| feed.mint == *mint)
+            .ok_or(TradingBotError::UnregisteredMint)?
+            .stake_pool
+            .ok_or_else(|| TradingBotError::InvalidStakePool.into())
+    }
+
+    // Replaces the mint's entry, or adds one while there is room
+    pub fn set(&mut self, feed: RegisteredFeed) -> Result<()> {
+        match self.feeds.iter_mut().find(|entry| entry.mint == feed.mint) {
+            Some(entry) => *entry = feed,
+            None => {
+                require_gt!(
+                    MAX_REGISTERED_FEEDS,
+                    self.feeds.len(),
+                    TradingBotError::FeedRegistryFull
+                );
+                self.feeds.push(feed);
+            }
+        }
+        Ok(())
+    }
+
+    // Every entry is one set_registered_feed would accept, and no mint has two
+    pub fn check_integrity(&self) -> Result<()> {
+        require_gte!(
+            MAX_REGISTERED_FEEDS,
+            self.feeds.len(),
+            TradingBotError::InvalidRegisteredFeed
+        );
+        for (index, feed) in self.feeds.iter().enumerate() {
+            feed.validate()?;
+            require!(
+                self.feeds[..index]
+                    .iter()
+                    .all(|other| other.mint != feed.mint),
+                TradingBotError::InvalidRegisteredFeed
+            );
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, mint: &Pubkey) -> Result<()> {
+        let index = self
+            .feeds
+            .iter()
+            .position(|feed| feed.mint == *mint)
+            .ok_or(TradingBotError::UnregisteredMint)?;
+        self.feeds.swap_remove(index);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::constants::PRICE_PRECISION;
+use crate::dex::serum_events::FillEvent;
+use crate::math;
+use crate::state::MarketMaker;
+use anchor_lang::prelude::*;
+
+// One resting quote and how much of it has filled
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct QuoteFillState {
+    pub client_order_id: u64,
+    pub quoted_price: u64,
+    pub size: u64,
+    pub filled_base: u64,
+    pub filled_quote: u64,
+}
+
+impl QuoteFillState {
+    pub fn is_filled(&self) -> bool {
+        self.size > 0 && self.filled_base >= self.size
+    }
+}
+
+// Fills read straight off the market's event queue for a market maker's open orders, with the
+// slippage of each against the price it was quoted at
+#[account]
+#[derive(InitSpace)]
+pub struct FillTracker {
+    pub market_maker: Pubkey,
+    // event queue sequence number to resume from
+    pub next_event_seq: u64,
+    pub bid: QuoteFillState,
+    pub ask: QuoteFillState,
+    pub fill_count: u64,
+    pub base_bought: u64,
+    pub base_sold: u64,
+    pub quote_spent: u64,
+    pub quote_received: u64,
+    // quote units lost (positive) or gained (negative) against the quoted prices
+    pub realized_slippage: i64,
+    // fees paid net of maker rebates
+    pub net_fees: i64,
+    // fills of quotes replaced before they were consumed; no quoted price is known for them
+    pub unmatched_fills: u64,
+    pub bump: u8,
+}
+
+impl FillTracker {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Starts tracking the market maker's current pair once it has requoted
+    pub fn track_quotes(&mut self, market_maker: &MarketMaker) {
+        if self.bid.client_order_id != market_maker.bid_client_id() {
+            self.bid = QuoteFillState {
+                client_order_id: market_maker.bid_client_id(),
+                quoted_price: market_maker.last_bid_price,
+                size: market_maker.order_size,
+                ..Default::default()
+            };
+        }
+        if self.ask.client_order_id != market_maker.ask_client_id() {
+            self.ask = QuoteFillState {
+                client_order_id: market_maker.ask_client_id(),
+                quoted_price: market_maker.last_ask_price,
+                size: market_maker.order_size,
+                ..Default::default()
+            };
+        }
+    }
+
+    // Books a fill and returns its slippage in bps against the quote, if the quote is known
+    pub fn apply_fill(&mut self, fill: &FillEvent) -> Result<Option<i64>> {
+        let (base, quote) = if fill.is_bid {
+            self.base_bought = math::checked_add(self.base_bought, fill.native_qty_received)?;
+            self.quote_spent = math::checked_add(self.quote_spent, fill.native_qty_paid)?;
+            (fill.native_qty_received, fill.native_qty_paid)
+        } else {
+            self.base_sold = math::checked_add(self.base_sold, fill.native_qty_paid)?;
+            self.quote_received = math::checked_add(self.quote_received, fill.native_qty_received)?;
+            (fill.native_qty_paid, fill.native_qty_received)
+        };
+        let fee = fill.native_fee_or_rebate as i64;
+        self.net_fees = math::checked_add(self.net_fees, if fill.is_maker { -fee } else { fee })?;
+        self.fill_count = math::checked_add(self.fill_count, 1)?;
+        self.next_event_seq = fill.seq + 1;
+
+        let quote_state = if fill.is_bid {
+            &mut self.bid
+        } else {
+            &mut self.ask
+        };
+        if quote_state.client_order_id != fill.client_order_id || base == 0 {
+            self.unmatched_fills = math::checked_add(self.unmatched_fills, 1)?;
+            return Ok(None);
+        }
+        quote_state.filled_base = math::checked_add(quote_state.filled_base, base)?;
+        quote_state.filled_quote = math::checked_add(quote_state.filled_quote, quote)?;
+
+        let quoted_price = quote_state.quoted_price as i128;
+        let intended_quote = math::checked_div(
+            math::checked_mul(base as i128, quoted_price)?,
+            PRICE_PRECISION as i128,
+        )?;
+        // buying above or selling below the quote is a cost
+        let cost = if fill.is_bid {
+            math::checked_sub(quote as i128, intended_quote)?
+        } else {
+            math::checked_sub(intended_quote, quote as i128)?
+        };
+        self.realized_slippage = math::checked_add(self.realized_slippage, cost as i64)?;
+        if intended_quote == 0 {
+            return Ok(None);
+        }
+        let slippage_bps = math::checked_div(math::checked_mul(cost, 10000)?, intended_quote)?;
+        Ok(Some(slippage_bps as i64))
+    }
+}
@@ -0,0 +1,413 @@
+use crate::constants::PRICE_PRECISION;
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+
+pub const MAX_INDEX_COMPONENTS: usize = 8;
+// monthly reconstitution
+pub const DEFAULT_RECONSTITUTION_INTERVAL: i64 = 30 * 24 * 3600;
+pub const MAX_EARLY_EXIT_FEE_BPS: u16 = 1000;
+// what emergency liquidations may lose to slippage, above the usual MAX_SLIPPAGE_BPS
+pub const MAX_EMERGENCY_SLIPPAGE_BPS: u16 = 2500;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum WeightRule {
+    // each component's `static_weight_bps`
+    Static,
+    // oracle price times mint supply, each capped at the component's `max_weight_bps`
+    MarketCap,
+}
+
+// Limits checked on every deposit, in USD with USD_DECIMALS; zero leaves a limit off
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct DepositLimits {
+    // NAV the fund may grow to through deposits
+    pub max_tvl: u64,
+    pub min_deposit: u64,
+    // what one wallet may have deposited, net of what it withdrew
+    pub max_per_wallet: u64,
+    // only wallets the owner allowed may deposit
+    pub allowlist_enforced: bool,
+}
+
+impl DepositLimits {
+    // `deposited` is what the wallet already has in, net of withdrawals
+    pub fn check(&self, value: u64, nav: u64, deposited: u64, allowed: bool) -> Result<()> {
+        require_gte!(
+            value,
+            self.min_deposit,
+            TradingBotError::DepositBelowMinimum
+        );
+        if self.max_tvl > 0 {
+            require_gte!(
+                self.max_tvl,
+                math::checked_add(nav, value)?,
+                TradingBotError::FundCapacityExceeded
+            );
+        }
+        if self.max_per_wallet > 0 {
+            require_gte!(
+                self.max_per_wallet,
+                math::checked_add(deposited, value)?,
+                TradingBotError::WalletLimitExceeded
+            );
+        }
+        require!(
+            allowed || !self.allowlist_enforced,
+            TradingBotError::DepositorNotAllowed
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct IndexComponent {
+    pub trading_vault: Pubkey,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub static_weight_bps: u16,
+    pub max_weight_bps: u16,
+    // weight set at the last reconstitution
+    pub target_bps: u16,
+}
+
+// An index over the owner's trading vaults: a base vault holding the fund's cash and up to
+// MAX_INDEX_COMPONENTS component vaults. Targets are reset every `reconstitution_interval` from
+// the weight rule, after which keepers have `rebalance_window` to trade the components back to
+// them through the base asset. Deposits and withdrawals are in the base asset and priced in index
+// units at the fund's oracle NAV, so the NAV per unit tracks the index. Units are held as tokens
+// of `share_mint`, which the fund's PDA mints on deposit and burns when a withdrawal is requested.
+#[account]
+#[derive(InitSpace)]
+pub struct IndexFund {
+    pub owner: Pubkey,
+    pub base_vault: Pubkey,
+    // Token-2022 mint of the fund's units
+    pub share_mint: Pubkey,
+    // shares can only be minted and burned, not transferred
+    pub non_transferable: bool,
+    pub weight_rule: WeightRule,
+    pub components: [IndexComponent; MAX_INDEX_COMPONENTS],
+    pub component_count: u8,
+    pub reconstitution_interval: i64,
+    pub rebalance_window: i64,
+    pub last_reconstitution: i64,
+    // end of the current rebalance window
+    pub rebalance_until: i64,
+    // how far, in bps of NAV, a component may sit from its target before it's rebalanced
+    pub drift_bps: u16,
+    pub max_slippage_bps: u16,
+    pub limits: DepositLimits,
+    // how long each deposit locks the wallet's shares, and the fee for withdrawing before then
+    pub lockup_period: i64,
+    pub early_exit_fee_bps: u16,
+    // set by the owner when the normal paths are broken: deposits and rebalancing stop, the owner
+    // sells components at market within `emergency_slippage_bps`, and holders take base cash
+    pub emergency: bool,
+    pub emergency_slippage_bps: u16,
+    // share supply plus the burned units still waiting to be paid out
+    pub units: u64,
+    // units holders asked to withdraw, whose value rebalancing raises as base cash
+    pub pending_withdrawal_units: u64,
+    // NAV per unit in PRICE_PRECISION after the last deposit, withdrawal or rebalance, which
+    // post_upgrade_check holds the units to
+    pub nav_per_unit: u64,
+    pub reconstitutions: u32,
+    // creation order, which post_upgrade_check walks
+    pub seq: u32,
+    pub bump: u8,
+}
+
+impl IndexFund {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn components(&self) -> &[IndexComponent] {
+        &self.components[..self.component_count as usize]
+    }
+
+    pub fn add_component(&mut self, component: IndexComponent) -> Result<()> {
+        let count = self.component_count as usize;
+        require!(count < MAX_INDEX_COMPONENTS, TradingBotError::IndexFull);
+        require!(
+            self.components().iter().all(|c| c.mint != component.mint),
+            TradingBotError::InvalidIndexComponent
+        );
+        let static_total = self
+            .components()
+            .iter()
+            .try_fold(component.static_weight_bps as u64, |total, c| {
+                math::checked_add(total, c.static_weight_bps as u64)
+            })?;
+        require_gte!(10000, static_total, TradingBotError::InvalidIndexParams);
+        self.components[count] = component;
+        self.component_count += 1;
+        Ok(())
+    }
+
+    // Sets the targets from the weight rule; `market_caps` are in USD, one per component, and
+    // ignored for static weights. Weight cut by a cap is left in base cash.
+    pub fn reconstitute(&mut self, market_caps: &[u64], now: i64) -> Result<()> {
+        let count = self.component_count as usize;
+        match self.weight_rule {
+            WeightRule::Static => {
+                for component in self.components[..count].iter_mut() {
+                    component.target_bps = component.static_weight_bps;
+                }
+            }
+            WeightRule::MarketCap => {
+                require_eq!(
+                    market_caps.len(),
+                    count,
+                    TradingBotError::InvalidIndexComponent
+                );
+                let total = market_caps
+                    .iter()
+                    .try_fold(0u128, |total, cap| math::checked_add(total, *cap as u128))?;
+                require_gt!(total, 0, TradingBotError::InvalidCalculation);
+                for (component, cap) in self.components[..count].iter_mut().zip(market_caps) {
+                    let weight = math::checked_div(math::checked_mul(*cap as u128, 10000)?, total)?;
+                    component.target_bps = (weight as u16).min(component.max_weight_bps);
+                }
+            }
+        }
+        self.last_reconstitution = now;
+        self.reconstitutions = math::checked_add(self.reconstitutions, 1)?;
+        self.open_window(now)
+    }
+
+    pub fn reconstitution_due(&self, now: i64) -> bool {
+        now.saturating_sub(self.last_reconstitution) >= self.reconstitution_interval
+    }
+
+    // Flows and reconstitutions give keepers a window to trade the fund back to its targets
+    pub fn open_window(&mut self, now: i64) -> Result<()> {
+        self.rebalance_until = math::checked_add(now, self.rebalance_window)?;
+        Ok(())
+    }
+
+    // NAV left invested once pending withdrawals are paid out
+    pub fn investable(&self, nav: u64) -> Result<u64> {
+        if self.units == 0 {
+            return Ok(nav);
+        }
+        let kept = math::checked_sub(self.units, self.pending_withdrawal_units)?;
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(nav as u128, kept as u128)?,
+            self.units as u128,
+        )?)
+    }
+
+    // How far component `index`, worth `value`, sits over (positive) or under its target, in USD
+    pub fn deviation(&self, index: usize, value: u64, nav: u64) -> Result<i128> {
+        let target = math::checked_div(
+            math::checked_mul(
+                self.investable(nav)? as u128,
+                self.components[index].target_bps as u128,
+            )?,
+            10000,
+        )?;
+        Ok(value as i128 - target as i128)
+    }
+
+    // A rebalance has to bring the component closer to its target, and may only overshoot it to
+    // within the drift tolerance
+    pub fn check_rebalanced(&self, before: i128, after: i128, nav: u64) -> Result<()> {
+        let tolerance = self.tolerance(nav)? as u128;
+        require_gt!(
+            before.unsigned_abs(),
+            tolerance,
+            TradingBotError::IndexBalanced
+        );
+        require_gt!(
+            before.unsigned_abs(),
+            after.unsigned_abs(),
+            TradingBotError::RebalanceNotCloser
+        );
+        require!(
+            before.signum() == after.signum() || after.unsigned_abs() <= tolerance,
+            TradingBotError::RebalanceNotCloser
+        );
+        Ok(())
+    }
+
+    pub fn tolerance(&self, nav: u64) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(nav as u128, self.drift_bps as u128)?,
+            10000,
+        )?)
+    }
+
+    // Units for a deposit worth `value` into a fund worth `nav`; the first deposit sets one unit
+    // to one USD unit
+    pub fn units_for(&self, value: u64, nav: u64) -> Result<u64> {
+        if self.units == 0 || nav == 0 {
+            return Ok(value);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(value as u128, self.units as u128)?,
+            nav as u128,
+        )?)
+    }
+
+    // Units of a withdrawal request kept as the early-exit fee. They're cancelled rather than paid
+    // out, so their value goes to the units that remain.
+    pub fn exit_fee(&self, units: u64, depositor: &IndexDepositor, now: i64) -> Result<u64> {
+        if now >= depositor.locked_until {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(units as u128, depositor.exit_fee_bps as u128)?,
+            10000,
+        )?)
+    }
+
+    // Share of `amount` that `units` are owed, without going through the oracles
+    pub fn pro_rata(&self, units: u64, amount: u64) -> Result<u64> {
+        require_gt!(self.units, 0, TradingBotError::InvalidCalculation);
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(units as u128, amount as u128)?,
+            self.units as u128,
+        )?)
+    }
+
+    // Zero while the fund has no units
+    pub fn nav_per_unit_at(&self, nav: u64) -> Result<u64> {
+        if self.units == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(nav as u128, PRICE_PRECISION as u128)?,
+            self.units as u128,
+        )?)
+    }
+
+    // Called once `units` reflect the flow or trade that left the fund worth `nav`
+    pub fn mark_nav(&mut self, nav: u64) -> Result<()> {
+        self.nav_per_unit = self.nav_per_unit_at(nav)?;
+        Ok(())
+    }
+
+    // A fund worth `nav` has to price its units within `tolerance_bps` of the last mark, so units
+    // or holdings a deploy corrupted show up as a jump. Funds without a mark yet pass.
+    pub fn check_nav(&self, nav: u64, tolerance_bps: u16) -> Result<()> {
+        if self.nav_per_unit == 0 {
+            return Ok(());
+        }
+        let drift = self.nav_per_unit_at(nav)?.abs_diff(self.nav_per_unit);
+        require_gte!(
+            math::checked_mul(self.nav_per_unit as u128, tolerance_bps as u128)?,
+            math::checked_mul(drift as u128, 10000)?,
+            TradingBotError::NavOutOfTolerance
+        );
+        Ok(())
+    }
+
+    pub fn value_of(&self, units: u64, nav: u64) -> Result<u64> {
+        require_gt!(self.units, 0, TradingBotError::InvalidCalculation);
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(units as u128, nav as u128)?,
+            self.units as u128,
+        )?)
+    }
+}
+
+// A wallet's standing with a fund: whether the owner allowed it to deposit, the USD value it
+// deposited net of what it withdrew, which the per-wallet limit applies to, and the lock-up from
+// its last deposit. Shares moved between wallets don't count toward either.
+#[account]
+#[derive(InitSpace)]
+pub struct IndexDepositor {
+    pub fund: Pubkey,
+    pub wallet: Pubkey,
+    pub allowed: bool,
+    pub deposited: u64,
+    // the fund's lock-up terms when the wallet last deposited
+    pub locked_until: i64,
+    pub exit_fee_bps: u16,
+    pub bump: u8,
+}
+
+impl IndexDepositor {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+// A holder's requested withdrawal: units burned from their shares and paid out by withdraw_index
+#[account]
+#[derive(InitSpace)]
+pub struct IndexRedemption {
+    pub fund: Pubkey,
+    pub holder: Pubkey,
+    pub units: u64,
+    pub bump: u8,
+}
+
+impl IndexRedemption {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1_000 units last marked at 1.0 each
+    fn fund() -> IndexFund {
+        IndexFund {
+            owner: Pubkey::new_unique(),
+            base_vault: Pubkey::new_unique(),
+            share_mint: Pubkey::new_unique(),
+            non_transferable: false,
+            weight_rule: WeightRule::Static,
+            components: Default::default(),
+            component_count: 0,
+            reconstitution_interval: DEFAULT_RECONSTITUTION_INTERVAL,
+            rebalance_window: 3600,
+            last_reconstitution: 0,
+            rebalance_until: 0,
+            drift_bps: 500,
+            max_slippage_bps: 100,
+            limits: DepositLimits::default(),
+            lockup_period: 0,
+            early_exit_fee_bps: 0,
+            emergency: false,
+            emergency_slippage_bps: 0,
+            units: 1_000,
+            pending_withdrawal_units: 0,
+            nav_per_unit: PRICE_PRECISION,
+            reconstitutions: 0,
+            seq: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn nav_within_tolerance_of_the_mark_passes() {
+        let fund = fund();
+        fund.check_nav(1_000, 0).unwrap();
+        fund.check_nav(1_050, 500).unwrap();
+        fund.check_nav(950, 500).unwrap();
+        assert!(fund.check_nav(1_051, 500).is_err());
+        assert!(fund.check_nav(949, 500).is_err());
+    }
+
+    #[test]
+    fn units_out_of_line_with_nav_fail() {
+        let mut fund = fund();
+        // a deploy that doubled the units halves what each is worth
+        fund.units = 2_000;
+        assert!(fund.check_nav(1_000, 2000).is_err());
+    }
+
+    #[test]
+    fn marks_follow_flows() {
+        let mut fund = fund();
+        fund.units = 1_500;
+        fund.mark_nav(1_800).unwrap();
+        assert_eq!(fund.nav_per_unit, 1_200_000);
+
+        // a fund with no units has no mark to hold it to
+        fund.units = 0;
+        fund.mark_nav(0).unwrap();
+        fund.check_nav(5_000, 0).unwrap();
+    }
+}
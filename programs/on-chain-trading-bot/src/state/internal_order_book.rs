@@ -0,0 +1,60 @@
+use crate::errors::TradingBotError;
+use anchor_lang::prelude::*;
+
+pub const MAX_BOOK_ORDERS: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct BookEntry {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub is_buy: bool,
+    pub limit_price: u64,
+}
+
+// Limit orders on one pair whose owners opted in to being crossed by other vaults before those
+// route to a DEX. The orders keep their own reservations; the book only lists them so takers
+// know which are eligible.
+#[account]
+#[derive(InitSpace)]
+pub struct InternalOrderBook {
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub entries: [BookEntry; MAX_BOOK_ORDERS],
+    pub count: u8,
+    pub bump: u8,
+}
+
+impl InternalOrderBook {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn entry_index(&self, order: &Pubkey) -> Option<usize> {
+        self.entries[..self.count as usize]
+            .iter()
+            .position(|entry| entry.order == *order)
+    }
+
+    pub fn list(&mut self, entry: BookEntry) -> Result<()> {
+        let count = self.count as usize;
+        require!(count < MAX_BOOK_ORDERS, TradingBotError::OrderBookFull);
+        require!(
+            self.entry_index(&entry.order).is_none(),
+            TradingBotError::InvalidOrderParams
+        );
+        self.entries[count] = entry;
+        self.count += 1;
+        Ok(())
+    }
+
+    // The last entry takes the removed one's slot
+    pub fn unlist(&mut self, order: &Pubkey) -> Result<BookEntry> {
+        let index = self
+            .entry_index(order)
+            .ok_or(TradingBotError::OrderNotListed)?;
+        let entry = self.entries[index];
+        let last = self.count as usize - 1;
+        self.entries[index] = self.entries[last];
+        self.entries[last] = BookEntry::default();
+        self.count -= 1;
+        Ok(entry)
+    }
+}
@@ -0,0 +1,223 @@
+use crate::constants::KEEPER_SLASH_BPS;
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+
+pub const MAX_OPEN_VIOLATIONS: usize = 4;
+
+// A swap the keeper ran with a minimum out below the oracle floor at the strategy's slippage,
+// written by the program as it happened so the owner can slash on it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct KeeperViolation {
+    pub strategy: Pubkey,
+    pub minimum_out: u64,
+    pub oracle_min_out: u64,
+    pub slot: u64,
+}
+
+// A keeper's bond, in lamports this account holds above rent, and its record across every
+// strategy it executes. A keeper opens an attempt in a transaction of its own before each
+// execution, so one that reverts still leaves the attempt behind; it counts as reverted once the
+// next attempt supersedes it.
+#[account]
+#[derive(InitSpace)]
+pub struct KeeperRecord {
+    pub keeper: Pubkey,
+    pub bond: u64,
+    pub executions: u64,
+    pub attempts: u64,
+    pub reverted: u64,
+    // strategy of the attempt awaiting its execution, default when none is open
+    pub open_attempt: Pubkey,
+    pub open_attempt_slot: u64,
+    // seconds past eligibility, summed over executions of strategies with an interval
+    pub total_delay: u64,
+    pub delay_samples: u64,
+    pub violations: [KeeperViolation; MAX_OPEN_VIOLATIONS],
+    pub violation_count: u8,
+    pub slash_count: u32,
+    pub slashed: u64,
+    // zero until the keeper asks for its bond back
+    pub exit_requested_at: i64,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl KeeperRecord {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Executions over attempts that have resolved; an open attempt counts for neither
+    pub fn success_rate_bps(&self) -> Result<u64> {
+        let resolved = math::checked_add(self.executions, self.reverted)?;
+        if resolved == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.executions as u128, 10000)?,
+            resolved as u128,
+        )?)
+    }
+
+    pub fn open_attempt(&mut self, strategy: Pubkey, slot: u64) -> Result<()> {
+        if self.open_attempt != Pubkey::default() {
+            self.reverted = math::checked_add(self.reverted, 1)?;
+        }
+        self.attempts = math::checked_add(self.attempts, 1)?;
+        self.open_attempt = strategy;
+        self.open_attempt_slot = slot;
+        Ok(())
+    }
+
+    // An attempt opened in the execution's own slot could have been rolled back with it
+    pub fn check_attempt(&self, strategy: &Pubkey, slot: u64) -> Result<()> {
+        require!(
+            self.open_attempt == *strategy && self.open_attempt_slot < slot,
+            TradingBotError::KeeperAttemptNotOpen
+        );
+        Ok(())
+    }
+
+    pub fn avg_delay(&self) -> u64 {
+        self.total_delay
+            .checked_div(self.delay_samples)
+            .unwrap_or(0)
+    }
+
+    pub fn record_execution(&mut self, delay: Option<u64>) -> Result<()> {
+        self.executions = math::checked_add(self.executions, 1)?;
+        self.open_attempt = Pubkey::default();
+        if let Some(delay) = delay {
+            self.total_delay = math::checked_add(self.total_delay, delay)?;
+            self.delay_samples = math::checked_add(self.delay_samples, 1)?;
+        }
+        Ok(())
+    }
+
+    // A keeper with every slot taken can't execute again until owners slash what is open
+    pub fn record_violation(&mut self, violation: KeeperViolation) -> Result<()> {
+        let count = self.violation_count as usize;
+        require!(
+            count < MAX_OPEN_VIOLATIONS,
+            TradingBotError::KeeperViolationsOutstanding
+        );
+        self.violations[count] = violation;
+        self.violation_count += 1;
+        Ok(())
+    }
+
+    // Removes the oldest open violation on `strategy`; the last one takes its slot
+    pub fn take_violation(&mut self, strategy: &Pubkey) -> Result<KeeperViolation> {
+        let count = self.violation_count as usize;
+        let index = self.violations[..count]
+            .iter()
+            .position(|violation| violation.strategy == *strategy)
+            .ok_or(TradingBotError::KeeperViolationNotFound)?;
+        let violation = self.violations[index];
+        self.violations[index] = self.violations[count - 1];
+        self.violations[count - 1] = KeeperViolation::default();
+        self.violation_count -= 1;
+        Ok(violation)
+    }
+
+    pub fn slash_amount(&self) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.bond as u128, KEEPER_SLASH_BPS as u128)?,
+            10000,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(bond: u64) -> KeeperRecord {
+        KeeperRecord {
+            keeper: Pubkey::new_unique(),
+            bond,
+            executions: 0,
+            attempts: 0,
+            reverted: 0,
+            open_attempt: Pubkey::default(),
+            open_attempt_slot: 0,
+            total_delay: 0,
+            delay_samples: 0,
+            violations: [KeeperViolation::default(); MAX_OPEN_VIOLATIONS],
+            violation_count: 0,
+            slash_count: 0,
+            slashed: 0,
+            exit_requested_at: 0,
+            registered_at: 0,
+            bump: 255,
+        }
+    }
+
+    fn violation(strategy: Pubkey, slot: u64) -> KeeperViolation {
+        KeeperViolation {
+            strategy,
+            minimum_out: 900,
+            oracle_min_out: 990,
+            slot,
+        }
+    }
+
+    #[test]
+    fn slash_takes_ten_percent_of_the_bond() {
+        assert_eq!(record(2_000_000_000).slash_amount().unwrap(), 200_000_000);
+        assert_eq!(record(9).slash_amount().unwrap(), 0);
+    }
+
+    #[test]
+    fn violations_fill_up_and_are_taken_per_strategy() {
+        let mut record = record(1_000_000_000);
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        record.record_violation(violation(a, 1)).unwrap();
+        record.record_violation(violation(b, 2)).unwrap();
+        record.record_violation(violation(a, 3)).unwrap();
+        record.record_violation(violation(b, 4)).unwrap();
+        assert!(record.record_violation(violation(a, 5)).is_err());
+
+        // the oldest on the strategy goes first and the last open one takes its slot
+        assert_eq!(record.take_violation(&a).unwrap().slot, 1);
+        assert_eq!(record.violations[0].slot, 4);
+        assert_eq!(record.take_violation(&a).unwrap().slot, 3);
+        assert!(record.take_violation(&a).is_err());
+        assert_eq!(record.violation_count, 2);
+        record.record_violation(violation(a, 5)).unwrap();
+    }
+
+    #[test]
+    fn superseded_attempts_count_as_reverted() {
+        let mut record = record(1_000_000_000);
+        let strategy = Pubkey::new_unique();
+        assert_eq!(record.success_rate_bps().unwrap(), 0);
+
+        record.open_attempt(strategy, 10).unwrap();
+        record.check_attempt(&strategy, 11).unwrap();
+        record.record_execution(None).unwrap();
+        assert!(record.check_attempt(&strategy, 12).is_err());
+
+        // an attempt whose execution reverted is left open until the next one
+        record.open_attempt(strategy, 20).unwrap();
+        record.open_attempt(strategy, 30).unwrap();
+        assert_eq!((record.attempts, record.reverted), (3, 1));
+        record.record_execution(Some(4)).unwrap();
+        assert_eq!(record.success_rate_bps().unwrap(), 6666);
+        assert_eq!(record.avg_delay(), 4);
+
+        // still open, so neither a success nor a revert yet
+        record.open_attempt(strategy, 40).unwrap();
+        assert_eq!(record.success_rate_bps().unwrap(), 6666);
+    }
+
+    #[test]
+    fn attempt_must_precede_the_execution_on_the_same_strategy() {
+        let mut record = record(1_000_000_000);
+        let strategy = Pubkey::new_unique();
+        assert!(record.check_attempt(&strategy, 5).is_err());
+        record.open_attempt(strategy, 5).unwrap();
+        assert!(record.check_attempt(&strategy, 5).is_err());
+        assert!(record.check_attempt(&Pubkey::new_unique(), 6).is_err());
+        record.check_attempt(&strategy, 6).unwrap();
+    }
+}
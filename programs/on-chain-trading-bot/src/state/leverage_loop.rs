@@ -0,0 +1,96 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::oracles::lending_obligation::ObligationHealth;
+use anchor_lang::prelude::*;
+
+// A leveraged loop on one of the owner's vaults: its Solend obligation is levered up by depositing
+// collateral, borrowing against it and swapping the borrow back into collateral until it reaches
+// `target_ltv_bps`, and unwound the other way. Every pass has to leave the obligation at least
+// `min_health_bps` away from liquidation, and levering up is held to the strategy's leverage cap.
+#[account]
+#[derive(InitSpace)]
+pub struct LeverageLoop {
+    pub owner: Pubkey,
+    // the owner's strategy whose risk parameters cap the loop's leverage
+    pub strategy: Pubkey,
+    pub trading_vault: Pubkey,
+    // owned by the vault's PDA, which signs the lending CPIs
+    pub obligation: Pubkey,
+    pub target_ltv_bps: u16,
+    pub min_health_bps: u16,
+    // value a pass may lose between the debt moved and the collateral it buys or frees
+    pub max_slippage_bps: u16,
+    pub loops: u32,
+    pub unwinds: u32,
+    pub bump: u8,
+}
+
+impl LeverageLoop {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn require_healthy(&self, health: &ObligationHealth) -> Result<()> {
+        require_gte!(
+            health.health_bps()?,
+            self.min_health_bps as u64,
+            TradingBotError::ObligationUnhealthy
+        );
+        Ok(())
+    }
+
+    // Levering up: the new debt has to have ended up as collateral, less slippage, within the
+    // target LTV and the strategy's leverage cap
+    pub fn check_loop(
+        &self,
+        before: &ObligationHealth,
+        after: &ObligationHealth,
+        max_leverage_bps: u32,
+    ) -> Result<()> {
+        let borrowed = math::checked_sub(after.borrowed_value, before.borrowed_value)?;
+        let deposited = math::checked_sub(after.deposited_value, before.deposited_value)?;
+        require_gt!(borrowed, 0, TradingBotError::InvalidCalculation);
+        require_gte!(
+            deposited,
+            self.less_slippage(borrowed)?,
+            TradingBotError::SlippageExceeded
+        );
+        require_gte!(
+            self.target_ltv_bps as u64,
+            after.ltv_bps()?,
+            TradingBotError::LeverageLimitExceeded
+        );
+        if max_leverage_bps > 0 {
+            require_gte!(
+                max_leverage_bps as u64,
+                after.leverage_bps()?,
+                TradingBotError::LeverageExceeded
+            );
+        }
+        self.require_healthy(after)
+    }
+
+    // Unwinding: the collateral withdrawn has to have gone to repaying debt, less slippage, and
+    // the obligation can't end up closer to liquidation than it started
+    pub fn check_unwind(&self, before: &ObligationHealth, after: &ObligationHealth) -> Result<()> {
+        let repaid = math::checked_sub(before.borrowed_value, after.borrowed_value)?;
+        let withdrawn = math::checked_sub(before.deposited_value, after.deposited_value)?;
+        require_gt!(repaid, 0, TradingBotError::InvalidCalculation);
+        require_gte!(
+            repaid,
+            self.less_slippage(withdrawn)?,
+            TradingBotError::SlippageExceeded
+        );
+        require_gte!(
+            after.health_bps()?,
+            before.health_bps()?.min(self.min_health_bps as u64),
+            TradingBotError::ObligationUnhealthy
+        );
+        Ok(())
+    }
+
+    fn less_slippage(&self, value: u128) -> Result<u128> {
+        math::checked_div(
+            math::checked_mul(value, (10_000 - self.max_slippage_bps) as u128)?,
+            10_000,
+        )
+    }
+}
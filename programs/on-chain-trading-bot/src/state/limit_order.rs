@@ -0,0 +1,135 @@
+use crate::constants::PRICE_PRECISION;
+use crate::math;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum OrderStatus {
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Expired,
+}
+
+impl OrderStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Expired
+        )
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct LimitOrder {
+    pub idx: u64,
+    pub owner: Pubkey,
+    pub trading_vault: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub is_buy: bool,
+    pub limit_price: u64,
+    pub size: u64,
+    pub reserved: u64,
+    pub filled_size: u64,
+    pub filled_quote: u64,
+    pub avg_fill_price: u64,
+    pub fill_count: u32,
+    pub status: OrderStatus,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl LimitOrder {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Zero expiry means good-til-cancelled
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at > 0 && now >= self.expires_at
+    }
+
+    pub fn remaining_size(&self) -> u64 {
+        self.size.saturating_sub(self.filled_size)
+    }
+
+    // Buys reserve quote, sells reserve base
+    pub fn reserve_mint(&self) -> Pubkey {
+        if self.is_buy {
+            self.quote_mint
+        } else {
+            self.base_mint
+        }
+    }
+
+    pub fn payment_mint(&self) -> Pubkey {
+        if self.is_buy {
+            self.base_mint
+        } else {
+            self.quote_mint
+        }
+    }
+
+    pub fn reserve_amount(is_buy: bool, size: u64, limit_price: u64) -> Result<u64> {
+        if is_buy {
+            Self::quote_amount(size, limit_price)
+        } else {
+            Ok(size)
+        }
+    }
+
+    pub fn quote_amount(size: u64, price: u64) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(size as u128, price as u128)?,
+            PRICE_PRECISION as u128,
+        )?)
+    }
+
+    // What a fill moves in quote. A buy spends it out of the vault rounded down and a sell is paid
+    // it rounded up, so the rounding never goes the keeper's way.
+    pub fn fill_quote(&self, fill_size: u64, fill_price: u64) -> Result<u64> {
+        if self.is_buy {
+            return Self::quote_amount(fill_size, fill_price);
+        }
+        math::checked_as_u64(math::checked_ceil_div(
+            math::checked_mul(fill_size as u128, fill_price as u128)?,
+            PRICE_PRECISION as u128,
+        )?)
+    }
+
+    // Whether a fill's quote is within `band_bps` of what the oracles put on its size, either way
+    pub fn within_oracle_band(fill_quote: u64, oracle_quote: u64, band_bps: u16) -> Result<bool> {
+        let deviation = fill_quote.abs_diff(oracle_quote);
+        Ok(math::checked_mul(deviation as u128, 10_000)?
+            <= math::checked_mul(oracle_quote as u128, band_bps as u128)?)
+    }
+
+    pub fn is_marketable(&self, price: u64) -> bool {
+        if self.is_buy {
+            price <= self.limit_price
+        } else {
+            price >= self.limit_price
+        }
+    }
+
+    // Open -> PartiallyFilled -> Filled, with the volume-weighted average price kept per fill
+    pub fn apply_fill(&mut self, fill_size: u64, fill_quote: u64, now: i64) -> Result<()> {
+        self.filled_size = math::checked_add(self.filled_size, fill_size)?;
+        self.filled_quote = math::checked_add(self.filled_quote, fill_quote)?;
+        self.avg_fill_price = math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.filled_quote as u128, PRICE_PRECISION as u128)?,
+            self.filled_size as u128,
+        )?)?;
+        self.fill_count = math::checked_add(self.fill_count, 1)?;
+        self.status = if self.remaining_size() == 0 {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        self.updated_at = now;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use crate::math;
+
+#[account]
+#[derive(InitSpace)]
+pub struct LiquidationAuction {
+    pub idx: u64,
+    pub owner: Pubkey,
+    // the strategy a forced exit sells out of, default for the owner's own auctions
+    pub strategy: Pubkey,
+    pub lot_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    // where the lot came from and goes back to on cancel, and where takes pay into
+    pub lot_account: Pubkey,
+    pub proceeds_account: Pubkey,
+    pub reason: ForcedExitReason,
+    pub initial_amount: u64,
+    pub remaining_amount: u64,
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub start_at: i64,
+    pub duration: i64,
+    pub proceeds: u64,
+    pub bump: u8,
+}
+
+impl LiquidationAuction {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Linear decay from the oracle mid at start down to the floor, then flat
+    pub fn current_price(&self, now: i64) -> Result<u64> {
+        let elapsed = now.saturating_sub(self.start_at).max(0);
+        if elapsed >= self.duration {
+            return Ok(self.floor_price);
+        }
+
+        let range = math::checked_sub(self.start_price, self.floor_price)?;
+        let decay = math::checked_div(
+            math::checked_mul(range as u128, elapsed as u128)?,
+            self.duration as u128,
+        )?;
+
+        math::checked_sub(self.start_price, math::checked_as_u64(decay)?)
+    }
+
+    // The owner can always call an auction off; anyone else only a forced exit whose strategy is
+    // back within its loss limit
+    pub fn can_cancel(&self, caller: &Pubkey, strategy_breached: Option<bool>) -> bool {
+        *caller == self.owner
+            || (self.strategy != Pubkey::default() && strategy_breached == Some(false))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ForcedExitReason {
+    StrategyClosed,
+    RiskBreach,
+    Orphaned,
+}
+
+#[macro_export]
+macro_rules! liquidation_auction_seeds {
+    ( $auction:expr, $idx_bytes:expr ) => {
+        &[
+            LIQUIDATION_AUCTION_SEED,
+            $auction.owner.as_ref(),
+            $idx_bytes.as_ref(),
+            &[$auction.bump],
+        ]
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forced() -> LiquidationAuction {
+        LiquidationAuction {
+            idx: 0,
+            owner: Pubkey::new_unique(),
+            strategy: Pubkey::new_unique(),
+            lot_mint: Pubkey::new_unique(),
+            quote_mint: Pubkey::new_unique(),
+            lot_account: Pubkey::new_unique(),
+            proceeds_account: Pubkey::new_unique(),
+            reason: ForcedExitReason::RiskBreach,
+            initial_amount: 1_000,
+            remaining_amount: 1_000,
+            start_price: 150_000_000,
+            floor_price: 142_500_000,
+            start_at: 1_700_000_000,
+            duration: 3600,
+            proceeds: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn owner_cancels_any_auction() {
+        let auction = forced();
+        assert!(auction.can_cancel(&auction.owner, None));
+        assert!(auction.can_cancel(&auction.owner, Some(true)));
+    }
+
+    #[test]
+    fn others_cancel_only_a_recovered_forced_exit() {
+        let auction = forced();
+        let keeper = Pubkey::new_unique();
+        assert!(!auction.can_cancel(&keeper, Some(true)));
+        assert!(!auction.can_cancel(&keeper, None));
+        assert!(auction.can_cancel(&keeper, Some(false)));
+
+        // the owner's own auctions have no strategy to recover
+        let own = LiquidationAuction {
+            strategy: Pubkey::default(),
+            ..forced()
+        };
+        assert!(!own.can_cancel(&keeper, Some(false)));
+    }
+
+    #[test]
+    fn price_decays_to_the_floor() {
+        let auction = forced();
+        assert_eq!(auction.current_price(auction.start_at).unwrap(), 150_000_000);
+        assert_eq!(auction.current_price(auction.start_at + 1800).unwrap(), 146_250_000);
+        assert_eq!(auction.current_price(auction.start_at + 7200).unwrap(), 142_500_000);
+    }
+}
@@ -0,0 +1,223 @@
+use anchor_lang::prelude::*;
+use crate::math;
+use crate::types::StrategyType;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MarketMaker {
+    pub owner: Pubkey,
+    pub strategy_type: StrategyType,
+    pub market: Pubkey,
+    pub variant: u16,
+    pub template: Pubkey,
+    pub open_orders: Pubkey,
+    pub price_feed: Pubkey,
+    pub base_wallet: Pubkey,
+    pub quote_wallet: Pubkey,
+    pub spread_bps: u16,
+    pub requote_threshold_bps: u16,
+    pub max_skew_bps: u16,
+    pub order_size: u64,
+    pub target_inventory: u64,
+    pub max_inventory_deviation: u64,
+    pub max_confidence: u64,
+    pub last_quote_mid: u64,
+    pub last_bid_price: u64,
+    pub last_ask_price: u64,
+    pub last_quote_at: i64,
+    pub quote_ttl: i64,
+    pub quote_expires_at: i64,
+    pub quote_seq: u64,
+    pub inventory_band: u64,
+    pub hedge_venue: HedgeVenue,
+    pub perp_position: i64,
+    pub perp_entry_price: u64,
+    pub initial_nav: u64,
+    // the initial NAV plus deposits less withdrawals through deposit_market_maker_funds and
+    // withdraw_market_maker_funds, at the oracle mid of the time
+    pub net_contributions: i64,
+    pub hedge_count: u64,
+    pub hedge_volume: u64,
+    pub hedge_cost: i64,
+    // venue fees inside hedge_cost, in native quote
+    pub hedge_fees: u64,
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+impl MarketMaker {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const TEMPLATE_VARIANT: u16 = 0;
+    // client ids carry the variant above the quote sequence and side
+    const CLIENT_ID_VARIANT_SHIFT: u32 = 48;
+
+    pub fn params(&self) -> MarketMakerParams {
+        MarketMakerParams {
+            spread_bps: self.spread_bps,
+            requote_threshold_bps: self.requote_threshold_bps,
+            max_skew_bps: self.max_skew_bps,
+            order_size: self.order_size,
+            target_inventory: self.target_inventory,
+            max_inventory_deviation: self.max_inventory_deviation,
+            max_confidence: self.max_confidence,
+            inventory_band: self.inventory_band,
+            hedge_venue: self.hedge_venue,
+            quote_ttl: self.quote_ttl,
+        }
+    }
+
+    pub fn apply_params(&mut self, params: &MarketMakerParams) {
+        self.spread_bps = params.spread_bps;
+        self.requote_threshold_bps = params.requote_threshold_bps;
+        self.max_skew_bps = params.max_skew_bps;
+        self.order_size = params.order_size;
+        self.target_inventory = params.target_inventory;
+        self.max_inventory_deviation = params.max_inventory_deviation;
+        self.max_confidence = params.max_confidence;
+        self.inventory_band = params.inventory_band;
+        self.hedge_venue = params.hedge_venue;
+        self.quote_ttl = params.quote_ttl;
+    }
+
+    // Client order ids are derived from the quote sequence so the previous pair can be cancelled,
+    // and from the variant so no two variants of a market maker ever quote under the same id
+    pub fn client_id(variant: u16, quote_seq: u64, is_ask: bool) -> u64 {
+        ((variant as u64) << Self::CLIENT_ID_VARIANT_SHIFT)
+            | ((quote_seq << 1) & ((1 << Self::CLIENT_ID_VARIANT_SHIFT) - 1))
+            | is_ask as u64
+    }
+
+    pub fn bid_client_id(&self) -> u64 {
+        Self::client_id(self.variant, self.quote_seq, false)
+    }
+
+    pub fn ask_client_id(&self) -> u64 {
+        Self::client_id(self.variant, self.quote_seq, true)
+    }
+
+    // Resting quotes past their good-til-time must be pulled before they get picked off
+    pub fn quotes_expired(&self, now: i64) -> bool {
+        self.last_quote_mid > 0 && self.quote_expires_at > 0 && now >= self.quote_expires_at
+    }
+
+    // Spot inventory plus any perp hedge, relative to the target
+    pub fn net_deviation(&self, spot_inventory: u64) -> i128 {
+        spot_inventory as i128 + self.perp_position as i128 - self.target_inventory as i128
+    }
+
+    // Spot inventory and the perp hedge both count, whichever way they face
+    pub fn gross_exposure(&self, spot_inventory: u64) -> u128 {
+        spot_inventory as u128 + self.perp_position.unsigned_abs() as u128
+    }
+
+    // Average entry is kept while the perp grows, untouched while it shrinks, reset when it flips
+    pub fn apply_perp_fill(&mut self, signed_size: i64, price: u64) -> Result<()> {
+        let old_position = self.perp_position;
+        let new_position = math::checked_add(old_position, signed_size)?;
+
+        if old_position == 0 || (old_position > 0) != (new_position > 0) {
+            self.perp_entry_price = price;
+        } else if new_position.unsigned_abs() > old_position.unsigned_abs() {
+            let old_notional = math::checked_mul(
+                old_position.unsigned_abs() as u128,
+                self.perp_entry_price as u128,
+            )?;
+            let added_notional =
+                math::checked_mul(signed_size.unsigned_abs() as u128, price as u128)?;
+            self.perp_entry_price = math::checked_as_u64(math::checked_div(
+                math::checked_add(old_notional, added_notional)?,
+                new_position.unsigned_abs() as u128,
+            )?)?;
+        }
+
+        self.perp_position = new_position;
+        if new_position == 0 {
+            self.perp_entry_price = 0;
+        }
+
+        Ok(())
+    }
+}
+
+// Held for each open orders account and wallet a market maker quotes from, so no two market makers
+// or variants share one
+#[account]
+#[derive(InitSpace)]
+pub struct MarketMakerClaim {
+    pub market_maker: Pubkey,
+    pub bump: u8,
+}
+
+impl MarketMakerClaim {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn claim(&mut self, market_maker: Pubkey, bump: u8) {
+        self.market_maker = market_maker;
+        self.bump = bump;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MarketMakerParams {
+    pub spread_bps: u16,
+    pub requote_threshold_bps: u16,
+    pub max_skew_bps: u16,
+    pub order_size: u64,
+    pub target_inventory: u64,
+    pub max_inventory_deviation: u64,
+    pub max_confidence: u64,
+    pub inventory_band: u64,
+    pub hedge_venue: HedgeVenue,
+    pub quote_ttl: i64,
+}
+
+// Parameters a clone changes relative to its template; unset fields are inherited
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct MarketMakerOverrides {
+    pub spread_bps: Option<u16>,
+    pub requote_threshold_bps: Option<u16>,
+    pub max_skew_bps: Option<u16>,
+    pub order_size: Option<u64>,
+    pub target_inventory: Option<u64>,
+    pub max_inventory_deviation: Option<u64>,
+    pub inventory_band: Option<u64>,
+    pub quote_ttl: Option<i64>,
+}
+
+impl MarketMakerOverrides {
+    pub fn apply(&self, params: &mut MarketMakerParams) {
+        params.spread_bps = self.spread_bps.unwrap_or(params.spread_bps);
+        params.requote_threshold_bps = self
+            .requote_threshold_bps
+            .unwrap_or(params.requote_threshold_bps);
+        params.max_skew_bps = self.max_skew_bps.unwrap_or(params.max_skew_bps);
+        params.order_size = self.order_size.unwrap_or(params.order_size);
+        params.target_inventory = self.target_inventory.unwrap_or(params.target_inventory);
+        params.max_inventory_deviation = self
+            .max_inventory_deviation
+            .unwrap_or(params.max_inventory_deviation);
+        params.inventory_band = self.inventory_band.unwrap_or(params.inventory_band);
+        params.quote_ttl = self.quote_ttl.unwrap_or(params.quote_ttl);
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum HedgeVenue {
+    Raydium,
+    Jupiter,
+    // the Drift perp market the hedge is booked on
+    DriftPerp { market_index: u16 },
+}
+
+#[macro_export]
+macro_rules! market_maker_seeds {
+    ( $market_maker:expr, $variant_bytes:expr ) => {
+        &[
+            MARKET_MAKER_SEED,
+            $market_maker.owner.as_ref(),
+            $market_maker.market.as_ref(),
+            $variant_bytes.as_ref(),
+            &[$market_maker.bump],
+        ]
+    };
+}
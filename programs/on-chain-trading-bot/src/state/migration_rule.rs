@@ -0,0 +1,72 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::types::TokenPair;
+use anchor_lang::prelude::*;
+
+pub const MAX_MIGRATION_POOLS: usize = 4;
+const DAY: i64 = 24 * 3600;
+
+// Moves liquidity out of a pool whose realized fee APR has sat below `apr_floor_bps` for
+// `min_streak` consecutive samples, into the best-yielding of the other pools on the same pair.
+// At most `daily_cap` moves per day.
+#[account]
+#[derive(InitSpace)]
+pub struct MigrationRule {
+    pub owner: Pubkey,
+    pub keeper: Pubkey,
+    pub pair: TokenPair,
+    // pool samplers of the candidate pools
+    pub samplers: [Pubkey; MAX_MIGRATION_POOLS],
+    pub sampler_count: u8,
+    pub apr_floor_bps: u64,
+    pub min_streak: u8,
+    pub daily_cap: u64,
+    pub day_start: i64,
+    pub migrated_today: u64,
+    pub migration_count: u32,
+    pub bump: u8,
+}
+
+impl MigrationRule {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn samplers(&self) -> &[Pubkey] {
+        &self.samplers[..self.sampler_count as usize]
+    }
+
+    pub fn remaining_today(&self, now: i64) -> u64 {
+        if now >= self.day_start.saturating_add(DAY) {
+            self.daily_cap
+        } else {
+            self.daily_cap.saturating_sub(self.migrated_today)
+        }
+    }
+
+    pub fn record_migration(&mut self, amount: u64, now: i64) -> Result<()> {
+        require_gte!(
+            self.remaining_today(now),
+            amount,
+            TradingBotError::MigrationCapExceeded
+        );
+        if now >= self.day_start.saturating_add(DAY) {
+            self.day_start = now;
+            self.migrated_today = 0;
+        }
+        self.migrated_today = math::checked_add(self.migrated_today, amount)?;
+        self.migration_count = math::checked_add(self.migration_count, 1)?;
+        Ok(())
+    }
+}
+
+#[macro_export]
+macro_rules! migration_rule_seeds {
+    ( $rule:expr ) => {
+        &[
+            MIGRATION_RULE_SEED,
+            $rule.owner.as_ref(),
+            $rule.pair.base_mint.as_ref(),
+            $rule.pair.quote_mint.as_ref(),
+            &[$rule.bump],
+        ]
+    };
+}
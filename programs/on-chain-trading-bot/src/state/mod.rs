@@ -1,3 +1,101 @@
+pub mod bridge_transfer;
+pub mod callback;
+pub mod compute_profile;
+pub mod correlation_matrix;
+pub mod covered_call;
+pub mod depeg_guard;
+pub mod devnet;
+pub mod epoch_history;
 pub mod escrow;
+pub mod execution_auction;
+pub mod execution_metrics;
+pub mod fee_oracle;
+pub mod feed_registry;
+pub mod fill_tracker;
+pub mod index_fund;
+pub mod internal_order_book;
+pub mod keeper;
+pub mod leverage_loop;
+pub mod limit_order;
+pub mod liquidation_auction;
+pub mod market_maker;
+pub mod migration_rule;
+pub mod nav_feed;
+pub mod otc_offer;
+pub mod outbox;
+pub mod page_cursor;
+pub mod peg_arb;
+pub mod pool_registry;
+pub mod pool_sampler;
+pub mod portfolio;
+pub mod position_stop;
+pub mod price_band;
+pub mod principal_note;
+pub mod public_profile;
+pub mod quote_lock;
+pub mod recovery_config;
+pub mod recurring_transfer;
+pub mod regime_switch;
+pub mod rewards_distributor;
+pub mod session;
+pub mod strategy;
+pub mod subscription;
+pub mod token_launch;
+pub mod trade_approval;
+pub mod trade_history;
+pub mod trade_mining;
+pub mod trading_vault;
+pub mod treasury_plan;
+pub mod upgrade_guard;
+pub mod vault_log;
 
-pub use escrow::*;
\ No newline at end of file
+pub use bridge_transfer::*;
+pub use callback::*;
+pub use compute_profile::*;
+pub use correlation_matrix::*;
+pub use covered_call::*;
+pub use depeg_guard::*;
+pub use devnet::*;
+pub use epoch_history::*;
+pub use escrow::*;
+pub use execution_auction::*;
+pub use execution_metrics::*;
+pub use fee_oracle::*;
+pub use feed_registry::*;
+pub use fill_tracker::*;
+pub use index_fund::*;
+pub use internal_order_book::*;
+pub use keeper::*;
+pub use leverage_loop::*;
+pub use limit_order::*;
+pub use liquidation_auction::*;
+pub use market_maker::*;
+pub use migration_rule::*;
+pub use nav_feed::*;
+pub use otc_offer::*;
+pub use outbox::*;
+pub use page_cursor::*;
+pub use peg_arb::*;
+pub use pool_registry::*;
+pub use pool_sampler::*;
+pub use portfolio::*;
+pub use position_stop::*;
+pub use price_band::*;
+pub use principal_note::*;
+pub use public_profile::*;
+pub use quote_lock::*;
+pub use recovery_config::*;
+pub use recurring_transfer::*;
+pub use regime_switch::*;
+pub use rewards_distributor::*;
+pub use session::*;
+pub use strategy::*;
+pub use subscription::*;
+pub use token_launch::*;
+pub use trade_approval::*;
+pub use trade_history::*;
+pub use trade_mining::*;
+pub use trading_vault::*;
+pub use treasury_plan::*;
+pub use upgrade_guard::*;
+pub use vault_log::*;
\ No newline at end of file
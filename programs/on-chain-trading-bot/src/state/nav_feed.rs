@@ -0,0 +1,42 @@
+use crate::constants::{PRICE_PRECISION, USD_DECIMALS};
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+
+// An index fund's NAV per unit, republished by anyone from the oracles and kept in a fixed layout
+// other programs can read without this program's types: after the 8-byte discriminator, `fund`
+// at 8, `price` at 40, `expo` at 48, `nav` at 52, `units` at 60, `publish_time` at 68 and
+// `publish_slot` at 76. Consumers should hold `publish_time` to their own staleness limit.
+#[account]
+#[derive(InitSpace)]
+pub struct NavFeed {
+    pub fund: Pubkey,
+    // USD per unit as price * 10^expo, units counted with USD_DECIMALS decimals
+    pub price: u64,
+    pub expo: i32,
+    // USD with USD_DECIMALS
+    pub nav: u64,
+    pub units: u64,
+    pub publish_time: i64,
+    pub publish_slot: u64,
+    pub bump: u8,
+}
+
+impl NavFeed {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const EXPO: i32 = -(USD_DECIMALS as i32);
+
+    pub fn publish(&mut self, nav: u64, units: u64, clock: &Clock) -> Result<()> {
+        require_gt!(units, 0, TradingBotError::NoFundUnits);
+        self.price = math::checked_as_u64(math::checked_div(
+            math::checked_mul(nav as u128, PRICE_PRECISION as u128)?,
+            units as u128,
+        )?)?;
+        self.expo = Self::EXPO;
+        self.nav = nav;
+        self.units = units;
+        self.publish_time = clock.unix_timestamp;
+        self.publish_slot = clock.slot;
+        Ok(())
+    }
+}
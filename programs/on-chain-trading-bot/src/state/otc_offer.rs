@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct OtcOffer {
+    pub idx: u64,
+    pub maker: Pubkey,
+    pub taker: Option<Pubkey>,
+    pub offer_mint: Pubkey,
+    pub ask_mint: Pubkey,
+    pub offer_amount: u64,
+    pub ask_amount: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl OtcOffer {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+#[macro_export]
+macro_rules! otc_offer_seeds {
+    ( $offer:expr, $idx_bytes:expr ) => {
+        &[
+            OTC_OFFER_SEED,
+            $offer.maker.as_ref(),
+            $idx_bytes.as_ref(),
+            &[$offer.bump],
+        ]
+    };
+}
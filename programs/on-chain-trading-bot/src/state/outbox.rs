@@ -0,0 +1,77 @@
+use crate::events::NotificationPosted;
+use crate::math;
+use anchor_lang::prelude::*;
+
+pub const OUTBOX_CAPACITY: usize = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum NotificationKind {
+    // unwritten slot
+    #[default]
+    None,
+    TradeExecuted,
+    StopHit,
+    RiskBreach,
+    KeeperFailure,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct Notification {
+    pub seq: u64,
+    pub kind: NotificationKind,
+    pub timestamp: i64,
+    pub subject: Pubkey,
+    pub value: u64,
+}
+
+// Fixed-size ring of the latest notifications for one strategy. Sequence numbers never reset, so a
+// poller that sees a jump of more than one past its last seq knows records were overwritten.
+#[account]
+#[derive(InitSpace)]
+pub struct Outbox {
+    pub strategy: Pubkey,
+    pub owner: Pubkey,
+    pub next_seq: u64,
+    pub head: u8,
+    pub records: [Notification; OUTBOX_CAPACITY],
+    pub bump: u8,
+}
+
+impl Outbox {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn push(
+        &mut self,
+        kind: NotificationKind,
+        subject: Pubkey,
+        value: u64,
+        timestamp: i64,
+    ) -> Result<u64> {
+        let record = Notification {
+            seq: self.next_seq,
+            kind,
+            timestamp,
+            subject,
+            value,
+        };
+        self.records[self.head as usize] = record;
+        self.head = ((self.head as usize + 1) % OUTBOX_CAPACITY) as u8;
+        self.next_seq = math::checked_add(self.next_seq, 1)?;
+
+        emit!(NotificationPosted {
+            strategy: self.strategy,
+            seq: record.seq,
+            kind,
+            timestamp,
+            subject,
+            value,
+        });
+
+        Ok(record.seq)
+    }
+
+    // Oldest sequence number still held in the ring
+    pub fn oldest_seq(&self) -> u64 {
+        self.next_seq.saturating_sub(OUTBOX_CAPACITY as u64)
+    }
+}
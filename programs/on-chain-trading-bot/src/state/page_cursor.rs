@@ -0,0 +1,47 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+use std::ops::Range;
+
+// Where a paged crank over a large collection resumes, so the work can be spread over as many
+// transactions as it takes. Each call names the index it starts at, which must be where the last
+// call stopped, or zero to start a new pass; two cranks racing for the same page can't both run.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PageCursor {
+    pub next_index: u64,
+    pub updated_at: i64,
+}
+
+impl PageCursor {
+    pub fn page(&self, start_index: u64, max_items: u16) -> Result<Range<u64>> {
+        require_gt!(max_items, 0, TradingBotError::InvalidPageSize);
+        require!(
+            start_index == 0 || start_index == self.next_index,
+            TradingBotError::StaleCursor
+        );
+        Ok(start_index..math::checked_add(start_index, max_items as u64)?)
+    }
+
+    // Rewinds to zero once `end` reaches the collection's length, where it is known
+    pub fn advance(&mut self, end: u64, len: Option<u64>, now: i64) {
+        self.next_index = if len.map_or(false, |len| end >= len) {
+            0
+        } else {
+            end
+        };
+        self.updated_at = now;
+    }
+}
+
+// Per-owner cursor of the expire_orders crank, walking the owner's limit orders by index
+#[account]
+#[derive(InitSpace)]
+pub struct ExpiryCursor {
+    pub owner: Pubkey,
+    pub cursor: PageCursor,
+    pub bump: u8,
+}
+
+impl ExpiryCursor {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
@@ -0,0 +1,95 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::oracles::stake_pool::ExchangeRate;
+use anchor_lang::prelude::*;
+
+// epochs an unstake waits before its SOL can be claimed: a stake account deactivates, and a
+// Marinade ticket matures, at the next epoch boundary
+pub const UNBONDING_EPOCHS: u64 = 1;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PegArbStatus {
+    // all in SOL, waiting for a discount
+    Idle,
+    // holds LST bought below its redemption rate
+    Holding,
+    // the LST was unstaked and its SOL waits out the unbonding period
+    Unbonding,
+}
+
+// Preset pairing the owner's SOL vault with an LST vault: keepers buy the LST once it trades at
+// least `entry_discount_bps` below its stake pool's redemption rate, and exit either by selling
+// once the discount has closed to `exit_discount_bps` or by unstaking at the rate. The position
+// is accounted in lamports, with an unstake's SOL counted but locked until `claimable_epoch`.
+#[account]
+#[derive(InitSpace)]
+pub struct PegArb {
+    pub owner: Pubkey,
+    pub sol_vault: Pubkey,
+    pub lst_vault: Pubkey,
+    pub entry_discount_bps: u16,
+    pub exit_discount_bps: u16,
+    pub status: PegArbStatus,
+    // SOL paid for the position and the LST it bought
+    pub cost: u64,
+    pub position: u64,
+    // SOL an unstake delivers once claimed, and the first epoch it can be
+    pub unbonding_lamports: u64,
+    pub claimable_epoch: u64,
+    // lamports made or lost over the closed rounds
+    pub realized_pnl: i64,
+    pub round_count: u32,
+    pub bump: u8,
+}
+
+impl PegArb {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Discount in bps of `paid` SOL against what the LST bought redeems for
+    pub fn discount_bps(paid: u64, redeemable: u64) -> Result<u64> {
+        if redeemable <= paid {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul((redeemable - paid) as u128, 10_000)?,
+            redeemable as u128,
+        )?)
+    }
+
+    // SOL the position is worth: the held LST at the redemption rate, or the unbonding SOL
+    pub fn value(&self, rate: &ExchangeRate) -> Result<u64> {
+        match self.status {
+            PegArbStatus::Idle => Ok(0),
+            PegArbStatus::Holding => rate.apply(self.position),
+            PegArbStatus::Unbonding => Ok(self.unbonding_lamports),
+        }
+    }
+
+    pub fn open(&mut self, paid: u64, bought: u64) {
+        self.status = PegArbStatus::Holding;
+        self.cost = paid;
+        self.position = bought;
+    }
+
+    pub fn start_unbonding(&mut self, lamports: u64, epoch: u64) -> Result<()> {
+        self.status = PegArbStatus::Unbonding;
+        self.position = 0;
+        self.unbonding_lamports = lamports;
+        self.claimable_epoch = math::checked_add(epoch, UNBONDING_EPOCHS)?;
+        Ok(())
+    }
+
+    // Books the round's SOL back against its cost and returns the profit
+    pub fn settle(&mut self, received: u64) -> Result<i64> {
+        let pnl = i64::try_from(received as i128 - self.cost as i128)
+            .map_err(|_| TradingBotError::InvalidCalculation)?;
+        self.realized_pnl = math::checked_add(self.realized_pnl, pnl)?;
+        self.round_count = math::checked_add(self.round_count, 1)?;
+        self.status = PegArbStatus::Idle;
+        self.cost = 0;
+        self.position = 0;
+        self.unbonding_lamports = 0;
+        self.claimable_epoch = 0;
+        Ok(pnl)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::errors::TradingBotError;
+use crate::types::{DexType, TokenPair};
+use anchor_lang::prelude::*;
+
+// keeps the registry inside the 10KiB an account can be created with
+pub const MAX_REGISTERED_POOLS: usize = 48;
+
+// The canonical pool or market of a pair on one venue, with the token accounts holding its
+// reserves; the reserves are default on order-book venues
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct RegisteredPool {
+    pub pair: TokenPair,
+    pub dex: DexType,
+    pub pool: Pubkey,
+    pub base_reserve: Pubkey,
+    pub quote_reserve: Pubkey,
+}
+
+impl RegisteredPool {
+    // AMM pools need both reserves, order-book markets neither
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.pair.base_mint != self.pair.quote_mint && self.pool != Pubkey::default(),
+            TradingBotError::InvalidRegisteredPool
+        );
+        let has_reserves = match self.dex {
+            DexType::Serum => false,
+            DexType::Raydium | DexType::Jupiter => true,
+        };
+        require!(
+            (self.base_reserve != Pubkey::default()) == has_reserves
+                && (self.quote_reserve != Pubkey::default()) == has_reserves,
+            TradingBotError::InvalidRegisteredPool
+        );
+        Ok(())
+    }
+
+    // Either orientation of the pair finds the pool
+    fn matches(&self, pair: &TokenPair, dex: DexType) -> bool {
+        self.dex == dex && (self.pair == *pair || self.pair == pair.inverse())
+    }
+}
+
+// The program-wide (pair, venue) -> pool map that pool and reserve accounts are checked against,
+// so a look-alike pool with skewed reserves can't steer routing, arbitrage or quotes. Kept by the
+// admin like the FeedRegistry.
+#[account]
+#[derive(InitSpace)]
+pub struct PoolRegistry {
+    pub admin: Pubkey,
+    // MAX_REGISTERED_POOLS; InitSpace only takes a literal
+    #[max_len(48)]
+    pub pools: Vec<RegisteredPool>,
+    pub bump: u8,
+}
+
+impl PoolRegistry {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn find(&self, pair: &TokenPair, dex: DexType) -> Result<&RegisteredPool> {
+        self.pools
+            .iter()
+            .find(|pool| pool.matches(pair, dex))
+            .ok_or_else(|| TradingBotError::UnregisteredPool.into())
+    }
+
+    pub fn require_pool(&self, pair: &TokenPair, dex: DexType, pool: &Pubkey) -> Result<()> {
+        require_keys_eq!(
+            *pool,
+            self.find(pair, dex)?.pool,
+            TradingBotError::PoolMismatch
+        );
+        Ok(())
+    }
+
+    // `reserve` has to hold one side of the registered pool's liquidity
+    pub fn require_reserve(&self, pair: &TokenPair, dex: DexType, reserve: &Pubkey) -> Result<()> {
+        let pool = self.find(pair, dex)?;
+        require!(
+            *reserve != Pubkey::default()
+                && (*reserve == pool.base_reserve || *reserve == pool.quote_reserve),
+            TradingBotError::PoolMismatch
+        );
+        Ok(())
+    }
+
+    // Replaces the pair's pool on the venue, or adds one while there is room
+    pub fn set(&mut self, pool: RegisteredPool) -> Result<()> {
+        match self
+            .pools
+            .iter_mut()
+            .find(|entry| entry.matches(&pool.pair, pool.dex))
+        {
+            Some(entry) => *entry = pool,
+            None => {
+                require_gt!(
+                    MAX_REGISTERED_POOLS,
+                    self.pools.len(),
+                    TradingBotError::PoolRegistryFull
+                );
+                self.pools.push(pool);
+            }
+        }
+        Ok(())
+    }
+
+    // Every entry is one set_registered_pool would accept, and no pair has two pools on a venue
+    pub fn check_integrity(&self) -> Result<()> {
+        require_gte!(
+            MAX_REGISTERED_POOLS,
+            self.pools.len(),
+            TradingBotError::InvalidRegisteredPool
+        );
+        for (index, pool) in self.pools.iter().enumerate() {
+            pool.validate()?;
+            require!(
+                self.pools[..index]
+                    .iter()
+                    .all(|other| !other.matches(&pool.pair, pool.dex)),
+                TradingBotError::InvalidRegisteredPool
+            );
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, pair: &TokenPair, dex: DexType) -> Result<()> {
+        let index = self
+            .pools
+            .iter()
+            .position(|pool| pool.matches(pair, dex))
+            .ok_or(TradingBotError::UnregisteredPool)?;
+        self.pools.swap_remove(index);
+        Ok(())
+    }
+}
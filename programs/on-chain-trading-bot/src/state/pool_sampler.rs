@@ -0,0 +1,110 @@
+use crate::constants::SECONDS_PER_YEAR;
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::types::{DexType, TokenPair};
+use anchor_lang::prelude::*;
+
+pub const POOL_SAMPLE_LEN: usize = 24;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PoolSample {
+    pub timestamp: i64,
+    // cumulative fees the pool has paid to LPs, in quote units
+    pub fee_growth: u64,
+    pub tvl: u64,
+    // pool mid, quote per base in PRICE_PRECISION
+    pub price: u64,
+    // realized APR as of this sample
+    pub apr_bps: u64,
+}
+
+// Keeper-fed history of a pool's fee growth and TVL, oldest overwritten first, from which the
+// fee APR LPs actually realized is derived
+#[account]
+#[derive(InitSpace)]
+pub struct PoolSampler {
+    pub owner: Pubkey,
+    pub keeper: Pubkey,
+    pub pool: Pubkey,
+    pub dex: DexType,
+    pub pair: TokenPair,
+    pub min_interval: i64,
+    pub samples: [PoolSample; POOL_SAMPLE_LEN],
+    pub head: u8,
+    pub count: u8,
+    pub bump: u8,
+}
+
+impl PoolSampler {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    fn sample(&self, age: usize) -> &PoolSample {
+        let idx = (self.head as usize + POOL_SAMPLE_LEN - 1 - age) % POOL_SAMPLE_LEN;
+        &self.samples[idx]
+    }
+
+    pub fn latest(&self) -> Option<&PoolSample> {
+        (self.count > 0).then(|| self.sample(0))
+    }
+
+    // Records the sample along with the APR it brings the window to
+    pub fn push(&mut self, sample: PoolSample) -> Result<()> {
+        if let Some(latest) = self.latest() {
+            require!(
+                sample.timestamp >= latest.timestamp.saturating_add(self.min_interval),
+                TradingBotError::SampleTooEarly
+            );
+            // fee growth is cumulative, a drop means a bad reading or a reset pool
+            require_gte!(
+                sample.fee_growth,
+                latest.fee_growth,
+                TradingBotError::InvalidPoolSample
+            );
+        }
+        self.samples[self.head as usize] = sample;
+        self.head = ((self.head as usize + 1) % POOL_SAMPLE_LEN) as u8;
+        if (self.count as usize) < POOL_SAMPLE_LEN {
+            self.count += 1;
+        }
+        let apr_bps = self.realized_apr_bps()?;
+        let head = (self.head as usize + POOL_SAMPLE_LEN - 1) % POOL_SAMPLE_LEN;
+        self.samples[head].apr_bps = apr_bps;
+        Ok(())
+    }
+
+    // Consecutive latest samples whose APR is below `floor_bps`. The oldest sample has no APR of
+    // its own and never counts.
+    pub fn below_floor_streak(&self, floor_bps: u64) -> u8 {
+        (0..(self.count as usize).saturating_sub(1))
+            .take_while(|age| self.sample(*age).apr_bps < floor_bps)
+            .count() as u8
+    }
+
+    // Fees earned between the oldest and newest sample over the mean TVL, annualized, in bps.
+    // Zero until there are two samples to compare.
+    pub fn realized_apr_bps(&self) -> Result<u64> {
+        if self.count < 2 {
+            return Ok(0);
+        }
+        let newest = self.sample(0);
+        let oldest = self.sample(self.count as usize - 1);
+        let elapsed = math::checked_sub(newest.timestamp, oldest.timestamp)?;
+        let tvl_sum = (0..self.count as usize).try_fold(0u128, |sum, age| {
+            math::checked_add(sum, self.sample(age).tvl as u128)
+        })?;
+        let mean_tvl = math::checked_div(tvl_sum, self.count as u128)?;
+        if elapsed <= 0 || mean_tvl == 0 {
+            return Ok(0);
+        }
+
+        let fees = math::checked_sub(newest.fee_growth, oldest.fee_growth)?;
+        let annualized = math::checked_mul(
+            math::checked_mul(fees as u128, 10000)?,
+            SECONDS_PER_YEAR as u128,
+        )?;
+        math::checked_as_u64(math::checked_div(
+            annualized,
+            math::checked_mul(mean_tvl, elapsed as u128)?,
+        )?)
+    }
+}
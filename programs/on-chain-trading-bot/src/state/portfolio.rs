@@ -0,0 +1,182 @@
+use crate::constants::PRICE_PRECISION;
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::types::{TokenPair, TradeSide};
+use crate::valuation::Valuation;
+use anchor_lang::prelude::*;
+
+pub const MAX_PORTFOLIO_STRATEGIES: usize = 8;
+
+// What a member strategy wants traded, in the side's input mint: quote for buys, base for
+// sells. Netting fills it against opposing members first; `remaining` is left for a DEX.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct NettingOrder {
+    pub pair: TokenPair,
+    pub side: TradeSide,
+    pub remaining: u64,
+    pub filled_in: u64,
+    pub filled_out: u64,
+    pub posted_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PortfolioMember {
+    pub strategy: Pubkey,
+    pub order: Option<NettingOrder>,
+}
+
+// One member's share of a cross, `amount_out` net of `fee`, both in the side's output mint
+pub struct NettedFill {
+    pub strategy: Pubkey,
+    pub side: TradeSide,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+// Strategies of one owner grouped so opposing orders on a pair cross with each other at the
+// oracle mid, less `crossing_fee_bps`, instead of both sides paying DEX costs. Members trade from
+// the owner's shared trading vaults, so a cross moves no tokens between them; only the fee
+// leaves the vaults.
+#[account]
+#[derive(InitSpace)]
+pub struct Portfolio {
+    pub owner: Pubkey,
+    pub crossing_fee_bps: u16,
+    pub members: [PortfolioMember; MAX_PORTFOLIO_STRATEGIES],
+    pub member_count: u8,
+    // quote crossed internally over the portfolio's life
+    pub crossed_volume: u64,
+    pub bump: u8,
+}
+
+impl Portfolio {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn member_index(&self, strategy: &Pubkey) -> Option<usize> {
+        self.members[..self.member_count as usize]
+            .iter()
+            .position(|member| member.strategy == *strategy)
+    }
+
+    pub fn member(&mut self, strategy: &Pubkey) -> Result<&mut PortfolioMember> {
+        let index = self
+            .member_index(strategy)
+            .ok_or(TradingBotError::NotAPortfolioMember)?;
+        Ok(&mut self.members[index])
+    }
+
+    pub fn add_strategy(&mut self, strategy: Pubkey) -> Result<()> {
+        let count = self.member_count as usize;
+        require!(
+            count < MAX_PORTFOLIO_STRATEGIES,
+            TradingBotError::PortfolioFull
+        );
+        require!(
+            self.member_index(&strategy).is_none(),
+            TradingBotError::InvalidNettingOrder
+        );
+        self.members[count] = PortfolioMember {
+            strategy,
+            order: None,
+        };
+        self.member_count += 1;
+        Ok(())
+    }
+
+    // The last member takes the removed one's slot. Its order has to be closed first so the
+    // residual is not lost track of.
+    pub fn remove_strategy(&mut self, strategy: &Pubkey) -> Result<()> {
+        let index = self
+            .member_index(strategy)
+            .ok_or(TradingBotError::NotAPortfolioMember)?;
+        require!(
+            self.members[index].order.is_none(),
+            TradingBotError::InvalidNettingOrder
+        );
+        let last = self.member_count as usize - 1;
+        self.members[index] = self.members[last];
+        self.members[last] = PortfolioMember::default();
+        self.member_count -= 1;
+        Ok(())
+    }
+
+    fn base_value(quote_amount: u64, mid: u64) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(quote_amount as u128, PRICE_PRECISION as u128)?,
+            mid as u128,
+        )?)
+    }
+
+    fn pro_rata(amount: u64, crossed: u64, total: u64) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(amount as u128, crossed as u128)?,
+            total as u128,
+        )?)
+    }
+
+    // Crosses the open orders on `pair` at `mid`, quote per base in PRICE_PRECISION. The smaller
+    // side fills completely and the larger one pro rata, so no member jumps the queue.
+    pub fn net(&mut self, pair: &TokenPair, mid: u64) -> Result<Vec<NettedFill>> {
+        require_gt!(mid, 0, TradingBotError::InvalidCalculation);
+        let count = self.member_count as usize;
+        let (mut bid_quote, mut ask_base) = (0u64, 0u64);
+        for order in self.members[..count]
+            .iter()
+            .filter_map(|member| member.order)
+        {
+            if order.pair != *pair {
+                continue;
+            }
+            if order.side.is_buy() {
+                bid_quote = math::checked_add(bid_quote, order.remaining)?;
+            } else {
+                ask_base = math::checked_add(ask_base, order.remaining)?;
+            }
+        }
+
+        let crossed_quote = bid_quote.min(Valuation::quote_value(ask_base, mid)?);
+        let crossed_base = ask_base.min(Self::base_value(crossed_quote, mid)?);
+        if crossed_quote == 0 || crossed_base == 0 {
+            return Ok(Vec::new());
+        }
+
+        let fee_bps = self.crossing_fee_bps as u64;
+        let mut fills = Vec::new();
+        for member in self.members[..count].iter_mut() {
+            let order = match member.order.as_mut() {
+                Some(order) if order.pair == *pair && order.remaining > 0 => order,
+                _ => continue,
+            };
+            let (amount_in, gross_out) = if order.side.is_buy() {
+                let amount_in = Self::pro_rata(order.remaining, crossed_quote, bid_quote)?;
+                (amount_in, Self::base_value(amount_in, mid)?)
+            } else {
+                let amount_in = Self::pro_rata(order.remaining, crossed_base, ask_base)?;
+                (amount_in, Valuation::quote_value(amount_in, mid)?)
+            };
+            if amount_in == 0 {
+                continue;
+            }
+            let fee = math::checked_as_u64(math::checked_div(
+                math::checked_mul(gross_out as u128, fee_bps as u128)?,
+                10000,
+            )?)?;
+            let amount_out = math::checked_sub(gross_out, fee)?;
+
+            order.remaining = math::checked_sub(order.remaining, amount_in)?;
+            order.filled_in = math::checked_add(order.filled_in, amount_in)?;
+            order.filled_out = math::checked_add(order.filled_out, amount_out)?;
+            fills.push(NettedFill {
+                strategy: member.strategy,
+                side: order.side,
+                amount_in,
+                amount_out,
+                fee,
+            });
+        }
+
+        self.crossed_volume = math::checked_add(self.crossed_volume, crossed_quote)?;
+        Ok(fills)
+    }
+}
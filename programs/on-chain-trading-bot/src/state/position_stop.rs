@@ -0,0 +1,107 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::types::TradeSide;
+use anchor_lang::prelude::*;
+
+// The stop-loss on one of a strategy's positions, in oracle price. `side` opened the position, a
+// buy for a long. A trailing stop follows the best price seen by `trailing_bps`, and a
+// breakeven block lifts the stop to entry once the position is far enough in profit. Stops only
+// ever tighten; zero is no stop.
+#[account]
+#[derive(InitSpace)]
+pub struct PositionStop {
+    pub strategy: Pubkey,
+    pub position_id: Pubkey,
+    pub side: TradeSide,
+    pub entry_price: u64,
+    pub stop_price: u64,
+    // zero for a fixed stop
+    pub trailing_bps: u16,
+    // highest price seen for a long, lowest for a short
+    pub best_price: u64,
+    pub at_breakeven: bool,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl PositionStop {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    fn is_long(&self) -> bool {
+        self.side.is_buy()
+    }
+
+    fn offset(price: u64, bps: u16) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(price as u128, bps as u128)?,
+            10000,
+        )?)
+    }
+
+    // Profit at `price` in bps of the entry, negative while in a loss
+    pub fn unrealized_bps(&self, price: u64) -> Result<i64> {
+        let diff = math::checked_sub(price as i128, self.entry_price as i128)?;
+        let diff = if self.is_long() { diff } else { -diff };
+        i64::try_from(math::checked_div(
+            math::checked_mul(diff, 10000)?,
+            self.entry_price as i128,
+        )?)
+        .map_err(|_| error!(TradingBotError::InvalidCalculation))
+    }
+
+    // The entry moved `fee_bps` in the position's favour, so a stop there still covers fees
+    pub fn breakeven_price(&self, fee_bps: u16) -> Result<u64> {
+        let offset = Self::offset(self.entry_price, fee_bps)?;
+        if self.is_long() {
+            math::checked_add(self.entry_price, offset)
+        } else {
+            math::checked_sub(self.entry_price, offset)
+        }
+    }
+
+    fn tighten(&mut self, stop_price: u64) -> bool {
+        let tighter = self.stop_price == 0
+            || if self.is_long() {
+                stop_price > self.stop_price
+            } else {
+                stop_price < self.stop_price
+            };
+        if tighter {
+            self.stop_price = stop_price;
+        }
+        tighter
+    }
+
+    // Records `price` as the best seen if it is, and pulls a trailing stop up behind it
+    pub fn trail(&mut self, price: u64) -> Result<bool> {
+        let better = if self.is_long() {
+            price > self.best_price
+        } else {
+            price < self.best_price
+        };
+        if better {
+            self.best_price = price;
+        }
+        if self.trailing_bps == 0 {
+            return Ok(false);
+        }
+        let offset = Self::offset(self.best_price, self.trailing_bps)?;
+        let stop_price = if self.is_long() {
+            self.best_price.saturating_sub(offset)
+        } else {
+            math::checked_add(self.best_price, offset)?
+        };
+        Ok(self.tighten(stop_price))
+    }
+
+    // Once `price` is at least `trigger_bps` in profit, moves the stop to breakeven plus fees.
+    // Applies once per stop; a trailing stop already past breakeven is left alone.
+    pub fn apply_breakeven(&mut self, price: u64, trigger_bps: u16, fee_bps: u16) -> Result<bool> {
+        if self.at_breakeven || self.unrealized_bps(price)? < trigger_bps as i64 {
+            return Ok(false);
+        }
+        self.at_breakeven = true;
+        let breakeven = self.breakeven_price(fee_bps)?;
+        Ok(self.tighten(breakeven))
+    }
+}
@@ -0,0 +1,50 @@
+use crate::constants::PRICE_PRECISION;
+use crate::math;
+use crate::types::DexType;
+use anchor_lang::prelude::*;
+
+// Price range an LP position is sized for. While the pool's implied price stays inside it the
+// position is left alone; leaving it is what makes a rebalance worth its cost.
+#[account]
+#[derive(InitSpace)]
+pub struct PriceBand {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub dex: DexType,
+    pub base_reserve: Pubkey,
+    pub quote_reserve: Pubkey,
+    // quote per base in raw token units, scaled by PRICE_PRECISION
+    pub lower_price: u64,
+    pub upper_price: u64,
+    // half-width of the band around the trigger price when it is recentered
+    pub width_bps: u16,
+    pub last_triggered_price: u64,
+    pub last_triggered_at: i64,
+    pub trigger_count: u32,
+    pub bump: u8,
+}
+
+impl PriceBand {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn implied_price(base_amount: u64, quote_amount: u64) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(quote_amount as u128, PRICE_PRECISION as u128)?,
+            base_amount as u128,
+        )?)
+    }
+
+    pub fn contains(&self, price: u64) -> bool {
+        price >= self.lower_price && price <= self.upper_price
+    }
+
+    pub fn recenter(&mut self, price: u64) -> Result<()> {
+        let offset = math::checked_div(
+            math::checked_mul(price as u128, self.width_bps as u128)?,
+            10000,
+        )?;
+        self.lower_price = math::checked_as_u64(math::checked_sub(price as u128, offset)?)?;
+        self.upper_price = math::checked_as_u64(math::checked_add(price as u128, offset)?)?;
+        Ok(())
+    }
+}
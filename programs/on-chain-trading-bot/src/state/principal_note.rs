@@ -0,0 +1,117 @@
+use crate::constants::SECONDS_PER_YEAR;
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum NoteStatus {
+    Idle,
+    // principal is lent out and the calls are held
+    Active,
+}
+
+// One leg of the note, in the vault's asset: what the open note put in and its last valuation,
+// plus the PnL of the notes closed so far
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct NoteLeg {
+    pub cost: u64,
+    pub value: u64,
+    pub realized_pnl: i64,
+}
+
+impl NoteLeg {
+    fn open(&mut self, cost: u64) {
+        self.cost = cost;
+        self.value = cost;
+    }
+
+    fn close(&mut self, proceeds: u64) -> Result<i64> {
+        let pnl = i64::try_from(proceeds as i128 - self.cost as i128)
+            .map_err(|_| TradingBotError::InvalidCalculation)?;
+        self.realized_pnl = math::checked_add(self.realized_pnl, pnl)?;
+        self.cost = 0;
+        self.value = 0;
+        Ok(pnl)
+    }
+}
+
+// A principal-protected note on a vault: most of the principal is lent on Solend so that, at the
+// reserve's current supply rate, it grows back to `floor_bps` of the principal by maturity, and
+// the rest buys PsyOptions calls expiring by then. The lending leg and the calls are valued and
+// booked separately as the principal and upside legs.
+#[account]
+#[derive(InitSpace)]
+pub struct PrincipalNote {
+    pub owner: Pubkey,
+    pub trading_vault: Pubkey,
+    pub lending_reserve: Pubkey,
+    // the vault's cToken account for the reserve
+    pub collateral_account: Pubkey,
+    // share of the principal the lending leg has to be projected to return
+    pub floor_bps: u16,
+    pub status: NoteStatus,
+    // the open note
+    pub principal: u64,
+    pub maturity: i64,
+    pub option_market: Pubkey,
+    pub option_account: Pubkey,
+    pub collateral: u64,
+    pub options: u64,
+    pub principal_leg: NoteLeg,
+    pub upside_leg: NoteLeg,
+    pub valued_at: i64,
+    pub notes: u32,
+    pub bump: u8,
+}
+
+impl PrincipalNote {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // The deposit, earning `supply_apr_bps` as simple interest until maturity, has to come back to
+    // the floor
+    pub fn check_protected(
+        &self,
+        principal: u64,
+        deposit: u64,
+        supply_apr_bps: u64,
+        tenor: i64,
+    ) -> Result<()> {
+        let year = SECONDS_PER_YEAR as u128 * 10_000;
+        let growth = math::checked_mul(supply_apr_bps as u128, tenor as u128)?;
+        let projected = math::checked_div(
+            math::checked_mul(deposit as u128, math::checked_add(year, growth)?)?,
+            year,
+        )?;
+        let floor = math::checked_div(
+            math::checked_mul(principal as u128, self.floor_bps as u128)?,
+            10_000,
+        )?;
+        require_gte!(projected, floor, TradingBotError::PrincipalNotProtected);
+        Ok(())
+    }
+
+    // Books the legs of a note whose principal, maturity and calls are already set
+    pub fn open(&mut self, deposit: u64, collateral: u64, premium: u64, options: u64, now: i64) {
+        self.status = NoteStatus::Active;
+        self.collateral = collateral;
+        self.options = options;
+        self.principal_leg.open(deposit);
+        self.upside_leg.open(premium);
+        self.valued_at = now;
+    }
+
+    // Books what each leg returned and resets for the next note; returns each leg's PnL
+    pub fn settle(&mut self, principal_proceeds: u64, upside_proceeds: u64) -> Result<(i64, i64)> {
+        let principal_pnl = self.principal_leg.close(principal_proceeds)?;
+        let upside_pnl = self.upside_leg.close(upside_proceeds)?;
+        self.status = NoteStatus::Idle;
+        self.principal = 0;
+        self.maturity = 0;
+        self.option_market = Pubkey::default();
+        self.option_account = Pubkey::default();
+        self.collateral = 0;
+        self.options = 0;
+        self.notes = math::checked_add(self.notes, 1)?;
+        Ok((principal_pnl, upside_pnl))
+    }
+}
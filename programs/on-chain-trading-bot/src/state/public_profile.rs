@@ -0,0 +1,157 @@
+use crate::constants::{MIN_SNAPSHOT_INTERVAL, PRICE_PRECISION};
+use crate::state::PageCursor;
+use crate::types::StrategyType;
+use crate::{errors::TradingBotError, math};
+use anchor_lang::prelude::*;
+
+pub const REGISTRY_CAPACITY: usize = 64;
+pub const MAX_PROFILE_NAME_LEN: usize = 32;
+
+// Opt-in public record of a strategy's performance. Every figure comes from NAV snapshots the
+// program takes itself from the oracle and the strategy's wallets, so rankings built on it don't
+// rely on anything the owner reports.
+#[account]
+#[derive(InitSpace)]
+pub struct PublicProfile {
+    pub owner: Pubkey,
+    pub strategy: Pubkey,
+    pub strategy_type: StrategyType,
+    #[max_len(32)]
+    pub name: String,
+    pub published_at: i64,
+    pub baseline_nav: u64,
+    pub latest_nav: u64,
+    pub peak_nav: u64,
+    pub max_drawdown_bps: u16,
+    // the wallets' holdings at publishing; holding them untouched is the HODL benchmark
+    pub baseline_base: u64,
+    pub baseline_quote: u64,
+    pub latest_hodl_nav: u64,
+    // quote per SOL in PRICE_PRECISION for the SOL benchmark; the feed is default and the prices
+    // zero when the profile has none
+    pub sol_price_feed: Pubkey,
+    pub baseline_sol_price: u64,
+    pub latest_sol_price: u64,
+    pub snapshot_count: u32,
+    pub last_snapshot_at: i64,
+    pub bump: u8,
+}
+
+impl PublicProfile {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn snapshot_due(&self, now: i64) -> bool {
+        now >= self.last_snapshot_at + MIN_SNAPSHOT_INTERVAL
+    }
+
+    pub fn has_sol_benchmark(&self) -> bool {
+        self.sol_price_feed != Pubkey::default()
+    }
+
+    pub fn record_snapshot(
+        &mut self,
+        nav: u64,
+        hodl_nav: u64,
+        sol_price: Option<u64>,
+        now: i64,
+    ) -> Result<()> {
+        self.latest_nav = nav;
+        self.latest_hodl_nav = hodl_nav;
+        if let Some(sol_price) = sol_price {
+            self.latest_sol_price = sol_price;
+        }
+        self.peak_nav = self.peak_nav.max(nav);
+        if self.peak_nav > 0 {
+            let drawdown_bps = math::checked_div(
+                math::checked_mul((self.peak_nav - nav) as u128, 10000)?,
+                self.peak_nav as u128,
+            )? as u16;
+            self.max_drawdown_bps = self.max_drawdown_bps.max(drawdown_bps);
+        }
+        self.snapshot_count = math::checked_add(self.snapshot_count, 1)?;
+        self.last_snapshot_at = now;
+        Ok(())
+    }
+
+    fn change_bps(from: u64, to: u64) -> Result<i64> {
+        if from == 0 {
+            return Ok(0);
+        }
+        let change = math::checked_sub(to as i128, from as i128)?;
+        let change_bps = math::checked_div(math::checked_mul(change, 10000)?, from as i128)?;
+        i64::try_from(change_bps).map_err(|_| error!(TradingBotError::InvalidCalculation))
+    }
+
+    // Return since publishing, in bps of the baseline NAV
+    pub fn return_bps(&self) -> Result<i64> {
+        Self::change_bps(self.baseline_nav, self.latest_nav)
+    }
+
+    // Return over what the baseline holdings would be worth had the strategy never traded
+    pub fn alpha_vs_hodl_bps(&self) -> Result<i64> {
+        math::checked_sub(
+            self.return_bps()?,
+            Self::change_bps(self.baseline_nav, self.latest_hodl_nav)?,
+        )
+    }
+
+    // Return over converting the baseline NAV to SOL at publishing and holding that
+    pub fn alpha_vs_sol_bps(&self) -> Result<Option<i64>> {
+        if !self.has_sol_benchmark() {
+            return Ok(None);
+        }
+        Ok(Some(math::checked_sub(
+            self.return_bps()?,
+            Self::change_bps(self.baseline_sol_price, self.latest_sol_price)?,
+        )?))
+    }
+
+    // Latest NAV in lamports, as the SOL price is native quote per lamport in PRICE_PRECISION
+    pub fn nav_in_sol(&self) -> Result<Option<u64>> {
+        if !self.has_sol_benchmark() || self.latest_sol_price == 0 {
+            return Ok(None);
+        }
+        Ok(Some(math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.latest_nav as u128, PRICE_PRECISION as u128)?,
+            self.latest_sol_price as u128,
+        )?)?))
+    }
+}
+
+// Enumerates every published profile so clients can rank them without scanning the program
+#[account]
+#[derive(InitSpace)]
+pub struct StrategyRegistry {
+    pub profiles: [Pubkey; REGISTRY_CAPACITY],
+    pub count: u16,
+    // where the paged snapshot_registry crank resumes
+    pub snapshot_cursor: PageCursor,
+    pub bump: u8,
+}
+
+impl StrategyRegistry {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn add(&mut self, profile: Pubkey) -> Result<()> {
+        require!(
+            (self.count as usize) < REGISTRY_CAPACITY,
+            TradingBotError::RegistryFull
+        );
+        self.profiles[self.count as usize] = profile;
+        self.count += 1;
+        Ok(())
+    }
+
+    // Order isn't kept; the last entry fills the gap
+    pub fn remove(&mut self, profile: &Pubkey) -> Result<()> {
+        let count = self.count as usize;
+        let index = self.profiles[..count]
+            .iter()
+            .position(|p| p == profile)
+            .ok_or(TradingBotError::ProfileNotRegistered)?;
+        self.profiles[index] = self.profiles[count - 1];
+        self.profiles[count - 1] = Pubkey::default();
+        self.count -= 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::math;
+use anchor_lang::prelude::*;
+
+// A quoted swap the owner commits to before executing; the fill must land within `max_slots`
+// and within `tolerance_bps` of the quoted output
+#[account]
+#[derive(InitSpace)]
+pub struct QuoteLock {
+    pub idx: u64,
+    pub owner: Pubkey,
+    pub input_account: Pubkey,
+    pub output_account: Pubkey,
+    pub swap_program: Pubkey,
+    pub amount_in: u64,
+    pub quoted_out: u64,
+    pub tolerance_bps: u16,
+    pub locked_slot: u64,
+    pub max_slots: u64,
+    pub bump: u8,
+}
+
+impl QuoteLock {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot > self.locked_slot.saturating_add(self.max_slots)
+    }
+
+    pub fn min_out(&self) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(
+                self.quoted_out as u128,
+                (10000 - self.tolerance_bps) as u128,
+            )?,
+            10000,
+        )?)
+    }
+}
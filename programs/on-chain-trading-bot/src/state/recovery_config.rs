@@ -0,0 +1,90 @@
+use crate::errors::TradingBotError;
+use anchor_lang::prelude::*;
+
+pub const MAX_GUARDIANS: usize = 8;
+
+// Guardians that can hand a strategy and its trading vaults to a new owner key once `threshold`
+// of them back the same key and `timelock` has passed since it was first proposed. Until then
+// the current owner can veto.
+#[account]
+#[derive(InitSpace)]
+pub struct RecoveryConfig {
+    pub strategy: Pubkey,
+    pub guardians: [Pubkey; MAX_GUARDIANS],
+    pub guardian_count: u8,
+    pub threshold: u8,
+    pub timelock: i64,
+    // default while no recovery is pending
+    pub proposed_owner: Pubkey,
+    pub proposed_at: i64,
+    // one bit per guardian index
+    pub approvals: u8,
+    pub recovery_count: u32,
+    pub bump: u8,
+}
+
+impl RecoveryConfig {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn set_guardians(
+        &mut self,
+        guardians: &[Pubkey],
+        threshold: u8,
+        timelock: i64,
+    ) -> Result<()> {
+        require!(
+            !guardians.is_empty() && guardians.len() <= MAX_GUARDIANS,
+            TradingBotError::InvalidRecoveryConfig
+        );
+        require!(
+            threshold > 0 && threshold as usize <= guardians.len(),
+            TradingBotError::InvalidRecoveryConfig
+        );
+        for (i, guardian) in guardians.iter().enumerate() {
+            require!(
+                *guardian != Pubkey::default() && !guardians[..i].contains(guardian),
+                TradingBotError::InvalidRecoveryConfig
+            );
+        }
+
+        self.guardians = [Pubkey::default(); MAX_GUARDIANS];
+        self.guardians[..guardians.len()].copy_from_slice(guardians);
+        self.guardian_count = guardians.len() as u8;
+        self.threshold = threshold;
+        self.timelock = timelock;
+        self.clear_pending();
+        Ok(())
+    }
+
+    pub fn guardian_index(&self, key: &Pubkey) -> Option<usize> {
+        self.guardians[..self.guardian_count as usize]
+            .iter()
+            .position(|guardian| guardian == key)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.proposed_owner != Pubkey::default()
+    }
+
+    // Backing a different key than the pending one restarts the proposal and its timelock
+    pub fn approve(&mut self, guardian_index: usize, new_owner: Pubkey, now: i64) {
+        if self.proposed_owner != new_owner {
+            self.proposed_owner = new_owner;
+            self.proposed_at = now;
+            self.approvals = 0;
+        }
+        self.approvals |= 1 << guardian_index;
+    }
+
+    pub fn is_ready(&self, now: i64) -> bool {
+        self.is_pending()
+            && self.approvals.count_ones() >= self.threshold as u32
+            && now >= self.proposed_at.saturating_add(self.timelock)
+    }
+
+    pub fn clear_pending(&mut self) {
+        self.proposed_owner = Pubkey::default();
+        self.proposed_at = 0;
+        self.approvals = 0;
+    }
+}
@@ -0,0 +1,71 @@
+use crate::math;
+use crate::types::TimeBasis;
+use anchor_lang::prelude::*;
+
+// Fixed-interval schedule shared by anything that runs in cycles. `cycle_frequency` and
+// `next_cycle_at` count in `basis`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct CycleSchedule {
+    pub basis: TimeBasis,
+    pub cycle_frequency: i64,
+    pub next_cycle_at: i64,
+    // zero runs until the funds run out
+    pub max_cycles: u32,
+    pub cycles_completed: u32,
+}
+
+impl CycleSchedule {
+    pub fn new(basis: TimeBasis, cycle_frequency: i64, start_at: i64, max_cycles: u32) -> Self {
+        Self {
+            basis,
+            cycle_frequency,
+            next_cycle_at: start_at,
+            max_cycles,
+            cycles_completed: 0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.max_cycles > 0 && self.cycles_completed >= self.max_cycles
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        !self.is_finished() && now >= self.next_cycle_at
+    }
+
+    // Missed cycles are caught up one call at a time rather than skipped
+    pub fn advance(&mut self) -> Result<()> {
+        self.next_cycle_at = math::checked_add(self.next_cycle_at, self.cycle_frequency)?;
+        self.cycles_completed = math::checked_add(self.cycles_completed, 1)?;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RecurringTransfer {
+    pub idx: u64,
+    pub owner: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub amount_per_cycle: u64,
+    pub schedule: CycleSchedule,
+    pub total_transferred: u64,
+    pub bump: u8,
+}
+
+impl RecurringTransfer {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+#[macro_export]
+macro_rules! recurring_transfer_seeds {
+    ( $transfer:expr, $idx_bytes:expr ) => {
+        &[
+            RECURRING_TRANSFER_SEED,
+            $transfer.owner.as_ref(),
+            $idx_bytes.as_ref(),
+            &[$transfer.bump],
+        ]
+    };
+}
@@ -0,0 +1,65 @@
+use crate::regime::{PriceRing, RegimeThresholds, VolatilityRegime};
+use crate::state::MarketMakerParams;
+use anchor_lang::prelude::*;
+
+// Per-regime overrides of a market maker's quoting parameters, e.g. wider spreads and smaller
+// sizes while volatility is high. Field order and padding follow the zero-copy layout.
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RegimeParams {
+    pub order_size: u64,
+    pub max_inventory_deviation: u64,
+    pub spread_bps: u16,
+    pub requote_threshold_bps: u16,
+    pub padding: [u8; 4],
+}
+
+impl RegimeParams {
+    pub fn apply(&self, params: &mut MarketMakerParams) {
+        params.spread_bps = self.spread_bps;
+        params.requote_threshold_bps = self.requote_threshold_bps;
+        params.order_size = self.order_size;
+        params.max_inventory_deviation = self.max_inventory_deviation;
+    }
+}
+
+// Samples the market maker's oracle into a ring buffer and swaps in the parameter set of the
+// current volatility regime. Zero-copy, so each crank writes one sample in place rather than
+// round-tripping the whole ring through Borsh.
+#[account(zero_copy)]
+pub struct RegimeSwitch {
+    pub owner: Pubkey,
+    pub market_maker: Pubkey,
+    pub samples: PriceRing,
+    pub sample_interval: i64,
+    pub last_sample_at: i64,
+    pub thresholds: RegimeThresholds,
+    // indexed by VolatilityRegime
+    pub param_sets: [RegimeParams; 3],
+    pub last_switch_at: i64,
+    pub switch_count: u32,
+    // VolatilityRegime index, see `current()`
+    pub current: u8,
+    pub bump: u8,
+    pub padding: [u8; 2],
+}
+
+impl RegimeSwitch {
+    pub const LEN: usize = 8 + std::mem::size_of::<RegimeSwitch>();
+
+    pub fn current(&self) -> VolatilityRegime {
+        VolatilityRegime::from_index(self.current)
+    }
+
+    pub fn set_current(&mut self, regime: VolatilityRegime) {
+        self.current = regime.index() as u8;
+    }
+
+    pub fn params_for(&self, regime: VolatilityRegime) -> &RegimeParams {
+        &self.param_sets[regime.index()]
+    }
+
+    pub fn sample_due(&self, now: i64) -> bool {
+        self.last_sample_at == 0 || now >= self.last_sample_at.saturating_add(self.sample_interval)
+    }
+}
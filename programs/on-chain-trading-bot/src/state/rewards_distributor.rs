@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardsDistributor {
+    pub campaign_id: u64,
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub root_version: u64,
+    pub max_total_claim: u64,
+    pub total_claimed: u64,
+    pub num_claimants: u64,
+    pub bump: u8,
+}
+
+impl RewardsDistributor {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+// Leaves carry cumulative amounts, so a claimant only receives what exceeds `amount_claimed`
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimStatus {
+    pub distributor: Pubkey,
+    pub claimant: Pubkey,
+    pub amount_claimed: u64,
+    pub last_claimed_at: i64,
+    pub bump: u8,
+}
+
+impl ClaimStatus {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+#[macro_export]
+macro_rules! rewards_distributor_seeds {
+    ( $distributor:expr, $campaign_bytes:expr ) => {
+        &[
+            REWARDS_DISTRIBUTOR_SEED,
+            $distributor.admin.as_ref(),
+            $campaign_bytes.as_ref(),
+            &[$distributor.bump],
+        ]
+    };
+}
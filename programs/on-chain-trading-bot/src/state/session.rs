@@ -0,0 +1,35 @@
+use crate::math;
+use anchor_lang::prelude::*;
+
+// A short-lived key the owner lets sign `execute_strategy` in its place, e.g. a keypair held by
+// the frontend. It can only run the strategy's blocks, within the per-swap and session volume caps.
+#[account]
+#[derive(InitSpace)]
+pub struct Session {
+    pub strategy: Pubkey,
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub max_trade_amount: u64,
+    pub volume_cap: u64,
+    pub volume_used: u64,
+    pub bump: u8,
+}
+
+impl Session {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn is_live(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
+
+    // Books the swaps of one execution against the caps
+    pub fn spend(&mut self, largest_swap: u64, total_volume: u64) -> Result<bool> {
+        let volume_used = math::checked_add(self.volume_used, total_volume)?;
+        if largest_swap > self.max_trade_amount || volume_used > self.volume_cap {
+            return Ok(false);
+        }
+        self.volume_used = volume_used;
+        Ok(true)
+    }
+}
@@ -0,0 +1,692 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::oracles::PythOracle;
+use crate::types::{DexType, StrategyType, TimeBasis, TokenPair};
+use anchor_lang::prelude::*;
+use std::collections::BTreeMap;
+
+pub const MAX_STRATEGY_ID_LEN: usize = 32;
+pub const VOLUME_BUCKETS: usize = 24;
+const VOLUME_BUCKET_SECONDS: i64 = 3600;
+pub const MAX_RETRYING_BLOCKS: usize = 4;
+pub const MAX_ALLOWED_KEEPERS: usize = 4;
+
+// The one account every block-based strategy instruction, risk check and account manager works
+// against. Space is fixed up front so the account never needs a realloc.
+#[account]
+#[derive(InitSpace)]
+pub struct Strategy {
+    pub owner: Pubkey,
+    // the PDA seed key; stays fixed when recovery rotates the owner
+    pub creator: Pubkey,
+    #[max_len(32)]
+    pub strategy_id: String,
+    pub strategy_type: StrategyType,
+    pub config: StrategyConfig,
+    pub risk_parameters: RiskParameters,
+    pub performance_metrics: PerformanceMetrics,
+    pub accounting: AccountingCurrency,
+    pub volatility_halt: VolatilityHalt,
+    pub approval_policy: ApprovalPolicy,
+    pub volume_window: VolumeWindow,
+    pub block_retries: BlockRetries,
+    pub keeper_allowlist: KeeperAllowlist,
+    // non-owner executions go through the strategy's ExecutionAuction
+    pub auctioned_execution: bool,
+    pub is_active: bool,
+    // held for the length of an execute_strategy and written out before its first swap, so an
+    // execution nested in one of its CPIs finds it
+    pub executing: bool,
+    // native quote paid in through deposit_strategy_funds less what withdraw_strategy_funds took
+    // out, each at the oracle mid of the time; the summary's P&L is the NAV above it
+    pub net_contributions: i64,
+    pub total_trades: u64,
+    pub last_executed_at: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl Strategy {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn record_reward_income(&mut self, amount: u64, now: i64) -> Result<()> {
+        let metrics = &mut self.performance_metrics;
+        metrics.reward_income = math::checked_add(metrics.reward_income, amount)?;
+        metrics.harvest_count = math::checked_add(metrics.harvest_count, 1)?;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    // Now in the strategy's time basis, which its execution deadlines, volume window, sessions and
+    // trade approvals count in. The `now` the volume methods take is this.
+    pub fn clock_now(&self) -> Result<i64> {
+        self.config.time_basis.current()
+    }
+
+    fn volume_bucket_len(&self) -> i64 {
+        self.config.time_basis.from_seconds(VOLUME_BUCKET_SECONDS)
+    }
+
+    pub fn volume_24h(&self, now: i64) -> u64 {
+        self.volume_window.total(now, self.volume_bucket_len())
+    }
+
+    pub fn record_volume(&mut self, amount: u64, now: i64) -> Result<()> {
+        let bucket_len = self.volume_bucket_len();
+        self.volume_window.record(amount, now, bucket_len)
+    }
+
+    // None while the strategy has no daily volume limit
+    pub fn remaining_daily_volume(&self, now: i64) -> Option<u64> {
+        let limit = self.risk_parameters.daily_volume_limit;
+        (limit > 0).then(|| limit.saturating_sub(self.volume_24h(now)))
+    }
+
+    pub fn volume_utilization_bps(&self, now: i64) -> Result<u64> {
+        let limit = self.risk_parameters.daily_volume_limit;
+        if limit == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.volume_24h(now) as u128, 10000)?,
+            limit as u128,
+        )?)
+    }
+
+    // How long after it became due this execution came, None without an interval or before the
+    // first execution
+    pub fn execution_delay(&self, now: i64) -> Option<u64> {
+        let interval = self.config.execution_interval;
+        if interval == 0 || self.last_executed_at == 0 {
+            return None;
+        }
+        let due_at = self.last_executed_at.saturating_add(interval);
+        Some(now.saturating_sub(due_at).max(0) as u64)
+    }
+
+    // A failed execution reverts the transaction, so the lock can't be left held
+    pub fn lock_execution(&mut self) -> Result<()> {
+        require!(!self.executing, TradingBotError::ExecutionInProgress);
+        self.executing = true;
+        Ok(())
+    }
+
+    pub fn unlock_execution(&mut self) {
+        self.executing = false;
+    }
+
+    pub fn record_execution(&mut self, trades: u64, now: i64) -> Result<()> {
+        self.total_trades = math::checked_add(self.total_trades, trades)?;
+        self.last_executed_at = now;
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct StrategyConfig {
+    pub pair: TokenPair,
+    pub dex: DexType,
+    pub slippage_bps: u16,
+    // minimum seconds between executions, zero for none
+    pub execution_interval: i64,
+    pub min_out_mode: MinOutMode,
+    pub time_basis: TimeBasis,
+}
+
+// Where a swap's minimum output comes from. `Oracle` floors it at the oracle-implied output less
+// `slippage_bps`, so a keeper passing `minimum_out = 1` can't hand the trade to a sandwich; a
+// caller's `minimum_out` can still tighten that floor but never loosen it. The feed is pinned
+// here so the keeper can't bring its own.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MinOutMode {
+    Caller,
+    Oracle { price_feed: Pubkey },
+}
+
+// The currency a strategy's P&L and its trade size and loss limits are kept in: the pair's quote or
+// a mint on ACCOUNTING_MINTS. Quote values are converted at the USD oracle prices of the quote and
+// of `mint`, so the feeds are unused while `mint` is the quote. Unset leaves the limits in raw
+// units of the token a swap spends.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct AccountingCurrency {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub quote_decimals: u8,
+    pub quote_usd_feed: Pubkey,
+    pub mint_usd_feed: Pubkey,
+}
+
+impl AccountingCurrency {
+    pub fn is_set(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+
+    pub fn is_quote(&self, pair: &TokenPair) -> bool {
+        self.mint == pair.quote_mint
+    }
+
+    // Native quote units to native units of `mint`, from both USD prices as get_usd_price
+    // returns them
+    pub fn from_quote(&self, quote_amount: u64, quote_usd: u64, mint_usd: u64) -> Result<u64> {
+        PythOracle::usd_convert(
+            quote_amount,
+            self.quote_decimals,
+            quote_usd,
+            self.decimals,
+            mint_usd,
+        )
+    }
+}
+
+// Limits are in the accounting currency once one is set, except that `limit_unit` can put the
+// trade size and volume limits in USD
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct RiskParameters {
+    pub max_trade_size: u64,
+    pub max_position_size: u64,
+    pub daily_loss_limit: u64,
+    // live entries across the owner's UserPositions, zero for no cap
+    pub max_open_positions: u32,
+    // rolling 24h swap volume, zero for no limit
+    pub daily_volume_limit: u64,
+    // gross exposure over NAV for perp hedges and leverage loops, 10000 = 1x; zero for no cap
+    pub max_leverage_bps: u32,
+    pub limit_unit: LimitUnit,
+}
+
+impl RiskParameters {
+    pub fn is_valid(&self) -> bool {
+        let unit_valid = match self.limit_unit {
+            LimitUnit::Token => true,
+            LimitUnit::Usd { quote_usd_feed, .. } => quote_usd_feed != Pubkey::default(),
+        };
+        // a cap below 1x would refuse an unlevered book itself
+        let leverage_valid = self.max_leverage_bps == 0 || self.max_leverage_bps >= 10000;
+        self.max_trade_size > 0 && unit_valid && leverage_valid
+    }
+}
+
+// What `max_trade_size` and `daily_volume_limit` count. `Token` is the spent token's raw units, or
+// the accounting currency once one is set. `Usd` is USD notional with USD_DECIMALS, valued at
+// execution through the pair's oracle and the quote's USD feed, at the quote's registered decimals,
+// so the caps keep their meaning when the traded tokens reprice by orders of magnitude.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum LimitUnit {
+    Token,
+    Usd { quote_usd_feed: Pubkey },
+}
+
+// USD notional per unit of the token a swap spends, as the notional of a sample amount. Converts
+// swap sizes to and from the USD volume limit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct NotionalRate {
+    pub amount: u64,
+    pub notional: u64,
+}
+
+impl NotionalRate {
+    pub fn to_notional(&self, amount: u64) -> Result<u64> {
+        if self.amount == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(amount as u128, self.notional as u128)?,
+            self.amount as u128,
+        )?)
+    }
+
+    pub fn to_amount(&self, notional: u64) -> Result<u64> {
+        if self.notional == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(notional as u128, self.amount as u128)?,
+            self.notional as u128,
+        )?)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PerformanceMetrics {
+    pub total_profit_loss: i64,
+    pub win_count: u32,
+    pub loss_count: u32,
+    pub largest_profit: u64,
+    pub largest_loss: u64,
+    // farm emissions, in base units, kept out of trading P&L
+    pub reward_income: u64,
+    pub harvest_count: u32,
+    // highest total_profit_loss reached and the deepest fall from it, both since the epoch began
+    pub peak_profit_loss: i64,
+    pub max_drawdown: u64,
+}
+
+impl PerformanceMetrics {
+    pub fn win_rate_bps(&self) -> Result<u64> {
+        let trades = self.win_count as u64 + self.loss_count as u64;
+        if trades == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.win_count as u128, 10000)?,
+            trades as u128,
+        )?)
+    }
+}
+
+// Blocks new entries for `cooldown` seconds after the oracle moves more than `max_move_bps`
+// between observations at most `window` seconds apart, or prints with a confidence interval wider
+// than `max_confidence_bps`. Exits stay allowed. Zero thresholds disable the respective check.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct VolatilityHalt {
+    // only observations from this feed count
+    pub price_feed: Pubkey,
+    pub max_move_bps: u16,
+    pub max_confidence_bps: u16,
+    pub window: i64,
+    pub cooldown: i64,
+    pub last_price: u64,
+    pub last_price_at: i64,
+    pub halted_until: i64,
+}
+
+impl VolatilityHalt {
+    pub fn entries_halted(&self, now: i64) -> bool {
+        now < self.halted_until
+    }
+
+    // Returns true when this observation starts (or extends) a halt
+    pub fn observe(&mut self, price: u64, confidence: u64, now: i64) -> Result<bool> {
+        let mut tripped = false;
+        if self.max_move_bps > 0
+            && self.last_price > 0
+            && now.saturating_sub(self.last_price_at) <= self.window
+        {
+            let move_bps = math::checked_div(
+                math::checked_mul(
+                    (price as i128 - self.last_price as i128).unsigned_abs(),
+                    10000,
+                )?,
+                self.last_price as u128,
+            )?;
+            tripped |= move_bps > self.max_move_bps as u128;
+        }
+        if self.max_confidence_bps > 0 && price > 0 {
+            let confidence_bps =
+                math::checked_div(math::checked_mul(confidence as u128, 10000)?, price as u128)?;
+            tripped |= confidence_bps > self.max_confidence_bps as u128;
+        }
+
+        self.last_price = price;
+        self.last_price_at = now;
+        if tripped {
+            self.halted_until = math::checked_add(now, self.cooldown)?;
+        }
+        Ok(tripped)
+    }
+}
+
+// Swaps above `large_trade_threshold` need a second key: the co-signer signs the execution itself
+// or approves a queued `TradeApproval` ahead of time. A zero threshold turns the policy off.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ApprovalPolicy {
+    pub co_signer: Pubkey,
+    pub large_trade_threshold: u64,
+}
+
+impl ApprovalPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.large_trade_threshold > 0
+    }
+
+    pub fn requires_approval(&self, amount: u64) -> bool {
+        self.is_enabled() && amount > self.large_trade_threshold
+    }
+}
+
+// Keepers a conservative owner accepts executions from. While enforced, a session key has to be
+// one of them, registered with a bonded KeeperRecord, and pass `price_feed`, against which its
+// swap minimums are judged for slashing.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct KeeperAllowlist {
+    pub keepers: [Pubkey; MAX_ALLOWED_KEEPERS],
+    pub enforced: bool,
+    pub price_feed: Pubkey,
+}
+
+impl KeeperAllowlist {
+    pub fn contains(&self, keeper: &Pubkey) -> bool {
+        *keeper != Pubkey::default() && self.keepers.contains(keeper)
+    }
+
+    pub fn add(&mut self, keeper: Pubkey) -> Result<()> {
+        require!(
+            keeper != Pubkey::default() && !self.contains(&keeper),
+            TradingBotError::KeeperNotAllowed
+        );
+        let slot = self
+            .keepers
+            .iter_mut()
+            .find(|slot| **slot == Pubkey::default())
+            .ok_or(TradingBotError::KeeperAllowlistFull)?;
+        *slot = keeper;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, keeper: &Pubkey) -> Result<()> {
+        let slot = self
+            .keepers
+            .iter_mut()
+            .find(|slot| **slot == *keeper && *keeper != Pubkey::default())
+            .ok_or(TradingBotError::KeeperNotAllowed)?;
+        *slot = Pubkey::default();
+        Ok(())
+    }
+}
+
+// Rolling 24h swap volume in hourly buckets, the oldest hour dropping out as a new one starts.
+// `bucket_len` is an hour in the strategy's time basis.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct VolumeWindow {
+    pub buckets: [u64; VOLUME_BUCKETS],
+    // hour of the newest bucket, in bucket lengths since the basis' zero
+    pub latest_hour: i64,
+}
+
+impl VolumeWindow {
+    fn bucket(hour: i64) -> usize {
+        hour.rem_euclid(VOLUME_BUCKETS as i64) as usize
+    }
+
+    pub fn total(&self, now: i64, bucket_len: i64) -> u64 {
+        let hour = now / bucket_len;
+        let from = hour - VOLUME_BUCKETS as i64 + 1;
+        (from.max(self.latest_hour - VOLUME_BUCKETS as i64 + 1)..=self.latest_hour)
+            .map(|h| self.buckets[Self::bucket(h)])
+            .fold(0u64, |total, volume| total.saturating_add(volume))
+    }
+
+    pub fn record(&mut self, amount: u64, now: i64, bucket_len: i64) -> Result<()> {
+        let hour = now / bucket_len;
+        if hour > self.latest_hour {
+            let stale = (hour - self.latest_hour).min(VOLUME_BUCKETS as i64);
+            for h in (hour - stale + 1)..=hour {
+                self.buckets[Self::bucket(h)] = 0;
+            }
+            self.latest_hour = hour;
+        }
+        let bucket = &mut self.buckets[Self::bucket(hour.min(self.latest_hour))];
+        *bucket = math::checked_add(*bucket, amount)?;
+        Ok(())
+    }
+}
+
+// Failure counts of retrying blocks carried from one crank to the next, keyed by block index. A
+// zero count marks a free slot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct BlockRetries {
+    pub entries: [BlockRetry; MAX_RETRYING_BLOCKS],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct BlockRetry {
+    pub block_index: u16,
+    pub failures: u8,
+}
+
+impl BlockRetries {
+    pub fn counts(&self) -> BTreeMap<u16, u8> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.failures > 0)
+            .map(|entry| (entry.block_index, entry.failures))
+            .collect()
+    }
+
+    pub fn store(&mut self, counts: &BTreeMap<u16, u8>) -> Result<()> {
+        require_gte!(
+            MAX_RETRYING_BLOCKS,
+            counts.len(),
+            TradingBotError::InvalidBlockRetry
+        );
+        self.entries = Default::default();
+        for (entry, (block_index, failures)) in self.entries.iter_mut().zip(counts) {
+            entry.block_index = *block_index;
+            entry.failures = *failures;
+        }
+        Ok(())
+    }
+}
+
+#[macro_export]
+macro_rules! strategy_seeds {
+    ( $strategy:expr ) => {
+        &[
+            STRATEGY_SEED,
+            $strategy.creator.as_ref(),
+            $strategy.strategy_id.as_bytes(),
+            &[$strategy.bump],
+        ]
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    // Every enum at its largest variant and the id at its longest, so the encoding fills LEN
+    fn fixture() -> Strategy {
+        let owner = Pubkey::new_unique();
+        Strategy {
+            owner,
+            creator: owner,
+            strategy_id: "s".repeat(MAX_STRATEGY_ID_LEN),
+            strategy_type: StrategyType::MarketMaking,
+            config: StrategyConfig {
+                pair: TokenPair::new(Pubkey::new_unique(), Pubkey::new_unique()),
+                dex: DexType::Serum,
+                slippage_bps: 50,
+                execution_interval: 60,
+                min_out_mode: MinOutMode::Oracle {
+                    price_feed: Pubkey::new_unique(),
+                },
+                time_basis: TimeBasis::Slot {
+                    milli_slots_per_second: 2500,
+                },
+            },
+            risk_parameters: RiskParameters {
+                max_trade_size: 1_000_000,
+                max_position_size: 5_000_000,
+                daily_loss_limit: 250_000,
+                max_open_positions: 3,
+                daily_volume_limit: 10_000_000,
+                max_leverage_bps: 30_000,
+                limit_unit: LimitUnit::Usd {
+                    quote_usd_feed: Pubkey::new_unique(),
+                },
+            },
+            performance_metrics: PerformanceMetrics {
+                total_profit_loss: -42,
+                win_count: 3,
+                loss_count: 4,
+                peak_profit_loss: 17,
+                max_drawdown: 59,
+                ..PerformanceMetrics::default()
+            },
+            accounting: AccountingCurrency {
+                mint: Pubkey::new_unique(),
+                decimals: 9,
+                quote_decimals: 6,
+                quote_usd_feed: Pubkey::new_unique(),
+                mint_usd_feed: Pubkey::new_unique(),
+            },
+            volatility_halt: VolatilityHalt {
+                price_feed: Pubkey::new_unique(),
+                max_move_bps: 300,
+                halted_until: 1_700_000_600,
+                ..VolatilityHalt::default()
+            },
+            approval_policy: ApprovalPolicy {
+                co_signer: Pubkey::new_unique(),
+                large_trade_threshold: 500_000,
+            },
+            volume_window: VolumeWindow {
+                buckets: [7; VOLUME_BUCKETS],
+                latest_hour: 472_222,
+            },
+            block_retries: BlockRetries {
+                entries: [BlockRetry {
+                    block_index: 2,
+                    failures: 1,
+                }; MAX_RETRYING_BLOCKS],
+            },
+            keeper_allowlist: KeeperAllowlist {
+                keepers: [Pubkey::new_unique(); MAX_ALLOWED_KEEPERS],
+                enforced: true,
+                price_feed: Pubkey::new_unique(),
+            },
+            auctioned_execution: true,
+            is_active: true,
+            executing: false,
+            net_contributions: 1_500_000,
+            total_trades: 11,
+            last_executed_at: 1_700_000_000,
+            created_at: 1_690_000_000,
+            updated_at: 1_700_000_000,
+            bump: 254,
+        }
+    }
+
+    fn encode(strategy: &Strategy) -> Vec<u8> {
+        let mut data = Vec::new();
+        strategy.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn space_matches_the_fields() {
+        let fields = 32
+            + 32
+            + 4
+            + MAX_STRATEGY_ID_LEN
+            + StrategyType::INIT_SPACE
+            + StrategyConfig::INIT_SPACE
+            + RiskParameters::INIT_SPACE
+            + PerformanceMetrics::INIT_SPACE
+            + AccountingCurrency::INIT_SPACE
+            + VolatilityHalt::INIT_SPACE
+            + ApprovalPolicy::INIT_SPACE
+            + VolumeWindow::INIT_SPACE
+            + BlockRetries::INIT_SPACE
+            + KeeperAllowlist::INIT_SPACE
+            + 3
+            + 8 * 5
+            + 1;
+        assert_eq!(Strategy::INIT_SPACE, fields);
+        assert_eq!(Strategy::LEN, 8 + Strategy::INIT_SPACE);
+        assert_eq!(encode(&fixture()).len(), Strategy::LEN);
+    }
+
+    #[test]
+    fn fixture_round_trips() {
+        let strategy = fixture();
+        let data = encode(&strategy);
+        assert_eq!(data[..8], Strategy::DISCRIMINATOR);
+        assert_eq!(&data[8..40], strategy.owner.as_ref());
+
+        let decoded = Strategy::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(encode(&decoded), data);
+        assert_eq!(decoded.strategy_id, strategy.strategy_id);
+        assert_eq!(decoded.config.time_basis, strategy.config.time_basis);
+        assert!(decoded.config.min_out_mode == strategy.config.min_out_mode);
+        assert!(decoded.risk_parameters.limit_unit == strategy.risk_parameters.limit_unit);
+        assert_eq!(decoded.volume_window.buckets, strategy.volume_window.buckets);
+        assert_eq!(decoded.block_retries.counts(), strategy.block_retries.counts());
+        assert_eq!(decoded.keeper_allowlist.keepers, strategy.keeper_allowlist.keepers);
+        assert_eq!(decoded.performance_metrics.total_profit_loss, -42);
+        assert_eq!(decoded.bump, 254);
+    }
+
+    #[test]
+    fn short_id_decodes_from_a_full_size_account() {
+        let mut strategy = fixture();
+        strategy.strategy_id = "dca-1".to_string();
+        strategy.config.time_basis = TimeBasis::UnixTime;
+        let mut data = encode(&strategy);
+        let len = data.len();
+        data.resize(Strategy::LEN, 0);
+
+        let decoded = Strategy::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.strategy_id, "dca-1");
+        assert_eq!(encode(&decoded), data[..len]);
+    }
+
+    #[test]
+    fn foreign_discriminator_is_rejected() {
+        let mut data = encode(&fixture());
+        data[0] ^= 1;
+        assert!(Strategy::try_deserialize(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn lock_rejects_a_second_execution_until_unlocked() {
+        let mut strategy = fixture();
+        strategy.lock_execution().unwrap();
+        assert!(strategy.executing);
+        assert!(strategy.lock_execution().is_err());
+        assert!(strategy.executing);
+
+        strategy.unlock_execution();
+        assert!(!strategy.executing);
+        strategy.lock_execution().unwrap();
+    }
+
+    #[test]
+    fn nested_execution_reads_the_written_lock() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = encode(&fixture());
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+
+        // the outer execution locks and writes the lock out before its first CPI
+        let mut outer = Account::<Strategy>::try_from(&info).unwrap();
+        outer.lock_execution().unwrap();
+        outer.exit(&crate::ID).unwrap();
+
+        // a nested execution loads the same account and fails to take the lock
+        let mut nested = Account::<Strategy>::try_from(&info).unwrap();
+        assert!(nested.executing);
+        assert!(nested.lock_execution().is_err());
+
+        // once the outer execution unlocks and exits, the next one gets through
+        outer.unlock_execution();
+        outer.exit(&crate::ID).unwrap();
+        let mut next = Account::<Strategy>::try_from(&info).unwrap();
+        next.lock_execution().unwrap();
+    }
+
+    #[test]
+    fn accounting_converts_quote_at_both_usd_prices() {
+        // books kept in a 9-decimal token at $2 for a USDC-quoted pair
+        let accounting = AccountingCurrency {
+            mint: Pubkey::new_unique(),
+            decimals: 9,
+            quote_decimals: 6,
+            ..AccountingCurrency::default()
+        };
+        let value = accounting.from_quote(150_000_000, 1_000_000, 2_000_000).unwrap();
+        assert_eq!(value, 75_000_000_000);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{errors::TradingBotError, math};
+use anchor_lang::prelude::*;
+
+// A leader's offer to let followers copy a published strategy for a recurring token fee
+#[account]
+#[derive(InitSpace)]
+pub struct SubscriptionPlan {
+    pub leader: Pubkey,
+    pub profile: Pubkey,
+    pub fee_mint: Pubkey,
+    // charged per SUBSCRIPTION_PERIOD
+    pub period_fee: u64,
+    pub subscriber_count: u32,
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+impl SubscriptionPlan {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Leader share and protocol cut of a released fee
+    pub fn split_fee(amount: u64, protocol_fee_bps: u16) -> Result<(u64, u64)> {
+        let protocol = math::checked_as_u64(math::checked_div(
+            math::checked_mul(amount as u128, protocol_fee_bps as u128)?,
+            10000,
+        )?)?;
+        Ok((math::checked_sub(amount, protocol)?, protocol))
+    }
+}
+
+// Prepaid fees sit in the subscription's vault and are released to the leader as time passes,
+// so a cancellation refunds exactly the unused part
+#[account]
+#[derive(InitSpace)]
+pub struct Subscription {
+    pub plan: Pubkey,
+    pub follower: Pubkey,
+    pub subscribed_at: i64,
+    pub paid_until: i64,
+    // fees not yet released, covering released_until..paid_until
+    pub escrowed: u64,
+    pub released_until: i64,
+    pub total_paid: u64,
+    pub bump: u8,
+}
+
+impl Subscription {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.paid_until
+    }
+
+    // Checked before a follower's copy account mirrors the leader
+    pub fn require_active(&self, now: i64) -> Result<()> {
+        require!(self.is_active(now), TradingBotError::SubscriptionInactive);
+        Ok(())
+    }
+
+    // Fees earned by the leader since the last release, pro rata over the prepaid time
+    pub fn earned(&self, now: i64) -> Result<u64> {
+        let until = now.min(self.paid_until);
+        if until <= self.released_until || self.escrowed == 0 {
+            return Ok(0);
+        }
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.escrowed as u128, (until - self.released_until) as u128)?,
+            (self.paid_until - self.released_until) as u128,
+        )?)
+    }
+
+    pub fn release(&mut self, now: i64) -> Result<u64> {
+        let earned = self.earned(now)?;
+        self.escrowed = math::checked_sub(self.escrowed, earned)?;
+        self.released_until = self.released_until.max(now.min(self.paid_until));
+        Ok(earned)
+    }
+
+    // Extends the paid period; a lapsed subscription restarts from now, which is only sound once
+    // everything it had escrowed was released
+    pub fn extend(&mut self, amount: u64, duration: i64, now: i64) -> Result<()> {
+        if !self.is_active(now) {
+            require_eq!(self.escrowed, 0, TradingBotError::SubscriptionFeesUnsettled);
+            self.paid_until = now;
+            self.released_until = now;
+        }
+        self.paid_until = math::checked_add(self.paid_until, duration)?;
+        self.escrowed = math::checked_add(self.escrowed, amount)?;
+        self.total_paid = math::checked_add(self.total_paid, amount)?;
+        Ok(())
+    }
+}
+
+#[macro_export]
+macro_rules! subscription_seeds {
+    ( $subscription:expr ) => {
+        &[
+            SUBSCRIPTION_SEED,
+            $subscription.plan.as_ref(),
+            $subscription.follower.as_ref(),
+            &[$subscription.bump],
+        ]
+    };
+}
@@ -0,0 +1,94 @@
+use crate::constants::PRICE_PRECISION;
+use crate::math;
+use anchor_lang::prelude::*;
+
+// An LBP-style sell schedule for a treasury distributing a token it launches. The inventory is
+// offered at a price decaying linearly from `start_price` to `floor_price` between `start_at` and
+// `end_at`, and at most `interval_cap` can be bought per `interval`; unsold cap does not roll over.
+#[account]
+#[derive(InitSpace)]
+pub struct TokenLaunch {
+    pub idx: u64,
+    pub owner: Pubkey,
+    pub sale_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub total_amount: u64,
+    pub sold_amount: u64,
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub interval: i64,
+    pub interval_cap: u64,
+    // interval `sold_in_interval` counts toward, numbered from `start_at`
+    pub current_interval: i64,
+    pub sold_in_interval: u64,
+    pub proceeds: u64,
+    pub bump: u8,
+}
+
+impl TokenLaunch {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn remaining(&self) -> u64 {
+        self.total_amount.saturating_sub(self.sold_amount)
+    }
+
+    pub fn is_live(&self, now: i64) -> bool {
+        now >= self.start_at && now < self.end_at
+    }
+
+    pub fn current_price(&self, now: i64) -> Result<u64> {
+        let duration = math::checked_sub(self.end_at, self.start_at)?;
+        let elapsed = now.saturating_sub(self.start_at).clamp(0, duration);
+        let range = math::checked_sub(self.start_price, self.floor_price)?;
+        let decay = math::checked_div(
+            math::checked_mul(range as u128, elapsed as u128)?,
+            duration as u128,
+        )?;
+        math::checked_sub(self.start_price, math::checked_as_u64(decay)?)
+    }
+
+    fn interval_at(&self, now: i64) -> i64 {
+        now.saturating_sub(self.start_at) / self.interval
+    }
+
+    // What is left of the cap for the interval `now` falls in
+    pub fn interval_remaining(&self, now: i64) -> u64 {
+        if self.interval_at(now) != self.current_interval {
+            return self.interval_cap;
+        }
+        self.interval_cap.saturating_sub(self.sold_in_interval)
+    }
+
+    pub fn payment_for(amount: u64, price: u64) -> Result<u64> {
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(amount as u128, price as u128)?,
+            PRICE_PRECISION as u128,
+        )?)
+    }
+
+    pub fn record_sale(&mut self, amount: u64, payment: u64, now: i64) -> Result<()> {
+        let interval = self.interval_at(now);
+        if interval != self.current_interval {
+            self.current_interval = interval;
+            self.sold_in_interval = 0;
+        }
+        self.sold_in_interval = math::checked_add(self.sold_in_interval, amount)?;
+        self.sold_amount = math::checked_add(self.sold_amount, amount)?;
+        self.proceeds = math::checked_add(self.proceeds, payment)?;
+        Ok(())
+    }
+}
+
+#[macro_export]
+macro_rules! token_launch_seeds {
+    ( $launch:expr, $idx_bytes:expr ) => {
+        &[
+            TOKEN_LAUNCH_SEED,
+            $launch.owner.as_ref(),
+            $idx_bytes.as_ref(),
+            &[$launch.bump],
+        ]
+    };
+}
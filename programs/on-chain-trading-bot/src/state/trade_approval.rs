@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+// A large trade queued by the strategy owner for the co-signer to approve. Covers a single
+// execution whose swaps stay within `max_amount`, and is closed when that execution consumes it.
+#[account]
+#[derive(InitSpace)]
+pub struct TradeApproval {
+    pub strategy: Pubkey,
+    pub owner: Pubkey,
+    pub max_amount: u64,
+    pub requested_at: i64,
+    pub expires_at: i64,
+    pub approved: bool,
+    pub bump: u8,
+}
+
+impl TradeApproval {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn covers(&self, amount: u64, now: i64) -> bool {
+        self.approved && now <= self.expires_at && amount <= self.max_amount
+    }
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use crate::events::FillRecorded;
+use crate::math;
+
+pub const RECENT_FILLS: usize = 16;
+
+// Every fill is folded into a hash chain and emitted as an event; only the chain head and a short
+// ring of recent fills live in the account, so it never grows
+#[account]
+#[derive(InitSpace)]
+pub struct TradeHistory {
+    pub owner: Pubkey,
+    pub leaf_count: u64,
+    pub accumulator: [u8; 32],
+    pub head: u8,
+    pub recent: [FillLeaf; RECENT_FILLS],
+    pub bump: u8,
+}
+
+impl TradeHistory {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn append(&mut self, leaf: FillLeaf) -> Result<()> {
+        let leaf_hash = leaf.hash();
+        self.accumulator = keccak::hashv(&[&self.accumulator, &leaf_hash]).0;
+        self.recent[self.head as usize] = leaf;
+        self.head = ((self.head as usize + 1) % RECENT_FILLS) as u8;
+        self.leaf_count = math::checked_add(self.leaf_count, 1)?;
+
+        emit!(FillRecorded {
+            owner: self.owner,
+            index: self.leaf_count - 1,
+            timestamp: leaf.timestamp,
+            pair: leaf.pair,
+            is_buy: leaf.is_buy,
+            size: leaf.size,
+            price: leaf.price,
+            fee: leaf.fee,
+            accumulator: self.accumulator,
+        });
+
+        Ok(())
+    }
+
+    // Fill paths always pass the owner's history PDA and append once it has been initialised, so
+    // no caller can leave a fill out of an existing chain
+    pub fn append_if_initialized(account: &AccountInfo, leaf: FillLeaf) -> Result<()> {
+        if account.owner != &crate::ID {
+            return Ok(());
+        }
+        let mut data = account.try_borrow_mut_data()?;
+        let mut history = Self::try_deserialize(&mut &data[..])?;
+        history.append(leaf)?;
+        history.try_serialize(&mut &mut data[..])
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FillLeaf {
+    pub timestamp: i64,
+    pub pair: Pubkey,
+    pub is_buy: bool,
+    pub size: u64,
+    pub price: u64,
+    pub fee: u64,
+}
+
+impl FillLeaf {
+    pub fn hash(&self) -> [u8; 32] {
+        keccak::hashv(&[
+            &self.timestamp.to_le_bytes(),
+            self.pair.as_ref(),
+            &[self.is_buy as u8],
+            &self.size.to_le_bytes(),
+            &self.price.to_le_bytes(),
+            &self.fee.to_le_bytes(),
+        ])
+        .0
+    }
+}
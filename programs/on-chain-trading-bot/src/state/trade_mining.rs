@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use crate::errors::TradingBotError;
+use crate::math;
+
+#[account]
+#[derive(InitSpace)]
+pub struct TradeMiningEpoch {
+    pub distributor: Pubkey,
+    pub admin: Pubkey,
+    pub epoch: u64,
+    // volume counts in this mint, on whichever side of a fill or swap it was
+    pub quote_mint: Pubkey,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub reward_pool: u64,
+    pub total_fee_volume: u64,
+    pub total_fees: u64,
+    pub allocated: u64,
+    pub num_participants: u64,
+    pub bump: u8,
+}
+
+impl TradeMiningEpoch {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn is_active(&self, now: i64) -> bool {
+        now >= self.start_at && now < self.end_at
+    }
+
+    // The quote mint leg of a trade's two (mint, amount) legs, zero if neither is in it
+    pub fn quote_volume(&self, legs: [(Pubkey, u64); 2]) -> u64 {
+        legs.iter()
+            .find(|(mint, _)| *mint == self.quote_mint)
+            .map_or(0, |(_, amount)| *amount)
+    }
+
+    // Credits a trade to `user_volume` while the epoch runs. Only fee-paying trades count: a
+    // fill that paid nothing, or with neither leg in the quote mint, is skipped.
+    pub fn credit(
+        &mut self,
+        user_volume: &mut UserEpochVolume,
+        legs: [(Pubkey, u64); 2],
+        fees_paid: u64,
+        now: i64,
+    ) -> Result<()> {
+        let volume = self.quote_volume(legs);
+        if !self.is_active(now) || volume == 0 || fees_paid == 0 {
+            return Ok(());
+        }
+
+        if user_volume.trade_count == 0 {
+            self.num_participants = math::checked_add(self.num_participants, 1)?;
+        }
+        user_volume.fee_volume = math::checked_add(user_volume.fee_volume, volume)?;
+        user_volume.fees_paid = math::checked_add(user_volume.fees_paid, fees_paid)?;
+        user_volume.trade_count = math::checked_add(user_volume.trade_count, 1)?;
+
+        self.total_fee_volume = math::checked_add(self.total_fee_volume, volume)?;
+        self.total_fees = math::checked_add(self.total_fees, fees_paid)?;
+        Ok(())
+    }
+
+    // Pro-rata share of the pool by fee-paying volume, rounded down
+    pub fn allocation_for(&self, fee_volume: u64) -> Result<u64> {
+        if self.total_fee_volume == 0 {
+            return Ok(0);
+        }
+        let share = math::checked_div(
+            math::checked_mul(self.reward_pool as u128, fee_volume as u128)?,
+            self.total_fee_volume as u128,
+        )?;
+        math::checked_as_u64(share)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserEpochVolume {
+    pub epoch: Pubkey,
+    pub user: Pubkey,
+    // the strategy the volume was swapped through, default for limit order and OTC fills
+    pub strategy: Pubkey,
+    pub fee_volume: u64,
+    pub fees_paid: u64,
+    pub trade_count: u64,
+    pub allocation: u64,
+    pub allocated: bool,
+    pub bump: u8,
+}
+
+impl UserEpochVolume {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // Credits a fill or swap the program settled to `user`'s volume, when the trader passed their
+    // epoch accounts. Mining never fails a trade: outside the epoch, without a fee, or with
+    // neither leg in its quote mint, nothing is counted.
+    pub fn record_fill(
+        mining_epoch: Option<&mut Account<TradeMiningEpoch>>,
+        user_volume: Option<&mut Account<UserEpochVolume>>,
+        user: Pubkey,
+        strategy: Pubkey,
+        legs: [(Pubkey, u64); 2],
+        fees_paid: u64,
+    ) -> Result<()> {
+        let (Some(mining_epoch), Some(user_volume)) = (mining_epoch, user_volume) else {
+            return Ok(());
+        };
+        require!(
+            user_volume.epoch == mining_epoch.key()
+                && user_volume.user == user
+                && user_volume.strategy == strategy,
+            TradingBotError::EpochMismatch
+        );
+        mining_epoch.credit(user_volume, legs, fees_paid, Clock::get()?.unix_timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn epoch(quote_mint: Pubkey) -> TradeMiningEpoch {
+        TradeMiningEpoch {
+            distributor: Pubkey::new_unique(),
+            admin: Pubkey::new_unique(),
+            epoch: 1,
+            quote_mint,
+            start_at: 100,
+            end_at: 200,
+            reward_pool: 1_000_000,
+            total_fee_volume: 0,
+            total_fees: 0,
+            allocated: 0,
+            num_participants: 0,
+            bump: 255,
+        }
+    }
+
+    fn user_volume() -> UserEpochVolume {
+        UserEpochVolume {
+            epoch: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            strategy: Pubkey::default(),
+            fee_volume: 0,
+            fees_paid: 0,
+            trade_count: 0,
+            allocation: 0,
+            allocated: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn only_fee_paying_quote_legs_are_credited() {
+        let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut epoch = epoch(quote);
+        let mut user = user_volume();
+
+        // volume counts on whichever side the quote mint is
+        epoch.credit(&mut user, [(base, 5), (quote, 3_000)], 3, 150).unwrap();
+        epoch.credit(&mut user, [(quote, 1_000), (base, 2)], 1, 150).unwrap();
+        assert_eq!((user.fee_volume, user.fees_paid, user.trade_count), (4_000, 4, 2));
+
+        // free fills, fills outside the quote mint and fills outside the epoch count nothing
+        epoch.credit(&mut user, [(base, 5), (quote, 9_000)], 0, 150).unwrap();
+        epoch.credit(&mut user, [(base, 5), (Pubkey::new_unique(), 9_000)], 9, 150).unwrap();
+        epoch.credit(&mut user, [(base, 5), (quote, 9_000)], 9, 200).unwrap();
+        assert_eq!(user.trade_count, 2);
+        assert_eq!((epoch.total_fee_volume, epoch.total_fees), (4_000, 4));
+        assert_eq!(epoch.num_participants, 1);
+    }
+
+    #[test]
+    fn allocations_are_pro_rata_and_never_exceed_the_pool() {
+        let quote = Pubkey::new_unique();
+        let mut epoch = epoch(quote);
+        assert_eq!(epoch.allocation_for(0).unwrap(), 0);
+
+        let mut users = [user_volume(), user_volume(), user_volume()];
+        for (user, volume) in users.iter_mut().zip([1_000, 1_000, 1_000]) {
+            epoch.credit(user, [(quote, volume), (quote, 0)], 1, 150).unwrap();
+        }
+        assert_eq!(epoch.num_participants, 3);
+        let allocations: Vec<u64> = users
+            .iter()
+            .map(|user| epoch.allocation_for(user.fee_volume).unwrap())
+            .collect();
+        assert_eq!(allocations, vec![333_333; 3]);
+        assert!(allocations.iter().sum::<u64>() <= epoch.reward_pool);
+    }
+}
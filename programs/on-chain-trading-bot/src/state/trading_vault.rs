@@ -0,0 +1,213 @@
+use crate::constants::WITHDRAWAL_ACTIVATION_DELAY;
+use crate::{errors::TradingBotError, math};
+use anchor_lang::prelude::*;
+
+pub const MAX_WITHDRAWAL_ADDRESSES: usize = 4;
+
+// Single-mint vault strategies trade from. Resting orders reserve what they have committed so
+// concurrent actions can only spend the unreserved balance.
+#[account]
+#[derive(InitSpace)]
+pub struct TradingVault {
+    pub owner: Pubkey,
+    // the PDA seed key; stays fixed when recovery rotates the owner
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub reserved: u64,
+    pub open_reservations: u32,
+    pub withdrawal_allowlist: WithdrawalAllowlist,
+    // USD value, with USD_DECIMALS, up to which sweep_dust may sell a balance
+    pub dust_threshold_usd: u64,
+    // creation order, which post_upgrade_check walks
+    pub seq: u32,
+    pub bump: u8,
+}
+
+impl TradingVault {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn available(&self, balance: u64) -> u64 {
+        balance.saturating_sub(self.reserved)
+    }
+
+    pub fn reserve(&mut self, amount: u64, balance: u64) -> Result<()> {
+        require_gte!(
+            self.available(balance),
+            amount,
+            TradingBotError::InsufficientAvailableBalance
+        );
+        self.reserved = math::checked_add(self.reserved, amount)?;
+        self.open_reservations = math::checked_add(self.open_reservations, 1)?;
+        Ok(())
+    }
+
+    // Partial releases keep the reservation open; `close` drops it once the order is terminal
+    pub fn release(&mut self, amount: u64, close: bool) -> Result<()> {
+        self.reserved = math::checked_sub(self.reserved, amount)?;
+        if close {
+            self.open_reservations = math::checked_sub(self.open_reservations, 1)?;
+        }
+        Ok(())
+    }
+
+    // Reserved funds must always be backed by the vault's token balance
+    pub fn check_invariant(&self, balance: u64) -> Result<()> {
+        require_gte!(
+            balance,
+            self.reserved,
+            TradingBotError::InsufficientAvailableBalance
+        );
+        if self.open_reservations == 0 {
+            require_eq!(self.reserved, 0, TradingBotError::InvalidCalculation);
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct AllowedAddress {
+    pub address: Pubkey,
+    pub active_at: i64,
+}
+
+// Wallets the vault may withdraw to. New addresses, and lifting enforcement, only take effect
+// after WITHDRAWAL_ACTIVATION_DELAY, so a stolen owner key can't drain the vault right away.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct WithdrawalAllowlist {
+    pub addresses: [AllowedAddress; MAX_WITHDRAWAL_ADDRESSES],
+    pub enforced: bool,
+    // when a requested lift of enforcement takes effect, zero for none
+    pub lift_at: i64,
+}
+
+impl WithdrawalAllowlist {
+    pub fn is_enforced(&self, now: i64) -> bool {
+        self.enforced && (self.lift_at == 0 || now < self.lift_at)
+    }
+
+    pub fn allows(&self, wallet: &Pubkey, now: i64) -> bool {
+        self.addresses.iter().any(|entry| {
+            entry.address != Pubkey::default() && entry.address == *wallet && now >= entry.active_at
+        })
+    }
+
+    pub fn add(&mut self, address: Pubkey, now: i64) -> Result<()> {
+        require!(
+            address != Pubkey::default()
+                && !self.addresses.iter().any(|entry| entry.address == address),
+            TradingBotError::InvalidWithdrawalAddress
+        );
+        let slot = self
+            .addresses
+            .iter_mut()
+            .find(|entry| entry.address == Pubkey::default())
+            .ok_or(TradingBotError::WithdrawalAllowlistFull)?;
+        slot.address = address;
+        slot.active_at = math::checked_add(now, WITHDRAWAL_ACTIVATION_DELAY)?;
+        Ok(())
+    }
+
+    // Removing only ever narrows the list, so it applies immediately
+    pub fn remove(&mut self, address: Pubkey) -> Result<()> {
+        let slot = self
+            .addresses
+            .iter_mut()
+            .find(|entry| entry.address != Pubkey::default() && entry.address == address)
+            .ok_or(TradingBotError::InvalidWithdrawalAddress)?;
+        *slot = AllowedAddress::default();
+        Ok(())
+    }
+
+    pub fn set_enforced(&mut self, enforced: bool, now: i64) -> Result<()> {
+        if enforced {
+            self.enforced = true;
+            self.lift_at = 0;
+        } else if self.enforced && self.lift_at == 0 {
+            self.lift_at = math::checked_add(now, WITHDRAWAL_ACTIVATION_DELAY)?;
+        }
+        Ok(())
+    }
+}
+
+#[macro_export]
+macro_rules! trading_vault_seeds {
+    ( $vault:expr ) => {
+        &[
+            TRADING_VAULT_SEED,
+            $vault.creator.as_ref(),
+            $vault.mint.as_ref(),
+            &[$vault.bump],
+        ]
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault() -> TradingVault {
+        TradingVault {
+            owner: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            token_account: Pubkey::new_unique(),
+            reserved: 0,
+            open_reservations: 0,
+            withdrawal_allowlist: WithdrawalAllowlist::default(),
+            dust_threshold_usd: 0,
+            seq: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn reserve_only_takes_the_available_balance() {
+        let mut vault = vault();
+        vault.reserve(600, 1000).unwrap();
+        assert_eq!(vault.available(1000), 400);
+        assert!(vault.reserve(401, 1000).is_err());
+        vault.reserve(400, 1000).unwrap();
+        assert_eq!((vault.reserved, vault.open_reservations), (1000, 2));
+        assert_eq!(vault.available(1000), 0);
+        assert!(vault.reserve(1, 1000).is_err());
+        vault.check_invariant(1000).unwrap();
+    }
+
+    #[test]
+    fn release_after_a_partial_fill_keeps_the_reservation_open() {
+        let mut vault = vault();
+        vault.reserve(500, 500).unwrap();
+
+        // a fill pays 200 out of the vault and releases it
+        vault.release(200, false).unwrap();
+        assert_eq!((vault.reserved, vault.open_reservations), (300, 1));
+        vault.check_invariant(300).unwrap();
+
+        // the rest is cancelled
+        vault.release(300, true).unwrap();
+        assert_eq!((vault.reserved, vault.open_reservations), (0, 0));
+        vault.check_invariant(300).unwrap();
+    }
+
+    #[test]
+    fn over_release_fails() {
+        let mut vault = vault();
+        vault.reserve(100, 100).unwrap();
+        assert!(vault.release(101, true).is_err());
+        vault.release(100, true).unwrap();
+        assert!(vault.release(0, true).is_err());
+    }
+
+    #[test]
+    fn invariant_needs_the_reserved_amount_backed() {
+        let mut vault = vault();
+        vault.reserve(100, 100).unwrap();
+        assert!(vault.check_invariant(99).is_err());
+        vault.check_invariant(100).unwrap();
+
+        // nothing may stay reserved once every reservation is closed
+        vault.open_reservations = 0;
+        assert!(vault.check_invariant(100).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+
+pub const MAX_BASKET_TARGETS: usize = 4;
+
+// A DAO treasury's plan to diversify one large holding, kept in `source_vault`, into a basket over
+// weeks. Keepers drip TWAP slices along a linear schedule and rest limit orders near each target's
+// price, governance places large blocks as OTC offers; all three draw on the same `total_amount`.
+#[account]
+#[derive(InitSpace)]
+pub struct TreasuryPlan {
+    pub owner: Pubkey,
+    // changes the parameters and places OTC blocks, e.g. a governance program's PDA
+    pub governance: Pubkey,
+    pub keeper: Pubkey,
+    pub source_vault: Pubkey,
+    pub source_price_feed: Pubkey,
+    pub targets: [BasketTarget; MAX_BASKET_TARGETS],
+    pub target_count: u8,
+    pub total_amount: u64,
+    pub committed: u64,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub slice_interval: i64,
+    pub last_slice_at: i64,
+    pub next_slice_at: i64,
+    // how far, as a share of the interval and of the slice, each slice's timing and size are
+    // randomized so searchers can't line up against the schedule
+    pub jitter_bps: u16,
+    pub max_slippage_bps: u16,
+    pub limit_band_bps: u16,
+    pub otc_min_block: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct BasketTarget {
+    pub mint: Pubkey,
+    pub price_feed: Pubkey,
+    pub weight_bps: u16,
+    // the source priced in this target at PRICE_PRECISION; limit orders and OTC blocks may not
+    // sell below it by more than the plan's band
+    pub target_price: u64,
+    // source committed toward this target by any mode
+    pub committed: u64,
+}
+
+// Everything governance can change after init
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TreasuryPlanParams {
+    pub keeper: Pubkey,
+    pub targets: Vec<BasketTarget>,
+    pub total_amount: u64,
+    pub end_at: i64,
+    pub slice_interval: i64,
+    pub jitter_bps: u16,
+    pub max_slippage_bps: u16,
+    pub limit_band_bps: u16,
+    pub otc_min_block: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DiversificationMode {
+    Drip,
+    Limit,
+    Otc,
+}
+
+impl TreasuryPlan {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn targets(&self) -> &[BasketTarget] {
+        &self.targets[..self.target_count as usize]
+    }
+
+    pub fn target_index(&self, mint: &Pubkey) -> Option<usize> {
+        self.targets()
+            .iter()
+            .position(|target| target.mint == *mint)
+    }
+
+    // Targets that stay in the basket keep what was committed toward them
+    pub fn apply_params(&mut self, params: &TreasuryPlanParams) {
+        let mut targets = [BasketTarget::default(); MAX_BASKET_TARGETS];
+        for (slot, target) in targets.iter_mut().zip(params.targets.iter()) {
+            *slot = *target;
+            slot.committed = self
+                .target_index(&target.mint)
+                .map_or(0, |i| self.targets[i].committed);
+        }
+        self.targets = targets;
+        self.target_count = params.targets.len() as u8;
+        self.keeper = params.keeper;
+        self.total_amount = params.total_amount;
+        self.end_at = params.end_at;
+        self.slice_interval = params.slice_interval;
+        self.jitter_bps = params.jitter_bps;
+        self.max_slippage_bps = params.max_slippage_bps;
+        self.limit_band_bps = params.limit_band_bps;
+        self.otc_min_block = params.otc_min_block;
+    }
+
+    pub fn params(&self) -> TreasuryPlanParams {
+        TreasuryPlanParams {
+            keeper: self.keeper,
+            targets: self.targets().to_vec(),
+            total_amount: self.total_amount,
+            end_at: self.end_at,
+            slice_interval: self.slice_interval,
+            jitter_bps: self.jitter_bps,
+            max_slippage_bps: self.max_slippage_bps,
+            limit_band_bps: self.limit_band_bps,
+            otc_min_block: self.otc_min_block,
+        }
+    }
+
+    // How much of the total the linear drip schedule has reached by `now`
+    pub fn scheduled(&self, now: i64) -> Result<u64> {
+        let duration = math::checked_sub(self.end_at, self.start_at)?;
+        let elapsed = now.saturating_sub(self.start_at).clamp(0, duration);
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(self.total_amount as u128, elapsed as u128)?,
+            duration as u128,
+        )?)
+    }
+
+    pub fn slice_due(&self, now: i64) -> bool {
+        now >= self.next_slice_at
+    }
+
+    // The next slice is due `slice_interval` from now, moved earlier or later by up to
+    // `jitter_bps` of it; `offset` is in 0..=2 * that span
+    pub fn schedule_next_slice(&mut self, now: i64, offset: u64) -> Result<()> {
+        let span = self.jitter_span()?;
+        let delay =
+            math::checked_add(math::checked_sub(self.slice_interval, span)?, offset as i64)?;
+        self.next_slice_at = math::checked_add(now, delay)?;
+        Ok(())
+    }
+
+    pub fn jitter_span(&self) -> Result<i64> {
+        math::checked_div(
+            math::checked_mul(self.slice_interval, self.jitter_bps as i64)?,
+            10000,
+        )
+    }
+
+    // Cuts `amount` by `offset` bps, at most `jitter_bps`; what is left stays scheduled and
+    // catches up in later slices
+    pub fn jittered_slice(&self, amount: u64, offset: u64) -> Result<u64> {
+        let cut = math::checked_div(
+            math::checked_mul(amount as u128, offset.min(self.jitter_bps as u64) as u128)?,
+            10000,
+        )?;
+        math::checked_as_u64(math::checked_sub(amount as u128, cut)?)
+    }
+
+    // The target furthest below its weight, by committed amount per weight
+    pub fn most_underweight(&self) -> Option<usize> {
+        let targets = self.targets();
+        (0..targets.len())
+            .filter(|i| targets[*i].weight_bps > 0)
+            .min_by(|a, b| {
+                let (a, b) = (&targets[*a], &targets[*b]);
+                (a.committed as u128 * b.weight_bps as u128)
+                    .cmp(&(b.committed as u128 * a.weight_bps as u128))
+            })
+    }
+
+    // Selling above the target price is always fine, below it only within the band
+    pub fn within_band(&self, index: usize, price: u64) -> Result<bool> {
+        let floor = math::checked_div(
+            math::checked_mul(
+                self.targets[index].target_price as u128,
+                (10000 - self.limit_band_bps) as u128,
+            )?,
+            10000,
+        )?;
+        Ok(price as u128 >= floor)
+    }
+
+    pub fn commit(&mut self, index: usize, amount: u64) -> Result<()> {
+        let committed = math::checked_add(self.committed, amount)?;
+        require_gte!(
+            self.total_amount,
+            committed,
+            TradingBotError::InvalidTreasuryPlan
+        );
+        self.committed = committed;
+        let target = &mut self.targets[index];
+        target.committed = math::checked_add(target.committed, amount)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use anchor_lang::prelude::*;
+
+// Records the last deploy whose state passed post_upgrade_check. A deploy moves the program
+// data's slot past `checked_slot`, so fills, rebalances, market making and strategies stop until
+// the check passes again. Vaults and index funds are numbered as they're created, and the check
+// walks both in that order a page at a time, so it only passes once every one has been checked.
+#[account]
+#[derive(InitSpace)]
+pub struct UpgradeGuard {
+    pub admin: Pubkey,
+    pub program_data: Pubkey,
+    // deploy slot of the program data when the check last passed
+    pub checked_slot: u64,
+    pub healthy: bool,
+    pub checked_at: i64,
+    // every vault and index fund created so far; each takes the count as its `seq`
+    pub vault_count: u32,
+    pub index_fund_count: u32,
+    // deploy slot the paged check in progress is for, and how far it has got
+    pub checking_slot: u64,
+    pub vaults_checked: u32,
+    pub index_funds_checked: u32,
+    pub bump: u8,
+}
+
+impl UpgradeGuard {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    // `deploy_slot` is the program data's current slot
+    pub fn is_healthy(&self, deploy_slot: u64) -> bool {
+        self.healthy && self.checked_slot == deploy_slot
+    }
+
+    pub fn require_healthy(&self, deploy_slot: u64) -> Result<()> {
+        require!(
+            self.is_healthy(deploy_slot),
+            TradingBotError::UpgradeCheckPending
+        );
+        Ok(())
+    }
+
+    pub fn next_vault_seq(&mut self) -> Result<u32> {
+        let seq = self.vault_count;
+        self.vault_count = math::checked_add(seq, 1)?;
+        Ok(seq)
+    }
+
+    pub fn next_index_fund_seq(&mut self) -> Result<u32> {
+        let seq = self.index_fund_count;
+        self.index_fund_count = math::checked_add(seq, 1)?;
+        Ok(seq)
+    }
+
+    // A new deploy restarts the walk from the first vault
+    pub fn start_page(&mut self, deploy_slot: u64) {
+        if self.checking_slot != deploy_slot {
+            self.checking_slot = deploy_slot;
+            self.vaults_checked = 0;
+            self.index_funds_checked = 0;
+        }
+    }
+
+    // Vaults are checked in order, all of them before the first index fund
+    pub fn record_vault(&mut self, seq: u32) -> Result<()> {
+        require_eq!(seq, self.vaults_checked, TradingBotError::UpgradeCheckOutOfOrder);
+        self.vaults_checked = math::checked_add(seq, 1)?;
+        Ok(())
+    }
+
+    pub fn record_index_fund(&mut self, seq: u32) -> Result<()> {
+        require_eq!(
+            self.vaults_checked,
+            self.vault_count,
+            TradingBotError::UpgradeCheckOutOfOrder
+        );
+        require_eq!(
+            seq,
+            self.index_funds_checked,
+            TradingBotError::UpgradeCheckOutOfOrder
+        );
+        self.index_funds_checked = math::checked_add(seq, 1)?;
+        Ok(())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.vaults_checked == self.vault_count && self.index_funds_checked == self.index_fund_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a guard with two vaults and one index fund, last checked at slot 100
+    fn guard() -> UpgradeGuard {
+        UpgradeGuard {
+            admin: Pubkey::new_unique(),
+            program_data: Pubkey::new_unique(),
+            checked_slot: 100,
+            healthy: true,
+            checked_at: 1_700_000_000,
+            vault_count: 2,
+            index_fund_count: 1,
+            checking_slot: 100,
+            vaults_checked: 2,
+            index_funds_checked: 1,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn a_new_deploy_blocks_until_checked() {
+        let guard = guard();
+        assert!(guard.require_healthy(100).is_ok());
+        assert!(guard.require_healthy(150).is_err());
+    }
+
+    #[test]
+    fn pages_walk_vaults_then_funds_in_order() {
+        let mut guard = guard();
+        guard.start_page(150);
+        assert!(!guard.is_complete());
+        // no fund before every vault, and no vault skipped or repeated
+        assert!(guard.record_index_fund(0).is_err());
+        assert!(guard.record_vault(1).is_err());
+        guard.record_vault(0).unwrap();
+        assert!(guard.record_vault(0).is_err());
+
+        // the next page for the same deploy carries on where this one stopped
+        guard.start_page(150);
+        guard.record_vault(1).unwrap();
+        guard.record_index_fund(0).unwrap();
+        assert!(guard.is_complete());
+    }
+
+    #[test]
+    fn another_deploy_restarts_the_walk() {
+        let mut guard = guard();
+        guard.start_page(150);
+        guard.record_vault(0).unwrap();
+        guard.start_page(200);
+        assert_eq!(guard.vaults_checked, 0);
+        assert!(guard.record_vault(1).is_err());
+    }
+
+    #[test]
+    fn seqs_count_up() {
+        let mut guard = guard();
+        assert_eq!(guard.next_vault_seq().unwrap(), 2);
+        assert_eq!(guard.next_index_fund_seq().unwrap(), 1);
+        assert_eq!(guard.vault_count, 3);
+        assert_eq!(guard.index_fund_count, 2);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::constants::VAULT_LOG_SEED;
+use crate::errors::TradingBotError;
+use crate::events::VaultLogAppended;
+use crate::math;
+use crate::state::TradingVault;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+pub const RECENT_LOG_ENTRIES: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum VaultOp {
+    // unwritten slot
+    #[default]
+    None,
+    Deposit,
+    Withdraw,
+    AllowlistAdd,
+    AllowlistRemove,
+    AllowlistEnforce,
+    PlaceOrder,
+    FillOrder,
+    CancelOrder,
+    ExpireOrder,
+    // an internal crossing or portfolio netting moved funds between the owner's vaults
+    Cross,
+    // a preset instruction (treasury plan, leverage loop, depeg rotation, peg arb, covered call,
+    // principal note, dust sweep, index fund) moved the vault's funds or changed its settings
+    Preset,
+    OwnerChange,
+}
+
+// `key` and `amount` are the instruction's parameters: the depositor or the destination's owner
+// and the amount moved, the allowlisted address, whether the allowlist is enforced, the order
+// and what it reserved, spent or released, the preset account and the amount it moved, or the
+// new owner. `reserved` and `balance` are the vault's state after
+// the instruction; `balance` is None where the instruction doesn't read the vault's token account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct VaultLogEntry {
+    pub prev_hash: [u8; 32],
+    pub op: VaultOp,
+    pub slot: u64,
+    pub key: Pubkey,
+    pub amount: u64,
+    pub reserved: u64,
+    pub balance: Option<u64>,
+}
+
+impl VaultLogEntry {
+    // keccak of the entry's Borsh encoding, so an indexer can recompute it from the event
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        let data = self
+            .try_to_vec()
+            .map_err(|_| TradingBotError::InvalidCalculation)?;
+        Ok(keccak::hash(&data).0)
+    }
+}
+
+// A vault's replay log: every logged instruction appends an entry chained to the previous one's
+// hash and emits it, and only the chain head and a short ring of recent entries stay in the
+// account. Each entry carries the vault's reserved amount and, where read, its balance after the
+// instruction, so a change no entry accounts for shows up as a gap between consecutive entries.
+#[account]
+#[derive(InitSpace)]
+pub struct VaultLog {
+    pub trading_vault: Pubkey,
+    pub entry_count: u64,
+    pub head_hash: [u8; 32],
+    pub head: u8,
+    pub recent: [VaultLogEntry; RECENT_LOG_ENTRIES],
+    pub bump: u8,
+}
+
+impl VaultLog {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn append(
+        &mut self,
+        op: VaultOp,
+        key: Pubkey,
+        amount: u64,
+        vault: &TradingVault,
+        balance: Option<u64>,
+    ) -> Result<()> {
+        let entry = VaultLogEntry {
+            prev_hash: self.head_hash,
+            op,
+            slot: Clock::get()?.slot,
+            key,
+            amount,
+            reserved: vault.reserved,
+            balance,
+        };
+        self.head_hash = entry.hash()?;
+        self.recent[self.head as usize] = entry;
+        self.head = ((self.head as usize + 1) % RECENT_LOG_ENTRIES) as u8;
+        self.entry_count = math::checked_add(self.entry_count, 1)?;
+
+        emit!(VaultLogAppended {
+            trading_vault: self.trading_vault,
+            index: self.entry_count - 1,
+            entry,
+            hash: self.head_hash,
+        });
+
+        Ok(())
+    }
+
+    // Instructions that change a vault always pass its log PDA and append once the owner has
+    // initialised it, so no caller can leave a change out of the chain
+    pub fn append_if_initialized(
+        account: &AccountInfo,
+        op: VaultOp,
+        key: Pubkey,
+        amount: u64,
+        vault: &TradingVault,
+        balance: Option<u64>,
+    ) -> Result<()> {
+        if account.owner != &crate::ID {
+            return Ok(());
+        }
+        let mut data = account.try_borrow_mut_data()?;
+        let mut log = Self::try_deserialize(&mut &data[..])?;
+        log.append(op, key, amount, vault, balance)?;
+        log.try_serialize(&mut &mut data[..])
+    }
+
+    // Logs passed as remaining accounts aren't checked by Anchor's seeds constraint
+    pub fn require_address(account: &AccountInfo, trading_vault: &Pubkey) -> Result<()> {
+        let (address, _) =
+            Pubkey::find_program_address(&[VAULT_LOG_SEED, trading_vault.as_ref()], &crate::ID);
+        require_keys_eq!(account.key(), address, TradingBotError::InvalidVaultLog);
+        Ok(())
+    }
+}
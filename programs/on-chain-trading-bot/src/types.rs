@@ -1,4 +1,152 @@
+use crate::errors::TradingBotError;
+use crate::math;
+use crate::state::HedgeVenue;
 use anchor_lang::prelude::*;
+use pyth_sdk_solana::Price;
+
+// Canonical venue list shared by the arbitrage, liquidity and account management modules
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DexType {
+    Raydium,
+    Jupiter,
+    Serum,
+}
+
+impl DexType {
+    pub const COUNT: usize = 3;
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<HedgeVenue> for DexType {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(venue: HedgeVenue) -> Result<Self> {
+        match venue {
+            HedgeVenue::Raydium => Ok(DexType::Raydium),
+            HedgeVenue::Jupiter => Ok(DexType::Jupiter),
+            HedgeVenue::DriftPerp { .. } => Err(TradingBotError::InvalidDexType.into()),
+        }
+    }
+}
+
+impl TryFrom<DexType> for HedgeVenue {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(dex: DexType) -> Result<Self> {
+        match dex {
+            DexType::Raydium => Ok(HedgeVenue::Raydium),
+            DexType::Jupiter => Ok(HedgeVenue::Jupiter),
+            DexType::Serum => Err(TradingBotError::InvalidDexType.into()),
+        }
+    }
+}
+
+// What a schedule's times count. `UnixTime` is the cluster's voted clock, which drifts and which
+// leaders can nudge; `Slot` counts slots, with `milli_slots_per_second` turning durations the
+// program sets in seconds, such as the volume window's hour or a session's lifetime, into slots.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum TimeBasis {
+    UnixTime,
+    Slot { milli_slots_per_second: u32 },
+}
+
+impl TimeBasis {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            TimeBasis::UnixTime => true,
+            TimeBasis::Slot {
+                milli_slots_per_second,
+            } => *milli_slots_per_second > 0,
+        }
+    }
+
+    pub fn now(&self, clock: &Clock) -> i64 {
+        match self {
+            TimeBasis::UnixTime => clock.unix_timestamp,
+            TimeBasis::Slot { .. } => clock.slot as i64,
+        }
+    }
+
+    pub fn current(&self) -> Result<i64> {
+        Ok(self.now(&Clock::get()?))
+    }
+
+    // `seconds` in this basis' units, never less than one
+    pub fn from_seconds(&self, seconds: i64) -> i64 {
+        match self {
+            TimeBasis::UnixTime => seconds,
+            TimeBasis::Slot {
+                milli_slots_per_second,
+            } => (seconds as i128 * *milli_slots_per_second as i128 / 1000)
+                .clamp(1, i64::MAX as i128) as i64,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct TokenPair {
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+}
+
+impl TokenPair {
+    pub fn new(base_mint: Pubkey, quote_mint: Pubkey) -> Self {
+        Self {
+            base_mint,
+            quote_mint,
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self::new(self.quote_mint, self.base_mint)
+    }
+}
+
+// Direction of a strategy trade on its pair. On an AMM a buy spends the quote mint for base and a
+// sell the reverse; on an order book they are bids and asks.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+impl TradeSide {
+    pub fn is_buy(&self) -> bool {
+        *self == TradeSide::Buy
+    }
+
+    pub fn input_mint(&self, pair: &TokenPair) -> Pubkey {
+        match self {
+            TradeSide::Buy => pair.quote_mint,
+            TradeSide::Sell => pair.base_mint,
+        }
+    }
+
+    pub fn output_mint(&self, pair: &TokenPair) -> Pubkey {
+        match self {
+            TradeSide::Buy => pair.base_mint,
+            TradeSide::Sell => pair.quote_mint,
+        }
+    }
+
+    pub fn serum_side(&self) -> serum_dex::matching::Side {
+        match self {
+            TradeSide::Buy => serum_dex::matching::Side::Bid,
+            TradeSide::Sell => serum_dex::matching::Side::Ask,
+        }
+    }
+
+    // Buys may pay up to the limit, sells must get at least it
+    pub fn within_limit(&self, price: u64, limit_price: u64) -> bool {
+        match self {
+            TradeSide::Buy => price <= limit_price,
+            TradeSide::Sell => price >= limit_price,
+        }
+    }
+}
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct SwapRoute {
@@ -9,13 +157,26 @@ pub struct SwapRoute {
     pub slippage_bps: u16,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub struct PriceData {
     pub price: u64,
     pub confidence: u64,
     pub timestamp: i64,
 }
 
+// Oracle prices are signed; anything below zero is rejected
+impl TryFrom<Price> for PriceData {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(price: Price) -> Result<Self> {
+        Ok(Self {
+            price: math::checked_as_u64(price.price)?,
+            confidence: price.conf,
+            timestamp: price.publish_time,
+        })
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct PoolInfo {
     pub token_a: Pubkey,
@@ -40,4 +201,11 @@ pub struct TradeConfig {
     pub max_slippage: u16,
     pub deadline: i64,
     pub min_output: u64,
-} 
\ No newline at end of file
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum StrategyType {
+    Custom,
+    Dca,
+    MarketMaking,
+}
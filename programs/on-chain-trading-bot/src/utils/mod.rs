@@ -1,6 +1,7 @@
 pub mod pubkey_ext;
 
 use anchor_lang::prelude::*;
+use crate::{errors::TradingBotError, constants::*, types::TimeBasis};
 use std::io::{self, Write};
 
 pub trait WritePubkey {
@@ -11,4 +12,66 @@ impl<W: Write> WritePubkey for W {
     fn write_pubkey(&mut self, pubkey: &Pubkey) -> io::Result<()> {
         self.write_all(pubkey.as_ref())
     }
+} 
+
+pub fn validate_slippage(slippage_bps: u16) -> Result<()> {
+    require!(
+        slippage_bps <= MAX_SLIPPAGE_BPS,
+        TradingBotError::SlippageExceeded
+    );
+    Ok(())
+}
+
+// Keeper transactions carry the latest time they may land; one delayed past it would otherwise
+// execute against whatever the price has become since it was signed
+pub fn validate_deadline(deadline: i64) -> Result<()> {
+    validate_deadline_in(deadline, &TimeBasis::UnixTime)
+}
+
+// A deadline counted in `basis`, e.g. a slot for strategies scheduled in slots
+pub fn validate_deadline_in(deadline: i64, basis: &TimeBasis) -> Result<()> {
+    let current_time = basis.current()?;
+    require!(deadline >= current_time, TradingBotError::DeadlineExceeded);
+    require!(
+        deadline <= current_time + basis.from_seconds(MAX_DEADLINE),
+        TradingBotError::InvalidDeadline
+    );
+    Ok(())
+}
+
+pub fn calculate_price_impact(
+    amount_in: u64,
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+) -> Result<u16> {
+    let expected_out = amount_in
+        .checked_mul(reserve_out)
+        .ok_or(TradingBotError::Overflow)?
+        .checked_div(reserve_in)
+        .ok_or(TradingBotError::Overflow)?;
+
+    let impact = expected_out
+        .checked_sub(amount_out)
+        .ok_or(TradingBotError::Overflow)?
+        .checked_mul(10000)
+        .ok_or(TradingBotError::Overflow)?
+        .checked_div(expected_out)
+        .ok_or(TradingBotError::Overflow)?;
+
+    Ok(impact as u16)
+}
+
+// TICK_SPACING is 1 for now, which makes the spacing check a no-op until it's raised
+#[allow(clippy::modulo_one)]
+pub fn validate_tick_range(lower: i32, upper: i32) -> Result<()> {
+    require!(
+        lower >= MIN_TICK && upper <= MAX_TICK && lower < upper,
+        TradingBotError::InvalidTickRange
+    );
+    require!(
+        lower % TICK_SPACING == 0 && upper % TICK_SPACING == 0,
+        TradingBotError::InvalidTickRange
+    );
+    Ok(())
 } 
\ No newline at end of file
@@ -13,16 +13,14 @@ impl BorshSerialize for PubkeyWrapper {
 }
 
 impl BorshDeserialize for PubkeyWrapper {
-    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
-        if buf.len() < 32 {
-            return Err(io::Error::new(
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let mut bytes = [0u8; 32];
+        reader.read_exact(&mut bytes).map_err(|_| {
+            io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "Insufficient length for Pubkey",
-            ));
-        }
-        let mut bytes = [0u8; 32];
-        bytes.copy_from_slice(&buf[..32]);
-        *buf = &buf[32..];
+            )
+        })?;
         Ok(PubkeyWrapper(Pubkey::new_from_array(bytes)))
     }
 }
@@ -0,0 +1,175 @@
+use crate::constants::PRICE_PRECISION;
+use crate::math::{self, tick};
+use anchor_lang::prelude::*;
+
+// Lending protocols quote collateral exchange rates as 18-decimal fixed point
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+// All values are in native quote units; `mid`/`price` are quote per base scaled by PRICE_PRECISION
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum PositionValuation {
+    Token {
+        amount: u64,
+        price: u64,
+    },
+    SerumOpenOrders {
+        base_total: u64,
+        quote_total: u64,
+        mid: u64,
+    },
+    RaydiumLp {
+        lp_amount: u64,
+        lp_supply: u64,
+        reserve_base: u64,
+        reserve_quote: u64,
+        mid: u64,
+    },
+    Clmm {
+        liquidity: u128,
+        lower_tick: i32,
+        upper_tick: i32,
+        mid: u64,
+    },
+    LendingDeposit {
+        collateral_amount: u64,
+        exchange_rate_wad: u128,
+        price: u64,
+    },
+    Perp {
+        size: i64,
+        entry_price: u64,
+        mark_price: u64,
+        collateral: u64,
+    },
+}
+
+pub struct Valuation;
+
+impl Valuation {
+    pub fn quote_value(base_amount: u64, price: u64) -> Result<u64> {
+        let value = math::checked_div(
+            math::checked_mul(base_amount as u128, price as u128)?,
+            PRICE_PRECISION as u128,
+        )?;
+        math::checked_as_u64(value)
+    }
+
+    // Open orders are worth their free plus locked balances on both sides
+    pub fn serum_open_orders(base_total: u64, quote_total: u64, mid: u64) -> Result<u64> {
+        math::checked_add(Self::quote_value(base_total, mid)?, quote_total)
+    }
+
+    // Pro-rata share of the pool's fair value, 2·sqrt(k·Pa·Pb) with the quote as numeraire. Only
+    // the reserves' product k is trusted: a pool pushed off the oracle price within a transaction
+    // keeps k, so its reserves are revalued to what they'd be at the oracle price instead of
+    // being taken at face value.
+    pub fn raydium_lp(
+        lp_amount: u64,
+        lp_supply: u64,
+        reserve_base: u64,
+        reserve_quote: u64,
+        mid: u64,
+    ) -> Result<u64> {
+        if lp_supply == 0 {
+            return Ok(0);
+        }
+        // k·Pa in quote units squared, as the base reserve at the oracle times the quote reserve
+        let k_at_mid = math::checked_mul(
+            Self::quote_value(reserve_base, mid)? as u128,
+            reserve_quote as u128,
+        )?;
+        let pool_value = math::checked_mul(tick::isqrt(k_at_mid), 2)?;
+        math::checked_as_u64(math::checked_div(
+            math::checked_mul(pool_value, lp_amount as u128)?,
+            lp_supply as u128,
+        )?)
+    }
+
+    // Token amounts are derived at the oracle price so a skewed pool can't inflate the position
+    pub fn clmm(liquidity: u128, lower_tick: i32, upper_tick: i32, mid: u64) -> Result<u64> {
+        let (amount_base, amount_quote) = tick::amounts_for_liquidity(
+            liquidity,
+            tick::sqrt_price_from_price(mid)?,
+            tick::sqrt_price_at_tick(lower_tick)?,
+            tick::sqrt_price_at_tick(upper_tick)?,
+        )?;
+        math::checked_add(Self::quote_value(amount_base, mid)?, amount_quote)
+    }
+
+    pub fn lending_deposit(
+        collateral_amount: u64,
+        exchange_rate_wad: u128,
+        price: u64,
+    ) -> Result<u64> {
+        let underlying = math::checked_div(
+            math::checked_mul(collateral_amount as u128, exchange_rate_wad)?,
+            WAD,
+        )?;
+        Self::quote_value(math::checked_as_u64(underlying)?, price)
+    }
+
+    // Mark-to-oracle P&L of a perp position, positive for longs when the mark is above entry
+    pub fn perp_pnl(size: i64, entry_price: u64, mark_price: u64) -> Result<i64> {
+        let moved = mark_price.abs_diff(entry_price);
+        let move_value = Self::quote_value(size.unsigned_abs(), moved)? as i64;
+        Ok(if (size > 0) == (mark_price > entry_price) {
+            move_value
+        } else {
+            -move_value
+        })
+    }
+
+    pub fn value(position: &PositionValuation) -> Result<i128> {
+        let value = match *position {
+            PositionValuation::Token { amount, price } => Self::quote_value(amount, price)? as i128,
+            PositionValuation::SerumOpenOrders {
+                base_total,
+                quote_total,
+                mid,
+            } => Self::serum_open_orders(base_total, quote_total, mid)? as i128,
+            PositionValuation::RaydiumLp {
+                lp_amount,
+                lp_supply,
+                reserve_base,
+                reserve_quote,
+                mid,
+            } => Self::raydium_lp(lp_amount, lp_supply, reserve_base, reserve_quote, mid)? as i128,
+            PositionValuation::Clmm {
+                liquidity,
+                lower_tick,
+                upper_tick,
+                mid,
+            } => Self::clmm(liquidity, lower_tick, upper_tick, mid)? as i128,
+            PositionValuation::LendingDeposit {
+                collateral_amount,
+                exchange_rate_wad,
+                price,
+            } => Self::lending_deposit(collateral_amount, exchange_rate_wad, price)? as i128,
+            PositionValuation::Perp {
+                size,
+                entry_price,
+                mark_price,
+                collateral,
+            } => {
+                if size == 0 {
+                    collateral as i128
+                } else {
+                    math::checked_add(
+                        collateral as i128,
+                        Self::perp_pnl(size, entry_price, mark_price)? as i128,
+                    )?
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    // Single NAV figure across every position; an underwater perp can't take it below zero
+    pub fn nav(positions: &[PositionValuation]) -> Result<u64> {
+        let mut total: i128 = 0;
+        for position in positions.iter() {
+            total = math::checked_add(total, Self::value(position)?)?;
+        }
+        math::checked_as_u64(total.max(0))
+    }
+}
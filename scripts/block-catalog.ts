@@ -0,0 +1,106 @@
+// Builds target/idl/block_catalog.json from the IDL written by `anchor build`:
+// every block kind the program executes with the config fields it requires or
+// reads. Requirements come from the `Required by:` / `Used by:` tags in the
+// BlockConfig field docs. Run with `anchor run catalog`.
+import * as fs from 'fs';
+import * as path from 'path';
+
+const IDL_PATH = path.join('target', 'idl', 'on_chain_trading_bot.json');
+const CATALOG_PATH = path.join('target', 'idl', 'block_catalog.json');
+
+// block types whose kind is picked by a second enum
+const SUBTYPES: { [blockType: string]: string } = {
+  Trigger: 'TriggerType',
+  Condition: 'ConditionType',
+  Action: 'ActionType',
+};
+
+type BlockSpec = { required: string[]; optional: string[] };
+
+function findType(idl: any, name: string): any {
+  const ty = idl.types.find((t: any) => t.name === name);
+  if (!ty) {
+    throw new Error(`${name} missing from ${IDL_PATH}, run anchor build first`);
+  }
+  return ty.type;
+}
+
+function variants(idl: any, name: string): string[] {
+  return findType(idl, name).variants.map((v: any) => v.name);
+}
+
+// "Trigger.Price" matches its own kind, "Action" every action kind
+function matches(tag: string, kind: string): boolean {
+  return tag === kind || kind.startsWith(`${tag}.`);
+}
+
+function tags(docs: string[] | undefined, prefix: string): string[] {
+  return (docs || [])
+    .filter((line) => line.startsWith(prefix))
+    .reduce(
+      (all: string[], line) => all.concat(line.slice(prefix.length).split(',')),
+      []
+    )
+    .map((tag) => tag.trim())
+    .filter((tag) => tag.length > 0);
+}
+
+function collectFields(
+  idl: any,
+  typeName: string,
+  prefix: string,
+  out: { field: string; docs?: string[] }[]
+) {
+  for (const field of findType(idl, typeName).fields) {
+    const name = prefix + field.name;
+    if (field.type && field.type.defined) {
+      collectFields(idl, field.type.defined, `${name}.`, out);
+    } else {
+      out.push({ field: name, docs: field.docs });
+    }
+  }
+}
+
+function main() {
+  const idl = JSON.parse(fs.readFileSync(IDL_PATH, 'utf8'));
+
+  const kinds: string[] = [];
+  for (const blockType of variants(idl, 'BlockType')) {
+    if (SUBTYPES[blockType]) {
+      for (const sub of variants(idl, SUBTYPES[blockType])) {
+        kinds.push(`${blockType}.${sub}`);
+      }
+    } else {
+      kinds.push(blockType);
+    }
+  }
+
+  const fields: { field: string; docs?: string[] }[] = [];
+  collectFields(idl, 'BlockConfig', '', fields);
+
+  const blocks: { [kind: string]: BlockSpec } = {};
+  for (const kind of kinds) {
+    const tagged = (prefix: string) =>
+      fields
+        .filter((f) => tags(f.docs, prefix).some((t) => matches(t, kind)))
+        .map((f) => f.field);
+    blocks[kind] = {
+      required: tagged('Required by:'),
+      optional: tagged('Used by:'),
+    };
+  }
+
+  const catalog = {
+    version: idl.version,
+    blockTypes: variants(idl, 'BlockType'),
+    triggerTypes: variants(idl, 'TriggerType'),
+    conditionTypes: variants(idl, 'ConditionType'),
+    actionTypes: variants(idl, 'ActionType'),
+    priceConditionTypes: variants(idl, 'PriceConditionType'),
+    blocks,
+  };
+  fs.writeFileSync(CATALOG_PATH, JSON.stringify(catalog, null, 2) + '\n');
+  console.log(`Wrote ${kinds.length} block kinds to ${CATALOG_PATH}`);
+}
+
+main();
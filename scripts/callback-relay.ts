@@ -0,0 +1,146 @@
+// Relays the program's callback requests to Telegram and Discord. Each pass it
+// fires every armed rule whose condition holds on the strategies this wallet
+// relays for, re-arms rules that have cleared, then pushes every pending
+// CallbackRequest to its channel and acknowledges it, which closes the request.
+// Requests that fail to deliver stay open and are retried on the next pass.
+// Run with `anchor run relay`; configured through the environment:
+//   TELEGRAM_BOT_TOKEN, TELEGRAM_CHAT_ID  Telegram bot and chat to post to
+//   DISCORD_WEBHOOK_URL                   Discord webhook to post to
+//   RELAY_INTERVAL_MS                     pause between passes, default 30000
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { OnChainTradingBot } from '../target/types/on_chain_trading_bot';
+
+const CALLBACK_REQUEST_SEED = Buffer.from('callback-request');
+// CallbackConfig: discriminator, strategy, owner, then relay
+const RELAY_OFFSET = 8 + 32 + 32;
+const INTERVAL_MS = Number(process.env.RELAY_INTERVAL_MS || 30000);
+
+function requestAddress(
+  program: Program<OnChainTradingBot>,
+  config: PublicKey,
+  seq: anchor.BN
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [CALLBACK_REQUEST_SEED, config.toBuffer(), seq.toArrayLike(Buffer, 'le', 8)],
+    program.programId
+  )[0];
+}
+
+function describe(request: any): string {
+  const [kind, fields] = Object.entries(request.condition)[0] as [string, any];
+  const threshold = fields && Object.values(fields)[0];
+  return (
+    `Strategy ${request.strategy.toBase58()}: ${kind}` +
+    (threshold !== undefined ? ` (threshold ${threshold.toString()})` : '') +
+    `, observed ${request.value.toString()}` +
+    ` at ${new Date(request.createdAt.toNumber() * 1000).toISOString()}`
+  );
+}
+
+async function post(url: string, body: object) {
+  const res = await fetch(url, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(body),
+  });
+  if (!res.ok) {
+    throw new Error(`${url.split('/')[2]} answered ${res.status}`);
+  }
+}
+
+async function deliver(request: any) {
+  const text = describe(request);
+  if ('telegram' in request.channel) {
+    const { TELEGRAM_BOT_TOKEN, TELEGRAM_CHAT_ID } = process.env;
+    if (!TELEGRAM_BOT_TOKEN || !TELEGRAM_CHAT_ID) {
+      throw new Error('TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID are not set');
+    }
+    await post(`https://api.telegram.org/bot${TELEGRAM_BOT_TOKEN}/sendMessage`, {
+      chat_id: TELEGRAM_CHAT_ID,
+      text,
+    });
+  } else {
+    const { DISCORD_WEBHOOK_URL } = process.env;
+    if (!DISCORD_WEBHOOK_URL) {
+      throw new Error('DISCORD_WEBHOOK_URL is not set');
+    }
+    await post(DISCORD_WEBHOOK_URL, { content: text });
+  }
+}
+
+// A rule whose condition doesn't hold fails simulation, so only holding rules
+// are sent and pay rent
+async function fireRules(program: Program<OnChainTradingBot>, configKey: PublicKey) {
+  const config = await program.account.callbackConfig.fetch(configKey);
+  let seq = config.nextSeq;
+  for (let index = 0; index < config.rules.length; index++) {
+    const rule = config.rules[index];
+    if (!rule || !rule.armed) {
+      continue;
+    }
+    const call = program.methods.fireCallback(index).accountsPartial({
+      payer: program.provider.publicKey,
+      strategy: config.strategy,
+      config: configKey,
+      request: requestAddress(program, configKey, seq),
+    });
+    try {
+      await call.simulate();
+    } catch {
+      continue;
+    }
+    await call.rpc();
+    seq = seq.addn(1);
+  }
+  await program.methods
+    .rearmCallbacks()
+    .accountsPartial({ strategy: config.strategy, config: configKey })
+    .rpc();
+}
+
+async function pass(program: Program<OnChainTradingBot>) {
+  const relay = program.provider.publicKey!;
+  const configs = await program.account.callbackConfig.all([
+    { memcmp: { offset: RELAY_OFFSET, bytes: relay.toBase58() } },
+  ]);
+  for (const { publicKey } of configs) {
+    await fireRules(program, publicKey).catch((err) =>
+      console.error(`Firing ${publicKey.toBase58()} failed: ${err}`)
+    );
+  }
+
+  const relayed = new Set(configs.map((c) => c.publicKey.toBase58()));
+  const requests = (await program.account.callbackRequest.all())
+    .filter((r) => relayed.has(r.account.config.toBase58()))
+    .sort((a, b) => a.account.createdAt.cmp(b.account.createdAt));
+  for (const { publicKey, account } of requests) {
+    try {
+      await deliver(account);
+      await program.methods
+        .ackCallbackRequest()
+        .accountsPartial({
+          relay,
+          config: account.config,
+          request: publicKey,
+          payer: account.payer,
+        })
+        .rpc();
+      console.log(`Relayed #${account.seq.toString()}: ${describe(account)}`);
+    } catch (err) {
+      console.error(`Request ${publicKey.toBase58()} left open: ${err}`);
+    }
+  }
+}
+
+async function main() {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.OnChainTradingBot as Program<OnChainTradingBot>;
+  for (;;) {
+    await pass(program).catch((err) => console.error(`Pass failed: ${err}`));
+    await new Promise((resolve) => setTimeout(resolve, INTERVAL_MS));
+  }
+}
+
+main();
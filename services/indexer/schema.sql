@@ -0,0 +1,108 @@
+-- Applied on every start, so everything is IF NOT EXISTS. Event rows are keyed by the
+-- transaction signature and the event's position in its logs, which makes re-indexing a
+-- transaction a no-op.
+
+CREATE TABLE IF NOT EXISTS trades (
+    id BIGSERIAL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    event_index INT NOT NULL,
+    slot BIGINT NOT NULL,
+    owner TEXT NOT NULL,
+    history_index BIGINT NOT NULL,
+    pair TEXT NOT NULL,
+    is_buy BOOLEAN NOT NULL,
+    size BIGINT NOT NULL,
+    price BIGINT NOT NULL,
+    fee BIGINT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS trades_owner ON trades (owner, id DESC);
+CREATE INDEX IF NOT EXISTS trades_pair ON trades (pair, timestamp);
+
+CREATE TABLE IF NOT EXISTS quote_fills (
+    id BIGSERIAL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    event_index INT NOT NULL,
+    slot BIGINT NOT NULL,
+    market_maker TEXT NOT NULL,
+    seq BIGINT NOT NULL,
+    client_order_id BIGINT NOT NULL,
+    is_bid BOOLEAN NOT NULL,
+    base_amount BIGINT NOT NULL,
+    quote_amount BIGINT NOT NULL,
+    slippage_bps BIGINT,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS quote_fills_market_maker ON quote_fills (market_maker, id DESC);
+
+CREATE TABLE IF NOT EXISTS position_changes (
+    id BIGSERIAL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    event_index INT NOT NULL,
+    slot BIGINT NOT NULL,
+    owner TEXT NOT NULL,
+    position_id TEXT NOT NULL,
+    dex TEXT NOT NULL,
+    reduced BIGINT NOT NULL,
+    remaining BIGINT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS position_changes_owner ON position_changes (owner, position_id, id DESC);
+
+CREATE TABLE IF NOT EXISTS nav_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    event_index INT NOT NULL,
+    slot BIGINT NOT NULL,
+    strategy TEXT NOT NULL,
+    nav BIGINT NOT NULL,
+    return_bps BIGINT NOT NULL,
+    max_drawdown_bps INT NOT NULL,
+    alpha_vs_hodl_bps BIGINT NOT NULL,
+    alpha_vs_sol_bps BIGINT,
+    timestamp BIGINT NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS nav_snapshots_strategy ON nav_snapshots (strategy, timestamp);
+
+-- keeper price samples, keyed by base mint like limit order fills so both feed the same candles
+CREATE TABLE IF NOT EXISTS pool_samples (
+    id BIGSERIAL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    event_index INT NOT NULL,
+    slot BIGINT NOT NULL,
+    pool TEXT NOT NULL,
+    dex TEXT NOT NULL,
+    base_mint TEXT NOT NULL,
+    quote_mint TEXT NOT NULL,
+    price BIGINT NOT NULL,
+    tvl BIGINT NOT NULL,
+    apr_bps BIGINT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS pool_samples_base_mint ON pool_samples (base_mint, timestamp);
+
+-- latest polled state of every Strategy account
+CREATE TABLE IF NOT EXISTS strategies (
+    address TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    strategy_id TEXT NOT NULL,
+    is_active BOOLEAN NOT NULL,
+    total_trades BIGINT NOT NULL,
+    total_profit_loss BIGINT NOT NULL,
+    max_drawdown BIGINT NOT NULL,
+    last_executed_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL,
+    slot BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS strategies_owner ON strategies (owner);
+
+-- newest transaction indexed, where backfill resumes after a restart
+CREATE TABLE IF NOT EXISTS cursor (
+    id SMALLINT PRIMARY KEY CHECK (id = 0),
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL
+);
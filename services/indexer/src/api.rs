@@ -0,0 +1,138 @@
+use crate::store::Store;
+use crate::types::{
+    Candle, CandleQuery, NavPoint, Page, Position, QuoteFill, Range, StrategyFilter,
+    StrategySnapshot, Trade,
+};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn router(store: Arc<Store>) -> Router {
+    Router::new()
+        .route("/strategies", get(strategies))
+        .route("/strategies/:address", get(strategy))
+        .route("/strategies/:address/nav", get(nav))
+        .route("/owners/:owner/trades", get(trades))
+        .route("/owners/:owner/positions", get(positions))
+        .route("/market-makers/:address/fills", get(quote_fills))
+        .route("/candles/:pair", get(candles))
+        .with_state(store)
+}
+
+pub struct ApiError(StatusCode, String);
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        eprintln!("Query failed: {}", err);
+        ApiError(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "query failed".to_string(),
+        )
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+// Rejects anything that isn't a base58 address before it reaches a query
+fn address(value: &str) -> Result<&str, ApiError> {
+    Pubkey::from_str(value).map(|_| value).map_err(|_| {
+        ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("{} is not an address", value),
+        )
+    })
+}
+
+async fn strategies(
+    State(store): State<Arc<Store>>,
+    Query(filter): Query<StrategyFilter>,
+) -> ApiResult<Vec<StrategySnapshot>> {
+    let owner = filter.owner.as_deref().map(address).transpose()?;
+    Ok(Json(store.strategies(owner).await?))
+}
+
+async fn strategy(
+    State(store): State<Arc<Store>>,
+    Path(strategy): Path<String>,
+) -> ApiResult<StrategySnapshot> {
+    store
+        .strategy(address(&strategy)?)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, "strategy not indexed".to_string()))
+}
+
+async fn nav(
+    State(store): State<Arc<Store>>,
+    Path(strategy): Path<String>,
+    Query(range): Query<Range>,
+) -> ApiResult<Vec<NavPoint>> {
+    Ok(Json(store.nav(address(&strategy)?, &range).await?))
+}
+
+async fn trades(
+    State(store): State<Arc<Store>>,
+    Path(owner): Path<String>,
+    Query(page): Query<Page>,
+) -> ApiResult<Vec<Trade>> {
+    Ok(Json(store.trades(address(&owner)?, &page).await?))
+}
+
+async fn positions(
+    State(store): State<Arc<Store>>,
+    Path(owner): Path<String>,
+) -> ApiResult<Vec<Position>> {
+    Ok(Json(store.positions(address(&owner)?).await?))
+}
+
+async fn quote_fills(
+    State(store): State<Arc<Store>>,
+    Path(market_maker): Path<String>,
+    Query(page): Query<Page>,
+) -> ApiResult<Vec<QuoteFill>> {
+    Ok(Json(
+        store.quote_fills(address(&market_maker)?, &page).await?,
+    ))
+}
+
+async fn candles(
+    State(store): State<Arc<Store>>,
+    Path(pair): Path<String>,
+    Query(query): Query<CandleQuery>,
+) -> ApiResult<Vec<Candle>> {
+    let interval = query.interval().ok_or_else(|| {
+        ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("interval must be one of {:?}", CandleQuery::INTERVALS),
+        )
+    })?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default();
+    let (from, to) = query.range(interval, now).ok_or_else(|| {
+        ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("range spans more than {} candles", CandleQuery::MAX_CANDLES),
+        )
+    })?;
+    let owner = query.owner.as_deref().map(address).transpose()?;
+    Ok(Json(
+        store
+            .candles(address(&pair)?, owner, interval, from, to)
+            .await?,
+    ))
+}
@@ -0,0 +1,45 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use on_chain_trading_bot::events::{
+    FillRecorded, PoolSampled, PositionReduced, ProfileSnapshotTaken, QuoteFilled,
+};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+// The events the indexer stores; every other event is skipped
+pub enum IndexedEvent {
+    Fill(FillRecorded),
+    QuoteFill(QuoteFilled),
+    PositionReduced(PositionReduced),
+    NavSnapshot(ProfileSnapshotTaken),
+    PoolSample(PoolSampled),
+}
+
+// Events in the order the transaction emitted them, which is what keys their rows
+pub fn parse_logs(logs: &[String]) -> Vec<IndexedEvent> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA))
+        .filter_map(|data| base64::decode(data).ok())
+        .filter_map(|bytes| decode(&bytes))
+        .collect()
+}
+
+fn decode(bytes: &[u8]) -> Option<IndexedEvent> {
+    if bytes.len() < 8 {
+        return None;
+    }
+    let (discriminator, mut data) = bytes.split_at(8);
+    let event = if discriminator == FillRecorded::DISCRIMINATOR {
+        IndexedEvent::Fill(FillRecorded::deserialize(&mut data).ok()?)
+    } else if discriminator == QuoteFilled::DISCRIMINATOR {
+        IndexedEvent::QuoteFill(QuoteFilled::deserialize(&mut data).ok()?)
+    } else if discriminator == PositionReduced::DISCRIMINATOR {
+        IndexedEvent::PositionReduced(PositionReduced::deserialize(&mut data).ok()?)
+    } else if discriminator == ProfileSnapshotTaken::DISCRIMINATOR {
+        IndexedEvent::NavSnapshot(ProfileSnapshotTaken::deserialize(&mut data).ok()?)
+    } else if discriminator == PoolSampled::DISCRIMINATOR {
+        IndexedEvent::PoolSample(PoolSampled::deserialize(&mut data).ok()?)
+    } else {
+        return None;
+    };
+    Some(event)
+}
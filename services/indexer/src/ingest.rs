@@ -0,0 +1,192 @@
+use crate::events::parse_logs;
+use crate::store::Store;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anyhow::Result;
+use futures::StreamExt;
+use on_chain_trading_bot::state::Strategy;
+use on_chain_trading_bot::ID;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig, RpcTransactionLogsConfig,
+    RpcTransactionLogsFilter,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::time::sleep;
+
+// getSignaturesForAddress's largest page
+const SIGNATURE_PAGE: usize = 1000;
+
+pub enum Message {
+    // a (re)connected subscription, so anything sent while it was down is backfilled
+    Connected,
+    Logs {
+        signature: String,
+        slot: u64,
+        logs: Vec<String>,
+    },
+}
+
+// Streams the logs of successful transactions that touch the program, reconnecting when the
+// websocket drops
+pub async fn subscribe(ws_url: String, sender: UnboundedSender<Message>) {
+    loop {
+        if let Err(err) = stream_logs(&ws_url, &sender).await {
+            eprintln!("Log subscription failed: {}", err);
+        }
+        sleep(Duration::from_secs(5)).await;
+    }
+}
+
+async fn stream_logs(ws_url: &str, sender: &UnboundedSender<Message>) -> Result<()> {
+    let client = PubsubClient::new(ws_url).await?;
+    let (mut stream, _unsubscribe) = client
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![ID.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )
+        .await?;
+    sender.send(Message::Connected)?;
+    while let Some(response) = stream.next().await {
+        if response.value.err.is_some() {
+            continue;
+        }
+        sender.send(Message::Logs {
+            signature: response.value.signature,
+            slot: response.context.slot,
+            logs: response.value.logs,
+        })?;
+    }
+    Ok(())
+}
+
+// The single writer, so transactions are stored in the order they arrive and the cursor only moves
+// forward between backfills
+pub async fn write(rpc: RpcClient, store: Arc<Store>, mut receiver: UnboundedReceiver<Message>) {
+    while let Some(message) = receiver.recv().await {
+        let result = match message {
+            Message::Connected => backfill(&rpc, &store)
+                .await
+                .map(|count| println!("Backfilled {} transactions", count)),
+            Message::Logs {
+                signature,
+                slot,
+                logs,
+            } => store.record(&signature, slot, &parse_logs(&logs)).await,
+        };
+        if let Err(err) = result {
+            eprintln!("Indexing failed: {}", err);
+        }
+    }
+}
+
+// Indexes every transaction since the cursor, oldest first; from the program's first
+// transaction when the database is empty
+async fn backfill(rpc: &RpcClient, store: &Store) -> Result<usize> {
+    let until = store
+        .cursor()
+        .await?
+        .map(|signature| Signature::from_str(&signature))
+        .transpose()?;
+    let mut statuses = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc
+            .get_signatures_for_address_with_config(
+                &ID,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURE_PAGE),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await?;
+        let full = page.len() == SIGNATURE_PAGE;
+        before = page
+            .last()
+            .map(|status| Signature::from_str(&status.signature))
+            .transpose()?;
+        statuses.extend(page);
+        if !full {
+            break;
+        }
+    }
+
+    let mut indexed = 0;
+    for status in statuses.iter().rev().filter(|status| status.err.is_none()) {
+        let transaction = rpc
+            .get_transaction_with_config(
+                &Signature::from_str(&status.signature)?,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await?;
+        let logs: Option<Vec<String>> = transaction
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into());
+        store
+            .record(
+                &status.signature,
+                status.slot,
+                &parse_logs(&logs.unwrap_or_default()),
+            )
+            .await?;
+        indexed += 1;
+    }
+    Ok(indexed)
+}
+
+// Strategy state changes without an event, so accounts are snapshotted on an interval instead
+pub async fn poll_strategies(rpc: RpcClient, store: Arc<Store>, interval: Duration) {
+    loop {
+        if let Err(err) = snapshot_strategies(&rpc, &store).await {
+            eprintln!("Strategy snapshot failed: {}", err);
+        }
+        sleep(interval).await;
+    }
+}
+
+async fn snapshot_strategies(rpc: &RpcClient, store: &Store) -> Result<()> {
+    let slot = rpc.get_slot().await?;
+    let accounts = rpc
+        .get_program_accounts_with_config(
+            &ID,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    0,
+                    &Strategy::DISCRIMINATOR,
+                ))]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..RpcAccountInfoConfig::default()
+                },
+                with_context: None,
+            },
+        )
+        .await?;
+    for (address, account) in accounts {
+        // accounts written under an older layout are skipped rather than failing the snapshot
+        match Strategy::try_deserialize(&mut account.data.as_slice()) {
+            Ok(strategy) => store.upsert_strategy(&address, &strategy, slot).await?,
+            Err(err) => eprintln!("Skipping strategy {}: {}", address, err),
+        }
+    }
+    Ok(())
+}
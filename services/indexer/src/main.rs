@@ -0,0 +1,49 @@
+// Indexes the program's events and Strategy accounts into Postgres and serves them as JSON, for
+// the history queries the program can't answer itself. Configured through RPC_URL, WS_URL,
+// DATABASE_URL, INDEXER_ADDR and STRATEGY_POLL_SECS; run with `cargo run -p indexer`.
+mod api;
+mod events;
+mod ingest;
+mod store;
+mod types;
+
+use anyhow::Result;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use store::Store;
+use tokio::sync::mpsc;
+
+fn env_or(name: &str, default: &str) -> String {
+    env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let rpc_url = env_or("RPC_URL", "http://127.0.0.1:8899");
+    let ws_url = env_or("WS_URL", "ws://127.0.0.1:8900");
+    let poll_secs: u64 = env_or("STRATEGY_POLL_SECS", "60").parse()?;
+    let addr: SocketAddr = env_or("INDEXER_ADDR", "0.0.0.0:8080").parse()?;
+    let rpc = || RpcClient::new_with_commitment(rpc_url.clone(), CommitmentConfig::confirmed());
+
+    let store = Arc::new(
+        Store::connect(&env_or("DATABASE_URL", "postgres://localhost/trading_bot")).await?,
+    );
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(ingest::subscribe(ws_url, sender));
+    tokio::spawn(ingest::write(rpc(), store.clone(), receiver));
+    tokio::spawn(ingest::poll_strategies(
+        rpc(),
+        store.clone(),
+        Duration::from_secs(poll_secs),
+    ));
+
+    println!("Serving on {}", addr);
+    axum::Server::bind(&addr)
+        .serve(api::router(store).into_make_service())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,387 @@
+use crate::events::IndexedEvent;
+use crate::types::{Candle, NavPoint, Page, Position, QuoteFill, Range, StrategySnapshot, Trade};
+use anyhow::{Context, Result};
+use on_chain_trading_bot::state::Strategy;
+use solana_sdk::pubkey::Pubkey;
+use tokio_postgres::{Client, NoTls, Row};
+
+const SCHEMA: &str = include_str!("../schema.sql");
+
+pub struct Store {
+    client: Client,
+}
+
+// Postgres has no unsigned integers; program amounts above i64::MAX are refused, not wrapped
+fn int(value: u64) -> Result<i64> {
+    i64::try_from(value).context("value out of BIGINT range")
+}
+
+impl Store {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(url, NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(err) = connection.await {
+                eprintln!("Postgres connection closed: {}", err);
+            }
+        });
+        client.batch_execute(SCHEMA).await?;
+        Ok(Self { client })
+    }
+
+    pub async fn cursor(&self) -> Result<Option<String>> {
+        let row = self
+            .client
+            .query_opt("SELECT signature FROM cursor WHERE id = 0", &[])
+            .await?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    // Stores one transaction's events and moves the cursor to it
+    pub async fn record(&self, signature: &str, slot: u64, events: &[IndexedEvent]) -> Result<()> {
+        let slot = int(slot)?;
+        for (index, event) in events.iter().enumerate() {
+            let index = index as i32;
+            match event {
+                IndexedEvent::Fill(fill) => {
+                    self.client
+                        .execute(
+                            "INSERT INTO trades (signature, event_index, slot, owner, history_index, \
+                             pair, is_buy, size, price, fee, timestamp) \
+                             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+                             ON CONFLICT DO NOTHING",
+                            &[
+                                &signature,
+                                &index,
+                                &slot,
+                                &fill.owner.to_string(),
+                                &int(fill.index)?,
+                                &fill.pair.to_string(),
+                                &fill.is_buy,
+                                &int(fill.size)?,
+                                &int(fill.price)?,
+                                &int(fill.fee)?,
+                                &fill.timestamp,
+                            ],
+                        )
+                        .await?;
+                }
+                IndexedEvent::QuoteFill(fill) => {
+                    self.client
+                        .execute(
+                            "INSERT INTO quote_fills (signature, event_index, slot, market_maker, seq, \
+                             client_order_id, is_bid, base_amount, quote_amount, slippage_bps) \
+                             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+                             ON CONFLICT DO NOTHING",
+                            &[
+                                &signature,
+                                &index,
+                                &slot,
+                                &fill.market_maker.to_string(),
+                                &int(fill.seq)?,
+                                &int(fill.client_order_id)?,
+                                &fill.is_bid,
+                                &int(fill.base_amount)?,
+                                &int(fill.quote_amount)?,
+                                &fill.slippage_bps,
+                            ],
+                        )
+                        .await?;
+                }
+                IndexedEvent::PositionReduced(change) => {
+                    self.client
+                        .execute(
+                            "INSERT INTO position_changes (signature, event_index, slot, owner, \
+                             position_id, dex, reduced, remaining, timestamp) \
+                             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+                             ON CONFLICT DO NOTHING",
+                            &[
+                                &signature,
+                                &index,
+                                &slot,
+                                &change.owner.to_string(),
+                                &change.position_id.to_string(),
+                                &format!("{:?}", change.dex),
+                                &int(change.reduced)?,
+                                &int(change.remaining)?,
+                                &change.timestamp,
+                            ],
+                        )
+                        .await?;
+                }
+                IndexedEvent::NavSnapshot(snapshot) => {
+                    self.client
+                        .execute(
+                            "INSERT INTO nav_snapshots (signature, event_index, slot, strategy, nav, \
+                             return_bps, max_drawdown_bps, alpha_vs_hodl_bps, alpha_vs_sol_bps, \
+                             timestamp) \
+                             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+                             ON CONFLICT DO NOTHING",
+                            &[
+                                &signature,
+                                &index,
+                                &slot,
+                                &snapshot.strategy.to_string(),
+                                &int(snapshot.nav)?,
+                                &snapshot.return_bps,
+                                &(snapshot.max_drawdown_bps as i32),
+                                &snapshot.alpha_vs_hodl_bps,
+                                &snapshot.alpha_vs_sol_bps,
+                                &snapshot.timestamp,
+                            ],
+                        )
+                        .await?;
+                }
+                IndexedEvent::PoolSample(sample) => {
+                    self.client
+                        .execute(
+                            "INSERT INTO pool_samples (signature, event_index, slot, pool, dex, \
+                             base_mint, quote_mint, price, tvl, apr_bps, timestamp) \
+                             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+                             ON CONFLICT DO NOTHING",
+                            &[
+                                &signature,
+                                &index,
+                                &slot,
+                                &sample.pool.to_string(),
+                                &format!("{:?}", sample.dex),
+                                &sample.pair.base_mint.to_string(),
+                                &sample.pair.quote_mint.to_string(),
+                                &int(sample.price)?,
+                                &int(sample.tvl)?,
+                                &int(sample.apr_bps)?,
+                                &sample.timestamp,
+                            ],
+                        )
+                        .await?;
+                }
+            }
+        }
+        self.client
+            .execute(
+                "INSERT INTO cursor (id, signature, slot) VALUES (0, $1, $2) \
+                 ON CONFLICT (id) DO UPDATE SET signature = $1, slot = $2",
+                &[&signature, &slot],
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn upsert_strategy(
+        &self,
+        address: &Pubkey,
+        strategy: &Strategy,
+        slot: u64,
+    ) -> Result<()> {
+        let metrics = &strategy.performance_metrics;
+        self.client
+            .execute(
+                "INSERT INTO strategies (address, owner, strategy_id, is_active, total_trades, \
+                 total_profit_loss, max_drawdown, last_executed_at, updated_at, slot) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+                 ON CONFLICT (address) DO UPDATE SET owner = $2, is_active = $4, \
+                 total_trades = $5, total_profit_loss = $6, max_drawdown = $7, \
+                 last_executed_at = $8, updated_at = $9, slot = $10",
+                &[
+                    &address.to_string(),
+                    &strategy.owner.to_string(),
+                    &strategy.strategy_id,
+                    &strategy.is_active,
+                    &int(strategy.total_trades)?,
+                    &metrics.total_profit_loss,
+                    &int(metrics.max_drawdown)?,
+                    &strategy.last_executed_at,
+                    &strategy.updated_at,
+                    &int(slot)?,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn trades(&self, owner: &str, page: &Page) -> Result<Vec<Trade>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT id, signature, owner, pair, is_buy, size, price, fee, timestamp \
+                 FROM trades WHERE owner = $1 AND id < $2 ORDER BY id DESC LIMIT $3",
+                &[&owner, &page.before(), &page.limit()],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Trade {
+                id: row.get(0),
+                signature: row.get(1),
+                owner: row.get(2),
+                pair: row.get(3),
+                is_buy: row.get(4),
+                size: row.get(5),
+                price: row.get(6),
+                fee: row.get(7),
+                timestamp: row.get(8),
+            })
+            .collect())
+    }
+
+    pub async fn quote_fills(&self, market_maker: &str, page: &Page) -> Result<Vec<QuoteFill>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT id, signature, market_maker, seq, client_order_id, is_bid, base_amount, \
+                 quote_amount, slippage_bps, slot \
+                 FROM quote_fills WHERE market_maker = $1 AND id < $2 ORDER BY id DESC LIMIT $3",
+                &[&market_maker, &page.before(), &page.limit()],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| QuoteFill {
+                id: row.get(0),
+                signature: row.get(1),
+                market_maker: row.get(2),
+                seq: row.get(3),
+                client_order_id: row.get(4),
+                is_bid: row.get(5),
+                base_amount: row.get(6),
+                quote_amount: row.get(7),
+                slippage_bps: row.get(8),
+                slot: row.get(9),
+            })
+            .collect())
+    }
+
+    // Positions are only seen once something reduces them; opening one emits no event
+    pub async fn positions(&self, owner: &str) -> Result<Vec<Position>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT DISTINCT ON (position_id) position_id, dex, remaining, reduced, timestamp \
+                 FROM position_changes WHERE owner = $1 ORDER BY position_id, id DESC",
+                &[&owner],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Position {
+                position_id: row.get(0),
+                dex: row.get(1),
+                remaining: row.get(2),
+                last_reduced: row.get(3),
+                updated_at: row.get(4),
+            })
+            .collect())
+    }
+
+    pub async fn nav(&self, strategy: &str, range: &Range) -> Result<Vec<NavPoint>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT nav, return_bps, max_drawdown_bps, alpha_vs_hodl_bps, alpha_vs_sol_bps, \
+                 timestamp FROM nav_snapshots \
+                 WHERE strategy = $1 AND timestamp >= $2 AND timestamp <= $3 ORDER BY timestamp",
+                &[
+                    &strategy,
+                    &range.from.unwrap_or(0),
+                    &range.to.unwrap_or(i64::MAX),
+                ],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| NavPoint {
+                nav: row.get(0),
+                return_bps: row.get(1),
+                max_drawdown_bps: row.get(2),
+                alpha_vs_hodl_bps: row.get(3),
+                alpha_vs_sol_bps: row.get(4),
+                timestamp: row.get(5),
+            })
+            .collect())
+    }
+
+    // OHLCV over fills and pool samples of one base mint, or market for market maker fills.
+    // Prices are quote per base in PRICE_PRECISION on both sides.
+    pub async fn candles(
+        &self,
+        pair: &str,
+        owner: Option<&str>,
+        interval: i64,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<Candle>> {
+        let rows = self
+            .client
+            .query(
+                "WITH points AS ( \
+                     SELECT timestamp, price, size AS volume FROM trades \
+                     WHERE pair = $1 AND ($2::TEXT IS NULL OR owner = $2) \
+                     AND timestamp BETWEEN $4 AND $5 \
+                     UNION ALL \
+                     SELECT timestamp, price, 0 FROM pool_samples \
+                     WHERE base_mint = $1 AND timestamp BETWEEN $4 AND $5 \
+                 ) \
+                 SELECT timestamp / $3 * $3 AS start, \
+                     (array_agg(price ORDER BY timestamp))[1], \
+                     max(price), min(price), \
+                     (array_agg(price ORDER BY timestamp DESC))[1], \
+                     sum(volume)::BIGINT, count(*) FILTER (WHERE volume > 0) \
+                 FROM points GROUP BY start ORDER BY start",
+                &[&pair, &owner, &interval, &from, &to],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Candle {
+                start: row.get(0),
+                open: row.get(1),
+                high: row.get(2),
+                low: row.get(3),
+                close: row.get(4),
+                volume: row.get(5),
+                fills: row.get(6),
+            })
+            .collect())
+    }
+
+    pub async fn strategies(&self, owner: Option<&str>) -> Result<Vec<StrategySnapshot>> {
+        let rows = match owner {
+            Some(owner) => {
+                self.client
+                    .query(
+                        "SELECT * FROM strategies WHERE owner = $1 ORDER BY address",
+                        &[&owner],
+                    )
+                    .await?
+            }
+            None => {
+                self.client
+                    .query("SELECT * FROM strategies ORDER BY address", &[])
+                    .await?
+            }
+        };
+        Ok(rows.iter().map(strategy_snapshot).collect())
+    }
+
+    pub async fn strategy(&self, address: &str) -> Result<Option<StrategySnapshot>> {
+        let row = self
+            .client
+            .query_opt("SELECT * FROM strategies WHERE address = $1", &[&address])
+            .await?;
+        Ok(row.as_ref().map(strategy_snapshot))
+    }
+}
+
+fn strategy_snapshot(row: &Row) -> StrategySnapshot {
+    StrategySnapshot {
+        address: row.get("address"),
+        owner: row.get("owner"),
+        strategy_id: row.get("strategy_id"),
+        is_active: row.get("is_active"),
+        total_trades: row.get("total_trades"),
+        total_profit_loss: row.get("total_profit_loss"),
+        max_drawdown: row.get("max_drawdown"),
+        last_executed_at: row.get("last_executed_at"),
+        updated_at: row.get("updated_at"),
+        slot: row.get("slot"),
+    }
+}
@@ -0,0 +1,136 @@
+// What the API serves. Addresses are base58, amounts in the program's raw units.
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+pub struct Trade {
+    pub id: i64,
+    pub signature: String,
+    pub owner: String,
+    pub pair: String,
+    pub is_buy: bool,
+    pub size: i64,
+    pub price: i64,
+    pub fee: i64,
+    pub timestamp: i64,
+}
+
+#[derive(Serialize)]
+pub struct QuoteFill {
+    pub id: i64,
+    pub signature: String,
+    pub market_maker: String,
+    pub seq: i64,
+    pub client_order_id: i64,
+    pub is_bid: bool,
+    pub base_amount: i64,
+    pub quote_amount: i64,
+    pub slippage_bps: Option<i64>,
+    pub slot: i64,
+}
+
+// The latest change to each of an owner's tracked positions
+#[derive(Serialize)]
+pub struct Position {
+    pub position_id: String,
+    pub dex: String,
+    pub remaining: i64,
+    pub last_reduced: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize)]
+pub struct NavPoint {
+    pub nav: i64,
+    pub return_bps: i64,
+    pub max_drawdown_bps: i32,
+    pub alpha_vs_hodl_bps: i64,
+    pub alpha_vs_sol_bps: Option<i64>,
+    pub timestamp: i64,
+}
+
+// Fills and pool samples in one bucket. Only fills add volume, so a bucket with no fills still
+// charts the pool price.
+#[derive(Serialize)]
+pub struct Candle {
+    pub start: i64,
+    pub open: i64,
+    pub high: i64,
+    pub low: i64,
+    pub close: i64,
+    pub volume: i64,
+    pub fills: i64,
+}
+
+#[derive(Serialize)]
+pub struct StrategySnapshot {
+    pub address: String,
+    pub owner: String,
+    pub strategy_id: String,
+    pub is_active: bool,
+    pub total_trades: i64,
+    pub total_profit_loss: i64,
+    pub max_drawdown: i64,
+    pub last_executed_at: i64,
+    pub updated_at: i64,
+    pub slot: i64,
+}
+
+// Newest first; `before` is the `id` of the last row of the previous page
+#[derive(Deserialize)]
+pub struct Page {
+    pub before: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+impl Page {
+    pub const MAX_LIMIT: i64 = 500;
+
+    pub fn before(&self) -> i64 {
+        self.before.unwrap_or(i64::MAX)
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(100).clamp(1, Self::MAX_LIMIT)
+    }
+}
+
+// Unix timestamps, both inclusive
+#[derive(Deserialize)]
+pub struct Range {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct StrategyFilter {
+    pub owner: Option<String>,
+}
+
+// `owner` restricts the fills to one owner's, to chart its entries and exits against the pool
+#[derive(Deserialize)]
+pub struct CandleQuery {
+    pub interval: Option<i64>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub owner: Option<String>,
+}
+
+impl CandleQuery {
+    pub const INTERVALS: [i64; 6] = [60, 300, 900, 3600, 14400, 86400];
+    pub const MAX_CANDLES: i64 = 1000;
+
+    pub fn interval(&self) -> Option<i64> {
+        let interval = self.interval.unwrap_or(3600);
+        Self::INTERVALS.contains(&interval).then_some(interval)
+    }
+
+    // Defaults to the latest MAX_CANDLES buckets; None when the range holds more than that
+    pub fn range(&self, interval: i64, now: i64) -> Option<(i64, i64)> {
+        let to = self.to.unwrap_or(now);
+        let from = self
+            .from
+            .unwrap_or_else(|| to.saturating_sub(interval * Self::MAX_CANDLES));
+        (from <= to && to.saturating_sub(from) <= interval * Self::MAX_CANDLES)
+            .then_some((from, to))
+    }
+}
@@ -0,0 +1,159 @@
+use crate::snapshot::AccountSnapshot;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, InstructionData, ToAccountMetas};
+use anyhow::{anyhow, bail, Result};
+use on_chain_trading_bot::constants::{FEED_REGISTRY_SEED, SESSION_SEED, TRADE_APPROVAL_SEED};
+use on_chain_trading_bot::instructions::StrategySummary;
+use on_chain_trading_bot::state::{MarketMaker, Strategy, TradeApproval};
+use on_chain_trading_bot::types::TimeBasis;
+use on_chain_trading_bot::{accounts, instruction, ID};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The keeper's view of the program. Its key is whatever the owner handed it: a session key can
+// only pause, the approval co-signer can approve, and the owner key can also resume. The program
+// enforces all of it; the keeper just signs.
+pub struct Chain {
+    rpc: RpcClient,
+    signer: Keypair,
+    // every strategy, so lookups don't wait on the RPC
+    pub strategies: AccountSnapshot<Strategy>,
+}
+
+impl Chain {
+    pub fn new(rpc_url: &str, ws_url: &str, signer: Keypair) -> Result<Self> {
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            signer,
+            strategies: AccountSnapshot::start(rpc_url, ws_url)?,
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    fn account<T: AccountDeserialize>(&self, key: &Pubkey) -> Result<T> {
+        let data = self.rpc.get_account_data(key)?;
+        Ok(T::try_deserialize(&mut data.as_slice())?)
+    }
+
+    fn transaction(&self, instruction: Instruction) -> Result<Transaction> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        Ok(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.signer.pubkey()),
+            &[&self.signer],
+            blockhash,
+        ))
+    }
+
+    fn send(&self, instruction: Instruction) -> Result<Signature> {
+        let transaction = self.transaction(instruction)?;
+        Ok(self.rpc.send_and_confirm_transaction(&transaction)?)
+    }
+
+    // Falls back to the RPC for a strategy created since the last update
+    pub fn strategy(&self, strategy: &Pubkey) -> Result<Strategy> {
+        match self.strategies.get(strategy) {
+            Some(cached) => Ok(cached),
+            None => self.account(strategy),
+        }
+    }
+
+    // The strategy's clock: the slot for strategies scheduled in slots, otherwise unix time
+    pub fn strategy_now(&self, strategy: &Strategy) -> Result<i64> {
+        Ok(match strategy.config.time_basis {
+            TimeBasis::UnixTime => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs() as i64)
+                .unwrap_or_default(),
+            TimeBasis::Slot { .. } => self.rpc.get_slot()? as i64,
+        })
+    }
+
+    // Signs as the owner when the keeper holds the owner key, otherwise through its session
+    pub fn set_active(&self, strategy_key: &Pubkey, active: bool) -> Result<Signature> {
+        let strategy = self.strategy(strategy_key)?;
+        let authority = self.pubkey();
+        let session = (authority != strategy.owner).then(|| {
+            Pubkey::find_program_address(
+                &[SESSION_SEED, strategy_key.as_ref(), authority.as_ref()],
+                &ID,
+            )
+            .0
+        });
+        if active && session.is_some() {
+            bail!("only the owner can resume a strategy; the keeper holds a session key");
+        }
+        self.send(Instruction {
+            program_id: ID,
+            accounts: accounts::SetStrategyActive {
+                authority,
+                strategy: *strategy_key,
+                session,
+            }
+            .to_account_metas(None),
+            data: instruction::SetStrategyActive { active }.data(),
+        })
+    }
+
+    pub fn pending_approval(&self, strategy: &Pubkey) -> Result<Option<(Pubkey, TradeApproval)>> {
+        let address =
+            Pubkey::find_program_address(&[TRADE_APPROVAL_SEED, strategy.as_ref()], &ID).0;
+        let account = self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())?
+            .value;
+        match account {
+            Some(account) => Ok(Some((
+                address,
+                TradeApproval::try_deserialize(&mut account.data.as_slice())?,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn approve_trade(&self, strategy: &Pubkey, trade_approval: &Pubkey) -> Result<Signature> {
+        self.send(Instruction {
+            program_id: ID,
+            accounts: accounts::ApproveTrade {
+                co_signer: self.pubkey(),
+                strategy: *strategy,
+                trade_approval: *trade_approval,
+            }
+            .to_account_metas(None),
+            data: instruction::ApproveTrade {}.data(),
+        })
+    }
+
+    // Simulates view_strategy_summary and decodes its return data; nothing is sent
+    pub fn summary(&self, market_maker_key: &Pubkey) -> Result<StrategySummary> {
+        let market_maker: MarketMaker = self.account(market_maker_key)?;
+        let transaction = self.transaction(Instruction {
+            program_id: ID,
+            accounts: accounts::ViewStrategySummary {
+                market_maker: *market_maker_key,
+                price_feed: market_maker.price_feed,
+                base_wallet: market_maker.base_wallet,
+                quote_wallet: market_maker.quote_wallet,
+                feed_registry: Pubkey::find_program_address(&[FEED_REGISTRY_SEED], &ID).0,
+            }
+            .to_account_metas(None),
+            data: instruction::ViewStrategySummary {}.data(),
+        })?;
+        let result = self.rpc.simulate_transaction(&transaction)?.value;
+        if let Some(err) = result.err {
+            bail!("view_strategy_summary failed: {}", err);
+        }
+        let return_data = result
+            .return_data
+            .ok_or_else(|| anyhow!("view_strategy_summary returned nothing"))?;
+        let data = base64::decode(return_data.data.0)?;
+        Ok(StrategySummary::deserialize(&mut data.as_slice())?)
+    }
+}
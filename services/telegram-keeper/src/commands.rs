@@ -0,0 +1,145 @@
+use crate::chain::Chain;
+use anyhow::{bail, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const HELP: &str = "/overview - strategy counts across the program\n\
+/status <strategy> - state, P&L and volume use\n\
+/nav <market maker> - NAV, inventory and next action\n\
+/pause <strategy> - stop executions\n\
+/resume <strategy> - restart executions (owner key only)\n\
+/approvals <strategy> - show the pending large-trade request\n\
+/approve <strategy> - co-sign the pending large-trade request";
+
+pub enum Command {
+    Help,
+    Overview,
+    Status(Pubkey),
+    Nav(Pubkey),
+    Pause(Pubkey),
+    Resume(Pubkey),
+    Approvals(Pubkey),
+    Approve(Pubkey),
+}
+
+impl Command {
+    // Accepts `/cmd@botname` as group chats send it
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut words = text.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let name = name.split('@').next().unwrap_or_default();
+        match name {
+            "/help" | "/start" => return Ok(Command::Help),
+            "/overview" => return Ok(Command::Overview),
+            _ => {}
+        }
+        let target = match words.next() {
+            Some(word) => Pubkey::from_str(word)?,
+            None => bail!("{} needs an account address\n\n{}", name, HELP),
+        };
+        Ok(match name {
+            "/status" => Command::Status(target),
+            "/nav" => Command::Nav(target),
+            "/pause" => Command::Pause(target),
+            "/resume" => Command::Resume(target),
+            "/approvals" => Command::Approvals(target),
+            "/approve" => Command::Approve(target),
+            _ => bail!("Unknown command {}\n\n{}", name, HELP),
+        })
+    }
+
+    pub fn run(self, chain: &Chain) -> Result<String> {
+        match self {
+            Command::Help => Ok(HELP.to_string()),
+            Command::Overview => {
+                let strategies = &chain.strategies;
+                let now = now();
+                Ok(format!(
+                    "strategies: {}\nactive: {}\nentries halted: {}",
+                    strategies.count(|_| true),
+                    strategies.count(|strategy| strategy.is_active),
+                    strategies.count(|strategy| strategy.volatility_halt.entries_halted(now)),
+                ))
+            }
+            Command::Status(key) => {
+                let strategy = chain.strategy(&key)?;
+                let metrics = &strategy.performance_metrics;
+                Ok(format!(
+                    "{} ({})\nactive: {}\nP&L: {}\nmax drawdown: {}\ntrades: {}\n\
+                     24h volume use: {} bps\nentries halted: {}",
+                    strategy.strategy_id,
+                    key,
+                    strategy.is_active,
+                    metrics.total_profit_loss,
+                    metrics.max_drawdown,
+                    strategy.total_trades,
+                    strategy.volume_utilization_bps(chain.strategy_now(&strategy)?)?,
+                    strategy.volatility_halt.entries_halted(now()),
+                ))
+            }
+            Command::Nav(key) => {
+                let summary = chain.summary(&key)?;
+                Ok(format!(
+                    "{}\nactive: {}\nmid: {}\nNAV: {}\nunrealized P&L: {}\nrisk use: {} bps\n\
+                     next requote outside {}..{}",
+                    key,
+                    summary.is_active,
+                    summary.mid_price,
+                    summary.nav,
+                    summary.unrealized_pnl,
+                    summary.risk_utilization_bps,
+                    summary.requote_below,
+                    summary.requote_above,
+                ))
+            }
+            Command::Pause(key) => {
+                let signature = chain.set_active(&key, false)?;
+                Ok(format!("Paused {}\n{}", key, signature))
+            }
+            Command::Resume(key) => {
+                let signature = chain.set_active(&key, true)?;
+                Ok(format!("Resumed {}\n{}", key, signature))
+            }
+            // expiries count in the strategy's clock, slots for strategies scheduled in slots
+            Command::Approvals(key) => Ok(match chain.pending_approval(&key)? {
+                Some((_, approval)) => format!(
+                    "Trades up to {} requested, {}, expires in {}",
+                    approval.max_amount,
+                    if approval.approved {
+                        "already approved"
+                    } else {
+                        "waiting for /approve"
+                    },
+                    approval.expires_at - chain.strategy_now(&chain.strategy(&key)?)?,
+                ),
+                None => "No pending request".to_string(),
+            }),
+            // Re-reads the request so what gets signed is what was just shown
+            Command::Approve(key) => {
+                let (address, approval) = match chain.pending_approval(&key)? {
+                    Some(pending) => pending,
+                    None => bail!("No pending request"),
+                };
+                if approval.approved {
+                    bail!("Already approved");
+                }
+                if approval.expires_at < chain.strategy_now(&chain.strategy(&key)?)? {
+                    bail!("Request expired");
+                }
+                let signature = chain.approve_trade(&key, &address)?;
+                Ok(format!(
+                    "Approved trades up to {}\n{}",
+                    approval.max_amount, signature
+                ))
+            }
+        }
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
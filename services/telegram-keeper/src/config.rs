@@ -0,0 +1,35 @@
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use std::env;
+
+// Read from the environment, with the same names `scripts/callback-relay.ts` uses
+pub struct Config {
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub keypair_path: String,
+    pub bot_token: String,
+    // the only chat the bot answers; messages from anywhere else are ignored
+    pub chat_id: i64,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        let home = env::var("HOME").unwrap_or_default();
+        Ok(Self {
+            rpc_url: env::var("RPC_URL").unwrap_or_else(|_| "http://127.0.0.1:8899".to_string()),
+            ws_url: env::var("WS_URL").unwrap_or_else(|_| "ws://127.0.0.1:8900".to_string()),
+            keypair_path: env::var("KEEPER_KEYPAIR")
+                .unwrap_or_else(|_| format!("{}/.config/solana/id.json", home)),
+            bot_token: env::var("TELEGRAM_BOT_TOKEN").context("TELEGRAM_BOT_TOKEN is not set")?,
+            chat_id: env::var("TELEGRAM_CHAT_ID")
+                .context("TELEGRAM_CHAT_ID is not set")?
+                .parse()
+                .context("TELEGRAM_CHAT_ID is not a chat id")?,
+        })
+    }
+
+    pub fn keypair(&self) -> Result<Keypair> {
+        read_keypair_file(&self.keypair_path)
+            .map_err(|err| anyhow!("reading {}: {}", self.keypair_path, err))
+    }
+}
@@ -0,0 +1,58 @@
+// Telegram companion for strategy owners: pause, resume and inspect strategies and co-sign large
+// trades from a chat. Configured through RPC_URL, WS_URL, KEEPER_KEYPAIR, TELEGRAM_BOT_TOKEN and
+// TELEGRAM_CHAT_ID; run with `cargo run -p telegram-keeper`.
+mod chain;
+mod commands;
+mod config;
+mod snapshot;
+mod telegram;
+
+use anyhow::Result;
+use chain::Chain;
+use commands::Command;
+use config::Config;
+use std::thread;
+use std::time::Duration;
+use telegram::Bot;
+
+fn main() -> Result<()> {
+    let config = Config::from_env()?;
+    let chain = Chain::new(&config.rpc_url, &config.ws_url, config.keypair()?)?;
+    let bot = Bot::new(&config.bot_token);
+    println!(
+        "Keeper {} listening for chat {}",
+        chain.pubkey(),
+        config.chat_id
+    );
+
+    let mut offset = 0;
+    loop {
+        let updates = match bot.get_updates(offset) {
+            Ok(updates) => updates,
+            Err(err) => {
+                eprintln!("Polling failed: {}", err);
+                thread::sleep(Duration::from_secs(5));
+                continue;
+            }
+        };
+        for update in updates {
+            offset = update.update_id + 1;
+            let Some(message) = update.message else {
+                continue;
+            };
+            // the bot can sign for the owner's strategies, so strangers get no answer at all
+            if message.chat.id != config.chat_id {
+                continue;
+            }
+            let Some(text) = message.text else {
+                continue;
+            };
+            let reply = Command::parse(&text)
+                .and_then(|command| command.run(&chain))
+                .unwrap_or_else(|err| format!("Failed: {}", err));
+            if let Err(err) = bot.send_message(config.chat_id, &reply) {
+                eprintln!("Reply failed: {}", err);
+            }
+        }
+    }
+}
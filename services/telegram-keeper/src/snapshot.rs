@@ -0,0 +1,131 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anyhow::Result;
+use crossbeam_channel::Receiver;
+use on_chain_trading_bot::ID;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::pubsub_client::{PubsubClient, PubsubProgramClientSubscription};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_client::rpc_response::{Response, RpcKeyedAccount};
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// the subscription has to outlive the receiver, dropping it unsubscribes
+type Connection = (
+    PubsubProgramClientSubscription,
+    Receiver<Response<RpcKeyedAccount>>,
+);
+
+// Every program account of type T, loaded in one getProgramAccounts call filtered on T's
+// discriminator and then kept current by a programSubscribe with the same filter. The
+// subscription is opened before the load so nothing written in between is missed; updates that
+// arrive meanwhile are applied after it, oldest first, so the map converges on the latest state.
+pub struct AccountSnapshot<T> {
+    accounts: Arc<RwLock<HashMap<Pubkey, T>>>,
+}
+
+impl<T> AccountSnapshot<T>
+where
+    T: AccountDeserialize + Discriminator + Clone + Send + Sync + 'static,
+{
+    // Returns once the first load is in; a dropped subscription reconnects and reloads in the
+    // background
+    pub fn start(rpc_url: &str, ws_url: &str) -> Result<Self> {
+        let rpc =
+            RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+        let accounts = Arc::new(RwLock::new(HashMap::new()));
+        let mut connection = Some(Self::connect(&rpc, ws_url, &accounts)?);
+
+        let shared = accounts.clone();
+        let ws_url = ws_url.to_string();
+        thread::spawn(move || loop {
+            if let Some((_subscription, updates)) = connection.take() {
+                for update in updates {
+                    match Pubkey::from_str(&update.value.pubkey) {
+                        Ok(key) => Self::apply(&shared, key, update.value.account.decode()),
+                        Err(err) => eprintln!("Bad account key in update: {}", err),
+                    }
+                }
+                eprintln!("Account subscription closed, reconnecting");
+            }
+            thread::sleep(Duration::from_secs(5));
+            match Self::connect(&rpc, &ws_url, &shared) {
+                Ok(reconnected) => connection = Some(reconnected),
+                Err(err) => eprintln!("Reconnecting failed: {}", err),
+            }
+        });
+        Ok(Self { accounts })
+    }
+
+    fn config() -> RpcProgramAccountsConfig {
+        RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &T::DISCRIMINATOR,
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..RpcAccountInfoConfig::default()
+            },
+            with_context: None,
+        }
+    }
+
+    fn connect(
+        rpc: &RpcClient,
+        ws_url: &str,
+        accounts: &RwLock<HashMap<Pubkey, T>>,
+    ) -> Result<Connection> {
+        let connection = PubsubClient::program_subscribe(ws_url, &ID, Some(Self::config()))?;
+        let started = Instant::now();
+        let loaded: HashMap<Pubkey, T> = rpc
+            .get_program_accounts_with_config(&ID, Self::config())?
+            .into_iter()
+            // accounts written under an older layout don't deserialize and are left out
+            .filter_map(|(key, account)| {
+                T::try_deserialize(&mut account.data.as_slice())
+                    .ok()
+                    .map(|value| (key, value))
+            })
+            .collect();
+        println!(
+            "Loaded {} accounts in {:?}",
+            loaded.len(),
+            started.elapsed()
+        );
+        *accounts.write().unwrap() = loaded;
+        Ok(connection)
+    }
+
+    // A closed account arrives empty and is dropped, as is one that no longer deserializes
+    fn apply(accounts: &RwLock<HashMap<Pubkey, T>>, key: Pubkey, account: Option<Account>) {
+        let value =
+            account.and_then(|account| T::try_deserialize(&mut account.data.as_slice()).ok());
+        let mut accounts = accounts.write().unwrap();
+        match value {
+            Some(value) => accounts.insert(key, value),
+            None => accounts.remove(&key),
+        };
+    }
+
+    pub fn get(&self, key: &Pubkey) -> Option<T> {
+        self.accounts.read().unwrap().get(key).cloned()
+    }
+
+    pub fn count(&self, predicate: impl Fn(&T) -> bool) -> usize {
+        self.accounts
+            .read()
+            .unwrap()
+            .values()
+            .filter(|value| predicate(value))
+            .count()
+    }
+}
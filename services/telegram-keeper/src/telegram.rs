@@ -0,0 +1,80 @@
+use anyhow::{bail, Result};
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+// seconds getUpdates holds the connection open waiting for a message
+const POLL_TIMEOUT: u64 = 30;
+
+#[derive(Deserialize)]
+struct Response<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    pub message: Option<Message>,
+}
+
+#[derive(Deserialize)]
+pub struct Message {
+    pub chat: Chat,
+    pub text: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct Chat {
+    pub id: i64,
+}
+
+// The two Bot API calls the keeper needs, over long polling so it runs without a public endpoint
+pub struct Bot {
+    client: Client,
+    base_url: String,
+}
+
+impl Bot {
+    pub fn new(token: &str) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(POLL_TIMEOUT + 10))
+                .build()
+                .expect("reqwest client"),
+            base_url: format!("https://api.telegram.org/bot{}", token),
+        }
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, body: serde_json::Value) -> Result<T> {
+        let response: Response<T> = self
+            .client
+            .post(format!("{}/{}", self.base_url, method))
+            .json(&body)
+            .send()?
+            .json()?;
+        match response.result {
+            Some(result) if response.ok => Ok(result),
+            _ => bail!(
+                "{} failed: {}",
+                method,
+                response.description.unwrap_or_default()
+            ),
+        }
+    }
+
+    pub fn get_updates(&self, offset: i64) -> Result<Vec<Update>> {
+        self.call(
+            "getUpdates",
+            json!({ "offset": offset, "timeout": POLL_TIMEOUT, "allowed_updates": ["message"] }),
+        )
+    }
+
+    pub fn send_message(&self, chat_id: i64, text: &str) -> Result<()> {
+        self.call::<serde_json::Value>("sendMessage", json!({ "chat_id": chat_id, "text": text }))?;
+        Ok(())
+    }
+}